@@ -0,0 +1,81 @@
+use std::{collections::BTreeSet, str::FromStr};
+
+/// Markers that are displayed in the breakpoint column for breakpoints of groups, in the order of the groups.
+const GROUP_MARKERS: [char; 9] = ['◆', '▲', '■', '●', '▼', '★', '♦', '♣', '♠'];
+
+/// Named group of breakpoints that can be enabled and disabled together.
+///
+/// Is parsed from `<name>=<lines>`, e.g. `loop=3,5,7`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct BreakpointGroup {
+    pub name: String,
+    /// Lines in which the breakpoints of this group are set, starting at 1.
+    pub lines: BTreeSet<usize>,
+    pub enabled: bool,
+}
+
+impl BreakpointGroup {
+    /// Returns true if this group contains a breakpoint at the instruction with index `idx`.
+    pub fn contains(&self, idx: usize) -> bool {
+        self.lines.contains(&(idx + 1))
+    }
+}
+
+impl FromStr for BreakpointGroup {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((name, lines)) = s.split_once('=') else {
+            return Err(format!(
+                "'{s}' is not of the form <name>=<lines>, e.g. loop=3,5,7"
+            ));
+        };
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("the name of the group is missing".to_string());
+        }
+        let lines = lines
+            .split(',')
+            .map(|line| match line.trim().parse::<usize>() {
+                Ok(line) if line > 0 => Ok(line),
+                _ => Err(format!("'{}' is not a line number", line.trim())),
+            })
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        Ok(Self {
+            name: name.to_string(),
+            lines,
+            enabled: true,
+        })
+    }
+}
+
+/// Returns the marker that is displayed for breakpoints of the group with index `group_idx`.
+///
+/// Markers are repeated when more groups than markers exist.
+pub fn group_marker(group_idx: usize) -> char {
+    GROUP_MARKERS[group_idx % GROUP_MARKERS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, str::FromStr};
+
+    use super::BreakpointGroup;
+
+    #[test]
+    fn test_breakpoint_group_from_str() {
+        assert_eq!(
+            BreakpointGroup::from_str("loop= 3,7, 5"),
+            Ok(BreakpointGroup {
+                name: "loop".to_string(),
+                lines: BTreeSet::from([3, 5, 7]),
+                enabled: true,
+            })
+        );
+        assert!(BreakpointGroup::from_str("loop").is_err());
+        assert!(BreakpointGroup::from_str("=3").is_err());
+        assert!(BreakpointGroup::from_str("loop=3,a").is_err());
+        assert!(BreakpointGroup::from_str("loop=0").is_err());
+        assert!(BreakpointGroup::from_str("loop=3").unwrap().contains(2));
+    }
+}
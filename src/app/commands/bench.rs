@@ -0,0 +1,171 @@
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use miette::{miette, Result};
+
+use crate::{cli::BenchArgs, runtime::builder::RuntimeBuilder};
+
+/// A workload of which the performance is measured.
+struct Benchmark {
+    name: &'static str,
+    /// Prepares a single run of the benchmark, only the returned function is measured.
+    setup: fn() -> Box<dyn FnOnce()>,
+}
+
+/// Returns all benchmarks.
+fn benchmarks() -> Vec<Benchmark> {
+    vec![
+        Benchmark {
+            name: "parse_large_program",
+            setup: || {
+                let lines = large_program(2_000);
+                Box::new(move || {
+                    let rt = RuntimeBuilder::new(&lines, "bench")
+                        .expect("benchmark program should be valid")
+                        .build()
+                        .expect("benchmark program should build");
+                    black_box(rt);
+                })
+            },
+        },
+        Benchmark {
+            name: "tight_loop",
+            setup: || {
+                let mut rt =
+                    runtime(&["a := 0", "loop: a := a + 1", "if a < 100000 then goto loop"]);
+                Box::new(move || {
+                    rt.run().expect("benchmark program should run");
+                    black_box(rt);
+                })
+            },
+        },
+        Benchmark {
+            name: "index_memory",
+            setup: || {
+                let mut rt = runtime(&[
+                    "a1 := 0",
+                    "fill: p(a1) := a1 * 2",
+                    "a1 := a1 + 1",
+                    "if a1 < 10000 then goto fill",
+                    "p(h1) := 0",
+                    "a0 := 0",
+                    "sum: a0 := a0 + p(p(h1))",
+                    "p(h1) := p(h1) + 1",
+                    "if p(h1) < 5000 then goto sum",
+                ]);
+                Box::new(move || {
+                    rt.run().expect("benchmark program should run");
+                    black_box(rt);
+                })
+            },
+        },
+    ]
+}
+
+/// Builds the runtime of a benchmark program.
+fn runtime(lines: &[&str]) -> crate::runtime::Runtime {
+    let lines = lines.iter().map(ToString::to_string).collect::<Vec<_>>();
+    RuntimeBuilder::new(&lines, "bench")
+        .expect("benchmark program should be valid")
+        .build()
+        .expect("benchmark program should build")
+}
+
+/// Returns a program with `blocks` blocks, each block contains a label, calculations, a subroutine call and
+/// conditional jumps, so that all parts of the parser are used.
+fn large_program(blocks: usize) -> Vec<String> {
+    let mut lines = vec!["goto main".to_string(), "f: a1 := a1 + 1".to_string()];
+    lines.push("return".to_string());
+    lines.push("main: a1 := 0".to_string());
+    for block in 0..blocks {
+        lines.push(format!("l{block}: a0 := {block} // block {block}"));
+        lines.push(format!("p(h{}) := a0 * 3", block % 16));
+        lines.push(format!("p(a0) := p(h{}) - a1", block % 16));
+        lines.push("call f".to_string());
+        lines.push(format!("if a1 > {blocks} then goto l{}", block / 2));
+        lines.push(format!("if a0 == y then a2 := max(a0, {block})"));
+    }
+    lines
+}
+
+/// Runs the benchmarks whose name contains the filter and prints the results.
+///
+/// If alpha_tui is built with the `bench` feature, the benchmarks are measured with criterion.
+pub fn bench(args: &BenchArgs) -> Result<()> {
+    let benchmarks = benchmarks()
+        .into_iter()
+        .filter(|b| args.filter.as_ref().is_none_or(|f| b.name.contains(f)))
+        .collect::<Vec<_>>();
+    if benchmarks.is_empty() {
+        return Err(miette!("No benchmark matches the filter"));
+    }
+    if args.samples == 0 {
+        return Err(miette!("At least one sample is required"));
+    }
+    run(&benchmarks, args.samples);
+    Ok(())
+}
+
+#[cfg(not(feature = "bench"))]
+fn run(benchmarks: &[Benchmark], samples: usize) {
+    println!(
+        "{:<20} {:>12} {:>12} {:>12}",
+        "benchmark", "min", "median", "mean"
+    );
+    for benchmark in benchmarks {
+        let durations = measure(benchmark, samples);
+        println!(
+            "{:<20} {:>12?} {:>12?} {:>12?}",
+            benchmark.name,
+            durations[0],
+            durations[durations.len() / 2],
+            durations.iter().sum::<Duration>() / durations.len() as u32
+        );
+    }
+}
+
+#[cfg(feature = "bench")]
+fn run(benchmarks: &[Benchmark], samples: usize) {
+    // criterion requires at least 10 samples
+    let mut criterion = criterion::Criterion::default().sample_size(samples.max(10));
+    for benchmark in benchmarks {
+        criterion.bench_function(benchmark.name, |b| {
+            b.iter_batched(
+                benchmark.setup,
+                |run| run(),
+                criterion::BatchSize::SmallInput,
+            );
+        });
+    }
+    criterion.final_summary();
+}
+
+/// Runs the benchmark once to warm up and then `samples` times, returns the sorted durations of the measured runs.
+#[cfg_attr(feature = "bench", allow(dead_code))]
+fn measure(benchmark: &Benchmark, samples: usize) -> Vec<Duration> {
+    (benchmark.setup)()();
+    let mut durations = (0..samples)
+        .map(|_| {
+            let run = (benchmark.setup)();
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .collect::<Vec<_>>();
+    durations.sort();
+    durations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{benchmarks, measure};
+
+    #[test]
+    fn test_benchmarks_run() {
+        for benchmark in benchmarks() {
+            assert_eq!(measure(&benchmark, 1).len(), 1, "{}", benchmark.name);
+        }
+    }
+}
@@ -0,0 +1,639 @@
+use std::{fs, io::Read, path::Path, process::exit};
+
+use miette::{miette, IntoDiagnostic, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    app::commands::run_metadata::RunMetadata,
+    cli::{BundleArgs, CheckLoadArgs, GlobalArgs},
+    runtime::{
+        builder::RuntimeBuilder, memory_config::MemoryConfig, EndBehavior, Runtime, RuntimeSettings,
+    },
+    utils,
+};
+
+/// Name of the file in the bundle that contains the memory config the program was run with.
+pub const MEMORY_CONFIG_FILE: &str = "memory_config.json";
+/// Name of the file in the bundle that contains the memory after the program was run.
+pub const FINAL_MEMORY_FILE: &str = "final_memory.json";
+/// Name of the file in the bundle that contains the run summary.
+pub const SUMMARY_FILE: &str = "summary.json";
+/// Name of the file in the bundle that contains the settings of the runtime the program was run with.
+pub const SETTINGS_FILE: &str = "settings.json";
+/// Name of the file in the bundle that contains the information that is needed to reproduce the run.
+pub const RUN_METADATA_FILE: &str = "run_metadata.json";
+/// Name of the file in the bundle that contains the sha256 hashes of all other files.
+pub const HASHES_FILE: &str = "SHA256SUMS";
+
+/// Summary of the run of the bundled program.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    /// File name of the program.
+    pub program: String,
+    /// Version of alpha_tui that was used to run the program.
+    pub alpha_tui_version: String,
+    /// Seed of the random number generator.
+    pub seed: u64,
+    /// If the instruction limit was disabled.
+    pub instruction_limit_disabled: bool,
+    /// Number of instructions that were executed.
+    pub instruction_runs: usize,
+    /// Values on the stack after the program was run.
+    pub final_stack: Vec<i32>,
+    /// Set if the program stopped because of a runtime error.
+    pub runtime_error: Option<String>,
+}
+
+/// Settings of the runtime that change the result of a run, the program is run with them again when the bundle is
+/// verified.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSettings {
+    /// Seed of the random number generator.
+    pub seed: u64,
+    /// If the instruction limit was disabled.
+    pub instruction_limit_disabled: bool,
+    /// Maximum number of instructions that may be run.
+    pub instruction_limit: usize,
+    /// Maximum number of values on the stack, `None` if the size of the stack is not limited.
+    pub stack_limit: Option<usize>,
+    /// Maximum depth of the call stack.
+    pub call_stack_limit: usize,
+    pub tail_call_optimization: bool,
+    pub skip_empty_lines: bool,
+    pub end_behavior: EndBehavior,
+    pub case_insensitive_labels: bool,
+}
+
+impl RunSettings {
+    /// Collects the settings of the runtime `rt`.
+    fn from_runtime(rt: &Runtime) -> Self {
+        let settings = rt.settings();
+        Self {
+            seed: settings.rng.seed(),
+            instruction_limit_disabled: settings.disable_instruction_limit,
+            instruction_limit: settings.instruction_limit,
+            stack_limit: settings.stack_limit,
+            call_stack_limit: settings.call_stack_limit,
+            tail_call_optimization: settings.tail_call_optimization,
+            skip_empty_lines: settings.skip_empty_lines,
+            end_behavior: settings.end_behavior,
+            case_insensitive_labels: rt.control_flow().case_insensitive_labels,
+        }
+    }
+
+    /// Returns the settings of a bundle that was created before the settings were stored in the bundle.
+    ///
+    /// Only the seed and if the instruction limit was disabled are known from the summary, all other settings had
+    /// their default values.
+    fn from_summary(summary: &RunSummary) -> Self {
+        let defaults = RuntimeSettings::default();
+        Self {
+            seed: summary.seed,
+            instruction_limit_disabled: summary.instruction_limit_disabled,
+            instruction_limit: defaults.instruction_limit,
+            stack_limit: defaults.stack_limit,
+            call_stack_limit: defaults.call_stack_limit,
+            tail_call_optimization: defaults.tail_call_optimization,
+            skip_empty_lines: defaults.skip_empty_lines,
+            end_behavior: defaults.end_behavior,
+            case_insensitive_labels: false,
+        }
+    }
+
+    /// Applies the settings to `rb` with the same arguments that set them when the bundle was created.
+    fn apply(&self, rb: &mut RuntimeBuilder) -> Result<()> {
+        rb.apply_global_cli_args(&GlobalArgs {
+            seed: Some(self.seed),
+            disable_instruction_limit: self.instruction_limit_disabled,
+            instruction_limit: Some(self.instruction_limit),
+            stack_limit: self.stack_limit,
+            call_stack_limit: Some(self.call_stack_limit),
+            tail_call_optimization: self.tail_call_optimization,
+            ..GlobalArgs::default()
+        })?;
+        rb.apply_check_load_args(&CheckLoadArgs {
+            skip_empty_lines: self.skip_empty_lines,
+            end_behavior: Some(self.end_behavior),
+            case_insensitive_labels: self.case_insensitive_labels,
+            ..CheckLoadArgs::default()
+        })?;
+        Ok(())
+    }
+}
+
+/// Submission bundle that contains a program, the memory config and the results of a run of the program.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bundle {
+    /// Name and content of the files in the bundle, in the order in which they are stored.
+    pub files: Vec<(String, Vec<u8>)>,
+}
+
+impl Bundle {
+    /// Runs the program and creates the bundle from the results.
+    ///
+    /// `program` is the content of the program file `input`. Returns an error if the program does not compile.
+    pub fn create(
+        global_args: &GlobalArgs,
+        instructions: &[String],
+        input: &str,
+        program: Vec<u8>,
+    ) -> Result<Self> {
+        let mut rb = RuntimeBuilder::new(instructions, input)?;
+        rb.apply_global_cli_args(global_args)?;
+        Self::from_runtime_builder(rb, input, program)
+    }
+
+    /// Builds the runtime, runs the program and creates the bundle from the results.
+    fn from_runtime_builder(rb: RuntimeBuilder, input: &str, program: Vec<u8>) -> Result<Self> {
+        let metadata = RunMetadata::new(&rb);
+        let mut rt = rb.build()?;
+        let memory_config = MemoryConfig::from_runtime_memory(rt.runtime_memory(), rt.settings());
+        let runtime_error = rt.run().err().map(|e| format!("{e}: {}", e.reason));
+        let final_memory = MemoryConfig::from_runtime_memory(rt.runtime_memory(), rt.settings());
+        let settings = RunSettings::from_runtime(&rt);
+        let program_name = program_name(input);
+        let summary = RunSummary {
+            program: program_name.clone(),
+            alpha_tui_version: env!("CARGO_PKG_VERSION").to_string(),
+            seed: rt.settings().rng.seed(),
+            instruction_limit_disabled: rt.settings().disable_instruction_limit,
+            instruction_runs: rt.instruction_runs(),
+            final_stack: rt.runtime_memory().stack.clone(),
+            runtime_error,
+        };
+        let mut files = vec![
+            (program_name, program),
+            (MEMORY_CONFIG_FILE.to_string(), to_json(&memory_config)?),
+            (FINAL_MEMORY_FILE.to_string(), to_json(&final_memory)?),
+            (SUMMARY_FILE.to_string(), to_json(&summary)?),
+            (SETTINGS_FILE.to_string(), to_json(&settings)?),
+            (RUN_METADATA_FILE.to_string(), to_json(&metadata)?),
+        ];
+        files.push((HASHES_FILE.to_string(), hashes(&files).into_bytes()));
+        Ok(Self { files })
+    }
+
+    /// Reads the bundle from the tar archive at `path`.
+    pub fn read(path: &str) -> Result<Self> {
+        let file = fs::File::open(path)
+            .into_diagnostic()
+            .map_err(|e| miette!("Unable to read file [{}]: {}", path, e))?;
+        let mut archive = tar::Archive::new(file);
+        let mut files = Vec::new();
+        for entry in archive
+            .entries()
+            .into_diagnostic()
+            .map_err(|e| miette!("Unable to read bundle [{}]: {}", path, e))?
+        {
+            let mut entry = entry.into_diagnostic()?;
+            let name = entry
+                .path()
+                .into_diagnostic()?
+                .to_string_lossy()
+                .to_string();
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content).into_diagnostic()?;
+            files.push((name, content));
+        }
+        Ok(Self { files })
+    }
+
+    /// Returns the content of the file with the name `name`.
+    pub fn file(&self, name: &str) -> Option<&[u8]> {
+        self.files
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, content)| content.as_slice())
+    }
+
+    /// Returns the content hash of the bundle, this is the sha256 hash of the hashes file.
+    pub fn content_hash(&self) -> String {
+        self.file(HASHES_FILE).map(sha256_hex).unwrap_or_default()
+    }
+
+    /// Returns the run summary that is stored in the bundle.
+    pub fn summary(&self) -> Option<RunSummary> {
+        serde_json::from_slice(self.file(SUMMARY_FILE)?).ok()
+    }
+
+    /// Verifies that the hashes of all files match and that running the program again with the memory config and the
+    /// settings of the bundle results in the final memory and summary that are stored in the bundle.
+    ///
+    /// Returns the problems that were found, the bundle is valid if no problems are returned.
+    pub fn verify(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let Some(hashes_file) = self.file(HASHES_FILE) else {
+            return vec![format!("missing file '{HASHES_FILE}'")];
+        };
+        let mut listed = Vec::new();
+        for line in String::from_utf8_lossy(hashes_file).lines() {
+            let Some((hash, name)) = line.split_once("  ") else {
+                problems.push(format!("invalid line in '{HASHES_FILE}': {line}"));
+                continue;
+            };
+            match self.file(name) {
+                None => problems.push(format!("missing file '{name}'")),
+                Some(content) if sha256_hex(content) != hash => {
+                    problems.push(format!("hash of '{name}' does not match"));
+                }
+                Some(_) => (),
+            }
+            listed.push(name.to_string());
+        }
+        for (name, _) in &self.files {
+            if name != HASHES_FILE && !listed.contains(name) {
+                problems.push(format!("'{name}' is not listed in '{HASHES_FILE}'"));
+            }
+        }
+
+        // run program again
+        let Some(summary) = self.summary() else {
+            problems.push(format!("'{SUMMARY_FILE}' is missing or invalid"));
+            return problems;
+        };
+        let Some(memory_config) = self
+            .file(MEMORY_CONFIG_FILE)
+            .and_then(|c| serde_json::from_slice::<MemoryConfig>(c).ok())
+        else {
+            problems.push(format!("'{MEMORY_CONFIG_FILE}' is missing or invalid"));
+            return problems;
+        };
+        let Some(program) = self.file(&summary.program) else {
+            problems.push(format!("missing file '{}'", summary.program));
+            return problems;
+        };
+        let instructions = String::from_utf8_lossy(program)
+            .lines()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        // bundles that were created with older versions don't contain the settings
+        let settings = match self.file(SETTINGS_FILE) {
+            Some(content) => match serde_json::from_slice::<RunSettings>(content) {
+                Ok(settings) => settings,
+                Err(_) => {
+                    problems.push(format!("'{SETTINGS_FILE}' is invalid"));
+                    return problems;
+                }
+            },
+            None => RunSettings::from_summary(&summary),
+        };
+        let rerun = RuntimeBuilder::new(&instructions, &summary.program)
+            .map_err(|e| miette!(e))
+            .and_then(|mut rb| {
+                rb.set_memory_config(memory_config);
+                settings.apply(&mut rb)?;
+                Self::from_runtime_builder(rb, &summary.program, program.to_vec())
+            });
+        let rerun = match rerun {
+            Ok(rerun) => rerun,
+            Err(e) => {
+                problems.push(format!("program could not be run again: {e}"));
+                return problems;
+            }
+        };
+        // the settings are only compared, if they are stored in the bundle
+        let settings_file = self.file(SETTINGS_FILE).map(|_| SETTINGS_FILE);
+        for name in [MEMORY_CONFIG_FILE, FINAL_MEMORY_FILE]
+            .into_iter()
+            .chain(settings_file)
+        {
+            if rerun.file(name) != self.file(name) {
+                problems.push(format!("'{name}' does not match the result of the re-run"));
+            }
+        }
+        let rerun_summary = RunSummary {
+            // bundles created with another version are valid as long as the results are the same
+            alpha_tui_version: summary.alpha_tui_version.clone(),
+            ..rerun.summary().expect("summary of re-run should be valid")
+        };
+        if rerun_summary != summary {
+            problems.push(format!(
+                "'{SUMMARY_FILE}' does not match the result of the re-run"
+            ));
+        }
+        problems
+    }
+
+    /// Writes the bundle as tar archive to `path`.
+    ///
+    /// The modification time and permissions of all files are fixed, so that the same bundle always results in the same archive.
+    pub fn write(&self, path: &str) -> Result<()> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, content) in &self.files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, content.as_slice())
+                .into_diagnostic()?;
+        }
+        let archive = builder.into_inner().into_diagnostic()?;
+        fs::write(path, archive)
+            .into_diagnostic()
+            .map_err(|e| miette!("Unable to write file [{}]: {}", path, e))
+    }
+}
+
+/// Serializes `value` as pretty json with sorted keys.
+fn to_json(value: &impl Serialize) -> Result<Vec<u8>> {
+    // converting to `serde_json::Value` first sorts the keys of maps
+    let value = serde_json::to_value(value).into_diagnostic()?;
+    let mut json = serde_json::to_string_pretty(&value).into_diagnostic()?;
+    json.push('\n');
+    Ok(json.into_bytes())
+}
+
+/// Returns the content of the hashes file for `files`, one line per file in the format of `sha256sum`.
+fn hashes(files: &[(String, Vec<u8>)]) -> String {
+    files
+        .iter()
+        .map(|(name, content)| format!("{}  {name}\n", sha256_hex(content)))
+        .collect()
+}
+
+/// Name under which a program that was read from stdin is stored in the bundle.
+const STDIN_PROGRAM_NAME: &str = "program.alpha";
+
+/// Returns the name under which the program that was read from `input` is stored in the bundle.
+fn program_name(input: &str) -> String {
+    if input == utils::STDIN_FILENAME {
+        return STDIN_PROGRAM_NAME.to_string();
+    }
+    Path::new(input)
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or(input)
+        .to_string()
+}
+
+/// Returns the sha256 hash of `data` as lowercase hex string.
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+pub fn bundle(
+    global_args: &GlobalArgs,
+    bundle_args: &BundleArgs,
+    instructions: Vec<String>,
+    input: &str,
+) -> Result<()> {
+    // the program was read already, so that programs from URLs can be bundled as well
+    let program = (instructions.join("\n") + "\n").into_bytes();
+    let bundle = Bundle::create(global_args, &instructions, input, program)?;
+    let output = bundle_args.output.clone().unwrap_or_else(|| {
+        // the bundle of a program from stdin or a URL is written into the current directory
+        let path = if input == utils::STDIN_FILENAME || utils::is_url(input) {
+            program_name(input)
+        } else {
+            input.to_string()
+        };
+        Path::new(&path)
+            .with_extension("bundle.tar")
+            .to_string_lossy()
+            .to_string()
+    });
+    bundle.write(&output)?;
+    println!("Bundle written to {output}");
+    println!("Content hash: {}", bundle.content_hash());
+    Ok(())
+}
+
+/// Verifies the bundle stored in `archive`.
+///
+/// If `content_hash` is set, the content hash of the bundle has to match it. Exits with code 1 if the bundle could not be verified.
+pub fn verify(archive: &str, content_hash: Option<&str>) -> Result<()> {
+    let bundle = Bundle::read(archive)?;
+    let mut problems = bundle.verify();
+    if let Some(expected) = content_hash {
+        if bundle.content_hash() != expected {
+            problems.push(format!(
+                "content hash is {}, expected {expected}",
+                bundle.content_hash()
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        for problem in &problems {
+            println!("{problem}");
+        }
+        println!("Bundle could not be verified");
+        exit(1);
+    }
+    if let Some(summary) = bundle.summary() {
+        if summary.alpha_tui_version != env!("CARGO_PKG_VERSION") {
+            println!(
+                "Note: bundle was created with alpha_tui {}",
+                summary.alpha_tui_version
+            );
+        }
+    }
+    println!("Bundle verified");
+    println!("Content hash: {}", bundle.content_hash());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cli::GlobalArgs,
+        utils::{self, test_utils},
+    };
+
+    use super::{
+        sha256_hex, Bundle, RunSettings, RunSummary, FINAL_MEMORY_FILE, HASHES_FILE, SETTINGS_FILE,
+    };
+
+    fn bundle(program: &str) -> Bundle {
+        Bundle::create(
+            &GlobalArgs::default(),
+            &test_utils::string_literal_to_vec(program),
+            "dir/program.alpha",
+            program.as_bytes().to_vec(),
+        )
+        .unwrap()
+    }
+
+    fn summary(bundle: &Bundle) -> RunSummary {
+        bundle.summary().unwrap()
+    }
+
+    /// Replaces the content of the file `name` in `bundle` and optionally updates the hashes file.
+    fn replace_file(bundle: &mut Bundle, name: &str, content: Vec<u8>, update_hashes: bool) {
+        bundle.files.iter_mut().find(|(n, _)| n == name).unwrap().1 = content;
+        if update_hashes {
+            let hashes = super::hashes(&bundle.files[..bundle.files.len() - 1]);
+            replace_file(bundle, HASHES_FILE, hashes.into_bytes(), false);
+        }
+    }
+
+    #[test]
+    fn test_program_name() {
+        assert_eq!(super::program_name("dir/program.alpha"), "program.alpha");
+        assert_eq!(
+            super::program_name("https://example.com/solutions/program.alpha"),
+            "program.alpha"
+        );
+        assert_eq!(super::program_name(utils::STDIN_FILENAME), "program.alpha");
+    }
+
+    #[test]
+    fn test_create_bundle() {
+        let bundle = bundle("a := 4\npush\np(h1) := a");
+        assert_eq!(
+            bundle
+                .files
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "program.alpha",
+                "memory_config.json",
+                "final_memory.json",
+                "summary.json",
+                "settings.json",
+                "run_metadata.json",
+                "SHA256SUMS"
+            ]
+        );
+        let summary = summary(&bundle);
+        assert_eq!(summary.instruction_runs, 3);
+        assert_eq!(summary.final_stack, vec![4]);
+        assert_eq!(summary.runtime_error, None);
+        let hashes = String::from_utf8(bundle.files[6].1.clone()).unwrap();
+        assert_eq!(
+            hashes.lines().next().unwrap(),
+            format!("{}  program.alpha", sha256_hex(b"a := 4\npush\np(h1) := a"))
+        );
+        assert_eq!(bundle.content_hash(), sha256_hex(hashes.as_bytes()));
+        // bundles are reproducible
+        assert_eq!(
+            bundle.content_hash(),
+            self::bundle("a := 4\npush\np(h1) := a").content_hash()
+        );
+    }
+
+    #[test]
+    fn test_create_bundle_runtime_error() {
+        let bundle = bundle("a := 4\npop");
+        assert_eq!(
+            summary(&bundle).runtime_error,
+            Some(
+                "runtime error in line 2: Attempt to pop value from stack while stack is empty"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_write_read_bundle() {
+        let bundle = bundle("a := 4");
+        let path = std::env::temp_dir().join("alpha_tui_test_write_bundle.tar");
+        bundle.write(path.to_str().unwrap()).unwrap();
+        let read = Bundle::read(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(read, bundle);
+    }
+
+    #[test]
+    fn test_verify_bundle() {
+        let bundle = bundle("a := rand(1, 100)\npush\np(h1) := a");
+        assert_eq!(bundle.verify(), Vec::<String>::new());
+        let bundle = self::bundle("a := 4\npop");
+        assert_eq!(bundle.verify(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_verify_bundle_settings() {
+        // the program stops at the limits, the limits have to be used again when the bundle is verified
+        let program = "loop: call f\ngoto loop\nf: push\ncall f";
+        let bundle = Bundle::create(
+            &GlobalArgs {
+                instruction_limit: Some(20),
+                call_stack_limit: Some(3),
+                stack_limit: Some(5),
+                tail_call_optimization: true,
+                seed: Some(7),
+                ..GlobalArgs::default()
+            },
+            &test_utils::string_literal_to_vec(program),
+            "program.alpha",
+            program.as_bytes().to_vec(),
+        )
+        .unwrap();
+        let settings: RunSettings =
+            serde_json::from_slice(bundle.file(SETTINGS_FILE).unwrap()).unwrap();
+        assert_eq!(settings.instruction_limit, 20);
+        assert_eq!(settings.call_stack_limit, 3);
+        assert_eq!(settings.stack_limit, Some(5));
+        assert!(settings.tail_call_optimization);
+        assert!(summary(&bundle).runtime_error.is_some());
+        assert_eq!(bundle.verify(), Vec::<String>::new());
+
+        let bundle = Bundle::create(
+            &GlobalArgs {
+                instruction_limit: Some(20),
+                ..GlobalArgs::default()
+            },
+            &test_utils::string_literal_to_vec("loop: a := 1\ngoto loop"),
+            "program.alpha",
+            b"loop: a := 1\ngoto loop".to_vec(),
+        )
+        .unwrap();
+        assert!(summary(&bundle).runtime_error.is_some());
+        assert_eq!(bundle.verify(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_verify_bundle_without_settings() {
+        // bundles that were created before the settings were stored use the default settings
+        let mut bundle = bundle("a := rand(1, 100)\np(h1) := a");
+        bundle.files.retain(|(name, _)| name != SETTINGS_FILE);
+        replace_file(&mut bundle, HASHES_FILE, Vec::new(), false);
+        let hashes = super::hashes(&bundle.files[..bundle.files.len() - 1]);
+        replace_file(&mut bundle, HASHES_FILE, hashes.into_bytes(), false);
+        assert_eq!(bundle.verify(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_verify_bundle_tampered() {
+        let mut bundle = bundle("a := 4\np(h1) := a");
+        let final_memory = String::from_utf8(bundle.file(FINAL_MEMORY_FILE).unwrap().to_vec())
+            .unwrap()
+            .replace('4', "5");
+        replace_file(
+            &mut bundle,
+            FINAL_MEMORY_FILE,
+            final_memory.into_bytes(),
+            false,
+        );
+        assert_eq!(
+            bundle.verify(),
+            vec![
+                "hash of 'final_memory.json' does not match",
+                "'final_memory.json' does not match the result of the re-run"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verify_bundle_stale() {
+        // program was changed after the bundle was created and the hashes were updated
+        let mut bundle = bundle("a := 4\np(h1) := a");
+        replace_file(
+            &mut bundle,
+            "program.alpha",
+            b"a := 5\np(h1) := a".to_vec(),
+            true,
+        );
+        assert_eq!(
+            bundle.verify(),
+            vec!["'final_memory.json' does not match the result of the re-run"]
+        );
+    }
+}
@@ -1,85 +1,637 @@
-use std::process::exit;
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+    process::Command,
+    thread,
+    time::{Duration, SystemTime},
+};
 
-use miette::miette;
+use crossterm::style::Stylize;
+use miette::{miette, Result};
 
 use crate::{
-    cli::{CheckArgs, CheckCommand, GlobalArgs},
-    runtime::builder::RuntimeBuilder,
+    cli::{CheckArgs, CheckCommand, CheckLoadArgs, GlobalArgs, Verbosity},
+    error_codes,
+    exit_code::ExitCode,
+    export::ast::Ast,
+    instructions::error_handling::{BuildProgramError, BuildProgramErrorTypes},
+    runtime::{
+        builder::{ProgramBuilder, RuntimeBuilder},
+        error_handling::{RuntimeError, RuntimeErrorType},
+        lints::{self, LintLevel, LintLevels},
+        memory_usage::MemoryUsage,
+        observer::ExecutionEvent,
+        statistics::ProgramStatistics,
+        watchpoints::Watchpoints,
+    },
+    utils,
 };
 
+use super::progress_bar;
+
+/// How often the watched file is polled for changes.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Result of a single check of a program.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckResult {
+    Success,
+    /// The program could not be parsed, contains the message that should be printed.
+    ParseError(String),
+    /// The program was parsed but could not be build, contains the message that should be printed.
+    BuildError(String),
+    /// The program compiled but a runtime error occurred, contains the message that should be printed.
+    RuntimeError(String),
+    /// The program compiled but the instruction limit was reached, contains the message that should be printed.
+    InstructionLimitReached(String),
+    /// Some file could not be read, contains the message that should be printed.
+    IoError(String),
+    /// The program was stopped because a watchpoint was triggered, contains the message that should be printed.
+    Stopped(String),
+}
+
+impl CheckResult {
+    /// Returns the exit code that the check command should exit with for this result.
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::Success | Self::Stopped(_) => ExitCode::Success,
+            Self::ParseError(_) => ExitCode::ParseError,
+            Self::BuildError(_) => ExitCode::BuildError,
+            Self::RuntimeError(_) => ExitCode::RuntimeError,
+            Self::InstructionLimitReached(_) => ExitCode::InstructionLimitReached,
+            Self::IoError(_) => ExitCode::IoError,
+        }
+    }
+
+    /// Returns the message that describes this result.
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Success => "Check successful",
+            Self::ParseError(msg)
+            | Self::BuildError(msg)
+            | Self::RuntimeError(msg)
+            | Self::InstructionLimitReached(msg)
+            | Self::IoError(msg)
+            | Self::Stopped(msg) => msg,
+        }
+    }
+
+    /// Returns a short, single line summary of this result.
+    fn summary(&self) -> &'static str {
+        match self {
+            Self::Success => "check successful",
+            Self::ParseError(_) | Self::BuildError(_) => "program did not compile",
+            Self::RuntimeError(_) => "runtime error while running program",
+            Self::InstructionLimitReached(_) => "instruction limit reached",
+            Self::IoError(_) => "unable to read file",
+            Self::Stopped(_) => "stopped at data breakpoint",
+        }
+    }
+}
+
 pub fn check(
     global_args: &GlobalArgs,
     check_args: &CheckArgs,
     instructions: Vec<String>,
     input: &str,
 ) {
+    let emit_events = matches!(
+        check_args.command,
+        CheckCommand::Run {
+            emit_events: true,
+            ..
+        }
+    );
+    // status messages would be mixed with the events
+    let verbosity = if emit_events {
+        Verbosity::Quiet
+    } else {
+        global_args.verbosity()
+    };
+    let res = run_check(global_args, check_args, &instructions, input, verbosity);
+    if emit_events {
+        // stdout only contains the events
+        eprintln!("{}", res.message());
+    } else {
+        println!("{}", res.message());
+    }
+    if res != CheckResult::Success {
+        res.exit_code().exit();
+    }
+}
+
+/// Performs the check that is specified in `check_args` on the provided instructions.
+///
+/// Unless `verbosity` is quiet, status messages are printed to stdout while the runtime is build.
+pub fn run_check(
+    global_args: &GlobalArgs,
+    check_args: &CheckArgs,
+    instructions: &[String],
+    input: &str,
+    verbosity: Verbosity,
+) -> CheckResult {
+    let print_progress = verbosity >= Verbosity::Normal;
     // create runtime builder and apply cli args
-    println!("Building instructions");
-    let mut rb = match RuntimeBuilder::new(&instructions, input) {
-        Ok(rb) => rb,
-        Err(e) => {
-            println!(
-                "Check unsuccessful, program did not compile.\nError: {:?}",
-                miette!(e)
-            );
-            exit(1);
+    if print_progress {
+        println!("Building instructions");
+    }
+    // programs that are read from json are written as lines, so that comments that suppress lints are found
+    let (rb, lines) = if check_args.ast {
+        match program_from_ast(instructions) {
+            Ok(program) => (program.runtime_builder(), program.lines()),
+            Err(e) => (Err(e), Vec::new()),
         }
+    } else {
+        (
+            RuntimeBuilder::new(instructions, input),
+            instructions.to_vec(),
+        )
+    };
+    let mut rb = match rb {
+        Ok(rb) => rb,
+        Err(e) => return build_program_error_result(e),
     };
 
-    println!("Building runtime");
+    if print_progress {
+        println!("Building runtime");
+    }
     if let Err(e) = rb.apply_global_cli_args(global_args) {
-        println!(
+        return CheckResult::IoError(format!(
             "Check unsuccessful: {:?}",
             miette!(
                 "Unable to create RuntimeBuilder, memory config could not be loaded from file:\n{e}"
             )
-        );
-        exit(10);
+        ));
     }
     if let Err(e) =
         rb.apply_instruction_limiting_args(&check_args.check_load_args.instruction_limiting_args)
     {
-        println!(
+        return CheckResult::BuildError(format!(
             "Check unsuccessful: {:?}",
             miette!("Unable to create RuntimeBuilder:\n{:?}", e)
-        );
-        exit(1);
+        ));
     }
     if let Err(e) = rb.apply_check_load_args(&check_args.check_load_args) {
-        println!(
+        return CheckResult::BuildError(format!(
             "Check unsuccessful: {:?}",
             miette!("Unable to create RuntimeBuilder:\n{:?}", e)
-        );
-        exit(1);
+        ));
     }
+    let memory_config = rb.memory_config().cloned().unwrap_or_default();
     // build runtime
     let mut rt = match rb.build() {
         Ok(rt) => rt,
         Err(e) => {
-            println!(
-                "Check unsuccessful, program did not compile.\nError: {:?}",
-                miette!(e)
-            );
-            exit(1);
+            return CheckResult::BuildError(format!(
+                "Check unsuccessful, program did not compile.\nError: {}",
+                format_error(e)
+            ));
         }
     };
+    if verbosity == Verbosity::Verbose {
+        match &global_args.memory_config_file {
+            Some(path) => println!("Memory config: {path}"),
+            None => println!("Memory config: command line arguments"),
+        }
+        if rt.settings().disable_instruction_limit {
+            println!("Instruction limit: disabled");
+        } else {
+            println!("Instruction limit: {}", rt.settings().instruction_limit);
+        }
+    }
 
-    match check_args.command {
-        CheckCommand::Compile => {
-            println!("Check successful");
-            return;
+    if let Some(path) = &check_args.emit {
+        let ast = Ast::new(&rt, &lines, input);
+        let json = serde_json::to_string_pretty(&ast).expect("ast should be serializable");
+        if let Err(e) = fs::write(path, json + "\n") {
+            return CheckResult::IoError(format!(
+                "Check unsuccessful: {:?}",
+                miette!("Unable to write ast to file [{}]: {}", path, e)
+            ));
         }
-        CheckCommand::Run => (),
     }
 
+    let lint_levels = LintLevels::new(&check_args.allow, &check_args.warn, &check_args.deny)
+        .with_suppressions(&lines);
+    let mut denied = Vec::new();
+    if print_progress {
+        println!("{}", empty_lines_behavior(&check_args.check_load_args));
+        println!(
+            "Return with an empty call stack {}",
+            check_args
+                .check_load_args
+                .end_behavior
+                .unwrap_or_default()
+                .describe()
+        );
+    }
+    for warning in lints::lint(&rt, &lines) {
+        match lint_levels.level(&warning) {
+            LintLevel::Allow => (),
+            LintLevel::Warn if print_progress => {
+                println!("{}", format_error(miette::Report::new(warning)));
+            }
+            LintLevel::Warn => (),
+            LintLevel::Deny => denied.push(warning),
+        }
+    }
+    if !denied.is_empty() {
+        let mut msg = format!(
+            "Check unsuccessful, {} denied lint warning{} found.",
+            denied.len(),
+            if denied.len() == 1 { " was" } else { "s were" }
+        );
+        for warning in denied {
+            msg.push_str(&format!(
+                "\nError: {}",
+                format_error(miette::Report::new(warning))
+            ));
+        }
+        return CheckResult::BuildError(msg);
+    }
+
+    let statistics = ProgramStatistics::new(&rt);
+    let res = match check_args.command {
+        CheckCommand::Compile => Ok(true),
+        CheckCommand::Memory => {
+            println!("{}", MemoryUsage::new(&memory_config, rt.instructions()));
+            return CheckResult::Success;
+        }
+        CheckCommand::Run {
+            emit_events,
+            keep_going,
+        } => {
+            // the instruction limit is the largest number of instructions that can be run
+            let progress = progress_bar(
+                (!rt.settings().disable_instruction_limit)
+                    .then_some(rt.settings().instruction_limit as u64),
+                "instructions",
+                verbosity,
+            );
+            let mut observer = |event: &ExecutionEvent| {
+                progress.inc(1);
+                if !emit_events {
+                    return;
+                }
+                match serde_json::to_string(event) {
+                    // errors are ignored, as the receiver of the events may stop reading at any time
+                    Ok(json) => {
+                        let _ = writeln!(io::stdout().lock(), "{json}");
+                    }
+                    Err(e) => eprintln!("Unable to serialize event: {e}"),
+                }
+            };
+            let mut watchpoints = Watchpoints::new(
+                check_args.check_load_args.watchpoints(),
+                rt.runtime_memory(),
+            );
+            if keep_going {
+                let errors = rt.run_keep_going(&mut observer);
+                if !errors.is_empty() {
+                    return keep_going_result(errors);
+                }
+                Ok(true)
+            } else if watchpoints.is_empty() {
+                rt.run_observed(&mut observer)
+            } else {
+                match rt.run_until_watchpoint(&mut observer, &mut watchpoints) {
+                    Ok(Some(hit)) => {
+                        return CheckResult::Stopped(format!(
+                            "Check stopped, a data breakpoint was triggered.\n{hit}"
+                        ))
+                    }
+                    Ok(None) => Ok(true),
+                    Err(e) => Err(e),
+                }
+            }
+        }
+    };
+
     // run runtime
-    if let Err(e) = rt.run() {
+    if let Err(e) = res {
+        let limit_reached = matches!(e.reason, RuntimeErrorType::DesignLimitReached(_));
+        let msg = format!(
+            "Check unsuccessful, runtime error while running program.\nError: {}",
+            format_error(miette!(e))
+        );
+        if limit_reached {
+            return CheckResult::InstructionLimitReached(msg);
+        }
+        return CheckResult::RuntimeError(msg);
+    }
+
+    if verbosity == Verbosity::Verbose && matches!(check_args.command, CheckCommand::Run { .. }) {
+        println!("Executed {} instructions", rt.instruction_runs());
+    }
+    if print_progress {
+        println!("{statistics}");
+        if let Some(timings) = rt.timings() {
+            println!("{timings}");
+        }
+    }
+    CheckResult::Success
+}
+
+/// Reads the program from the lines of a JSON file that was written with `--emit`.
+#[allow(clippy::result_large_err)]
+fn program_from_ast(lines: &[String]) -> Result<ProgramBuilder, BuildProgramError> {
+    match serde_json::from_str::<Ast>(&lines.join("\n")) {
+        Ok(ast) => ast.program(),
+        Err(e) => Err(BuildProgramError {
+            reason: BuildProgramErrorTypes::AstInvalid(e.to_string()),
+        }),
+    }
+}
+
+/// Returns the result of a program that could not be parsed or build.
+///
+/// Invalid instructions and invalid ast files are parse errors, all other errors are build errors.
+#[allow(clippy::result_large_err)]
+fn build_program_error_result(e: BuildProgramError) -> CheckResult {
+    let parse_error = matches!(
+        e.reason,
+        BuildProgramErrorTypes::ParseError { .. } | BuildProgramErrorTypes::AstInvalid(_)
+    );
+    let msg = format!(
+        "Check unsuccessful, program did not compile.\nError: {}",
+        format_error(miette!(e))
+    );
+    if parse_error {
+        CheckResult::ParseError(msg)
+    } else {
+        CheckResult::BuildError(msg)
+    }
+}
+
+/// Formats the error like miette does and adds a hint how the explanation of its error code can be printed.
+fn format_error(e: miette::Report) -> String {
+    let mut msg = format!("{e:?}");
+    if let Some(hint) = error_codes::explain_hint(e.as_ref()) {
+        msg.push_str(&hint);
+    }
+    msg
+}
+
+/// Returns the result of a run in which instructions that caused a runtime error were skipped.
+fn keep_going_result(errors: Vec<RuntimeError>) -> CheckResult {
+    if errors.is_empty() {
+        return CheckResult::Success;
+    }
+    let limit_reached = errors
+        .iter()
+        .any(|e| matches!(e.reason, RuntimeErrorType::DesignLimitReached(_)));
+    let mut msg = format!(
+        "Check unsuccessful, {} runtime error{} while running program.",
+        errors.len(),
+        if errors.len() == 1 { "" } else { "s" }
+    );
+    for e in errors {
+        msg.push_str(&format!("\nError: {}", format_error(miette!(e))));
+    }
+    if limit_reached {
+        CheckResult::InstructionLimitReached(msg)
+    } else {
+        CheckResult::RuntimeError(msg)
+    }
+}
+
+/// Returns a description of how empty lines are handled with the provided arguments.
+///
+/// Empty lines are lines that contain no instruction, for example lines that only contain a comment or a label.
+fn empty_lines_behavior(args: &CheckLoadArgs) -> String {
+    let steps = if args.skip_empty_lines {
+        "are skipped when the program is run"
+    } else {
+        "consume an execution step"
+    };
+    let labels = if args.deny_labels_on_empty_lines {
+        "not allowed"
+    } else {
+        "allowed"
+    };
+    format!("Empty lines count as lines for jump targets and {steps}, labels on empty lines are {labels}")
+}
+
+/// Keeps running and checks the file located at `input` every time it is changed.
+///
+/// The result of each check is printed to stdout. If `check_args.notify` is set,
+/// a desktop notification is sent every time the result changes.
+///
+/// While the file can not be accessed, e.g. because an editor replaces it when it is saved, it is treated
+/// as unchanged, so watching only ends when the process is stopped.
+pub fn watch(global_args: &GlobalArgs, check_args: &CheckArgs, input: &str) -> ! {
+    println!("Watching {input} for changes, press Ctrl+C to stop");
+    let mut last_modified: Option<SystemTime> = None;
+    let mut last_result: Option<CheckResult> = None;
+    loop {
+        let modified = fs::metadata(input).and_then(|m| m.modified()).ok();
+        if let Some(modified) = modified.filter(|m| last_modified != Some(*m)) {
+            last_modified = Some(modified);
+            let res = match utils::read_file(input) {
+                Ok(instructions) => run_check(
+                    global_args,
+                    check_args,
+                    &instructions,
+                    input,
+                    Verbosity::Quiet,
+                ),
+                Err(e) => CheckResult::IoError(format!("Unable to read file [{input}]: {e}")),
+            };
+            print_watch_result(input, &res, last_result.is_none());
+            // only notify when the status has changed, not on every save
+            let status_changed = match &last_result {
+                Some(last) => last.exit_code() != res.exit_code(),
+                None => false,
+            };
+            if status_changed && check_args.notify {
+                send_desktop_notification(
+                    "alpha_tui",
+                    &format!("{}: {}", file_name(input), res.summary()),
+                );
+            }
+            last_result = Some(res);
+        }
+        thread::sleep(WATCH_POLL_INTERVAL);
+    }
+}
+
+/// Prints the result of a check run in watch mode.
+///
+/// Successful checks are printed in green, unsuccessful checks in red.
+fn print_watch_result(input: &str, res: &CheckResult, first_run: bool) {
+    if !first_run {
+        println!();
         println!(
-            "Check unsuccessful, runtime error while running program.\nError: {:?}",
-            miette!(e)
+            "{}",
+            format!("Change detected in {input}, rechecking").dark_grey()
         );
-        exit(1);
     }
+    match res {
+        CheckResult::Success => println!("{}", res.message().green()),
+        _ => println!("{}", res.message().red()),
+    }
+}
 
-    println!("Check successful");
+/// Returns the name of the file located at `path` or `path` itself, if the name can not be determined.
+fn file_name(path: &str) -> &str {
+    Path::new(path)
+        .file_name()
+        .and_then(|f| f.to_str())
+        .unwrap_or(path)
+}
+
+/// Tries to send a desktop notification using the tools provided by the operating system.
+///
+/// Errors are ignored, as notifications are only a convenience feature.
+fn send_desktop_notification(summary: &str, body: &str) {
+    let res = if cfg!(target_os = "macos") {
+        Command::new("osascript")
+            .arg("-e")
+            .arg(format!(
+                "display notification \"{}\" with title \"{}\"",
+                body.replace('"', "'"),
+                summary.replace('"', "'")
+            ))
+            .output()
+    } else {
+        Command::new("notify-send").arg(summary).arg(body).output()
+    };
+    // notifications are optional, so errors are ignored
+    let _ = res;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cli::{CheckArgs, CheckCommand, CheckLoadArgs, GlobalArgs, Verbosity},
+        exit_code::ExitCode,
+        utils::test_utils,
+    };
+
+    use super::{empty_lines_behavior, run_check, CheckResult};
+
+    fn check_args(command: CheckCommand) -> CheckArgs {
+        CheckArgs {
+            check_load_args: CheckLoadArgs::default(),
+            file: "test".to_string(),
+            watch: false,
+            notify: false,
+            allow: Vec::new(),
+            warn: Vec::new(),
+            deny: Vec::new(),
+            emit: None,
+            ast: false,
+            command,
+        }
+    }
+
+    #[test]
+    fn test_run_check_success() {
+        let res = run_check(
+            &GlobalArgs::default(),
+            &check_args(CheckCommand::Run {
+                emit_events: false,
+                keep_going: false,
+            }),
+            &test_utils::string_literal_to_vec("a := 5\np(h1) := a * 2"),
+            "test",
+            Verbosity::Quiet,
+        );
+        assert_eq!(res, CheckResult::Success);
+        assert_eq!(res.exit_code(), ExitCode::Success);
+    }
+
+    #[test]
+    fn test_run_check_compile_error() {
+        let res = run_check(
+            &GlobalArgs::default(),
+            &check_args(CheckCommand::Compile),
+            &test_utils::string_literal_to_vec("a := xyz"),
+            "test",
+            Verbosity::Quiet,
+        );
+        assert!(matches!(res, CheckResult::ParseError(_)));
+        assert_eq!(res.exit_code(), ExitCode::ParseError);
+        let res = run_check(
+            &GlobalArgs::default(),
+            &check_args(CheckCommand::Compile),
+            &test_utils::string_literal_to_vec("goto missing"),
+            "test",
+            Verbosity::Quiet,
+        );
+        assert!(matches!(res, CheckResult::BuildError(_)));
+        assert_eq!(res.exit_code(), ExitCode::BuildError);
+    }
+
+    #[test]
+    fn test_run_check_runtime_error() {
+        let compile = run_check(
+            &GlobalArgs::default(),
+            &check_args(CheckCommand::Compile),
+            &test_utils::string_literal_to_vec("pop"),
+            "test",
+            Verbosity::Quiet,
+        );
+        assert_eq!(compile, CheckResult::Success);
+        let run = run_check(
+            &GlobalArgs::default(),
+            &check_args(CheckCommand::Run {
+                emit_events: false,
+                keep_going: false,
+            }),
+            &test_utils::string_literal_to_vec("pop"),
+            "test",
+            Verbosity::Quiet,
+        );
+        assert!(matches!(run, CheckResult::RuntimeError(_)));
+        assert_eq!(run.exit_code(), ExitCode::RuntimeError);
+    }
+
+    #[test]
+    fn test_run_check_instruction_limit_reached() {
+        let global_args = GlobalArgs {
+            instruction_limit: Some(10),
+            ..GlobalArgs::default()
+        };
+        let res = run_check(
+            &global_args,
+            &check_args(CheckCommand::Run {
+                emit_events: false,
+                keep_going: false,
+            }),
+            &test_utils::string_literal_to_vec("loop: goto loop"),
+            "test",
+            Verbosity::Quiet,
+        );
+        assert!(matches!(res, CheckResult::InstructionLimitReached(_)));
+        assert_eq!(res.exit_code(), ExitCode::InstructionLimitReached);
+    }
+
+    #[test]
+    fn test_run_check_labels_on_empty_lines() {
+        let program = test_utils::string_literal_to_vec("a := 1\nloop:\na := a + 1");
+        let res = run_check(
+            &GlobalArgs::default(),
+            &check_args(CheckCommand::Compile),
+            &program,
+            "test",
+            Verbosity::Quiet,
+        );
+        assert_eq!(res, CheckResult::Success);
+        let mut args = check_args(CheckCommand::Compile);
+        args.check_load_args.deny_labels_on_empty_lines = true;
+        let res = run_check(
+            &GlobalArgs::default(),
+            &args,
+            &program,
+            "test",
+            Verbosity::Quiet,
+        );
+        assert!(matches!(res, CheckResult::BuildError(_)));
+        assert_eq!(
+            empty_lines_behavior(&args.check_load_args),
+            "Empty lines count as lines for jump targets and consume an execution step, labels on empty lines are not allowed"
+        );
+    }
 }
@@ -0,0 +1,20 @@
+use miette::{miette, Result};
+
+use crate::{
+    cli::ExplainArgs,
+    error_codes::{self, ERROR_CODES},
+};
+
+pub fn explain(explain_args: &ExplainArgs) -> Result<()> {
+    let Some(code) = &explain_args.code else {
+        for code in ERROR_CODES {
+            println!("{}  {}", code.code, code.title);
+        }
+        return Ok(());
+    };
+    let code = error_codes::find(code).ok_or_else(|| {
+        miette!("Unknown error code '{code}', run `alpha_tui explain` to list all error codes")
+    })?;
+    println!("{}: {}\n\n{}", code.code, code.title, code.explanation);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::{fs, rc::Rc, time::Duration};
+
+use miette::{miette, IntoDiagnostic, Result};
+use ratatui::{backend::TestBackend, Terminal};
+
+use crate::{
+    app::{
+        ui::{style::Theme, syntax_highlighting::SyntaxHighlighter},
+        App,
+    },
+    base::ComparisonNotation,
+    cli::{ExportArgs, GlobalArgs},
+    export::{
+        self,
+        animation::{self, AnimationFormat},
+    },
+    runtime::{builder::RuntimeBuilder, Runtime},
+};
+
+pub fn export(
+    global_args: &GlobalArgs,
+    export_args: &ExportArgs,
+    instructions: Vec<String>,
+    input: &str,
+) -> Result<()> {
+    let mut rb = RuntimeBuilder::new(&instructions, input).map_err(|e| miette!(e))?;
+    rb.apply_global_cli_args(global_args)
+        .map_err(|e| miette!(e))?;
+    let rt = rb.build()?;
+
+    let content = match (export_args.animate, export_args.to) {
+        (Some(format), _) => animate(rt, export_args, format, &instructions, input)?,
+        (None, Some(target)) => {
+            let mut content = export::convert(&rt, target).join("\n");
+            content.push('\n');
+            content
+        }
+        (None, None) => unreachable!("clap requires --to when --animate is not set"),
+    };
+    match &export_args.output {
+        Some(path) => {
+            fs::write(path, content)
+                .into_diagnostic()
+                .map_err(|e| miette!("Unable to write file [{}]: {}", path, e))?;
+            if export_args.animate.is_some() {
+                println!("Animation written to {path}");
+            } else {
+                println!("Program converted and written to {path}");
+            }
+        }
+        None => print!("{content}"),
+    }
+    Ok(())
+}
+
+/// Runs the program off-screen and returns the animation of its execution in `format`.
+fn animate(
+    rt: Runtime,
+    export_args: &ExportArgs,
+    format: AnimationFormat,
+    instructions: &[String],
+    input: &str,
+) -> Result<String> {
+    let theme = Rc::new(Theme::default());
+    let lines = SyntaxHighlighter::new(&theme.syntax_highlighting_theme())
+        .input_to_lines(instructions, true)?;
+    let mut app = App::from_runtime(
+        rt,
+        input.to_string(),
+        &lines,
+        &None,
+        None,
+        None,
+        None,
+        false,
+        true,
+        ComparisonNotation::default(),
+        theme,
+    );
+    let (width, height) = (export_args.width, export_args.height);
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).into_diagnostic()?;
+    let mut frames = Vec::new();
+    app.replay(&mut terminal, export_args.max_frames, |buffer| {
+        frames.push(match format {
+            AnimationFormat::Asciinema => animation::frame_to_ansi(buffer),
+            AnimationFormat::Frames => animation::frame_to_text(buffer),
+        });
+    })?;
+    Ok(match format {
+        AnimationFormat::Asciinema => animation::asciinema_cast(
+            &frames,
+            width,
+            height,
+            Duration::from_millis(export_args.frame_delay),
+            input,
+        ),
+        AnimationFormat::Frames => animation::text_frames(&frames),
+    })
+}
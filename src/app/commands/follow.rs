@@ -0,0 +1,227 @@
+use std::{
+    io::{BufRead, BufReader},
+    net::{Ipv4Addr, TcpStream},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use miette::{miette, IntoDiagnostic, Result};
+use ratatui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    text::Line,
+    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+    Frame, Terminal,
+};
+
+use crate::{
+    app::{
+        follow::FollowState,
+        signals::{self, Signals},
+        ui::style::Theme,
+    },
+    cli::FollowArgs,
+};
+
+/// How long to wait for key presses before new states are received.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+pub fn follow(follow_args: &FollowArgs) -> Result<()> {
+    let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, follow_args.port)).map_err(|e| {
+        miette!(
+            "Unable to connect to port {}: {e}\nMake sure that a program is loaded with \"--serve {}\"",
+            follow_args.port,
+            follow_args.port
+        )
+    })?;
+    // states are received in a background thread, so that the view stays responsive
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            if let Ok(state) = serde_json::from_str::<FollowState>(&line) {
+                if sender.send(state).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    let theme = follow_args
+        .theme
+        .as_ref()
+        .map(Theme::from)
+        .unwrap_or_default();
+    let mut terminal = super::setup_terminal()?;
+    let res = Follower::new(receiver, theme).run(&mut terminal);
+    super::restore_terminal(&mut terminal)?;
+    res
+}
+
+/// Displays the states that are received from the program that is followed.
+struct Follower {
+    receiver: Receiver<FollowState>,
+    /// Last state that was received.
+    state: Option<FollowState>,
+    /// True if the program that is followed has exited.
+    disconnected: bool,
+    theme: Theme,
+}
+
+impl Follower {
+    fn new(receiver: Receiver<FollowState>, theme: Theme) -> Self {
+        Self {
+            receiver,
+            state: None,
+            disconnected: false,
+            theme,
+        }
+    }
+
+    fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let signals = Signals::register()?;
+        loop {
+            if signals.terminate_requested() {
+                return Ok(());
+            }
+            if signals.take_resumed() {
+                signals::resume()?;
+                terminal.clear().into_diagnostic()?;
+            }
+            loop {
+                match self.receiver.try_recv() {
+                    Ok(state) => self.state = Some(state),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.disconnected = true;
+                        break;
+                    }
+                }
+            }
+            terminal.draw(|f| self.draw(f)).into_diagnostic()?;
+            if !event::poll(POLL_INTERVAL).into_diagnostic()? {
+                continue;
+            }
+            if let Event::Key(key) = event::read().into_diagnostic()? {
+                if key.kind == KeyEventKind::Release {
+                    continue;
+                }
+                if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    signals::suspend()?;
+                } else if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn draw(&self, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Fill(1),
+                Constraint::Length(1),
+            ])
+            .split(f.size());
+
+        // status of the execution
+        let (title, status) = match &self.state {
+            Some(state) => {
+                let status = match (&state.line, &state.instruction) {
+                    (Some(line), Some(instruction)) => {
+                        format!("{}, next line {line}: {instruction}", state.status)
+                    }
+                    (Some(line), None) => format!("{}, next line {line}", state.status),
+                    _ => state.status.clone(),
+                };
+                (format!("Following: {}", state.filename), status)
+            }
+            None => (
+                "Following".to_string(),
+                "Waiting for execution state".to_string(),
+            ),
+        };
+        let status = if self.disconnected {
+            format!("{status} (disconnected)")
+        } else {
+            status
+        };
+        let status_block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.code_block_border())
+            .style(self.theme.code_block());
+        f.render_widget(Paragraph::new(status).block(status_block), chunks[0]);
+
+        // memory
+        let memory_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Ratio(1, 4),
+                Constraint::Ratio(1, 4),
+                Constraint::Ratio(1, 4),
+                Constraint::Ratio(1, 4),
+            ])
+            .split(chunks[1]);
+        let state = self.state.clone().unwrap_or_default();
+        let mut accumulators = state
+            .accumulators
+            .iter()
+            .map(|(name, value)| memory_item(name, *value))
+            .collect::<Vec<_>>();
+        if let Some(gamma) = state.gamma {
+            accumulators.push(memory_item("y", gamma));
+        }
+        let memory_cells = state
+            .memory_cells
+            .iter()
+            .map(|(name, value)| memory_item(name, *value))
+            .collect();
+        let index_memory_cells = state
+            .index_memory_cells
+            .iter()
+            .map(|(idx, value)| memory_item(&format!("[{idx}]"), *value))
+            .collect();
+        let stack = state
+            .stack
+            .iter()
+            .rev()
+            .map(|value| ListItem::new(value.to_string()))
+            .collect();
+        for (idx, (title, items)) in [
+            ("Accumulators", accumulators),
+            ("Memory cells", memory_cells),
+            ("Index memory cells", index_memory_cells),
+            ("Stack", stack),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.memory_block_border())
+                .style(self.theme.memory_block());
+            f.render_widget(List::new(items).block(block), memory_chunks[idx]);
+        }
+
+        f.render_widget(
+            Paragraph::new(Line::from("Read-only view, press [q] to exit"))
+                .style(self.theme.keybinding_hint_paragraph()),
+            chunks[2],
+        );
+    }
+}
+
+/// Creates the list item that displays the value of a memory location.
+fn memory_item(name: &str, value: Option<i32>) -> ListItem<'static> {
+    match value {
+        Some(value) => ListItem::new(format!("{name}: {value}")),
+        None => ListItem::new(format!("{name}: None")),
+    }
+}
@@ -0,0 +1,425 @@
+use std::collections::BTreeMap;
+
+use miette::{miette, Result};
+use serde::Deserialize;
+
+use crate::{
+    base::{Comparison, Operation},
+    cli::{GlobalArgs, GradeArgs},
+    instructions::{Identifier, Value},
+    runtime::{builder::RuntimeBuilder, RuntimeMemory},
+    utils,
+};
+
+use super::run_metadata::RunMetadata;
+
+/// Rubric that is used to grade a program, criteria that are not set are not graded.
+#[derive(Debug, Clone, PartialEq, Deserialize, Default)]
+pub struct Rubric {
+    /// Maximum number of instructions that may be executed.
+    pub instruction_runs: Option<LimitCriterion>,
+    /// Maximum number of memory cells and index memory cells that may contain a value when the program has finished.
+    pub memory_cells: Option<LimitCriterion>,
+    /// Instructions that may not be used in the program.
+    pub forbidden_instructions: Option<ForbiddenInstructionsCriterion>,
+    /// Values the memory has to contain when the program has finished.
+    pub final_values: Option<FinalValuesCriterion>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct LimitCriterion {
+    pub max: usize,
+    #[serde(default = "default_points")]
+    pub points: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ForbiddenInstructionsCriterion {
+    /// Instructions in the same format as in the allowed instructions file.
+    #[serde(default)]
+    pub instructions: Vec<String>,
+    #[serde(default)]
+    pub comparisons: Vec<Comparison>,
+    #[serde(default)]
+    pub operations: Vec<Operation>,
+    #[serde(default = "default_points")]
+    pub points: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FinalValuesCriterion {
+    /// Maps memory locations in alpha notation, e.g. `a0`, `y`, `p(h1)` or `p(3)`, to the expected value.
+    pub values: BTreeMap<String, i32>,
+    #[serde(default = "default_points")]
+    pub points: u32,
+}
+
+fn default_points() -> u32 {
+    1
+}
+
+impl Rubric {
+    /// Tries to parse the provided file into a rubric.
+    pub fn try_from_file(path: &str) -> Result<Self> {
+        match serde_json::from_str::<Self>(&utils::read_file(path)?.join("\n")) {
+            Ok(rubric) => Ok(rubric),
+            Err(e) => Err(miette!(
+                "Rubric file '{path}' is invalid: json parse error: {e}"
+            )),
+        }
+    }
+}
+
+/// Result of a single criterion of the rubric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CriterionResult {
+    pub points: u32,
+    pub max_points: u32,
+    /// Single line description of the result.
+    pub summary: String,
+    /// Additional lines that explain why points were deducted.
+    pub details: Vec<String>,
+}
+
+impl CriterionResult {
+    fn new(passed: bool, max_points: u32, summary: String) -> Self {
+        Self {
+            points: if passed { max_points } else { 0 },
+            max_points,
+            summary,
+            details: Vec::new(),
+        }
+    }
+}
+
+/// Score breakdown of a graded program.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Grade {
+    pub criteria: Vec<CriterionResult>,
+    /// Set if the program did not compile or a runtime error occurred.
+    pub error: Option<String>,
+    /// Information that is needed to reproduce the run, not set if the memory config could not be loaded.
+    pub metadata: Option<RunMetadata>,
+}
+
+impl Grade {
+    pub fn score(&self) -> u32 {
+        self.criteria.iter().map(|c| c.points).sum()
+    }
+
+    pub fn max_score(&self) -> u32 {
+        self.criteria.iter().map(|c| c.max_points).sum()
+    }
+}
+
+impl std::fmt::Display for Grade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(metadata) = &self.metadata {
+            writeln!(f, "{metadata}\n")?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "{error}")?;
+        }
+        for criterion in &self.criteria {
+            writeln!(
+                f,
+                "[{}/{}] {}",
+                criterion.points, criterion.max_points, criterion.summary
+            )?;
+            for detail in &criterion.details {
+                writeln!(f, "      {detail}")?;
+            }
+        }
+        write!(f, "Score: {}/{}", self.score(), self.max_score())
+    }
+}
+
+pub fn grade(
+    global_args: &GlobalArgs,
+    grade_args: &GradeArgs,
+    instructions: Vec<String>,
+    input: &str,
+) -> Result<()> {
+    let rubric = Rubric::try_from_file(&grade_args.rubric)?;
+    println!(
+        "{}",
+        grade_program(global_args, &rubric, &instructions, input)?
+    );
+    Ok(())
+}
+
+/// Grades the program according to the rubric.
+///
+/// If the program does not compile, no points are awarded. If a runtime error occurs, only the forbidden instructions
+/// criterion can award points.
+///
+/// Returns an error if the rubric is invalid.
+pub fn grade_program(
+    global_args: &GlobalArgs,
+    rubric: &Rubric,
+    instructions: &[String],
+    input: &str,
+) -> Result<Grade> {
+    // validate rubric before the program is run
+    let forbidden = match &rubric.forbidden_instructions {
+        Some(criterion) => Some(utils::build_instruction_whitelist(
+            criterion.instructions.clone(),
+            "rubric",
+        )?),
+        None => None,
+    };
+    let final_values = match &rubric.final_values {
+        Some(criterion) => criterion
+            .values
+            .iter()
+            .map(
+                |(location, expected)| match Value::try_from((location, (0, location.len()))) {
+                    Ok(Value::Constant(_)) | Err(_) => Err(miette!(
+                        "Invalid memory location '{location}' in final values of rubric"
+                    )),
+                    Ok(value) => Ok((location, value, *expected)),
+                },
+            )
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+
+    let mut grade = Grade::default();
+    let rt = RuntimeBuilder::new(instructions, input)
+        .map_err(|e| format!("Program did not compile:\n{:?}", miette!(e)))
+        .and_then(|mut rb| {
+            rb.apply_global_cli_args(global_args)
+                .map_err(|e| format!("Memory config could not be loaded:\n{e:?}"))?;
+            grade.metadata = Some(RunMetadata::new(&rb));
+            rb.build()
+                .map_err(|e| format!("Program did not compile:\n{e:?}"))
+        });
+    let mut rt = match rt {
+        Ok(rt) => Some(rt),
+        Err(e) => {
+            grade.error = Some(e);
+            None
+        }
+    };
+
+    if let (Some(criterion), Some(forbidden)) = (&rubric.forbidden_instructions, forbidden) {
+        let used = rt
+            .as_ref()
+            .map(|rt| {
+                rt.instructions()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, i)| {
+                        forbidden.contains(&i.identifier())
+                            || i.comparison()
+                                .is_some_and(|c| criterion.comparisons.contains(c))
+                            || i.operation()
+                                .is_some_and(|o| criterion.operations.contains(o))
+                    })
+                    .map(|(idx, i)| format!("line {}: {i}", idx + 1))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut result = CriterionResult::new(
+            rt.is_some() && used.is_empty(),
+            criterion.points,
+            format!("forbidden instructions: {} used", used.len()),
+        );
+        result.details = used;
+        grade.criteria.push(result);
+    }
+
+    // run program, criteria below can only award points if the program finished
+    let finished_rt = rt.as_mut().and_then(|rt| match rt.run() {
+        Ok(_) => Some(rt),
+        Err(e) => {
+            grade.error = Some(format!(
+                "Runtime error while running program:\n{:?}",
+                miette!(e)
+            ));
+            None
+        }
+    });
+    let not_finished = "program did not finish";
+
+    if let Some(criterion) = &rubric.instruction_runs {
+        grade.criteria.push(match &finished_rt {
+            Some(rt) => CriterionResult::new(
+                rt.instruction_runs() <= criterion.max,
+                criterion.points,
+                format!(
+                    "instructions executed: {} (max {})",
+                    rt.instruction_runs(),
+                    criterion.max
+                ),
+            ),
+            None => CriterionResult::new(
+                false,
+                criterion.points,
+                format!("instructions executed: {not_finished}"),
+            ),
+        });
+    }
+
+    if let Some(criterion) = &rubric.memory_cells {
+        grade.criteria.push(match &finished_rt {
+            Some(rt) => {
+                let used = memory_cells_used(rt.runtime_memory());
+                CriterionResult::new(
+                    used <= criterion.max,
+                    criterion.points,
+                    format!("memory cells used: {used} (max {})", criterion.max),
+                )
+            }
+            None => CriterionResult::new(
+                false,
+                criterion.points,
+                format!("memory cells used: {not_finished}"),
+            ),
+        });
+    }
+
+    if let Some(criterion) = &rubric.final_values {
+        grade.criteria.push(match &finished_rt {
+            Some(rt) => {
+                let wrong = final_values
+                    .iter()
+                    .filter_map(|(location, value, expected)| {
+                        match value.value(rt.runtime_memory()) {
+                            Ok(actual) if actual == *expected => None,
+                            Ok(actual) => {
+                                Some(format!("{location}: expected {expected}, got {actual}"))
+                            }
+                            Err(_) => {
+                                Some(format!("{location}: expected {expected}, got no value"))
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let mut result = CriterionResult::new(
+                    wrong.is_empty(),
+                    criterion.points,
+                    format!(
+                        "final values: {} of {} correct",
+                        final_values.len() - wrong.len(),
+                        final_values.len()
+                    ),
+                );
+                result.details = wrong;
+                result
+            }
+            None => CriterionResult::new(
+                false,
+                criterion.points,
+                format!("final values: {not_finished}"),
+            ),
+        });
+    }
+    Ok(grade)
+}
+
+/// Returns the number of memory cells and index memory cells that contain a value.
+fn memory_cells_used(memory: &RuntimeMemory) -> usize {
+    memory
+        .memory_cells
+        .values()
+        .filter(|c| c.data.is_some())
+        .count()
+        + memory
+            .index_memory_cells
+            .values()
+            .filter(|c| c.is_some())
+            .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{base::Operation, cli::GlobalArgs, utils::test_utils};
+
+    use super::{
+        grade_program, FinalValuesCriterion, ForbiddenInstructionsCriterion, LimitCriterion, Rubric,
+    };
+
+    fn rubric() -> Rubric {
+        Rubric {
+            instruction_runs: Some(LimitCriterion { max: 3, points: 2 }),
+            memory_cells: Some(LimitCriterion { max: 1, points: 1 }),
+            forbidden_instructions: Some(ForbiddenInstructionsCriterion {
+                instructions: vec!["A := M".to_string()],
+                comparisons: Vec::new(),
+                operations: vec![Operation::Mul],
+                points: 2,
+            }),
+            final_values: Some(FinalValuesCriterion {
+                values: BTreeMap::from([("p(h1)".to_string(), 4), ("a0".to_string(), 4)]),
+                points: 3,
+            }),
+        }
+    }
+
+    fn grade(program: &str) -> super::Grade {
+        grade_program(
+            &GlobalArgs::default(),
+            &rubric(),
+            &test_utils::string_literal_to_vec(program),
+            "test",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_grade_full_score() {
+        let grade = grade("a := 2\na := a + 2\np(h1) := a");
+        assert_eq!(grade.error, None);
+        assert_eq!(grade.score(), 8);
+        assert_eq!(grade.max_score(), 8);
+    }
+
+    #[test]
+    fn test_grade_breakdown() {
+        let grade = grade("a := 2\na := a * 2\np(h1) := a\np(h2) := 1\na := p(h2)\np(h1) := 3");
+        let points = grade.criteria.iter().map(|c| c.points).collect::<Vec<_>>();
+        // forbidden instruction used, too many instructions and memory cells, wrong value for p(h1)
+        assert_eq!(points, vec![0, 0, 0, 0]);
+        assert_eq!(
+            grade.criteria[0].details,
+            vec!["line 2: a0 := a0 * 2", "line 5: a0 := p(h2)"]
+        );
+        assert_eq!(
+            grade.criteria[3].details,
+            vec!["a0: expected 4, got 1", "p(h1): expected 4, got 3"]
+        );
+    }
+
+    #[test]
+    fn test_grade_runtime_error() {
+        let grade = grade("a := 2\npop");
+        assert!(grade.error.is_some());
+        assert_eq!(grade.score(), 2);
+    }
+
+    #[test]
+    fn test_grade_compile_error() {
+        let grade = grade("a := 2\ngoto loop");
+        assert!(grade.error.is_some());
+        assert_eq!(grade.score(), 0);
+    }
+
+    #[test]
+    fn test_grade_invalid_rubric() {
+        let mut rubric = rubric();
+        rubric.final_values = Some(FinalValuesCriterion {
+            values: BTreeMap::from([("5".to_string(), 4)]),
+            points: 1,
+        });
+        assert!(grade_program(
+            &GlobalArgs::default(),
+            &rubric,
+            &test_utils::string_literal_to_vec("a := 1"),
+            "test",
+        )
+        .is_err());
+    }
+}
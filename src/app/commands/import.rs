@@ -0,0 +1,25 @@
+use std::fs;
+
+use miette::{miette, IntoDiagnostic, Result};
+
+use crate::{cli::ImportArgs, import};
+
+pub fn import(import_args: &ImportArgs, lines: Vec<String>) -> Result<()> {
+    let converted = import::convert(&lines, import_args.format).map_err(|e| miette!(e))?;
+    match &import_args.output {
+        Some(path) => {
+            let mut content = converted.join("\n");
+            content.push('\n');
+            fs::write(path, content)
+                .into_diagnostic()
+                .map_err(|e| miette!("Unable to write file [{}]: {}", path, e))?;
+            println!("Program converted and written to {path}");
+        }
+        None => {
+            for line in converted {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,17 +1,23 @@
 use std::rc::Rc;
 
-use miette::Result;
+use miette::{miette, Result};
 
 use crate::{
     app::{
         commands::load_instruction_history,
+        crash_report,
+        follow::StateServer,
+        session::Autosave,
+        statistics::Statistics,
         ui::{style::SyntaxHighlightingTheme, syntax_highlighting::SyntaxHighlighter},
+        value_format::ValueFormat,
         App,
     },
-    cli::{GlobalArgs, LoadArgs},
+    cli::{GlobalArgs, LoadArgs, Verbosity},
     instructions::instruction_config::InstructionConfig,
+    preset::ConfigFile,
     runtime::builder,
-    utils::write_file,
+    utils::{self, write_file},
 };
 
 #[allow(clippy::match_wildcard_for_single_variants)]
@@ -21,18 +27,35 @@ pub fn load(
     instructions: Vec<String>,
     input: String,
 ) -> Result<()> {
+    let print_progress = global_args.verbosity() >= Verbosity::Normal;
     // check if command history is set
-    let instruction_history = load_instruction_history(&load_args.custom_instruction_history_file)?;
+    let instruction_history = load_instruction_history(
+        &load_args.custom_instruction_history_file,
+        global_args.verbosity(),
+    )?;
 
     // create runtime builder and apply cli args
-    println!("Building instructions");
-    let mut rb = builder::RuntimeBuilder::new(&instructions, &input)?;
-    rb.apply_global_cli_args(global_args)?
-        .apply_check_load_args(&load_args.check_load_args)?
-        .apply_instruction_limiting_args(&load_args.check_load_args.instruction_limiting_args)?;
+    if print_progress {
+        println!("Building instructions");
+    }
+    let build_program = {
+        let (global_args, load_args, input) =
+            (global_args.clone(), load_args.clone(), input.clone());
+        move |instructions: &[String]| {
+            let mut rb = builder::RuntimeBuilder::new(instructions, &input)?;
+            rb.apply_global_cli_args(&global_args)?
+                .apply_check_load_args(&load_args.check_load_args)?
+                .apply_instruction_limiting_args(
+                    &load_args.check_load_args.instruction_limiting_args,
+                )?;
+            rb.build()
+        }
+    };
     // build runtime
-    println!("Building runtime");
-    let rt = rb.build()?;
+    if print_progress {
+        println!("Building runtime");
+    }
+    let rt = build_program(&instructions)?;
 
     let theme = Rc::new(super::load_theme(&load_args.load_playground_args)?);
 
@@ -43,11 +66,15 @@ pub fn load(
         theme.syntax_highlighting_theme()
     };
     let instructions = SyntaxHighlighter::new(&syntax_highlighting_theme)
+        .with_comparison_notation(load_args.load_playground_args.comparison_notation)
+        .with_identifier_notation(load_args.load_playground_args.identifier_notation)
         .input_to_lines(&instructions, !load_args.disable_alignment)?;
 
     if load_args.write_alignment {
         // write new formatting to file if enabled
-        println!("Writing alignment to source file");
+        if print_progress {
+            println!("Writing alignment to source file");
+        }
         write_file(
             &instructions.iter().map(|f| f.to_string()).collect(),
             &input,
@@ -67,9 +94,36 @@ pub fn load(
         None => None,
     };
 
+    let memory_visibilities =
+        super::load_memory_visibilities(global_args, &load_args.load_playground_args)?;
+
+    let config_file = ConfigFile::load(global_args)?;
+    let confirm_reset = config_file.confirm_reset.unwrap_or(true);
+    let confirm_quit = config_file.confirm_quit.unwrap_or(true);
+    // programs from stdin can't be recognized again, so that their sessions could be restored
+    let autosave = if input == utils::STDIN_FILENAME {
+        None
+    } else {
+        Autosave::new(&config_file.autosave, &input, rt.settings().clock.clone())
+    };
+    let recovered_session = autosave.as_ref().and_then(Autosave::recover);
+    let statistics = Statistics::new(&config_file.statistics, &input, rt.settings().clock.clone());
+
+    // share the execution state with followers
+    let state_server = match load_args.serve {
+        Some(port) => Some(
+            StateServer::bind(port)
+                .map_err(|e| miette!("Unable to share the execution state on port {port}: {e}"))?,
+        ),
+        None => None,
+    };
+
     // tui
     // setup terminal
-    println!("Ready to run, launching tui");
+    if print_progress {
+        println!("Ready to run, launching tui");
+    }
+    crash_report::install_hook(&input);
     let mut terminal = super::setup_terminal()?;
 
     // create app
@@ -84,8 +138,32 @@ pub fn load(
         load_args.custom_instruction_history_file.clone(),
         false,
         !load_args.load_playground_args.disable_syntax_highlighting,
+        load_args.load_playground_args.comparison_notation,
         theme,
-    );
+    )
+    .with_identifier_notation(load_args.load_playground_args.identifier_notation)
+    .with_value_format(ValueFormat::from(&config_file.value_format))
+    .with_stack_direction(load_args.load_playground_args.show_stack_direction)
+    .with_debug_keys(load_args.load_playground_args.debug_keys)
+    .with_alignment(!load_args.disable_alignment)
+    .with_program_builder(Box::new(build_program))
+    .with_resumable_division_by_zero(load_args.resumable_division_by_zero)
+    .with_confirm_custom_instructions(load_args.confirm_custom_instructions)
+    .with_custom_instructions(!load_args.no_custom_instructions)
+    .with_read_only(load_args.read_only)
+    .with_instructor_only(&config_file.instructor_only, load_args.instructor)
+    .with_presentation_mode(load_args.presentation)
+    .with_confirm_reset(confirm_reset)
+    .with_confirm_quit(confirm_quit)
+    .with_autosave(autosave)
+    .with_statistics(statistics)
+    .with_recovered_session(recovered_session)
+    .with_breakpoint_groups(load_args.breakpoint_group.clone())
+    .with_watchpoints(load_args.check_load_args.watchpoints())
+    .with_memory_visibilities(memory_visibilities);
+    if let Some(state_server) = state_server {
+        app = app.with_state_server(state_server);
+    }
     let res = app.run(&mut terminal);
 
     // restore terminal
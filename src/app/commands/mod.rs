@@ -9,26 +9,51 @@ use crossterm::{
     terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use directories::UserDirs;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressFinish, ProgressStyle};
 use miette::{miette, IntoDiagnostic, Result};
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 use crate::{
-    cli::LoadPlaygroundArgs,
+    cli::{GlobalArgs, LoadPlaygroundArgs, Verbosity},
     instructions::Instruction,
+    runtime::memory_config::{MemoryConfig, MemoryVisibilities},
     utils::{self, remove_comment},
 };
 
 use super::ui::style::Theme;
 
+/// Hidden command that runs the benchmarks
+pub mod bench;
+/// Bundle command
+pub mod bundle;
 /// Check command
 pub mod check;
+/// Explain command
+pub mod explain;
+/// Export command
+pub mod export;
+/// Follow command
+pub mod follow;
+/// Grade command
+pub mod grade;
+/// Import command
+pub mod import;
 /// Load command
 pub mod load;
 /// Playground command
 pub mod playground;
+/// Refactor command
+pub mod refactor;
+/// Information that is needed to reproduce a run, included in the outputs of commands
+pub mod run_metadata;
+/// Test command
+pub mod test;
+/// Update command
+pub mod update;
 
 fn load_instruction_history(
     custom_instruction_history_file: &Option<String>,
+    verbosity: Verbosity,
 ) -> Result<Option<Vec<String>>> {
     let mut instruction_history = None;
     if let Some(file) = custom_instruction_history_file {
@@ -47,13 +72,15 @@ fn load_instruction_history(
                 ))
             }
         };
-        println!("Instruction history provided, checking validity of provided instructions");
+        if verbosity >= Verbosity::Normal {
+            println!("Instruction history provided, checking validity of provided instructions");
+        }
         let mut checked_instructions = Vec::new();
         for (idx, instruction) in content.iter().enumerate() {
             // remove comment
             let instruction = remove_comment(instruction);
             // remove label if it exists
-            let mut splits = instruction.split_whitespace().collect::<Vec<&str>>();
+            let mut splits = utils::split_tokens(&instruction);
             if splits.is_empty() {
                 continue;
             }
@@ -71,12 +98,42 @@ fn load_instruction_history(
                 checked_instructions.push(instruction);
             }
         }
-        println!("Instruction history checked successfully");
+        if verbosity >= Verbosity::Normal {
+            println!("Instruction history checked successfully");
+        }
         instruction_history = Some(checked_instructions);
     }
     Ok(instruction_history)
 }
 
+/// Creates a progress bar that is drawn to stderr, `unit` is displayed after the position, e.g. "instructions".
+///
+/// If `len` is `None`, a spinner with the position is shown instead of a bar.
+/// The progress bar is hidden if stderr is not a terminal or if `verbosity` is quiet. It is removed when it is dropped.
+fn progress_bar(len: Option<u64>, unit: &str, verbosity: Verbosity) -> ProgressBar {
+    let target = if verbosity == Verbosity::Quiet {
+        ProgressDrawTarget::hidden()
+    } else {
+        // hidden automatically, if stderr is not a terminal
+        ProgressDrawTarget::stderr()
+    };
+    let (progress, template) = match len {
+        Some(len) => (
+            ProgressBar::with_draw_target(Some(len), target),
+            format!("[{{elapsed_precise}}] {{wide_bar}} {{human_pos}}/{{human_len}} {unit}"),
+        ),
+        None => (
+            ProgressBar::with_draw_target(None, target),
+            format!("{{spinner}} [{{elapsed_precise}}] {{human_pos}} {unit}"),
+        ),
+    };
+    progress
+        .with_style(
+            ProgressStyle::with_template(&template).expect("progress bar template should be valid"),
+        )
+        .with_finish(ProgressFinish::AndClear)
+}
+
 /// Setup the terminal and returns it.
 fn setup_terminal() -> Result<Terminal<CrosstermBackend<Stdout>>> {
     terminal::enable_raw_mode().into_diagnostic()?;
@@ -133,6 +190,23 @@ fn load_theme(load_playground_args: &LoadPlaygroundArgs) -> miette::Result<Theme
     Ok(Theme::default())
 }
 
+/// Returns when values of each memory type should be displayed.
+///
+/// The visibility is read from the memory config file, if it is set, and is overwritten by the cli args.
+fn load_memory_visibilities(
+    global_args: &GlobalArgs,
+    load_playground_args: &LoadPlaygroundArgs,
+) -> miette::Result<MemoryVisibilities> {
+    let mut visibilities = match &global_args.memory_config_file {
+        Some(path) => MemoryVisibilities::from(&MemoryConfig::try_from_file(path)?),
+        None => MemoryVisibilities::default(),
+    };
+    if let Some(args) = &load_playground_args.memory_visibility {
+        visibilities.apply_args(args);
+    }
+    Ok(visibilities)
+}
+
 /// Loads the content of the file located at `path` and tries to parse it into a theme.
 ///
 /// Returns error if file does not exist or content can not be parsed into a theme.
@@ -3,27 +3,57 @@ use std::rc::Rc;
 use miette::Result;
 
 use crate::{
-    app::App,
-    cli::{GlobalArgs, PlaygroundArgs},
-    runtime::builder::RuntimeBuilder,
+    app::{
+        crash_report, session::Autosave, statistics::Statistics, value_format::ValueFormat, App,
+    },
+    cli::{GlobalArgs, PlaygroundArgs, Verbosity},
+    preset::ConfigFile,
+    runtime::builder::ProgramBuilder,
 };
 
 use super::load_instruction_history;
 
 pub fn playground(global_args: &GlobalArgs, playground_args: &PlaygroundArgs) -> Result<()> {
+    let print_progress = global_args.verbosity() >= Verbosity::Normal;
     // check if command history is set
-    let instruction_history =
-        load_instruction_history(&playground_args.custom_instruction_history_file)?;
+    let instruction_history = load_instruction_history(
+        &playground_args.custom_instruction_history_file,
+        global_args.verbosity(),
+    )?;
 
-    println!("Building runtime");
+    if print_progress {
+        println!("Building runtime");
+    }
 
-    let dummy_instructions = Vec::new();
-    let mut rb = RuntimeBuilder::new(&dummy_instructions, "playground")?;
-    rb.apply_global_cli_args(global_args)?;
+    // the playground starts with an empty program
+    let mut rb = ProgramBuilder::new().runtime_builder()?;
+    rb.apply_global_cli_args(global_args)?
+        .apply_memory_args(&playground_args.memory_args)?
+        .apply_instruction_limiting_args(&playground_args.instruction_limiting_args)?;
+    // the instructions that are entered are checked against the allowed instructions, like custom instructions
+    let instruction_config = rb.instruction_config().clone();
     let rt = rb.build()?;
 
+    let config_file = ConfigFile::load(global_args)?;
+    let autosave = Autosave::new(
+        &config_file.autosave,
+        "Playground",
+        rt.settings().clock.clone(),
+    );
+    let statistics = Statistics::new(
+        &config_file.statistics,
+        "Playground",
+        rt.settings().clock.clone(),
+    );
+
+    let memory_visibilities =
+        super::load_memory_visibilities(global_args, &playground_args.load_playground_args)?;
+
     // setup terminal
-    println!("Ready to run, launching tui");
+    if print_progress {
+        println!("Ready to run, launching tui");
+    }
+    crash_report::install_hook("Playground");
     let mut terminal = super::setup_terminal()?;
 
     let mut app = App::from_runtime(
@@ -32,14 +62,22 @@ pub fn playground(global_args: &GlobalArgs, playground_args: &PlaygroundArgs) ->
         &Vec::new(),
         &None,
         instruction_history,
-        None,
+        Some(instruction_config),
         playground_args.custom_instruction_history_file.clone(),
         true,
         !playground_args
             .load_playground_args
             .disable_syntax_highlighting,
+        playground_args.load_playground_args.comparison_notation,
         Rc::new(super::load_theme(&playground_args.load_playground_args)?),
-    );
+    )
+    .with_identifier_notation(playground_args.load_playground_args.identifier_notation)
+    .with_value_format(ValueFormat::from(&config_file.value_format))
+    .with_stack_direction(playground_args.load_playground_args.show_stack_direction)
+    .with_debug_keys(playground_args.load_playground_args.debug_keys)
+    .with_memory_visibilities(memory_visibilities)
+    .with_autosave(autosave)
+    .with_statistics(statistics);
     let res = app.run(&mut terminal);
 
     // restore terminal
@@ -0,0 +1,66 @@
+use miette::Result;
+
+use crate::{
+    cli::{RefactorArgs, RefactorCommand},
+    instructions::refactor::{normalize_notation, ExtractSubroutine, Rename, RenameKind},
+    runtime::builder::RuntimeBuilder,
+    utils::write_file,
+};
+
+/// Applies the refactoring to the program and writes the result back to the program file.
+pub fn refactor(
+    refactor_args: &RefactorArgs,
+    instructions: Vec<String>,
+    input: &str,
+) -> Result<()> {
+    // all lines have to be parsed to locate the occurrences
+    RuntimeBuilder::new(&instructions, input)?;
+    match &refactor_args.command {
+        RefactorCommand::Rename {
+            old,
+            new,
+            memory_cell,
+            label,
+            ..
+        } => {
+            let kind = match (memory_cell, label) {
+                (true, _) => Some(RenameKind::MemoryCell),
+                (_, true) => Some(RenameKind::Label),
+                _ => None,
+            };
+            let rename = Rename::new(&instructions, old, new, kind)?;
+            let renamed = rename.apply(&instructions)?;
+            let changed_lines = instructions
+                .iter()
+                .zip(&renamed)
+                .filter(|(line, renamed)| line != renamed)
+                .count();
+            write_file(&renamed, input)?;
+            println!(
+                "Renamed {} '{}' to '{}' in {changed_lines} lines",
+                rename.kind, rename.old, rename.new
+            );
+        }
+        RefactorCommand::Extract {
+            start, end, label, ..
+        } => {
+            let extracted =
+                ExtractSubroutine::new(&instructions, *start, *end, label)?.apply(&instructions)?;
+            // make sure that the refactored program still compiles
+            RuntimeBuilder::new(&extracted, input)?;
+            write_file(&extracted, input)?;
+            println!("Extracted lines {start}-{end} into subroutine '{label}'");
+        }
+        RefactorCommand::Notation { to, .. } => {
+            let normalized = normalize_notation(&instructions, *to);
+            let changed_lines = instructions
+                .iter()
+                .zip(&normalized)
+                .filter(|(line, normalized)| line != normalized)
+                .count();
+            write_file(&normalized, input)?;
+            println!("Changed the notation in {changed_lines} lines");
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use std::env;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::runtime::{builder::RuntimeBuilder, memory_config::MemoryConfig, DEFAULT_RNG_SEED};
+
+use super::bundle::sha256_hex;
+
+/// Key of the object in the first line of a golden trace that contains the metadata of the run.
+const TRACE_HEADER_KEY: &str = "run_metadata";
+
+/// Information that is needed to reproduce a run, it is included in bundles, golden traces and grade reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// Version of alpha_tui that was used to run the program.
+    pub alpha_tui_version: String,
+    /// Arguments with which alpha_tui was started, without the name of the executable.
+    pub arguments: Vec<String>,
+    /// sha256 hash of the memory config the program was run with.
+    pub memory_config_hash: String,
+    /// sha256 hash of the allowed instructions, comparisons and operations.
+    pub instruction_config_hash: String,
+    /// Seed of the random number generator.
+    pub seed: u64,
+}
+
+impl RunMetadata {
+    /// Collects the metadata of the run of the runtime that is built by `rb`.
+    ///
+    /// The global args have to be applied to `rb` already, so that the memory config and the seed are set.
+    pub fn new(rb: &RuntimeBuilder) -> Self {
+        let memory_config = rb.memory_config().cloned().unwrap_or_default();
+        Self {
+            alpha_tui_version: env!("CARGO_PKG_VERSION").to_string(),
+            arguments: env::args().skip(1).collect(),
+            memory_config_hash: memory_config_hash(&memory_config),
+            instruction_config_hash: instruction_config_hash(rb),
+            seed: rb
+                .runtime_settings()
+                .map_or(DEFAULT_RNG_SEED, |settings| settings.rng.seed()),
+        }
+    }
+
+    /// Returns the metadata as first line of a golden trace, e.g. `{"run_metadata":{"seed":0,...}}`.
+    pub fn trace_header(&self) -> String {
+        json!({ TRACE_HEADER_KEY: self }).to_string()
+    }
+
+    /// Reads the metadata from the first line of a golden trace, returns `None` if the line is no metadata header.
+    ///
+    /// Golden traces that were recorded with older versions of alpha_tui don't contain a header.
+    pub fn from_trace_header(line: &str) -> Option<Self> {
+        let mut value = serde_json::from_str::<serde_json::Value>(line).ok()?;
+        serde_json::from_value(value.get_mut(TRACE_HEADER_KEY)?.take()).ok()
+    }
+
+    /// Returns the names of the settings in which `other` differs from this metadata, that change the result of a run.
+    pub fn differences(&self, other: &Self) -> Vec<&'static str> {
+        let mut differences = Vec::new();
+        if self.memory_config_hash != other.memory_config_hash {
+            differences.push("memory config");
+        }
+        if self.instruction_config_hash != other.instruction_config_hash {
+            differences.push("instruction config");
+        }
+        if self.seed != other.seed {
+            differences.push("seed");
+        }
+        differences
+    }
+}
+
+impl std::fmt::Display for RunMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "alpha_tui {}", self.alpha_tui_version)?;
+        writeln!(f, "Arguments:          {}", self.arguments.join(" "))?;
+        writeln!(f, "Memory config:      {}", self.memory_config_hash)?;
+        writeln!(f, "Instruction config: {}", self.instruction_config_hash)?;
+        write!(f, "Seed:               {}", self.seed)
+    }
+}
+
+/// Returns the sha256 hash of `memory_config`, the keys are sorted so that the same config always has the same hash.
+fn memory_config_hash(memory_config: &MemoryConfig) -> String {
+    // converting to `serde_json::Value` first sorts the keys of maps
+    let value = serde_json::to_value(memory_config).expect("memory config should be serializable");
+    sha256_hex(value.to_string().as_bytes())
+}
+
+/// Returns the sha256 hash of the instruction config of `rb`, the allowed values are sorted so that the order in
+/// which they are listed does not change the hash.
+fn instruction_config_hash(rb: &RuntimeBuilder) -> String {
+    let config = rb.instruction_config();
+    let sorted = |values: Vec<serde_json::Value>| {
+        let mut values = values.iter().map(ToString::to_string).collect::<Vec<_>>();
+        values.sort();
+        values
+    };
+    let value = json!({
+        "instructions": config.allowed_instruction_identifiers.as_ref().map(|ids| {
+            sorted(ids.iter().map(|id| json!(id)).collect())
+        }),
+        "comparisons": config.allowed_comparisons.as_ref().map(|cmps| {
+            sorted(cmps.iter().map(|cmp| json!(cmp)).collect())
+        }),
+        "operations": config.allowed_operations.as_ref().map(|ops| {
+            sorted(ops.iter().map(|op| json!(op)).collect())
+        }),
+        "deny_structured_loops": config.deny_structured_loops,
+        "deny_labels_on_empty_lines": config.deny_labels_on_empty_lines,
+    });
+    sha256_hex(value.to_string().as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cli::{CheckLoadArgs, GlobalArgs, InstructionLimitingArgs, MemoryArgs},
+        runtime::builder::RuntimeBuilder,
+        utils::test_utils,
+    };
+
+    use super::RunMetadata;
+
+    fn metadata(seed: Option<u64>, check_load_args: &CheckLoadArgs) -> RunMetadata {
+        let mut rb =
+            RuntimeBuilder::new(&test_utils::string_literal_to_vec("a := 1"), "test").unwrap();
+        rb.apply_global_cli_args(&GlobalArgs {
+            seed,
+            ..GlobalArgs::default()
+        })
+        .unwrap();
+        rb.apply_check_load_args(check_load_args).unwrap();
+        rb.apply_instruction_limiting_args(&check_load_args.instruction_limiting_args)
+            .unwrap();
+        RunMetadata::new(&rb)
+    }
+
+    #[test]
+    fn test_run_metadata() {
+        let default = metadata(None, &CheckLoadArgs::default());
+        assert_eq!(default.alpha_tui_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(default.seed, 0);
+        assert_eq!(default, metadata(None, &CheckLoadArgs::default()));
+        let other = metadata(
+            Some(5),
+            &CheckLoadArgs {
+                memory_args: MemoryArgs {
+                    accumulators: Some(3),
+                    ..Default::default()
+                },
+                instruction_limiting_args: InstructionLimitingArgs {
+                    allowed_instructions: Some(vec!["A := C".to_string()]),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            default.differences(&other),
+            vec!["memory config", "instruction config", "seed"]
+        );
+    }
+
+    #[test]
+    fn test_trace_header() {
+        let metadata = metadata(None, &CheckLoadArgs::default());
+        assert_eq!(
+            RunMetadata::from_trace_header(&metadata.trace_header()),
+            Some(metadata)
+        );
+        assert_eq!(
+            RunMetadata::from_trace_header(r#"{"line":1,"instruction":"a0 := 1"}"#),
+            None
+        );
+        assert_eq!(RunMetadata::from_trace_header("not json"), None);
+    }
+}
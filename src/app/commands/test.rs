@@ -0,0 +1,316 @@
+use std::{fs, path::Path};
+
+use miette::{miette, IntoDiagnostic, Result};
+
+use crate::{
+    cli::{GlobalArgs, TestArgs, Verbosity},
+    exit_code::ExitCode,
+    instructions::error_handling::BuildProgramErrorTypes,
+    runtime::{
+        builder::RuntimeBuilder, error_handling::RuntimeErrorType, observer::ExecutionEvent,
+    },
+};
+
+use super::{progress_bar, run_metadata::RunMetadata};
+
+/// Name of the test case that is used when no test cases are specified.
+const DEFAULT_CASE_NAME: &str = "default";
+
+/// A test case, the program is run with the memory config of the test case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestCase {
+    pub name: String,
+    /// Memory config file that contains the input values of this test case.
+    pub memory_config_file: Option<String>,
+}
+
+impl TestCase {
+    /// Returns the test cases that are set in `test_args`.
+    ///
+    /// If no test cases are set, a single test case is returned that uses the memory config file set in `global_args`.
+    pub fn from_args(global_args: &GlobalArgs, test_args: &TestArgs) -> Vec<Self> {
+        match &test_args.cases {
+            Some(cases) => cases
+                .iter()
+                .map(|path| Self {
+                    name: Path::new(path)
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or(path)
+                        .to_string(),
+                    memory_config_file: Some(path.clone()),
+                })
+                .collect(),
+            None => vec![Self {
+                name: DEFAULT_CASE_NAME.to_string(),
+                memory_config_file: global_args.memory_config_file.clone(),
+            }],
+        }
+    }
+
+    /// Runs the program with the memory config of this test case and returns the metadata of the run and the
+    /// execution trace.
+    ///
+    /// The trace contains one JSON formatted `ExecutionEvent` per executed instruction.
+    /// Returns the exit code for the kind of error and an error message if the program did not compile or a runtime error occurred.
+    pub fn trace(
+        &self,
+        global_args: &GlobalArgs,
+        instructions: &[String],
+        input: &str,
+    ) -> Result<(RunMetadata, Vec<String>), (ExitCode, String)> {
+        let global_args = GlobalArgs {
+            memory_config_file: self.memory_config_file.clone(),
+            ..global_args.clone()
+        };
+        let mut rb = RuntimeBuilder::new(instructions, input).map_err(|e| {
+            let exit_code = match e.reason {
+                BuildProgramErrorTypes::ParseError { .. } => ExitCode::ParseError,
+                _ => ExitCode::BuildError,
+            };
+            (
+                exit_code,
+                format!("program did not compile:\n{:?}", miette!(e)),
+            )
+        })?;
+        rb.apply_global_cli_args(&global_args).map_err(|e| {
+            (
+                ExitCode::IoError,
+                format!("memory config could not be loaded:\n{:?}", miette!(e)),
+            )
+        })?;
+        let metadata = RunMetadata::new(&rb);
+        let mut rt = rb.build().map_err(|e| {
+            (
+                ExitCode::BuildError,
+                format!("program did not compile:\n{e:?}"),
+            )
+        })?;
+        let mut trace = Vec::new();
+        rt.run_observed(&mut |event: &ExecutionEvent| {
+            trace.push(serde_json::to_string(event).expect("event should be serializable"));
+        })
+        .map_err(|e| {
+            let exit_code = match e.reason {
+                RuntimeErrorType::DesignLimitReached(_) => ExitCode::InstructionLimitReached,
+                _ => ExitCode::RuntimeError,
+            };
+            (
+                exit_code,
+                format!("runtime error while running program:\n{:?}", miette!(e)),
+            )
+        })?;
+        Ok((metadata, trace))
+    }
+}
+
+/// Difference between a golden trace and a new trace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceDifference {
+    /// The event in step `.0` is different.
+    Event(usize, String, String),
+    /// The new trace has a different number of steps than the golden trace.
+    Length(usize, usize),
+}
+
+impl std::fmt::Display for TraceDifference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Event(step, expected, actual) => write!(
+                f,
+                "trace differs in step {step}\n  expected: {expected}\n  actual:   {actual}"
+            ),
+            Self::Length(expected, actual) => write!(
+                f,
+                "trace has {actual} steps, golden trace has {expected} steps"
+            ),
+        }
+    }
+}
+
+/// Compares a new trace with the golden trace and returns the first difference.
+pub fn compare_traces(golden: &[String], actual: &[String]) -> Option<TraceDifference> {
+    if let Some((step, (expected, actual))) = golden
+        .iter()
+        .zip(actual)
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+    {
+        return Some(TraceDifference::Event(
+            step + 1,
+            expected.clone(),
+            actual.clone(),
+        ));
+    }
+    if golden.len() != actual.len() {
+        return Some(TraceDifference::Length(golden.len(), actual.len()));
+    }
+    None
+}
+
+/// Records golden traces for all test cases or compares new traces against the golden traces.
+///
+/// Exits with `ExitCode::TestFailure` if a trace differs. If a test case failed because the program could not be run, the exit code of the first such error is used instead.
+pub fn test(
+    global_args: &GlobalArgs,
+    test_args: &TestArgs,
+    instructions: Vec<String>,
+    input: &str,
+) -> Result<()> {
+    let traces_dir = test_args
+        .traces_dir
+        .clone()
+        .unwrap_or_else(|| format!("{input}.traces"));
+    let verbosity = global_args.verbosity();
+    if test_args.record {
+        fs::create_dir_all(&traces_dir)
+            .into_diagnostic()
+            .map_err(|e| miette!("Unable to create directory [{}]: {}", traces_dir, e))?;
+        if verbosity >= Verbosity::Normal {
+            println!("Recording golden traces into {traces_dir}");
+        }
+    }
+    let cases = TestCase::from_args(global_args, test_args);
+    let mut failed = 0;
+    let mut exit_code = ExitCode::TestFailure;
+    let progress = progress_bar(Some(cases.len() as u64), "test cases", verbosity);
+    for case in progress.wrap_iter(cases.iter()) {
+        let path = Path::new(&traces_dir).join(format!("{}.trace", case.name));
+        if verbosity == Verbosity::Verbose {
+            progress.suspend(|| {
+                println!(
+                    "{}: memory config {}, golden trace {}",
+                    case.name,
+                    case.memory_config_file.as_deref().unwrap_or("-"),
+                    path.display()
+                );
+            });
+        }
+        let (metadata, trace) = match case.trace(global_args, &instructions, input) {
+            Ok(trace) => trace,
+            Err((code, e)) => {
+                progress.suspend(|| println!("{}: {e}", case.name));
+                if exit_code == ExitCode::TestFailure {
+                    exit_code = code;
+                }
+                failed += 1;
+                continue;
+            }
+        };
+        if test_args.record {
+            let mut content = metadata.trace_header();
+            for event in &trace {
+                content.push('\n');
+                content.push_str(event);
+            }
+            content.push('\n');
+            fs::write(&path, content)
+                .into_diagnostic()
+                .map_err(|e| miette!("Unable to write file [{}]: {}", path.display(), e))?;
+            if verbosity >= Verbosity::Normal {
+                progress.suspend(|| println!("{}: recorded {} steps", case.name, trace.len()));
+            }
+            continue;
+        }
+        let mut golden = match fs::read_to_string(&path) {
+            Ok(golden) => golden.lines().map(ToString::to_string).collect::<Vec<_>>(),
+            Err(e) => {
+                progress.finish_and_clear();
+                ExitCode::IoError.exit_with_error(&miette!(
+                    "Unable to read golden trace [{}]: {}\nRecord the golden traces with \"--record\" first.",
+                    path.display(),
+                    e
+                ));
+            }
+        };
+        let golden_metadata = golden
+            .first()
+            .and_then(|line| RunMetadata::from_trace_header(line));
+        if golden_metadata.is_some() {
+            golden.remove(0);
+        }
+        match compare_traces(&golden, &trace) {
+            Some(difference) => {
+                progress.suspend(|| println!("{}: {difference}", case.name));
+                let differences = golden_metadata
+                    .map(|golden| golden.differences(&metadata))
+                    .unwrap_or_default();
+                if !differences.is_empty() {
+                    progress.suspend(|| {
+                        println!(
+                            "  note: the golden trace was recorded with a different {}",
+                            differences.join(", ")
+                        );
+                    });
+                }
+                failed += 1;
+            }
+            None if verbosity >= Verbosity::Normal => {
+                progress.suspend(|| {
+                    println!("{}: trace matches ({} steps)", case.name, trace.len());
+                });
+            }
+            None => (),
+        }
+    }
+    progress.finish_and_clear();
+    if failed > 0 {
+        println!("{failed} of {} test cases failed", cases.len());
+        exit_code.exit();
+    }
+    if !test_args.record {
+        println!("All traces match");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cli::GlobalArgs, exit_code::ExitCode, utils::test_utils};
+
+    use super::{compare_traces, TestCase, TraceDifference};
+
+    fn trace(program: &str) -> Vec<String> {
+        TestCase {
+            name: "test".to_string(),
+            memory_config_file: None,
+        }
+        .trace(
+            &GlobalArgs::default(),
+            &test_utils::string_literal_to_vec(program),
+            "test",
+        )
+        .unwrap()
+        .1
+    }
+
+    #[test]
+    fn test_compare_traces() {
+        let golden = trace("a := 2\na := a * 2\np(h1) := a");
+        assert_eq!(compare_traces(&golden, &golden), None);
+        // same result in memory, but calculated differently
+        let other = trace("a := 2\na := a + 2\np(h1) := a");
+        assert!(matches!(
+            compare_traces(&golden, &other),
+            Some(TraceDifference::Event(2, _, _))
+        ));
+        assert_eq!(
+            compare_traces(&golden, &golden[..2]),
+            Some(TraceDifference::Length(3, 2))
+        );
+    }
+
+    #[test]
+    fn test_trace_runtime_error() {
+        let res = TestCase {
+            name: "test".to_string(),
+            memory_config_file: None,
+        }
+        .trace(
+            &GlobalArgs::default(),
+            &test_utils::string_literal_to_vec("pop"),
+            "test",
+        );
+        assert!(matches!(res, Err((ExitCode::RuntimeError, _))));
+    }
+}
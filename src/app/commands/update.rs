@@ -0,0 +1,123 @@
+use miette::{miette, Result};
+use serde::Deserialize;
+
+use crate::{cli::UpdateArgs, utils};
+
+/// Url of the GitHub api that returns the latest release of alpha_tui.
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/LMH01/alpha_tui/releases/latest";
+
+/// Page on which all releases of alpha_tui are listed.
+const RELEASES_PAGE: &str = "https://github.com/LMH01/alpha_tui/releases";
+
+/// The fields of a release of the GitHub api that are needed to check for updates.
+#[derive(Debug, Deserialize, PartialEq)]
+struct Release {
+    /// Tag of the release, e.g. `v1.8.0`.
+    tag_name: String,
+    /// Page of the release, from which the binaries can be downloaded.
+    html_url: String,
+}
+
+pub fn update(update_args: &UpdateArgs) -> Result<()> {
+    if !update_args.check {
+        return Err(miette!(
+            help = "run `alpha_tui update --check` to check whether a newer version exists",
+            "alpha_tui can not update itself, download the newest version from {RELEASES_PAGE} or run `cargo install alpha_tui`"
+        ));
+    }
+    let installed = env!("CARGO_PKG_VERSION");
+    let release = match latest_release() {
+        Ok(release) => release,
+        Err(e) => {
+            println!("Unable to check for updates: {e}");
+            println!("Installed version: {installed}");
+            println!("The newest version can be found at {RELEASES_PAGE}");
+            return Ok(());
+        }
+    };
+    let latest = release.tag_name.trim_start_matches('v');
+    if is_newer(latest, installed) {
+        println!("A newer version of alpha_tui is available: {installed} -> {latest}");
+        println!(
+            "Download it from {} or run `cargo install alpha_tui`",
+            release.html_url
+        );
+    } else {
+        println!("alpha_tui {installed} is up to date");
+    }
+    Ok(())
+}
+
+/// Queries the GitHub api for the latest release, fails if network access is disabled.
+fn latest_release() -> Result<Release> {
+    let response = utils::read_file(LATEST_RELEASE_URL)?.join("\n");
+    parse_release(&response)
+}
+
+/// Parses the response of the GitHub api.
+fn parse_release(response: &str) -> Result<Release> {
+    serde_json::from_str(response).map_err(|e| miette!("invalid response of the GitHub api: {e}"))
+}
+
+/// Returns true if `latest` is a higher version than `installed`.
+///
+/// Versions are compared by their numeric components, a pre-release suffix like `-beta` is ignored. Returns false if
+/// a version can not be parsed, so that no update is reported because of an unexpected tag.
+fn is_newer(latest: &str, installed: &str) -> bool {
+    match (parse_version(latest), parse_version(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => false,
+    }
+}
+
+/// Parses a version like `1.8.0` into its components, missing components are 0.
+fn parse_version(version: &str) -> Option<[u64; 3]> {
+    let version = version.split(['-', '+']).next()?;
+    let mut components = [0; 3];
+    for (idx, component) in version.split('.').enumerate() {
+        *components.get_mut(idx)? = component.parse().ok()?;
+    }
+    Some(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_newer, parse_release, parse_version, Release};
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.8.0"), Some([1, 8, 0]));
+        assert_eq!(parse_version("2.0"), Some([2, 0, 0]));
+        assert_eq!(parse_version("1.9.0-beta.1"), Some([1, 9, 0]));
+        assert_eq!(parse_version("1.2.3.4"), None);
+        assert_eq!(parse_version("latest"), None);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("1.9.0", "1.8.0"));
+        assert!(is_newer("1.10.0", "1.9.5"));
+        assert!(is_newer("2.0.0", "1.8.0"));
+        assert!(!is_newer("1.8.0", "1.8.0"));
+        assert!(!is_newer("1.7.2", "1.8.0"));
+        assert!(!is_newer("nightly", "1.8.0"));
+    }
+
+    #[test]
+    fn test_parse_release() {
+        let response = r#"{
+            "url": "https://api.github.com/repos/LMH01/alpha_tui/releases/1",
+            "tag_name": "v1.9.0",
+            "html_url": "https://github.com/LMH01/alpha_tui/releases/tag/v1.9.0",
+            "prerelease": false
+        }"#;
+        assert_eq!(
+            parse_release(response).unwrap(),
+            Release {
+                tag_name: "v1.9.0".to_string(),
+                html_url: "https://github.com/LMH01/alpha_tui/releases/tag/v1.9.0".to_string(),
+            }
+        );
+        assert!(parse_release(r#"{"message": "Not Found"}"#).is_err());
+    }
+}
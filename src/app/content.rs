@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 use ratatui::{
     style::Style,
@@ -6,20 +6,45 @@ use ratatui::{
     widgets::{ListItem, ListState},
 };
 
-use crate::runtime::{Runtime, RuntimeMemory};
+use crate::{
+    base::{Accumulator, IdentifierNotation, MemoryCell},
+    instructions::{variables::Variables, Instruction},
+    runtime::{
+        memory_config::{MemoryVisibilities, MemoryVisibility},
+        memory_usage::MemoryLocation,
+        ControlFlow, Runtime, RuntimeMemory,
+    },
+};
 
-use super::ui::style::SharedTheme;
+use super::{
+    breakpoints::{group_marker, BreakpointGroup},
+    folds::Fold,
+    ui::style::SharedTheme,
+    value_format::ValueFormat,
+};
 
 /// Used to store the instructions and to remember what instruction should currently be highlighted.
 #[derive(Debug, Clone)]
 pub struct InstructionListStates {
     instruction_list_state: ListState,
-    breakpoint_list_state: ListState,
     instructions: Vec<(usize, Line<'static>, bool)>, // index, line content, is a breakpoint present
     last_index: i32,
     current_index: i32,
+    /// Named groups of breakpoints that can be enabled and disabled together.
+    breakpoint_groups: Vec<BreakpointGroup>,
+    /// If true, all breakpoints are disabled temporarily, they are kept to enable them again later.
+    breakpoints_disabled: bool,
+    /// Index of the first line that is visible in the code area.
+    offset: usize,
+    /// Number of lines that fit into the code area, set when the list is drawn.
+    visible_height: usize,
+    /// Subroutines that can be folded, sorted by their first line.
+    folds: Vec<Fold>,
 }
 
+/// Number of lines that are kept visible above and below the selected line.
+const SCROLL_PADDING: usize = 2;
+
 #[allow(clippy::cast_sign_loss)]
 impl InstructionListStates {
     /// Creates new `InstructionListStates` which hold the current state of the instruction list.
@@ -38,29 +63,64 @@ impl InstructionListStates {
         }
         InstructionListStates {
             instruction_list_state: ListState::default(),
-            breakpoint_list_state: ListState::default(),
             instructions: i,
             last_index: -1,
             current_index: -1,
+            breakpoint_groups: Vec::new(),
+            breakpoints_disabled: false,
+            offset: 0,
+            visible_height: 0,
+            folds: Vec::new(),
         }
     }
 
-    /// Returns the instruction states as a vector of list items to be printed in the ui.
+    /// Sets the named groups of breakpoints.
+    pub fn with_breakpoint_groups(mut self, breakpoint_groups: Vec<BreakpointGroup>) -> Self {
+        self.breakpoint_groups = breakpoint_groups;
+        self
+    }
+
+    /// Sets the subroutines that can be folded.
+    pub fn with_folds(mut self, folds: Vec<Fold>) -> Self {
+        self.folds = folds;
+        self
+    }
+
+    /// Returns the lines with index `lines` as a vector of list items to be printed in the ui.
+    ///
+    /// Lines in the `marked_range` are displayed with a bar behind the line number. Behind the first line of a folded
+    /// subroutine the number of hidden lines is displayed and the line that is run next, if it is hidden.
+    /// If `gutter` is set, it contains the text that is displayed between the line number and the line, for each line.
     pub fn as_list_items(
         &self,
+        lines: &[usize],
+        gutter: Option<&[String]>,
         is_playground: bool,
+        marked_range: Option<(usize, usize)>,
         theme: &SharedTheme,
     ) -> Vec<ListItem<'static>> {
-        let mut items: Vec<ListItem<'static>> = self
-            .instructions()
+        let mut items: Vec<ListItem<'static>> = lines
             .iter()
-            .map(|i| {
+            .enumerate()
+            .map(|(row, idx)| (row, &self.instructions[*idx]))
+            .map(|(row, i)| {
                 let content = if is_playground {
                     i.1.clone()
                 } else {
-                    let mut content =
-                        vec![Span::from(format!("{:2}: ", i.0 + 1)).style(theme.line_numbers())];
+                    let separator = match marked_range {
+                        Some((start, end)) if (start..=end).contains(&i.0) => '│',
+                        _ => ':',
+                    };
+                    let mut content = vec![Span::from(format!("{:2}{separator} ", i.0 + 1))
+                        .style(theme.line_numbers())];
+                    if let Some(gutter) = gutter.and_then(|gutter| gutter.get(row)) {
+                        content.push(Span::from(format!("{gutter} ")).style(theme.line_numbers()));
+                    }
                     content.append(&mut i.1.clone().spans);
+                    if let Some(fold) = self.folds.iter().find(|f| f.folded && f.start == i.0) {
+                        content
+                            .push(Span::from(self.fold_summary(fold)).style(theme.line_numbers()));
+                    }
                     Line::from(content)
                 };
                 ListItem::new(content).style(Style::default())
@@ -72,6 +132,67 @@ impl InstructionListStates {
         items
     }
 
+    /// Returns the text that is displayed behind the first line of the folded subroutine.
+    fn fold_summary(&self, fold: &Fold) -> String {
+        let hidden = fold.end - fold.start;
+        let lines = if hidden == 1 { "line" } else { "lines" };
+        match self.selected_line().filter(|idx| fold.hides(*idx)) {
+            // the execution has entered the folded subroutine
+            Some(idx) => format!("  ⋯ {hidden} {lines} folded, at line {}", idx + 1),
+            None => format!("  ⋯ {hidden} {lines} folded"),
+        }
+    }
+
+    /// Returns the indices of the lines that are not hidden by a folded subroutine.
+    fn displayed_lines(&self) -> Vec<usize> {
+        (0..self.instructions.len())
+            .filter(|idx| !self.folds.iter().any(|fold| fold.hides(*idx)))
+            .collect()
+    }
+
+    /// Returns the line that is displayed for the line with index `idx`.
+    ///
+    /// Lines that are hidden by a folded subroutine are displayed by the first line of the subroutine.
+    pub fn displayed_line(&self, idx: usize) -> usize {
+        self.folds
+            .iter()
+            .find(|fold| fold.hides(idx))
+            .map_or(idx, |fold| fold.start)
+    }
+
+    /// Returns the lines that are visible in a code area with `height` lines.
+    ///
+    /// The lines are scrolled as little as possible to keep the selected line and the lines around it visible,
+    /// so that only the list items of these lines have to be created when the list is drawn.
+    pub fn visible_lines(&mut self, height: usize) -> Vec<usize> {
+        self.visible_height = height;
+        if height == 0 {
+            return Vec::new();
+        }
+        let lines = self.displayed_lines();
+        let mut offset = lines.partition_point(|idx| *idx < self.offset);
+        if let Some(selected) = self.selected_line() {
+            let selected = lines.partition_point(|idx| *idx < self.displayed_line(selected));
+            let padding = SCROLL_PADDING.min((height - 1) / 2);
+            if selected < offset + padding {
+                offset = selected.saturating_sub(padding);
+            } else if selected + padding >= offset + height {
+                offset = selected + padding + 1 - height;
+            }
+        }
+        offset = offset.min(lines.len().saturating_sub(height));
+        self.offset = lines.get(offset).copied().unwrap_or(0);
+        lines[offset..(offset + height).min(lines.len())].to_vec()
+    }
+
+    /// Returns a list state for `lines`, in which the selected line is selected by its position in `lines`.
+    pub fn visible_list_state(&self, lines: &[usize]) -> ListState {
+        ListState::default().with_selected(self.selected_line().and_then(|selected| {
+            let selected = self.displayed_line(selected);
+            lines.iter().position(|idx| *idx == selected)
+        }))
+    }
+
     /// Selects the line in which the program starts
     pub fn set_start(&mut self, current_instruction_index: i32) {
         self.set(current_instruction_index);
@@ -84,41 +205,119 @@ impl InstructionListStates {
         if current_instruction_idx - self.last_index == 1 {
             self.instruction_list_state
                 .select(Some(current_instruction_idx as usize));
-            self.breakpoint_list_state
-                .select(Some(current_instruction_idx as usize));
         } else {
             // line jump detected, only increase state by one
             self.instruction_list_state
                 .select(Some((self.last_index + 1) as usize));
-            self.breakpoint_list_state
-                .select(Some((self.last_index + 1) as usize));
         }
         self.last_index = current_instruction_idx - 1;
     }
 
+    /// Highlights the instruction with index `idx` as the instruction that is run next, without detecting a line jump.
+    ///
+    /// Used when the execution is continued at a line, e.g. when a saved session is restored.
+    pub fn set_current(&mut self, idx: usize) {
+        self.last_index = idx as i32 - 1;
+        self.set(idx as i32);
+    }
+
+    /// Sets a breakpoint in each of the lines, starting at 1, lines that don't exist are ignored.
+    pub fn set_breakpoints(&mut self, lines: &[usize]) {
+        for line in lines {
+            if let Some(instruction) = line
+                .checked_sub(1)
+                .and_then(|idx| self.instructions.get_mut(idx))
+            {
+                instruction.2 = true;
+            }
+        }
+    }
+
     /// Used to force the highlight of a specific line.
     ///
     /// Should only be used for visuals if the line is known that should be highlighted.
     pub fn force_set(&mut self, highlight_index: usize) {
         self.instruction_list_state.select(Some(highlight_index));
-        self.breakpoint_list_state.select(Some(highlight_index));
     }
 
     pub fn deselect(&mut self) {
         self.instruction_list_state.select(None);
-        self.breakpoint_list_state.select(None);
     }
 
-    /// Updates instruction list and breakpoint list to select the next value
+    /// Updates the instruction list to select the next displayed line, the first line follows the last line.
     pub fn set_next_visual(&mut self) {
-        list_next(&mut self.instruction_list_state, self.instructions.len());
-        list_next(&mut self.breakpoint_list_state, self.instructions.len());
+        self.move_selection(|position, len| (position + 1) % len, 0);
     }
 
-    /// Updates the instructions list and breakpoint list to select the previous value
+    /// Updates the instruction list to select the previous displayed line, the last line precedes the first line.
     pub fn set_prev_visual(&mut self) {
-        list_prev(&mut self.instruction_list_state, self.instructions.len());
-        list_prev(&mut self.breakpoint_list_state, self.instructions.len());
+        self.move_selection(|position, len| (position + len - 1) % len, 0);
+    }
+
+    /// Selects the line one page below the selected line, or the last line if there is no such line.
+    pub fn set_next_page_visual(&mut self) {
+        let page = self.visible_height.saturating_sub(1).max(1);
+        self.move_selection(|position, len| (position + page).min(len - 1), 0);
+    }
+
+    /// Selects the line one page above the selected line, or the first line if there is no such line.
+    pub fn set_prev_page_visual(&mut self) {
+        let page = self.visible_height.saturating_sub(1).max(1);
+        self.move_selection(|position, _| position.saturating_sub(page), 0);
+    }
+
+    /// Selects the first line.
+    pub fn set_first_visual(&mut self) {
+        self.move_selection(|_, _| 0, 0);
+    }
+
+    /// Selects the last line.
+    pub fn set_last_visual(&mut self) {
+        self.move_selection(|_, len| len - 1, usize::MAX);
+    }
+
+    /// Selects a displayed line, `select` returns the position of the line that is selected from the position of the
+    /// currently selected line and the number of displayed lines.
+    ///
+    /// If no line is selected, the line at position `default` is selected, or the last line if it does not exist.
+    fn move_selection(&mut self, select: impl Fn(usize, usize) -> usize, default: usize) {
+        let lines = self.displayed_lines();
+        if lines.is_empty() {
+            return;
+        }
+        let position = match self.selected_line() {
+            Some(selected) => {
+                let selected = self.displayed_line(selected);
+                select(lines.partition_point(|idx| *idx < selected), lines.len())
+            }
+            None => default,
+        };
+        self.force_set(lines[position.min(lines.len() - 1)]);
+    }
+
+    /// Folds the subroutine that contains the selected line or unfolds it, if it is folded.
+    ///
+    /// When the subroutine is folded, its first line is selected.
+    pub fn toggle_fold(&mut self) {
+        let Some(selected) = self.selected_line().map(|idx| self.displayed_line(idx)) else {
+            return;
+        };
+        if let Some(fold) = self
+            .folds
+            .iter_mut()
+            .find(|fold| fold.start <= selected && selected <= fold.end)
+        {
+            fold.folded = !fold.folded;
+            if fold.folded {
+                let start = fold.start;
+                self.force_set(start);
+            }
+        }
+    }
+
+    /// Returns true if the program contains subroutines that can be folded.
+    pub fn has_folds(&self) -> bool {
+        !self.folds.is_empty()
     }
 
     pub fn set_instruction_list_state(&mut self, index: Option<usize>) {
@@ -131,26 +330,91 @@ impl InstructionListStates {
         self.instructions[self.instruction_list_state.selected().unwrap()].2 = !val;
     }
 
-    /// Checks if the current line contains a breakpoint
+    /// Checks if the current line contains a breakpoint that is enabled
     pub fn is_breakpoint(&self) -> bool {
         if let Some(idx) = self.instruction_list_state.selected() {
-            match self.instructions.get(idx) {
-                Some(i) => return i.2,
-                None => return false,
-            };
+            return self.is_enabled_breakpoint(idx);
         }
         false
         // self.instructions[self.instruction_list_state.selected().unwrap()].2
     }
 
-    /// Returns true if at least one breakpoint is set.
+    /// Returns true if at least one breakpoint is set and enabled.
     pub fn breakpoints_set(&self) -> bool {
-        for state in &self.instructions {
-            if state.2 {
-                return true;
-            }
+        (0..self.instructions.len()).any(|idx| self.is_enabled_breakpoint(idx))
+    }
+
+    /// Returns the lines in which the user has set a breakpoint, starting at 1.
+    ///
+    /// Breakpoints of breakpoint groups are not included.
+    pub fn breakpoints(&self) -> Vec<usize> {
+        self.instructions
+            .iter()
+            .filter(|i| i.2)
+            .map(|i| i.0 + 1)
+            .collect()
+    }
+
+    /// Returns the number of lines that contain an enabled breakpoint.
+    pub fn breakpoint_count(&self) -> usize {
+        (0..self.instructions.len())
+            .filter(|idx| self.is_enabled_breakpoint(*idx))
+            .count()
+    }
+
+    /// Checks if the line with index `idx` contains a breakpoint that is enabled, either set by the user or by an
+    /// enabled breakpoint group.
+    fn is_enabled_breakpoint(&self, idx: usize) -> bool {
+        if self.breakpoints_disabled {
+            return false;
         }
-        false
+        self.instructions.get(idx).is_some_and(|i| i.2)
+            || self
+                .breakpoint_groups
+                .iter()
+                .any(|group| group.enabled && group.contains(idx))
+    }
+
+    /// Returns the marker of the breakpoint in the line with index `idx` and if the breakpoint is enabled.
+    ///
+    /// Breakpoints set by the user are marked with `*`, breakpoints of groups with the marker of the group.
+    /// If multiple groups contain the line, an enabled group is preferred.
+    pub fn breakpoint_marker(&self, idx: usize) -> Option<(char, bool)> {
+        if self.instructions.get(idx).is_some_and(|i| i.2) {
+            return Some(('*', !self.breakpoints_disabled));
+        }
+        self.breakpoint_groups
+            .iter()
+            .enumerate()
+            .filter(|(_, group)| group.contains(idx))
+            .min_by_key(|(_, group)| !group.enabled)
+            .map(|(group_idx, group)| {
+                (
+                    group_marker(group_idx),
+                    group.enabled && !self.breakpoints_disabled,
+                )
+            })
+    }
+
+    /// Disables all breakpoints or enables them again, breakpoints are not removed.
+    pub fn toggle_all_breakpoints(&mut self) {
+        self.breakpoints_disabled = !self.breakpoints_disabled;
+    }
+
+    /// Returns true if all breakpoints are disabled temporarily.
+    pub fn breakpoints_disabled(&self) -> bool {
+        self.breakpoints_disabled
+    }
+
+    /// Enables or disables the breakpoint group with index `group_idx`, does nothing if the group does not exist.
+    pub fn toggle_breakpoint_group(&mut self, group_idx: usize) {
+        if let Some(group) = self.breakpoint_groups.get_mut(group_idx) {
+            group.enabled = !group.enabled;
+        }
+    }
+
+    pub fn breakpoint_groups(&self) -> &[BreakpointGroup] {
+        &self.breakpoint_groups
     }
 
     pub fn selected_line(&self) -> Option<usize> {
@@ -161,12 +425,15 @@ impl InstructionListStates {
         &self.instructions
     }
 
-    pub fn instruction_list_state_mut(&mut self) -> &mut ListState {
-        &mut self.instruction_list_state
+    /// Replaces the displayed content of the lines, breakpoints and the selected line are kept.
+    pub fn set_lines(&mut self, lines: &[Line<'static>]) {
+        for (instruction, line) in self.instructions.iter_mut().zip(lines) {
+            instruction.1 = line.clone();
+        }
     }
 
-    pub fn breakpoint_list_state_mut(&mut self) -> &mut ListState {
-        &mut self.breakpoint_list_state
+    pub fn instruction_list_state_mut(&mut self) -> &mut ListState {
+        &mut self.instruction_list_state
     }
 
     /// Adds a new instruction to the list (display only)
@@ -183,85 +450,160 @@ impl PartialEq for InstructionListStates {
     }
 }
 
-fn list_next(list_state: &mut ListState, instruction_length: usize) {
-    let i = match list_state.selected() {
-        Some(i) => {
-            if i >= instruction_length - 1 {
-                0
-            } else {
-                i + 1
-            }
-        }
-        None => 0,
-    };
-    list_state.select(Some(i));
-}
-
-fn list_prev(list_state: &mut ListState, max_index: usize) {
-    let i = match list_state.selected() {
-        Some(i) => {
-            if i == 0 {
-                max_index - 1
-            } else {
-                i - 1
-            }
-        }
-        None => 0,
-    };
-    list_state.select(Some(i));
+/// Returns the character that corresponds to the value, if the value is a printable ASCII character.
+fn ascii_char(value: i32) -> Option<char> {
+    u8::try_from(value)
+        .ok()
+        .filter(|v| (32..=126).contains(v))
+        .map(char::from)
 }
 
 /// Used to update and set the lists for accumulators, memory cells, stack and call stack.
 pub struct MemoryListsManager {
-    accumulators: HashMap<usize, (String, bool)>,
+    /// Values of the accumulators, the names are added when the list is created.
+    accumulators: BTreeMap<usize, (String, bool)>,
     gamma: Option<(Option<i32>, bool)>,
-    memory_cells: HashMap<String, (String, bool)>,
-    index_memory_cells: HashMap<usize, (String, bool)>,
-    stack: Vec<ListItem<'static>>,
+    /// Values of the memory cells, the names are added when the list is created.
+    memory_cells: BTreeMap<String, (String, bool)>,
+    /// Values of the index memory cells, the indices are added when the list is created.
+    index_memory_cells: BTreeMap<usize, (String, bool)>,
+    stack: Vec<i32>,
+    /// If true, the size of the stack has changed with the last update.
+    stack_changed: bool,
+    /// Number of values at the top of the stack that the next instruction consumes.
+    stack_consumed: usize,
     call_stack: Vec<ListItem<'static>>,
+    /// If true, values are additionally displayed as ASCII characters.
+    show_ascii: bool,
+    /// Determines when values of each memory type are displayed.
+    visibility: MemoryVisibilities,
+    /// Notation in which the names of accumulators and gamma are displayed.
+    identifier_notation: IdentifierNotation,
+    /// Format in which the values are displayed.
+    value_format: ValueFormat,
     theme: SharedTheme,
 }
 
 impl MemoryListsManager {
     /// Creates a new `MemoryListsManager` with the current values of the runtime arguments.
-    pub fn new(runtime_args: &RuntimeMemory, theme: &SharedTheme) -> Self {
-        let mut accumulators = HashMap::new();
+    ///
+    /// Only values are added that should be displayed according to `visibility`.
+    pub fn new(
+        runtime_args: &RuntimeMemory,
+        visibility: MemoryVisibilities,
+        theme: &SharedTheme,
+    ) -> Self {
+        let mut accumulators = BTreeMap::new();
         for acc in &runtime_args.accumulators {
-            accumulators.insert(*acc.0, (format!("{}", acc.1), false));
+            if visibility.accumulators.is_visible(acc.1.data.is_some()) {
+                accumulators.insert(*acc.0, (accumulator_value(acc.1), false));
+            }
         }
         //accumulators.sort_by(|a, b| a.0.cmp(&b.0));
-        let mut memory_cells = HashMap::new();
+        let mut memory_cells = BTreeMap::new();
         for cell in &runtime_args.memory_cells {
-            memory_cells.insert(cell.1.label.clone(), (format!("{}", cell.1), false));
+            if visibility.memory_cells.is_visible(cell.1.data.is_some()) {
+                memory_cells.insert(cell.1.label.clone(), (memory_cell_value(cell.1), false));
+            }
         }
-        let mut index_memory_cells = HashMap::new();
+        let mut index_memory_cells = BTreeMap::new();
         for cell in &runtime_args.index_memory_cells {
-            if let Some(v) = cell.1 {
-                index_memory_cells.insert(*cell.0, (format!("[{:2}]: {}", *cell.0, *v), false));
-            } else {
-                index_memory_cells.insert(*cell.0, (format!("[{:2}]: None", *cell.0), false));
+            if !visibility.index_memory_cells.is_visible(cell.1.is_some()) {
+                continue;
             }
+            index_memory_cells.insert(*cell.0, (index_memory_cell_value(*cell.1), false));
         }
-        let gamma = runtime_args.gamma.map(|value| (value, false));
+        let gamma = runtime_args
+            .gamma
+            .filter(|value| visibility.gamma.is_visible(value.is_some()))
+            .map(|value| (value, false));
         Self {
             accumulators,
             gamma,
             memory_cells,
             index_memory_cells,
             stack: Vec::new(),
+            stack_changed: false,
+            stack_consumed: 0,
             call_stack: Vec::new(),
+            show_ascii: false,
+            visibility,
+            identifier_notation: IdentifierNotation::Unicode,
+            value_format: ValueFormat::default(),
             theme: theme.clone(),
         }
     }
 
+    /// Sets the notation in which the names of accumulators and gamma are displayed.
+    pub fn with_identifier_notation(mut self, identifier_notation: IdentifierNotation) -> Self {
+        self.identifier_notation = identifier_notation;
+        self
+    }
+
+    /// Sets the format in which the values are displayed.
+    pub fn with_value_format(mut self, value_format: ValueFormat) -> Self {
+        self.value_format = value_format;
+        self
+    }
+
+    /// Returns the visibility of each memory type.
+    pub fn visibility(&self) -> MemoryVisibilities {
+        self.visibility
+    }
+
+    /// Returns true if values are additionally displayed as ASCII characters.
+    pub fn show_ascii(&self) -> bool {
+        self.show_ascii
+    }
+
+    /// Toggles if values are additionally displayed as ASCII characters.
+    pub fn toggle_ascii(&mut self) {
+        self.show_ascii = !self.show_ascii;
+    }
+
+    /// Appends the ASCII character of `value` at the end of `text`, if ASCII values are shown
+    /// and the value is a printable ASCII character.
+    fn with_ascii(&self, text: &str, value: Option<i32>) -> String {
+        if !self.show_ascii {
+            return text.to_string();
+        }
+        match value.and_then(ascii_char) {
+            Some(c) => format!("{text} '{c}'"),
+            None => text.to_string(),
+        }
+    }
+
     /// Updates the lists values.
     /// The old values are compared against the new values, if a value has changed the background color
     /// of that list item is changed.
     ///
     /// `control_flow` is used to update call stack values.
     pub fn update(&mut self, runtime: &Runtime) {
+        self.update_memory(
+            runtime.runtime_memory(),
+            runtime.control_flow(),
+            runtime.instructions().get(runtime.next_instruction_index()),
+        );
+    }
+
+    /// Updates the lists values with `memory` and `control_flow`, instead of the current state of a runtime.
+    ///
+    /// `next_instruction` is the instruction that is run next, it is used to mark the stack values that it consumes.
+    pub fn update_memory(
+        &mut self,
+        memory: &RuntimeMemory,
+        control_flow: &ControlFlow,
+        next_instruction: Option<&Instruction>,
+    ) {
         // Update accumulators
-        for acc in &runtime.runtime_memory().accumulators {
+        for acc in &memory.accumulators {
+            if !self
+                .visibility
+                .accumulators
+                .is_visible(acc.1.data.is_some())
+            {
+                continue;
+            }
             let a = match self.accumulators.get_mut(acc.0) {
                 Some(value) => value,
                 None => {
@@ -269,7 +611,7 @@ impl MemoryListsManager {
                     self.accumulators.get_mut(acc.0).unwrap()
                 }
             };
-            let update = format!("{}", acc.1);
+            let update = accumulator_value(acc.1);
             if update == *a.0 {
                 a.1 = false;
             } else {
@@ -277,7 +619,14 @@ impl MemoryListsManager {
             }
         }
         // Update memory_cells
-        for cell in &runtime.runtime_memory().memory_cells {
+        for cell in &memory.memory_cells {
+            if !self
+                .visibility
+                .memory_cells
+                .is_visible(cell.1.data.is_some())
+            {
+                continue;
+            }
             let a = match self.memory_cells.get_mut(&cell.1.label) {
                 Some(value) => value,
                 None => {
@@ -286,7 +635,7 @@ impl MemoryListsManager {
                     self.memory_cells.get_mut(&cell.1.label).unwrap()
                 }
             };
-            let update = format!("{}", cell.1);
+            let update = memory_cell_value(cell.1);
             if update == *a.0 {
                 a.1 = false;
             } else {
@@ -294,21 +643,18 @@ impl MemoryListsManager {
             }
         }
         // Update index memory cells
-        for cell in &runtime.runtime_memory().index_memory_cells {
-            if !self.index_memory_cells.contains_key(cell.0) {
-                if let Some(v) = cell.1 {
-                    self.index_memory_cells
-                        .insert(*cell.0, (format!("[{:2}]: {}", cell.0, v), true));
-                } else {
-                    self.index_memory_cells
-                        .insert(*cell.0, (format!("[{:2}]: None", cell.0), true));
-                }
+        for cell in &memory.index_memory_cells {
+            if !self
+                .visibility
+                .index_memory_cells
+                .is_visible(cell.1.is_some())
+            {
                 continue;
             }
-            let a = self.index_memory_cells.get_mut(cell.0).unwrap();
-            let update = match cell.1 {
-                Some(v) => format!("[{:2}]: {}", cell.0, v),
-                None => format!("[{:2}]: None", cell.0),
+            let update = index_memory_cell_value(*cell.1);
+            let Some(a) = self.index_memory_cells.get_mut(cell.0) else {
+                self.index_memory_cells.insert(*cell.0, (update, true));
+                continue;
             };
             if update == *a.0 {
                 a.1 = false;
@@ -317,7 +663,10 @@ impl MemoryListsManager {
             }
         }
         // Update gamma
-        if let Some(update) = runtime.runtime_memory().gamma {
+        if let Some(update) = memory
+            .gamma
+            .filter(|value| self.visibility.gamma.is_visible(value.is_some()))
+        {
             if let Some(value) = self.gamma.as_mut() {
                 if update == value.0 {
                     value.1 = false;
@@ -329,28 +678,17 @@ impl MemoryListsManager {
             }
         }
         // Update stack
-        let stack_changed = self.stack.len() != runtime.runtime_memory().stack.len();
-        let mut new_stack: Vec<ListItem<'_>> = runtime
-            .runtime_memory()
-            .stack
-            .iter()
-            .map(|f| ListItem::new(f.to_string()))
-            .collect();
-        if stack_changed && !new_stack.is_empty() {
-            let last_stack = new_stack
-                .pop()
-                .unwrap()
-                .style(self.theme.list_item_highlight(false));
-            new_stack.push(last_stack);
-        }
-        self.stack = new_stack;
+        let stack = &memory.stack;
+        self.stack_changed = self.stack.len() != stack.len();
+        self.stack.clone_from(stack);
+        self.stack_consumed = next_instruction.map_or(0, consumed_stack_values);
         // update call stack
-        let call_stack_changed = self.call_stack.len() != runtime.control_flow().call_stack.len();
-        let mut new_call_stack: Vec<ListItem<'_>> = runtime
-            .control_flow()
+        let call_stack_changed = self.call_stack.len() != control_flow.call_stack.len();
+        let mut new_call_stack: Vec<ListItem<'_>> = control_flow
             .call_stack
             .iter()
-            .map(|f| ListItem::new(format!("{}", f + 1)))
+            .zip(&control_flow.tail_calls)
+            .map(|(f, tail_calls)| ListItem::new(call_stack_entry(*f, *tail_calls)))
             .collect();
         if call_stack_changed && !new_call_stack.is_empty() {
             let last_stack = new_call_stack
@@ -363,69 +701,166 @@ impl MemoryListsManager {
     }
 
     /// Returns the current accumulators as list
-    pub fn accumulator_list(&self) -> Vec<ListItem<'static>> {
-        let mut list = Vec::new();
-        for acc in &self.accumulators {
-            let mut item = ListItem::new(acc.1 .0.clone());
-            if acc.1 .1 {
-                item = item.style(self.theme.list_item_highlight(false));
-            }
-            list.push((item, acc.0));
+    ///
+    /// The names of declared `variables` are displayed next to the accumulators, accumulators that are
+    /// contained in `autodetected` are marked as new.
+    pub fn accumulator_list(
+        &self,
+        variables: &Variables,
+        autodetected: &BTreeSet<MemoryLocation>,
+    ) -> Vec<ListItem<'static>> {
+        // gamma is displayed at the top of the list, if it is in use
+        let mut entries = Vec::new();
+        if let Some((value, changed)) = self.gamma {
+            entries.push(MemoryEntry {
+                location: with_variable(self.identifier_notation.gamma(), variables.name("y")),
+                value: value.map_or_else(|| "None".to_string(), |v| v.to_string()),
+                changed,
+                is_new: autodetected.contains(&MemoryLocation::Gamma),
+            });
+        } else if self.visibility.gamma == MemoryVisibility::Always {
+            entries.push(MemoryEntry {
+                location: self.identifier_notation.gamma().to_string(),
+                value: "disabled".to_string(),
+                changed: false,
+                is_new: false,
+            });
         }
-        list.sort_by(|a, b| a.1.cmp(b.1));
-        list.reverse();
-        // Insert gamma accumulator if it is in use
-        if let Some(value) = self.gamma {
-            if let Some(inner_value) = value.0 {
-                let mut item = ListItem::new(format!("  γ: {inner_value}"));
-                if value.1 {
-                    item = item.style(self.theme.list_item_highlight(false));
-                }
-                list.push((item, &0));
-            } else {
-                let mut item = ListItem::new("  γ: None".to_string());
-                if value.1 {
-                    item = item.style(self.theme.list_item_highlight(false));
-                }
-                list.push((item, &0));
-            }
+        for (idx, (value, changed)) in &self.accumulators {
+            entries.push(MemoryEntry {
+                location: with_variable(
+                    &self.identifier_notation.accumulator(*idx),
+                    variables.name(&format!("a{idx}")),
+                ),
+                value: value.clone(),
+                changed: *changed,
+                is_new: autodetected.contains(&MemoryLocation::Accumulator(*idx)),
+            });
+        }
+        // names of accumulators are right aligned, so that `a9` and `a10` end in the same column
+        let width = entries
+            .iter()
+            .map(|entry| entry.location.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(3);
+        for entry in &mut entries {
+            entry.location = format!("{:>width$}", entry.location);
         }
-        list.reverse(); // reverse list to make gamma appear at top of list
-        list.iter().map(|f| f.0.clone()).collect()
+        self.list_items(entries)
     }
 
     /// Returns the current memory cells as list (also contains index memory cells)
-    pub fn memory_cell_list(&self) -> Vec<ListItem<'static>> {
-        let mut list = Vec::new();
-        for cell in &self.memory_cells {
-            let mut item = ListItem::new(cell.1 .0.clone());
-            if cell.1 .1 {
-                item = item.style(self.theme.list_item_highlight(false));
-            }
-            list.push((item, cell.0.clone()));
-        }
-        list.sort_by(|a, b| a.1.cmp(&b.1));
-        // Add index memory cells
-        let mut imc: Vec<(usize, bool, String)> = Vec::new();
-        for cell in &self.index_memory_cells {
-            imc.push((*cell.0, cell.1 .1, cell.1 .0.clone()));
+    ///
+    /// The names of declared `variables` are displayed next to the memory cells, memory cells that are
+    /// contained in `autodetected` are marked as new.
+    pub fn memory_cell_list(
+        &self,
+        variables: &Variables,
+        autodetected: &BTreeSet<MemoryLocation>,
+    ) -> Vec<ListItem<'static>> {
+        let mut entries = Vec::new();
+        for (name, (value, changed)) in &self.memory_cells {
+            entries.push(MemoryEntry {
+                location: with_variable(
+                    &format!("{name:2}"),
+                    variables.name(&format!("p({name})")),
+                ),
+                value: value.clone(),
+                changed: *changed,
+                is_new: autodetected.contains(&MemoryLocation::MemoryCell(name.clone())),
+            });
         }
-        imc.sort(); // Make sure that index memory cells are properly sorted by index
-        for cell in imc {
-            let mut item = ListItem::new(cell.2.clone());
-            if cell.1 {
-                item = item.style(self.theme.list_item_highlight(false));
-            }
-            list.push((item, format!("{}", cell.0)));
+        for (idx, (value, changed)) in &self.index_memory_cells {
+            entries.push(MemoryEntry {
+                location: with_variable(
+                    &format!("[{idx:2}]"),
+                    variables.name(&format!("p({idx})")),
+                ),
+                value: value.clone(),
+                changed: *changed,
+                is_new: autodetected.contains(&MemoryLocation::IndexMemoryCell(*idx)),
+            });
         }
-        list.iter().map(|f| f.0.clone()).collect()
+        self.list_items(entries)
     }
 
-    /// Returns the stack items as list
+    /// Returns the stack items as list, the top of the stack is the first item.
+    ///
+    /// Each item contains the index of the value in the stack, the top of the stack is marked with `▶`.
+    /// Values that are consumed by the next instruction are highlighted.
     pub fn stack_list(&self) -> Vec<ListItem<'static>> {
-        let mut list = self.stack.clone();
-        list.reverse();
-        list
+        let top = self.stack.len().saturating_sub(1);
+        let consumed_from = self.stack.len().saturating_sub(self.stack_consumed);
+        let entries = self
+            .stack
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(idx, value)| {
+                let marker = if idx == top { '▶' } else { ' ' };
+                (
+                    format!("{marker}[{idx:2}]"),
+                    self.value_format.value(&value.to_string()),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.value_format
+            .columns(&entries)
+            .into_iter()
+            .zip((0..self.stack.len()).rev())
+            .map(|(text, idx)| {
+                let mut style = Style::default();
+                if idx == top && self.stack_changed {
+                    style = style.patch(self.theme.list_item_highlight(false));
+                }
+                if idx >= consumed_from {
+                    style = style.patch(self.theme.stack_item_consumed());
+                }
+                ListItem::new(self.with_ascii(&text, Some(self.stack[idx]))).style(style)
+            })
+            .collect()
+    }
+
+    /// Creates the list items of `entries`, the values are formatted according to the value format.
+    ///
+    /// Changed values are highlighted.
+    fn list_items(&self, entries: Vec<MemoryEntry>) -> Vec<ListItem<'static>> {
+        let columns = entries
+            .iter()
+            .map(|entry| {
+                (
+                    entry.location.clone(),
+                    self.value_format.value(&entry.value),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.value_format
+            .columns(&columns)
+            .into_iter()
+            .zip(entries)
+            .map(|(text, entry)| {
+                let item = ListItem::new(with_badge(
+                    self.with_ascii(&text, entry.value.parse().ok()),
+                    entry.is_new,
+                ));
+                if entry.changed {
+                    item.style(self.theme.list_item_highlight(false))
+                } else {
+                    item
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the number of values on the stack.
+    pub fn stack_depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns the depth of the call stack.
+    pub fn call_stack_depth(&self) -> usize {
+        self.call_stack.len()
     }
 
     /// Returns the call stack items as list
@@ -435,3 +870,158 @@ impl MemoryListsManager {
         list
     }
 }
+
+/// Returns the value of the accumulator as it is displayed, e.g. `5` or `None`.
+fn accumulator_value(accumulator: &Accumulator) -> String {
+    accumulator
+        .data
+        .map_or_else(|| "None".to_string(), |value| value.to_string())
+}
+
+/// Returns the value of the memory cell as it is displayed, e.g. `5` or `None`.
+fn memory_cell_value(memory_cell: &MemoryCell) -> String {
+    memory_cell
+        .data
+        .map_or_else(|| "None".to_string(), |value| value.to_string())
+}
+
+/// Returns the value of an index memory cell as it is displayed, e.g. `5` or `None`.
+fn index_memory_cell_value(value: Option<i32>) -> String {
+    value.map_or_else(|| "None".to_string(), |value| value.to_string())
+}
+
+/// Returns the text of a call stack frame, `return_index` is the index of the instruction to which the function
+/// returns.
+///
+/// Frames that were reused by tail calls are marked with `↺` and the number of tail calls, e.g. `5 ↺3`.
+fn call_stack_entry(return_index: usize, tail_calls: usize) -> String {
+    if tail_calls == 0 {
+        format!("{}", return_index + 1)
+    } else {
+        format!("{} ↺{tail_calls}", return_index + 1)
+    }
+}
+
+/// Appends the name of the variable in parentheses to `location`, e.g. `h1 (sum)`.
+fn with_variable(location: &str, variable: Option<&str>) -> String {
+    match variable {
+        Some(name) => format!("{location} ({name})"),
+        None => location.to_string(),
+    }
+}
+
+/// Appends a badge to `text` that marks a memory location as created by autodetection, if `is_new` is set.
+fn with_badge(text: String, is_new: bool) -> String {
+    if is_new {
+        format!("{text} [new]")
+    } else {
+        text
+    }
+}
+
+/// A memory location and its value, as it is displayed in a memory list.
+struct MemoryEntry {
+    /// Name of the memory location, including the name of the variable.
+    location: String,
+    /// Value of the memory location, without formatting.
+    value: String,
+    /// If true, the value has changed with the last update.
+    changed: bool,
+    /// If true, the memory location was created by autodetection.
+    is_new: bool,
+}
+
+/// Returns how many values at the top of the stack are consumed when `instruction` is run.
+fn consumed_stack_values(instruction: &Instruction) -> usize {
+    match instruction {
+        Instruction::Pop => 1,
+        Instruction::StackOp(_) => 2,
+        Instruction::CondExec(_, _, _, instruction) => consumed_stack_values(instruction),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::text::Line;
+
+    use crate::app::folds::Fold;
+
+    use super::InstructionListStates;
+
+    fn list_states(lines: usize) -> InstructionListStates {
+        let lines = (0..lines)
+            .map(|idx| Line::from(format!("a0 := {idx}")))
+            .collect::<Vec<_>>();
+        InstructionListStates::new(&lines, None)
+    }
+
+    #[test]
+    fn test_visible_lines() {
+        let mut states = list_states(1000);
+        assert_eq!(states.visible_lines(10), (0..10).collect::<Vec<_>>());
+        states.force_set(8);
+        assert_eq!(states.visible_lines(10), (1..11).collect::<Vec<_>>());
+        // scrolling up keeps the lines until the padding is reached
+        states.force_set(3);
+        assert_eq!(states.visible_lines(10), (1..11).collect::<Vec<_>>());
+        states.force_set(500);
+        let lines = states.visible_lines(10);
+        assert_eq!(lines, (493..503).collect::<Vec<_>>());
+        assert_eq!(states.visible_list_state(&lines).selected(), Some(7));
+        states.force_set(999);
+        assert_eq!(states.visible_lines(10), (990..1000).collect::<Vec<_>>());
+        // programs that are shorter than the code area are never scrolled
+        let mut states = list_states(5);
+        states.force_set(4);
+        assert_eq!(states.visible_lines(10), (0..5).collect::<Vec<_>>());
+        assert!(states.visible_lines(0).is_empty());
+    }
+
+    #[test]
+    fn test_page_navigation() {
+        let mut states = list_states(100);
+        states.visible_lines(20);
+        states.set_next_page_visual();
+        assert_eq!(states.selected_line(), Some(0));
+        states.set_next_page_visual();
+        assert_eq!(states.selected_line(), Some(19));
+        states.set_prev_page_visual();
+        assert_eq!(states.selected_line(), Some(0));
+        states.set_last_visual();
+        assert_eq!(states.selected_line(), Some(99));
+        states.set_next_page_visual();
+        assert_eq!(states.selected_line(), Some(99));
+        states.set_first_visual();
+        assert_eq!(states.selected_line(), Some(0));
+    }
+
+    #[test]
+    fn test_folding() {
+        let mut states = list_states(10).with_folds(vec![Fold {
+            label: "f".to_string(),
+            start: 3,
+            end: 6,
+            folded: false,
+        }]);
+        states.force_set(5);
+        states.toggle_fold();
+        assert_eq!(states.selected_line(), Some(3));
+        assert_eq!(states.visible_lines(10), vec![0, 1, 2, 3, 7, 8, 9]);
+        states.set_next_visual();
+        assert_eq!(states.selected_line(), Some(7));
+        states.set_prev_visual();
+        assert_eq!(states.selected_line(), Some(3));
+        // the execution enters the folded subroutine
+        states.force_set(4);
+        assert_eq!(states.displayed_line(4), 3);
+        let lines = states.visible_lines(10);
+        assert_eq!(states.visible_list_state(&lines).selected(), Some(3));
+        assert_eq!(
+            states.fold_summary(&states.folds[0]),
+            "  ⋯ 3 lines folded, at line 5"
+        );
+        states.toggle_fold();
+        assert_eq!(states.visible_lines(10), (0..10).collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    backtrace::Backtrace,
+    collections::VecDeque,
+    env, fs, io, panic,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crossterm::{
+    cursor::Show,
+    event::DisableMouseCapture,
+    terminal::{self, LeaveAlternateScreen},
+};
+
+use super::State;
+
+/// Number of input events that are included in a crash report.
+const CRASH_REPORT_EVENTS: usize = 20;
+
+/// Page on which crash reports can be submitted.
+const ISSUES_PAGE: &str = "https://github.com/LMH01/alpha_tui/issues";
+
+/// Information about the app that is written into the crash report, when the app panics.
+static CONTEXT: Mutex<CrashContext> = Mutex::new(CrashContext::new());
+
+/// The information about the app that is updated while the app is running.
+#[derive(Debug)]
+struct CrashContext {
+    /// Name of the program file, `Playground` in the playground.
+    program: String,
+    /// Name of the variant of the state the app was in, e.g. `DebugSelect`.
+    state: String,
+    /// The last input events, the last event is the newest.
+    events: VecDeque<String>,
+}
+
+impl CrashContext {
+    const fn new() -> Self {
+        Self {
+            program: String::new(),
+            state: String::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    fn record_event(&mut self, event: String) {
+        if self.events.len() == CRASH_REPORT_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Returns the content of the crash report, `panic` is the message of the panic, including its location.
+    fn report(&self, panic: &str, backtrace: &str) -> String {
+        let mut report = format!(
+            "alpha_tui crash report\n\nVersion: {}\nProgram: {}\nState:   {}\n\n{panic}\n\nLast input events (oldest first):\n",
+            env!("CARGO_PKG_VERSION"),
+            self.program,
+            self.state,
+        );
+        if self.events.is_empty() {
+            report.push_str("  none\n");
+        }
+        for event in &self.events {
+            report.push_str(&format!("  {event}\n"));
+        }
+        report.push_str(&format!("\nBacktrace:\n{backtrace}\n"));
+        report
+    }
+}
+
+/// Installs a panic hook that restores the terminal and writes a crash report into the temp directory.
+///
+/// The report contains the backtrace, the state of the app, `program` and the last input events. Its path is printed
+/// after the panic message.
+pub fn install_hook(program: &str) {
+    context().program = program.to_string();
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        // the terminal is restored first, otherwise the messages are not readable
+        let _ = terminal::disable_raw_mode();
+        let _ = crossterm::execute!(
+            io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            Show
+        );
+        default_hook(info);
+        let report = context().report(&info.to_string(), &Backtrace::force_capture().to_string());
+        match write_report(&report) {
+            Ok(path) => eprintln!(
+                "alpha_tui crashed, a crash report was written to {}\nPlease attach it when you report the crash at {ISSUES_PAGE}",
+                path.display()
+            ),
+            Err(e) => eprintln!("alpha_tui crashed, unable to write the crash report: {e}"),
+        }
+    }));
+}
+
+/// Sets the state that is written into the crash report.
+pub fn set_state(state: &State) {
+    context().state = state_name(state);
+}
+
+/// Records an input event, the last events are written into the crash report.
+pub fn record_event(event: String) {
+    context().record_event(event);
+}
+
+fn context() -> MutexGuard<'static, CrashContext> {
+    // the context is only read when the app panics, a poisoned lock still contains useful information
+    CONTEXT.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Returns the name of the variant of `state`, without the values it contains.
+fn state_name(state: &State) -> String {
+    let debug = format!("{state:?}");
+    debug
+        .split(['(', ' ', '{'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Writes the crash report into a new file in the temp directory and returns its path.
+fn write_report(report: &str) -> io::Result<PathBuf> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+    let path = env::temp_dir().join(format!("alpha_tui_crash_{timestamp}.txt"));
+    fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::app::State;
+
+    use super::{state_name, CrashContext, CRASH_REPORT_EVENTS};
+
+    #[test]
+    fn test_state_name() {
+        assert_eq!(state_name(&State::Default), "Default");
+        assert_eq!(state_name(&State::Running(true)), "Running");
+    }
+
+    #[test]
+    fn test_crash_report() {
+        let mut context = CrashContext::new();
+        context.program = "program.alpha".to_string();
+        context.state = "DebugSelect".to_string();
+        for idx in 0..=CRASH_REPORT_EVENTS {
+            context.record_event(format!("Char('{idx}') Press"));
+        }
+        assert_eq!(context.events.len(), CRASH_REPORT_EVENTS);
+        assert_eq!(context.events[0], "Char('1') Press");
+        let report = context.report("panicked at src/main.rs:1:1:\ntest", "<backtrace>");
+        assert!(report.starts_with(&format!(
+            "alpha_tui crash report\n\nVersion: {}\nProgram: program.alpha\nState:   DebugSelect\n\npanicked at src/main.rs:1:1:\ntest\n\nLast input events (oldest first):\n  Char('1') Press\n",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(report.ends_with("  Char('20') Press\n\nBacktrace:\n<backtrace>\n"));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{instructions::Instruction, runtime::Runtime};
+
+/// Lines of a subroutine that can be folded into a single line.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Fold {
+    /// Label of the subroutine.
+    pub label: String,
+    /// Index of the line that contains the label, this line stays visible when the subroutine is folded.
+    pub start: usize,
+    /// Index of the line that contains the `return` of the subroutine.
+    pub end: usize,
+    pub folded: bool,
+}
+
+impl Fold {
+    /// Returns true if the line with index `idx` is hidden by this fold.
+    pub fn hides(&self, idx: usize) -> bool {
+        self.folded && idx > self.start && idx <= self.end
+    }
+}
+
+/// Returns the subroutines of the program that can be folded, sorted by their first line.
+///
+/// A subroutine is a label that is only reached by `call` instructions, its body reaches until the first `return`
+/// that follows the label. Subroutines that lie inside the body of another subroutine can not be folded.
+pub fn subroutine_folds(runtime: &Runtime) -> Vec<Fold> {
+    let instructions = runtime.instructions();
+    let mut called = Vec::new();
+    let mut jumped_to = Vec::new();
+    for instruction in instructions {
+        match instruction {
+            Instruction::Call(label) => called.push(label.as_str()),
+            Instruction::Goto(label) | Instruction::JumpIf(_, _, _, label) => {
+                jumped_to.push(label.as_str());
+            }
+            _ => (),
+        }
+    }
+    let mut folds = runtime
+        .control_flow()
+        .instruction_labels
+        .iter()
+        .filter(|(label, _)| {
+            called.contains(&label.as_str()) && !jumped_to.contains(&label.as_str())
+        })
+        .filter_map(|(label, start)| {
+            let end = instructions[*start..]
+                .iter()
+                .position(|instruction| *instruction == Instruction::Return)?;
+            Some(Fold {
+                label: label.clone(),
+                start: *start,
+                end: start + end,
+                folded: false,
+            })
+        })
+        // a subroutine that only consists of `return` can not be folded
+        .filter(|fold| fold.end > fold.start)
+        .collect::<Vec<_>>();
+    folds.sort_by_key(|fold| fold.start);
+    let mut end = None;
+    folds.retain(|fold| {
+        if end.is_some_and(|end| fold.start <= end) {
+            return false;
+        }
+        end = Some(fold.end);
+        true
+    });
+    folds
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::builder::RuntimeBuilder;
+
+    use super::{subroutine_folds, Fold};
+
+    fn folds(program: &str) -> Vec<Fold> {
+        let lines = program.lines().map(ToString::to_string).collect::<Vec<_>>();
+        let rt = RuntimeBuilder::new(&lines, "test")
+            .unwrap()
+            .build()
+            .unwrap();
+        subroutine_folds(&rt)
+    }
+
+    #[test]
+    fn test_subroutine_folds() {
+        let folds = folds(
+            "call double\ncall inc\ngoto end\ndouble: a0 := a0 * 2\ncall inc\nreturn\ninc: a0 := a0 + 1\nreturn\nend: a0 := a0",
+        );
+        assert_eq!(
+            folds,
+            vec![
+                Fold {
+                    label: "double".to_string(),
+                    start: 3,
+                    end: 5,
+                    folded: false,
+                },
+                Fold {
+                    label: "inc".to_string(),
+                    start: 6,
+                    end: 7,
+                    folded: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subroutine_folds_excluded() {
+        // labels that are reached by jumps are no subroutines
+        assert!(folds("call f\ngoto f\nf: a0 := 1\nreturn").is_empty());
+        // subroutines without return can not be folded
+        assert!(folds("call f\nf: a0 := 1").is_empty());
+        // subroutines inside of other subroutines can not be folded
+        let folds = folds("call f\ncall g\nf: a0 := 1\ng: a0 := 2\nreturn");
+        assert_eq!(folds.len(), 1);
+        assert_eq!(folds[0].label, "f");
+    }
+
+    #[test]
+    fn test_fold_hides() {
+        let mut fold = Fold {
+            label: "f".to_string(),
+            start: 2,
+            end: 4,
+            folded: false,
+        };
+        assert!(!fold.hides(3));
+        fold.folded = true;
+        assert!(!fold.hides(2));
+        assert!(fold.hides(3));
+        assert!(fold.hides(4));
+        assert!(!fold.hides(5));
+    }
+}
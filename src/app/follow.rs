@@ -0,0 +1,236 @@
+use std::{
+    io::{self, Write},
+    net::{Ipv4Addr, TcpListener},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::Runtime;
+
+/// Time after which a follower that does not read the states is disconnected.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Execution state of a program that is sent to followers.
+///
+/// Followers display the memory in another terminal, see the `follow` command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FollowState {
+    /// Name of the file that contains the program.
+    pub filename: String,
+    /// Describes the state of the execution, e.g. `running` or `finished`.
+    pub status: String,
+    /// Line of the instruction that is executed next, is `None` if the program was not started.
+    pub line: Option<usize>,
+    /// The instruction that is executed next.
+    pub instruction: Option<String>,
+    /// Accumulators and their values, sorted by index.
+    pub accumulators: Vec<(String, Option<i32>)>,
+    /// Value of the gamma accumulator, is `None` if the gamma accumulator does not exist.
+    pub gamma: Option<Option<i32>>,
+    /// Memory cells and their values, sorted by name.
+    pub memory_cells: Vec<(String, Option<i32>)>,
+    /// Index memory cells and their values, sorted by index.
+    pub index_memory_cells: Vec<(String, Option<i32>)>,
+    /// Values on the stack, the last value is on top of the stack.
+    pub stack: Vec<i32>,
+}
+
+impl FollowState {
+    /// Creates the state of the runtime, `started` is false if the execution of the program was not started yet.
+    pub fn new(filename: &str, status: &str, runtime: &Runtime, started: bool) -> Self {
+        let memory = runtime.runtime_memory();
+        let mut accumulators = memory
+            .accumulators
+            .iter()
+            .map(|(idx, acc)| (*idx, acc.data))
+            .collect::<Vec<_>>();
+        accumulators.sort_by_key(|(idx, _)| *idx);
+        let mut memory_cells = memory
+            .memory_cells
+            .iter()
+            .map(|(name, cell)| (name.clone(), cell.data))
+            .collect::<Vec<_>>();
+        memory_cells.sort();
+        let mut index_memory_cells = memory
+            .index_memory_cells
+            .iter()
+            .map(|(idx, value)| (*idx, *value))
+            .collect::<Vec<_>>();
+        index_memory_cells.sort_by_key(|(idx, _)| *idx);
+        let next = runtime.next_instruction_index();
+        Self {
+            filename: filename.to_string(),
+            status: status.to_string(),
+            line: started.then_some(next + 1),
+            instruction: runtime
+                .instructions()
+                .get(next)
+                .filter(|_| started)
+                .map(ToString::to_string),
+            accumulators: accumulators
+                .into_iter()
+                .map(|(idx, value)| (format!("a{idx}"), value))
+                .collect(),
+            gamma: memory.gamma,
+            memory_cells,
+            index_memory_cells: index_memory_cells
+                .into_iter()
+                .map(|(idx, value)| (idx.to_string(), value))
+                .collect(),
+            stack: memory.stack.clone(),
+        }
+    }
+}
+
+/// Sends the execution state to all followers that are connected to a local port.
+///
+/// Each state is sent as a single line of json. The states are written in a background thread, so that a follower
+/// that does not read them can not block the tui.
+pub struct StateServer {
+    /// Passes the states to the thread that writes them to the followers.
+    sender: Sender<String>,
+    /// The last state that was sent, it is sent to followers when they connect.
+    last_state: Arc<Mutex<String>>,
+}
+
+impl StateServer {
+    /// Listens for followers on the local port `port`.
+    ///
+    /// Followers are accepted in a background thread.
+    pub fn bind(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let last_state = Arc::new(Mutex::new(String::new()));
+        {
+            let (clients, last_state) = (clients.clone(), last_state.clone());
+            thread::spawn(move || {
+                for mut stream in listener.incoming().map_while(Result::ok) {
+                    if stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err() {
+                        continue;
+                    }
+                    let state = last_state.lock().unwrap().clone();
+                    if !state.is_empty() && writeln!(stream, "{state}").is_err() {
+                        continue;
+                    }
+                    clients.lock().unwrap().push(stream);
+                }
+            });
+        }
+        let (sender, receiver) = mpsc::channel::<String>();
+        thread::spawn(move || {
+            while let Ok(json) = receiver.recv() {
+                // states that were sent while the followers were written to are outdated
+                let json = receiver.try_iter().last().unwrap_or(json);
+                clients
+                    .lock()
+                    .unwrap()
+                    .retain_mut(|stream| writeln!(stream, "{json}").is_ok());
+            }
+        });
+        Ok(Self { sender, last_state })
+    }
+
+    /// Sends the state to all followers, if it changed since the last state was sent.
+    ///
+    /// Followers that can not be reached or that don't read the state in time are removed.
+    pub fn broadcast(&self, state: &FollowState) {
+        let Ok(json) = serde_json::to_string(state) else {
+            return;
+        };
+        let mut last_state = self.last_state.lock().unwrap();
+        if *last_state == json {
+            return;
+        }
+        *last_state = json.clone();
+        // the thread only stops when the server is dropped
+        let _ = self.sender.send(json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::{Ipv4Addr, TcpListener, TcpStream},
+        time::{Duration, Instant},
+    };
+
+    use crate::utils::test_utils;
+
+    use super::{FollowState, StateServer};
+
+    #[test]
+    fn test_follow_state() {
+        let mut rt = test_utils::runtime_from_str("a := 5\npush\np(h1) := a\np(2) := 1").unwrap();
+        let state = FollowState::new("test", "not started", &rt, false);
+        assert_eq!(state.line, None);
+        rt.step().unwrap();
+        rt.step().unwrap();
+        let state = FollowState::new("test", "running", &rt, true);
+        assert_eq!(state.line, Some(3));
+        assert_eq!(state.instruction, Some("p(h1) := a0".to_string()));
+        assert_eq!(state.accumulators[0], ("a0".to_string(), Some(5)));
+        assert!(state.memory_cells.contains(&("h1".to_string(), None)));
+        assert_eq!(state.stack, vec![5]);
+    }
+
+    #[test]
+    fn test_state_server() {
+        // use a port that is free
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = StateServer::bind(port).unwrap();
+        let rt = test_utils::runtime_from_str("a := 5").unwrap();
+        let state = FollowState::new("test", "not started", &rt, false);
+        server.broadcast(&state);
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        // the last state is sent when the follower connects
+        let received: FollowState = serde_json::from_str(&lines.next().unwrap().unwrap()).unwrap();
+        assert_eq!(received, state);
+    }
+
+    #[test]
+    fn test_state_server_follower_not_reading() {
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let server = StateServer::bind(port).unwrap();
+        let _stalled = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let follower = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let mut state = FollowState {
+            memory_cells: (0..2_000)
+                .map(|idx| (format!("h{idx}"), Some(idx)))
+                .collect(),
+            ..FollowState::default()
+        };
+        // the states are larger than the buffers of the connection of the follower that does not read them
+        let start = Instant::now();
+        for step in 0..500 {
+            state.status = format!("step {step}");
+            server.broadcast(&state);
+        }
+        // writing to the follower would block until it reads
+        assert!(start.elapsed() < Duration::from_secs(10));
+        // the follower that reads the states receives the last state
+        let mut lines = BufReader::new(follower).lines();
+        let last = lines
+            .find_map(|line| {
+                let received: FollowState = serde_json::from_str(&line.unwrap()).unwrap();
+                (received.status == "step 499").then_some(received)
+            })
+            .unwrap();
+        assert_eq!(last, state);
+    }
+}
@@ -0,0 +1,487 @@
+//! Runs the app without a terminal, so that whole interactions can be tested.
+//!
+//! Key events are fed into the app like they are when the app runs in a terminal, the resulting state and the runtime
+//! can be inspected after each key press.
+
+use std::rc::Rc;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use miette::Result;
+
+use crate::{
+    app::{
+        key_events,
+        statistics::SessionStatistics,
+        ui::{style::Theme, syntax_highlighting::SyntaxHighlighter},
+        App, State,
+    },
+    base::ComparisonNotation,
+    runtime::{memory_usage::MemoryLocation, Runtime},
+    utils::test_utils,
+};
+
+/// Creates the app for `program`, like the load command does with the default options.
+pub fn app(program: &str, playground: bool) -> App {
+    app_with_filename(program, "test.alpha", playground)
+}
+
+/// Creates the app for `program` that was read from `filename`, see [`app`].
+pub fn app_with_filename(program: &str, filename: &str, playground: bool) -> App {
+    let instructions = test_utils::string_literal_to_vec(program);
+    let rt = test_utils::runtime_from_str(program).unwrap();
+    let theme = Rc::new(Theme::default());
+    let lines = SyntaxHighlighter::new(&theme.syntax_highlighting_theme())
+        .input_to_lines(&instructions, true)
+        .unwrap();
+    App::from_runtime(
+        rt,
+        filename.to_string(),
+        &lines,
+        &None,
+        None,
+        None,
+        None,
+        playground,
+        true,
+        ComparisonNotation::default(),
+        theme,
+    )
+}
+
+/// Drives an [`App`] with synthetic key events.
+pub struct HeadlessApp {
+    app: App,
+    /// Set when a key press closed the app, further key presses are not allowed.
+    closed: bool,
+}
+
+impl HeadlessApp {
+    /// Creates the driver for `program`, see [`app`].
+    pub fn new(program: &str) -> Self {
+        Self::from_app(app(program, false))
+    }
+
+    /// Creates the driver for an empty playground.
+    pub fn playground() -> Self {
+        Self::from_app(app("", true))
+    }
+
+    /// Creates the driver for an app that was configured already.
+    pub fn from_app(mut app: App) -> Self {
+        app.update_after_input().unwrap();
+        Self { app, closed: false }
+    }
+
+    /// Presses and releases the key `code`.
+    ///
+    /// Returns the error that the app would exit with, e.g. when `q` is pressed while a runtime error is displayed.
+    pub fn press(&mut self, code: KeyCode) -> Result<()> {
+        self.send(KeyEvent::new(code, KeyModifiers::NONE))?;
+        if self.closed {
+            return Ok(());
+        }
+        self.send(KeyEvent::new_with_kind(
+            code,
+            KeyModifiers::NONE,
+            KeyEventKind::Release,
+        ))
+    }
+
+    /// Presses all keys in `codes` one after another, stops at the first error.
+    pub fn press_all(&mut self, codes: &[KeyCode]) -> Result<()> {
+        codes.iter().try_for_each(|code| self.press(*code))
+    }
+
+    /// Types `text` into the app, one key press per character.
+    pub fn type_str(&mut self, text: &str) -> Result<()> {
+        text.chars().try_for_each(|c| self.press(KeyCode::Char(c)))
+    }
+
+    /// Sends a key event to the app, like the terminal does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the app was closed by an earlier key press.
+    pub fn send(&mut self, raw: KeyEvent) -> Result<()> {
+        assert!(
+            !self.closed,
+            "key {raw:?} was sent after the app was closed"
+        );
+        let key = key_events::normalize(raw);
+        if key.kind == KeyEventKind::Release {
+            return Ok(());
+        }
+        self.closed = self.app.handle_key(key)?;
+        self.app.update_after_input()
+    }
+
+    /// Returns true if the app was closed by a key press.
+    pub fn closed(&self) -> bool {
+        self.closed
+    }
+
+    pub fn state(&self) -> &State {
+        &self.app.state
+    }
+
+    pub fn runtime(&self) -> &Runtime {
+        &self.app.runtime
+    }
+
+    /// Returns the usage statistics that were recorded so far, `None` if they are disabled.
+    pub fn statistics(&mut self) -> Option<&SessionStatistics> {
+        self.app
+            .statistics
+            .as_mut()
+            .map(|statistics| &*statistics.session())
+    }
+
+    /// Returns the value of `location`, `None` if the location does not exist or has no value.
+    pub fn value(&self, location: MemoryLocation) -> Option<i32> {
+        self.app.runtime.runtime_memory().get(&location).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::{
+        app::{
+            statistics::{Statistics, UsedFeature},
+            BranchDecision, ProgramBuilder, State, UnsavedArtifact,
+        },
+        clock::SharedClock,
+        instructions::instruction_config::InstructionConfig,
+        preset::StatisticsConfig,
+        runtime::memory_usage::MemoryLocation,
+        utils::{self, test_utils},
+    };
+
+    use super::HeadlessApp;
+
+    const PROGRAM: &str = "a := 1\na := a + 1\np(h1) := a\na := a * 3\np(h2) := a";
+
+    fn a() -> MemoryLocation {
+        MemoryLocation::Accumulator(0)
+    }
+
+    fn cell(name: &str) -> MemoryLocation {
+        MemoryLocation::MemoryCell(name.to_string())
+    }
+
+    #[test]
+    fn test_headless_breakpoint_and_custom_instruction() {
+        let mut app = HeadlessApp::new(PROGRAM);
+        app.press(KeyCode::Char('s')).unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        // the first instruction is run on start, the selection starts at the next instruction in line 2
+        app.press_all(&[
+            KeyCode::Char('d'),
+            KeyCode::Down,
+            KeyCode::Char('b'),
+            KeyCode::Char('d'),
+        ])
+        .unwrap();
+        // breakpoint in line 3 is set
+        assert_eq!(app.state(), &State::Running(true));
+        app.press(KeyCode::Char('r')).unwrap();
+        assert_eq!(app.runtime().next_instruction_index(), 2);
+        assert_eq!(app.value(a()), Some(2));
+        // change the accumulator before the line with the breakpoint is run
+        app.press(KeyCode::Char('i')).unwrap();
+        assert!(matches!(app.state(), State::CustomInstruction(_)));
+        app.type_str("a := 10").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        assert_eq!(app.value(a()), Some(10));
+        assert_eq!(app.runtime().next_instruction_index(), 2);
+        app.press(KeyCode::Char('r')).unwrap();
+        assert!(matches!(app.state(), State::Finished(_)));
+        assert_eq!(app.value(cell("h1")), Some(10));
+        assert_eq!(app.value(cell("h2")), Some(30));
+        app.press(KeyCode::Char('q')).unwrap();
+        assert!(app.closed());
+    }
+
+    #[test]
+    fn test_headless_playground() {
+        let mut app = HeadlessApp::playground();
+        app.type_str("p(h1) := 4").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        app.type_str("a := p(h1) * p(h1)").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::Playground(_)));
+        assert_eq!(app.value(a()), Some(16));
+        // q is typed into the input field
+        app.press(KeyCode::Char('q')).unwrap();
+        assert!(!app.closed());
+    }
+
+    #[test]
+    fn test_headless_playground_forbidden_instruction() {
+        let mut app = super::app("", true);
+        app.instruction_config = Some(InstructionConfig {
+            allowed_instruction_identifiers: Some(
+                utils::build_instruction_whitelist(
+                    vec!["M := C".to_string(), "A := M".to_string()],
+                    "test",
+                )
+                .unwrap(),
+            ),
+            ..InstructionConfig::default()
+        });
+        let mut app = HeadlessApp::from_app(app);
+        app.type_str("p(h1) := 4").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        app.type_str("a := p(h1) * 2").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::BuildProgramError(_, true)));
+        assert_eq!(
+            app.app.forbidden_identifier,
+            Some("A := M OP C".to_string())
+        );
+        assert_eq!(app.value(a()), None);
+        // the popup is closed and the next instruction can be entered
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::Playground(_)));
+        app.type_str("a := p(h1)").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert_eq!(app.value(a()), Some(4));
+    }
+
+    #[test]
+    fn test_headless_custom_instruction_suggestion() {
+        let mut app = HeadlessApp::new(PROGRAM);
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('i')])
+            .unwrap();
+        app.type_str("a := h2").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        let State::CustomInstructionError(e, _) = app.state() else {
+            panic!("expected parse error, got {:?}", app.state());
+        };
+        // the memory cells of the runtime are suggested
+        assert_eq!(e.suggestion, Some("Did you mean 'p(h2)'?".to_string()));
+    }
+
+    #[test]
+    fn test_headless_statistics() {
+        let config = StatisticsConfig {
+            enabled: true,
+            file: Some("statistics.jsonl".to_string()),
+        };
+        let statistics = Statistics::new(&config, "test.alpha", SharedClock::default());
+        let mut app = HeadlessApp::from_app(super::app(PROGRAM, false).with_statistics(statistics));
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('i')])
+            .unwrap();
+        app.type_str("a := 10").unwrap();
+        app.press_all(&[KeyCode::Enter, KeyCode::Char('r'), KeyCode::Left])
+            .unwrap();
+        app.press_all(&[KeyCode::Char('t'), KeyCode::Char('s')])
+            .unwrap();
+        let statistics = app.statistics().unwrap();
+        assert_eq!(statistics.file, "test.alpha");
+        assert_eq!(statistics.programs_run, 2);
+        assert_eq!(statistics.programs_finished, 1);
+        assert_eq!(statistics.instructions_executed, 6);
+        assert_eq!(statistics.custom_instructions, 1);
+        assert_eq!(statistics.runtime_errors, 0);
+        assert_eq!(
+            statistics.features.iter().copied().collect::<Vec<_>>(),
+            vec![UsedFeature::CustomInstructions, UsedFeature::Timeline]
+        );
+    }
+
+    #[test]
+    fn test_headless_quit_with_runtime_error() {
+        let mut app = HeadlessApp::new("a := 1\npop");
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('n'), KeyCode::Char('n')])
+            .unwrap();
+        assert!(matches!(app.state(), State::RuntimeError(_, false)));
+        // the error is returned, so that it is printed when the app is closed
+        assert!(app.press(KeyCode::Char('q')).is_err());
+    }
+
+    #[test]
+    fn test_headless_division_by_zero() {
+        let program = "a := 0\na1 := 4 / a\np(h1) := a1";
+        // the failed instruction can be run again after the memory was fixed
+        let mut app = HeadlessApp::new(program);
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('n')])
+            .unwrap();
+        assert!(matches!(app.state(), State::RuntimeError(_, false)));
+        // the popup can only be closed if the program was loaded with --resumable-division-by-zero
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::RuntimeError(_, false)));
+        app.press(KeyCode::Char('i')).unwrap();
+        app.type_str("a := 2").unwrap();
+        app.press_all(&[KeyCode::Enter, KeyCode::Char('n')])
+            .unwrap();
+        assert_eq!(app.value(MemoryLocation::Accumulator(1)), Some(2));
+        let mut app =
+            HeadlessApp::from_app(super::app(program, false).with_resumable_division_by_zero(true));
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('n'), KeyCode::Enter])
+            .unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        assert_eq!(app.runtime().next_instruction_index(), 1);
+    }
+
+    #[test]
+    fn test_headless_refactoring_without_program_file() {
+        // the program file can not be changed, if the program was read from stdin or a URL
+        for filename in [utils::STDIN_FILENAME, "https://example.com/program.alpha"] {
+            let mut app = HeadlessApp::from_app(super::app_with_filename(PROGRAM, filename, false));
+            app.press(KeyCode::Char('e')).unwrap();
+            assert_eq!(app.state(), &State::Default);
+            assert!(app
+                .app
+                .unavailable_notice
+                .as_ref()
+                .is_some_and(|notice| notice.contains("not read from a local file")));
+            // lines can not be extracted into a subroutine in debug select mode
+            let program_builder: ProgramBuilder =
+                Box::new(|lines: &[String]| test_utils::runtime_from_str(&lines.join("\n")));
+            let mut app = HeadlessApp::from_app(
+                super::app_with_filename(PROGRAM, filename, false)
+                    .with_program_builder(program_builder),
+            );
+            app.press_all(&[KeyCode::Char('d'), KeyCode::Char('e')])
+                .unwrap();
+            assert!(matches!(app.state(), State::DebugSelect(_, _)));
+        }
+    }
+
+    #[test]
+    fn test_headless_branch_decision() {
+        let mut app =
+            HeadlessApp::new("a := 0\nloop: a := a + 1\nif a < 2 then goto loop\np(h1) := a");
+        // the jump in line 3 is taken after the first iteration
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('n')])
+            .unwrap();
+        assert_eq!(app.app.branch_decision, None);
+        app.press(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.app.branch_decision, Some(BranchDecision::Taken(1)));
+        app.press(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.app.branch_decision, None);
+        app.press(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.app.branch_decision, Some(BranchDecision::NotTaken(3)));
+    }
+
+    #[test]
+    fn test_headless_step_count() {
+        let mut app = HeadlessApp::new("a := 0\nloop: a := a + 1\ngoto loop");
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char(':')])
+            .unwrap();
+        assert!(matches!(app.state(), State::StepCount(_)));
+        // the input is not a number, nothing is run
+        app.type_str("step x").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::StepCount(_)));
+        app.press(KeyCode::Esc).unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        // the first instruction is run on start
+        app.press(KeyCode::Char(':')).unwrap();
+        app.type_str("step 50").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        assert_eq!(app.runtime().instruction_runs(), 51);
+        assert_eq!(app.value(a()), Some(25));
+        app.press(KeyCode::Char(':')).unwrap();
+        app.type_str("3").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        assert_eq!(app.runtime().instruction_runs(), 54);
+    }
+
+    #[test]
+    fn test_headless_skip_instruction() {
+        let mut app = HeadlessApp::new(PROGRAM);
+        // the first instruction is run on start, the second instruction is skipped
+        app.press_all(&[KeyCode::Char('s'), KeyCode::Char('k')])
+            .unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        assert_eq!(app.runtime().next_instruction_index(), 2);
+        assert_eq!(app.runtime().instruction_runs(), 1);
+        let skipped = app.app.trace.entries().next_back().unwrap();
+        assert!(skipped.skipped);
+        assert_eq!(skipped.line, 2);
+        app.press(KeyCode::Char('n')).unwrap();
+        assert_eq!(app.value(cell("h1")), Some(1));
+        // instructions can also be skipped in debug select mode
+        app.press_all(&[KeyCode::Char('d'), KeyCode::Char('k')])
+            .unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        app.press(KeyCode::Char('k')).unwrap();
+        assert!(matches!(app.state(), State::Finished(_)));
+        assert_eq!(app.value(cell("h2")), None);
+        assert_eq!(app.app.trace.entries().filter(|e| e.skipped).count(), 3);
+    }
+
+    #[test]
+    fn test_headless_confirm_quit_trace() {
+        const LOOP: &str = "a := 0\nloop: a := a + 1\ngoto loop";
+        let mut app = HeadlessApp::from_app(super::app(LOOP, false).with_confirm_quit(true));
+        app.press(KeyCode::Char('s')).unwrap();
+        for _ in 0..98 {
+            app.press(KeyCode::Char('n')).unwrap();
+        }
+        // a short run can be repeated, so the trace is not offered for saving
+        assert_eq!(app.runtime().instruction_runs(), 99);
+        assert!(app.app.unsaved_artifacts().is_empty());
+        app.press(KeyCode::Char('n')).unwrap();
+        app.press(KeyCode::Char('q')).unwrap();
+        assert!(!app.closed());
+        let State::ConfirmQuit(_, unsaved) = app.state() else {
+            panic!("quitting should be confirmed");
+        };
+        assert_eq!(unsaved.len(), 1);
+        let (artifact, path) = &unsaved[0];
+        assert_eq!(*artifact, UnsavedArtifact::Trace);
+        assert!(path.starts_with("trace") && path.ends_with(".txt"));
+        assert_eq!(
+            app.app.describe_unsaved(*artifact),
+            "trace of the last 100 executed instructions"
+        );
+        let content = app.app.unsaved_content(*artifact);
+        assert_eq!(content.lines().count(), 100);
+        assert_eq!(content.lines().last().unwrap(), " 100   2: a0 := a0 + 1");
+        // going back keeps the program running
+        app.press(KeyCode::Esc).unwrap();
+        assert!(matches!(app.state(), State::Running(_)));
+        app.press(KeyCode::Esc).unwrap();
+        assert!(matches!(app.state(), State::ConfirmQuit(_, _)));
+        // quit without saving
+        app.press(KeyCode::Char('q')).unwrap();
+        assert!(app.closed());
+        // the confirmation can be disabled
+        let mut app = HeadlessApp::new(LOOP);
+        app.press(KeyCode::Char('s')).unwrap();
+        for _ in 0..100 {
+            app.press(KeyCode::Char('n')).unwrap();
+        }
+        app.press(KeyCode::Char('q')).unwrap();
+        assert!(app.closed());
+    }
+
+    #[test]
+    fn test_headless_confirm_quit_playground() {
+        let mut app = HeadlessApp::playground();
+        // nothing is lost
+        app.press(KeyCode::Esc).unwrap();
+        assert!(app.closed());
+        let mut app = HeadlessApp::playground();
+        app.type_str("a := 4").unwrap();
+        app.press(KeyCode::Enter).unwrap();
+        app.press(KeyCode::Esc).unwrap();
+        let State::ConfirmQuit(_, unsaved) = app.state() else {
+            panic!("quitting should be confirmed");
+        };
+        assert_eq!(unsaved[0].0, UnsavedArtifact::PlaygroundSession);
+        assert!(unsaved[0].1.ends_with(".alpha"));
+        assert_eq!(
+            app.app.unsaved_content(UnsavedArtifact::PlaygroundSession),
+            "a := 4\n"
+        );
+        app.press(KeyCode::Esc).unwrap();
+        assert!(matches!(app.state(), State::Playground(_)));
+    }
+}
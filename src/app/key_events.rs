@@ -0,0 +1,149 @@
+use std::collections::VecDeque;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Number of key events that are displayed by the key diagnostic overlay.
+const KEY_LOG_LENGTH: usize = 8;
+
+/// Converts a key event into the form that the key handling of the app expects.
+///
+/// Terminals report some keys differently, depending on the platform:
+/// - On Windows, characters that are typed with AltGr are reported with the Ctrl and Alt modifiers, e.g. `@` on a
+///   German keyboard is reported as Ctrl+Alt+@. The modifiers are removed, as the character is already the typed one.
+/// - Characters that are typed with Shift are reported with the Shift modifier by some terminals, the modifier is
+///   removed, as the character is already upper case.
+/// - Some terminals send the control characters DEL and BS as characters when Backspace is pressed, they are converted
+///   into Backspace.
+pub fn normalize(key: KeyEvent) -> KeyEvent {
+    normalize_for(key, cfg!(windows))
+}
+
+/// Converts a key event like [`normalize`], `windows` determines if the key event was reported by a Windows terminal.
+fn normalize_for(mut key: KeyEvent, windows: bool) -> KeyEvent {
+    if let KeyCode::Char(c) = key.code {
+        if c == '\u{7f}' || c == '\u{8}' {
+            key.code = KeyCode::Backspace;
+            return key;
+        }
+        if windows
+            && key
+                .modifiers
+                .contains(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            key.modifiers
+                .remove(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        }
+        key.modifiers.remove(KeyModifiers::SHIFT);
+    }
+    key
+}
+
+/// Records the last key events, they are displayed by the key diagnostic overlay that is enabled with `--debug-keys`.
+#[derive(Debug, Default)]
+pub struct KeyLog {
+    /// The key events as they were reported by the terminal and after they were normalized, the last event is the
+    /// newest.
+    events: VecDeque<(KeyEvent, KeyEvent)>,
+}
+
+impl KeyLog {
+    /// Records a key event, `raw` is the event that was reported by the terminal.
+    pub fn record(&mut self, raw: KeyEvent, normalized: KeyEvent) {
+        if self.events.len() == KEY_LOG_LENGTH {
+            self.events.pop_front();
+        }
+        self.events.push_back((raw, normalized));
+    }
+
+    /// Returns one line for each recorded key event, the newest event is the last line.
+    ///
+    /// If the key event was changed by the normalization, the normalized event is displayed after an arrow.
+    pub fn lines(&self) -> Vec<String> {
+        self.events
+            .iter()
+            .map(|(raw, normalized)| {
+                let mut line = format!("{} {:?}", describe(raw), raw.kind);
+                if raw.state.bits() != 0 {
+                    line.push_str(&format!(" {:?}", raw.state));
+                }
+                if raw.code != normalized.code || raw.modifiers != normalized.modifiers {
+                    line.push_str(&format!(" -> {}", describe(normalized)));
+                }
+                line
+            })
+            .collect()
+    }
+}
+
+/// Returns the modifiers and the key code of a key event, e.g. `CONTROL+ALT+Char('q')`.
+pub fn describe(key: &KeyEvent) -> String {
+    key.modifiers
+        .iter_names()
+        .map(|(name, _)| name.to_string())
+        .chain(std::iter::once(format!("{:?}", key.code)))
+        .collect::<Vec<_>>()
+        .join("+")
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+    use super::{normalize_for, KeyLog};
+
+    #[test]
+    fn test_normalize_altgr_on_windows() {
+        let key = KeyEvent::new(
+            KeyCode::Char('@'),
+            KeyModifiers::CONTROL | KeyModifiers::ALT,
+        );
+        assert_eq!(
+            normalize_for(key, true),
+            KeyEvent::new(KeyCode::Char('@'), KeyModifiers::NONE)
+        );
+        // Ctrl+Alt combinations are only caused by AltGr on windows
+        assert_eq!(normalize_for(key, false), key);
+    }
+
+    #[test]
+    fn test_normalize_shift_and_backspace() {
+        assert_eq!(
+            normalize_for(
+                KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT),
+                false
+            ),
+            KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE)
+        );
+        assert_eq!(
+            normalize_for(
+                KeyEvent::new(KeyCode::Char('\u{7f}'), KeyModifiers::NONE),
+                false
+            )
+            .code,
+            KeyCode::Backspace
+        );
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(normalize_for(ctrl_z, true), ctrl_z);
+    }
+
+    #[test]
+    fn test_key_log() {
+        let mut log = KeyLog::default();
+        for _ in 0..10 {
+            log.record(
+                KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Delete, KeyModifiers::NONE),
+            );
+        }
+        let raw = KeyEvent::new_with_kind(
+            KeyCode::Char('G'),
+            KeyModifiers::SHIFT,
+            KeyEventKind::Release,
+        );
+        log.record(raw, normalize_for(raw, false));
+        let lines = log.lines();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], "Delete Press");
+        assert_eq!(lines[7], "SHIFT+Char('G') Release -> Char('G')");
+    }
+}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    mem::{discriminant, Discriminant},
+};
 
 use anyhow::{anyhow, Result};
 use ratatui::{
@@ -12,6 +16,131 @@ use super::{ui::style::SharedTheme, State};
 pub struct KeybindingHints {
     hints: HashMap<String, KeybindingHint>,
     theme: SharedTheme,
+    /// If the keybinding hint to expand structured loops should be shown.
+    loop_expansion: bool,
+    /// If the keybinding hint to toggle breakpoint groups should be shown.
+    breakpoint_groups: bool,
+    /// If the keybinding hint to fold subroutines should be shown.
+    folding: bool,
+    /// If the keybinding hint to toggle the label legend should be shown.
+    labels: bool,
+    /// If the keybinding hint to run custom instructions should be shown.
+    custom_instructions: bool,
+    /// If the label legend is displayed, its labels can be selected.
+    label_legend: bool,
+    /// If the popup of a division by zero can be closed to continue the program.
+    resumable_division_by_zero: bool,
+    /// Categories of the hints in the order in which they are displayed, set depending on the state of the app.
+    categories: [HintCategory; 6],
+    /// If the hints can be paged through, because the key to show the next page is available in the current state.
+    paging: bool,
+    /// Page of hints that is displayed, if the hints do not fit into [`MAX_HINT_LINES`] lines.
+    page: usize,
+    /// State of the app for which the page was selected, the first page is displayed again when the state changes.
+    paged_state: Option<Discriminant<State>>,
+    /// Actions that are not available in the current configuration and the reason why, by the label of their hint.
+    ///
+    /// The hints of these actions are displayed as disabled.
+    unavailable: HashMap<String, String>,
+}
+
+/// Maximum number of lines of keybinding hints, further hints are displayed on the next page.
+const MAX_HINT_LINES: usize = 3;
+
+/// Category of a keybinding hint, the hints of a category are displayed next to each other.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum HintCategory {
+    /// Quitting, confirming and cancelling.
+    #[default]
+    General,
+    /// Starting, running and resetting the program.
+    Execution,
+    /// Moving through lists, the input and the timeline.
+    Navigation,
+    Breakpoints,
+    /// Changing what is displayed.
+    View,
+    /// Changing the program.
+    Refactoring,
+}
+
+impl HintCategory {
+    /// Returns the categories in the order in which they are displayed in `state`, the most relevant category first.
+    fn by_relevance(state: &State) -> [HintCategory; 6] {
+        use HintCategory::{Breakpoints, Execution, General, Navigation, Refactoring, View};
+        match state {
+            State::Default => [
+                General,
+                Execution,
+                View,
+                Refactoring,
+                Breakpoints,
+                Navigation,
+            ],
+            State::DebugSelect(_, _) => [
+                General,
+                Navigation,
+                Breakpoints,
+                Execution,
+                Refactoring,
+                View,
+            ],
+            State::CustomInstruction(_)
+            | State::Playground(_)
+            | State::Rename(_)
+            | State::StepCount(_)
+            | State::ExtractSubroutine(_, _, _) => [
+                General,
+                Navigation,
+                Execution,
+                Breakpoints,
+                View,
+                Refactoring,
+            ],
+            _ => [
+                General,
+                Execution,
+                Navigation,
+                Breakpoints,
+                View,
+                Refactoring,
+            ],
+        }
+    }
+}
+
+/// Returns the text of the hint that shows the next page of hints.
+fn more_hints_text(page: usize, pages: usize) -> String {
+    format!("More hints {page}/{pages} [h]")
+}
+
+/// Splits hints with the text lengths `lengths` into rows that fit into `width`, the hints are separated by a space.
+///
+/// `reserved` characters are kept free at the end of every [`MAX_HINT_LINES`]th row, if it is not zero. Returns the
+/// indices of the hints in each row.
+fn hint_rows(lengths: &[usize], width: usize, reserved: usize) -> Vec<Vec<usize>> {
+    let mut rows: Vec<Vec<usize>> = vec![Vec::new()];
+    let mut line_length = 0;
+    for (idx, length) in lengths.iter().enumerate() {
+        let mut available = width;
+        if reserved > 0 && rows.len().is_multiple_of(MAX_HINT_LINES) {
+            available = available.saturating_sub(reserved + 1);
+        }
+        let row = rows.last_mut().expect("rows should not be empty");
+        let needed = if row.is_empty() {
+            *length
+        } else {
+            line_length + 1 + length
+        };
+        if row.is_empty() || needed <= available {
+            row.push(idx);
+            line_length = needed;
+        } else {
+            rows.push(vec![idx]);
+            line_length = *length;
+        }
+    }
+    rows
 }
 
 impl KeybindingHints {
@@ -19,43 +148,158 @@ impl KeybindingHints {
         Ok(Self {
             hints: default_keybindings()?,
             theme,
+            loop_expansion: false,
+            breakpoint_groups: false,
+            folding: false,
+            labels: false,
+            label_legend: false,
+            custom_instructions: true,
+            resumable_division_by_zero: false,
+            categories: HintCategory::by_relevance(&State::Default),
+            paging: false,
+            page: 0,
+            paged_state: None,
+            unavailable: HashMap::new(),
         })
     }
 
+    /// Sets if the keybinding hint to expand structured loops should be shown.
+    pub fn with_loop_expansion(mut self, loop_expansion: bool) -> Self {
+        self.loop_expansion = loop_expansion;
+        self
+    }
+
+    /// Sets if the keybinding hint to toggle breakpoint groups should be shown.
+    pub fn with_breakpoint_groups(mut self, breakpoint_groups: bool) -> Self {
+        self.breakpoint_groups = breakpoint_groups;
+        self
+    }
+
+    /// Sets if the keybinding hint to fold subroutines should be shown.
+    pub fn with_folding(mut self, folding: bool) -> Self {
+        self.folding = folding;
+        self
+    }
+
+    /// Sets if the keybinding hint to fold subroutines should be shown, used when the program has changed.
+    pub fn set_folding(&mut self, folding: bool) {
+        self.folding = folding;
+    }
+
+    /// Sets if the keybinding hint to run custom instructions should be shown.
+    pub fn with_custom_instructions(mut self, custom_instructions: bool) -> Self {
+        self.custom_instructions = custom_instructions;
+        self
+    }
+
+    /// Sets if the keybinding hint to close the popup of a division by zero should be shown.
+    pub fn with_resumable_division_by_zero(mut self, resumable_division_by_zero: bool) -> Self {
+        self.resumable_division_by_zero = resumable_division_by_zero;
+        self
+    }
+
+    /// Sets if the keybinding hint to toggle the label legend should be shown.
+    pub fn with_labels(mut self, labels: bool) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Sets if the keybinding hint to toggle the label legend should be shown, used when the program has changed.
+    pub fn set_labels(&mut self, labels: bool) {
+        self.labels = labels;
+    }
+
+    /// Sets if the label legend is displayed.
+    pub fn set_label_legend(&mut self, label_legend: bool) {
+        self.label_legend = label_legend;
+    }
+
+    /// Marks the action with the hint label `action` as unavailable because of `reason`, or as available again if
+    /// `reason` is `None`.
+    pub fn set_unavailable(&mut self, action: &str, reason: Option<&str>) {
+        match reason {
+            Some(reason) => self
+                .unavailable
+                .insert(action.to_string(), reason.to_string()),
+            None => self.unavailable.remove(action),
+        };
+    }
+
+    /// Returns a message that explains why the action of `key` is not available, if its hint is displayed as
+    /// unavailable.
+    pub fn unavailable_reason(&self, key: &str) -> Option<String> {
+        let hint = self.hints.get(key).filter(|hint| hint.shown)?;
+        let reason = self.unavailable.get(&hint.label())?;
+        Some(format!("{} is not available: {reason}", hint.label()))
+    }
+
+    /// Shows the next page of keybinding hints, the first page follows the last page.
+    pub fn next_page(&mut self) {
+        self.page += 1;
+    }
+
     /// Returns the keybinding hint paragraph ready to be printed.
     ///
-    /// `width` is used to determine how many keybinding hints can be printed in one line.
+    /// `width` is used to determine how many keybinding hints can be printed in one line. The hints are grouped by
+    /// category, the categories that are most relevant for the current state are displayed first. If paging is
+    /// possible and the hints need more than [`MAX_HINT_LINES`] lines, only the current page is displayed followed by
+    /// a hint to show the next page.
     ///
     /// Return value `u16` is the amount of lines that this paragraph contains.
-    pub fn keybinding_hint_paragraph(&self, width: u16) -> (Paragraph, u16) {
+    pub fn keybinding_hint_paragraph(&self, width: u16) -> (Paragraph<'_>, u16) {
         let mut active_hints = self.active_keybinds();
-        active_hints.sort_by_key(|f| f.order());
-        let mut styled_keybinds_row = Vec::new();
+        active_hints.sort_by_key(|hint| {
+            let category = self
+                .categories
+                .iter()
+                .position(|c| *c == hint.category)
+                .unwrap_or(self.categories.len());
+            (category, hint.order(), hint.key.clone())
+        });
+        let texts = active_hints
+            .iter()
+            .map(|hint| format!("{} [{}]", hint.label(), hint.key))
+            .collect::<Vec<_>>();
+        let lengths = texts.iter().map(|t| t.chars().count()).collect::<Vec<_>>();
+        let mut pages = vec![hint_rows(&lengths, width as usize, 0)];
+        let mut more_hint = None;
+        if self.paging && pages[0].len() > MAX_HINT_LINES {
+            // the page count has at most two digits for any realistic terminal
+            let reserved = more_hints_text(99, 99).chars().count();
+            pages = hint_rows(&lengths, width as usize, reserved)
+                .chunks(MAX_HINT_LINES)
+                .map(<[Vec<usize>]>::to_vec)
+                .collect();
+            more_hint = Some(more_hints_text(self.page % pages.len() + 1, pages.len()));
+        }
+        let page = &pages[self.page % pages.len()];
+
         let mut styled_keybinds = Vec::new();
-        let mut first_hint = true;
-        let mut line_length = 0;
-        for hint in &active_hints {
-            if first_hint {
-                first_hint = false;
-            } else {
-                styled_keybinds_row
-                    .push(Span::from(" ").style(self.theme.keybinding_hint_paragraph()));
-                line_length += 1;
+        for (row_idx, row) in page.iter().enumerate() {
+            let mut styled_keybinds_row = Vec::new();
+            for (idx, hint_idx) in row.iter().enumerate() {
+                if idx > 0 {
+                    styled_keybinds_row
+                        .push(Span::from(" ").style(self.theme.keybinding_hint_paragraph()));
+                }
+                styled_keybinds_row.push(
+                    Span::from(texts[*hint_idx].clone())
+                        .style(self.theme.keybinding_hints(active_hints[*hint_idx].enabled)),
+                );
             }
-            let text = format!("{} [{}]", hint.label(), hint.key);
-            let remaining_space = usize::saturating_sub(width as usize, text.len());
-            if line_length > remaining_space {
-                line_length = 0;
-                styled_keybinds.push(Line::from(styled_keybinds_row));
-                styled_keybinds_row = Vec::new();
+            if row_idx == page.len() - 1 {
+                if let Some(more_hint) = &more_hint {
+                    styled_keybinds_row
+                        .push(Span::from(" ").style(self.theme.keybinding_hint_paragraph()));
+                    styled_keybinds_row.push(
+                        Span::from(more_hint.clone()).style(self.theme.keybinding_hints(true)),
+                    );
+                }
             }
-            line_length += text.len();
-            styled_keybinds_row
-                .push(Span::from(text).style(self.theme.keybinding_hints(hint.enabled)));
+            styled_keybinds.push(Line::from(styled_keybinds_row));
         }
-        styled_keybinds.push(Line::from(styled_keybinds_row));
 
-        let hight = styled_keybinds.len() as u16;
+        let hight = styled_keybinds.len().max(1) as u16;
         let keybinding_hints =
             Paragraph::new(styled_keybinds).style(self.theme.keybinding_hint_paragraph());
         (keybinding_hints, hight)
@@ -143,38 +387,119 @@ impl KeybindingHints {
     pub fn update(&mut self, state: &State) -> Result<()> {
         // reset keybinding hints to be able to configure them properly for current app state
         self.hints.values_mut().for_each(|x| x.reset());
+        self.categories = HintCategory::by_relevance(state);
+        // the key to show the next page is entered as text in the input fields
+        self.paging = !matches!(
+            state,
+            State::CustomInstruction(_)
+                | State::Playground(_)
+                | State::Rename(_)
+                | State::StepCount(_)
+                | State::ExtractSubroutine(_, _, _)
+        );
+        if self.paged_state != Some(discriminant(state)) {
+            self.paged_state = Some(discriminant(state));
+            self.page = 0;
+        }
+
+        if self.loop_expansion
+            && matches!(
+                state,
+                State::Default | State::Running(_) | State::DebugSelect(_, _)
+            )
+        {
+            self.show_and_enable("x");
+        }
+
+        if matches!(state, State::Running(_) | State::Finished(_)) {
+            self.show_and_enable(&KeySymbol::ArrowLeft.to_string());
+            self.show_and_enable(&KeySymbol::ArrowRight.to_string());
+            self.set_state(&KeySymbol::ArrowLeft.to_string(), 1)?;
+            self.set_state(&KeySymbol::ArrowRight.to_string(), 1)?;
+        }
+
+        if self.labels
+            && matches!(
+                state,
+                State::Default | State::Running(_) | State::DebugSelect(_, _) | State::Finished(_)
+            )
+        {
+            self.show_and_enable("l");
+        }
+
+        if matches!(
+            state,
+            State::Default | State::Running(_) | State::DebugSelect(_, _) | State::Finished(_)
+        ) {
+            self.show_and_enable("o");
+        }
+
+        if matches!(state, State::Running(_) | State::DebugSelect(_, _)) {
+            self.show_and_enable("a");
+            if self.breakpoint_groups {
+                self.show_and_enable("1-9");
+            }
+        }
+
+        if self.label_legend && matches!(state, State::Default | State::Running(_)) {
+            self.show_and_enable(&KeySymbol::ArrowUp.to_string());
+            self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+            self.show_and_enable(&KeySymbol::Enter.to_string());
+            self.set_state(&KeySymbol::Enter.to_string(), 8)?;
+        }
 
         // set more specific keybinding hints
         match state {
             State::Default => {
                 self.show_and_enable("q");
+                self.show_and_enable("v");
                 self.show_and_enable("s");
                 self.show_and_enable("r");
                 self.show_and_enable("d");
                 self.show_and_enable("i");
                 self.show_and_enable("c");
+                self.show_and_enable("e");
+                self.show_and_enable("p");
             }
             State::Running(breakpoint_set) => {
                 self.show_and_enable("q");
+                self.show_and_enable("v");
                 self.show_and_enable("n");
                 self.show_and_enable("d");
                 self.show_and_enable("t");
                 self.show_and_enable("i");
                 self.show_and_enable("c");
                 self.show_and_enable("r");
+                self.show_and_enable(":");
+                self.show_and_enable("k");
+                self.show_and_enable("p");
                 if *breakpoint_set {
                     self.set_state("r", 1)?;
                 }
             }
-            State::DebugSelect(_, _) => {
+            State::DebugSelect(previous_state, _) => {
                 self.show_and_enable("q");
+                self.show_and_enable("v");
                 self.show_and_enable("d");
                 self.show_and_enable("c");
                 self.show_and_enable("b");
                 self.show_and_enable("j");
+                self.show_and_enable("p");
                 self.show_and_enable(&KeySymbol::ArrowUp.to_string());
                 self.show_and_enable(&KeySymbol::ArrowDown.to_string());
+                if self.folding {
+                    self.show_and_enable("f");
+                }
                 self.set_state("d", 1)?;
+                if matches!(**previous_state, State::Running(_)) {
+                    self.show_and_enable("k");
+                }
+                // lines can only be extracted when the program is not running
+                if **previous_state == State::Default {
+                    self.show_and_enable("m");
+                    self.show_and_enable("e");
+                    self.set_state("e", 1)?;
+                }
             }
             State::Finished(message_shown) => {
                 self.show_and_enable("q");
@@ -186,7 +511,7 @@ impl KeybindingHints {
                 }
                 self.set_state("d", 2)?;
             }
-            State::RuntimeError(_, is_playground) => {
+            State::RuntimeError(e, is_playground) => {
                 self.show_and_enable("q");
 
                 if *is_playground {
@@ -194,9 +519,21 @@ impl KeybindingHints {
                     self.show(&KeySymbol::Enter.to_string());
                 } else {
                     self.show_and_enable("t");
+                    if e.is_retryable() {
+                        self.show_and_enable("i");
+                        self.show_and_enable("n");
+                        self.set_state("n", 1)?;
+                    }
+                    // the popup is closed and the program is paused
+                    if self.resumable_division_by_zero && e.is_division_by_zero() {
+                        self.show_and_enable(&KeySymbol::Enter.to_string());
+                        self.set_state(&KeySymbol::Enter.to_string(), 2)?;
+                    }
                 }
             }
-            State::CustomInstructionError(_, _) | State::BuildProgramError(_) => {
+            State::CustomInstructionError(_, _)
+            | State::BuildProgramError(_, _)
+            | State::RefactorError(_) => {
                 self.show_and_enable("q");
 
                 self.show_and_enable(&KeySymbol::Enter.to_string());
@@ -234,6 +571,63 @@ impl KeybindingHints {
                     self.enable(&KeySymbol::Tab.to_string())
                 }
             }
+            State::ConfirmCustomInstruction(_, _, preview) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                if preview.is_ok() {
+                    self.set_state(&KeySymbol::Enter.to_string(), 5)?;
+                } else {
+                    self.set_state(&KeySymbol::Enter.to_string(), 2)?;
+                }
+            }
+            State::ConfirmQuit(_, _) => {
+                self.show_and_enable("s");
+                self.set_state("s", 1)?;
+                self.show_and_enable("quit_without_saving");
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+            }
+            State::ConfirmReset(_) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.set_state(&KeySymbol::Enter.to_string(), 6)?;
+            }
+            State::ConfirmRestore(_) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.set_state(&KeySymbol::Enter.to_string(), 7)?;
+                self.set_state(&KeySymbol::Escape.to_string(), 2)?;
+            }
+            State::StepCount(input_state) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.show_and_enable(&KeySymbol::ArrowLeft.to_string());
+                self.show_and_enable(&KeySymbol::ArrowRight.to_string());
+                self.set_state(&KeySymbol::Enter.to_string(), 9)?;
+                if super::parse_step_count(&input_state.input).is_none() {
+                    self.disable(&KeySymbol::Enter.to_string());
+                }
+            }
+            State::Rename(input_state) | State::ExtractSubroutine(input_state, _, _) => {
+                self.show_and_enable(&KeySymbol::Enter.to_string());
+                self.show_and_enable(&KeySymbol::Escape.to_string());
+                self.show_and_enable(&KeySymbol::ArrowLeft.to_string());
+                self.show_and_enable(&KeySymbol::ArrowRight.to_string());
+                if let State::Rename(_) = state {
+                    self.set_state(&KeySymbol::Enter.to_string(), 3)?;
+                    if input_state.input.split_whitespace().count() != 2 {
+                        self.disable(&KeySymbol::Enter.to_string());
+                    }
+                } else {
+                    self.set_state(&KeySymbol::Enter.to_string(), 4)?;
+                    if input_state.input.split_whitespace().count() != 1 {
+                        self.disable(&KeySymbol::Enter.to_string());
+                    }
+                }
+                if input_state.input.is_empty() {
+                    self.disable(&KeySymbol::ArrowLeft.to_string());
+                    self.disable(&KeySymbol::ArrowRight.to_string());
+                }
+            }
             State::Playground(state) => {
                 self.show_and_enable(&KeySymbol::Enter.to_string());
                 self.show_and_enable(&KeySymbol::Escape.to_string());
@@ -266,6 +660,17 @@ impl KeybindingHints {
                 }
             }
         }
+
+        if !self.custom_instructions {
+            self.hide("i");
+        }
+
+        // actions that are not available in the current configuration are displayed as disabled
+        for hint in self.hints.values_mut() {
+            if hint.shown && self.unavailable.contains_key(&hint.label()) {
+                hint.enabled = false;
+            }
+        }
         Ok(())
     }
 }
@@ -275,12 +680,30 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
     let mut hints = HashMap::new();
     hints.insert(
         "q".to_string(),
-        KeybindingHint::new(0, &format!("q|{}", KeySymbol::Escape), "Quit"),
+        KeybindingHint::new(0, &format!("q|{}", KeySymbol::Escape), "Quit")
+            .in_category(HintCategory::General),
+    );
+    hints.insert(
+        "s".to_string(),
+        KeybindingHint::new_many(vec![2, 2], "s", vec!["Start", "Save and quit"])?
+            .in_category(HintCategory::Execution),
+    );
+    hints.insert(
+        "quit_without_saving".to_string(),
+        KeybindingHint::new(3, "q", "Quit without saving").in_category(HintCategory::General),
     );
-    hints.insert("s".to_string(), KeybindingHint::new(2, "s", "Start"));
     hints.insert(
         "n".to_string(),
-        KeybindingHint::new_many(vec![4], "n", vec!["Run next instruction"])?,
+        KeybindingHint::new_many(
+            vec![4, 4],
+            "n",
+            vec!["Run next instruction", "Retry instruction"],
+        )?
+        .in_category(HintCategory::Execution),
+    );
+    hints.insert(
+        ":".to_string(),
+        KeybindingHint::new(5, ":", "Run steps").in_category(HintCategory::Execution),
     );
     hints.insert(
         "r".to_string(),
@@ -288,7 +711,8 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
             vec![2, 2],
             "r",
             vec!["Run to end", "Run to next breakpoint"],
-        )?,
+        )?
+        .in_category(HintCategory::Execution),
     );
     hints.insert(
         "d".to_string(),
@@ -300,64 +724,137 @@ fn default_keybindings() -> Result<HashMap<String, KeybindingHint>> {
                 "Exit debug select mode",
                 "Dismiss message",
             ],
-        )?,
+        )?
+        .in_category(HintCategory::Execution),
+    );
+    hints.insert(
+        "t".to_string(),
+        KeybindingHint::new(1, "t", "Reset").in_category(HintCategory::Execution),
     );
-    hints.insert("t".to_string(), KeybindingHint::new(1, "t", "Reset"));
     hints.insert(
         "b".to_string(),
-        KeybindingHint::new(8, "b", "Toggle breakpoint"),
+        KeybindingHint::new(8, "b", "Toggle breakpoint").in_category(HintCategory::Breakpoints),
     );
     hints.insert(
         "j".to_string(),
-        KeybindingHint::new(11, "j", "Jump to line"),
+        KeybindingHint::new(11, "j", "Jump to line").in_category(HintCategory::Execution),
+    );
+    hints.insert(
+        "k".to_string(),
+        KeybindingHint::new(11, "k", "Skip instruction").in_category(HintCategory::Execution),
     );
     hints.insert(
         KeySymbol::ArrowUp.to_string(),
-        KeybindingHint::new(12, &KeySymbol::ArrowUp.to_string(), "Up"),
+        KeybindingHint::new(12, &KeySymbol::ArrowUp.to_string(), "Up")
+            .in_category(HintCategory::Navigation),
     );
     hints.insert(
         KeySymbol::ArrowDown.to_string(),
-        KeybindingHint::new(13, &KeySymbol::ArrowDown.to_string(), "Down"),
+        KeybindingHint::new(13, &KeySymbol::ArrowDown.to_string(), "Down")
+            .in_category(HintCategory::Navigation),
     );
     hints.insert(
         "i".to_string(),
-        KeybindingHint::new(9, "i", "Run custom instruction"),
+        KeybindingHint::new(9, "i", "Run custom instruction").in_category(HintCategory::Execution),
     );
     hints.insert(
         "c".to_string(),
-        KeybindingHint::new(10, "c", "Toggle call stack"),
+        KeybindingHint::new(10, "c", "Toggle call stack").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "a".to_string(),
+        KeybindingHint::new(15, "a", "Toggle all breakpoints")
+            .in_category(HintCategory::Breakpoints),
+    );
+    hints.insert(
+        "1-9".to_string(),
+        KeybindingHint::new(16, "1-9", "Toggle breakpoint group")
+            .in_category(HintCategory::Breakpoints),
+    );
+    hints.insert(
+        "p".to_string(),
+        KeybindingHint::new(14, "p", "Presentation mode").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "f".to_string(),
+        KeybindingHint::new(8, "f", "Fold subroutine").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "l".to_string(),
+        KeybindingHint::new(10, "l", "Toggle labels").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "o".to_string(),
+        KeybindingHint::new(10, "o", "Toggle trace").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "v".to_string(),
+        KeybindingHint::new(10, "v", "Toggle ASCII values").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "x".to_string(),
+        KeybindingHint::new(10, "x", "Toggle loop expansion").in_category(HintCategory::View),
+    );
+    hints.insert(
+        "e".to_string(),
+        KeybindingHint::new_many(vec![10, 10], "e", vec!["Rename", "Extract subroutine"])?
+            .in_category(HintCategory::Refactoring),
+    );
+    hints.insert(
+        "m".to_string(),
+        KeybindingHint::new(10, "m", "Mark range start").in_category(HintCategory::Refactoring),
     );
     hints.insert(
         KeySymbol::ArrowLeft.to_string(),
-        KeybindingHint::new(10, &KeySymbol::ArrowLeft.to_string(), "Cursor left"),
+        KeybindingHint::new_many(
+            vec![10, 17],
+            &KeySymbol::ArrowLeft.to_string(),
+            vec!["Cursor left", "Step back in timeline"],
+        )?
+        .in_category(HintCategory::Navigation),
     );
     hints.insert(
         KeySymbol::ArrowRight.to_string(),
-        KeybindingHint::new(11, &KeySymbol::ArrowRight.to_string(), "Cursor right"),
+        KeybindingHint::new_many(
+            vec![11, 18],
+            &KeySymbol::ArrowRight.to_string(),
+            vec!["Cursor right", "Step forward in timeline"],
+        )?
+        .in_category(HintCategory::Navigation),
     );
     hints.insert(
         KeySymbol::Enter.to_string(),
         KeybindingHint::new_many(
-            vec![5, 5, 5],
+            vec![5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
             &KeySymbol::Enter.to_string(),
             vec![
                 "Run entered instruction",
                 "Run selected instruction",
                 "Close",
+                "Rename",
+                "Extract",
+                "Apply",
+                "Reset",
+                "Restore",
+                "Show label",
+                "Run steps",
             ],
-        )?,
+        )?
+        .in_category(HintCategory::General),
     );
     hints.insert(
         KeySymbol::Escape.to_string(),
         KeybindingHint::new_many(
-            vec![1, 1],
+            vec![1, 1, 1],
             &KeySymbol::Escape.to_string(),
-            vec!["Cancel", "Exit"],
-        )?,
+            vec!["Cancel", "Exit", "Discard"],
+        )?
+        .in_category(HintCategory::General),
     );
     hints.insert(
         KeySymbol::Tab.to_string(),
-        KeybindingHint::new(9, &KeySymbol::Tab.to_string(), "Fill in selected"),
+        KeybindingHint::new(9, &KeySymbol::Tab.to_string(), "Fill in selected")
+            .in_category(HintCategory::Navigation),
     );
     Ok(hints)
 }
@@ -379,6 +876,7 @@ pub struct KeybindingHint {
     shown: bool,
     /// Stores the index of the label and order that is currently active.
     state: usize,
+    category: HintCategory,
 }
 
 impl KeybindingHint {
@@ -393,6 +891,7 @@ impl KeybindingHint {
             enabled: false,
             shown: false,
             state: 0,
+            category: HintCategory::default(),
         }
     }
 
@@ -408,6 +907,7 @@ impl KeybindingHint {
             enabled,
             shown,
             state: 0,
+            category: HintCategory::default(),
         }
     }
 
@@ -427,13 +927,22 @@ impl KeybindingHint {
             enabled: false,
             shown: false,
             state: 0,
+            category: HintCategory::default(),
         })
     }
 
-    /// Reset the keybinding hint, meaning that the fields enabled and shown are set to false.
+    /// Sets the category of the keybinding hint.
+    fn in_category(mut self, category: HintCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Reset the keybinding hint, meaning that the fields enabled and shown are set to false and that the first
+    /// label is active.
     fn reset(&mut self) {
         self.enabled = false;
         self.shown = false;
+        self.state = 0;
     }
 
     /// Updates the state of this keybinding.
@@ -491,7 +1000,9 @@ mod tests {
 
     use crate::app::ui::style::{SharedTheme, Theme};
 
-    use super::{KeybindingHint, KeybindingHints};
+    use crate::app::State;
+
+    use super::{hint_rows, HintCategory, KeybindingHint, KeybindingHints, MAX_HINT_LINES};
 
     fn test_keybinding_hints() -> KeybindingHints {
         let mut hints = HashMap::new();
@@ -500,6 +1011,18 @@ mod tests {
         hints.insert("c".to_string(), KeybindingHint::new(0, "c", "test_label_3"));
         hints.insert("d".to_string(), KeybindingHint::new(0, "d", "test_label_4"));
         let mut hints = KeybindingHints {
+            loop_expansion: false,
+            breakpoint_groups: false,
+            folding: false,
+            labels: false,
+            label_legend: false,
+            custom_instructions: true,
+            resumable_division_by_zero: false,
+            categories: HintCategory::by_relevance(&State::Default),
+            paging: true,
+            page: 0,
+            paged_state: None,
+            unavailable: HashMap::new(),
             hints,
             theme: SharedTheme::new(Theme::default()),
         };
@@ -635,4 +1158,88 @@ mod tests {
                 true
             )));
     }
+
+    #[test]
+    fn test_hint_rows() {
+        assert_eq!(hint_rows(&[5, 5, 5], 11, 0), vec![vec![0, 1], vec![2]]);
+        // a hint that is wider than the terminal gets a row of its own
+        assert_eq!(hint_rows(&[20, 5], 11, 0), vec![vec![0], vec![1]]);
+        // space is kept free at the end of the last row of each page
+        let rows = hint_rows(&[5; 7], 11, 5);
+        assert_eq!(rows[MAX_HINT_LINES - 1], vec![4]);
+        assert_eq!(rows.len(), 4);
+    }
+
+    #[test]
+    fn test_keybinding_hints_grouped_by_category() {
+        let mut hints = test_keybinding_hints();
+        let b = KeybindingHint::new(0, "b", "test_label_2").in_category(HintCategory::Breakpoints);
+        hints.hints.insert("b".to_string(), b);
+        hints.show_and_enable("b");
+        hints.categories = HintCategory::by_relevance(&State::Default);
+        let (_, height) = hints.keybinding_hint_paragraph(200);
+        assert_eq!(height, 1);
+        // breakpoints are more relevant in debug select mode
+        let debug_select = State::DebugSelect(Box::new(State::Default), None);
+        assert_eq!(
+            HintCategory::by_relevance(&debug_select)[..3],
+            [
+                HintCategory::General,
+                HintCategory::Navigation,
+                HintCategory::Breakpoints
+            ]
+        );
+    }
+
+    #[test]
+    fn test_keybinding_hints_paging() {
+        let mut hints = test_keybinding_hints();
+        for key in ["b", "c", "d"] {
+            hints.show_and_enable(key);
+        }
+        // each hint needs a row of its own, so the hints don't fit on one page
+        let (_, height) = hints.keybinding_hint_paragraph(16);
+        assert_eq!(height as usize, MAX_HINT_LINES);
+        hints.next_page();
+        let (_, height) = hints.keybinding_hint_paragraph(16);
+        assert_eq!(height, 1);
+        hints.paging = false;
+        let (_, height) = hints.keybinding_hint_paragraph(16);
+        assert_eq!(height, 4);
+    }
+
+    #[test]
+    fn test_keybinding_hints_unavailable() {
+        let mut hints = test_keybinding_hints();
+        hints.set_unavailable("test_label_1", Some("not configured"));
+        hints.update(&State::Default).unwrap();
+        hints.show("a");
+        assert_eq!(
+            hints.unavailable_reason("a").as_deref(),
+            Some("test_label_1 is not available: not configured")
+        );
+        // hints that are not displayed have no reason
+        assert_eq!(hints.unavailable_reason("b"), None);
+        hints.set_unavailable("test_label_1", None);
+        assert_eq!(hints.unavailable_reason("a"), None);
+    }
+
+    #[test]
+    fn test_keybinding_hints_unavailable_disabled() {
+        let mut hints = KeybindingHints::new(SharedTheme::new(Theme::default())).unwrap();
+        hints.set_unavailable("Start", Some("not configured"));
+        hints.update(&State::Default).unwrap();
+        assert!(hints._status("s"));
+        assert!(!hints.hints.get("s").unwrap().enabled);
+        assert!(hints.hints.get("q").unwrap().enabled);
+    }
+
+    #[test]
+    fn test_keybinding_hints_custom_instructions_disabled() {
+        let hints = KeybindingHints::new(SharedTheme::new(Theme::default())).unwrap();
+        let mut hints = hints.with_custom_instructions(false);
+        hints.update(&State::Running(false)).unwrap();
+        assert!(!hints._status("i"));
+        assert!(hints._status("n"));
+    }
 }
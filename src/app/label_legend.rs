@@ -0,0 +1,136 @@
+use std::collections::BTreeSet;
+
+use ratatui::widgets::ListState;
+
+use crate::{
+    instructions::Instruction,
+    runtime::{builder::END_LABELS, Runtime},
+};
+
+/// Lists the labels of the program and counts how often each label was jumped to during the current run.
+#[derive(Debug, Clone, Default)]
+pub struct LabelLegend {
+    /// Labels of the program and the index of their line, sorted by line.
+    ///
+    /// The end labels and the labels of structured loops are not included.
+    labels: Vec<(String, usize)>,
+    /// Number of jumps to each label, in the order of `labels`.
+    jumps: Vec<usize>,
+    list_state: ListState,
+}
+
+impl LabelLegend {
+    /// Creates the legend for the labels of the program of the runtime, no label was jumped to yet.
+    pub fn new(runtime: &Runtime) -> Self {
+        let generated_labels = runtime
+            .structured_loops()
+            .iter()
+            .flat_map(|l| std::iter::once(&l.start_label).chain(l.exit_label.as_ref()))
+            .collect::<BTreeSet<_>>();
+        let mut labels = runtime
+            .control_flow()
+            .instruction_labels
+            .iter()
+            .filter(|(label, _)| {
+                !END_LABELS.contains(&label.as_str()) && !generated_labels.contains(label)
+            })
+            .map(|(label, idx)| (label.clone(), *idx))
+            .collect::<Vec<_>>();
+        labels.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        Self {
+            jumps: vec![0; labels.len()],
+            labels,
+            list_state: ListState::default(),
+        }
+    }
+
+    /// Counts the jump to a label, if `instruction` jumped to it.
+    ///
+    /// `next_idx` is the index of the instruction that is run after `instruction`.
+    pub fn record(&mut self, instruction: &Instruction, next_idx: usize) {
+        let label = match instruction {
+            Instruction::Goto(label)
+            | Instruction::JumpIf(_, _, _, label)
+            | Instruction::Call(label) => label,
+            _ => return,
+        };
+        if let Some(pos) = self
+            .labels
+            .iter()
+            .position(|(l, idx)| l == label && *idx == next_idx)
+        {
+            self.jumps[pos] += 1;
+        }
+    }
+
+    /// Forgets the counted jumps, used when the program is reset.
+    pub fn reset(&mut self) {
+        self.jumps.iter_mut().for_each(|jumps| *jumps = 0);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// Returns the labels with the index of their line and the number of jumps to them, sorted by line.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, usize, usize)> {
+        self.labels
+            .iter()
+            .zip(&self.jumps)
+            .map(|((label, idx), jumps)| (label.as_str(), *idx, *jumps))
+    }
+
+    /// Returns the index of the line of the selected label.
+    pub fn selected_line(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|pos| self.labels.get(pos))
+            .map(|(_, idx)| *idx)
+    }
+
+    pub fn list_state_mut(&mut self) -> &mut ListState {
+        &mut self.list_state
+    }
+
+    /// Number of labels in the legend.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::builder::RuntimeBuilder;
+
+    use super::LabelLegend;
+
+    #[test]
+    fn test_label_legend_counts_jumps() {
+        let lines = "a0 := 3\nloop: a0 := a0 - 1\nif a0 > 0 then goto loop\ncall done\ngoto end\ndone: a1 := 1\nreturn"
+            .lines()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        let mut rt = RuntimeBuilder::new(&lines, "test")
+            .unwrap()
+            .build()
+            .unwrap();
+        let mut legend = LabelLegend::new(&rt);
+        while !rt.finished() {
+            let instruction = rt.instructions()[rt.next_instruction_index()].clone();
+            rt.step().unwrap();
+            legend.record(&instruction, rt.next_instruction_index());
+        }
+        assert_eq!(
+            legend.entries().collect::<Vec<_>>(),
+            vec![("loop", 1, 2), ("done", 5, 1)]
+        );
+        legend.reset();
+        assert_eq!(
+            legend
+                .entries()
+                .map(|(_, _, jumps)| jumps)
+                .collect::<Vec<_>>(),
+            vec![0, 0]
+        );
+    }
+}
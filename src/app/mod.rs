@@ -1,9 +1,15 @@
-use std::borrow::BorrowMut;
+use std::{
+    borrow::BorrowMut,
+    fs,
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use miette::{miette, IntoDiagnostic, Result};
 use ratatui::{
     backend::Backend,
+    buffer::Buffer,
     layout::{Constraint, Layout, Rect},
     text::Line,
     widgets::ListState,
@@ -11,35 +17,82 @@ use ratatui::{
 };
 
 use crate::{
+    base::{ComparisonNotation, IdentifierNotation},
     instructions::{
         error_handling::{BuildProgramError, ParseSingleInstructionError},
         instruction_config::InstructionConfig,
-        Instruction,
+        refactor::{ExtractSubroutine, RefactorError, Rename},
+        Identifier, Instruction,
+    },
+    preset::Feature,
+    runtime::{
+        self,
+        error_handling::RuntimeError,
+        memory_config::MemoryVisibilities,
+        observer::ExecutionEvent,
+        watchpoints::{Watchpoint, WatchpointHit, Watchpoints},
+        Runtime,
     },
-    runtime::{self, error_handling::RuntimeError, Runtime},
     utils,
 };
 
 use self::{
+    breakpoints::BreakpointGroup,
     content::{InstructionListStates, MemoryListsManager},
+    follow::{FollowState, StateServer},
+    key_events::KeyLog,
     keybindings::KeybindingHints,
+    label_legend::LabelLegend,
     run_instruction::SingleInstruction,
+    session::{Autosave, ExecutionSnapshot, Session},
+    signals::{Signals, SIGNAL_POLL_INTERVAL},
+    statistics::{SessionStatistics, Statistics, UsedFeature},
+    timeline::Timeline,
+    trace::Trace,
     ui::{
-        style::SharedTheme,
+        style::{SharedTheme, SyntaxHighlightingTheme},
         syntax_highlighting::{SyntaxHighlighter, ToSpans},
     },
+    value_format::ValueFormat,
 };
 
+/// Named groups of breakpoints.
+pub mod breakpoints;
 /// Contains all commands that this app can run
 pub mod commands;
 /// Content used to fill the tui elements.
 mod content;
+/// Crash reports that are written when the app panics.
+pub mod crash_report;
+/// Folding of subroutines in the code area.
+mod folds;
+/// Sharing of the execution state with followers in other terminals.
+pub mod follow;
+/// Driver that runs the app without a terminal, used by end-to-end tests.
+#[cfg(test)]
+pub mod headless;
+/// Normalization of key events and the key diagnostic overlay.
+mod key_events;
 /// Everything related to keybindings.
 mod keybindings;
+/// Panel that lists the labels of the program.
+mod label_legend;
 /// Everything related to running a single instruction while a program is loaded.
 mod run_instruction;
+/// Automatic saving of the session, so that interactive work is not lost when the terminal is closed.
+mod session;
+/// Handling of the signals that suspend, continue and terminate the process.
+mod signals;
+/// Local usage statistics of the sessions of the tui.
+pub mod statistics;
+/// Recording of the executed steps, to display earlier states of the program.
+mod timeline;
+/// Panel that lists the last executed instructions.
+mod trace;
 /// Drawing of the ui.
 pub mod ui;
+/// Formatting of the values in the memory lists.
+pub mod value_format;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum State {
@@ -57,7 +110,9 @@ pub enum State {
     CustomInstructionError(ParseSingleInstructionError, bool),
     /// Indicates that the custom instruction could not be build, because instructions, operations or comparisons
     /// where used that are not allowed.
-    BuildProgramError(BuildProgramError),
+    ///
+    /// Boolean value indicates if this error originates in the playground mode.
+    BuildProgramError(BuildProgramError, bool),
     // 0 = state to restore to when debug mode is exited
     // 1 = index of instruction that was selected before debug mode was started
     DebugSelect(Box<State>, Option<usize>),
@@ -67,8 +122,83 @@ pub enum State {
     ///
     /// Boolean value indicates if this error originates in the playground mode.
     RuntimeError(RuntimeError, bool),
+    /// Indicates that the changes of a custom instruction are displayed and that the user confirms if it should be run.
+    ///
+    /// 2 = changes the instruction would make or the error that would occur
+    ConfirmCustomInstruction(
+        SingleInstruction,
+        Instruction,
+        Box<Result<ExecutionEvent, RuntimeError>>,
+    ),
     /// Indicates that this app is in playground mode.
     Playground(SingleInstruction),
+    /// Indicates that the user enters the memory cell or label that should be renamed and the new name.
+    Rename(SingleInstruction),
+    /// Indicates that the user enters the number of instructions that should be run.
+    StepCount(SingleInstruction),
+    /// Indicates that the user enters the label of the subroutine into which lines should be extracted.
+    ///
+    /// 1 = index of the first displayed line that is extracted
+    /// 2 = index of the last displayed line that is extracted
+    ExtractSubroutine(SingleInstruction, usize, usize),
+    /// Indicates that the program could not be refactored.
+    RefactorError(RefactorError),
+    /// Indicates that the user confirms if the program should be reset.
+    ///
+    /// 0 = state to restore to when the reset is cancelled
+    ConfirmReset(Box<State>),
+    /// Indicates that the user confirms if the app should be closed, although some things are not saved.
+    ///
+    /// 0 = state to restore to when closing is cancelled
+    /// 1 = what is not saved, with the path of the file into which it is saved
+    ConfirmQuit(Box<State>, Vec<(UnsavedArtifact, String)>),
+    /// Indicates that the user confirms if the session should be restored, that was saved automatically before the
+    /// app was closed unexpectedly.
+    ConfirmRestore(Box<Session>),
+}
+
+/// Something that is lost when the app is closed, unless it is saved.
+///
+/// Changes of the program, e.g. by renaming a memory cell, are not listed, as they are written to the program file
+/// immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsavedArtifact {
+    /// Instructions that were run in the playground.
+    PlaygroundSession,
+    /// Last executed instructions of the program, listed in the trace panel.
+    Trace,
+}
+
+/// Reason that is displayed, when an action is not available because the program is loaded read-only.
+const READ_ONLY_REASON: &str = "the program is loaded with --read-only";
+
+/// Reason that is displayed, when an action changes the program file, but the program was read from stdin or a URL.
+const NO_PROGRAM_FILE_REASON: &str = "the program was not read from a local file";
+
+/// Reason that is displayed, when an action is only available to instructors.
+const INSTRUCTOR_ONLY_REASON: &str = "only available with --instructor";
+
+/// Number of executed instructions from which a reset or closing the app without saving the trace has to be
+/// confirmed.
+const RESET_CONFIRMATION_THRESHOLD: usize = 100;
+
+/// Name of the file into which the instructions of a playground session are saved, a number is added if the file
+/// exists already.
+const PLAYGROUND_SESSION_FILE: &str = "playground_session";
+
+/// Name of the file into which the trace is saved, a number is added if the file exists already.
+const TRACE_FILE: &str = "trace";
+
+/// Builds the runtime for the lines of a program, the same arguments are used that where used to build the loaded program.
+pub type ProgramBuilder = Box<dyn Fn(&[String]) -> Result<Runtime>>;
+
+/// Outcome of the conditional jump that was executed by the last step.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BranchDecision {
+    /// The condition was met, contains the index of the instruction that is executed next.
+    Taken(usize),
+    /// The condition was not met, contains the index of the instruction that is executed next.
+    NotTaken(usize),
 }
 
 /// App holds the state of the application
@@ -92,10 +222,107 @@ pub struct App {
     ///
     /// Used to prevent forbidden instructions from getting executed in run custom instruction popup.
     instruction_config: Option<InstructionConfig>,
+    /// Identifier of the last custom instruction that was forbidden by the instruction config, e.g. `A := M OP C`.
+    ///
+    /// Displayed in the error popup together with the closest allowed identifiers.
+    forbidden_identifier: Option<String>,
     /// Determines if syntax highlighting should be used.
     enable_syntax_highlighting: bool,
+    /// Notation in which comparisons of executed custom instructions are displayed.
+    comparison_notation: ComparisonNotation,
+    /// Notation in which accumulators, memory cells and gamma are displayed.
+    identifier_notation: IdentifierNotation,
+    /// Format in which the values in the memory lists are displayed.
+    value_format: ValueFormat,
+    /// Lines of the program as written and with structured loops expanded into labels and jumps.
+    ///
+    /// Is `None` if the program does not contain structured loops.
+    loop_lines: Option<LoopLines>,
+    /// Determines if an arrow is displayed that indicates in which direction the stack grows.
+    show_stack_direction: bool,
     /// Theme of the application.
     theme: SharedTheme,
+    /// Determines if labels, instructions and comments of the program are aligned.
+    ///
+    /// Used to display the program again after it was changed.
+    enable_alignment: bool,
+    /// Index of the line at which the marked range of lines starts.
+    ///
+    /// The range ends at the selected line, it is used to extract lines into a subroutine.
+    range_start: Option<usize>,
+    /// Used to build the program again after it was changed.
+    ///
+    /// Lines can only be extracted into a subroutine if this is set.
+    program_builder: Option<ProgramBuilder>,
+    /// Determines if the popup of a division by zero can be closed, so that the program is paused instead of ended.
+    resumable_division_by_zero: bool,
+    /// Determines if the changes of a custom instruction are displayed and have to be confirmed before it is run.
+    confirm_custom_instructions: bool,
+    /// Determines if custom instructions can be run, if false the memory can only be changed by the program.
+    custom_instructions_enabled: bool,
+    /// Features that are not available and the reason why.
+    disabled_features: Vec<(Feature, &'static str)>,
+    /// Sends the execution state to followers in other terminals, if set.
+    state_server: Option<StateServer>,
+    /// Determines if the next instruction and the memory values are displayed larger and if keybinding hints are hidden.
+    presentation_mode: bool,
+    /// Notes of the instructions that were run and the index of their instruction, the newest note is last.
+    narration: Vec<(usize, String)>,
+    /// Data breakpoints at which running to the next breakpoint stops.
+    watchpoints: Watchpoints,
+    /// The watchpoints that were triggered by the last instruction.
+    watchpoint_hit: Option<WatchpointHit>,
+    /// States of the program after each executed instruction.
+    timeline: Timeline,
+    /// Memory lists of the step that is selected in the timeline, `None` if the current state is displayed.
+    timeline_memory_lists: Option<MemoryListsManager>,
+    /// Line that was highlighted before a step of the timeline was selected.
+    highlight_before_timeline: Option<usize>,
+    /// Time at which the program file was last modified, when it was loaded or written by the app.
+    ///
+    /// Used to indicate that the file was changed by another program. Is `None` if the time is not available.
+    file_modified: Option<SystemTime>,
+    /// Determines if a reset has to be confirmed, when many instructions were run.
+    confirm_reset: bool,
+    /// Determines if closing the app has to be confirmed, when many instructions were run and the trace was not saved.
+    confirm_quit: bool,
+    /// Instructions that were run in the playground and that were not saved yet, in the order in which they were run.
+    playground_session: Vec<String>,
+    /// Saves the session periodically, if set.
+    autosave: Option<Autosave>,
+    /// Records the usage statistics of the session, if set.
+    statistics: Option<Statistics>,
+    /// Determines if the debug panel is displayed, it shows the measured instruction timings.
+    ///
+    /// The panel is toggled with [F12], it is not listed in the keybinding hints.
+    show_debug_panel: bool,
+    /// Number of lines by which the memory lists are scrolled down.
+    memory_scroll: usize,
+    /// Number of lines that fit into the memory cell list, set when the list is drawn.
+    memory_page_height: usize,
+    /// Labels of the program and how often they were jumped to during the current run.
+    label_legend: LabelLegend,
+    /// Determines if the panel that lists the labels is displayed.
+    show_label_legend: bool,
+    /// Explains why the action of the last pressed key is not available, displayed in the status bar.
+    unavailable_notice: Option<String>,
+    /// Outcome of the conditional jump executed by the last step, displayed in the status bar.
+    branch_decision: Option<BranchDecision>,
+    /// Last executed instructions, listed in the trace panel.
+    trace: Trace,
+    /// Determines if the panel that lists the last executed instructions is displayed.
+    show_trace: bool,
+    /// Last key events, displayed in the key diagnostic overlay if set.
+    key_log: Option<KeyLog>,
+}
+
+/// Lines of a program that contains structured loops.
+struct LoopLines {
+    /// Lines as they are written in the program.
+    written: Vec<Line<'static>>,
+    /// Lines where structured loops are expanded into labels and jumps.
+    expanded: Vec<Line<'static>>,
+    show_expanded: bool,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -118,10 +345,40 @@ impl App {
         command_history_file: Option<String>,
         playground: bool,
         enable_syntax_highlighting: bool,
+        comparison_notation: ComparisonNotation,
         theme: SharedTheme,
     ) -> App {
-        let mlm = MemoryListsManager::new(runtime.runtime_memory(), &theme);
+        let mlm = MemoryListsManager::new(
+            runtime.runtime_memory(),
+            MemoryVisibilities::default(),
+            &theme,
+        );
+        let loop_lines = if runtime.structured_loops().is_empty() {
+            None
+        } else {
+            Some(LoopLines {
+                written: instructions.to_vec(),
+                expanded: SyntaxHighlighter::new(&theme.syntax_highlighting_theme())
+                    .with_comparison_notation(comparison_notation)
+                    .expand_loops(
+                        instructions,
+                        runtime.instructions(),
+                        runtime.structured_loops(),
+                    ),
+                show_expanded: false,
+            })
+        };
         let show_call_stack = runtime.contains_call_instruction();
+        // the executed instructions of the playground can not be folded
+        let folds = if playground {
+            Vec::new()
+        } else {
+            folds::subroutine_folds(&runtime)
+        };
+        let has_folds = !folds.is_empty();
+        let label_legend = LabelLegend::new(&runtime);
+        let timeline = Timeline::new(runtime.runtime_memory());
+        let file_modified = modified_time(&filename);
         let executed_custom_instructions = custom_instructions.unwrap_or_default();
         let state = if playground {
             State::Playground(SingleInstruction::new(
@@ -131,202 +388,752 @@ impl App {
         } else {
             State::Default
         };
-        Self {
+        // refactorings change the program file, which is not possible if the program was not read from a file
+        let file_unavailable = filename == utils::STDIN_FILENAME || utils::is_url(&filename);
+        let mut app = Self {
             runtime,
             filename,
             instruction_list_states: InstructionListStates::new(
                 instructions,
                 set_breakpoints.as_ref(),
-            ),
+            )
+            .with_folds(folds),
             keybinding_hints: KeybindingHints::new(theme.clone())
-                .expect("Keybinding hints should be properly initialized"),
+                .expect("Keybinding hints should be properly initialized")
+                .with_loop_expansion(loop_lines.is_some())
+                .with_folding(has_folds)
+                .with_labels(!label_legend.is_empty()),
             memory_lists_manager: mlm,
             state,
             executed_custom_instructions,
             command_history_file,
             show_call_stack,
             instruction_config,
+            forbidden_identifier: None,
             enable_syntax_highlighting,
+            comparison_notation,
+            identifier_notation: IdentifierNotation::Unicode,
+            value_format: ValueFormat::default(),
+            loop_lines,
+            show_stack_direction: false,
             theme,
+            enable_alignment: true,
+            range_start: None,
+            program_builder: None,
+            resumable_division_by_zero: false,
+            confirm_custom_instructions: false,
+            custom_instructions_enabled: true,
+            disabled_features: Vec::new(),
+            state_server: None,
+            presentation_mode: false,
+            narration: Vec::new(),
+            watchpoints: Watchpoints::default(),
+            watchpoint_hit: None,
+            timeline,
+            timeline_memory_lists: None,
+            highlight_before_timeline: None,
+            file_modified,
+            confirm_reset: false,
+            confirm_quit: false,
+            playground_session: Vec::new(),
+            autosave: None,
+            statistics: None,
+            show_debug_panel: false,
+            memory_scroll: 0,
+            memory_page_height: 0,
+            label_legend,
+            show_label_legend: false,
+            unavailable_notice: None,
+            branch_decision: None,
+            trace: Trace::default(),
+            show_trace: false,
+            key_log: None,
+        };
+        if file_unavailable {
+            app.disable_feature(Feature::Refactoring, NO_PROGRAM_FILE_REASON);
         }
+        app
+    }
+
+    /// Sets if the key diagnostic overlay is displayed, it shows the last key events as they are reported by the terminal.
+    pub fn with_debug_keys(mut self, debug_keys: bool) -> Self {
+        self.key_log = debug_keys.then(KeyLog::default);
+        self
+    }
+
+    /// Sets when values of each memory type are displayed.
+    pub fn with_memory_visibilities(mut self, visibility: MemoryVisibilities) -> Self {
+        self.memory_lists_manager =
+            MemoryListsManager::new(self.runtime.runtime_memory(), visibility, &self.theme)
+                .with_identifier_notation(self.identifier_notation)
+                .with_value_format(self.value_format.clone());
+        self
+    }
+
+    /// Sets the format in which the values in the memory lists are displayed.
+    pub fn with_value_format(mut self, value_format: ValueFormat) -> Self {
+        self.value_format = value_format.clone();
+        self.memory_lists_manager = self.memory_lists_manager.with_value_format(value_format);
+        self
+    }
+
+    /// Sets the notation in which accumulators, memory cells and gamma are displayed.
+    ///
+    /// The lines of the program are displayed as they were passed to [`App::from_runtime`], they have to be
+    /// created with the same notation.
+    pub fn with_identifier_notation(mut self, identifier_notation: IdentifierNotation) -> Self {
+        self.identifier_notation = identifier_notation;
+        self.memory_lists_manager = self
+            .memory_lists_manager
+            .with_identifier_notation(identifier_notation);
+        if let Some(loop_lines) = &mut self.loop_lines {
+            loop_lines.expanded = SyntaxHighlighter::new(&self.theme.syntax_highlighting_theme())
+                .with_comparison_notation(self.comparison_notation)
+                .with_identifier_notation(identifier_notation)
+                .expand_loops(
+                    &loop_lines.written,
+                    self.runtime.instructions(),
+                    self.runtime.structured_loops(),
+                );
+        }
+        self
+    }
+
+    /// Sets if labels, instructions and comments are aligned, when the program is displayed again after it was changed.
+    pub fn with_alignment(mut self, enable_alignment: bool) -> Self {
+        self.enable_alignment = enable_alignment;
+        self
+    }
+
+    /// Sets how the program is built again after it was changed.
+    pub fn with_program_builder(mut self, program_builder: ProgramBuilder) -> Self {
+        self.program_builder = Some(program_builder);
+        self
+    }
+
+    /// Sets if the popup of a division by zero can be closed, so that the program is paused instead of ended.
+    pub fn with_resumable_division_by_zero(mut self, resumable_division_by_zero: bool) -> Self {
+        self.resumable_division_by_zero = resumable_division_by_zero;
+        self.keybinding_hints = self
+            .keybinding_hints
+            .with_resumable_division_by_zero(resumable_division_by_zero);
+        self
+    }
+
+    /// Sets if the changes of a custom instruction have to be confirmed before it is run.
+    pub fn with_confirm_custom_instructions(mut self, confirm_custom_instructions: bool) -> Self {
+        self.confirm_custom_instructions = confirm_custom_instructions;
+        self
+    }
+
+    /// Sets if custom instructions can be run, if disabled the custom instruction popup can not be opened.
+    pub fn with_custom_instructions(mut self, custom_instructions_enabled: bool) -> Self {
+        self.custom_instructions_enabled = custom_instructions_enabled;
+        self.keybinding_hints = self
+            .keybinding_hints
+            .with_custom_instructions(custom_instructions_enabled);
+        self
+    }
+
+    /// Sets if the program can only be stepped through and run, custom instructions, jumping to a line, skipping
+    /// instructions and refactoring are not available.
+    pub fn with_read_only(mut self, read_only: bool) -> Self {
+        if read_only {
+            for feature in [
+                Feature::CustomInstructions,
+                Feature::JumpToLine,
+                Feature::Refactoring,
+            ] {
+                self.disable_feature(feature, READ_ONLY_REASON);
+            }
+        }
+        self
+    }
+
+    /// Disables the `instructor_only` features, unless the app is used by an instructor.
+    pub fn with_instructor_only(mut self, instructor_only: &[Feature], instructor: bool) -> Self {
+        if !instructor {
+            for feature in instructor_only {
+                self.disable_feature(*feature, INSTRUCTOR_ONLY_REASON);
+            }
+        }
+        self
+    }
+
+    /// Disables `feature`, the keybinding hints of its actions are displayed as unavailable because of `reason`.
+    ///
+    /// If the feature is disabled already, the first reason is kept.
+    fn disable_feature(&mut self, feature: Feature, reason: &'static str) {
+        if self.feature_enabled(feature) {
+            self.disabled_features.push((feature, reason));
+            for action in feature.actions() {
+                self.keybinding_hints.set_unavailable(action, Some(reason));
+            }
+        }
+    }
+
+    /// Returns the reason why `feature` is disabled, `None` if it is available.
+    fn disabled_reason(&self, feature: Feature) -> Option<&'static str> {
+        self.disabled_features
+            .iter()
+            .find(|(f, _)| *f == feature)
+            .map(|(_, reason)| *reason)
+    }
+
+    fn feature_enabled(&self, feature: Feature) -> bool {
+        self.disabled_reason(feature).is_none()
+    }
+
+    /// Sets the server that sends the execution state to followers in other terminals.
+    pub fn with_state_server(mut self, state_server: StateServer) -> Self {
+        self.state_server = Some(state_server);
+        self
+    }
+
+    /// Sets the named groups of breakpoints that can be enabled and disabled together.
+    pub fn with_breakpoint_groups(mut self, breakpoint_groups: Vec<BreakpointGroup>) -> Self {
+        self.keybinding_hints = self
+            .keybinding_hints
+            .with_breakpoint_groups(!breakpoint_groups.is_empty());
+        self.instruction_list_states = self
+            .instruction_list_states
+            .with_breakpoint_groups(breakpoint_groups);
+        self
+    }
+
+    /// Sets the data breakpoints at which running to the next breakpoint stops.
+    pub fn with_watchpoints(mut self, watchpoints: Vec<Watchpoint>) -> Self {
+        self.watchpoints = Watchpoints::new(watchpoints, self.runtime.runtime_memory());
+        self
+    }
+
+    /// Sets if a reset has to be confirmed, when many instructions were run.
+    pub fn with_confirm_reset(mut self, confirm_reset: bool) -> Self {
+        self.confirm_reset = confirm_reset;
+        self
+    }
+
+    /// Sets if closing the app has to be confirmed, when many instructions were run and the trace was not saved.
+    pub fn with_confirm_quit(mut self, confirm_quit: bool) -> Self {
+        self.confirm_quit = confirm_quit;
+        self
+    }
+
+    /// Sets how the session is saved periodically, it is not saved if `autosave` is `None`.
+    pub fn with_autosave(mut self, autosave: Option<Autosave>) -> Self {
+        self.autosave = autosave;
+        self
+    }
+
+    /// Sets the usage statistics of the session, they are appended to the statistics file when the app is closed.
+    pub fn with_statistics(mut self, statistics: Option<Statistics>) -> Self {
+        self.statistics = statistics;
+        self
+    }
+
+    /// Offers to restore the session, that was saved before the app was closed unexpectedly, when the app is started.
+    pub fn with_recovered_session(mut self, session: Option<Session>) -> Self {
+        if let Some(session) = session {
+            self.state = State::ConfirmRestore(Box::new(session));
+        }
+        self
+    }
+
+    /// Sets if the app is started in presentation mode.
+    pub fn with_presentation_mode(mut self, presentation_mode: bool) -> Self {
+        self.presentation_mode = presentation_mode;
+        self
+    }
+
+    /// Sets if an arrow is displayed that indicates in which direction the stack grows.
+    pub fn with_stack_direction(mut self, show_stack_direction: bool) -> Self {
+        self.show_stack_direction = show_stack_direction;
+        self
     }
 
-    #[allow(clippy::single_match)]
     pub fn run<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        let res = self.run_loop(terminal);
+        // the session is saved when the app is closed, so that it is known that the app was not closed unexpectedly
+        self.save_session(true);
+        let statistics_res = match &mut self.statistics {
+            Some(statistics) => statistics.write().map_err(|e| miette!(e)),
+            None => Ok(()),
+        };
+        res.and(statistics_res)
+    }
+
+    #[allow(clippy::single_match)]
+    fn run_loop<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
         // update keybinding hints one to make sure that start keybinding hints are displayed properly
         if let Err(e) = self.keybinding_hints.update(&self.state) {
             return Err(miette!("Error while updating keybinding hints:\n{e}"));
         }
+        let signals = Signals::register()?;
         loop {
+            if signals.terminate_requested() {
+                return Ok(());
+            }
+            if signals.take_resumed() {
+                // the terminal was restored when the process was suspended
+                signals::resume()?;
+                terminal.clear().into_diagnostic()?;
+            }
+            self.broadcast_state();
+            crash_report::set_state(&self.state);
             terminal.draw(|f| self.draw(f)).into_diagnostic()?;
-            if let Event::Key(key) = event::read().into_diagnostic()? {
+            let timeout = match &self.autosave {
+                Some(autosave) => autosave.time_until_save().min(SIGNAL_POLL_INTERVAL),
+                None => SIGNAL_POLL_INTERVAL,
+            };
+            if !event::poll(timeout).into_diagnostic()? {
+                if self
+                    .autosave
+                    .as_ref()
+                    .is_some_and(|autosave| autosave.time_until_save().is_zero())
+                {
+                    self.save_session(false);
+                }
+                continue;
+            }
+            if let Event::Key(raw) = event::read().into_diagnostic()? {
+                let key = key_events::normalize(raw);
+                crash_report::record_event(format!(
+                    "{} {:?}",
+                    key_events::describe(&raw),
+                    raw.kind
+                ));
+                if let Some(key_log) = &mut self.key_log {
+                    key_log.record(raw, key);
+                }
                 if key.kind == KeyEventKind::Release {
                     // ignore when key is released, to prevent dual input
                     continue;
                 }
-                match &self.state {
-                    State::CustomInstruction(_) | State::Playground(_) => {
-                        if let KeyCode::Char(to_insert) = key.code {
-                            self.any_char(to_insert)
+                if key.code == KeyCode::Char('z') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                    signals::suspend()?;
+                    continue;
+                }
+                if self.handle_key(key)? {
+                    return Ok(());
+                }
+            }
+            self.update_after_input()?;
+        }
+    }
+
+    /// Handles a key press, returns true if the app should be closed.
+    ///
+    /// The key event has to be normalized already, see [`key_events::normalize`].
+    fn handle_key(&mut self, key: KeyEvent) -> Result<bool> {
+        match &self.state {
+            State::CustomInstruction(_)
+            | State::Playground(_)
+            | State::Rename(_)
+            | State::StepCount(_)
+            | State::ExtractSubroutine(_, _, _) => {
+                if let KeyCode::Char(to_insert) = key.code {
+                    self.any_char(to_insert)
+                }
+            }
+            _ => {
+                self.unavailable_notice = match key.code {
+                    KeyCode::Char(c) => self.keybinding_hints.unavailable_reason(&c.to_string()),
+                    _ => None,
+                };
+                match key.code {
+                    KeyCode::Up => {
+                        if let State::DebugSelect(_s, _i) = &self.state {
+                            self.instruction_list_states.set_prev_visual();
                         }
                     }
-                    _ => {
-                        match key.code {
-                            KeyCode::Up => {
-                                if let State::DebugSelect(_s, _i) = &self.state {
-                                    self.instruction_list_states.set_prev_visual();
-                                }
-                            }
-                            KeyCode::Down => {
-                                if let State::DebugSelect(_s, _i) = &self.state {
-                                    self.instruction_list_states.set_next_visual();
-                                }
-                            }
-                            KeyCode::Char('b') => {
-                                if let State::DebugSelect(_, _) = &self.state {
-                                    self.instruction_list_states.toggle_breakpoint();
-                                }
+                    KeyCode::Down => {
+                        if let State::DebugSelect(_s, _i) = &self.state {
+                            self.instruction_list_states.set_next_visual();
+                        }
+                    }
+                    KeyCode::Char('f') => {
+                        if let State::DebugSelect(_, _) = &self.state {
+                            self.instruction_list_states.toggle_fold();
+                        }
+                    }
+                    KeyCode::Char('b') => {
+                        if let State::DebugSelect(_, _) = &self.state {
+                            self.instruction_list_states.toggle_breakpoint();
+                            self.record_feature(UsedFeature::Breakpoints);
+                        }
+                    }
+                    KeyCode::Char('j') if self.feature_enabled(Feature::JumpToLine) => {
+                        if let State::DebugSelect(_, _) = &self.state {
+                            self.record_feature(UsedFeature::JumpToLine);
+                            self.state = State::Running(self.breakpoints_set());
+                            let idx = self
+                                .instruction_list_states
+                                .instruction_list_state_mut()
+                                .selected()
+                                .unwrap();
+                            self.runtime.set_next_instruction(idx);
+                            _ = self.step();
+                        }
+                    }
+                    KeyCode::Char('k') if self.feature_enabled(Feature::JumpToLine) => {
+                        let running = match &self.state {
+                            State::DebugSelect(previous_state, _) => {
+                                matches!(**previous_state, State::Running(_))
                             }
-                            KeyCode::Char('j') => {
-                                if let State::DebugSelect(_, _) = &self.state {
-                                    self.state = State::Running(
-                                        self.instruction_list_states.breakpoints_set(),
-                                    );
-                                    let idx = self
-                                        .instruction_list_states
-                                        .instruction_list_state_mut()
-                                        .selected()
-                                        .unwrap();
-                                    self.runtime.set_next_instruction(idx);
-                                    _ = self.step();
-                                }
+                            state => matches!(state, State::Running(_)),
+                        };
+                        if running {
+                            self.record_feature(UsedFeature::SkipInstruction);
+                            self.state = State::Running(self.breakpoints_set());
+                            self.skip_instruction();
+                        }
+                    }
+                    // after a runtime error, the memory can be fixed before the failed instruction is run again
+                    KeyCode::Char('i')
+                        if self.custom_instructions_enabled
+                            && self.feature_enabled(Feature::CustomInstructions)
+                            && (matches!(self.state, State::Running(_)) || self.can_retry()) =>
+                    {
+                        self.state = State::CustomInstruction(SingleInstruction::new(
+                            &self.executed_custom_instructions,
+                            &self.theme,
+                        ))
+                    }
+                    KeyCode::Char('q') => match &self.state {
+                        State::RuntimeError(e, _) => Err(e.clone())?,
+                        State::CustomInstructionError(e, _) => Err(e.clone())?,
+                        State::BuildProgramError(e, _) => Err(e.clone())?,
+                        State::RefactorError(e) => Err(e.clone())?,
+                        State::CustomInstruction(_)
+                        | State::ConfirmCustomInstruction(_, _, _)
+                        | State::ConfirmReset(_)
+                        | State::ConfirmRestore(_) => (),
+                        // quit without saving
+                        State::ConfirmQuit(_, _) => return Ok(true),
+                        _ => return Ok(self.request_quit()),
+                    },
+                    KeyCode::Char('w') => {
+                        if let State::DebugSelect(_, _) = self.state {
+                            self.instruction_list_states.set_prev_visual();
+                        }
+                    }
+                    KeyCode::Char('t') => match self.state {
+                        State::Running(_)
+                        | State::Finished(_)
+                        | State::RuntimeError(_, false)
+                        | State::CustomInstructionError(_, false) => {
+                            self.request_reset();
+                        }
+                        State::ConfirmReset(_) => self.reset(),
+                        State::DebugSelect(_, _) => {
+                            self.instruction_list_states.set_next_visual();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('s') => match &self.state {
+                        State::Default => self.start(),
+                        State::ConfirmQuit(_, unsaved) => {
+                            for (artifact, path) in unsaved {
+                                fs::write(path, self.unsaved_content(*artifact)).map_err(|e| {
+                                    miette!(
+                                        "Unable to save {} to [{path}]: {e}",
+                                        self.describe_unsaved(*artifact)
+                                    )
+                                })?;
                             }
-                            KeyCode::Char('i') => match self.state {
-                                State::Running(_) => {
-                                    self.state = State::CustomInstruction(SingleInstruction::new(
-                                        &self.executed_custom_instructions,
-                                        &self.theme,
-                                    ))
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('q') => match &self.state {
-                                State::RuntimeError(e, _) => Err(e.clone())?,
-                                State::CustomInstructionError(e, _) => Err(e.clone())?,
-                                State::BuildProgramError(e) => Err(e.clone())?,
-                                State::CustomInstruction(_) => (),
-                                _ => return Ok(()),
-                            },
-                            KeyCode::Char('w') => {
-                                if let State::DebugSelect(_, _) = self.state {
-                                    self.instruction_list_states.set_prev_visual();
-                                }
+                            return Ok(true);
+                        }
+                        State::DebugSelect(_, _) => {
+                            self.instruction_list_states.set_next_visual();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('n') => {
+                        match &self.state {
+                            State::Running(_) => {
+                                _ = self.step();
                             }
-                            KeyCode::Char('t') => match self.state {
-                                State::Running(_) | State::Finished(_) => self.reset(),
-                                State::RuntimeError(_, false)
-                                | State::CustomInstructionError(_, false) => {
-                                    self.reset();
-                                }
-                                State::DebugSelect(_, _) => {
-                                    self.instruction_list_states.set_next_visual();
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('s') => match self.state {
-                                State::Default => {
-                                    self.instruction_list_states
-                                        .set_start(self.runtime.next_instruction_index() as i32);
-                                    self.state = State::Running(
-                                        self.instruction_list_states.breakpoints_set(),
-                                    );
-                                    _ = self.step();
-                                }
-                                State::DebugSelect(_, _) => {
-                                    self.instruction_list_states.set_next_visual();
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('n') => {
-                                match self.state {
-                                    State::Running(_) => {
-                                        _ = self.step();
-                                    }
-                                    _ => (),
-                                };
+                            // retry the failed instruction
+                            _ if self.can_retry() => {
+                                self.record_feature(UsedFeature::Retry);
+                                self.state = State::Running(self.breakpoints_set());
+                                _ = self.step();
                             }
-                            KeyCode::Char('r') => {
-                                // run to the next breakpoint
-                                if self.state == State::Running(true)
-                                    || self.state == State::Running(false)
-                                {
-                                    _ = self.step();
-                                    while !self.instruction_list_states.is_breakpoint() {
-                                        match self.step() {
-                                            Ok(bool) => {
-                                                if bool {
-                                                    break;
-                                                }
-                                            }
-                                            Err(_) => break,
-                                        }
+                            _ => (),
+                        };
+                    }
+                    KeyCode::Char(':') if matches!(self.state, State::Running(_)) => {
+                        self.state = State::StepCount(SingleInstruction::new(&[], &self.theme));
+                    }
+                    // run to the next breakpoint
+                    KeyCode::Char('r') if matches!(self.state, State::Running(_)) => {
+                        _ = self.step();
+                        while !self.instruction_list_states.is_breakpoint()
+                            && self.watchpoint_hit.is_none()
+                        {
+                            match self.step() {
+                                Ok(bool) => {
+                                    if bool {
+                                        break;
                                     }
                                 }
+                                Err(_) => break,
                             }
-                            KeyCode::Char('d') => match &self.state {
-                                State::DebugSelect(_, i) => {
-                                    self.instruction_list_states.set_instruction_list_state(*i);
-                                    self.state = State::Running(
-                                        self.instruction_list_states.breakpoints_set(),
-                                    );
-                                }
-                                State::Default | State::Running(_) => {
-                                    self.start_debug_select_mode()
-                                }
-                                State::Finished(b) => {
-                                    if *b {
-                                        self.state = State::Finished(false);
-                                    }
-                                }
-                                _ => (),
-                            },
-                            KeyCode::Char('c') => match &self.state {
-                                State::Default | State::Running(_) | State::DebugSelect(_, _) => {
-                                    self.show_call_stack = !self.show_call_stack;
-                                }
-                                _ => (),
-                            },
-                            _ => (),
                         }
                     }
-                }
-                // keybinding actions that are always checked
-                match key.code {
-                    KeyCode::Esc => {
-                        if self.escape_key()? {
-                            return Ok(());
+                    KeyCode::Char('d') => match &self.state {
+                        State::DebugSelect(_, i) => {
+                            self.instruction_list_states.set_instruction_list_state(*i);
+                            self.state = State::Running(self.breakpoints_set());
+                        }
+                        State::Default | State::Running(_) => self.start_debug_select_mode(),
+                        State::Finished(true) => {
+                            self.state = State::Finished(false);
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('c') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.show_call_stack = !self.show_call_stack;
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('x') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.toggle_loop_expansion();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('e') => match &self.state {
+                        State::Default if self.feature_enabled(Feature::Refactoring) => {
+                            self.record_feature(UsedFeature::Refactoring);
+                            self.state = State::Rename(SingleInstruction::new(&[], &self.theme));
+                        }
+                        State::DebugSelect(s, _) if self.extraction_possible(s) => {
+                            if let Some(selected) = self.instruction_list_states.selected_line() {
+                                self.record_feature(UsedFeature::Refactoring);
+                                let start = self.range_start.unwrap_or(selected);
+                                self.state = State::ExtractSubroutine(
+                                    SingleInstruction::new(&[], &self.theme),
+                                    start.min(selected),
+                                    start.max(selected),
+                                );
+                            }
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('p') => {
+                        if matches!(
+                            self.state,
+                            State::Default
+                                | State::Running(_)
+                                | State::DebugSelect(_, _)
+                                | State::Finished(_)
+                        ) {
+                            self.presentation_mode = !self.presentation_mode;
+                            self.record_feature(UsedFeature::PresentationMode);
+                        }
+                    }
+                    KeyCode::Char('m') => {
+                        if let State::DebugSelect(s, _) = &self.state {
+                            if self.extraction_possible(s) {
+                                let selected = self.instruction_list_states.selected_line();
+                                self.range_start = if self.range_start == selected {
+                                    None
+                                } else {
+                                    selected
+                                };
+                            }
                         }
                     }
-                    KeyCode::Backspace => self.backspace_key(),
-                    KeyCode::Delete => self.delete_key(),
-                    KeyCode::Left => self.left_key(),
-                    KeyCode::Right => self.right_key(),
-                    KeyCode::Down => self.down_key(),
-                    KeyCode::Up => self.up_key(),
-                    KeyCode::Enter => self.enter_key()?,
-                    KeyCode::Tab => self.tab_key(),
+                    KeyCode::Char('v') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.memory_lists_manager.toggle_ascii();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('a') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            self.instruction_list_states.toggle_all_breakpoints();
+                            self.update_breakpoints_set();
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('l') => match &self.state {
+                        State::Default
+                        | State::Running(_)
+                        | State::DebugSelect(_, _)
+                        | State::Finished(_)
+                            if !self.label_legend.is_empty() =>
+                        {
+                            self.show_label_legend = !self.show_label_legend;
+                            self.record_feature(UsedFeature::LabelLegend);
+                            self.keybinding_hints
+                                .set_label_legend(self.show_label_legend);
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('o') => match &self.state {
+                        State::Default
+                        | State::Running(_)
+                        | State::DebugSelect(_, _)
+                        | State::Finished(_) => {
+                            self.show_trace = !self.show_trace;
+                            self.record_feature(UsedFeature::Trace);
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char('h') => self.keybinding_hints.next_page(),
+                    KeyCode::F(12) => match &self.state {
+                        State::Default
+                        | State::Running(_)
+                        | State::DebugSelect(_, _)
+                        | State::Finished(_) => {
+                            self.show_debug_panel = !self.show_debug_panel;
+                        }
+                        _ => (),
+                    },
+                    KeyCode::Char(c @ '1'..='9') => match &self.state {
+                        State::Default | State::Running(_) | State::DebugSelect(_, _) => {
+                            let group_idx = c as usize - '1' as usize;
+                            self.instruction_list_states
+                                .toggle_breakpoint_group(group_idx);
+                            self.record_feature(UsedFeature::Breakpoints);
+                            self.update_breakpoints_set();
+                        }
+                        _ => (),
+                    },
                     _ => (),
                 }
             }
+        }
+        // keybinding actions that are always checked
+        match key.code {
+            KeyCode::Esc if self.escape_key()? => {
+                return Ok(true);
+            }
+            KeyCode::Backspace => self.backspace_key(),
+            KeyCode::Delete => self.delete_key(),
+            KeyCode::Left if matches!(self.state, State::Running(_) | State::Finished(_)) => {
+                self.move_in_timeline(false)
+            }
+            KeyCode::Right if matches!(self.state, State::Running(_) | State::Finished(_)) => {
+                self.move_in_timeline(true)
+            }
+            KeyCode::Left => self.left_key(),
+            KeyCode::Right => self.right_key(),
+            KeyCode::Down => self.down_key(),
+            KeyCode::Up => self.up_key(),
+            KeyCode::PageDown => self.page_down_key(),
+            KeyCode::PageUp => self.page_up_key(),
+            KeyCode::Home => self.home_key(),
+            KeyCode::End => self.end_key(),
+            KeyCode::Enter => self.enter_key()?,
+            KeyCode::Tab => self.tab_key(),
+            _ => (),
+        }
+        Ok(false)
+    }
 
-            self.memory_lists_manager.update(&self.runtime);
-            // update keybinding hints for next loop
+    /// Updates the parts of the app that depend on the state after a key was handled.
+    fn update_after_input(&mut self) -> Result<()> {
+        self.update_custom_instruction_warning();
+        self.memory_lists_manager.update(&self.runtime);
+        // update keybinding hints for next loop
+        if let Err(e) = self.keybinding_hints.update(&self.state) {
+            return Err(miette!("Error while updating keybinding hints:\n{e}"));
+        }
+        Ok(())
+    }
+
+    /// Runs the program without user input and draws a frame after every instruction, used to record animations.
+    ///
+    /// `on_frame` is called with the content of the terminal after each frame was drawn, the first frame shows the
+    /// program before it is started. The replay ends when the program has finished, a runtime error occurred or
+    /// `max_frames` frames were drawn.
+    pub fn replay<B: Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        max_frames: usize,
+        mut on_frame: impl FnMut(&Buffer),
+    ) -> Result<()> {
+        for _ in 0..max_frames {
             if let Err(e) = self.keybinding_hints.update(&self.state) {
                 return Err(miette!("Error while updating keybinding hints:\n{e}"));
             }
+            let frame = terminal.draw(|f| self.draw(f)).into_diagnostic()?;
+            on_frame(frame.buffer);
+            match self.state {
+                State::Default => self.start(),
+                State::Running(_) => _ = self.step(),
+                _ => break,
+            }
+            self.memory_lists_manager.update(&self.runtime);
+        }
+        Ok(())
+    }
+
+    /// Saves the session, if autosave is enabled.
+    ///
+    /// `clean_exit` is true if the session is saved because the app is closed.
+    fn save_session(&mut self, clean_exit: bool) {
+        let Some(autosave) = &self.autosave else {
+            return;
+        };
+        // the session that is offered to be restored would be overwritten
+        if let State::ConfirmRestore(_) = self.state {
+            return;
+        }
+        let session = Session {
+            file: self.filename.clone(),
+            saved_at: autosave.now(),
+            clean_exit,
+            custom_instructions: self.executed_custom_instructions.clone(),
+            playground_instructions: self.playground_session.clone(),
+            breakpoints: self.instruction_list_states.breakpoints(),
+            snapshot: (autosave.snapshot() && self.feature_enabled(Feature::Snapshots))
+                .then(|| ExecutionSnapshot::new(&self.runtime)),
+        };
+        if let Some(autosave) = &mut self.autosave {
+            autosave.save(&session);
+        }
+    }
+
+    /// Updates the usage statistics of the session, if they are enabled.
+    fn update_statistics(&mut self, update: impl FnOnce(&mut SessionStatistics)) {
+        if let Some(statistics) = &mut self.statistics {
+            update(statistics.session());
+        }
+    }
+
+    /// Records that `feature` was used, if usage statistics are enabled.
+    fn record_feature(&mut self, feature: UsedFeature) {
+        if let Some(statistics) = &mut self.statistics {
+            statistics.record_feature(feature);
+        }
+    }
+
+    /// Returns true if a breakpoint or a data breakpoint is set, at which running the program stops.
+    fn breakpoints_set(&self) -> bool {
+        self.instruction_list_states.breakpoints_set() || !self.watchpoints.is_empty()
+    }
+
+    /// Updates if at least one breakpoint is enabled, after breakpoints were enabled or disabled.
+    fn update_breakpoints_set(&mut self) {
+        if let State::Running(_) = self.state {
+            self.state = State::Running(self.breakpoints_set());
         }
     }
 
+    /// Starts the program and runs the first instruction.
+    fn start(&mut self) {
+        self.update_statistics(|statistics| statistics.programs_run += 1);
+        self.instruction_list_states
+            .set_start(self.runtime.next_instruction_index() as i32);
+        self.state = State::Running(self.breakpoints_set());
+        _ = self.step();
+    }
+
     /// returns true when the execution finished in this step
     fn step(&mut self) -> Result<bool, ()> {
         // update instruction list states before running instruction to set the highlighted line correctly
@@ -334,11 +1141,38 @@ impl App {
         self.instruction_list_states
             .set(self.runtime.next_instruction_index() as i32);
 
-        let res = self.runtime.step();
+        let idx = self.runtime.next_instruction_index();
+        let instruction = self.runtime.instructions().get(idx).cloned();
+        let mut event = None;
+        self.leave_timeline();
+        let res = self
+            .runtime
+            .step_observed(&mut |e: &ExecutionEvent| event = Some(e.clone()));
+        self.watchpoint_hit = None;
+        self.branch_decision = None;
         if let Err(e) = res {
+            self.update_statistics(|statistics| statistics.runtime_errors += 1);
             self.state = State::RuntimeError(e, false);
             return Err(());
         }
+        self.update_statistics(|statistics| statistics.instructions_executed += 1);
+        if let Some(event) = &event {
+            self.watchpoint_hit = self.watchpoints.check(event, self.runtime.runtime_memory());
+            self.timeline.record(event, &self.runtime);
+        }
+        if let Some(note) = self.runtime.notes().note(idx) {
+            self.narration.push((idx, note.to_string()));
+        }
+        if let Some(instruction) = instruction {
+            self.label_legend
+                .record(&instruction, self.runtime.next_instruction_index());
+            if let Instruction::JumpIf(..) = instruction {
+                self.branch_decision = Some(self.branch_decision(idx));
+            }
+        }
+        if let Some(event) = &event {
+            self.trace.record(event, self.branch_decision);
+        }
         self.instruction_list_states
             .set(self.runtime.next_instruction_index() as i32);
         if self.runtime.finished() {
@@ -348,12 +1182,201 @@ impl App {
                     self.state = State::Finished(true);
                 }
             }
+            self.update_statistics(|statistics| statistics.programs_finished += 1);
             return Ok(true);
         }
         Ok(false)
     }
 
+    /// Determines if the conditional jump in line `idx` that was just executed jumped.
+    ///
+    /// The jump was not taken, if the program continues with the following instruction.
+    fn branch_decision(&self, idx: usize) -> BranchDecision {
+        let next = self.runtime.next_instruction_index();
+        let instructions = self.runtime.instructions();
+        let mut following = idx + 1;
+        if self.runtime.settings().skip_empty_lines {
+            while let Some(Instruction::Noop) = instructions.get(following) {
+                following += 1;
+            }
+        }
+        if next == following {
+            BranchDecision::NotTaken(next)
+        } else {
+            BranchDecision::Taken(next)
+        }
+    }
+
+    /// Continues with the instruction after the pending instruction without running it, the skipped instruction is
+    /// marked in the trace.
+    fn skip_instruction(&mut self) {
+        let idx = self.runtime.next_instruction_index();
+        let Some(instruction) = self
+            .runtime
+            .instructions()
+            .get(idx)
+            .map(ToString::to_string)
+        else {
+            return;
+        };
+        self.leave_timeline();
+        self.runtime.skip_instruction();
+        self.trace
+            .record_skipped(self.runtime.instruction_runs(), idx + 1, instruction);
+        self.branch_decision = None;
+        self.instruction_list_states
+            .set(self.runtime.next_instruction_index() as i32);
+        if self.runtime.finished() {
+            self.state = State::Finished(true);
+        }
+    }
+
+    /// Runs the next `count` instructions, breakpoints are ignored.
+    ///
+    /// Stops early if the program finishes or a runtime error occurs.
+    fn step_count(&mut self, count: usize) {
+        for _ in 0..count {
+            match self.step() {
+                Ok(false) => (),
+                Ok(true) | Err(_) => break,
+            }
+        }
+    }
+
+    /// Moves through the recorded steps, the memory of the selected step is displayed instead of the current memory.
+    ///
+    /// `forward` is true to select the next step, false to select the previous step.
+    fn move_in_timeline(&mut self, forward: bool) {
+        let was_live = self.timeline.position().is_none();
+        let moved = if forward {
+            self.timeline.forward()
+        } else {
+            self.timeline.back()
+        };
+        if !moved {
+            return;
+        }
+        self.record_feature(UsedFeature::Timeline);
+        if was_live {
+            self.highlight_before_timeline = self.instruction_list_states.selected_line();
+        }
+        match self.timeline.selected() {
+            Some((snapshot, before)) => {
+                // the lists are created with the memory before the step, so that the changes of the step are marked
+                let mut memory_lists = MemoryListsManager::new(
+                    before,
+                    self.memory_lists_manager.visibility(),
+                    &self.theme,
+                )
+                .with_identifier_notation(self.identifier_notation)
+                .with_value_format(self.value_format.clone());
+                if self.memory_lists_manager.show_ascii() {
+                    memory_lists.toggle_ascii();
+                }
+                memory_lists.update_memory(
+                    &snapshot.memory,
+                    &snapshot.control_flow,
+                    self.runtime
+                        .instructions()
+                        .get(snapshot.control_flow.next_instruction_index),
+                );
+                self.instruction_list_states.force_set(snapshot.line - 1);
+                self.timeline_memory_lists = Some(memory_lists);
+            }
+            None => self.leave_timeline(),
+        }
+    }
+
+    /// Displays the current state again, if a step of the timeline is selected.
+    fn leave_timeline(&mut self) {
+        if self.timeline_memory_lists.take().is_some() {
+            self.timeline.leave();
+            match self.highlight_before_timeline.take() {
+                Some(line) => self.instruction_list_states.force_set(line),
+                None => self.instruction_list_states.deselect(),
+            }
+        }
+    }
+
+    /// Warns in the custom instruction popup, if the entered instruction uses memory that does not exist
+    /// and is not created by autodetection.
+    fn update_custom_instruction_warning(&mut self) {
+        let (State::CustomInstruction(state) | State::Playground(state)) = &mut self.state else {
+            return;
+        };
+        let input = state.instruction_str();
+        if input.trim().is_empty() {
+            state.warning = None;
+            return;
+        }
+        state.warning = Instruction::try_from(input.as_str())
+            .ok()
+            .and_then(|instruction| self.runtime.check_memory_exists(&instruction).err())
+            .map(|e| format!("Warning: {e} (memory autodetection is disabled)"));
+    }
+
+    /// Sends the current execution state to followers, if the state is shared.
+    fn broadcast_state(&self) {
+        let Some(server) = &self.state_server else {
+            return;
+        };
+        let (status, started) = match &self.state {
+            State::Default => ("not started", false),
+            State::Finished(_) => ("finished", true),
+            State::RuntimeError(_, _) => ("runtime error", true),
+            State::DebugSelect(s, _) if **s == State::Default => ("not started", false),
+            _ => ("running", true),
+        };
+        server.broadcast(&FollowState::new(
+            &self.filename,
+            status,
+            &self.runtime,
+            started,
+        ));
+    }
+
+    /// Checks if the instruction that caused a runtime error can be run again.
+    fn can_retry(&self) -> bool {
+        match &self.state {
+            State::RuntimeError(e, false) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Checks if the popup of a runtime error can be closed, so that the program is paused instead of ended.
+    ///
+    /// This is the case for divisions by zero, if the program was loaded with `--resumable-division-by-zero`.
+    fn can_close_runtime_error(&self) -> bool {
+        matches!(&self.state, State::RuntimeError(e, false) if self.resumable_division_by_zero && e.is_division_by_zero())
+    }
+
+    /// Checks if lines can be extracted into a subroutine in debug select mode.
+    ///
+    /// `previous_state` is the state to which the app returns when debug select mode is exited.
+    fn extraction_possible(&self, previous_state: &State) -> bool {
+        *previous_state == State::Default
+            && self.feature_enabled(Feature::Refactoring)
+            && self.program_builder.is_some()
+            && !self.loop_lines.as_ref().is_some_and(|l| l.show_expanded)
+    }
+
+    /// Returns the range of displayed lines that is marked in debug select mode.
+    fn marked_range(&self) -> Option<(usize, usize)> {
+        let State::DebugSelect(s, _) = &self.state else {
+            return None;
+        };
+        if !self.extraction_possible(s) {
+            return None;
+        }
+        let selected = self.instruction_list_states.selected_line()?;
+        let start = self.range_start?;
+        Some((start.min(selected), start.max(selected)))
+    }
+
     fn start_debug_select_mode(&mut self) {
+        self.record_feature(UsedFeature::DebugSelect);
+        self.leave_timeline();
+        self.range_start = None;
         let state = State::DebugSelect(
             Box::new(self.state.clone()),
             self.instruction_list_states.selected_line(),
@@ -369,14 +1392,64 @@ impl App {
         self.state = state;
     }
 
+    /// Resets the program, if many instructions were run the reset has to be confirmed first.
+    fn request_reset(&mut self) {
+        if self.confirm_reset && self.runtime.instruction_runs() >= RESET_CONFIRMATION_THRESHOLD {
+            self.state = State::ConfirmReset(Box::new(self.state.clone()));
+        } else {
+            self.reset();
+        }
+    }
+
     fn reset(&mut self) {
         self.runtime.reset();
         self.instruction_list_states.set(-1);
         self.instruction_list_states.deselect();
         self.state = State::Default;
+        self.narration.clear();
+        self.watchpoint_hit = None;
+        self.watchpoints.reset(self.runtime.runtime_memory());
+        self.timeline = Timeline::new(self.runtime.runtime_memory());
+        self.timeline_memory_lists = None;
+        self.highlight_before_timeline = None;
+        self.label_legend.reset();
+        self.branch_decision = None;
+        self.trace.clear();
         // recreate memory lists manager to remove set index memory cells from tui
-        self.memory_lists_manager =
-            MemoryListsManager::new(self.runtime.runtime_memory(), &self.theme);
+        let show_ascii = self.memory_lists_manager.show_ascii();
+        self.memory_lists_manager = MemoryListsManager::new(
+            self.runtime.runtime_memory(),
+            self.memory_lists_manager.visibility(),
+            &self.theme,
+        )
+        .with_identifier_notation(self.identifier_notation)
+        .with_value_format(self.value_format.clone());
+        if show_ascii {
+            self.memory_lists_manager.toggle_ascii();
+        }
+    }
+
+    /// Switches between displaying structured loops as written and expanded into labels and jumps.
+    fn toggle_loop_expansion(&mut self) {
+        let refactoring_disabled = self.disabled_reason(Feature::Refactoring);
+        if let Some(loop_lines) = &mut self.loop_lines {
+            loop_lines.show_expanded = !loop_lines.show_expanded;
+            if loop_lines.show_expanded {
+                self.instruction_list_states.set_lines(&loop_lines.expanded);
+            } else {
+                self.instruction_list_states.set_lines(&loop_lines.written);
+            }
+            // the reason why refactoring is disabled is kept
+            let reason = refactoring_disabled.or_else(|| {
+                loop_lines
+                    .show_expanded
+                    .then_some("structured loops are displayed expanded")
+            });
+            self.keybinding_hints
+                .set_unavailable("Extract subroutine", reason);
+            self.keybinding_hints
+                .set_unavailable("Mark range start", reason);
+        }
     }
 
     /// Performs an action. Action depends on current app state.
@@ -387,23 +1460,60 @@ impl App {
     /// Return value indicates if the program should be closed.
     fn escape_key(&mut self) -> Result<bool> {
         match &self.state {
-            State::CustomInstruction(_) => {
-                self.state = State::Running(self.instruction_list_states.breakpoints_set())
+            State::CustomInstruction(_) => self.state = State::Running(self.breakpoints_set()),
+            State::ConfirmCustomInstruction(state, _, _) => {
+                self.state = State::CustomInstruction(state.clone())
+            }
+            State::Rename(_) | State::ExtractSubroutine(_, _, _) => self.state = State::Default,
+            State::StepCount(_) => self.state = State::Running(self.breakpoints_set()),
+            State::ConfirmReset(previous_state) | State::ConfirmQuit(previous_state, _) => {
+                self.state = *previous_state.clone()
             }
+            State::ConfirmRestore(_) => self.state = State::Default,
             State::RuntimeError(e, _) => return Err(e.clone())?,
             State::CustomInstructionError(e, _) => return Err(e.clone())?,
-            State::BuildProgramError(e) => return Err(e.clone())?,
-            _ => return Ok(true),
+            State::BuildProgramError(e, _) => return Err(e.clone())?,
+            State::RefactorError(e) => return Err(e.clone())?,
+            _ => return Ok(self.request_quit()),
         }
         Ok(false)
     }
 
+    /// Closes the app, if nothing is lost. Otherwise closing has to be confirmed and the things that are not saved
+    /// can be saved first.
+    ///
+    /// Return value indicates if the app should be closed.
+    fn request_quit(&mut self) -> bool {
+        let unsaved = self.unsaved_artifacts();
+        if unsaved.is_empty() {
+            return true;
+        }
+        let unsaved = unsaved
+            .into_iter()
+            .map(|artifact| {
+                let path = match artifact {
+                    UnsavedArtifact::PlaygroundSession => {
+                        unused_file_path(PLAYGROUND_SESSION_FILE, "alpha")
+                    }
+                    UnsavedArtifact::Trace => unused_file_path(TRACE_FILE, "txt"),
+                };
+                (artifact, path)
+            })
+            .collect();
+        self.state = State::ConfirmQuit(Box::new(self.state.clone()), unsaved);
+        false
+    }
+
     /// Performs an action. Action depends on current app state.
     ///
     /// CustomInstruction: Enter a char
     fn any_char(&mut self, to_insert: char) {
         match self.state.borrow_mut() {
-            State::CustomInstruction(state) | State::Playground(state) => {
+            State::CustomInstruction(state)
+            | State::Playground(state)
+            | State::Rename(state)
+            | State::StepCount(state)
+            | State::ExtractSubroutine(state, _, _) => {
                 insert_char_at_index(&mut state.input, state.cursor_position, to_insert);
                 // check if selected item is still available in list
                 if let Some(idx) = state.allowed_values_state.selected() {
@@ -431,7 +1541,11 @@ impl App {
     /// CustomInstruction: Deletes a char
     fn backspace_key(&mut self) {
         match self.state.borrow_mut() {
-            State::CustomInstruction(state) | State::Playground(state) => {
+            State::CustomInstruction(state)
+            | State::Playground(state)
+            | State::Rename(state)
+            | State::StepCount(state)
+            | State::ExtractSubroutine(state, _, _) => {
                 let is_not_cursor_leftmost = state.cursor_position != 0;
                 if is_not_cursor_leftmost {
                     // Method "remove" is not used on the saved text for deleting the selected char.
@@ -462,7 +1576,11 @@ impl App {
     /// CustomInstruction: Deletes the char behind the cursor.
     fn delete_key(&mut self) {
         match self.state.borrow_mut() {
-            State::CustomInstruction(state) | State::Playground(state) => {
+            State::CustomInstruction(state)
+            | State::Playground(state)
+            | State::Rename(state)
+            | State::StepCount(state)
+            | State::ExtractSubroutine(state, _, _) => {
                 // Method "remove" is not used on the saved text for deleting the selected char.
                 // Reason: Using remove on String works on bytes instead of the chars.
                 // Using remove would require special care because of char boundaries.
@@ -488,7 +1606,11 @@ impl App {
     /// CustomInstruction: Move the cursor to the left.
     fn left_key(&mut self) {
         match self.state.borrow_mut() {
-            State::CustomInstruction(state) | State::Playground(state) => {
+            State::CustomInstruction(state)
+            | State::Playground(state)
+            | State::Rename(state)
+            | State::StepCount(state)
+            | State::ExtractSubroutine(state, _, _) => {
                 let cursor_moved_left = state.cursor_position.saturating_sub(1);
                 state.cursor_position = cursor_moved_left.clamp(0, state.input.len());
             }
@@ -501,7 +1623,11 @@ impl App {
     /// CustomInstruction: Move the cursor to the right.
     fn right_key(&mut self) {
         match self.state.borrow_mut() {
-            State::CustomInstruction(state) | State::Playground(state) => {
+            State::CustomInstruction(state)
+            | State::Playground(state)
+            | State::Rename(state)
+            | State::StepCount(state)
+            | State::ExtractSubroutine(state, _, _) => {
                 let cursor_moved_right = state.cursor_position.saturating_add(1);
                 state.cursor_position = cursor_moved_right.clamp(0, state.input.len());
             }
@@ -512,12 +1638,17 @@ impl App {
     /// Performs an action. Action depends on current app state.
     ///
     /// CustomInstruction: If not item is selected: Select first item, otherwise move down one item
+    /// Default | Running: Selects the next label, if the label legend is displayed.
     fn down_key(&mut self) {
         match self.state.borrow_mut() {
             State::CustomInstruction(state) | State::Playground(state) => {
                 let len = state.items_to_display().len();
                 list_down(&mut state.allowed_values_state, &len);
             }
+            State::Default | State::Running(_) if self.show_label_legend => {
+                let len = self.label_legend.len();
+                list_down(self.label_legend.list_state_mut(), &len);
+            }
             _ => (),
         }
     }
@@ -525,15 +1656,95 @@ impl App {
     /// Performs an action. Action depends on current app state.
     ///
     /// CustomInstruction: Moves the list up one item.
+    /// Default | Running: Selects the previous label, if the label legend is displayed.
     fn up_key(&mut self) {
         match self.state.borrow_mut() {
             State::CustomInstruction(state) | State::Playground(state) => {
                 list_up(&mut state.allowed_values_state, true);
             }
+            State::Default | State::Running(_) if self.show_label_legend => {
+                list_up(self.label_legend.list_state_mut(), false);
+            }
             _ => (),
         }
     }
 
+    /// Performs an action. Action depends on current app state.
+    ///
+    /// CustomInstruction | Playground: Moves the list down one page.
+    /// DebugSelect: Selects the line one page below.
+    /// Otherwise: Scrolls the memory lists down one page.
+    fn page_down_key(&mut self) {
+        match self.state.borrow_mut() {
+            State::CustomInstruction(state) | State::Playground(state) => {
+                let len = state.items_to_display().len();
+                list_page_down(&mut state.allowed_values_state, len, state.list_height);
+            }
+            State::DebugSelect(_, _) => self.instruction_list_states.set_next_page_visual(),
+            _ => {
+                self.memory_scroll = self
+                    .memory_scroll
+                    .saturating_add(self.memory_page_height.max(1));
+            }
+        }
+    }
+
+    /// Performs an action. Action depends on current app state.
+    ///
+    /// CustomInstruction | Playground: Moves the list up one page.
+    /// DebugSelect: Selects the line one page above.
+    /// Otherwise: Scrolls the memory lists up one page.
+    fn page_up_key(&mut self) {
+        match self.state.borrow_mut() {
+            State::CustomInstruction(state) | State::Playground(state) => {
+                list_page_up(&mut state.allowed_values_state, state.list_height);
+            }
+            State::DebugSelect(_, _) => self.instruction_list_states.set_prev_page_visual(),
+            _ => {
+                self.memory_scroll = self
+                    .memory_scroll
+                    .saturating_sub(self.memory_page_height.max(1));
+            }
+        }
+    }
+
+    /// Performs an action. Action depends on current app state.
+    ///
+    /// CustomInstruction | Playground: Selects the first item of the list.
+    /// DebugSelect: Selects the first line.
+    /// Otherwise: Scrolls the memory lists to the top.
+    fn home_key(&mut self) {
+        match self.state.borrow_mut() {
+            State::CustomInstruction(state) | State::Playground(state) => {
+                let len = state.items_to_display().len();
+                if len > 0 {
+                    state.allowed_values_state.select(Some(0));
+                }
+            }
+            State::DebugSelect(_, _) => self.instruction_list_states.set_first_visual(),
+            _ => self.memory_scroll = 0,
+        }
+    }
+
+    /// Performs an action. Action depends on current app state.
+    ///
+    /// CustomInstruction | Playground: Selects the last item of the list.
+    /// DebugSelect: Selects the last line.
+    /// Otherwise: Scrolls the memory lists to the bottom.
+    fn end_key(&mut self) {
+        match self.state.borrow_mut() {
+            State::CustomInstruction(state) | State::Playground(state) => {
+                let len = state.items_to_display().len();
+                if len > 0 {
+                    state.allowed_values_state.select(Some(len - 1));
+                }
+            }
+            State::DebugSelect(_, _) => self.instruction_list_states.set_last_visual(),
+            // the scroll is limited to the length of the lists when they are drawn
+            _ => self.memory_scroll = usize::MAX,
+        }
+    }
+
     /// Performs an action. Action depends on current app state.
     ///
     /// CustomInstruction | Playground: If an element is selected in the list, it is filled in to the text area
@@ -555,22 +1766,31 @@ impl App {
     ///
     /// CustomInstruction: Try to parse the text currently stored in the input field as instruction and run it
     /// CustomInstructionError: App state is set to running
+    /// Default | Running: Selects the line of the selected label in debug select mode, if the label legend is displayed.
     fn enter_key(&mut self) -> Result<()> {
         match &self.state.clone() {
             State::CustomInstruction(state) => self.custom_instruction_enter(state, false)?,
             State::Playground(state) => self.custom_instruction_enter(state, true)?,
-            State::CustomInstructionError(_, is_playground) => {
+            State::ConfirmCustomInstruction(state, instruction, preview) => {
+                if preview.is_ok() {
+                    self.run_custom_instruction(state, instruction.clone(), false)?;
+                } else {
+                    self.state = State::CustomInstruction(state.clone());
+                }
+            }
+            State::CustomInstructionError(_, is_playground)
+            | State::BuildProgramError(_, is_playground) => {
                 if *is_playground {
                     self.state = State::Playground(SingleInstruction::new(
                         &self.executed_custom_instructions,
                         &self.theme,
                     ))
                 } else {
-                    self.state = State::Running(self.instruction_list_states.breakpoints_set());
+                    self.state = State::Running(self.breakpoints_set());
                 }
             }
-            State::BuildProgramError(_) => {
-                self.state = State::Running(self.instruction_list_states.breakpoints_set());
+            State::RuntimeError(_, false) if self.can_close_runtime_error() => {
+                self.state = State::Running(self.breakpoints_set());
             }
             State::RuntimeError(_, true) => {
                 self.state = State::Playground(SingleInstruction::new(
@@ -578,22 +1798,248 @@ impl App {
                     &self.theme,
                 ));
             }
+            State::Rename(state) => self.rename_enter(&state.input)?,
+            State::StepCount(state) => {
+                if let Some(count) = parse_step_count(&state.input) {
+                    self.state = State::Running(self.breakpoints_set());
+                    self.step_count(count);
+                }
+            }
+            State::ExtractSubroutine(state, start, end) => {
+                self.extract_subroutine_enter(&state.input, *start, *end)?;
+            }
+            State::RefactorError(_) => self.state = State::Default,
+            State::ConfirmReset(_) => self.reset(),
+            State::ConfirmRestore(session) => self.restore_session(*session.clone()),
+            State::Default | State::Running(_) if self.show_label_legend => {
+                if let Some(line) = self.label_legend.selected_line() {
+                    self.start_debug_select_mode();
+                    self.instruction_list_states.force_set(line);
+                }
+            }
             _ => (),
         }
         Ok(())
     }
 
+    /// Renames a memory cell or label in the program file and in the loaded program.
+    ///
+    /// `input` contains the memory cell or label that should be renamed and the new name, separated by a whitespace.
+    fn rename_enter(&mut self, input: &str) -> Result<()> {
+        let [old, new] = input.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Ok(());
+        };
+        let lines = utils::read_file(&self.filename)?;
+        let renamed = Rename::new(&lines, old, new, None).and_then(|rename| {
+            let renamed = rename.apply(&lines)?;
+            self.runtime.rename(&rename)?;
+            Ok(renamed)
+        });
+        let renamed = match renamed {
+            Ok(renamed) => renamed,
+            Err(e) => {
+                self.state = State::RefactorError(e);
+                return Ok(());
+            }
+        };
+        utils::write_file(&renamed, &self.filename)?;
+        self.file_modified = modified_time(&self.filename);
+        // display the changed program
+        let syntax_highlighter = self.program_syntax_highlighter();
+        let lines = syntax_highlighter.input_to_lines(&renamed, self.enable_alignment)?;
+        self.instruction_list_states.set_lines(&lines);
+        if let Some(loop_lines) = &mut self.loop_lines {
+            loop_lines.expanded = syntax_highlighter.expand_loops(
+                &lines,
+                self.runtime.instructions(),
+                self.runtime.structured_loops(),
+            );
+            loop_lines.written = lines;
+            if loop_lines.show_expanded {
+                self.instruction_list_states.set_lines(&loop_lines.expanded);
+            }
+        }
+        self.label_legend = LabelLegend::new(&self.runtime);
+        // recreates the memory lists to display the renamed memory cell
+        self.reset();
+        Ok(())
+    }
+
+    /// Extracts the displayed lines from `start` to `end` into a subroutine, the program file is changed and the
+    /// program is loaded again.
+    ///
+    /// `input` contains the label of the subroutine.
+    fn extract_subroutine_enter(&mut self, input: &str, start: usize, end: usize) -> Result<()> {
+        let label = input.trim();
+        if label.is_empty() {
+            return Ok(());
+        }
+        let lines = utils::read_file(&self.filename)?;
+        // lines that start with '#' are not displayed, so line numbers are used to locate the lines in the file
+        let extracted = ExtractSubroutine::new(&lines, start + 1, end + 1, label)
+            .and_then(|extract| extract.apply(&lines))
+            .and_then(|extracted| {
+                let program_builder = self
+                    .program_builder
+                    .as_ref()
+                    .expect("program builder should be set when lines are extracted");
+                let runtime = program_builder(&extracted)
+                    .map_err(|e| RefactorError::BuildError(e.to_string()))?;
+                Ok((extracted, runtime))
+            });
+        let (extracted, runtime) = match extracted {
+            Ok(extracted) => extracted,
+            Err(e) => {
+                self.state = State::RefactorError(e);
+                return Ok(());
+            }
+        };
+        utils::write_file(&extracted, &self.filename)?;
+        self.file_modified = modified_time(&self.filename);
+        // display the changed program, breakpoints are removed because the lines have moved
+        let syntax_highlighter = self.program_syntax_highlighter();
+        let lines = syntax_highlighter.input_to_lines(&extracted, self.enable_alignment)?;
+        self.instruction_list_states =
+            InstructionListStates::new(&lines, None).with_folds(folds::subroutine_folds(&runtime));
+        self.keybinding_hints
+            .set_folding(self.instruction_list_states.has_folds());
+        self.loop_lines = self.loop_lines.take().map(|loop_lines| LoopLines {
+            expanded: syntax_highlighter.expand_loops(
+                &lines,
+                runtime.instructions(),
+                runtime.structured_loops(),
+            ),
+            written: lines,
+            show_expanded: loop_lines.show_expanded,
+        });
+        self.show_call_stack = true;
+        self.runtime = runtime;
+        self.label_legend = LabelLegend::new(&self.runtime);
+        self.keybinding_hints
+            .set_labels(!self.label_legend.is_empty());
+        self.reset();
+        Ok(())
+    }
+
+    /// Restores the custom instructions, the breakpoints and the execution state of a session that was saved
+    /// automatically.
+    ///
+    /// The execution state is only restored if the program was started in the session.
+    fn restore_session(&mut self, session: Session) {
+        self.state = State::Default;
+        for instruction in &session.custom_instructions {
+            if !self.executed_custom_instructions.contains(instruction) {
+                self.executed_custom_instructions.push(instruction.clone());
+            }
+        }
+        self.instruction_list_states
+            .set_breakpoints(&session.breakpoints);
+        if self.snapshot_outdated(&session) || !self.feature_enabled(Feature::Snapshots) {
+            return;
+        }
+        let Some(snapshot) = session.snapshot.filter(|s| s.instruction_runs > 0) else {
+            return;
+        };
+        let memory = snapshot.memory(self.runtime.runtime_memory());
+        self.runtime.restore_execution(
+            memory,
+            snapshot.next_instruction_index,
+            snapshot.call_stack,
+            snapshot.instruction_runs,
+        );
+        self.watchpoints.reset(self.runtime.runtime_memory());
+        self.timeline = Timeline::new(self.runtime.runtime_memory());
+        self.instruction_list_states
+            .set_current(snapshot.next_instruction_index);
+        self.state = if self.runtime.finished() {
+            State::Finished(true)
+        } else {
+            State::Running(self.breakpoints_set())
+        };
+    }
+
+    /// Returns true if the execution state of a saved session can not be restored, because the program was changed
+    /// after the session was saved.
+    fn snapshot_outdated(&self, session: &Session) -> bool {
+        let Some(snapshot) = &session.snapshot else {
+            return false;
+        };
+        let modified_after_save = self.file_modified.is_some_and(|modified| {
+            modified
+                .duration_since(UNIX_EPOCH)
+                .is_ok_and(|d| d.as_secs() > session.saved_at)
+        });
+        let len = self.runtime.instructions().len();
+        session.file != self.filename
+            || modified_after_save
+            || snapshot.next_instruction_index > len
+            || snapshot.call_stack.iter().any(|idx| *idx > len)
+    }
+
+    /// Returns the things that are lost when the app is closed.
+    fn unsaved_artifacts(&self) -> Vec<UnsavedArtifact> {
+        let mut unsaved = Vec::new();
+        if !self.playground_session.is_empty() {
+            unsaved.push(UnsavedArtifact::PlaygroundSession);
+        }
+        // a short run can simply be repeated
+        if self.confirm_quit
+            && !matches!(self.state, State::Playground(_))
+            && self.runtime.instruction_runs() >= RESET_CONFIRMATION_THRESHOLD
+        {
+            unsaved.push(UnsavedArtifact::Trace);
+        }
+        unsaved
+    }
+
+    /// Returns a description of `artifact`, e.g. `12 instructions run in the playground`.
+    fn describe_unsaved(&self, artifact: UnsavedArtifact) -> String {
+        match artifact {
+            UnsavedArtifact::PlaygroundSession => format!(
+                "{} instructions run in the playground",
+                self.playground_session.len()
+            ),
+            UnsavedArtifact::Trace => format!(
+                "trace of the last {} executed instructions",
+                self.trace.entries().count()
+            ),
+        }
+    }
+
+    /// Returns the content of the file into which `artifact` is saved.
+    fn unsaved_content(&self, artifact: UnsavedArtifact) -> String {
+        let lines = match artifact {
+            UnsavedArtifact::PlaygroundSession => self.playground_session.clone(),
+            UnsavedArtifact::Trace => self.trace.entries().map(ToString::to_string).collect(),
+        };
+        lines.join("\n") + "\n"
+    }
+
+    /// Returns true if the program file was changed by another program after it was loaded.
+    fn file_changed_on_disk(&self) -> bool {
+        self.file_modified.is_some() && modified_time(&self.filename) != self.file_modified
+    }
+
+    /// Returns the syntax highlighter that is used to display the program.
+    fn program_syntax_highlighter(&self) -> SyntaxHighlighter {
+        let syntax_highlighting_theme = if self.enable_syntax_highlighting {
+            self.theme.syntax_highlighting_theme()
+        } else {
+            Rc::new(SyntaxHighlightingTheme::new_disabled())
+        };
+        SyntaxHighlighter::new(&syntax_highlighting_theme)
+            .with_comparison_notation(self.comparison_notation)
+            .with_identifier_notation(self.identifier_notation)
+    }
+
     fn custom_instruction_enter(
         &mut self,
         state: &SingleInstruction,
         is_playground: bool,
     ) -> Result<()> {
-        let instruction_str = match state.allowed_values_state.selected() {
-            Some(idx) => state.items_to_display()[idx].clone(),
-            None => state.input.clone(),
-        };
+        let instruction_str = state.instruction_str();
         // check if something is entered
-        if instruction_str.is_empty() {
+        if instruction_str.trim().is_empty() {
             return Ok(());
         }
         let instruction = match Instruction::try_from(instruction_str.as_str()) {
@@ -604,6 +2050,13 @@ impl App {
                         instruction_str.to_string(),
                         "input_field",
                         1,
+                        &self
+                            .runtime
+                            .runtime_memory()
+                            .memory_cells
+                            .keys()
+                            .cloned()
+                            .collect::<Vec<_>>(),
                     ),
                     is_playground,
                 );
@@ -612,21 +2065,47 @@ impl App {
         };
         // check if instruction is allowed
         if let Some(ic) = &self.instruction_config {
-            if let Err(e) = runtime::builder::check_instructions(&[instruction.clone()], ic) {
+            if let Err(e) =
+                runtime::builder::check_instructions(std::slice::from_ref(&instruction), ic)
+            {
                 // instruction could not be build, because instruction is forbidden
-                self.state = State::BuildProgramError(*e);
+                self.forbidden_identifier = Some(instruction.identifier());
+                self.state = State::BuildProgramError(*e, is_playground);
                 return Ok(());
             }
         }
+        if self.confirm_custom_instructions && !is_playground {
+            self.state = State::ConfirmCustomInstruction(
+                state.clone(),
+                instruction.clone(),
+                Box::new(self.runtime.preview_foreign_instruction(&instruction)),
+            );
+            return Ok(());
+        }
+        self.run_custom_instruction(state, instruction, is_playground)
+    }
 
-        let instruction_line = Line::from(instruction.to_spans(&SyntaxHighlighter::new(
-            &self.theme.syntax_highlighting_theme(),
-        )));
+    /// Runs the instruction that was entered in the custom instruction popup.
+    fn run_custom_instruction(
+        &mut self,
+        state: &SingleInstruction,
+        instruction: Instruction,
+        is_playground: bool,
+    ) -> Result<()> {
+        let instruction_line = Line::from(
+            instruction.to_spans(
+                &SyntaxHighlighter::new(&self.theme.syntax_highlighting_theme())
+                    .with_comparison_notation(self.comparison_notation)
+                    .with_identifier_notation(self.identifier_notation),
+            ),
+        );
         if let Err(e) = self.runtime.run_foreign_instruction(instruction) {
             self.state = State::RuntimeError(e, is_playground);
             return Ok(());
         }
         // instruction was executed successfully
+        self.update_statistics(|statistics| statistics.custom_instructions += 1);
+        self.record_feature(UsedFeature::CustomInstructions);
         let instruction_run = state.input.clone();
         // add instruction to executed instructions, if it is not contained already and if it is not empty
         if !self.executed_custom_instructions.contains(&instruction_run)
@@ -638,6 +2117,9 @@ impl App {
             }
             self.executed_custom_instructions.push(instruction_run);
         }
+        if is_playground {
+            self.playground_session.push(state.instruction_str());
+        }
         // set new state
         if is_playground {
             // if in playground mode, add instruction to main window
@@ -646,14 +2128,14 @@ impl App {
                     .add_instruction(instruction_line);
             } else {
                 self.instruction_list_states
-                    .add_instruction(Line::from(instruction_str));
+                    .add_instruction(Line::from(state.instruction_str()));
             }
             self.state = State::Playground(SingleInstruction::new(
                 &self.executed_custom_instructions,
                 &self.theme,
             ));
         } else {
-            self.state = State::Running(self.instruction_list_states.breakpoints_set());
+            self.state = State::Running(self.breakpoints_set());
         }
         Ok(())
     }
@@ -711,6 +2193,24 @@ pub fn list_down(state: &mut ListState, len: &usize) {
     }
 }
 
+/// Moves the selection of the provided list down by `page` items, stops at the last item.
+pub fn list_page_down(state: &mut ListState, len: usize, page: usize) {
+    if len == 0 {
+        return;
+    }
+    let idx = state
+        .selected()
+        .map_or(0, |idx| idx.saturating_add(page.max(1)));
+    state.select(Some(idx.min(len - 1)));
+}
+
+/// Moves the selection of the provided list up by `page` items, stops at the first item.
+pub fn list_page_up(state: &mut ListState, page: usize) {
+    if let Some(idx) = state.selected() {
+        state.select(Some(idx.saturating_sub(page.max(1))));
+    }
+}
+
 /// Scrolls the provided list up.
 pub fn list_up(state: &mut ListState, deselect: bool) {
     if let Some(idx) = state.selected() {
@@ -728,3 +2228,29 @@ pub fn insert_char_at_index(s: &mut String, idx: usize, to_insert: char) {
     chars.insert(idx, to_insert);
     *s = chars.into_iter().collect()
 }
+
+/// Returns the time at which the file was last modified, `None` if the file does not exist or the time is not available.
+fn modified_time(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Returns the path of a file named `name` with the extension `extension` that does not exist yet, a number is added
+/// to the name if the file exists already.
+fn unused_file_path(name: &str, extension: &str) -> String {
+    let mut path = format!("{name}.{extension}");
+    let mut number = 2;
+    while fs::metadata(&path).is_ok() {
+        path = format!("{name}_{number}.{extension}");
+        number += 1;
+    }
+    path
+}
+
+/// Parses the number of instructions that is entered in the step prompt, e.g. `step 50` or `50`.
+///
+/// Returns `None` if the input is not a positive number.
+fn parse_step_count(input: &str) -> Option<usize> {
+    let input = input.trim();
+    let count = input.strip_prefix("step").unwrap_or(input).trim();
+    count.parse().ok().filter(|count| *count > 0)
+}
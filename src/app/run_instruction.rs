@@ -1,7 +1,7 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout},
     style::Style,
-    widgets::{Block, Borders, Clear, List, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListState, Paragraph, Wrap},
 };
 use trie_rs::TrieBuilder;
 
@@ -20,6 +20,10 @@ pub struct SingleInstruction {
     ///
     /// Used to populate the list.
     pub executed_instructions: Vec<String>,
+    /// Warning that is displayed below the input field, e.g. when the instruction uses memory that does not exist.
+    pub warning: Option<String>,
+    /// Number of items that fit into the list, set when the list is drawn.
+    pub list_height: usize,
     theme: SharedTheme,
 }
 
@@ -31,6 +35,8 @@ impl SingleInstruction {
             cursor_position: 0,
             allowed_values_state: ListState::default(),
             executed_instructions: executed_instructions.to_owned(),
+            warning: None,
+            list_height: 0,
             theme: theme.clone(),
         }
     }
@@ -55,9 +61,14 @@ impl SingleInstruction {
         } else {
             super::centered_rect(43, 40, None, r)
         };
+        let warning_height = if self.warning.is_some() { 2 } else { 0 };
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(3)])
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(warning_height),
+                Constraint::Min(3),
+            ])
             .margin(1)
             .split(area);
         // clear background
@@ -80,6 +91,14 @@ impl SingleInstruction {
             chunks[0].x + self.cursor_position as u16 + 1,
             chunks[0].y + 1,
         );
+        if let Some(warning) = &self.warning {
+            f.render_widget(
+                Paragraph::new(warning.as_str())
+                    .style(self.theme.error_block_border())
+                    .wrap(Wrap { trim: true }),
+                chunks[1],
+            );
+        }
         // setup list
         let items_to_display = self.items_to_display();
         let possible_items = List::new(items_to_display)
@@ -92,8 +111,45 @@ impl SingleInstruction {
             )
             .style(Style::default())
             .highlight_style(self.theme.list_item_highlight(false));
+        self.list_height = chunks[2].height.saturating_sub(2) as usize;
         // render list
-        f.render_stateful_widget(possible_items, chunks[1], &mut self.allowed_values_state)
+        f.render_stateful_widget(possible_items, chunks[2], &mut self.allowed_values_state)
+    }
+
+    /// Draws only the input field of this single instruction in a popup that is centered in `r`.
+    pub fn draw_input_popup(
+        &self,
+        f: &mut ratatui::prelude::Frame,
+        r: ratatui::prelude::Rect,
+        title: &str,
+        input_title: &str,
+    ) {
+        let area = super::centered_rect_abs(5, 50, r);
+        let input = Paragraph::new(self.input.as_str())
+            .block(Block::default().borders(Borders::ALL).title(input_title));
+        let outer_block = Block::default()
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .borders(Borders::ALL)
+            .border_style(self.theme.custom_instruction())
+            .style(self.theme.single_instruction_block());
+        let inner_area = outer_block.inner(area);
+        // clear background
+        f.render_widget(Clear, area);
+        f.render_widget(outer_block, area);
+        f.render_widget(input, inner_area);
+        f.set_cursor(
+            inner_area.x + self.cursor_position as u16 + 1,
+            inner_area.y + 1,
+        );
+    }
+
+    /// Returns the selected instruction or the entered instruction, if no instruction is selected.
+    pub fn instruction_str(&self) -> String {
+        match self.allowed_values_state.selected() {
+            Some(idx) => self.items_to_display()[idx].clone(),
+            None => self.input.clone(),
+        }
     }
 
     pub fn items_to_display(&self) -> Vec<String> {
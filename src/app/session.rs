@@ -0,0 +1,341 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    base::{Accumulator, MemoryCell},
+    clock::SharedClock,
+    preset::AutosaveConfig,
+    runtime::{Runtime, RuntimeMemory},
+};
+
+/// Saves the session of the app periodically.
+#[derive(Debug)]
+pub struct Autosave {
+    /// File into which the session is saved.
+    path: PathBuf,
+    interval: Duration,
+    /// If set, the memory and the position in the program are saved too.
+    snapshot: bool,
+    last_save: Instant,
+    clock: SharedClock,
+    /// Error of the last save, if it failed.
+    error: Option<String>,
+}
+
+impl Autosave {
+    /// Creates the autosave for the program file `file`, returns `None` if autosave is disabled or no directory is
+    /// available.
+    ///
+    /// `clock` determines when the session is saved and the time that is saved with it.
+    pub fn new(config: &AutosaveConfig, file: &str, clock: SharedClock) -> Option<Self> {
+        if config.interval == 0 {
+            return None;
+        }
+        Some(Self {
+            path: config.directory()?.join(session_file_name(file)),
+            interval: Duration::from_secs(config.interval),
+            snapshot: config.snapshot,
+            last_save: clock.now(),
+            clock,
+            error: None,
+        })
+    }
+
+    /// Returns the time until the session should be saved next.
+    pub fn time_until_save(&self) -> Duration {
+        self.interval
+            .saturating_sub(self.clock.elapsed(self.last_save))
+    }
+
+    /// Returns the current time in seconds since the unix epoch, it is saved with the session.
+    pub fn now(&self) -> u64 {
+        self.clock.unix_time()
+    }
+
+    /// Returns true if the memory and the position in the program are saved.
+    pub fn snapshot(&self) -> bool {
+        self.snapshot
+    }
+
+    /// Returns the error of the last save, if it failed.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Reads the session that was saved, when the app was not closed normally the last time.
+    ///
+    /// Returns `None` if the app was closed normally, if no session was saved or if the session contains nothing
+    /// that can be restored.
+    pub fn recover(&self) -> Option<Session> {
+        let content = fs::read_to_string(&self.path).ok()?;
+        let session = serde_json::from_str::<Session>(&content).ok()?;
+        (!session.clean_exit && !session.is_empty()).then_some(session)
+    }
+
+    /// Writes the session to the session file.
+    pub fn save(&mut self, session: &Session) {
+        self.last_save = self.clock.now();
+        self.error = session.write(&self.path).err();
+    }
+}
+
+/// Interactive work of a session that is saved, so that it is not lost when the terminal is closed unexpectedly.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Session {
+    /// Program file that was loaded, `Playground` for playground sessions.
+    pub file: String,
+    /// Time at which the session was saved, in seconds since the unix epoch.
+    pub saved_at: u64,
+    /// True if the session was saved when the app was closed normally.
+    pub clean_exit: bool,
+    /// Custom instructions that were run, including the instructions of the instruction history.
+    pub custom_instructions: Vec<String>,
+    /// Instructions that were run in the playground, in the order in which they were run.
+    pub playground_instructions: Vec<String>,
+    /// Lines in which breakpoints are set, starting at 1.
+    pub breakpoints: Vec<usize>,
+    /// State of the execution, `None` if the program was not started or snapshots are disabled.
+    pub snapshot: Option<ExecutionSnapshot>,
+}
+
+impl Session {
+    /// Returns true if the session contains nothing that can be restored.
+    pub fn is_empty(&self) -> bool {
+        self.custom_instructions.is_empty()
+            && self.playground_instructions.is_empty()
+            && self.breakpoints.is_empty()
+            && self
+                .snapshot
+                .as_ref()
+                .is_none_or(|s| s.instruction_runs == 0)
+    }
+
+    /// Returns how long ago the session was saved, e.g. `5 minutes ago`.
+    ///
+    /// `now` is the current time in seconds since the unix epoch.
+    pub fn age(&self, now: u64) -> String {
+        let secs = now.saturating_sub(self.saved_at);
+        match secs {
+            0..=59 => "less than a minute ago".to_string(),
+            60..=3599 => format!("{} minutes ago", secs / 60),
+            3600..=86_399 => format!("{} hours ago", secs / 3600),
+            _ => format!("{} days ago", secs / 86_400),
+        }
+    }
+
+    /// Writes the session as json into the file at `path`, missing directories are created.
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, content).map_err(|e| e.to_string())
+    }
+}
+
+/// Memory and position in the program of a running program.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionSnapshot {
+    /// Index of the instruction that is run next.
+    pub next_instruction_index: usize,
+    /// Number of instructions that were run.
+    pub instruction_runs: usize,
+    /// Indices of the instructions at which the execution continues when a function returns.
+    pub call_stack: Vec<usize>,
+    /// Accumulators and their values, sorted by index.
+    pub accumulators: Vec<(usize, Option<i32>)>,
+    /// Value of the gamma accumulator, is `None` if the gamma accumulator does not exist.
+    pub gamma: Option<Option<i32>>,
+    /// Memory cells and their values, sorted by name.
+    pub memory_cells: Vec<(String, Option<i32>)>,
+    /// Index memory cells and their values, sorted by index.
+    pub index_memory_cells: Vec<(usize, Option<i32>)>,
+    /// Values on the stack, the last value is on top of the stack.
+    pub stack: Vec<i32>,
+}
+
+impl ExecutionSnapshot {
+    /// Creates a snapshot of the current state of the runtime.
+    pub fn new(runtime: &Runtime) -> Self {
+        let memory = runtime.runtime_memory();
+        let mut accumulators = memory
+            .accumulators
+            .iter()
+            .map(|(idx, acc)| (*idx, acc.data))
+            .collect::<Vec<_>>();
+        accumulators.sort_by_key(|(idx, _)| *idx);
+        let mut memory_cells = memory
+            .memory_cells
+            .values()
+            .map(|cell| (cell.label.clone(), cell.data))
+            .collect::<Vec<_>>();
+        memory_cells.sort();
+        let mut index_memory_cells = memory
+            .index_memory_cells
+            .iter()
+            .map(|(idx, value)| (*idx, *value))
+            .collect::<Vec<_>>();
+        index_memory_cells.sort_by_key(|(idx, _)| *idx);
+        Self {
+            next_instruction_index: runtime.next_instruction_index(),
+            instruction_runs: runtime.instruction_runs(),
+            call_stack: runtime.control_flow().call_stack.clone(),
+            accumulators,
+            gamma: memory.gamma,
+            memory_cells,
+            index_memory_cells,
+            stack: memory.stack.clone(),
+        }
+    }
+}
+
+impl ExecutionSnapshot {
+    /// Returns the memory of the snapshot, memory locations that are not contained in the snapshot are taken from
+    /// `memory`.
+    pub fn memory(&self, memory: &RuntimeMemory) -> RuntimeMemory {
+        let mut memory = memory.clone();
+        for (idx, value) in &self.accumulators {
+            memory.accumulators.insert(
+                *idx,
+                Accumulator {
+                    id: *idx,
+                    data: *value,
+                },
+            );
+        }
+        memory.gamma = self.gamma;
+        for (label, value) in &self.memory_cells {
+            memory.memory_cells.insert(
+                label.clone(),
+                MemoryCell {
+                    label: label.clone(),
+                    data: *value,
+                },
+            );
+        }
+        memory
+            .index_memory_cells
+            .extend(self.index_memory_cells.iter().copied());
+        memory.stack.clone_from(&self.stack);
+        memory
+    }
+}
+
+/// Returns the name of the file into which the session of the program file `file` is saved.
+///
+/// The name is derived from the absolute path of the file, so that each program has its own session file.
+pub fn session_file_name(file: &str) -> String {
+    let path = fs::canonicalize(file).unwrap_or_else(|_| PathBuf::from(file));
+    let name = path
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if matches!(c, '/' | '\\' | ':') {
+                '%'
+            } else {
+                c
+            }
+        })
+        .collect::<String>();
+    format!("{name}.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, sync::Arc, time::Duration};
+
+    use crate::{
+        clock::{ManualClock, SharedClock},
+        preset::AutosaveConfig,
+        utils::test_utils,
+    };
+
+    use super::{session_file_name, Autosave, ExecutionSnapshot, Session};
+
+    #[test]
+    fn test_session_file_name() {
+        assert_eq!(session_file_name("Playground"), "Playground.json");
+        assert!(session_file_name("/tmp/program.alpha").ends_with("%tmp%program.alpha.json"));
+    }
+
+    #[test]
+    fn test_autosave_interval() {
+        let clock = Arc::new(ManualClock::new());
+        let config = AutosaveConfig {
+            interval: 30,
+            directory: Some(
+                env::temp_dir()
+                    .join("alpha_tui_test_autosave_interval")
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            snapshot: false,
+        };
+        let mut autosave = Autosave::new(
+            &config,
+            "program.alpha",
+            SharedClock::from(Arc::clone(&clock)),
+        )
+        .unwrap();
+        assert_eq!(autosave.time_until_save(), Duration::from_secs(30));
+        clock.advance(Duration::from_secs(20));
+        assert_eq!(autosave.time_until_save(), Duration::from_secs(10));
+        clock.advance(Duration::from_secs(100));
+        assert_eq!(autosave.time_until_save(), Duration::ZERO);
+        let session = Session {
+            file: "program.alpha".to_string(),
+            saved_at: autosave.now(),
+            clean_exit: false,
+            custom_instructions: vec!["a := 1".to_string()],
+            playground_instructions: Vec::new(),
+            breakpoints: Vec::new(),
+            snapshot: None,
+        };
+        autosave.save(&session);
+        assert_eq!(autosave.error(), None);
+        assert_eq!(autosave.recover(), Some(session.clone()));
+        assert_eq!(autosave.time_until_save(), Duration::from_secs(30));
+        clock.advance(Duration::from_secs(150));
+        assert_eq!(session.age(autosave.now()), "2 minutes ago");
+        _ = fs::remove_dir_all(config.directory().unwrap());
+    }
+
+    #[test]
+    fn test_session_serialization() {
+        let mut rt = test_utils::runtime_from_str("a := 5\npush\np(h1) := 3").unwrap();
+        for _ in 0..2 {
+            rt.step().unwrap();
+        }
+        let snapshot = ExecutionSnapshot::new(&rt);
+        assert_eq!(snapshot.next_instruction_index, 2);
+        assert_eq!(snapshot.instruction_runs, 2);
+        assert_eq!(snapshot.stack, vec![5]);
+        assert!(snapshot.accumulators.contains(&(0, Some(5))));
+        let session = Session {
+            file: "program.alpha".to_string(),
+            saved_at: 10,
+            clean_exit: false,
+            custom_instructions: vec!["a := 1".to_string()],
+            playground_instructions: Vec::new(),
+            breakpoints: vec![2],
+            snapshot: Some(snapshot),
+        };
+        assert!(!session.is_empty());
+        let json = serde_json::to_string(&session).unwrap();
+        assert_eq!(serde_json::from_str::<Session>(&json).unwrap(), session);
+    }
+
+    #[test]
+    fn test_restore_memory() {
+        let mut rt = test_utils::runtime_from_str("a := 5\npush\np(h1) := 3").unwrap();
+        let initial_memory = rt.runtime_memory().clone();
+        rt.run().unwrap();
+        let snapshot = ExecutionSnapshot::new(&rt);
+        assert_eq!(&snapshot.memory(&initial_memory), rt.runtime_memory());
+    }
+}
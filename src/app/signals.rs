@@ -0,0 +1,81 @@
+use std::{
+    io,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crossterm::{
+    event::EnableMouseCapture,
+    terminal::{self, EnterAlternateScreen},
+};
+use miette::{IntoDiagnostic, Result};
+
+/// How often received signals are checked while no key is pressed.
+pub const SIGNAL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Signals that are received while the tui is running.
+///
+/// On platforms other than unix no signals are received.
+#[derive(Debug, Default)]
+pub struct Signals {
+    /// Set when SIGTERM is received, the app should be closed.
+    terminate: Arc<AtomicBool>,
+    /// Set when SIGCONT is received, the terminal has to be set up again after the process was suspended.
+    resumed: Arc<AtomicBool>,
+}
+
+impl Signals {
+    /// Starts to listen for SIGTERM and SIGCONT.
+    pub fn register() -> Result<Self> {
+        let signals = Self::default();
+        #[cfg(unix)]
+        {
+            use signal_hook::consts::{SIGCONT, SIGTERM};
+            signal_hook::flag::register(SIGTERM, Arc::clone(&signals.terminate))
+                .into_diagnostic()?;
+            signal_hook::flag::register(SIGCONT, Arc::clone(&signals.resumed)).into_diagnostic()?;
+        }
+        Ok(signals)
+    }
+
+    /// Returns true if SIGTERM was received.
+    pub fn terminate_requested(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if SIGCONT was received since this function was called last.
+    pub fn take_resumed(&self) -> bool {
+        self.resumed.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Suspends the process, like Ctrl+Z does when the terminal is not in raw mode.
+///
+/// The terminal is restored before the process is stopped, so that the shell can be used. This function returns when
+/// the process is continued, e.g. with `fg`, the terminal then has to be set up again with [`resume`].
+pub fn suspend() -> Result<()> {
+    #[cfg(unix)]
+    {
+        terminal::disable_raw_mode().into_diagnostic()?;
+        crossterm::execute!(
+            io::stdout(),
+            terminal::LeaveAlternateScreen,
+            crossterm::event::DisableMouseCapture,
+            crossterm::cursor::Show
+        )
+        .into_diagnostic()?;
+        signal_hook::low_level::raise(signal_hook::consts::SIGTSTP).into_diagnostic()?;
+    }
+    Ok(())
+}
+
+/// Sets the terminal up again after the process was continued.
+pub fn resume() -> Result<()> {
+    terminal::enable_raw_mode().into_diagnostic()?;
+    crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)
+        .into_diagnostic()?;
+    Ok(())
+}
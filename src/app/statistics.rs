@@ -0,0 +1,180 @@
+use std::{
+    collections::BTreeSet,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{clock::SharedClock, preset::StatisticsConfig};
+
+/// Features of the tui whose use is recorded in the usage statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsedFeature {
+    Breakpoints,
+    DebugSelect,
+    JumpToLine,
+    SkipInstruction,
+    CustomInstructions,
+    Retry,
+    Timeline,
+    LabelLegend,
+    Trace,
+    PresentationMode,
+    Refactoring,
+}
+
+/// Aggregates of one session of the tui, one line is appended to the statistics file for each session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStatistics {
+    /// Program file that was loaded, `Playground` for playground sessions.
+    pub file: String,
+    /// Time at which the session was started, in seconds since the unix epoch.
+    pub started_at: u64,
+    /// Number of seconds the tui was open.
+    pub duration: u64,
+    /// Number of times the program was started.
+    pub programs_run: u64,
+    /// Number of times the program ran to the end.
+    pub programs_finished: u64,
+    /// Number of instructions of the program that were run.
+    pub instructions_executed: u64,
+    /// Number of custom instructions that were run successfully.
+    pub custom_instructions: u64,
+    /// Number of runtime errors that occurred.
+    pub runtime_errors: u64,
+    /// Features that were used at least once.
+    pub features: BTreeSet<UsedFeature>,
+}
+
+/// Records the usage statistics of a session, only created if the statistics are enabled in the config file.
+#[derive(Debug)]
+pub struct Statistics {
+    /// File to which the statistics are appended.
+    path: PathBuf,
+    started: Instant,
+    clock: SharedClock,
+    session: SessionStatistics,
+}
+
+impl Statistics {
+    /// Creates the statistics of a session of the program file `file`, returns `None` if the statistics are disabled
+    /// or no file is available.
+    pub fn new(config: &StatisticsConfig, file: &str, clock: SharedClock) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self {
+            path: config.file()?,
+            started: clock.now(),
+            session: SessionStatistics {
+                file: file.to_string(),
+                started_at: clock.unix_time(),
+                ..SessionStatistics::default()
+            },
+            clock,
+        })
+    }
+
+    /// Returns the statistics that were recorded so far.
+    pub fn session(&mut self) -> &mut SessionStatistics {
+        &mut self.session
+    }
+
+    /// Records that `feature` was used.
+    pub fn record_feature(&mut self, feature: UsedFeature) {
+        self.session.features.insert(feature);
+    }
+
+    /// Appends the statistics of the session to the statistics file, missing directories are created.
+    pub fn write(&mut self) -> Result<(), String> {
+        self.session.duration = self.clock.elapsed(self.started).as_secs();
+        append(&self.path, &self.session).map_err(|e| {
+            format!(
+                "Unable to write usage statistics to [{}]: {e}",
+                self.path.display()
+            )
+        })
+    }
+}
+
+/// Appends `session` as a line of json to the file at `path`.
+fn append(path: &Path, session: &SessionStatistics) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let line = serde_json::to_string(session).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{line}").map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, sync::Arc, time::Duration};
+
+    use crate::{
+        clock::{ManualClock, SharedClock},
+        preset::StatisticsConfig,
+    };
+
+    use super::{SessionStatistics, Statistics, UsedFeature};
+
+    #[test]
+    fn test_statistics_disabled() {
+        assert!(Statistics::new(
+            &StatisticsConfig::default(),
+            "program.alpha",
+            SharedClock::default()
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn test_statistics_write() {
+        let path = env::temp_dir().join("alpha_tui_test_statistics.jsonl");
+        _ = fs::remove_file(&path);
+        let config = StatisticsConfig {
+            enabled: true,
+            file: Some(path.to_string_lossy().to_string()),
+        };
+        let clock = Arc::new(ManualClock::new());
+        for runs in 1..=2 {
+            let mut statistics = Statistics::new(
+                &config,
+                "program.alpha",
+                SharedClock::from(Arc::clone(&clock)),
+            )
+            .unwrap();
+            statistics.session().programs_run = runs;
+            statistics.session().instructions_executed = 10;
+            statistics.record_feature(UsedFeature::Timeline);
+            statistics.record_feature(UsedFeature::Breakpoints);
+            statistics.record_feature(UsedFeature::Timeline);
+            clock.advance(Duration::from_secs(90));
+            statistics.write().unwrap();
+        }
+        let lines = fs::read_to_string(&path).unwrap();
+        let sessions = lines
+            .lines()
+            .map(|line| serde_json::from_str::<SessionStatistics>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[1].file, "program.alpha");
+        assert_eq!(sessions[1].programs_run, 2);
+        assert_eq!(sessions[1].instructions_executed, 10);
+        assert_eq!(sessions[1].duration, 90);
+        assert_eq!(
+            sessions[1].features.iter().copied().collect::<Vec<_>>(),
+            vec![UsedFeature::Breakpoints, UsedFeature::Timeline]
+        );
+        assert!(lines.contains(r#""features":["breakpoints","timeline"]"#));
+        fs::remove_file(&path).unwrap();
+    }
+}
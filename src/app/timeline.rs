@@ -0,0 +1,196 @@
+use std::{collections::VecDeque, ops::Range};
+
+use crate::runtime::{observer::ExecutionEvent, ControlFlow, Runtime, RuntimeMemory};
+
+/// Maximum number of steps that are recorded, the oldest steps are discarded when more steps are run.
+const MAX_STEPS: usize = 10_000;
+
+/// State of the program after an instruction was executed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Number of instructions that were executed, including this one.
+    pub step: usize,
+    /// Line of the instruction that was executed.
+    pub line: usize,
+    /// The instruction that was executed.
+    pub instruction: String,
+    pub memory: RuntimeMemory,
+    pub control_flow: ControlFlow,
+}
+
+/// Records the state of the program after each executed instruction, so that earlier states can be displayed again.
+///
+/// Moving through the timeline only changes which state is displayed, the runtime is not changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    /// Memory before the first recorded step was run.
+    base: RuntimeMemory,
+    steps: VecDeque<Snapshot>,
+    /// Index of the step whose state is displayed, `None` if the current state of the runtime is displayed.
+    position: Option<usize>,
+}
+
+impl Timeline {
+    /// Creates an empty timeline, `memory` is the memory before the program is run.
+    pub fn new(memory: &RuntimeMemory) -> Self {
+        Self {
+            base: memory.clone(),
+            steps: VecDeque::new(),
+            position: None,
+        }
+    }
+
+    /// Records the state of `runtime` after the instruction of `event` was executed.
+    pub fn record(&mut self, event: &ExecutionEvent, runtime: &Runtime) {
+        self.steps.push_back(Snapshot {
+            step: event.step,
+            line: event.line,
+            instruction: event.instruction.clone(),
+            memory: runtime.runtime_memory().clone(),
+            control_flow: runtime.control_flow().clone(),
+        });
+        if self.steps.len() > MAX_STEPS {
+            if let Some(discarded) = self.steps.pop_front() {
+                self.base = discarded.memory;
+            }
+            self.position = self.position.map(|p| p.saturating_sub(1));
+        }
+    }
+
+    /// Number of recorded steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Index of the step whose state is displayed, `None` if the current state is displayed.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Index of the step that is highlighted in the timeline, the last step if the current state is displayed.
+    pub fn highlighted(&self) -> Option<usize> {
+        self.position.or(self.steps.len().checked_sub(1))
+    }
+
+    /// Displays the state of the previous step.
+    ///
+    /// Returns false if no earlier step is recorded.
+    pub fn back(&mut self) -> bool {
+        match self.highlighted() {
+            Some(idx) if idx > 0 => {
+                self.position = Some(idx - 1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Displays the state of the next step, the current state is displayed when the last step is reached.
+    ///
+    /// Returns false if the current state is already displayed.
+    pub fn forward(&mut self) -> bool {
+        let Some(idx) = self.position else {
+            return false;
+        };
+        self.position = if idx + 1 >= self.steps.len() - 1 {
+            None
+        } else {
+            Some(idx + 1)
+        };
+        true
+    }
+
+    /// Displays the current state again.
+    pub fn leave(&mut self) {
+        self.position = None;
+    }
+
+    /// Returns the recorded step with index `idx`.
+    pub fn snapshot(&self, idx: usize) -> Option<&Snapshot> {
+        self.steps.get(idx)
+    }
+
+    /// Returns the displayed step and the memory before it was run, `None` if the current state is displayed.
+    pub fn selected(&self) -> Option<(&Snapshot, &RuntimeMemory)> {
+        let idx = self.position?;
+        let before = match idx {
+            0 => &self.base,
+            idx => &self.steps[idx - 1].memory,
+        };
+        Some((&self.steps[idx], before))
+    }
+
+    /// Returns the range of steps that are displayed in a timeline that is `width` characters wide.
+    ///
+    /// The highlighted step is kept in the middle, if not all steps fit.
+    pub fn visible_range(&self, width: usize) -> Range<usize> {
+        let len = self.steps.len();
+        if len <= width {
+            return 0..len;
+        }
+        let highlighted = self.highlighted().unwrap_or_default();
+        let start = highlighted.saturating_sub(width / 2).min(len - width);
+        start..start + width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{runtime::observer::ExecutionEvent, utils::test_utils};
+
+    use super::Timeline;
+
+    #[test]
+    fn test_timeline() {
+        let mut rt = test_utils::runtime_from_str("a := 1\na := 2\na := 3").unwrap();
+        let mut timeline = Timeline::new(rt.runtime_memory());
+        for _ in 0..3 {
+            let mut last_event = None;
+            rt.step_observed(&mut |event: &ExecutionEvent| last_event = Some(event.clone()))
+                .unwrap();
+            timeline.record(&last_event.unwrap(), &rt);
+        }
+        assert_eq!(timeline.len(), 3);
+        assert_eq!(timeline.highlighted(), Some(2));
+        assert!(timeline.selected().is_none());
+        assert!(!timeline.forward());
+        assert!(timeline.back());
+        assert!(timeline.back());
+        assert!(!timeline.back());
+        let (snapshot, before) = timeline.selected().unwrap();
+        assert_eq!(snapshot.line, 1);
+        assert_eq!(snapshot.instruction, "a0 := 1");
+        assert_eq!(snapshot.memory.accumulators[&0].data, Some(1));
+        assert_eq!(before.accumulators[&0].data, None);
+        assert!(timeline.forward());
+        assert_eq!(timeline.position(), Some(1));
+        // the last step is the current state
+        assert!(timeline.forward());
+        assert_eq!(timeline.position(), None);
+    }
+
+    #[test]
+    fn test_visible_range() {
+        let mut rt = test_utils::runtime_from_str("loop: a := 1\ngoto loop").unwrap();
+        let mut timeline = Timeline::new(rt.runtime_memory());
+        for _ in 0..20 {
+            let mut last_event = None;
+            rt.step_observed(&mut |event: &ExecutionEvent| last_event = Some(event.clone()))
+                .unwrap();
+            timeline.record(&last_event.unwrap(), &rt);
+        }
+        assert_eq!(timeline.visible_range(30), 0..20);
+        assert_eq!(timeline.visible_range(10), 10..20);
+        for _ in 0..15 {
+            timeline.back();
+        }
+        assert_eq!(timeline.visible_range(10), 0..10);
+        timeline.forward();
+        timeline.forward();
+        assert_eq!(timeline.visible_range(10), 1..11);
+    }
+}
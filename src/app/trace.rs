@@ -0,0 +1,132 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use crate::runtime::observer::ExecutionEvent;
+
+use super::BranchDecision;
+
+/// Number of executed instructions that are kept, the oldest instruction is discarded when more instructions are run.
+const TRACE_LENGTH: usize = 100;
+
+/// An instruction that was executed, listed in the trace panel.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    /// Number of instructions that were executed, including this one.
+    pub step: usize,
+    /// Line of the instruction that was executed.
+    pub line: usize,
+    /// The instruction that was executed.
+    pub instruction: String,
+    /// Outcome of the instruction, if it is a conditional jump.
+    pub branch: Option<BranchDecision>,
+    /// True if the instruction was skipped instead of executed.
+    pub skipped: bool,
+}
+
+impl Display for TraceEntry {
+    /// Formats the entry as it is listed in the trace panel.
+    ///
+    /// Conditional jumps are marked with `↷` if the jump was taken and with `↓` if it was not taken. Skipped
+    /// instructions are marked with `skip` instead of the step number.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the outcome is displayed in front of the instruction, so that it is not cut off in a narrow panel
+        let branch = match self.branch {
+            Some(BranchDecision::Taken(_)) => "↷ ",
+            Some(BranchDecision::NotTaken(_)) => "↓ ",
+            None => "",
+        };
+        if self.skipped {
+            return write!(f, "skip {:>3}: {}", self.line, self.instruction);
+        }
+        write!(
+            f,
+            "{:>4} {:>3}: {branch}{}",
+            self.step, self.line, self.instruction
+        )
+    }
+}
+
+/// Keeps the last executed instructions, so that the recent history of the run is visible in the tui.
+///
+/// In contrast to the timeline, no memory is stored for the instructions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    entries: VecDeque<TraceEntry>,
+}
+
+impl Trace {
+    /// Adds the instruction of `event` to the trace, `branch` is the outcome if the instruction is a conditional jump.
+    pub fn record(&mut self, event: &ExecutionEvent, branch: Option<BranchDecision>) {
+        self.entries.push_back(TraceEntry {
+            step: event.step,
+            line: event.line,
+            instruction: event.instruction.clone(),
+            branch,
+            skipped: false,
+        });
+        self.discard_oldest();
+    }
+
+    /// Adds an instruction in line `line` that was skipped, `step` is the number of instructions that were executed
+    /// before.
+    pub fn record_skipped(&mut self, step: usize, line: usize, instruction: String) {
+        self.entries.push_back(TraceEntry {
+            step,
+            line,
+            instruction,
+            branch: None,
+            skipped: true,
+        });
+        self.discard_oldest();
+    }
+
+    fn discard_oldest(&mut self) {
+        if self.entries.len() > TRACE_LENGTH {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Forgets the executed instructions, used when the program is reset.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the executed instructions, the most recent instruction is returned last.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{app::BranchDecision, runtime::observer::ExecutionEvent, utils::test_utils};
+
+    use super::{Trace, TRACE_LENGTH};
+
+    #[test]
+    fn test_trace() {
+        let mut rt = test_utils::runtime_from_str("loop: a := 1\ngoto loop").unwrap();
+        let mut trace = Trace::default();
+        for step in 0..TRACE_LENGTH + 6 {
+            let mut last_event = None;
+            rt.step_observed(&mut |event: &ExecutionEvent| last_event = Some(event.clone()))
+                .unwrap();
+            let branch = (step == TRACE_LENGTH + 5).then_some(BranchDecision::Taken(0));
+            trace.record(&last_event.unwrap(), branch);
+        }
+        // the oldest instructions are discarded
+        assert_eq!(trace.entries().count(), TRACE_LENGTH);
+        let first = trace.entries().next().unwrap();
+        assert_eq!(first.step, 7);
+        assert_eq!(first.line, 1);
+        let last = trace.entries().next_back().unwrap();
+        assert_eq!(last.step, TRACE_LENGTH + 6);
+        assert_eq!(last.instruction, "goto loop");
+        assert_eq!(last.branch, Some(BranchDecision::Taken(0)));
+        assert!(!last.skipped);
+        trace.record_skipped(TRACE_LENGTH + 6, 1, "a0 := 1".to_string());
+        assert_eq!(trace.entries().count(), TRACE_LENGTH);
+        assert!(trace.entries().next_back().unwrap().skipped);
+        trace.clear();
+        assert_eq!(trace.entries().count(), 0);
+    }
+}
@@ -0,0 +1,121 @@
+use ratatui::{style::Style, text::Line};
+
+/// Number of lines of text that is rendered with block characters.
+pub const BIG_TEXT_HEIGHT: u16 = 5;
+
+/// Glyphs of the block character font, `#` is a filled block.
+///
+/// Lowercase letters are displayed with the glyph of the uppercase letter.
+const GLYPHS: [(char, [&str; 5]); 52] = [
+    ('0', ["###", "# #", "# #", "# #", "###"]),
+    ('1', [" # ", "## ", " # ", " # ", "###"]),
+    ('2', ["###", "  #", "###", "#  ", "###"]),
+    ('3', ["###", "  #", "###", "  #", "###"]),
+    ('4', ["# #", "# #", "###", "  #", "  #"]),
+    ('5', ["###", "#  ", "###", "  #", "###"]),
+    ('6', ["###", "#  ", "###", "# #", "###"]),
+    ('7', ["###", "  #", "  #", "  #", "  #"]),
+    ('8', ["###", "# #", "###", "# #", "###"]),
+    ('9', ["###", "# #", "###", "  #", "###"]),
+    ('A', ["###", "# #", "###", "# #", "# #"]),
+    ('B', ["## ", "# #", "## ", "# #", "## "]),
+    ('C', ["###", "#  ", "#  ", "#  ", "###"]),
+    ('D', ["## ", "# #", "# #", "# #", "## "]),
+    ('E', ["###", "#  ", "## ", "#  ", "###"]),
+    ('F', ["###", "#  ", "## ", "#  ", "#  "]),
+    ('G', ["###", "#  ", "# #", "# #", "###"]),
+    ('H', ["# #", "# #", "###", "# #", "# #"]),
+    ('I', ["###", " # ", " # ", " # ", "###"]),
+    ('J', ["  #", "  #", "  #", "# #", "###"]),
+    ('K', ["# #", "# #", "## ", "# #", "# #"]),
+    ('L', ["#  ", "#  ", "#  ", "#  ", "###"]),
+    ('M', ["# #", "###", "###", "# #", "# #"]),
+    ('N', ["## ", "# #", "# #", "# #", "# #"]),
+    ('O', ["###", "# #", "# #", "# #", "###"]),
+    ('P', ["###", "# #", "###", "#  ", "#  "]),
+    ('Q', ["###", "# #", "# #", "###", "  #"]),
+    ('R', ["## ", "# #", "## ", "# #", "# #"]),
+    ('S', ["###", "#  ", "###", "  #", "###"]),
+    ('T', ["###", " # ", " # ", " # ", " # "]),
+    ('U', ["# #", "# #", "# #", "# #", "###"]),
+    ('V', ["# #", "# #", "# #", "# #", " # "]),
+    ('W', ["# #", "# #", "###", "###", "# #"]),
+    ('X', ["# #", "# #", " # ", "# #", "# #"]),
+    ('Y', ["# #", "# #", " # ", " # ", " # "]),
+    ('Z', ["###", "  #", " # ", "#  ", "###"]),
+    (' ', ["   ", "   ", "   ", "   ", "   "]),
+    ('-', ["   ", "   ", "###", "   ", "   "]),
+    ('+', ["   ", " # ", "###", " # ", "   "]),
+    ('*', ["   ", "# #", " # ", "# #", "   "]),
+    ('/', ["  #", "  #", " # ", "#  ", "#  "]),
+    ('%', ["# #", "  #", " # ", "#  ", "# #"]),
+    ('=', ["   ", "###", "   ", "###", "   "]),
+    (':', ["   ", " # ", "   ", " # ", "   "]),
+    ('<', ["  #", " # ", "#  ", " # ", "  #"]),
+    ('>', ["#  ", " # ", "  #", " # ", "#  "]),
+    ('!', [" # ", " # ", " # ", "   ", " # "]),
+    ('(', [" # ", "#  ", "#  ", "#  ", " # "]),
+    (')', [" # ", "  #", "  #", "  #", " # "]),
+    (',', ["   ", "   ", "   ", " # ", "#  "]),
+    ('_', ["   ", "   ", "   ", "   ", "###"]),
+    ('\'', [" # ", " # ", "   ", "   ", "   "]),
+];
+
+/// Glyph that is used for characters that are not contained in the font.
+const UNKNOWN_GLYPH: [&str; 5] = ["###", "  #", " ##", "   ", " # "];
+
+/// Returns the number of columns that `text` needs, when it is rendered with block characters.
+pub fn big_text_width(text: &str) -> usize {
+    (text.chars().count() * 4).saturating_sub(1)
+}
+
+/// Renders `text` with block characters, the returned lines are `BIG_TEXT_HEIGHT` lines high.
+///
+/// The greek letters of the alpha notation are displayed with the glyphs of `a`, `p` and `y`.
+pub fn big_text(text: &str, style: Style) -> Vec<Line<'static>> {
+    let glyphs = text.chars().map(glyph).collect::<Vec<_>>();
+    (0..BIG_TEXT_HEIGHT as usize)
+        .map(|row| {
+            let line = glyphs
+                .iter()
+                .map(|glyph| glyph[row].replace('#', "█"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            Line::styled(line, style)
+        })
+        .collect()
+}
+
+/// Returns the glyph of the character.
+fn glyph(c: char) -> [&'static str; 5] {
+    let c = match c {
+        'α' => 'A',
+        'ρ' => 'P',
+        'γ' => 'Y',
+        '≤' => '<',
+        '≥' => '>',
+        '≠' => '!',
+        c => c.to_ascii_uppercase(),
+    };
+    GLYPHS
+        .iter()
+        .find(|(g, _)| *g == c)
+        .map_or(UNKNOWN_GLYPH, |(_, glyph)| *glyph)
+}
+
+#[cfg(test)]
+mod tests {
+    use ratatui::style::Style;
+
+    use super::{big_text, big_text_width, BIG_TEXT_HEIGHT};
+
+    #[test]
+    fn test_big_text() {
+        let lines = big_text("-1", Style::default());
+        assert_eq!(lines.len(), BIG_TEXT_HEIGHT as usize);
+        assert_eq!(lines[0].to_string(), "     █ ");
+        assert_eq!(lines[2].to_string(), "███  █ ");
+        assert_eq!(lines[4].to_string(), "    ███");
+        assert_eq!(big_text_width("-1"), 7);
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{instructions::Instruction, runtime::Runtime};
+
+/// Maximum number of jumps that can be drawn next to each other, further overlapping jumps are not drawn.
+pub const MAX_LANES: usize = 3;
+
+/// Width of the gutter in which the jump arrows are drawn.
+pub const GUTTER_WIDTH: usize = MAX_LANES + 1;
+
+/// Returns the jumps of the program, each jump consists of the index of the `goto` or `if ... goto` instruction and
+/// the index of the instruction of the target label.
+pub fn jumps(runtime: &Runtime) -> Vec<(usize, usize)> {
+    runtime
+        .instructions()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, instruction)| match instruction {
+            Instruction::Goto(label) | Instruction::JumpIf(_, _, _, label) => runtime
+                .control_flow()
+                .instruction_labels
+                .get(label)
+                .map(|target| (idx, *target)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the gutter for each of the displayed `lines`, in which the `jumps` are drawn as arrows.
+///
+/// Only jumps for which the jump and the target are displayed are drawn, a jump is drawn as a bracket that connects
+/// both lines, the target is marked with `►`. Short jumps are drawn closest to the code.
+pub fn jump_gutter(lines: &[usize], jumps: &[(usize, usize)]) -> Vec<String> {
+    let row = |idx: usize| lines.iter().position(|line| *line == idx);
+    let mut visible = jumps
+        .iter()
+        .filter_map(|(source, target)| Some((row(*source)?, row(*target)?)))
+        .filter(|(source, target)| source != target)
+        .collect::<Vec<_>>();
+    visible.sort_by_key(|(source, target)| source.abs_diff(*target));
+
+    // columns of each row, the lanes are followed by the column of the arrow heads
+    let mut gutter = vec![vec![' '; GUTTER_WIDTH]; lines.len()];
+    // rows that are occupied in each lane
+    let mut lanes: Vec<Vec<(usize, usize)>> = vec![Vec::new(); MAX_LANES];
+    for (source, target) in visible {
+        let (top, bottom) = (source.min(target), source.max(target));
+        let Some(lane) = lanes.iter().position(|ranges| {
+            ranges
+                .iter()
+                .all(|(start, end)| bottom < *start || top > *end)
+        }) else {
+            continue;
+        };
+        lanes[lane].push((top, bottom));
+        // lane 0 is next to the arrow heads
+        let column = MAX_LANES - 1 - lane;
+        for (row, columns) in gutter.iter_mut().enumerate().take(bottom + 1).skip(top) {
+            columns[column] = match row {
+                _ if row == top => '┌',
+                _ if row == bottom => '└',
+                // the arrow of a longer jump crosses this lane
+                _ if columns[column] == '─' => '┼',
+                _ => '│',
+            };
+            if row == top || row == bottom {
+                for c in &mut columns[column + 1..MAX_LANES] {
+                    *c = match *c {
+                        ' ' | '─' => '─',
+                        _ => '┼',
+                    };
+                }
+            }
+        }
+        gutter[target][MAX_LANES] = '►';
+        if gutter[source][MAX_LANES] == ' ' {
+            gutter[source][MAX_LANES] = '─';
+        }
+    }
+    gutter
+        .into_iter()
+        .map(|columns| columns.into_iter().collect())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::jump_gutter;
+
+    #[test]
+    fn test_jump_gutter() {
+        // jump from line 0 to line 4 and a loop from line 3 back to line 1
+        let gutter = jump_gutter(&[0, 1, 2, 3, 4], &[(3, 1), (0, 4)]);
+        assert_eq!(gutter, vec![" ┌──", " │┌►", " ││ ", " │└─", " └─►"]);
+    }
+
+    #[test]
+    fn test_jump_gutter_crossing() {
+        let gutter = jump_gutter(&[0, 1, 2, 3], &[(0, 2), (1, 3)]);
+        assert_eq!(gutter, vec!["  ┌─", " ┌┼─", " │└►", " └─►"]);
+    }
+
+    #[test]
+    fn test_jump_gutter_hidden_lines() {
+        // the target is not displayed
+        assert_eq!(jump_gutter(&[0, 1], &[(1, 5)]), vec!["    ", "    "]);
+        // lines between the jump and the target are folded
+        assert_eq!(jump_gutter(&[0, 4], &[(4, 0)]), vec!["  ┌►", "  └─"]);
+    }
+}
@@ -1,16 +1,47 @@
 use ratatui::{
-    prelude::{Alignment, Constraint, Direction, Layout},
+    prelude::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Clear, List, ListDirection, ListItem, Paragraph},
+    widgets::{
+        block::{Position, Title},
+        Block, BorderType, Borders, Clear, List, ListDirection, ListItem, ListState, Paragraph,
+        Wrap,
+    },
     Frame,
 };
 use text_align::TextAlign;
 
-use super::{keybindings::KeySymbol, run_instruction::SingleInstruction, App, State};
+use crate::{error_codes, instructions::Instruction, runtime::observer::ExecutionEvent};
 
+use self::{
+    big_text::{big_text, big_text_width, BIG_TEXT_HEIGHT},
+    syntax_highlighting::ToSpans,
+};
+
+use super::{
+    breakpoints::group_marker, keybindings::KeySymbol, run_instruction::SingleInstruction,
+    session::Autosave, App, BranchDecision, State,
+};
+
+/// Text that is rendered with block characters.
+mod big_text;
+/// Arrows that connect jumps with their targets.
+mod jump_arrows;
+/// Snapshot tests of the rendered ui.
+#[cfg(test)]
+mod snapshot_tests;
 pub mod style;
 pub mod syntax_highlighting;
 
+/// Height of the panel that displays the notes of the program.
+const NOTES_HEIGHT: u16 = 8;
+
+/// Number of allowed instruction types that are suggested when a custom instruction is forbidden.
+const FORBIDDEN_INSTRUCTION_SUGGESTIONS: usize = 3;
+
+/// Minimal width of the text in the trace panel, so that the title fits when few instructions were run.
+const TRACE_MIN_WIDTH: usize = 12;
+
 impl App {
     /// Draw the ui
     #[allow(clippy::too_many_lines)]
@@ -20,17 +51,29 @@ impl App {
             State::Playground(_) => true,
             State::RuntimeError(_, is_playground) => is_playground,
             State::CustomInstructionError(_, is_playground) => is_playground,
+            State::ConfirmQuit(ref previous_state, _) => {
+                matches!(**previous_state, State::Playground(_))
+            }
             _ => false,
         };
 
-        let (keybinding_hints, keybinding_hints_height) = self
+        // in presentation mode keybinding hints are hidden and values are displayed larger
+        let presentation_mode = self.presentation_mode && !is_playground;
+
+        let (keybinding_hints, mut keybinding_hints_height) = self
             .keybinding_hints
             .keybinding_hint_paragraph(f.size().width);
+        let mut status_bar_height = 1;
+        if presentation_mode {
+            keybinding_hints_height = 0;
+            status_bar_height = 0;
+        }
 
         let global_chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([
                 Constraint::Fill(1),
+                Constraint::Length(status_bar_height),
                 Constraint::Length(keybinding_hints_height),
             ])
             .split(f.size());
@@ -53,12 +96,29 @@ impl App {
             .constraints(chunk_constraints)
             .split(global_chunks[0]);
 
-        // draw keybinding hints
-        f.render_widget(keybinding_hints, global_chunks[1]);
+        // draw status bar and keybinding hints
+        if !presentation_mode {
+            f.render_widget(self.status_bar(is_playground), global_chunks[1]);
+            f.render_widget(keybinding_hints, global_chunks[2]);
+        }
+
+        let next_instruction_index = self
+            .timeline
+            .selected()
+            .map_or(self.runtime.next_instruction_index(), |(snapshot, _)| {
+                snapshot.control_flow.next_instruction_index
+            });
+        let jump_target = self.jump_target_preview(next_instruction_index);
 
         let mut right_chunk_constraints = vec![Constraint::Percentage(30), Constraint::Fill(1)];
         if !is_playground {
-            right_chunk_constraints.push(Constraint::Length(3))
+            // the jump target takes up two additional lines
+            right_chunk_constraints.push(Constraint::Length(if jump_target.is_some() {
+                5
+            } else {
+                3
+            }));
+            right_chunk_constraints.push(Constraint::Length(4));
         }
         let right_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -75,10 +135,23 @@ impl App {
             .split(chunks[if is_playground { 2 } else { 3 }]);
 
         // central big part
+        let show_notes = !is_playground && !self.runtime.notes().is_empty();
+        let show_timeline = !is_playground && !self.timeline.is_empty();
         let central_constraints = if is_playground {
             vec![Constraint::Percentage(60), Constraint::Min(8)]
         } else {
-            vec![Constraint::Fill(1)]
+            let mut constraints = vec![Constraint::Fill(1)];
+            if show_timeline {
+                constraints.push(Constraint::Length(3));
+            }
+            if show_notes {
+                constraints.push(Constraint::Length(NOTES_HEIGHT));
+            }
+            if presentation_mode {
+                // the next instruction is displayed below the code
+                constraints.push(Constraint::Length(BIG_TEXT_HEIGHT + 2));
+            }
+            constraints
         };
         let central_chunks = Layout::default()
             .direction(Direction::Vertical)
@@ -111,31 +184,145 @@ impl App {
                 });
         }
 
-        // Create a List from all instructions and highlight current instruction
-        let items = List::new(
-            self.instruction_list_states
-                .as_list_items(is_playground, &self.theme),
-        )
-        .block(code_area)
-        .highlight_style(if let State::DebugSelect(_, _) = self.state {
-            self.theme.list_item_highlight(true)
+        // legend of the breakpoint groups
+        if !is_playground {
+            let mut legend = self
+                .instruction_list_states
+                .breakpoint_groups()
+                .iter()
+                .enumerate()
+                .map(|(idx, group)| {
+                    let state = if group.enabled { "" } else { " (off)" };
+                    format!("[{}] {} {}{state}", idx + 1, group_marker(idx), group.name)
+                })
+                .collect::<Vec<_>>();
+            if self.instruction_list_states.breakpoints_disabled() {
+                legend.push("all breakpoints disabled".to_string());
+            }
+            if let Some(hit) = &self.watchpoint_hit {
+                legend.insert(0, hit.to_string());
+            }
+            if !legend.is_empty() {
+                code_area = code_area.title(
+                    Title::from(format!(" {} ", legend.join("  "))).position(Position::Bottom),
+                );
+            }
+        }
+
+        // the label legend is displayed next to the code
+        let label_legend_width = if !is_playground && self.show_label_legend {
+            self.label_legend_width().min(central_chunks[0].width / 3)
         } else {
-            self.theme.list_item_highlight(false)
-        })
-        .highlight_symbol(">> ")
-        .direction(if is_playground {
-            ListDirection::BottomToTop
+            0
+        };
+        // the trace panel is displayed on the right of the label legend
+        let trace_width = if !is_playground && self.show_trace {
+            self.trace_width().min(central_chunks[0].width / 3)
         } else {
-            ListDirection::TopToBottom
-        })
-        .scroll_padding(2);
+            0
+        };
+        let code_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(label_legend_width),
+                Constraint::Length(trace_width),
+            ])
+            .split(central_chunks[0]);
 
-        // We can now render the item list
-        f.render_stateful_widget(
-            items,
-            central_chunks[0],
-            self.instruction_list_states.instruction_list_state_mut(),
+        // Only the list items of the visible lines are created, to keep drawing fast for large programs.
+        // The executed instructions of the playground are scrolled by the list itself.
+        let (visible_lines, sticky_label) = if is_playground {
+            (
+                (0..self.instruction_list_states.instructions().len()).collect(),
+                None,
+            )
+        } else {
+            self.visible_code_lines(code_chunks[0].height.saturating_sub(2) as usize)
+        };
+        let mut visible_list_state = self
+            .instruction_list_states
+            .visible_list_state(&visible_lines);
+        // the line of the sticky label is displayed above the visible lines
+        let displayed_lines = sticky_label
+            .into_iter()
+            .chain(visible_lines.iter().copied())
+            .collect::<Vec<_>>();
+        // the gutter with the jump arrows is only displayed, if the program contains jumps
+        let jumps = if is_playground {
+            Vec::new()
+        } else {
+            jump_arrows::jumps(&self.runtime)
+        };
+        let gutter = (!jumps.is_empty()).then(|| jump_arrows::jump_gutter(&visible_lines, &jumps));
+        let mut code_items = self.instruction_list_states.as_list_items(
+            &visible_lines,
+            gutter.as_deref(),
+            is_playground,
+            self.marked_range(),
+            &self.theme,
         );
+        if let Some(line) = sticky_label {
+            let empty_gutter = gutter
+                .as_ref()
+                .map(|_| vec![" ".repeat(jump_arrows::GUTTER_WIDTH)]);
+            let label_item = self
+                .instruction_list_states
+                .as_list_items(
+                    &[line],
+                    empty_gutter.as_deref(),
+                    false,
+                    self.marked_range(),
+                    &self.theme,
+                )
+                .remove(0)
+                .style(Style::default().add_modifier(Modifier::UNDERLINED));
+            code_items.insert(0, label_item);
+            visible_list_state.select(visible_list_state.selected().map(|idx| idx + 1));
+        }
+
+        // Create a List from all instructions and highlight current instruction
+        let items = List::new(code_items)
+            .block(code_area)
+            .highlight_style(if let State::DebugSelect(_, _) = self.state {
+                self.theme.list_item_highlight(true)
+            } else {
+                self.theme.list_item_highlight(false)
+            })
+            .highlight_symbol(">> ")
+            .direction(if is_playground {
+                ListDirection::BottomToTop
+            } else {
+                ListDirection::TopToBottom
+            })
+            .scroll_padding(2);
+
+        // We can now render the item list
+        if is_playground {
+            f.render_stateful_widget(
+                items,
+                central_chunks[0],
+                self.instruction_list_states.instruction_list_state_mut(),
+            );
+        } else {
+            f.render_stateful_widget(items, code_chunks[0], &mut visible_list_state);
+        }
+        if label_legend_width > 0 {
+            self.draw_label_legend(f, code_chunks[1]);
+        }
+        if trace_width > 0 {
+            self.draw_trace(f, code_chunks[2]);
+        }
+
+        if show_timeline {
+            self.draw_timeline(f, central_chunks[1]);
+        }
+        if show_notes {
+            self.draw_notes(f, central_chunks[if show_timeline { 2 } else { 1 }]);
+        }
+        if presentation_mode {
+            self.draw_presentation_instruction(f, central_chunks[central_chunks.len() - 1]);
+        }
 
         // Breakpoint list
         if !is_playground {
@@ -149,19 +336,21 @@ impl App {
                 .style(self.theme.breakpoint_block());
 
             // Create the items for the list
-            let breakpoint_list_items: Vec<ListItem> = self
-                .instruction_list_states
-                .instructions()
+            let breakpoint_list_items: Vec<ListItem> = displayed_lines
                 .iter()
-                .map(|f| {
-                    let v = if f.2 {
-                        "*".to_string()
-                    } else {
-                        " ".to_string()
+                .map(|line| {
+                    let (v, style) = match self.instruction_list_states.breakpoint_marker(*line) {
+                        Some((marker, true)) => (marker.to_string(), self.theme.breakpoint()),
+                        // disabled breakpoints are kept, but displayed dimmed
+                        Some((marker, false)) => (
+                            marker.to_string(),
+                            self.theme.breakpoint().add_modifier(Modifier::DIM),
+                        ),
+                        None => (" ".to_string(), self.theme.breakpoint()),
                     };
                     ListItem::new(Text::styled(
                         v.center_align(chunks[0].width.saturating_sub(2) as usize),
-                        self.theme.breakpoint(),
+                        style,
                     ))
                 })
                 .collect();
@@ -171,14 +360,20 @@ impl App {
                 .block(breakpoint_area)
                 .scroll_padding(2);
 
-            f.render_stateful_widget(
-                breakpoints,
-                chunks[0],
-                self.instruction_list_states.breakpoint_list_state_mut(),
-            );
+            f.render_stateful_widget(breakpoints, chunks[0], &mut visible_list_state);
         }
 
+        // when a step of the timeline is selected, the memory of that step is displayed
+        let selected_step = self.timeline.selected().map(|(snapshot, _)| snapshot);
+        let memory_lists = self
+            .timeline_memory_lists
+            .as_ref()
+            .unwrap_or(&self.memory_lists_manager);
+        let memory = selected_step.map_or(self.runtime.runtime_memory(), |s| &s.memory);
+
         // Accumulator block
+        // memory locations that were not declared are marked, to make typos visible
+        let autodetected = self.runtime.autodetected_memory();
         let accumulator_title = match right_chunks[0].width {
             0..=13 => "Accs",
             14..=u16::MAX => "Accumulators",
@@ -190,8 +385,49 @@ impl App {
             .border_type(BorderType::Rounded)
             .border_style(self.theme.memory_block_border())
             .style(self.theme.memory_block());
-        let accumulator_list =
-            List::new(self.memory_lists_manager.accumulator_list()).block(accumulator);
+        // the memory lists are scrolled together, a list is only scrolled as far as it has values left
+        let accumulator_height = right_chunks[0].height.saturating_sub(2) as usize;
+        let memory_cell_height = right_chunks[1].height.saturating_sub(2) as usize;
+        let accumulator_items =
+            memory_lists.accumulator_list(self.runtime.variables(), &autodetected);
+        let memory_cell_items =
+            memory_lists.memory_cell_list(self.runtime.variables(), &autodetected);
+        self.memory_page_height = memory_cell_height;
+        self.memory_scroll = self.memory_scroll.min(
+            accumulator_items
+                .len()
+                .saturating_sub(accumulator_height)
+                .max(memory_cell_items.len().saturating_sub(memory_cell_height)),
+        );
+        let accumulator_list = if presentation_mode {
+            let mut values = memory
+                .accumulators
+                .values()
+                .map(|acc| {
+                    (
+                        acc.id,
+                        self.identifier_notation.accumulator(acc.id),
+                        acc.data,
+                    )
+                })
+                .collect::<Vec<_>>();
+            values.sort_by_key(|(id, _, _)| *id);
+            let mut values = values
+                .into_iter()
+                .map(|(_, name, value)| (name, value))
+                .collect::<Vec<_>>();
+            if let Some(gamma) = memory.gamma {
+                values.push((self.identifier_notation.gamma().to_string(), gamma));
+            }
+            List::new(self.big_value_items(&values, right_chunks[0].width))
+        } else {
+            List::new(scrolled(
+                accumulator_items,
+                self.memory_scroll,
+                accumulator_height,
+            ))
+        }
+        .block(accumulator);
         f.render_widget(accumulator_list, right_chunks[0]);
 
         // Memory cell block
@@ -207,8 +443,33 @@ impl App {
             .border_type(BorderType::Rounded)
             .border_style(self.theme.memory_block_border())
             .style(self.theme.memory_block());
-        let memory_cell_list =
-            List::new(self.memory_lists_manager.memory_cell_list()).block(memory_cells);
+        let memory_cell_list = if presentation_mode {
+            let mut values = memory
+                .memory_cells
+                .values()
+                .map(|cell| (cell.label.clone(), cell.data))
+                .collect::<Vec<_>>();
+            values.sort();
+            let mut index_memory_cells = memory
+                .index_memory_cells
+                .iter()
+                .map(|(idx, value)| (*idx, *value))
+                .collect::<Vec<_>>();
+            index_memory_cells.sort_by_key(|(idx, _)| *idx);
+            values.extend(
+                index_memory_cells
+                    .into_iter()
+                    .map(|(idx, value)| (format!("[{idx}]"), value)),
+            );
+            List::new(self.big_value_items(&values, right_chunks[1].width))
+        } else {
+            List::new(scrolled(
+                memory_cell_items,
+                self.memory_scroll,
+                memory_cell_height,
+            ))
+        }
+        .block(memory_cells);
         f.render_widget(memory_cell_list, right_chunks[1]);
 
         // Next instruction block
@@ -225,17 +486,30 @@ impl App {
                 .border_type(BorderType::Rounded)
                 .border_style(self.theme.internal_memory_block_border())
                 .style(self.theme.internal_memory_block());
+            let mut next_instruction_lines =
+                vec![Line::from(format!("{}", next_instruction_index + 1))];
+            if let Some((target, instruction)) = jump_target {
+                next_instruction_lines.push(Line::from(format!("→ {target}")));
+                next_instruction_lines.push(instruction);
+            }
             let next_instruction =
-                Paragraph::new(format!("{}", self.runtime.next_instruction_index() + 1))
-                    .block(next_instruction_block);
+                Paragraph::new(next_instruction_lines).block(next_instruction_block);
             f.render_widget(next_instruction, right_chunks[2]);
+            self.draw_instruction_runs(f, right_chunks[3]);
         }
 
         // Stack block
-        let stack_title = match stack_chunks[0].width {
-            0..=6 => "Stck",
-            7..=u16::MAX => "Stack",
+        let (stack_name, stack_short_name) = if self.show_stack_direction {
+            ("Stack ↑", "Stck↑")
+        } else {
+            ("Stack", "Stck")
         };
+        let stack_title = self.depth_title(
+            (stack_name, stack_short_name),
+            memory_lists.stack_depth(),
+            self.runtime.settings().stack_limit,
+            stack_chunks[0].width,
+        );
         let stack = Block::default()
             .borders(Borders::ALL)
             .title(stack_title)
@@ -243,16 +517,17 @@ impl App {
             .border_type(BorderType::Rounded)
             .border_style(self.theme.memory_block_border())
             .style(self.theme.memory_block());
-        let stack_list = List::new(self.memory_lists_manager.stack_list()).block(stack);
+        let stack_list = List::new(memory_lists.stack_list()).block(stack);
         f.render_widget(stack_list, stack_chunks[0]);
 
         // Render call stack if enabled
         if self.show_call_stack {
-            let call_stack_title = if stack_chunks[1].width >= 12 {
-                "Call Stack"
-            } else {
-                "CS"
-            };
+            let call_stack_title = self.depth_title(
+                ("Call Stack", "CS"),
+                memory_lists.call_stack_depth(),
+                Some(self.runtime.settings().call_stack_limit),
+                stack_chunks[1].width,
+            );
             let call_stack_block = Block::default()
                 .borders(Borders::ALL)
                 .title(call_stack_title)
@@ -260,8 +535,7 @@ impl App {
                 .border_type(BorderType::Rounded)
                 .border_style(self.theme.internal_memory_block_border())
                 .style(self.theme.internal_memory_block());
-            let call_stack =
-                List::new(self.memory_lists_manager.call_stack_list()).block(call_stack_block);
+            let call_stack = List::new(memory_lists.call_stack_list()).block(call_stack_block);
             f.render_widget(call_stack, stack_chunks[1]);
         }
 
@@ -272,12 +546,167 @@ impl App {
                 .borders(Borders::ALL)
                 .border_style(self.theme.execution_finished_popup_border())
                 .style(self.theme.execution_finished_block());
-            let area = super::centered_rect_abs(5, 36, f.size());
-            let text = paragraph_with_line_wrap(
-            format!("Press [t] to reset to start.\nPress [d] to dismiss this message.\nPress [q] or [{}] to exit.", KeySymbol::Escape),
-            area.width,
-        )
-        .block(block);
+            let mut text = format!("Press [t] to reset to start.\nPress [d] to dismiss this message.\nPress [q] or [{}] to exit.", KeySymbol::Escape);
+            let mut height = 5;
+            if !autodetected.is_empty() {
+                let locations = autodetected
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let summary = format!("Not declared but created: {locations}");
+                // one line for each part of the wrapped summary and an empty line
+                height += summary.len().div_ceil(34) as u16 + 1;
+                text = format!("{summary}\n\n{text}");
+            }
+            let area = super::centered_rect_abs(height, 36, f.size());
+            let text = paragraph_with_line_wrap(text, area.width).block(block);
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(text, area);
+        }
+
+        // Debug panel with the measured instruction timings
+        if self.show_debug_panel {
+            let block = Block::default()
+                .title("Debug")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.memory_block_border())
+                .style(self.theme.memory_block());
+            let timings = match self.runtime.timings() {
+                Some(timings) => timings.to_string(),
+                None => "Timing:\n  not measured, load the program with --measure".to_string(),
+            };
+            let text = format!(
+                "Instructions run: {}\n\n{timings}",
+                self.runtime.instruction_runs()
+            );
+            let height = text.lines().count() as u16 + 2;
+            let width = text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as u16 + 4;
+            let area = super::centered_rect_abs(height, width, f.size());
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(Paragraph::new(text).block(block), area);
+        }
+
+        // Key diagnostic overlay in the bottom right corner, above the keybinding hints
+        if let Some(key_log) = &self.key_log {
+            let block = Block::default()
+                .title("Key events")
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(self.theme.memory_block_border())
+                .style(self.theme.memory_block());
+            let lines = key_log.lines();
+            let text = if lines.is_empty() {
+                "Press a key".to_string()
+            } else {
+                lines.join("\n")
+            };
+            let size = f.size();
+            let height = (text.lines().count() as u16 + 2).min(size.height);
+            let width = (text.lines().map(|l| l.chars().count()).max().unwrap_or(0) as u16 + 4)
+                .max(20)
+                .min(size.width);
+            let area = Rect::new(
+                size.width - width,
+                size.height
+                    .saturating_sub(height + keybinding_hints_height + status_bar_height),
+                width,
+                height,
+            );
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(Paragraph::new(text).block(block), area);
+        }
+
+        // Popup to confirm a reset
+        if let State::ConfirmReset(_) = self.state {
+            let block = Block::default()
+                .title("Reset program?")
+                .borders(Borders::ALL)
+                .border_style(self.theme.error_block_border())
+                .style(self.theme.error_block());
+            let text = format!(
+                "{} instructions were run, the progress is lost when the program is reset.\n\nPress [t] or [ENTER] to reset.\nPress [{}] to cancel.",
+                self.runtime.instruction_runs(),
+                KeySymbol::Escape
+            );
+            let area = super::centered_rect_abs(8, 40, f.size());
+            let text = paragraph_with_line_wrap(text, area.width - 2).block(block);
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(text, area);
+        }
+
+        // Popup to confirm restoring a session, that was saved before the app was closed unexpectedly
+        if let State::ConfirmRestore(session) = &self.state {
+            let block = Block::default()
+                .title("Restore session?")
+                .borders(Borders::ALL)
+                .border_style(self.theme.error_block_border())
+                .style(self.theme.error_block());
+            let mut restorable = Vec::new();
+            if !session.custom_instructions.is_empty() {
+                restorable.push(format!(
+                    "{} custom instructions",
+                    session.custom_instructions.len()
+                ));
+            }
+            if !session.breakpoints.is_empty() {
+                restorable.push(format!("{} breakpoints", session.breakpoints.len()));
+            }
+            if let Some(snapshot) = session.snapshot.as_ref().filter(|s| s.instruction_runs > 0) {
+                if self.snapshot_outdated(session) {
+                    restorable.push(
+                        "the execution state can not be restored, the program was changed"
+                            .to_string(),
+                    );
+                } else {
+                    restorable.push(format!(
+                        "the execution state after {} instructions",
+                        snapshot.instruction_runs
+                    ));
+                }
+            }
+            let text = format!(
+                "alpha_tui was not closed properly, the session was saved {}. It contains:\n{}\n\nPress [{}] to restore the session.\nPress [{}] to discard it.",
+                session.age(self.autosave.as_ref().map_or(session.saved_at, Autosave::now)),
+                restorable
+                    .iter()
+                    .map(|item| format!("- {item}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                KeySymbol::Enter,
+                KeySymbol::Escape
+            );
+            let area = super::centered_rect_abs(8 + restorable.len() as u16, 50, f.size());
+            let text = paragraph_with_line_wrap(text, area.width - 2).block(block);
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(text, area);
+        }
+
+        // Popup to confirm closing the app, when something is not saved
+        if let State::ConfirmQuit(_, unsaved) = &self.state {
+            let block = Block::default()
+                .title("Quit without saving?")
+                .borders(Borders::ALL)
+                .border_style(self.theme.error_block_border())
+                .style(self.theme.error_block());
+            let text = format!(
+                "The following is lost when alpha_tui is closed:\n{}\n\nPress [s] to save it and quit.\nPress [q] to quit without saving.\nPress [{}] to go back.",
+                unsaved
+                    .iter()
+                    .map(|(artifact, path)| format!(
+                        "- {}, saved to {path}",
+                        self.describe_unsaved(*artifact)
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                KeySymbol::Escape
+            );
+            // the description of each item can take two lines
+            let area = super::centered_rect_abs(9 + 2 * unsaved.len() as u16, 50, f.size());
+            let text = paragraph_with_line_wrap(text, area.width - 2).block(block);
             f.render_widget(Clear, area); //this clears out the background
             f.render_widget(text, area);
         }
@@ -300,6 +729,91 @@ impl App {
             _ => (),
         }
 
+        // Draw the changes of a custom instruction that have to be confirmed
+        if let State::ConfirmCustomInstruction(_, instruction, preview) = &self.state {
+            let (title, text, block_style, border_style) = match preview.as_ref() {
+                Ok(event) => (
+                    "Run custom instruction?",
+                    format!(
+                        "{}\n\n{}\n\nPress [ENTER] to run the instruction.\nPress [{}] to cancel.",
+                        instruction,
+                        preview_text(instruction, event),
+                        KeySymbol::Escape
+                    ),
+                    self.theme.single_instruction_block(),
+                    self.theme.custom_instruction(),
+                ),
+                Err(e) => (
+                    "Custom instruction would fail",
+                    format!(
+                        "{}\n\nThe instruction can not be run due to the following problem:\n{}\n\nPress [ENTER] or [{}] to go back.",
+                        instruction,
+                        e.reason,
+                        KeySymbol::Escape
+                    ),
+                    self.theme.error_block(),
+                    self.theme.error_block_border(),
+                ),
+            };
+            let block = Block::default()
+                .title(title)
+                .title_alignment(Alignment::Center)
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .style(block_style);
+            let area = super::centered_rect(60, 30, None, f.size());
+            let text = paragraph_with_line_wrap(text, area.width - 2).block(block);
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(text, area);
+        }
+
+        // Draw rename popup
+        if let State::Rename(single_instruction) = &mut self.state {
+            single_instruction.draw_input_popup(
+                f,
+                f.size(),
+                "Rename memory cell or label",
+                "Enter old and new name:",
+            );
+        }
+
+        // Draw popup in which the number of instructions to run is entered
+        if let State::StepCount(single_instruction) = &mut self.state {
+            single_instruction.draw_input_popup(
+                f,
+                f.size(),
+                "Run steps",
+                "Enter number of instructions, e.g. step 50:",
+            );
+        }
+
+        // Draw extract subroutine popup
+        if let State::ExtractSubroutine(single_instruction, start, end) = &mut self.state {
+            single_instruction.draw_input_popup(
+                f,
+                f.size(),
+                &format!("Extract lines {}-{} into subroutine", *start + 1, *end + 1),
+                "Enter label of the subroutine:",
+            );
+        }
+
+        // Draw error when the program could not be refactored
+        if let State::RefactorError(e) = &self.state {
+            let block = Block::default()
+                .title("Error: unable to refactor".to_string())
+                .borders(Borders::ALL)
+                .border_style(self.theme.error_block_border())
+                .style(self.theme.error_block());
+            let area = super::centered_rect(60, 30, None, f.size());
+            let text = paragraph_with_line_wrap(format!(
+                "{e}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
+                KeySymbol::Escape
+            ), area.width - 2)
+            .block(block);
+            f.render_widget(Clear, area); //this clears out the background
+            f.render_widget(text, area);
+        }
+
         // Popup if runtime error
         if let State::RuntimeError(e, _) = &self.state {
             let block = Block::default()
@@ -308,33 +822,48 @@ impl App {
                 .border_style(self.theme.error_block_border())
                 .style(self.theme.error_block());
             let area = super::centered_rect(60, 30, None, f.size());
-            let text = paragraph_with_line_wrap(if is_playground {format!("This instruction could not be executed due to the following problem:\n{}\n\nPress [q] to exit and to view further information regarding this error.\nPress [ENTER] to close.", e.reason)} else {format!(
-                "Execution can not continue due to the following problem:\n{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [t] to reset to start.",
-                e.reason, KeySymbol::Escape)}, area.width - 2).block(block);
+            let retry = if e.is_retryable() {
+                format!("\n{}", self.retry_text(e.line_number))
+            } else {
+                String::new()
+            };
+            // the program is only paused, if the popup can be closed
+            let (status, close) = if self.can_close_runtime_error() {
+                ("is paused", "\nPress [ENTER] to close.")
+            } else {
+                ("can not continue", "")
+            };
+            let text = paragraph_with_line_wrap(if is_playground {format!("This instruction could not be executed due to the following problem:\n{}\n\nPress [q] to exit and to view further information regarding this error.\nPress [ENTER] to close.", error_codes::describe(&e.reason))} else {format!(
+                "Execution {status} due to the following problem:\n{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [t] to reset to start.{retry}{close}",
+                error_codes::describe(&e.reason), KeySymbol::Escape)}, area.width - 2).block(block);
             f.render_widget(Clear, area); //this clears out the background
             f.render_widget(text, area);
         }
 
         // Draw error when instruction could not be parsed
-        if let State::CustomInstructionError(reason, _) = &self.state {
+        if let State::CustomInstructionError(e, _) = &self.state {
             let block = Block::default()
                 .title("Error: unable to parse instruction".to_string())
                 .borders(Borders::ALL)
                 .border_style(self.theme.error_block_border())
                 .style(self.theme.error_block());
+            // the suggestion is displayed in its own line
+            let suggestion = e.suggestion.as_ref().map(|s| format!("\n{s}"));
+            let suggestion_height = u16::from(suggestion.is_some());
             let area = super::centered_rect(
                 60,
                 30,
                 if f.size().width <= 124 {
-                    Some(7)
+                    Some(7 + suggestion_height)
                 } else {
-                    Some(6)
+                    Some(6 + suggestion_height)
                 },
                 f.size(),
             );
             let text = paragraph_with_line_wrap(format!(
-            "{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
-            reason,
+            "{}{}\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
+            error_codes::describe(e),
+            suggestion.unwrap_or_default(),
             KeySymbol::Escape
         ), area.width)
         .block(block);
@@ -343,24 +872,27 @@ impl App {
         }
 
         // Draw error when custom instruction could not be build
-        if let State::BuildProgramError(_) = &self.state {
+        if let State::BuildProgramError(e, _) = &self.state {
             let block = Block::default()
                 .title("Error: instruction forbidden".to_string())
                 .borders(Borders::ALL)
                 .border_style(self.theme.error_block_border())
                 .style(self.theme.error_block());
+            let hint = self.forbidden_instruction_hint();
+            let hint_lines = hint.lines().count() as u16;
             let area = super::centered_rect(
                 60,
                 30,
                 if f.size().width <= 124 {
-                    Some(7)
+                    Some(7 + hint_lines)
                 } else {
-                    Some(6)
+                    Some(6 + hint_lines)
                 },
                 f.size(),
             );
             let text = paragraph_with_line_wrap(format!(
-            "The entered instruction is forbidden.\n\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
+            "The entered instruction is forbidden.\n{}\n{hint}\nPress [q] or [{}] to exit and to view further information regarding this error.\nPress [ENTER] to close.",
+            error_codes::describe(e),
             KeySymbol::Escape
         ), area.width)
         .block(block);
@@ -368,6 +900,528 @@ impl App {
             f.render_widget(text, area);
         }
     }
+
+    /// Returns the identifier of the forbidden custom instruction and the closest allowed identifiers, that are
+    /// displayed in the error popup, each line is terminated by a newline.
+    ///
+    /// Returns an empty string if the identifier is not known.
+    fn forbidden_instruction_hint(&self) -> String {
+        let Some(identifier) = &self.forbidden_identifier else {
+            return String::new();
+        };
+        let mut hint = format!("\nInstruction type: {identifier}\n");
+        let closest = self
+            .instruction_config
+            .as_ref()
+            .map(|ic| ic.closest_allowed_identifiers(identifier, FORBIDDEN_INSTRUCTION_SUGGESTIONS))
+            .unwrap_or_default();
+        if !closest.is_empty() {
+            hint.push_str(&format!("Closest allowed types: {}\n", closest.join(", ")));
+        }
+        hint
+    }
+
+    /// Returns the lines that are displayed in a code area with `height` lines and the line of the label that is
+    /// pinned to the top of the code area.
+    ///
+    /// When the program is scrolled, the last label above the displayed lines is pinned, so that it is visible to which
+    /// block the displayed lines belong.
+    fn visible_code_lines(&mut self, height: usize) -> (Vec<usize>, Option<usize>) {
+        let lines = self.instruction_list_states.visible_lines(height);
+        if height < 2 || self.label_line_before(&lines).is_none() {
+            return (lines, None);
+        }
+        // the pinned label takes up one line
+        let lines = self.instruction_list_states.visible_lines(height - 1);
+        let label = self.label_line_before(&lines);
+        (lines, label)
+    }
+
+    /// Returns the index of the last line before the first of `lines` that contains a label.
+    ///
+    /// If the label is hidden by a folded subroutine, the first line of the subroutine is returned.
+    fn label_line_before(&self, lines: &[usize]) -> Option<usize> {
+        let first = *lines.first()?;
+        self.runtime
+            .control_flow()
+            .instruction_labels
+            .values()
+            .copied()
+            .filter(|idx| *idx < first)
+            .max()
+            .map(|idx| self.instruction_list_states.displayed_line(idx))
+    }
+
+    /// Returns the text that is displayed in the label legend for a label in line `idx`, that was jumped to `jumps`
+    /// times.
+    fn label_legend_entry(label: &str, idx: usize, jumps: usize) -> String {
+        format!("{:2}: {label} ({jumps}×)", idx + 1)
+    }
+
+    /// Returns the width of the label legend, so that all labels fit into it.
+    fn label_legend_width(&self) -> u16 {
+        let width = self
+            .label_legend
+            .entries()
+            .map(|(label, idx, jumps)| Self::label_legend_entry(label, idx, jumps).chars().count())
+            .max()
+            .unwrap_or(0);
+        // borders and highlight symbol
+        (width + 4) as u16
+    }
+
+    /// Draws the labels of the program with their line and how often they were jumped to during the current run.
+    ///
+    /// Labels that were not jumped to are dimmed.
+    fn draw_label_legend(&mut self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Labels")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.code_block_border())
+            .style(self.theme.code_block());
+        let items = self
+            .label_legend
+            .entries()
+            .map(|(label, idx, jumps)| {
+                let style = if jumps == 0 {
+                    Style::default().add_modifier(Modifier::DIM)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Self::label_legend_entry(label, idx, jumps)).style(style)
+            })
+            .collect::<Vec<_>>();
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(self.theme.list_item_highlight(false))
+            .highlight_symbol(">");
+        f.render_stateful_widget(list, area, self.label_legend.list_state_mut());
+    }
+
+    /// Returns the width of the trace panel, so that all listed instructions fit into it.
+    fn trace_width(&self) -> u16 {
+        let width = self
+            .trace
+            .entries()
+            .map(|entry| entry.to_string().chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(TRACE_MIN_WIDTH);
+        // borders
+        (width + 2) as u16
+    }
+
+    /// Draws the last executed instructions, the most recent instruction is displayed at the bottom.
+    ///
+    /// The step that is selected in the timeline is highlighted.
+    fn draw_trace(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Trace")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.code_block_border())
+            .style(self.theme.code_block());
+        let items = self
+            .trace
+            .entries()
+            .rev()
+            .map(|entry| {
+                let style = if entry.skipped {
+                    Style::default().add_modifier(Modifier::DIM)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(entry.to_string()).style(style)
+            })
+            .collect::<Vec<_>>();
+        let selected_step = self.timeline.selected().map(|(snapshot, _)| snapshot.step);
+        let mut list_state = ListState::default().with_selected(
+            self.trace
+                .entries()
+                .rev()
+                .position(|entry| !entry.skipped && Some(entry.step) == selected_step),
+        );
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(self.theme.list_item_highlight(false))
+            .direction(ListDirection::BottomToTop);
+        f.render_stateful_widget(list, area, &mut list_state);
+    }
+
+    /// Returns the text that explains how the instruction in line `line` is run again after a runtime error.
+    fn retry_text(&self, line: usize) -> String {
+        if self.custom_instructions_enabled {
+            format!("Press [i] to fix the memory with a custom instruction and [n] to run the instruction in line {line} again.")
+        } else {
+            format!("Press [n] to run the instruction in line {line} again.")
+        }
+    }
+
+    /// Draws the notes of the instructions that were run, the newest note is displayed first.
+    fn draw_notes(&self, f: &mut Frame, area: Rect) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Notes")
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.code_block_border())
+            .style(self.theme.code_block());
+        let mut lines = Vec::new();
+        for (idx, (line, note)) in self.narration.iter().rev().enumerate() {
+            let style = if idx == 0 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            for (i, text) in note.lines().enumerate() {
+                let text = if i == 0 {
+                    format!("Line {}: {text}", line + 1)
+                } else {
+                    text.to_string()
+                };
+                lines.push(Line::styled(text, style));
+            }
+        }
+        if lines.is_empty() {
+            lines.push(Line::styled(
+                "Notes are displayed here when their line is run",
+                Style::default().add_modifier(Modifier::ITALIC),
+            ));
+        }
+        let paragraph = Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .block(block);
+        f.render_widget(paragraph, area);
+    }
+
+    /// Returns the status bar that shows the mode of the app and the configuration of the loaded program.
+    /// Text that tells if the last conditional jump was taken and in which line the program continues.
+    fn branch_decision_text(&self, decision: BranchDecision) -> String {
+        let (text, idx) = match decision {
+            BranchDecision::Taken(idx) => ("↷ taken", idx),
+            BranchDecision::NotTaken(idx) => ("↓ not taken", idx),
+        };
+        if idx >= self.runtime.instructions().len() {
+            format!("{text} → END")
+        } else {
+            format!("{text} → line {}", idx + 1)
+        }
+    }
+
+    fn status_bar(&self, is_playground: bool) -> Paragraph<'_> {
+        let mode = match &self.state {
+            State::Default => "Default",
+            State::Running(_) | State::StepCount(_) => "Running",
+            State::DebugSelect(_, _) => "Debug",
+            State::Finished(_) => "Finished",
+            State::RuntimeError(_, _) => "Runtime error",
+            State::Rename(_) | State::ExtractSubroutine(_, _, _) | State::RefactorError(_) => {
+                "Refactor"
+            }
+            State::ConfirmReset(_) => "Confirm reset",
+            State::ConfirmQuit(_, _) => "Confirm quit",
+            State::ConfirmRestore(_) => "Restore session",
+            _ if is_playground => "Playground",
+            _ => "Custom instruction",
+        };
+        let mut items = vec![format!("Mode: {mode}")];
+        // the reason is displayed instead of the other items, so that it is not cut off
+        if let Some(notice) = &self.unavailable_notice {
+            items.push(notice.clone());
+            return Paragraph::new(format!(" {}", items.join(" │ ")))
+                .style(self.theme.keybinding_hints(true));
+        }
+        if !is_playground {
+            let mut file = format!("File: {}", self.filename);
+            if self.file_changed_on_disk() {
+                file.push_str(" (changed on disk)");
+            }
+            items.push(file);
+            if let Some(decision) = self.branch_decision {
+                items.push(self.branch_decision_text(decision));
+            }
+            items.push(format!(
+                "Instructions: {}",
+                self.runtime.instructions().len()
+            ));
+            let breakpoints = self.instruction_list_states.breakpoint_count();
+            items.push(if self.instruction_list_states.breakpoints_disabled() {
+                "Breakpoints: disabled".to_string()
+            } else {
+                format!("Breakpoints: {breakpoints}")
+            });
+        }
+        let settings = self.runtime.settings();
+        let autodetection = [
+            settings.autodetect_accumulators,
+            settings.autodetect_gamma_accumulator,
+            settings.autodetect_memory_cells,
+            settings.autodetect_index_memory_cells,
+        ];
+        items.push(format!(
+            "Autodetection: {}",
+            if autodetection.iter().all(|a| *a) {
+                "on"
+            } else if autodetection.iter().any(|a| *a) {
+                "partial"
+            } else {
+                "off"
+            }
+        ));
+        if let Some(e) = self.autosave.as_ref().and_then(|a| a.error()) {
+            items.push(format!("Autosave failed: {e}"));
+        }
+        Paragraph::new(format!(" {}", items.join(" │ "))).style(self.theme.keybinding_hints(true))
+    }
+
+    /// Draws the number of executed instructions and how many instructions can be run until the instruction limit
+    /// is reached.
+    fn draw_instruction_runs(&self, f: &mut Frame, area: Rect) {
+        let runs = self.runtime.instruction_runs();
+        let settings = self.runtime.settings();
+        let (remaining, style) = if settings.disable_instruction_limit {
+            ("no limit".to_string(), self.theme.instruction_runs(0.0))
+        } else {
+            let limit = settings.instruction_limit;
+            (
+                limit.saturating_sub(runs).to_string(),
+                self.theme
+                    .instruction_runs(runs as f64 / limit.max(1) as f64),
+            )
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Steps")
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.internal_memory_block_border())
+            .style(self.theme.internal_memory_block());
+        let text = vec![
+            Line::styled(format!("Run:  {runs}"), style),
+            Line::styled(format!("Left: {remaining}"), style),
+        ];
+        f.render_widget(Paragraph::new(text).block(block), area);
+    }
+
+    /// Returns the title of a stack panel that is `width` wide, it contains the name of the panel and the `depth`
+    /// of the stack, e.g. `Stack (12)`.
+    ///
+    /// When the depth approaches `limit`, the limit is added and the title is colored, e.g. `Stack (60/64)`. The
+    /// short name or only the depth is used, if the title does not fit.
+    fn depth_title(
+        &self,
+        (name, short_name): (&str, &str),
+        depth: usize,
+        limit: Option<usize>,
+        width: u16,
+    ) -> Title<'static> {
+        let used = limit.map_or(0.0, |limit| depth as f64 / limit.max(1) as f64);
+        let style = self.theme.stack_depth(used);
+        let depth = match limit {
+            Some(limit) if style != Style::default() => format!("{depth}/{limit}"),
+            _ => depth.to_string(),
+        };
+        let available = usize::from(width.saturating_sub(2));
+        let title = [
+            format!("{name} ({depth})"),
+            format!("{short_name} ({depth})"),
+            format!("({depth})"),
+        ]
+        .into_iter()
+        .find(|title| title.chars().count() <= available)
+        .unwrap_or_else(|| short_name.to_string());
+        Title::from(Span::styled(title, style))
+    }
+
+    /// Draws the timeline of the executed steps, the highlighted step is the step whose memory is displayed.
+    fn draw_timeline(&self, f: &mut Frame, area: Rect) {
+        let Some(highlighted) = self.timeline.highlighted() else {
+            return;
+        };
+        let last_step = self
+            .timeline
+            .snapshot(self.timeline.len() - 1)
+            .map_or(0, |s| s.step);
+        let title = match self.timeline.snapshot(highlighted) {
+            Some(snapshot) if self.timeline.position().is_some() => format!(
+                "Timeline: step {} of {last_step}, line {}: {}",
+                snapshot.step, snapshot.line, snapshot.instruction
+            ),
+            _ => format!("Timeline: step {last_step} (current state)"),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_type(BorderType::Rounded)
+            .border_style(if self.timeline.position().is_some() {
+                self.theme.breakpoint_border()
+            } else {
+                self.theme.code_block_border()
+            })
+            .style(self.theme.code_block());
+        // one character for each step, arrows are displayed when not all steps fit
+        let width = area.width.saturating_sub(2) as usize;
+        let all_visible = self.timeline.len() <= width;
+        let range = self.timeline.visible_range(if all_visible {
+            width
+        } else {
+            width.saturating_sub(2)
+        });
+        let mut spans = Vec::new();
+        if !all_visible {
+            spans.push(Span::raw(if range.start > 0 { "◀" } else { " " }));
+        }
+        for idx in range.clone() {
+            if idx == highlighted {
+                spans.push(Span::styled("█", self.theme.list_item_highlight(false)));
+            } else {
+                spans.push(Span::styled(
+                    "▪",
+                    Style::default().add_modifier(Modifier::DIM),
+                ));
+            }
+        }
+        if !all_visible && range.end < self.timeline.len() {
+            spans.push(Span::raw("▶"));
+        }
+        f.render_widget(Paragraph::new(Line::from(spans)).block(block), area);
+    }
+
+    /// Returns where the instruction with index `idx` jumps to, if it is `goto` or `if ... then goto`.
+    ///
+    /// The target is returned as line number and label, e.g. `4 (loop)`, together with the first instruction that is
+    /// run at the target. The target is returned even if the jump depends on a condition that is not met.
+    fn jump_target_preview(&self, idx: usize) -> Option<(String, Line<'static>)> {
+        let label = match self.runtime.instructions().get(idx)? {
+            Instruction::Goto(label) | Instruction::JumpIf(_, _, _, label) => label,
+            Instruction::CondExec(_, _, _, instruction) => match instruction.as_ref() {
+                Instruction::Goto(label) => label,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        let target = self.runtime.control_flow().label_index(label)?;
+        let instruction = self.runtime.instructions()[target..]
+            .iter()
+            .find(|instruction| **instruction != Instruction::Noop);
+        Some(match instruction {
+            Some(instruction) => {
+                let mut spans = vec![Span::from("  ")];
+                spans.extend(instruction.to_spans(&self.program_syntax_highlighter()));
+                (format!("{} ({label})", target + 1), Line::from(spans))
+            }
+            None => (label.to_string(), Line::from("  end of program")),
+        })
+    }
+
+    /// Draws the instruction that is run next with block characters, used in presentation mode.
+    fn draw_presentation_instruction(&self, f: &mut Frame, area: Rect) {
+        let started = match &self.state {
+            State::Default => false,
+            State::DebugSelect(s, _) => **s != State::Default,
+            _ => true,
+        };
+        let next = self.runtime.next_instruction_index();
+        let (title, text) = match self.runtime.instructions().get(next) {
+            _ if !started => (
+                "Next instruction".to_string(),
+                "Press [s] to start".to_string(),
+            ),
+            Some(instruction) => (
+                format!("Next instruction (line {})", next + 1),
+                instruction.to_string(),
+            ),
+            None => ("Next instruction".to_string(), "Finished".to_string()),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title_alignment(Alignment::Center)
+            .border_type(BorderType::Rounded)
+            .border_style(self.theme.code_block_border())
+            .style(self.theme.code_block());
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        // fall back to normal text if the instruction is too long
+        let paragraph = if big_text_width(&text) <= area.width.saturating_sub(2) as usize {
+            Paragraph::new(big_text(&text, style))
+        } else {
+            Paragraph::new(Line::styled(text, style))
+        };
+        f.render_widget(paragraph.alignment(Alignment::Center).block(block), area);
+    }
+
+    /// Creates the list items that display the values of memory locations with block characters,
+    /// used in presentation mode.
+    ///
+    /// Values that are too wide for `width` are displayed with normal text.
+    fn big_value_items(
+        &self,
+        values: &[(String, Option<i32>)],
+        width: u16,
+    ) -> Vec<ListItem<'static>> {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        values
+            .iter()
+            .map(|(name, value)| {
+                let value = value.map_or_else(|| "-".to_string(), |v| v.to_string());
+                if big_text_width(&value) > width.saturating_sub(2) as usize {
+                    return ListItem::new(Line::styled(format!("{name}: {value}"), style));
+                }
+                let mut lines = vec![Line::from(format!("{name}:"))];
+                lines.extend(big_text(&value, style));
+                ListItem::new(Text::from(lines))
+            })
+            .collect()
+    }
+}
+
+/// Describes the changes of a custom instruction that was not yet run, one change per line.
+fn preview_text(instruction: &Instruction, event: &ExecutionEvent) -> String {
+    let mut reads = Vec::new();
+    for location in instruction.reads() {
+        let location = location.to_string();
+        if !reads.contains(&location) {
+            reads.push(location);
+        }
+    }
+    let reads = if reads.is_empty() {
+        String::new()
+    } else {
+        format!("Reads {}.\n", reads.join(", "))
+    };
+    let mut changes = Vec::new();
+    for write in &event.writes {
+        let value = write
+            .value
+            .map_or_else(|| "uninitialized".to_string(), |v| v.to_string());
+        changes.push(format!("{} is set to {value}", write.target));
+    }
+    for value in &event.popped {
+        changes.push(format!("{value} is popped from the stack"));
+    }
+    for value in &event.pushed {
+        changes.push(format!("{value} is pushed onto the stack"));
+    }
+    if let Some(line) = event.jump {
+        changes.push(format!("line {line} is run next"));
+    }
+    if changes.is_empty() {
+        return format!("{reads}Running this instruction changes nothing.");
+    }
+    format!(
+        "{reads}Running this instruction changes:\n{}",
+        changes.join("\n")
+    )
+}
+
+/// Returns the items of a list with `height` lines that is scrolled down by `scroll` lines.
+///
+/// The list is scrolled at most so far, that the last item is displayed in the last line.
+fn scrolled(items: Vec<ListItem<'static>>, scroll: usize, height: usize) -> Vec<ListItem<'static>> {
+    let scroll = scroll.min(items.len().saturating_sub(height));
+    items.into_iter().skip(scroll).collect()
 }
 
 /// Creates a paragraph from the input text, where a new line is created when the space is to little
@@ -0,0 +1,216 @@
+//! Renders the app in representative states and compares the result with the snapshots stored in `snapshots/`.
+//!
+//! When the ui is changed on purpose, the snapshots are updated by running the tests with `UPDATE_SNAPSHOTS=1`,
+//! e.g. `UPDATE_SNAPSHOTS=1 cargo test snapshot`. The changed snapshots should be reviewed before they are committed.
+
+use std::{env, fs, path::PathBuf, rc::Rc};
+
+use ratatui::{backend::TestBackend, text::Line, Terminal};
+
+use crate::{
+    app::{
+        headless::app,
+        ui::{style::Theme, syntax_highlighting::SyntaxHighlighter},
+        App, State,
+    },
+    base::{ComparisonNotation, IdentifierNotation},
+    cli::GlobalArgs,
+    export::animation,
+    instructions::{instruction_config::InstructionConfig, Identifier, Instruction},
+    runtime::{self, builder::RuntimeBuilder},
+    utils::{self, test_utils},
+};
+
+/// Size of the terminal in which the app is rendered, if not stated otherwise.
+const SIZE: (u16, u16) = (110, 30);
+
+const PROGRAM: &str = "a := 3\nloop: a := a - 1\np(h1) := a * 2\nif a > 0 then goto loop\ncall done\ngoto END\ndone: push\nreturn";
+
+/// Renders the app into a terminal of `width` x `height` cells and returns the content as text.
+fn render(app: &mut App, (width, height): (u16, u16)) -> String {
+    app.keybinding_hints.update(&app.state).unwrap();
+    app.memory_lists_manager.update(&app.runtime);
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let frame = terminal.draw(|f| app.draw(f)).unwrap();
+    animation::frame_to_text(frame.buffer)
+}
+
+/// Compares `rendered` with the snapshot `name`, the snapshot is written instead, if `UPDATE_SNAPSHOTS` is set.
+fn assert_snapshot(name: &str, rendered: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/app/ui/snapshots")
+        .join(format!("{name}.txt"));
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, rendered).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| {
+        panic!(
+            "Unable to read snapshot [{}]: {e}\nCreate it by running the test with UPDATE_SNAPSHOTS=1",
+            path.display()
+        )
+    });
+    assert!(
+        expected == rendered,
+        "Rendered ui differs from snapshot [{}], run the test with UPDATE_SNAPSHOTS=1 if the change is intended.\n\nExpected:\n{expected}\nRendered:\n{rendered}",
+        path.display()
+    );
+}
+
+#[test]
+fn test_snapshot_default() {
+    let mut app = app(PROGRAM, false);
+    assert_snapshot("default", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_running() {
+    let mut app = app(PROGRAM, false);
+    app.start();
+    for _ in 0..4 {
+        app.step().unwrap();
+    }
+    assert_snapshot("running", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_finished() {
+    let mut app = app(PROGRAM, false);
+    app.start();
+    while !app.step().unwrap() {}
+    assert_snapshot("finished", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_runtime_error() {
+    let mut app = app("a := 1\npop", false);
+    app.start();
+    assert!(app.step().is_err());
+    assert_snapshot("runtime_error", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_playground() {
+    let mut app = app("", true);
+    assert_snapshot("playground", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_small_terminal() {
+    // the keybinding hints and the code are truncated
+    let mut app = app(PROGRAM, false);
+    app.start();
+    assert_snapshot("small_terminal", &render(&mut app, (60, 20)));
+}
+
+#[test]
+fn test_snapshot_ascii_notation() {
+    let theme = Rc::new(Theme::default());
+    let lines = SyntaxHighlighter::new(&theme.syntax_highlighting_theme())
+        .with_identifier_notation(IdentifierNotation::Ascii)
+        .input_to_lines(&test_utils::string_literal_to_vec(PROGRAM), true)
+        .unwrap();
+    let mut app = App::from_runtime(
+        test_utils::runtime_from_str(PROGRAM).unwrap(),
+        "test.alpha".to_string(),
+        &lines,
+        &None,
+        None,
+        None,
+        None,
+        false,
+        true,
+        ComparisonNotation::default(),
+        theme,
+    )
+    .with_identifier_notation(IdentifierNotation::Ascii);
+    app.start();
+    for _ in 0..4 {
+        app.step().unwrap();
+    }
+    assert_snapshot("ascii_notation", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_stack_limit() {
+    // the limit is displayed in the title of the stack when it is approached
+    let program = "a := 1\npush\npush\npush\npush";
+    let mut rb = RuntimeBuilder::new(&test_utils::string_literal_to_vec(program), "test").unwrap();
+    rb.apply_global_cli_args(&GlobalArgs {
+        stack_limit: Some(4),
+        ..GlobalArgs::default()
+    })
+    .unwrap();
+    let mut app = App::from_runtime(
+        rb.build().unwrap(),
+        "test.alpha".to_string(),
+        &test_utils::string_literal_to_vec(program)
+            .into_iter()
+            .map(Line::from)
+            .collect::<Vec<_>>(),
+        &None,
+        None,
+        None,
+        None,
+        false,
+        true,
+        ComparisonNotation::default(),
+        Rc::new(Theme::default()),
+    );
+    app.start();
+    for _ in 0..4 {
+        app.step().unwrap();
+    }
+    assert_snapshot("stack_limit", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_jump_target() {
+    // the next instruction is 'if a > 0 then goto loop', its target is displayed
+    let mut app = app(PROGRAM, false);
+    app.start();
+    for _ in 0..2 {
+        app.step().unwrap();
+    }
+    assert_snapshot("jump_target", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_trace() {
+    // the conditional jump in line 4 was taken, this is displayed in the trace and in the status bar
+    let mut app = app(PROGRAM, false);
+    app.show_trace = true;
+    app.start();
+    for _ in 0..3 {
+        app.step().unwrap();
+    }
+    assert_snapshot("trace", &render(&mut app, SIZE));
+}
+
+#[test]
+fn test_snapshot_forbidden_instruction() {
+    // the type of the instruction and the closest allowed types are displayed in the popup
+    let mut app = app("", true);
+    let config = InstructionConfig {
+        allowed_instruction_identifiers: Some(
+            utils::build_instruction_whitelist(
+                vec![
+                    "a := 1".to_string(),
+                    "a := p(h1)".to_string(),
+                    "a := a + p(h1)".to_string(),
+                ],
+                "test",
+            )
+            .unwrap(),
+        ),
+        ..InstructionConfig::default()
+    };
+    let instruction = Instruction::try_from("a := a * 2").unwrap();
+    let e = runtime::builder::check_instructions(std::slice::from_ref(&instruction), &config)
+        .unwrap_err();
+    app.instruction_config = Some(config);
+    app.forbidden_identifier = Some(instruction.identifier());
+    app.state = State::BuildProgramError(*e, true);
+    assert_snapshot("forbidden_instruction", &render(&mut app, SIZE));
+}
@@ -1,7 +1,7 @@
 use std::rc::Rc;
 
 use clap::ValueEnum;
-use ratatui::style::{palette::material::WHITE, Color, Style};
+use ratatui::style::{palette::material::WHITE, Color, Modifier, Style};
 use serde::{Deserialize, Serialize};
 
 pub type SharedTheme = Rc<Theme>;
@@ -16,6 +16,7 @@ pub struct Theme {
     foreground: Color,
     breakpoint_accent: Color,
     error: Color,
+    warning: Color,
     code_area_default: Color,
     list_item_highlight_fg: Color,
     list_item_highlight_bg: Color,
@@ -40,7 +41,7 @@ impl From<&BuildInTheme> for Theme {
         match value {
             BuildInTheme::DefaultOld => Theme::default_old(),
             BuildInTheme::Dracula => Theme::dracula(),
-            BuildInTheme::Gray => serde_json::from_str(r#"{"sh_theme":{"assignment":"White","op":"White","cmp":"White","label":"White","build_in":"White","accumulator":"White","gamma":"White","memory_cell_outer":"White","memory_cell_inner":"White","index_memory_cell_outer":"White","index_memory_cell_index_outer":"White","constant":"White","comment":"White"},"background":"Black","foreground":"White","breakpoint_accent":"DarkGray","error":"White","code_area_default":"White","list_item_highlight_fg":"White","list_item_highlight_bg":"DarkGray","line_numbers":"White","execution_finished_popup_border":"White","keybindings_fg":"White","keybindings_disabled_fg":"DarkGray","keybindings_bg":"DarkGray","custom_instruction_accent_fg":"White","memory_block_border":"White","internal_memory_block_border":"White"}"#).unwrap(),
+            BuildInTheme::Gray => serde_json::from_str(r#"{"sh_theme":{"assignment":"White","op":"White","cmp":"White","label":"White","build_in":"White","accumulator":"White","gamma":"White","memory_cell_outer":"White","memory_cell_inner":"White","index_memory_cell_outer":"White","index_memory_cell_index_outer":"White","constant":"White","comment":"White"},"background":"Black","foreground":"White","breakpoint_accent":"DarkGray","error":"White","warning":"White","code_area_default":"White","list_item_highlight_fg":"White","list_item_highlight_bg":"DarkGray","line_numbers":"White","execution_finished_popup_border":"White","keybindings_fg":"White","keybindings_disabled_fg":"DarkGray","keybindings_bg":"DarkGray","custom_instruction_accent_fg":"White","memory_block_border":"White","internal_memory_block_border":"White"}"#).unwrap(),
         }
     }
 }
@@ -54,6 +55,7 @@ impl Theme {
             foreground: Color::White,
             breakpoint_accent: Color::Magenta,
             error: Color::Red,
+            warning: Color::Yellow,
             code_area_default: Color::Green,
             list_item_highlight_fg: Color::White,
             list_item_highlight_bg: Color::Rgb(98, 114, 164),
@@ -76,6 +78,7 @@ impl Theme {
             foreground: FOREGROUND,
             breakpoint_accent: PURPLE,
             error: RED,
+            warning: YELLOW,
             code_area_default: GREEN,
             list_item_highlight_fg: FOREGROUND,
             list_item_highlight_bg: SELECTION,
@@ -107,6 +110,13 @@ impl Theme {
         }
     }
 
+    /// Style of stack values that are consumed by the next instruction.
+    pub fn stack_item_consumed(&self) -> Style {
+        Style::default()
+            .fg(self.breakpoint_accent)
+            .add_modifier(Modifier::BOLD)
+    }
+
     pub fn keybinding_hints(&self, enabled: bool) -> Style {
         let style = Style::default();
         if enabled {
@@ -116,6 +126,38 @@ impl Theme {
         }
     }
 
+    /// Style of the number of executed instructions, the color changes when the instruction limit is approached.
+    ///
+    /// `used` is the fraction of the instruction limit that is used.
+    pub fn instruction_runs(&self, used: f64) -> Style {
+        Style::default().fg(self.limit_color(used))
+    }
+
+    /// Style of the title of the stack and call stack panels, the color changes when the size limit is approached.
+    ///
+    /// `used` is the fraction of the size limit that is used.
+    pub fn stack_depth(&self, used: f64) -> Style {
+        if used >= 0.75 {
+            Style::default()
+                .fg(self.limit_color(used))
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        }
+    }
+
+    /// Returns the color that indicates how close a value is to its limit, `used` is the fraction of the limit
+    /// that is used.
+    fn limit_color(&self, used: f64) -> Color {
+        if used >= 0.9 {
+            self.error
+        } else if used >= 0.75 {
+            self.warning
+        } else {
+            self.foreground
+        }
+    }
+
     pub fn error_block(&self) -> Style {
         Style::default().bg(self.background).fg(self.foreground)
     }
@@ -6,8 +6,12 @@ use ratatui::{
 };
 
 use crate::{
-    base::Operation,
-    instructions::{IndexMemoryCellIndexType, Instruction, TargetType, Value},
+    base::{Comparison, ComparisonNotation, IdentifierNotation, Operation},
+    instructions::{
+        error_handling::InstructionParseError,
+        structured_loops::{LoopKeyword, StructuredLoop},
+        IndexMemoryCellIndexType, Instruction, TargetType, Value,
+    },
     utils::{self, remove_comment},
 };
 
@@ -19,6 +23,10 @@ const SPACING: usize = 2;
 /// Syntax highlighter used to pretty format instructions with syntax highlighting.
 pub struct SyntaxHighlighter {
     pub theme: SharedSyntaxHighlightingTheme,
+    /// Notation in which comparisons are displayed
+    pub comparison_notation: ComparisonNotation,
+    /// Notation in which accumulators, memory cells and gamma are displayed
+    pub identifier_notation: IdentifierNotation,
 }
 
 impl SyntaxHighlighter {
@@ -26,9 +34,64 @@ impl SyntaxHighlighter {
     pub fn new(theme: &SharedSyntaxHighlightingTheme) -> Self {
         Self {
             theme: theme.clone(),
+            comparison_notation: ComparisonNotation::default(),
+            identifier_notation: IdentifierNotation::Unicode,
         }
     }
 
+    /// Sets the notation in which comparisons are displayed.
+    pub fn with_comparison_notation(mut self, comparison_notation: ComparisonNotation) -> Self {
+        self.comparison_notation = comparison_notation;
+        self
+    }
+
+    /// Sets the notation in which accumulators, memory cells and gamma are displayed.
+    pub fn with_identifier_notation(mut self, identifier_notation: IdentifierNotation) -> Self {
+        self.identifier_notation = identifier_notation;
+        self
+    }
+
+    /// Replaces the lines of structured loops with the labels and jumps they were desugared into.
+    ///
+    /// `lines` are the formatted lines of the program, where each line belongs to the instruction with the same index.
+    pub fn expand_loops(
+        &self,
+        lines: &[Line<'static>],
+        instructions: &[Instruction],
+        structured_loops: &[StructuredLoop],
+    ) -> Vec<Line<'static>> {
+        let label_spans = |label: &str| {
+            vec![
+                string_into_span(label.to_string(), self.theme.label()),
+                string_into_span(":".to_string(), self.theme.build_in()),
+                fill_span(1),
+            ]
+        };
+        let mut lines = lines.to_vec();
+        for structured_loop in structured_loops {
+            if let Some(line) = lines.get_mut(structured_loop.start) {
+                let mut spans = label_spans(&structured_loop.start_label);
+                spans.append(&mut instructions[structured_loop.start].to_spans(self));
+                *line = Line::from(remove_trailing_whitespaces(spans));
+            }
+            if let Some(line) = lines.get_mut(structured_loop.end) {
+                *line = Line::from(instructions[structured_loop.end].to_spans(self));
+            }
+        }
+        // exit labels point to the line after the loop, which might also be part of a loop
+        for structured_loop in structured_loops {
+            if let (Some(label), Some(line)) = (
+                &structured_loop.exit_label,
+                lines.get_mut(structured_loop.end + 1),
+            ) {
+                let mut spans = label_spans(label);
+                spans.append(&mut line.spans.clone());
+                *line = Line::from(spans);
+            }
+        }
+        lines
+    }
+
     /// Creates a span containing ' := '.
     fn assignment_span(&self) -> Span<'static> {
         Span::from(" := ").style(self.theme.assignment())
@@ -51,18 +114,19 @@ impl SyntaxHighlighter {
 
     /// Creates a span formatted for an accumulator with index `idx`.
     fn accumulator_span(&self, idx: &usize) -> Span<'static> {
-        Span::from(format!("\u{03b1}{idx}")).style(self.theme.accumulator())
+        Span::from(self.identifier_notation.accumulator(*idx)).style(self.theme.accumulator())
     }
 
     /// Creates a span formatted for gamma.
     fn gamma_span(&self) -> Span<'static> {
-        Span::from("\u{03b3}").style(self.theme.gamma())
+        Span::from(self.identifier_notation.gamma()).style(self.theme.gamma())
     }
 
     /// Creates formatted spans for a memory cell with label `label`.
     fn memory_cell_spans(&self, label: &str) -> Vec<Span<'static>> {
         vec![
-            Span::from("\u{03c1}(".to_string()).style(self.theme.memory_cell_outer()),
+            Span::from(format!("{}(", self.identifier_notation.memory_cell()))
+                .style(self.theme.memory_cell_outer()),
             Span::from(label.to_string()).style(self.theme.memory_cell_inner()),
             Span::from(")".to_string()).style(self.theme.memory_cell_outer()),
         ]
@@ -70,8 +134,10 @@ impl SyntaxHighlighter {
 
     /// Creates formatted spans for a index memory cell with type `imcit`.
     fn index_memory_cell_spanns(&self, imcit: &IndexMemoryCellIndexType) -> Vec<Span<'static>> {
-        let mut spans =
-            vec![Span::from("\u{03c1}(".to_string()).style(self.theme.index_memory_cell_outer())];
+        let mut spans = vec![
+            Span::from(format!("{}(", self.identifier_notation.memory_cell()))
+                .style(self.theme.index_memory_cell_outer()),
+        ];
         spans.append(&mut imcit.to_spans(self));
         spans.push(Span::from(")".to_string()).style(self.theme.index_memory_cell_outer()));
         spans
@@ -118,8 +184,9 @@ impl SyntaxHighlighter {
             let mut spans = Vec::new();
 
             // if only comment is set, write comment at beginning of line
-            if parts.label.is_none() && parts.instruction.is_none() && parts.comment.is_some() {
-                let comment = parts.comment.unwrap();
+            if let (None, None, Some(comment)) = (&parts.label, &parts.instruction, &parts.comment)
+            {
+                let comment = comment.clone();
                 // if comment starts with '#' it will not be printed
                 if comment.starts_with('#') {
                     continue;
@@ -146,9 +213,9 @@ impl SyntaxHighlighter {
 
             // handle instruction
             if let Some(instruction) = parts.instruction {
-                let instruction = Instruction::try_from(instruction.as_str())?;
-                spans.append(&mut instruction.to_spans(self));
-                let len = Line::from(instruction.to_spans(self)).width();
+                let mut instruction_spans = instruction_spans(&instruction, self)?;
+                let len = Line::from(instruction_spans.clone()).width();
+                spans.append(&mut instruction_spans);
                 // fill spaces if enabled until next part is reached
                 if enable_alignment {
                     spans.push(fill_span(max_instruction_width - len + SPACING));
@@ -170,6 +237,18 @@ impl SyntaxHighlighter {
     }
 }
 
+/// Parses the instruction or loop keyword and returns it formatted with syntax highlighting.
+fn instruction_spans(
+    instruction: &str,
+    sh: &SyntaxHighlighter,
+) -> Result<Vec<Span<'static>>, InstructionParseError> {
+    let parts = utils::split_tokens(instruction);
+    match LoopKeyword::parse(&parts) {
+        Some(keyword) => Ok(keyword?.to_spans(sh)),
+        None => Ok(Instruction::try_from(instruction)?.to_spans(sh)),
+    }
+}
+
 /// This trait is used be able to transform specific data into spans.
 ///
 /// In used to make syntax highlighting possible.
@@ -178,6 +257,30 @@ pub trait ToSpans {
     fn to_spans(&self, sh: &SyntaxHighlighter) -> Vec<Span<'static>>;
 }
 
+impl ToSpans for LoopKeyword {
+    fn to_spans(&self, sh: &SyntaxHighlighter) -> Vec<Span<'static>> {
+        let condition = |keyword: &str, v: &Value, cmp: &Comparison, v2: &Value| {
+            let mut spans = vec![sh.build_in_span(&format!("{keyword} "))];
+            spans.append(&mut v.to_spans(sh));
+            spans.push(Span::from(" "));
+            spans.push(Span::from(cmp.notation(sh.comparison_notation)).style(sh.theme.cmp()));
+            spans.push(Span::from(" "));
+            spans.append(&mut v2.to_spans(sh));
+            spans
+        };
+        match self {
+            Self::While(v, cmp, v2) => {
+                let mut spans = condition("while", v, cmp, v2);
+                spans.push(sh.build_in_span(" do"));
+                spans
+            }
+            Self::End => vec![sh.build_in_span("end")],
+            Self::Repeat => vec![sh.build_in_span("repeat")],
+            Self::Until(v, cmp, v2) => condition("until", v, cmp, v2),
+        }
+    }
+}
+
 impl ToSpans for Instruction {
     fn to_spans(&self, sh: &SyntaxHighlighter) -> Vec<Span<'static>> {
         match self {
@@ -187,6 +290,17 @@ impl ToSpans for Instruction {
                 spans.append(&mut v.to_spans(sh));
                 spans
             }
+            Self::Calc(t, v, op, v2) if op.is_function() => {
+                let mut spans = t.to_spans(sh);
+                spans.push(sh.assignment_span());
+                spans.push(sh.op_span(op));
+                spans.push(Span::from("("));
+                spans.append(&mut v.to_spans(sh));
+                spans.push(Span::from(", "));
+                spans.append(&mut v2.to_spans(sh));
+                spans.push(Span::from(")"));
+                spans
+            }
             Self::Calc(t, v, op, v2) => {
                 let mut spans = t.to_spans(sh);
                 spans.push(sh.assignment_span());
@@ -197,6 +311,24 @@ impl ToSpans for Instruction {
                 spans.append(&mut v2.to_spans(sh));
                 spans
             }
+            Self::Rand(t, v, v2) => {
+                let mut spans = t.to_spans(sh);
+                spans.push(sh.assignment_span());
+                spans.push(Span::from("rand(").style(sh.theme.build_in()));
+                spans.append(&mut v.to_spans(sh));
+                spans.push(Span::from(", "));
+                spans.append(&mut v2.to_spans(sh));
+                spans.push(Span::from(")").style(sh.theme.build_in()));
+                spans
+            }
+            Self::Abs(t, v) => {
+                let mut spans = t.to_spans(sh);
+                spans.push(sh.assignment_span());
+                spans.push(Span::from("abs(").style(sh.theme.build_in()));
+                spans.append(&mut v.to_spans(sh));
+                spans.push(Span::from(")").style(sh.theme.build_in()));
+                spans
+            }
             Self::Call(label) => {
                 vec![sh.build_in_span("call"), sh.label_span(label)]
             }
@@ -207,13 +339,24 @@ impl ToSpans for Instruction {
                 let mut spans = vec![Span::from("if ").style(sh.theme.build_in())];
                 spans.append(&mut v.to_spans(sh));
                 spans.push(Span::from(" "));
-                spans.push(Span::from(format!("{cmp}")).style(sh.theme.cmp()));
+                spans.push(Span::from(cmp.notation(sh.comparison_notation)).style(sh.theme.cmp()));
                 spans.push(Span::from(" "));
                 spans.append(&mut v2.to_spans(sh));
                 spans.push(Span::from(" then goto").style(sh.theme.build_in()));
                 spans.push(sh.label_span(label));
                 spans
             }
+            Self::CondExec(v, cmp, v2, instruction) => {
+                let mut spans = vec![Span::from("if ").style(sh.theme.build_in())];
+                spans.append(&mut v.to_spans(sh));
+                spans.push(Span::from(" "));
+                spans.push(Span::from(cmp.notation(sh.comparison_notation)).style(sh.theme.cmp()));
+                spans.push(Span::from(" "));
+                spans.append(&mut v2.to_spans(sh));
+                spans.push(Span::from(" then ").style(sh.theme.build_in()));
+                spans.append(&mut instruction.to_spans(sh));
+                spans
+            }
             Self::Noop => vec![Span::from("")],
             Self::Pop => vec![sh.build_in_span("pop")],
             Self::Push => vec![sh.build_in_span("push")],
@@ -245,7 +388,7 @@ impl ToSpans for IndexMemoryCellIndexType {
             Self::MemoryCell(label) => sh.memory_cell_spans(label),
             Self::Index(idx) => {
                 vec![
-                    Span::from("\u{03c1}(".to_string())
+                    Span::from(format!("{}(", sh.identifier_notation.memory_cell()))
                         .style(sh.theme.index_memory_cell_index_outer()),
                     Span::from(format!("{idx}")).style(sh.theme.constant()),
                     Span::from(")".to_string()).style(sh.theme.index_memory_cell_index_outer()),
@@ -281,7 +424,7 @@ fn determine_alignment(instructions: &[String]) -> (usize, usize) {
         // Remove comments
         let instruction = remove_comment(instruction);
 
-        let mut parts = instruction.split_whitespace().collect::<Vec<&str>>();
+        let mut parts = utils::split_tokens(&instruction);
         if parts.is_empty() {
             continue;
         }
@@ -299,11 +442,11 @@ fn determine_alignment(instructions: &[String]) -> (usize, usize) {
         }
 
         let mut instruction_width = 0;
-        if let Ok(instruction) = Instruction::try_from(parts.join(" ").as_str()) {
-            let line = Line::from(instruction.to_spans(&SyntaxHighlighter::new(&Rc::new(
-                SyntaxHighlightingTheme::default(),
-            ))));
-            instruction_width = line.width();
+        if let Ok(spans) = instruction_spans(
+            &parts.join(" "),
+            &SyntaxHighlighter::new(&Rc::new(SyntaxHighlightingTheme::default())),
+        ) {
+            instruction_width = Line::from(spans).width();
         }
         if max_instruction_width < instruction_width {
             max_instruction_width = instruction_width;
@@ -352,7 +495,7 @@ fn input_parts(mut input: String) -> Option<InputParts> {
     }
 
     // check for label
-    let mut parts = input.split_whitespace().collect::<Vec<&str>>();
+    let mut parts = utils::split_tokens(&input);
     let label = if parts[0].ends_with(':') {
         Some(parts.remove(0).to_string().replace(':', ""))
     } else {
@@ -510,11 +653,11 @@ mod tests {
     #[test]
     fn test_determine_alignment() {
         assert_eq!(
-            determine_alignment(&vec!["test_label: a := 20 // comment".to_string()]),
+            determine_alignment(&["test_label: a := 20 // comment".to_string()]),
             (11, 8)
         );
         assert_eq!(
-            determine_alignment(&vec![
+            determine_alignment(&[
                 "test_label: a := 20 // comment".to_string(),
                 "main: if a == p(h2) then goto test_label // comment".to_string()
             ]),
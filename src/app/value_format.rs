@@ -0,0 +1,172 @@
+use std::env;
+
+use crate::preset::ValueFormatConfig;
+
+/// Value of `thousands_separator` in the config file that selects the separator from the locale of the system.
+pub const LOCALE_SEPARATOR: &str = "locale";
+
+/// Determines how values are displayed in the memory lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueFormat {
+    /// Separator that is inserted between groups of three digits, no separator is inserted if this is empty.
+    separator: String,
+    /// If set, the memory locations and values of a list are displayed in columns and the values are right aligned.
+    align: bool,
+    /// Minimal width of the value column.
+    width: usize,
+}
+
+impl Default for ValueFormat {
+    fn default() -> Self {
+        Self {
+            separator: String::new(),
+            align: true,
+            width: 0,
+        }
+    }
+}
+
+impl From<&ValueFormatConfig> for ValueFormat {
+    fn from(config: &ValueFormatConfig) -> Self {
+        let separator = if config.thousands_separator == LOCALE_SEPARATOR {
+            locale_separator(&system_locale()).to_string()
+        } else {
+            config.thousands_separator.clone()
+        };
+        Self {
+            separator,
+            align: config.align,
+            width: config.width,
+        }
+    }
+}
+
+impl ValueFormat {
+    /// Formats `value` with thousands separators, `value` is returned unchanged if it is not a number, e.g. `None`.
+    pub fn value(&self, value: &str) -> String {
+        if self.separator.is_empty() || value.parse::<i32>().is_err() {
+            return value.to_string();
+        }
+        let (sign, digits) = match value.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", value),
+        };
+        let mut grouped = String::new();
+        for (idx, digit) in digits.chars().enumerate() {
+            if idx > 0 && (digits.len() - idx) % 3 == 0 {
+                grouped.push_str(&self.separator);
+            }
+            grouped.push(digit);
+        }
+        format!("{sign}{grouped}")
+    }
+
+    /// Joins the memory locations and the already formatted values of `entries` to `location: value`.
+    ///
+    /// If values are aligned, the locations are padded to the widest location and the values are right aligned
+    /// to the widest value, or to the configured width if it is larger.
+    pub fn columns(&self, entries: &[(String, String)]) -> Vec<String> {
+        if !self.align {
+            return entries
+                .iter()
+                .map(|(location, value)| format!("{location}: {value}"))
+                .collect();
+        }
+        let location_width = entries
+            .iter()
+            .map(|(location, _)| location.chars().count())
+            .max()
+            .unwrap_or(0);
+        let value_width = entries
+            .iter()
+            .map(|(_, value)| value.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(self.width);
+        entries
+            .iter()
+            .map(|(location, value)| format!("{location:<location_width$}: {value:>value_width$}"))
+            .collect()
+    }
+}
+
+/// Returns the locale that is used to format numbers, e.g. `de_DE.UTF-8`.
+fn system_locale() -> String {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|locale| !locale.is_empty())
+        .unwrap_or_default()
+}
+
+/// Returns the thousands separator that is commonly used in `locale`.
+///
+/// A narrow no-break space is used instead of a space, so that the groups are not separated when the ascii
+/// character of a value is determined.
+fn locale_separator(locale: &str) -> &'static str {
+    let language = locale
+        .split(['_', '.', '@', '-'])
+        .next()
+        .unwrap_or_default();
+    match language {
+        "de" | "da" | "es" | "id" | "it" | "nl" | "pt" | "tr" => ".",
+        "cs" | "fi" | "fr" | "hu" | "nb" | "no" | "pl" | "ru" | "sk" | "sv" | "uk" => "\u{202f}",
+        _ => ",",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::preset::ValueFormatConfig;
+
+    use super::{locale_separator, ValueFormat};
+
+    fn value_format(separator: &str, align: bool, width: usize) -> ValueFormat {
+        ValueFormat::from(&ValueFormatConfig {
+            thousands_separator: separator.to_string(),
+            align,
+            width,
+        })
+    }
+
+    #[test]
+    fn test_thousands_separator() {
+        let format = value_format(",", false, 0);
+        assert_eq!(format.value("5"), "5");
+        assert_eq!(format.value("123"), "123");
+        assert_eq!(format.value("1234"), "1,234");
+        assert_eq!(format.value("-1234567"), "-1,234,567");
+        assert_eq!(format.value("-123456"), "-123,456");
+        assert_eq!(format.value("None"), "None");
+        assert_eq!(value_format("", false, 0).value("1234567"), "1234567");
+    }
+
+    #[test]
+    fn test_locale_separator() {
+        assert_eq!(locale_separator("de_DE.UTF-8"), ".");
+        assert_eq!(locale_separator("fr_FR"), "\u{202f}");
+        assert_eq!(locale_separator("en_US.UTF-8"), ",");
+        assert_eq!(locale_separator("C"), ",");
+        assert_eq!(locale_separator(""), ",");
+    }
+
+    #[test]
+    fn test_columns() {
+        let entries = vec![
+            ("h1".to_string(), "5".to_string()),
+            ("sum".to_string(), "1,234".to_string()),
+        ];
+        assert_eq!(
+            value_format(",", true, 0).columns(&entries),
+            vec!["h1 :     5", "sum: 1,234"]
+        );
+        assert_eq!(
+            value_format(",", true, 7).columns(&entries),
+            vec!["h1 :       5", "sum:   1,234"]
+        );
+        assert_eq!(
+            value_format(",", false, 7).columns(&entries),
+            vec!["h1: 5", "sum: 1,234"]
+        );
+    }
+}
@@ -63,12 +63,6 @@ impl Display for MemoryCell {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
-pub struct IndexMemoryCell {
-    pub index: usize,
-    pub data: i32,
-}
-
 /// Different ways of paring two values
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -95,6 +89,95 @@ impl Comparison {
     }
 }
 
+impl Comparison {
+    /// Returns the comparison that succeeds exactly when this comparison fails.
+    pub fn negate(self) -> Self {
+        match self {
+            Self::Lt => Self::Ge,
+            Self::Le => Self::Gt,
+            Self::Eq => Self::Neq,
+            Self::Neq => Self::Eq,
+            Self::Ge => Self::Lt,
+            Self::Gt => Self::Le,
+        }
+    }
+
+    /// Returns the symbol of the comparison in the selected notation.
+    pub fn notation(&self, notation: ComparisonNotation) -> &'static str {
+        match notation {
+            ComparisonNotation::Ascii => match self {
+                Self::Lt => "<",
+                Self::Le => "<=",
+                Self::Eq => "==",
+                Self::Neq => "!=",
+                Self::Ge => ">=",
+                Self::Gt => ">",
+            },
+            ComparisonNotation::Unicode => match self {
+                Self::Lt => "<",
+                Self::Le => "≤",
+                Self::Eq => "=",
+                Self::Neq => "≠",
+                Self::Ge => "≥",
+                Self::Gt => ">",
+            },
+        }
+    }
+}
+
+/// Notation in which comparisons are displayed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub enum ComparisonNotation {
+    /// `<`, `<=`, `==`, `!=`, `>=`, `>`
+    #[default]
+    Ascii,
+    /// `<`, `≤`, `=`, `≠`, `≥`, `>`
+    Unicode,
+}
+
+/// Notation in which accumulators, memory cells and the gamma accumulator are written or displayed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, ValueEnum)]
+pub enum IdentifierNotation {
+    /// `a0`, `p(h1)`, `y`
+    #[default]
+    Ascii,
+    /// `α0`, `ρ(h1)`, `γ`
+    Unicode,
+}
+
+impl IdentifierNotation {
+    /// Returns the name of the accumulator with index `idx`, e.g. `a0` or `α0`.
+    pub fn accumulator(self, idx: usize) -> String {
+        match self {
+            Self::Ascii => format!("a{idx}"),
+            Self::Unicode => format!("α{idx}"),
+        }
+    }
+
+    pub fn gamma(self) -> &'static str {
+        match self {
+            Self::Ascii => "y",
+            Self::Unicode => "γ",
+        }
+    }
+
+    /// Returns the symbol that is written in front of the name of a memory cell, e.g. `p` in `p(h1)`.
+    pub fn memory_cell(self) -> &'static str {
+        match self {
+            Self::Ascii => "p",
+            Self::Unicode => "ρ",
+        }
+    }
+
+    /// Returns the other notation.
+    pub fn other(self) -> Self {
+        match self {
+            Self::Ascii => Self::Unicode,
+            Self::Unicode => Self::Ascii,
+        }
+    }
+}
+
 impl TryFrom<&str> for Comparison {
     type Error = ();
 
@@ -113,14 +196,7 @@ impl TryFrom<&str> for Comparison {
 
 impl Display for Comparison {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Lt => write!(f, "<"),
-            Self::Le => write!(f, "<="),
-            Self::Eq => write!(f, "=="),
-            Self::Neq => write!(f, "!="),
-            Self::Ge => write!(f, ">="),
-            Self::Gt => write!(f, ">"),
-        }
+        write!(f, "{}", self.notation(ComparisonNotation::Ascii))
     }
 }
 
@@ -175,9 +251,18 @@ pub enum Operation {
     Mul,
     Div,
     Mod,
+    /// Smaller of the two values, written as `min(a, b)`
+    Min,
+    /// Larger of the two values, written as `max(a, b)`
+    Max,
 }
 
 impl Operation {
+    /// Returns true if the operation is written like a function (e.g. `min(a, b)`) instead of infix.
+    pub fn is_function(self) -> bool {
+        matches!(self, Self::Min | Self::Max)
+    }
+
     pub fn calc(self, x: i32, y: i32) -> Result<i32, RuntimeErrorType> {
         match self {
             Self::Add => match x.checked_add(y) {
@@ -238,6 +323,8 @@ impl Operation {
                     }
                 }
             }
+            Self::Min => Ok(x.min(y)),
+            Self::Max => Ok(x.max(y)),
         }
     }
 }
@@ -250,6 +337,8 @@ impl Display for Operation {
             Self::Mul => write!(f, "*"),
             Self::Div => write!(f, "/"),
             Self::Mod => write!(f, "%"),
+            Self::Min => write!(f, "min"),
+            Self::Max => write!(f, "max"),
         }
     }
 }
@@ -270,6 +359,8 @@ impl TryFrom<&str> for Operation {
             "*" | "×" => Ok(Operation::Mul),
             "/" | "÷" => Ok(Operation::Div),
             "%" => Ok(Operation::Mod),
+            "min" => Ok(Operation::Min),
+            "max" => Ok(Operation::Max),
             _ => Err(()),
         }
     }
@@ -283,6 +374,8 @@ impl ValueEnum for Operation {
             Operation::Mul,
             Operation::Div,
             Operation::Mod,
+            Operation::Min,
+            Operation::Max,
         ]
     }
 
@@ -293,6 +386,8 @@ impl ValueEnum for Operation {
             Self::Mul => Some(PossibleValue::new("mul")),
             Self::Div => Some(PossibleValue::new("div")),
             Self::Mod => Some(PossibleValue::new("mod")),
+            Self::Min => Some(PossibleValue::new("min")),
+            Self::Max => Some(PossibleValue::new("max")),
         }
     }
 }
@@ -305,14 +400,18 @@ impl CliHint for Operation {
             Self::Mul => String::from("mul"),
             Self::Div => String::from("div"),
             Self::Mod => String::from("mod"),
+            Self::Min => String::from("min"),
+            Self::Max => String::from("max"),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use clap::ValueEnum;
+
     use crate::{
-        base::{Comparison, MemoryCell, Operation},
+        base::{Comparison, ComparisonNotation, MemoryCell, Operation},
         cli::CliHint,
     };
 
@@ -375,6 +474,32 @@ mod tests {
         assert_eq!(format!("{}", Comparison::Gt), ">".to_string());
     }
 
+    #[test]
+    fn test_comparison_negate() {
+        for cmp in Comparison::value_variants() {
+            for (x, y) in [(1, 2), (2, 2), (3, 2)] {
+                assert_eq!(cmp.cmp(x, y), !cmp.negate().cmp(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparison_notation() {
+        for (s, ascii, unicode) in [
+            ("<=", "<=", "≤"),
+            ("=", "==", "="),
+            ("==", "==", "="),
+            ("≠", "!=", "≠"),
+            ("=>", ">=", "≥"),
+        ] {
+            let cmp = Comparison::try_from(s).unwrap();
+            assert_eq!(cmp.notation(ComparisonNotation::Ascii), ascii);
+            assert_eq!(cmp.notation(ComparisonNotation::Unicode), unicode);
+            // displayed notation can be parsed again
+            assert_eq!(Comparison::try_from(unicode), Ok(cmp));
+        }
+    }
+
     #[test]
     fn test_comparison_cli_hint() {
         assert_eq!(Comparison::Lt.cli_hint(), "lt".to_string());
@@ -391,7 +516,9 @@ mod tests {
         assert_eq!(Operation::Sub.calc(20, 5).unwrap(), 15);
         assert_eq!(Operation::Mul.calc(20, 5).unwrap(), 100);
         assert_eq!(Operation::Div.calc(20, 5).unwrap(), 4);
-        assert_eq!(Operation::Mod.calc(20, 5).unwrap(), 0)
+        assert_eq!(Operation::Mod.calc(20, 5).unwrap(), 0);
+        assert_eq!(Operation::Min.calc(20, -5).unwrap(), -5);
+        assert_eq!(Operation::Max.calc(20, -5).unwrap(), 20);
     }
 
     #[test]
@@ -403,6 +530,8 @@ mod tests {
         assert_eq!(Operation::try_from("/"), Ok(Operation::Div));
         assert_eq!(Operation::try_from("÷"), Ok(Operation::Div));
         assert_eq!(Operation::try_from("%"), Ok(Operation::Mod));
+        assert_eq!(Operation::try_from("min"), Ok(Operation::Min));
+        assert_eq!(Operation::try_from("max"), Ok(Operation::Max));
         assert_eq!(Operation::try_from("P"), Err(()));
     }
 
@@ -413,6 +542,8 @@ mod tests {
         assert_eq!(format!("{}", Operation::Mul), "*".to_string());
         assert_eq!(format!("{}", Operation::Div), "/".to_string());
         assert_eq!(format!("{}", Operation::Mod), "%".to_string());
+        assert_eq!(format!("{}", Operation::Min), "min".to_string());
+        assert_eq!(format!("{}", Operation::Max), "max".to_string());
     }
 
     #[test]
@@ -422,5 +553,7 @@ mod tests {
         assert_eq!(Operation::Mul.cli_hint(), "mul".to_string());
         assert_eq!(Operation::Div.cli_hint(), "div".to_string());
         assert_eq!(Operation::Mod.cli_hint(), "mod".to_string());
+        assert_eq!(Operation::Min.cli_hint(), "min".to_string());
+        assert_eq!(Operation::Max.cli_hint(), "max".to_string());
     }
 }
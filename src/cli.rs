@@ -3,9 +3,17 @@ use miette::{Diagnostic, Result};
 use thiserror::Error;
 
 use crate::{
-    app::ui::style::BuildInTheme,
-    base::{Comparison, Operation},
-    runtime::memory_config::MemoryConfig,
+    app::{breakpoints::BreakpointGroup, ui::style::BuildInTheme},
+    base::{Comparison, ComparisonNotation, IdentifierNotation, Operation},
+    exit_code::ExitCode,
+    export::{animation::AnimationFormat, ExportTarget},
+    import::ImportFormat,
+    runtime::{
+        lints::LINT_NAMES,
+        memory_config::{AutodetectionArg, MemoryConfig, MemoryVisibilityArg},
+        watchpoints::Watchpoint,
+        EndBehavior,
+    },
 };
 
 #[derive(Parser, Debug)]
@@ -37,6 +45,123 @@ pub struct GlobalArgs {
 
     #[arg(long, hide = true, global = true)]
     pub disable_instruction_limit: bool,
+
+    #[arg(
+        long,
+        help = "Seed for the random number generator used by rand",
+        long_help = "Seed for the random number generator that is used by the rand instruction.\nThe same seed always generates the same numbers, which makes runs reproducible. Defaults to 0.",
+        global = true,
+        display_order = 25
+    )]
+    pub seed: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Maximum number of instructions that may be run",
+        long_help = "Maximum number of instructions that may be run before the program is stopped with a runtime error.\nThis protects from infinite loops. Defaults to 1000000.",
+        global = true,
+        display_order = 28
+    )]
+    pub instruction_limit: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Maximum number of values on the stack",
+        long_help = "Maximum number of values on the stack, the program is stopped with a stack overflow when it is exceeded.\nThe title of the stack panel is colored when the limit is approached. By default the size of the stack is not limited.",
+        global = true,
+        display_order = 29
+    )]
+    pub stack_limit: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Maximum depth of the call stack",
+        long_help = "Maximum depth of the call stack, the program is stopped with a stack overflow when it is exceeded.\nThe title of the call stack panel is colored when the limit is approached. Defaults to 65535.",
+        global = true,
+        display_order = 29
+    )]
+    pub call_stack_limit: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Reuse the frame of the current function for calls that are followed by return",
+        long_help = "Reuse the frame of the current function for calls that are directly followed by return (tail calls).\nThe called function returns directly to the caller of the current function, so that tail recursive functions don't overflow the call stack.\nFrames that were reused are marked in the call stack panel.",
+        global = true,
+        display_order = 29
+    )]
+    pub tail_call_optimization: bool,
+
+    #[arg(
+        long,
+        help = "Apply a preset of options",
+        long_help = "Apply a preset of options.\nOptions that are set on the command line take precedence over the options of the preset.\n\nAvailable presets:\n  strict - options that match the course definition: no memory autodetection, only the instructions of the official instruction set, no structured loops and an instruction limit of 100000\n\nFurther presets can be defined in the config file, see \"--config-file\".",
+        global = true,
+        display_order = 29
+    )]
+    pub preset: Option<String>,
+
+    #[arg(
+        long,
+        help = "Load presets from a json file",
+        long_help = "Load presets from a json file.\nIf not set, the file config.json in the config directory (~/.config/alpha_tui in linux or %APPDATA%/alpha_tui in windows) is used, if it exists.\n\nFurther help can be found here: https://github.com/LMH01/alpha_tui/blob/master/docs/cli.md.",
+        global = true,
+        display_order = 30
+    )]
+    pub config_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Don't download files that are given as urls",
+        long_help = "Don't access the network.\nBy default the program and the memory config, instruction config and theme files can be given as http or https urls, from which they are downloaded. If this is set, files are only read from the file system and `update --check` does not query the latest release.",
+        global = true,
+        display_order = 31
+    )]
+    pub offline: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Only print errors and the result",
+        long_help = "Only print errors and the result of the command.\nProgress messages (e.g. \"Building runtime\"), lint warnings and statistics are not printed, this is useful for CI logs.",
+        conflicts_with = "verbose",
+        global = true,
+        display_order = 32
+    )]
+    pub quiet: bool,
+
+    #[arg(
+        short,
+        long,
+        help = "Print additional details",
+        long_help = "Print additional details while the command runs.\nThe check command prints the memory config and instruction limit that are used and the number of executed instructions, the test command prints the memory config and golden trace of each test case.",
+        global = true,
+        display_order = 33
+    )]
+    pub verbose: bool,
+}
+
+impl GlobalArgs {
+    /// Returns how much output the commands should print.
+    pub fn verbosity(&self) -> Verbosity {
+        if self.quiet {
+            Verbosity::Quiet
+        } else if self.verbose {
+            Verbosity::Verbose
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+/// How much output the commands print, set with `--quiet` and `--verbose`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only errors and the result are printed.
+    Quiet,
+    /// Progress messages are printed.
+    Normal,
+    /// Additional details are printed.
+    Verbose,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -48,7 +173,7 @@ pub struct LoadArgs {
     pub load_playground_args: LoadPlaygroundArgs,
 
     #[arg(
-        long_help = "Specify the input file that contains the program",
+        long_help = "Specify the input file that contains the program, use - to read the program from stdin",
         required = true
     )]
     pub file: String,
@@ -64,6 +189,15 @@ pub struct LoadArgs {
     )]
     pub breakpoints: Option<Vec<usize>>,
 
+    #[arg(
+        long,
+        help = "Define a named group of breakpoints",
+        long_help = "Define a named group of breakpoints, as <name>=<lines>.\nThe breakpoints of a group can be enabled and disabled together with the keys [1] to [9] in the order in which the groups are defined, this makes it possible to switch between debugging scenarios quickly.\nCan be used multiple times.\nExample: --breakpoint-group loop=3,5,7 --breakpoint-group init=1",
+        value_name = "NAME=LINES",
+        display_order = 30
+    )]
+    pub breakpoint_group: Vec<BreakpointGroup>,
+
     #[arg(
         short,
         long,
@@ -93,6 +227,65 @@ pub struct LoadArgs {
         display_order = 31
     )]
     pub custom_instruction_history_file: Option<String>,
+
+    #[arg(
+        long,
+        help = "Allow the popup of a division by zero to be closed",
+        long_help = "Allow the popup of a division or modulo by zero to be closed, the program is paused instead.\nThe program and the memory can be inspected, before the memory is fixed with a custom instruction and the failed instruction is run again.",
+        display_order = 34
+    )]
+    pub resumable_division_by_zero: bool,
+
+    #[arg(
+        long,
+        help = "Show the changes of a custom instruction before it is run",
+        long_help = "Show the changes that a custom instruction would make to the memory before it is run.\nThe instruction is only run when the changes are confirmed, this prevents accidental changes while debugging.",
+        display_order = 35
+    )]
+    pub confirm_custom_instructions: bool,
+
+    #[arg(
+        long,
+        help = "Disable running custom instructions",
+        long_help = "Disable running custom instructions, the memory can then only be changed by the program itself.\nThe key [i] is not available and the custom instruction popup can not be opened, this is intended for exams and demonstrations.",
+        conflicts_with = "confirm_custom_instructions",
+        display_order = 35
+    )]
+    pub no_custom_instructions: bool,
+
+    #[arg(
+        long,
+        help = "Only allow stepping through and running the program",
+        long_help = "Only allow stepping through and running the program, for supervised demonstrations and exams.\nNothing can be changed outside of what the program does: custom instructions, jumping to a line and renaming or extracting lines are not available.",
+        conflicts_with = "confirm_custom_instructions",
+        display_order = 35
+    )]
+    pub read_only: bool,
+
+    #[arg(
+        long,
+        help = "Enable the features that are only available to instructors",
+        long_help = "Enable the features that are listed in `instructor_only` in the config file.\nWithout this flag these features are not available, so that the same installation can be handed to students with a safer set of features.",
+        display_order = 35
+    )]
+    pub instructor: bool,
+
+    #[arg(
+        long,
+        help = "Share the execution state on a local port",
+        long_help = "Share the execution state (next line and memory) on a local port.\nThe memory can then be displayed in another terminal with \"alpha_tui follow <port>\", for example to show the memory on a projector while the code is shown on another screen.",
+        value_name = "PORT",
+        display_order = 36
+    )]
+    pub serve: Option<u16>,
+
+    #[arg(
+        long,
+        help = "Start in presentation mode",
+        long_help = "Start in presentation mode, for demonstrating executions on a projector.\nThe next instruction and the values of the memory are displayed with big block characters and keybinding hints are hidden.\nPresentation mode can be toggled at runtime with [p].",
+        display_order = 37
+    )]
+    pub presentation: bool,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -101,20 +294,311 @@ pub struct CheckArgs {
     pub check_load_args: CheckLoadArgs,
 
     #[arg(
-        long_help = "Specify the input file that contains the program",
+        long_help = "Specify the input file that contains the program, use - to read the program from stdin",
         required = true
     )]
     pub file: String,
 
+    #[arg(
+        long,
+        help = "Keep running and recheck the file every time it is changed",
+        long_help = "Keep running and recheck the file every time it is saved.\nThe result of each check is printed to the console.",
+        global = true,
+        display_order = 40
+    )]
+    pub watch: bool,
+
+    #[arg(
+        long,
+        help = "Send a desktop notification when the check status changes",
+        long_help = "Send a desktop notification when the check status changes.\nRequires `notify-send` on Linux, on macOS `osascript` is used.",
+        requires = "watch",
+        global = true,
+        display_order = 41
+    )]
+    pub notify: bool,
+
+    #[arg(
+        long,
+        help = "Lints that are not reported",
+        long_help = "Lints that are not reported, separated by commas.\nAvailable lints: pop_from_empty_stack, stack_op_on_short_stack, unbalanced_subroutine, mixed_notation, label_case",
+        value_delimiter = ',',
+        global = true,
+        display_order = 42
+    )]
+    pub allow: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Lints that are reported as warnings",
+        long_help = "Lints that are reported as warnings, separated by commas. This is the default for all lints.\nAvailable lints: pop_from_empty_stack, stack_op_on_short_stack, unbalanced_subroutine, mixed_notation, label_case",
+        value_delimiter = ',',
+        global = true,
+        display_order = 43
+    )]
+    pub warn: Vec<String>,
+
+    #[arg(
+        long,
+        help = "Lints that make the check fail",
+        long_help = "Lints that are reported as errors and make the check fail, separated by commas.\nDenied lints can't be suppressed with comments in the program.\nAvailable lints: pop_from_empty_stack, stack_op_on_short_stack, unbalanced_subroutine, mixed_notation, label_case",
+        value_delimiter = ',',
+        global = true,
+        display_order = 44
+    )]
+    pub deny: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Write the built program as JSON to the file",
+        long_help = "Write the built program as JSON to the file, when the program compiles.\nThe JSON contains the labels and for each line the location in the file, the source code and the instruction with its operands.\nThis way other tools can use the program without parsing it.\n\nFor more help see https://github.com/LMH01/alpha_tui/blob/master/docs/interface_and_usage.md",
+        global = true,
+        display_order = 45
+    )]
+    pub emit: Option<String>,
+
+    #[arg(
+        long,
+        help = "Read the program from a JSON file that was written with --emit",
+        long_help = "Read the program from a JSON file that was written with --emit, instead of parsing it.\nCan be used by tools that generate programs and to test that programs are parsed correctly.\n\nFor more help see https://github.com/LMH01/alpha_tui/blob/master/docs/interface_and_usage.md",
+        conflicts_with = "emit",
+        global = true,
+        display_order = 46
+    )]
+    pub ast: bool,
+
     #[command(subcommand)]
     pub command: CheckCommand,
 }
 
+#[derive(Args, Clone, Debug)]
+pub struct TestArgs {
+    #[arg(
+        long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+        required = true
+    )]
+    pub file: String,
+
+    #[arg(
+        short,
+        long,
+        help = "Memory config files that are used as test cases",
+        long_help = "Memory config files that are used as test cases, the name of a test case is the name of the file without extension.\nEach file contains the input values of one test case.\nIf not set, a single test case named \"default\" is run, it uses the memory config file set with \"--memory-config-file\".\nExample: --cases tests/small.json,tests/large.json",
+        value_delimiter = ','
+    )]
+    pub cases: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "Directory in which the golden traces are stored",
+        long_help = "Directory in which the golden traces are stored, one file per test case.\nDefaults to <FILE>.traces"
+    )]
+    pub traces_dir: Option<String>,
+
+    #[arg(
+        long,
+        help = "Record golden traces instead of comparing against them",
+        long_help = "Record golden traces instead of comparing against them.\nExisting golden traces are overwritten."
+    )]
+    pub record: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct GradeArgs {
+    #[arg(
+        long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+        required = true
+    )]
+    pub file: String,
+
+    #[arg(
+        short,
+        long,
+        help = "Rubric file that the program is graded against",
+        long_help = "Rubric file that the program is graded against.\nSee https://github.com/LMH01/alpha_tui/blob/master/examples/rubric.json for an example.",
+        required = true
+    )]
+    pub rubric: String,
+}
+
+#[derive(Args, Clone, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: Option<BundleCommand>,
+
+    #[arg(
+        long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+        required = true
+    )]
+    pub file: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "File to which the bundle is written",
+        long_help = "File to which the bundle is written.\nDefaults to <FILE>.bundle.tar, where the extension of <FILE> is replaced"
+    )]
+    pub output: Option<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct RefactorArgs {
+    #[command(subcommand)]
+    pub command: RefactorCommand,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct FollowArgs {
+    #[arg(long_help = "Local port on which the execution state is shared")]
+    pub port: u16,
+
+    #[arg(
+        short,
+        long,
+        help = "What build-in theme to use.",
+        long_help = "What build-in theme to use."
+    )]
+    pub theme: Option<BuildInTheme>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ExplainArgs {
+    #[arg(long_help = "Error code to explain, e.g. E311")]
+    pub code: Option<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct UpdateArgs {
+    #[arg(
+        long,
+        help = "Query the latest release and report whether it is newer than the installed version"
+    )]
+    pub check: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct BenchArgs {
+    #[arg(long_help = "Only run the benchmarks whose name contains this text")]
+    pub filter: Option<String>,
+
+    #[arg(
+        long,
+        help = "Number of measured runs of each benchmark",
+        default_value_t = 20
+    )]
+    pub samples: usize,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ImportArgs {
+    #[arg(
+        long_help = "Specify the input file that contains the program that should be converted, use - to read the program from stdin",
+        required = true
+    )]
+    pub file: String,
+
+    #[arg(
+        short,
+        long,
+        help = "Format of the input file",
+        value_enum,
+        default_value_t = ImportFormat::Ram
+    )]
+    pub format: ImportFormat,
+
+    #[arg(
+        short,
+        long,
+        help = "File to write the converted program to",
+        long_help = "File to write the converted program to.\nIf not set, the converted program is printed to the console."
+    )]
+    pub output: Option<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ExportArgs {
+    #[arg(
+        long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+        required = true
+    )]
+    pub file: String,
+
+    #[arg(
+        short,
+        long,
+        help = "Language to convert the program into",
+        value_enum,
+        required_unless_present = "animate",
+        conflicts_with = "animate"
+    )]
+    pub to: Option<ExportTarget>,
+
+    #[arg(
+        short,
+        long,
+        help = "File to write the converted program to",
+        long_help = "File to write the converted program to.\nIf not set, the converted program is printed to the console."
+    )]
+    pub output: Option<String>,
+
+    #[arg(
+        long,
+        help = "Export an animation of the execution instead of converting the program",
+        long_help = "Run the program off-screen and export an animation of the execution instead of converting the program.\nEach frame shows the tui after one instruction was run. Asciinema casts can be played with \"asciinema play\" or embedded into web pages, frames are written as plain text.",
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        default_missing_value = "asciinema"
+    )]
+    pub animate: Option<AnimationFormat>,
+
+    #[arg(
+        long,
+        help = "Width of the animation in columns",
+        long_help = "Width of the animation in columns.\nOnly used with \"--animate\".",
+        default_value_t = 120
+    )]
+    pub width: u16,
+
+    #[arg(
+        long,
+        help = "Height of the animation in lines",
+        long_help = "Height of the animation in lines.\nOnly used with \"--animate\".",
+        default_value_t = 30
+    )]
+    pub height: u16,
+
+    #[arg(
+        long,
+        help = "Time in milliseconds that each frame of an asciinema cast is displayed",
+        long_help = "Time in milliseconds that each frame of an asciinema cast is displayed.\nOnly used with \"--animate\".",
+        value_name = "MILLISECONDS",
+        default_value_t = 500
+    )]
+    pub frame_delay: u64,
+
+    #[arg(
+        long,
+        help = "Maximum number of frames in the animation",
+        long_help = "Maximum number of frames in the animation.\nThe animation ends early when the program runs more instructions.\nOnly used with \"--animate\".",
+        default_value_t = 1000
+    )]
+    pub max_frames: usize,
+}
+
 #[derive(Args, Clone, Debug)]
 pub struct PlaygroundArgs {
     #[command(flatten)]
     pub load_playground_args: LoadPlaygroundArgs,
 
+    #[command(flatten)]
+    pub instruction_limiting_args: InstructionLimitingArgs,
+
+    #[command(flatten)]
+    pub memory_args: MemoryArgs,
+
     #[arg(
         short,
         long,
@@ -137,6 +621,54 @@ pub struct LoadPlaygroundArgs {
     )]
     pub disable_syntax_highlighting: bool,
 
+    #[arg(
+        long,
+        help = "Notation in which comparisons are displayed.",
+        long_help = "Notation in which comparisons are displayed. All notations can be used in the program, regardless of this setting.",
+        global = true,
+        default_value = "ascii",
+        display_order = 33
+    )]
+    pub comparison_notation: ComparisonNotation,
+
+    #[arg(
+        long,
+        help = "Notation in which accumulators, memory cells and gamma are displayed.",
+        long_help = "Notation in which accumulators, memory cells and gamma are displayed, 'unicode' displays them as α0, ρ(h1) and γ, 'ascii' as a0, p(h1) and y. All notations can be used in the program, regardless of this setting.",
+        global = true,
+        default_value = "unicode",
+        display_order = 33
+    )]
+    pub identifier_notation: IdentifierNotation,
+
+    #[arg(
+        long,
+        help = "Show in which direction the stack grows.",
+        long_help = "Show an arrow in the title of the stack block that indicates in which direction the stack grows.",
+        global = true,
+        display_order = 34
+    )]
+    pub show_stack_direction: bool,
+
+    #[arg(
+        long,
+        help = "When values of a memory type are displayed.",
+        long_help = "When values of a memory type are displayed, as a list of <memory type>=<visibility>.\nMemory types: accumulators, gamma, memory-cells, index-memory-cells\nVisibilities: always, when-declared (default), when-used\nExample: --memory-visibility gamma=always,memory-cells=when-used\n\nOverwrites the visibility set in the memory config file.",
+        value_delimiter = ',',
+        global = true,
+        display_order = 35
+    )]
+    pub memory_visibility: Option<Vec<MemoryVisibilityArg>>,
+
+    #[arg(
+        long,
+        help = "Display the key events that are reported by the terminal",
+        long_help = "Display an overlay with the last key events as they are reported by the terminal, including the modifiers and whether a key was pressed or released.\nIf the key event is changed before it is handled, e.g. because the terminal reports AltGr as Ctrl+Alt on Windows, the changed key event is displayed after an arrow.\nThis can be used to find out why a keybinding does not work in a terminal.",
+        global = true,
+        display_order = 36
+    )]
+    pub debug_keys: bool,
+
     #[arg(
         short,
         long,
@@ -158,12 +690,9 @@ pub struct LoadPlaygroundArgs {
     pub theme_file: Option<String>,
 }
 
-/// Args only allowed in check and load
-#[derive(Args, Clone, Debug)]
-pub struct CheckLoadArgs {
-    #[command(flatten)]
-    pub instruction_limiting_args: InstructionLimitingArgs,
-
+/// Args that declare the available memory, allowed in check, load and playground
+#[derive(Args, Clone, Debug, Default)]
+pub struct MemoryArgs {
     #[arg(
         short,
         long,
@@ -198,8 +727,145 @@ pub struct CheckLoadArgs {
         display_order = 22
     )]
     pub memory_cells: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        help = "Disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection",
+        long_help = "Set to disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection.\nIf disabled, accumulators, gamma accumulator, memory cells and index memory cells won't be read from program and cannot be added by using them at runtime.\nInstead they have to be specified using \"--accumulators\", \"--enable-gamma-accumulator\", \"--memory-cells\" and \"--index-memory-cells\" or \"--memory-config-file\"",
+        global = true,
+        display_order = 25
+    )]
+    pub disable_memory_detection: bool,
+
+    #[arg(
+        long,
+        help = "Enable or disable the detection of single memory types",
+        long_help = "Enable or disable the detection of single memory types, as a list of <memory type>=<on|off>.\nMemory types: accumulators, gamma, memory-cells (mc), index-memory-cells (imc)\nExample: --autodetect accumulators=off,imc=on\n\nMemory types that are not listed are detected automatically. If the autodetection of a memory type is set in the memory config file, that value is used instead.",
+        value_delimiter = ',',
+        conflicts_with = "disable_memory_detection",
+        global = true,
+        display_order = 26
+    )]
+    pub autodetect: Option<Vec<AutodetectionArg>>,
+
+    #[arg(
+        short = 'g',
+        long,
+        help = "Enable the gamma accumulator",
+        long_help = "Enable the gamma accumulator, can be used to enable gamma accumulator when automatic detection is disabled by \"--disable-memory-detection\".",
+        conflicts_with = "memory_config_file",
+        global = true,
+        display_order = 21
+    )]
+    pub enable_gamma_accumulator: bool,
 }
 
+/// Args only allowed in check and load
+#[derive(Args, Clone, Debug, Default)]
+pub struct CheckLoadArgs {
+    #[command(flatten)]
+    pub instruction_limiting_args: InstructionLimitingArgs,
+
+    #[command(flatten)]
+    pub memory_args: MemoryArgs,
+
+    #[arg(
+        long,
+        help = "Don't count empty lines as execution steps",
+        long_help = "Per default empty lines, lines containing only a comment and lines containing only a label consume an execution step when they are reached.\nIf set, these lines are skipped. They still count as lines, so jump targets and line numbers are not changed.",
+        global = true,
+        display_order = 26
+    )]
+    pub skip_empty_lines: bool,
+
+    #[arg(
+        long,
+        help = "Don't allow labels on lines without instruction",
+        long_help = "Per default labels can be placed on lines that don't contain an instruction, jumping to such a label continues with the next instruction.\nIf set, the program fails to build when a label is not followed by an instruction in the same line.",
+        global = true,
+        display_order = 27
+    )]
+    pub deny_labels_on_empty_lines: bool,
+
+    #[arg(
+        long,
+        help = "What happens when return is run outside of a function [default: jump-to-end]",
+        long_help = "Determines what happens when return is run while the call stack is empty, for example when the program is ended with return.\njump-to-end: the program jumps to the END label, which is located after the last instruction\nhalt: the program stops at the return instruction\nerror: a runtime error is raised\n\nIf not set, jump-to-end is used.",
+        value_name = "BEHAVIOR",
+        global = true,
+        display_order = 28
+    )]
+    pub end_behavior: Option<EndBehavior>,
+
+    #[arg(
+        long,
+        help = "Don't distinguish labels by case",
+        long_help = "Per default labels are case sensitive, so 'loop' and 'Loop' are different labels.\nIf set, labels that only differ by case are the same label and defining both fails to build the program.",
+        global = true,
+        display_order = 29
+    )]
+    pub case_insensitive_labels: bool,
+
+    #[arg(
+        long,
+        help = "Stop the execution when the value of a memory location changes",
+        long_help = "Stop the execution when the value of a memory location changes (data breakpoint).\nIn the tui running to the next breakpoint also stops at this point, \"check run\" stops the program and reports the location.\nCan be used multiple times.\nExample: --break-on-write p(h1)",
+        value_name = "LOCATION",
+        value_parser = Watchpoint::on_write,
+        global = true,
+        display_order = 32
+    )]
+    pub break_on_write: Vec<Watchpoint>,
+
+    #[arg(
+        long,
+        help = "Stop the execution when a condition becomes true",
+        long_help = "Stop the execution when a condition becomes true (data breakpoint), the condition is written like the condition of if.\nIn the tui running to the next breakpoint also stops at this point, \"check run\" stops the program and reports the condition.\nThe execution only stops when the condition changes from false to true, uninitialized values never fulfill the condition.\nCan be used multiple times.\nExample: --break-on-value \"a0 > 100\"",
+        value_name = "CONDITION",
+        value_parser = Watchpoint::on_value,
+        global = true,
+        display_order = 33
+    )]
+    pub break_on_value: Vec<Watchpoint>,
+
+    #[arg(
+        long,
+        help = "Measure the time that is spent running each kind of instruction",
+        long_help = "Measure the time that is spent running each kind of instruction.\n\"check run\" prints the measured times after the statistics, in the tui they are displayed in the debug panel that is opened with [F12].\nMeasuring makes running the program slightly slower.",
+        global = true,
+        display_order = 34
+    )]
+    pub measure: bool,
+}
+
+impl CheckLoadArgs {
+    /// Returns the watchpoints that are set with `--break-on-write` and `--break-on-value`.
+    pub fn watchpoints(&self) -> Vec<Watchpoint> {
+        self.break_on_write
+            .iter()
+            .chain(&self.break_on_value)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Exit codes of the check command, in addition to the exit codes that every command can return.
+const CHECK_EXIT_CODES: &[ExitCode] = &[
+    ExitCode::ParseError,
+    ExitCode::BuildError,
+    ExitCode::RuntimeError,
+    ExitCode::InstructionLimitReached,
+];
+
+/// Exit codes of the test command, in addition to the exit codes that every command can return.
+const TEST_EXIT_CODES: &[ExitCode] = &[
+    ExitCode::ParseError,
+    ExitCode::BuildError,
+    ExitCode::RuntimeError,
+    ExitCode::InstructionLimitReached,
+    ExitCode::TestFailure,
+];
+
 #[derive(Subcommand, Clone, Debug)]
 pub enum Command {
     #[command(about = "Load an alpha notation program")]
@@ -207,15 +873,79 @@ pub enum Command {
 
     #[command(
         about = "Perform different checks on the program",
-        long_about = "Perform different checks on the program.\nReturn values:\n\n 0 - Check successful\n 1 - Compilation error\n 2 - Runtime error\n10 - IO error"
+        long_about = "Perform different checks on the program.",
+        after_long_help = ExitCode::help(CHECK_EXIT_CODES)
     )]
     Check(CheckArgs),
 
+    #[command(
+        about = "Compare the execution of the program against golden traces",
+        long_about = "Compare the execution of the program against golden traces.\nA trace contains every executed instruction with the values that were written, so differences are found even when the final memory matches.\nUse \"--record\" to record the golden traces.",
+        after_long_help = ExitCode::help(TEST_EXIT_CODES)
+    )]
+    Test(TestArgs),
+
+    #[command(
+        about = "Grade the program against a rubric",
+        long_about = "Grade the program against a rubric.\nThe rubric can limit the number of executed instructions and used memory cells, forbid instructions and require values in memory after the program has finished.\nA score breakdown is printed for all criteria of the rubric."
+    )]
+    Grade(GradeArgs),
+
+    #[command(
+        about = "Create a submission bundle of the program",
+        long_about = "Create a submission bundle of the program.\nThe program is run and a tar archive is created that contains the program, the memory config that was used, the memory after the program was run, a run summary and the sha256 hashes of these files.\nThe content hash of the bundle is printed."
+    )]
+    Bundle(BundleArgs),
+
+    #[command(
+        about = "Refactor the program",
+        long_about = "Refactor the program.\nThe program file is changed in place, only the parts of the lines that are affected by the refactoring are changed, so the formatting and comments of the program are preserved."
+    )]
+    Refactor(RefactorArgs),
+
     #[command(
         about = "Start the tool in playground mode",
         long_about = "Start the tool in playground mode. This allows for custom commands to be run."
     )]
     Playground(PlaygroundArgs),
+
+    #[command(
+        about = "Display the memory of a program that is run in another terminal",
+        long_about = "Display the memory of a program that is run in another terminal.\nThe program has to be loaded with \"--serve <port>\", the memory is updated every time the program changes.\nThe view is read-only, press [q] to exit."
+    )]
+    Follow(FollowArgs),
+
+    #[command(
+        about = "Convert a program written for another register machine into alpha notation",
+        long_about = "Convert a program written for another register machine into alpha notation.\nThe converted program is printed to the console, unless an output file is specified."
+    )]
+    Import(ImportArgs),
+
+    #[command(
+        about = "Convert an alpha notation program into pseudocode or C or export an animation of its execution",
+        long_about = "Convert an alpha notation program into pseudocode or C or export an animation of its execution with \"--animate\".\nThe converted program is printed to the console, unless an output file is specified."
+    )]
+    Export(ExportArgs),
+
+    #[command(
+        about = "Explain an error code",
+        long_about = "Print an extended explanation of an error code, with an example and common fixes.\nThe code is displayed with the error, e.g. E311. If no code is given, all error codes are listed."
+    )]
+    Explain(ExplainArgs),
+
+    #[command(
+        about = "Check whether a newer version of alpha_tui exists",
+        long_about = "Check whether a newer version of alpha_tui exists with \"--check\".\nThe latest release is queried from the GitHub api, alpha_tui never checks for updates on its own. If the network can not be accessed or \"--offline\" is set, the installed version is printed instead."
+    )]
+    Update(UpdateArgs),
+
+    #[command(
+        name = "bench-internal",
+        hide = true,
+        about = "Run the benchmarks of the runtime",
+        long_about = "Run the benchmarks of the runtime, used to check that changes to the runtime don't make it slower.\nThe benchmarks are measured with criterion, if alpha_tui is built with the \"bench\" feature."
+    )]
+    BenchInternal(BenchArgs),
 }
 
 #[derive(Args, Debug, Clone, Default)]
@@ -223,7 +953,7 @@ pub struct InstructionLimitingArgs {
     #[arg(
         long,
         help = "Set allowed comparisons",
-        long_help = "Set allowed comparisons. If set, comparisons not listed here will not be allowed.\nIf they are used anyway, they will lead to a build_program_error.",
+        long_help = "Set allowed comparisons. If set, comparisons not listed here will not be allowed.\nIf they are used anyway, they will lead to a build error (E100).",
         value_delimiter = ',',
         global = true,
         display_order = 10
@@ -242,32 +972,105 @@ pub struct InstructionLimitingArgs {
     #[arg(
         long,
         help = "Set allowed operations",
-        long_help = "Set allowed operations. If set, operations not listed here will be allowed.\nIf they are used anyway, they will lead to a build_program_error.",
+        long_help = "Set allowed operations. If set, operations not listed here will be allowed.\nIf they are used anyway, they will lead to a build error (E100).",
         value_delimiter = ',',
         global = true,
         display_order = 11
     )]
     pub allowed_operations: Option<Vec<Operation>>,
 
-    #[arg(
-        long,
-        help = "Disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection",
-        long_help = "Set to disable accumulator, gamma accumulator, memory_cell and index_memory_cell detection.\nIf disabled, accumulators, gamma accumulator, memory cells and index memory cells won't be read from program and cannot be added by using them at runtime.\nInstead they have to be specified using \"--accumulators\", \"--enable-gamma-accumulator\", \"--memory-cells\" and \"--index-memory-cells\" or \"--memory-config-file\"",
-        global = true,
-        display_order = 25
+    /// Instructions that are allowed in addition to the instructions of the allowed instructions file, in the format
+    /// of the allowed instructions file.
+    ///
+    /// Is set by presets.
+    #[arg(skip)]
+    pub allowed_instructions: Option<Vec<String>>,
+
+    /// If set, `while` and `repeat` loops are not allowed.
+    ///
+    /// Is set by presets.
+    #[arg(skip)]
+    pub deny_structured_loops: bool,
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum BundleCommand {
+    #[command(
+        about = "Verify a submission bundle",
+        long_about = "Verify a submission bundle.\nThe hashes of all files in the bundle are checked and the program is run again with the memory config and the settings of the bundle, the result has to match the final memory and run summary in the bundle.\nReturn values:\n\n 0 - The bundle was verified\n 1 - The bundle could not be verified"
     )]
-    pub disable_memory_detection: bool,
+    Verify {
+        #[arg(long_help = "The bundle that should be verified", required = true)]
+        archive: String,
 
-    #[arg(
-        short = 'g',
-        long,
-        help = "Enable the gamma accumulator",
-        long_help = "Enable the gamma accumulator, can be used to enable gamma accumulator when automatic detection is disabled by \"--disable-memory-detection\".",
-        conflicts_with = "memory_config_file",
-        global = true,
-        display_order = 21
+        #[arg(
+            long,
+            help = "Content hash that the bundle is expected to have",
+            long_help = "Content hash that the bundle is expected to have, for example the hash that was printed when the bundle was created"
+        )]
+        content_hash: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Clone, Debug)]
+pub enum RefactorCommand {
+    #[command(
+        about = "Rename a memory cell or a label",
+        long_about = "Rename a memory cell or a label in the whole program.\nThe occurrences are located using the parsed instructions, so only memory cells or labels with that name are renamed.\nMemory cells can be written as 'p(name)' or 'name', if a name is used as memory cell and as label, use '--memory-cell' or '--label' to specify what should be renamed."
     )]
-    pub enable_gamma_accumulator: bool,
+    Rename {
+        #[arg(
+            long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+            required = true
+        )]
+        file: String,
+
+        #[arg(long_help = "The memory cell or label that should be renamed")]
+        old: String,
+
+        #[arg(long_help = "The new name of the memory cell or label")]
+        new: String,
+
+        #[arg(long, help = "Rename a memory cell", conflicts_with = "label")]
+        memory_cell: bool,
+
+        #[arg(long, help = "Rename a label")]
+        label: bool,
+    },
+    #[command(
+        about = "Extract lines into a subroutine",
+        long_about = "Move the lines from START to END (inclusive) into a new subroutine that is placed at the end of the program.\nThe lines are replaced with a call of the subroutine and a return is added to the subroutine.\nThe refactoring is only applied if all jumps still reach the same instructions afterwards."
+    )]
+    Extract {
+        #[arg(
+            long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+            required = true
+        )]
+        file: String,
+
+        #[arg(long_help = "Number of the first line that should be moved into the subroutine")]
+        start: usize,
+
+        #[arg(long_help = "Number of the last line that should be moved into the subroutine")]
+        end: usize,
+
+        #[arg(long_help = "Label of the new subroutine")]
+        label: String,
+    },
+    #[command(
+        about = "Write memory cells and the gamma accumulator in the same notation",
+        long_about = "Write all memory cells and gamma accumulators in the same notation, e.g. 'p(h1)' instead of 'ρ(h1)' and 'y' instead of 'γ'.\nIf no notation is specified, each is written in the notation that is used most in the program.\nThis fixes the warnings of the 'mixed_notation' lint."
+    )]
+    Notation {
+        #[arg(
+            long_help = "Specify the input file that contains the program, use - to read the program from stdin",
+            required = true
+        )]
+        file: String,
+
+        #[arg(long, help = "Notation that should be used")]
+        to: Option<IdentifierNotation>,
+    },
 }
 
 #[derive(Subcommand, Clone, Debug)]
@@ -275,7 +1078,25 @@ pub enum CheckCommand {
     #[command(about = "Check if the program compiles")]
     Compile,
     #[command(about = "Check if the program can be run")]
-    Run,
+    Run {
+        #[arg(
+            long,
+            help = "Print an event as JSON for every executed instruction",
+            long_help = "Print an event as JSON for every executed instruction, one object per line (NDJSON).\nEach event contains the line and the instruction that was executed, the memory values that were written, the values that were pushed onto or popped from the stack and the line that was jumped to.\nAll other output is printed to stderr, so that stdout only contains the events.\n\nFor more help see https://github.com/LMH01/alpha_tui/blob/master/docs/interface_and_usage.md"
+        )]
+        emit_events: bool,
+        #[arg(
+            long,
+            help = "Skip instructions that cause a runtime error and report all errors",
+            long_help = "Skip instructions that cause a runtime error and continue with the next instruction.\nAll errors that occurred are reported when the program has finished, so that multiple problems can be found in one run.\nExecution still stops, if the call stack overflows or if the maximum number of instruction runs is reached."
+        )]
+        keep_going: bool,
+    },
+    #[command(
+        about = "Report which memory locations are declared and used",
+        long_about = "Report which accumulators and memory cells are declared (with cli arguments or a memory config file) but never used by the program and which are used but not declared, these are created by autodetection.\nThe program is not run, so index memory cells are only included, if their index is written directly in the program (e.g. p(3))."
+    )]
+    Memory,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -283,10 +1104,47 @@ pub trait CliHint {
     fn cli_hint(&self) -> String;
 }
 
+/// Returns the action of the command for which the program can't be read from stdin, if the program should be read
+/// from stdin.
+pub fn stdin_unsupported(command: &Command) -> Option<&'static str> {
+    // the program file is needed to watch for changes and to write the changes back
+    match command {
+        Command::Check(check_args) if check_args.watch && check_args.file == "-" => {
+            Some("watching for changes")
+        }
+        Command::Refactor(RefactorArgs {
+            command:
+                RefactorCommand::Rename { file, .. }
+                | RefactorCommand::Extract { file, .. }
+                | RefactorCommand::Notation { file, .. },
+        }) if file == "-" => Some("refactoring"),
+        _ => None,
+    }
+}
+
 /// Validates if the provided arguments are allowed.
 ///
 /// This function is used to test some additional requirements, that can't be programmed into clap.
 pub fn validate_arguments(cli: &Cli) -> Result<()> {
+    if let Some(action) = stdin_unsupported(&cli.command) {
+        return Err(CliError::new(CliErrorType::StdinNotSupported(action.to_string())).into());
+    }
+    if let Command::Check(check_args) = &cli.command {
+        let lints = check_args
+            .allow
+            .iter()
+            .chain(&check_args.warn)
+            .chain(&check_args.deny);
+        for lint in lints {
+            if !LINT_NAMES.contains(&lint.as_str()) {
+                return Err(CliError::new(CliErrorType::UnknownLint(
+                    lint.clone(),
+                    LINT_NAMES.join(", "),
+                ))
+                .into());
+            }
+        }
+    }
     let memory_cells = match cli.global_args.memory_config_file.clone() {
         Some(path) => Some(
             MemoryConfig::try_from_file(&path)?
@@ -297,9 +1155,29 @@ pub fn validate_arguments(cli: &Cli) -> Result<()> {
                 .collect::<Vec<String>>(),
         ),
         None => match &cli.command {
-            Command::Check(check_args) => check_args.check_load_args.memory_cells.to_owned(),
-            Command::Load(load_args) => load_args.check_load_args.memory_cells.to_owned(),
-            Command::Playground(_) => return Ok(()),
+            Command::Check(check_args) => check_args
+                .check_load_args
+                .memory_args
+                .memory_cells
+                .to_owned(),
+            Command::Load(load_args) => load_args
+                .check_load_args
+                .memory_args
+                .memory_cells
+                .to_owned(),
+            Command::Playground(playground_args) => {
+                playground_args.memory_args.memory_cells.to_owned()
+            }
+            Command::Test(_)
+            | Command::Grade(_)
+            | Command::Bundle(_)
+            | Command::Refactor(_)
+            | Command::Follow(_)
+            | Command::Import(_)
+            | Command::Export(_)
+            | Command::Explain(_)
+            | Command::Update(_)
+            | Command::BenchInternal(_) => return Ok(()),
         },
     };
     if let Some(memory_cells) = &memory_cells {
@@ -321,7 +1199,7 @@ pub struct CliError {
 }
 
 impl CliError {
-    fn new(reason: CliErrorType) -> Self {
+    pub fn new(reason: CliErrorType) -> Self {
         Self { reason }
     }
 }
@@ -332,4 +1210,34 @@ pub enum CliErrorType {
     #[error("memory cell found that has a name consisting of only numbers: {0}")]
     #[diagnostic(code("cli::memory_cells_invalid"), help("Try adding a char: a{0}"))]
     MemoryCellsInvalid(String),
+
+    #[error("unknown preset: {0}")]
+    #[diagnostic(code("cli::unknown_preset"), help("Available presets: {1}"))]
+    UnknownPreset(String, String),
+
+    #[error("presets extend each other: {0}")]
+    #[diagnostic(
+        code("cli::preset_cycle"),
+        help("Remove one of the \"extends\" fields of these presets")
+    )]
+    PresetCycle(String),
+
+    #[error("config file {0} is invalid: {1}")]
+    #[diagnostic(code("cli::config_file_invalid"))]
+    ConfigFileInvalid(String, String),
+
+    #[error("unknown lint: {0}")]
+    #[diagnostic(code("cli::unknown_lint"), help("Available lints: {1}"))]
+    UnknownLint(String, String),
+
+    #[error("directive in line {0} is invalid: {1}")]
+    #[diagnostic(
+        code("cli::directive_invalid"),
+        help("Directives are written as //! <key>: <value> in the first lines of the program")
+    )]
+    DirectiveInvalid(usize, String),
+
+    #[error("the program can't be read from stdin when {0}")]
+    #[diagnostic(code("cli::stdin_not_supported"), help("Save the program into a file"))]
+    StdinNotSupported(String),
 }
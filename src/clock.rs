@@ -0,0 +1,148 @@
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Source of the current time.
+///
+/// All parts of the app that measure time or wait for some time read it from a clock, instead of calling
+/// `Instant::now()` directly, so that tests can control the time with a manual clock.
+pub trait Clock: Debug + Send + Sync {
+    /// Returns the current point in time, used to measure durations.
+    fn now(&self) -> Instant;
+
+    /// Returns the current wall clock time, used for timestamps.
+    fn system_time(&self) -> SystemTime;
+}
+
+/// Clock that returns the time of the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that can be shared between the parts of the app, the system clock is used by default.
+#[derive(Debug, Clone)]
+pub struct SharedClock(Arc<dyn Clock>);
+
+impl SharedClock {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self(Arc::new(clock))
+    }
+
+    pub fn now(&self) -> Instant {
+        self.0.now()
+    }
+
+    /// Returns the time that passed since `earlier`, zero if `earlier` is in the future.
+    pub fn elapsed(&self, earlier: Instant) -> Duration {
+        self.now().saturating_duration_since(earlier)
+    }
+
+    /// Returns the current time in seconds since the unix epoch.
+    pub fn unix_time(&self) -> u64 {
+        self.0
+            .system_time()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs())
+    }
+}
+
+/// Shares a clock that is still used elsewhere, e.g. a manual clock that is advanced by a test.
+impl<C: Clock + 'static> From<Arc<C>> for SharedClock {
+    fn from(clock: Arc<C>) -> Self {
+        Self(clock)
+    }
+}
+
+impl Default for SharedClock {
+    fn default() -> Self {
+        Self::new(SystemClock)
+    }
+}
+
+/// The clock is not part of the state that is compared, e.g. two runtimes are equal regardless of their clocks.
+impl PartialEq for SharedClock {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Clock whose time only changes when it is advanced, used in tests.
+///
+/// The wall clock time starts at the unix epoch.
+#[cfg(test)]
+#[derive(Debug)]
+pub struct ManualClock {
+    start: Instant,
+    elapsed: std::sync::Mutex<Duration>,
+    /// Time by which the clock is advanced each time it is read.
+    tick: Duration,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> Self {
+        Self::with_tick(Duration::ZERO)
+    }
+
+    /// Creates a clock that advances by `tick` each time the current point in time is read.
+    pub fn with_tick(tick: Duration) -> Self {
+        Self {
+            start: Instant::now(),
+            elapsed: std::sync::Mutex::new(Duration::ZERO),
+            tick,
+        }
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        let mut elapsed = self.elapsed.lock().unwrap();
+        *elapsed += self.tick;
+        self.start + *elapsed
+    }
+
+    fn system_time(&self) -> SystemTime {
+        UNIX_EPOCH + *self.elapsed.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::{ManualClock, SharedClock};
+
+    #[test]
+    fn test_manual_clock() {
+        let manual = Arc::new(ManualClock::new());
+        let clock = SharedClock::from(Arc::clone(&manual));
+        let start = clock.now();
+        assert_eq!(clock.elapsed(start), Duration::ZERO);
+        manual.advance(Duration::from_secs(90));
+        assert_eq!(clock.elapsed(start), Duration::from_secs(90));
+        assert_eq!(clock.unix_time(), 90);
+    }
+
+    #[test]
+    fn test_manual_clock_tick() {
+        let clock = SharedClock::new(ManualClock::with_tick(Duration::from_micros(5)));
+        let start = clock.now();
+        assert_eq!(clock.elapsed(start), Duration::from_micros(5));
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+
+use crate::{
+    cli::{CliError, CliErrorType},
+    preset::Preset,
+    runtime::EndBehavior,
+    utils,
+};
+
+/// Prefix of the comment lines that contain directives.
+const DIRECTIVE_PREFIX: &str = "//!";
+
+/// Reads the directives in the header of a program, e.g. `//! accumulators: 4`.
+///
+/// The header consists of the comment and empty lines at the beginning of the program, directives in later lines are
+/// ignored. The directives are returned as preset, so that options that are set on the command line take precedence.
+/// Relative paths are resolved relative to the location of the program `file`.
+pub fn parse(lines: &[String], file: &str) -> Result<Preset, CliError> {
+    let mut preset = Preset::default();
+    for (idx, line) in lines.iter().enumerate() {
+        let line = line.trim();
+        if let Some(directive) = line.strip_prefix(DIRECTIVE_PREFIX) {
+            apply(&mut preset, directive, file)
+                .map_err(|reason| CliError::new(CliErrorType::DirectiveInvalid(idx + 1, reason)))?;
+        } else if !line.is_empty() && !utils::remove_comment(line).is_empty() {
+            break;
+        }
+    }
+    Ok(preset)
+}
+
+/// Sets the option of the directive `<key>: <value>` in the preset.
+fn apply(preset: &mut Preset, directive: &str, file: &str) -> Result<(), String> {
+    let Some((key, value)) = directive.split_once(':') else {
+        return Err(format!(
+            "'{}' is not of the form <key>: <value>, e.g. accumulators: 4",
+            directive.trim()
+        ));
+    };
+    let value = value.trim();
+    match key.trim() {
+        "accumulators" => preset.accumulators = Some(parse_value(value)?),
+        "gamma" => preset.enable_gamma_accumulator = Some(parse_value(value)?),
+        "memory_cells" => preset.memory_cells = Some(parse_list(value)?),
+        "index_memory_cells" => preset.index_memory_cells = Some(parse_list(value)?),
+        "memory_config" => preset.memory_config_file = Some(resolve_path(value, file)),
+        "allowed" => preset.allowed_instructions_file = Some(resolve_path(value, file)),
+        "disable_memory_detection" => preset.disable_memory_detection = Some(parse_value(value)?),
+        "deny_structured_loops" => preset.deny_structured_loops = Some(parse_value(value)?),
+        "skip_empty_lines" => preset.skip_empty_lines = Some(parse_value(value)?),
+        "deny_labels_on_empty_lines" => {
+            preset.deny_labels_on_empty_lines = Some(parse_value(value)?);
+        }
+        "end_behavior" => {
+            preset.end_behavior = Some(
+                EndBehavior::from_str(value, true)
+                    .map_err(|_| format!("'{value}' is not a valid value"))?,
+            );
+        }
+        "case_insensitive_labels" => preset.case_insensitive_labels = Some(parse_value(value)?),
+        "instruction_limit" => preset.instruction_limit = Some(parse_value(value)?),
+        "seed" => preset.seed = Some(parse_value(value)?),
+        key => return Err(format!("unknown directive '{key}'")),
+    }
+    Ok(())
+}
+
+/// Parses a single value of a directive.
+fn parse_value<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("'{value}' is not a valid value"))
+}
+
+/// Parses a comma separated list of values of a directive.
+fn parse_list<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(|v| parse_value(v.trim())).collect()
+}
+
+/// Resolves `path` relative to the location of the program `file`.
+///
+/// Absolute paths and urls are not changed, for programs that were downloaded the path is resolved relative to their
+/// url.
+fn resolve_path(path: &str, file: &str) -> String {
+    if utils::is_url(path) || Path::new(path).is_absolute() {
+        return path.to_string();
+    }
+    if utils::is_url(file) {
+        return match file.rsplit_once('/') {
+            Some((base, _)) => format!("{base}/{path}"),
+            None => path.to_string(),
+        };
+    }
+    match Path::new(file).parent() {
+        Some(parent) if file != utils::STDIN_FILENAME => {
+            parent.join(path).to_string_lossy().to_string()
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{runtime::EndBehavior, utils::test_utils};
+
+    use super::{parse, resolve_path};
+
+    #[test]
+    fn test_parse() {
+        let preset = parse(
+            &test_utils::string_literal_to_vec(
+                "// exercise 1\n//! accumulators: 2\n//! memory_cells: a, b\n\n//! allowed: allowed.json\na := 1\n//! seed: 5",
+            ),
+            "exercises/ex1.alpha",
+        )
+        .unwrap();
+        assert_eq!(preset.accumulators, Some(2));
+        assert_eq!(
+            preset.memory_cells,
+            Some(vec!["a".to_string(), "b".to_string()])
+        );
+        assert_eq!(
+            preset.allowed_instructions_file,
+            Some("exercises/allowed.json".to_string())
+        );
+        // directives after the header are ignored
+        assert_eq!(preset.seed, None);
+        assert!(parse(
+            &test_utils::string_literal_to_vec("//! limit: 5"),
+            "a.alpha"
+        )
+        .is_err());
+        assert!(parse(
+            &test_utils::string_literal_to_vec("//! accumulators: a"),
+            "a.alpha"
+        )
+        .is_err());
+        assert!(parse(&test_utils::string_literal_to_vec("//! gamma"), "a.alpha").is_err());
+    }
+
+    #[test]
+    fn test_parse_end_behavior() {
+        let preset = parse(
+            &test_utils::string_literal_to_vec("//! end_behavior: halt"),
+            "a.alpha",
+        )
+        .unwrap();
+        assert_eq!(preset.end_behavior, Some(EndBehavior::Halt));
+        assert!(parse(
+            &test_utils::string_literal_to_vec("//! end_behavior: stop"),
+            "a.alpha"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_resolve_path() {
+        assert_eq!(resolve_path("mem.json", "program.alpha"), "mem.json");
+        assert_eq!(
+            resolve_path("/tmp/mem.json", "ex/program.alpha"),
+            "/tmp/mem.json"
+        );
+        assert_eq!(
+            resolve_path("mem.json", "https://example.com/ex/program.alpha"),
+            "https://example.com/ex/mem.json"
+        );
+        assert_eq!(resolve_path("mem.json", "<stdin>"), "mem.json");
+    }
+}
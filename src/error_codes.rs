@@ -0,0 +1,1065 @@
+use miette::Diagnostic;
+
+/// Stable code of an error, the code is displayed with the error and the explanation is printed by
+/// `alpha_tui explain <CODE>`.
+///
+/// The codes are grouped by the stage in which the error occurs:
+/// - `E0xx`: the instruction could not be parsed
+/// - `E1xx`: the program could not be built
+/// - `E2xx`: the memory that the program uses is not available
+/// - `E3xx`: runtime errors
+/// - `E4xx`: lint warnings
+/// - `E5xx`: the program could not be refactored
+/// - `E6xx`: the program could not be imported
+///
+/// Codes are never reused or changed, so that they can be looked up in older versions of the documentation.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ErrorCode {
+    pub code: &'static str,
+    pub title: &'static str,
+    /// Extended explanation with an example and common fixes.
+    pub explanation: &'static str,
+}
+
+pub const ERROR_CODES: &[ErrorCode] = &[
+    ErrorCode {
+        code: "E001",
+        title: "Unknown operation",
+        explanation: "A calculation uses an operator that does not exist.
+
+Example:
+    a := p(h1) x p(h2)
+
+The supported operations are + - * / % min max, × and ÷ can be used instead of * and /.
+min, max and abs can also be written as functions, e.g. a := min(a, 5) or a := abs(p(h1)).
+
+Common fixes:
+- Use * to multiply, instead of x.
+- Separate the operator from the values with whitespace.",
+    },
+    ErrorCode {
+        code: "E002",
+        title: "Unknown comparison",
+        explanation: "A condition uses a comparison that does not exist.
+
+Example:
+    if a <> 5 then goto end
+
+The supported comparisons are < <= == != >= >.
+These can also be used: =< and => for <= and >=, = for ==, and ≤ ≠ ≥.
+
+Common fixes:
+- Use != to check that two values are not equal.
+- Separate the comparison from the values with whitespace.",
+    },
+    ErrorCode {
+        code: "E003",
+        title: "Not a number",
+        explanation: "The index of an accumulator or an index memory cell is not a number.
+
+Example:
+    if ax < 5 then goto loop
+
+Common fixes:
+- Accumulators are written as a, a0, a1, ...
+- Memory cells with names are written as p(name), e.g. p(x) instead of ax.",
+    },
+    ErrorCode {
+        code: "E004",
+        title: "Invalid expression",
+        explanation: "A part of the instruction is neither a value nor a keyword.
+
+Example:
+    if a < 3 than goto loop
+
+Common fixes:
+- Check the spelling of the keywords, e.g. then, goto and call.
+- Memory cells are written as p(name), accumulators as a0, a1, ... and the gamma accumulator as y.
+- Constants are whole numbers, e.g. 5 or -3.",
+    },
+    ErrorCode {
+        code: "E005",
+        title: "Unknown instruction",
+        explanation: "The line does not match any instruction.
+
+Example:
+    a := p(h1) + p(h2) + p(h3)
+
+An instruction contains at most one operation, so the sum has to be calculated in two steps:
+    a := p(h1) + p(h2)
+    a := a + p(h3)
+
+Common fixes:
+- Split calculations with several operations into several instructions.
+- Remove text after the instruction, comments start with # or //.
+- The supported instructions are listed in docs/instructions.md.",
+    },
+    ErrorCode {
+        code: "E006",
+        title: "Missing expression",
+        explanation: "The instruction ends before it is complete.
+
+Example:
+    a := p(h1) +
+
+Common fixes:
+- Add the missing value after the operator or after :=.
+- Put whitespace before and after :=, e.g. a := 5 instead of a:=5.",
+    },
+    ErrorCode {
+        code: "E007",
+        title: "Invalid instruction",
+        explanation: "An instruction that was entered, e.g. as custom instruction in the tui, can not be parsed. The
+cause is displayed below the error.
+
+Common fix:
+- Run `alpha_tui explain` with the code of the cause, it explains how the instruction can be fixed.",
+    },
+    ErrorCode {
+        code: "E100",
+        title: "Program can not be built",
+        explanation: "The program could not be built. The cause, e.g. an instruction that can not be parsed or a label
+that is defined twice, is displayed below the error.
+
+Common fix:
+- Run `alpha_tui explain` with the code of the cause, it explains how the program can be fixed.",
+    },
+    ErrorCode {
+        code: "E101",
+        title: "Label defined multiple times",
+        explanation: "The same label is placed in front of more than one instruction, so it is not clear where a jump to
+the label should continue.
+
+Example:
+    loop: a := a + 1
+    loop: if a < 10 then goto loop
+
+Common fixes:
+- Rename one of the labels and update the jumps to it.
+- Remove the label that is not needed.",
+    },
+    ErrorCode {
+        code: "E102",
+        title: "Main label defined multiple times",
+        explanation: "The program contains both the label main and the label MAIN. Both mark the instruction at which
+the program starts, so only one of them may be used.
+
+Example:
+    main: call f
+    MAIN: a := 1
+
+Common fix:
+- Remove or rename one of the labels.",
+    },
+    ErrorCode {
+        code: "E103",
+        title: "Instruction not allowed",
+        explanation: "The instruction is not contained in the allowed instructions, which are set with
+--allowed-instructions-file or by a preset.
+
+Example:
+    p(h1) := 20
+is not allowed if the allowed instructions file only contains
+    A := C
+
+Common fixes:
+- Rewrite the instruction with allowed instructions, e.g. assign the constant to an accumulator first.
+- If you wrote the allowed instructions file, add the type of the instruction (e.g. M := C) to it.",
+    },
+    ErrorCode {
+        code: "E104",
+        title: "Comparison not allowed",
+        explanation: "The comparison is not contained in the allowed comparisons, which are set with
+--allowed-comparisons.
+
+Example:
+    if a == 1 then goto end
+is not allowed with --allowed-comparisons lt
+
+Common fixes:
+- Express the condition with an allowed comparison, e.g. swap the values or negate the condition.
+- Allow the comparison, e.g. with --allowed-comparisons \"lt,eq\".",
+    },
+    ErrorCode {
+        code: "E105",
+        title: "Operation not allowed",
+        explanation: "The operation is not contained in the allowed operations, which are set with
+--allowed-operations.
+
+Example:
+    a := a - 1
+is not allowed with --allowed-operations add
+
+Common fixes:
+- Express the calculation with an allowed operation, e.g. a := a + -1.
+- Allow the operation, e.g. with --allowed-operations \"add,sub\".",
+    },
+    ErrorCode {
+        code: "E106",
+        title: "Violations of the allowed instructions",
+        explanation: "Several instructions, comparisons or operations of the program are not allowed. The violations
+are listed by line.
+
+Common fixes:
+- Rewrite the listed lines with allowed instructions, comparisons and operations.
+- Add the missing instruction types to the allowed instructions file, comparisons and operations can also be
+  allowed with --allowed-comparisons and --allowed-operations.",
+    },
+    ErrorCode {
+        code: "E107",
+        title: "Loop not closed",
+        explanation: "A while or repeat loop is opened, but never closed.
+
+Example:
+    while a < 10 do
+        a := a + 1
+
+Common fixes:
+- Close while loops with end.
+- Close repeat loops with until <condition>.",
+    },
+    ErrorCode {
+        code: "E108",
+        title: "Unexpected end of loop",
+        explanation: "An end or until closes a loop that was never opened.
+
+Example:
+    a := a + 1
+    end
+
+Common fixes:
+- Remove the end or until.
+- Make sure that every end belongs to a while loop and every until to a repeat loop.",
+    },
+    ErrorCode {
+        code: "E109",
+        title: "Structured loop not allowed",
+        explanation: "while and repeat loops are not part of the official instruction set and are not allowed by the
+selected preset, e.g. the strict preset.
+
+Example:
+    while a < 10 do
+
+Common fix:
+- Write the loop with labels and jumps:
+    loop: if a >= 10 then goto loop_end
+        a := a + 1
+        goto loop
+    loop_end:",
+    },
+    ErrorCode {
+        code: "E110",
+        title: "Label on empty line",
+        explanation: "A label is placed on a line without instruction, which is not allowed when
+--deny-labels-on-empty-lines is set.
+
+Example:
+    loop:
+    a := a + 1
+
+Common fix:
+- Place the label in the same line as the instruction it should jump to:
+    loop: a := a + 1",
+    },
+    ErrorCode {
+        code: "E111",
+        title: "Invalid variable declaration",
+        explanation: "A variable declaration can not be read, e.g. because the memory location is invalid or the
+name is used twice.
+
+Example:
+    #var sum = h1
+
+Common fixes:
+- Declare variables like this: #var sum = p(h1)
+- Use every name only once.",
+    },
+    ErrorCode {
+        code: "E112",
+        title: "Instruction can't be executed conditionally",
+        explanation: "Only instructions that don't change the control flow can be used in if ... then <instruction>.
+Jumps, calls and returns are not allowed there.
+
+Example:
+    if a > 0 then return
+
+Common fix:
+- Use a jump to a label instead:
+    if a <= 0 then goto skip
+    return
+    skip:",
+    },
+    ErrorCode {
+        code: "E113",
+        title: "Instruction can't be written in alpha notation",
+        explanation: "The instruction would be read differently when the program is written as text, this usually
+happens with imported programs.
+
+Example:
+    a memory cell named a1 would be written as p(a1), but a1 is read as accumulator 1
+
+Common fixes:
+- Don't use the names of accumulators (e.g. a1) or of the gamma accumulator (y) as names of memory cells.
+- Don't use whitespace in labels.",
+    },
+    ErrorCode {
+        code: "E114",
+        title: "Invalid program file",
+        explanation: "The program can't be read from the JSON file, the file is damaged or was not written by
+alpha_tui.
+
+Common fix:
+- Write the file again with check --emit.",
+    },
+    ErrorCode {
+        code: "E115",
+        title: "Instruction of the program can not be parsed",
+        explanation: "A line of the program can not be parsed. The line is marked and the cause is displayed below the
+error.
+
+Common fix:
+- Run `alpha_tui explain` with the code of the cause, it explains how the instruction can be fixed.",
+    },
+    ErrorCode {
+        code: "E116",
+        title: "Invalid allowed instructions file",
+        explanation: "An instruction in the file that is set with --allowed-instructions-file can not be parsed.
+
+Example:
+    \"instructions\": [\"A := C\", \"A := B\"]
+B is not a token.
+
+Common fixes:
+- Write the instructions with the tokens A (accumulator), M (memory cell), C (constant), Y (gamma accumulator),
+  OP (operation) and CMP (comparison).
+- See docs/cli.md for an example.",
+    },
+    ErrorCode {
+        code: "E201",
+        title: "Label undefined",
+        explanation: "A jump or call uses a label that is not defined anywhere in the program.
+
+Example:
+    goto loop
+
+Common fixes:
+- Define the label in front of the instruction the jump should continue at, e.g. loop: a := a + 1
+- Check the spelling, labels are case sensitive.",
+    },
+    ErrorCode {
+        code: "E202",
+        title: "Memory cell missing",
+        explanation: "The program uses a memory cell that is not available. This happens when memory detection is
+disabled, e.g. by the strict preset.
+
+Example:
+    p(h1) := 10
+with --disable-memory-detection
+
+Common fixes:
+- Make the memory cell available, e.g. with --memory-cells h1 or in a memory config file.
+- Use a memory cell that is available.",
+    },
+    ErrorCode {
+        code: "E203",
+        title: "Accumulator missing",
+        explanation: "The program uses an accumulator that is not available. This happens when memory detection is
+disabled, e.g. by the strict preset.
+
+Example:
+    a2 := 10
+with --disable-memory-detection --accumulators 2
+
+Common fix:
+- Set the number of accumulators to at least the highest index plus one, e.g. --accumulators 3.",
+    },
+    ErrorCode {
+        code: "E204",
+        title: "Gamma accumulator disabled",
+        explanation: "The program uses the gamma accumulator y, but it is disabled.
+
+Example:
+    y := 10
+with --disable-memory-detection
+
+Common fix:
+- Enable the gamma accumulator with --enable-gamma-accumulator or enable memory detection.",
+    },
+    ErrorCode {
+        code: "E205",
+        title: "Index memory cell missing",
+        explanation: "The program uses an index memory cell that is not available. This happens when memory
+detection is disabled, e.g. by the strict preset.
+
+Example:
+    p(5) := 10
+with --disable-memory-detection
+
+Common fix:
+- Make the index memory cell available, e.g. with --index-memory-cells 5.",
+    },
+    ErrorCode {
+        code: "E206",
+        title: "Invalid memory config file",
+        explanation: "The memory config file can't be read.
+
+Common fix:
+- Make sure that the file is valid JSON, see examples/memory_config.json for an example.",
+    },
+    ErrorCode {
+        code: "E301",
+        title: "Accumulator uninitialized",
+        explanation: "The value of an accumulator is used before a value was assigned to it.
+
+Example:
+    p(h1) := a
+
+Common fixes:
+- Assign a value to the accumulator first, e.g. a := 0
+- Give the accumulator a start value in a memory config file.",
+    },
+    ErrorCode {
+        code: "E302",
+        title: "Accumulator does not exist",
+        explanation: "An accumulator is used that does not exist, e.g. in an instruction that is run in the tui.
+
+Common fix:
+- Make the accumulator available with --accumulators or in a memory config file.",
+    },
+    ErrorCode {
+        code: "E303",
+        title: "Gamma accumulator uninitialized",
+        explanation: "The value of the gamma accumulator is used before a value was assigned to it.
+
+Example:
+    a := y
+
+Common fix:
+- Assign a value to the gamma accumulator first, e.g. y := 0",
+    },
+    ErrorCode {
+        code: "E304",
+        title: "Gamma accumulator does not exist",
+        explanation: "The gamma accumulator is used, but it is disabled.
+
+Common fix:
+- Enable it with --enable-gamma-accumulator or in a memory config file.",
+    },
+    ErrorCode {
+        code: "E305",
+        title: "Memory cell uninitialized",
+        explanation: "The value of a memory cell is used before a value was assigned to it.
+
+Example:
+    a := p(h1) + 1
+
+Common fixes:
+- Assign a value to the memory cell first, e.g. p(h1) := 0
+- Give the memory cell a start value in a memory config file.",
+    },
+    ErrorCode {
+        code: "E306",
+        title: "Memory cell does not exist",
+        explanation: "A memory cell is used that does not exist, e.g. in an instruction that is run in the tui.
+
+Common fix:
+- Make the memory cell available with --memory-cells or in a memory config file.",
+    },
+    ErrorCode {
+        code: "E307",
+        title: "Index memory cell uninitialized",
+        explanation: "The value of an index memory cell is used before a value was assigned to it.
+
+Example:
+    a := p(3)
+
+Common fixes:
+- Assign a value to the index memory cell first, e.g. p(3) := 0
+- When the index is calculated, e.g. p(p(h1)), check that the index is the intended one.",
+    },
+    ErrorCode {
+        code: "E308",
+        title: "Index memory cell does not exist",
+        explanation: "An index memory cell is used that does not exist.
+
+Common fix:
+- Make the index memory cell available with --index-memory-cells or in a memory config file.",
+    },
+    ErrorCode {
+        code: "E309",
+        title: "Negative index",
+        explanation: "An index memory cell is accessed with a negative index.
+
+Example:
+    p(h1) := -1
+    a := p(p(h1))
+
+Common fix:
+- Make sure that the value that is used as index is zero or larger.",
+    },
+    ErrorCode {
+        code: "E310",
+        title: "Push of uninitialized accumulator",
+        explanation: "push puts the value of a0 onto the stack, but a0 has no value.
+
+Example:
+    push
+
+Common fix:
+- Assign a value to a0 before push, e.g. a0 := 5",
+    },
+    ErrorCode {
+        code: "E311",
+        title: "Pop from empty stack",
+        explanation: "pop takes a value from the stack, but the stack is empty.
+
+Example:
+    a := 1
+    pop
+
+Common fixes:
+- Make sure that every pop has a matching push.
+- Check the stack in the tui while the program runs step by step.",
+    },
+    ErrorCode {
+        code: "E312",
+        title: "Stack operation on short stack",
+        explanation: "A stack operation, e.g. stack+, takes the two upper values of the stack, but the stack contains
+less than two values.
+
+Example:
+    a := 1
+    push
+    stack+
+
+Common fix:
+- Push two values before the stack operation.",
+    },
+    ErrorCode {
+        code: "E313",
+        title: "Stack overflow",
+        explanation: "Too many subroutines were called without returning, this is usually caused by an infinite
+recursion.
+
+Example:
+    f: call f
+
+Common fixes:
+- Make sure that every recursive subroutine has a condition under which it returns.
+- Make sure that every call is followed by a return in the called subroutine.",
+    },
+    ErrorCode {
+        code: "E314",
+        title: "Label missing",
+        explanation: "A jump or call goes to a label that does not exist, e.g. in an instruction that is run in the
+tui.
+
+Example:
+    goto loop
+
+Common fix:
+- Jump to a label that is defined in the program.",
+    },
+    ErrorCode {
+        code: "E315",
+        title: "Invalid range of random number",
+        explanation: "The first value of rand is larger than the second value.
+
+Example:
+    a := rand(6, 1)
+
+Common fix:
+- Swap the values, e.g. a := rand(1, 6)",
+    },
+    ErrorCode {
+        code: "E316",
+        title: "Instruction limit reached",
+        explanation: "The program ran more instructions than the instruction limit allows, this is usually caused by
+an infinite loop.
+
+Example:
+    loop: goto loop
+
+Common fixes:
+- Check the conditions of the loops, e.g. by running the program step by step.
+- Raise the limit with --instruction-limit or disable it with --disable-instruction-limit, if the program really
+  needs that many instructions.",
+    },
+    ErrorCode {
+        code: "E317",
+        title: "Division by zero",
+        explanation: "A value is divided by zero or the remainder of a division by zero is calculated.
+
+Example:
+    a1 := 0
+    a := a / a1
+
+Common fixes:
+- Check that the divisor is not zero before the division, e.g. if a1 == 0 then goto skip
+- In the tui, the divisor can be changed with a custom instruction and the division can be run again.",
+    },
+    ErrorCode {
+        code: "E318",
+        title: "Overflow",
+        explanation: "The result of a calculation is outside of the range of values, which is -2147483648 to
+2147483647.
+
+Example:
+    a := 2147483647
+    a := a + 1
+
+Common fix:
+- Make sure that the values stay in the range, e.g. by checking them before the calculation.",
+    },
+    ErrorCode {
+        code: "E319",
+        title: "Return with empty call stack",
+        explanation: "A return instruction is run outside of a function, while `--end-behavior error` is set.
+
+Example:
+    a := 1
+    return
+
+Common fixes:
+- End the program with goto END instead
+- Make sure that every function is entered with call
+- Use `--end-behavior jump-to-end` or `--end-behavior halt`, if your course material ends programs with return",
+    },
+    ErrorCode {
+        code: "E320",
+        title: "Illegal calculation",
+        explanation: "A calculation could not be performed. The cause, e.g. a division by zero (E317) or an overflow
+(E318), is displayed below the error.
+
+Common fix:
+- Run `alpha_tui explain` with the code of the cause, it explains how the calculation can be fixed.",
+    },
+    ErrorCode {
+        code: "E401",
+        title: "Pop from empty stack (lint)",
+        explanation: "The lint pop_from_empty_stack warns that pop or a stack operation can be run while the stack is
+empty, which would cause the runtime error E311.
+
+Example:
+    if a > 0 then goto skip
+    push
+    skip: pop
+
+Common fixes:
+- Push a value onto the stack on every path that leads to the line.
+- Suppress the warning with # alpha_tui: allow(pop_from_empty_stack), if the path can't be taken.",
+    },
+    ErrorCode {
+        code: "E402",
+        title: "Stack operation on short stack (lint)",
+        explanation: "The lint stack_op_on_short_stack warns that a stack operation, e.g. stack+, can be run while the
+stack contains less than two values, which would cause the runtime error E312.
+
+Example:
+    a := 1
+    push
+    stack+
+
+Common fixes:
+- Push two values onto the stack on every path that leads to the line.
+- Suppress the warning with # alpha_tui: allow(stack_op_on_short_stack), if the path can't be taken.",
+    },
+    ErrorCode {
+        code: "E403",
+        title: "Unbalanced subroutine (lint)",
+        explanation: "The lint unbalanced_subroutine warns that a subroutine leaves the stack with more or fewer values
+than it had when the subroutine was called.
+
+Example:
+    f: push
+    return
+
+Common fixes:
+- Pop every value that the subroutine pushes before it returns.
+- Suppress the warning with # alpha_tui: allow(unbalanced_subroutine), if the subroutine returns values on the stack.",
+    },
+    ErrorCode {
+        code: "E404",
+        title: "Mixed notation (lint)",
+        explanation: "The lint mixed_notation warns that memory cells or the gamma accumulator are written in ascii and
+in unicode notation in the same program.
+
+Example:
+    p(h1) := 1
+    ρ(h2) := 2
+
+Common fix:
+- Use the same notation in the whole program, alpha_tui refactor notation <FILE> changes it automatically.",
+    },
+    ErrorCode {
+        code: "E405",
+        title: "Labels only differ by case (lint)",
+        explanation: "The lint label_case warns that two labels only differ by case. Labels are case sensitive, so they
+are different labels.
+
+Example:
+    loop: a := a + 1
+    Loop: goto loop
+
+Common fixes:
+- Rename one of the labels.
+- Use --case-insensitive-labels, if the labels should be the same label.",
+    },
+    ErrorCode {
+        code: "E501",
+        title: "Name not found",
+        explanation: "The name that should be renamed is neither used as memory cell nor as label.
+
+Example:
+    alpha_tui refactor rename program.alpha hl sum
+while the program uses p(h1)
+
+Common fix:
+- Check the spelling of the name, names are case sensitive.",
+    },
+    ErrorCode {
+        code: "E502",
+        title: "Name not used",
+        explanation: "The memory cell or label that should be renamed is not used in the program.
+
+Example:
+    alpha_tui refactor rename program.alpha loop start --label
+while the program does not contain the label loop
+
+Common fixes:
+- Check the spelling of the name.
+- Check that --memory-cell or --label matches what should be renamed.",
+    },
+    ErrorCode {
+        code: "E503",
+        title: "Ambiguous name",
+        explanation: "The name that should be renamed is used as memory cell and as label, so it is not clear what
+should be renamed.
+
+Common fix:
+- Write the memory cell as p(name) or use --memory-cell or --label.",
+    },
+    ErrorCode {
+        code: "E504",
+        title: "Invalid name",
+        explanation: "The new name is not a valid name for a memory cell or a label.
+
+Example:
+    alpha_tui refactor rename program.alpha h1 \"my sum\"
+
+Common fixes:
+- Memory cell names may only contain letters, digits, _ and - and have to contain at least one letter.
+- Labels must not contain whitespace or the characters : ; and #.",
+    },
+    ErrorCode {
+        code: "E505",
+        title: "Reserved label",
+        explanation: "The labels main and MAIN mark the start of the program and labels like END mark its end, they can
+neither be renamed nor be used as new name.
+
+Common fix:
+- Choose a different label.",
+    },
+    ErrorCode {
+        code: "E506",
+        title: "Name already exists",
+        explanation: "The new name is already used by another memory cell or label, renaming would merge them.
+
+Common fix:
+- Choose a name that is not used yet.",
+    },
+    ErrorCode {
+        code: "E507",
+        title: "Program can not be parsed for refactoring",
+        explanation: "A line of the program can not be parsed, so the program can not be refactored.
+
+Common fix:
+- Fix the program first, alpha_tui check <FILE> compile shows the error.",
+    },
+    ErrorCode {
+        code: "E508",
+        title: "Line can not be rewritten",
+        explanation: "An occurrence of the renamed memory cell or label could not be located in the text of the line,
+e.g. because it is written in an unusual way.
+
+Common fix:
+- Rename the occurrence in the listed line manually.",
+    },
+    ErrorCode {
+        code: "E509",
+        title: "Invalid line range",
+        explanation: "The lines that should be extracted are not part of the program.
+
+Common fix:
+- Lines are counted from 1, lines that start with # are not counted.",
+    },
+    ErrorCode {
+        code: "E510",
+        title: "Empty selection",
+        explanation: "The lines that should be extracted into a subroutine don't contain an instruction.
+
+Common fix:
+- Select the lines that contain the instructions that should be moved into the subroutine.",
+    },
+    ErrorCode {
+        code: "E511",
+        title: "Return in selection",
+        explanation: "A selected line contains return. After the extraction, it would return from the new subroutine
+instead of the subroutine it is in now.
+
+Common fix:
+- Select only the lines before the return.",
+    },
+    ErrorCode {
+        code: "E512",
+        title: "Jump out of selection",
+        explanation: "A selected line jumps to a label outside of the selected lines. The subroutine has to return to
+the call, so it may only jump to labels inside of the selected lines.
+
+Common fix:
+- Extend the selection, so that it contains the jump target, or select fewer lines.",
+    },
+    ErrorCode {
+        code: "E513",
+        title: "Jump into selection",
+        explanation: "A line outside of the selected lines jumps to a label inside of the selected lines, after the
+extraction the label would be in the subroutine. Only the label of the first selected line may be used, it is placed
+in front of the call.
+
+Common fix:
+- Extend the selection, so that it contains the jump, or select fewer lines.",
+    },
+    ErrorCode {
+        code: "E514",
+        title: "Loop only partially selected",
+        explanation: "The selected lines contain the start or the end of a while or repeat loop, but not both.
+
+Common fix:
+- Select the whole loop or only lines inside of the loop.",
+    },
+    ErrorCode {
+        code: "E515",
+        title: "Refactored program can not be built",
+        explanation: "The program could not be built after it was refactored, the program is not changed.
+
+Common fix:
+- Make sure that the program can be built before it is refactored, alpha_tui check <FILE> compile shows the error.",
+    },
+    ErrorCode {
+        code: "E600",
+        title: "Program can not be imported",
+        explanation: "A line of the program can not be converted into alpha notation. The line and the cause are
+displayed with the error.
+
+Common fix:
+- Run `alpha_tui explain` with the code of the cause, it explains how the line can be fixed.",
+    },
+    ErrorCode {
+        code: "E601",
+        title: "Unknown instruction (import)",
+        explanation: "A line of the imported program contains an instruction that does not exist in the selected
+format.
+
+Example:
+    LODA 5
+with --format ram
+
+Common fix:
+- Check the spelling of the instruction and that the program is written in the format selected with --format.",
+    },
+    ErrorCode {
+        code: "E602",
+        title: "Unsupported instruction (import)",
+        explanation: "The instruction has no equivalent in alpha notation.
+
+Example:
+    READ 1
+with --format ram
+
+Common fixes:
+- Remove the instruction or replace it manually.
+- Input and output are not supported, use a memory config file to provide initial values instead.",
+    },
+    ErrorCode {
+        code: "E603",
+        title: "Missing operand (import)",
+        explanation: "The instruction requires an operand, but none is given.
+
+Example:
+    ADD
+with --format ram
+
+Common fix:
+- Add the operand, e.g. ADD #1 or ADD 5.",
+    },
+    ErrorCode {
+        code: "E604",
+        title: "Invalid operand (import)",
+        explanation: "The operand can not be used with the instruction.
+
+Example:
+    STORE #5
+with --format ram
+
+Common fixes:
+- Valid operands are constants (#5), addresses (5) and indirect addresses (*5).
+- Constants can not be used as target of a store instruction.",
+    },
+];
+
+/// Returns the error code `code`, the code is case insensitive and the leading `E` can be omitted.
+pub fn find(code: &str) -> Option<&'static ErrorCode> {
+    let code = code.trim().to_uppercase();
+    let code = if code.starts_with('E') {
+        code
+    } else {
+        format!("E{code}")
+    };
+    ERROR_CODES.iter().find(|c| c.code == code)
+}
+
+/// Returns the innermost diagnostic in the chain of diagnostic sources of `diagnostic` that has an error code.
+fn innermost(diagnostic: &dyn Diagnostic) -> Option<(&dyn Diagnostic, &'static ErrorCode)> {
+    let mut found = None;
+    let mut current = Some(diagnostic);
+    while let Some(d) = current {
+        if let Some(code) = d.code().and_then(|code| find(&code.to_string())) {
+            found = Some((d, code));
+        }
+        current = d.diagnostic_source();
+    }
+    found
+}
+
+/// Returns the error code of `diagnostic` or of the diagnostic that caused it.
+pub fn code_of(diagnostic: &dyn Diagnostic) -> Option<&'static ErrorCode> {
+    innermost(diagnostic).map(|(_, code)| code)
+}
+
+/// Returns the message of the error with its code, e.g. `E311: Attempt to pop value from stack while stack is empty`.
+///
+/// If the error is caused by another error, the message of the cause is used, as it is more specific.
+pub fn describe(diagnostic: &dyn Diagnostic) -> String {
+    match innermost(diagnostic) {
+        Some((d, code)) => format!("{}: {d}", code.code),
+        None => diagnostic.to_string(),
+    }
+}
+
+/// Returns the hint that tells how the explanation of the error can be printed.
+pub fn explain_hint(diagnostic: &dyn Diagnostic) -> Option<String> {
+    code_of(diagnostic).map(|code| {
+        format!(
+            "For more information about this error, run `alpha_tui explain {}`.",
+            code.code
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use miette::Diagnostic;
+
+    use crate::{
+        import::{ImportError, ImportErrorType},
+        instructions::{
+            error_handling::InstructionParseError, refactor::RefactorError, Instruction,
+        },
+        runtime::{
+            error_handling::{CalcError, RuntimeErrorType},
+            lints::LintWarning,
+        },
+        utils::test_utils,
+    };
+
+    use super::{code_of, describe, explain_hint, find, ERROR_CODES};
+
+    #[test]
+    fn test_error_codes_unique_and_used() {
+        let codes = ERROR_CODES.iter().map(|c| c.code).collect::<BTreeSet<_>>();
+        assert_eq!(codes.len(), ERROR_CODES.len());
+        // every code is assigned to an error and every error code is registered
+        let sources = [
+            include_str!("instructions/error_handling.rs"),
+            include_str!("runtime/error_handling.rs"),
+            include_str!("runtime/lints.rs"),
+            include_str!("instructions/refactor.rs"),
+            include_str!("import/mod.rs"),
+        ]
+        .join("\n");
+        let used = sources
+            .split("code(\"")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').next())
+            .filter(|code| code.starts_with('E') && code[1..].chars().all(|c| c.is_ascii_digit()))
+            .collect::<Vec<_>>();
+        assert_eq!(used.len(), codes.len(), "error code assigned twice");
+        assert_eq!(used.into_iter().collect::<BTreeSet<_>>(), codes);
+    }
+
+    #[test]
+    fn test_codes_of_warnings_and_wrappers() {
+        let warning = LintWarning::PopFromEmptyStack(1, "pop".to_string());
+        assert_eq!(code_of(&warning).unwrap().code, "E401");
+        assert_eq!(
+            code_of(&RefactorError::NotFound("h1".to_string()))
+                .unwrap()
+                .code,
+            "E501"
+        );
+        // the wrapper has a code, but the code of the cause is displayed
+        let e = ImportError {
+            reason: ImportErrorType::MissingOperand("ADD".to_string()),
+            line_number: 1,
+        };
+        assert_eq!(e.code().unwrap().to_string(), "E600");
+        assert_eq!(describe(&e), "E603: Instruction 'ADD' requires an operand");
+        let e = CalcError::AttemptToDivideByZero();
+        let e = RuntimeErrorType::IllegalCalculation { cause: e };
+        assert_eq!(e.code().unwrap().to_string(), "E320");
+        assert_eq!(code_of(&e).unwrap().code, "E317");
+    }
+
+    #[test]
+    fn test_find_error_code() {
+        assert_eq!(find("E311").unwrap().title, "Pop from empty stack");
+        assert_eq!(find("e311"), find("E311"));
+        assert_eq!(find("311"), find("E311"));
+        assert_eq!(find("E999"), None);
+    }
+
+    #[test]
+    fn test_describe_error() {
+        let e = test_utils::runtime_from_str("pop")
+            .unwrap()
+            .run()
+            .unwrap_err();
+        assert_eq!(
+            describe(&e),
+            "E311: Attempt to pop value from stack while stack is empty"
+        );
+        assert_eq!(
+            explain_hint(&e).unwrap(),
+            "For more information about this error, run `alpha_tui explain E311`."
+        );
+        // the code of the cause is used
+        let e = test_utils::runtime_from_str("a := 1\na1 := 0\na := a / a1")
+            .unwrap()
+            .run()
+            .unwrap_err();
+        assert_eq!(describe(&e), "E317: Attempt to divide by zero");
+        let e = Instruction::try_from("a := p(h1) x p(h2)").unwrap_err();
+        assert!(matches!(e, InstructionParseError::UnknownOperation(..)));
+        assert_eq!(
+            describe(&e.into_parse_single_instruction_error(
+                "a := p(h1) x p(h2)".to_string(),
+                "input",
+                1,
+                &[]
+            )),
+            "E001: unknown operation 'x'"
+        );
+    }
+}
@@ -0,0 +1,114 @@
+use std::process;
+
+use miette::Report;
+
+/// Exit codes of the commands, scripts can use them to find out why a command failed.
+///
+/// The codes are listed in the help of the commands, see [`ExitCode::help`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success,
+    /// Any error that has no own exit code, e.g. a refactoring that can not be applied.
+    ///
+    /// This is the exit code that is used when `main` returns an error.
+    Error,
+    /// The command line arguments are invalid, this exit code is used by clap.
+    InvalidArguments,
+    /// The program could not be parsed.
+    ParseError,
+    /// The program was parsed, but the runtime could not be build, e.g. because a label is missing or a lint was denied.
+    BuildError,
+    /// A runtime error occurred while the program was run.
+    RuntimeError,
+    /// The program was stopped because the instruction limit was reached.
+    InstructionLimitReached,
+    /// At least one test case failed.
+    TestFailure,
+    /// Some file could not be read or written, e.g. the program file or a memory config file.
+    IoError,
+}
+
+impl ExitCode {
+    /// Returns the code that the process exits with.
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::Error => 1,
+            Self::InvalidArguments => 2,
+            Self::ParseError => 3,
+            Self::BuildError => 4,
+            Self::RuntimeError => 5,
+            Self::InstructionLimitReached => 6,
+            Self::TestFailure => 7,
+            Self::IoError => 10,
+        }
+    }
+
+    /// Returns a short description of when this exit code is used.
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Success => "Success",
+            Self::Error => "Other error that has no exit code of its own",
+            Self::InvalidArguments => "Invalid command line arguments",
+            Self::ParseError => "The program could not be parsed",
+            Self::BuildError => {
+                "The program could not be build (e.g. missing label, forbidden instruction or denied lint)"
+            }
+            Self::RuntimeError => "Runtime error while running the program",
+            Self::InstructionLimitReached => "The instruction limit was reached",
+            Self::TestFailure => "A test case failed",
+            Self::IoError => "A file could not be read or written",
+        }
+    }
+
+    /// Exits the process with this exit code.
+    pub fn exit(self) -> ! {
+        process::exit(self.code())
+    }
+
+    /// Prints `error` in the same way as an error that is returned from `main` and exits the process with this exit code.
+    pub fn exit_with_error(self, error: &Report) -> ! {
+        eprintln!("Error: {error:?}");
+        self.exit()
+    }
+
+    /// Returns the section of the help of a command that lists the provided exit codes.
+    ///
+    /// The exit codes for other errors, invalid arguments and io errors are always listed, as they can be returned by
+    /// every command.
+    pub fn help(codes: &[Self]) -> String {
+        let mut all = vec![Self::Success];
+        all.extend_from_slice(codes);
+        all.extend([Self::Error, Self::InvalidArguments, Self::IoError]);
+        all.sort_by_key(|c| c.code());
+        all.dedup();
+        let mut help = String::from("Exit codes:");
+        for code in all {
+            help.push_str(&format!("\n  {:>2} - {}", code.code(), code.description()));
+        }
+        help
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{error::ErrorKind, CommandFactory};
+
+    use crate::cli::Cli;
+
+    use super::ExitCode;
+
+    #[test]
+    fn test_exit_code_invalid_arguments_matches_clap() {
+        let err = Cli::command().error(ErrorKind::InvalidValue, "invalid");
+        assert_eq!(err.exit_code(), ExitCode::InvalidArguments.code());
+    }
+
+    #[test]
+    fn test_exit_code_help() {
+        assert_eq!(
+            ExitCode::help(&[ExitCode::TestFailure, ExitCode::ParseError]),
+            "Exit codes:\n   0 - Success\n   1 - Other error that has no exit code of its own\n   2 - Invalid command line arguments\n   3 - The program could not be parsed\n   7 - A test case failed\n  10 - A file could not be read or written"
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use std::{fmt::Write, time::Duration};
+
+use clap::ValueEnum;
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use serde_json::json;
+
+/// Formats in which the execution of a program can be exported as an animation.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum AnimationFormat {
+    /// Asciinema cast file (version 2), can be played with `asciinema play` or embedded into web pages
+    Asciinema,
+    /// Plain text, one frame per step
+    Frames,
+}
+
+/// Creates an asciinema cast file (version 2) from frames that were converted with [`frame_to_ansi`].
+///
+/// A new frame is displayed every `delay`.
+pub fn asciinema_cast(
+    frames: &[String],
+    width: u16,
+    height: u16,
+    delay: Duration,
+    title: &str,
+) -> String {
+    let mut cast = json!({
+        "version": 2,
+        "width": width,
+        "height": height,
+        "title": title,
+    })
+    .to_string();
+    for (idx, frame) in frames.iter().enumerate() {
+        // the screen is cleared before each frame is drawn
+        let event = json!([
+            delay.as_secs_f64() * idx as f64,
+            "o",
+            format!("\x1b[H\x1b[2J{frame}")
+        ]);
+        cast.push('\n');
+        cast.push_str(&event.to_string());
+    }
+    cast.push('\n');
+    cast
+}
+
+/// Joins frames that were converted with [`frame_to_text`], each frame is preceded by a line that contains its number.
+pub fn text_frames(frames: &[String]) -> String {
+    let mut text = String::new();
+    for (idx, frame) in frames.iter().enumerate() {
+        let _ = writeln!(text, "--- frame {} ---", idx + 1);
+        text.push_str(frame);
+    }
+    text
+}
+
+/// Converts the content of a terminal into plain text, trailing whitespace of each line is removed.
+pub fn frame_to_text(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        let line = (0..buffer.area.width)
+            .map(|x| buffer.get(x, y).symbol())
+            .collect::<String>();
+        text.push_str(line.trim_end());
+        text.push('\n');
+    }
+    text
+}
+
+/// Converts the content of a terminal into text that contains ansi escape codes for colors and modifiers.
+pub fn frame_to_ansi(buffer: &Buffer) -> String {
+    let mut text = String::new();
+    for y in 0..buffer.area.height {
+        if y > 0 {
+            text.push_str("\r\n");
+        }
+        let mut last_style = None;
+        for x in 0..buffer.area.width {
+            let cell = buffer.get(x, y);
+            let style = (cell.fg, cell.bg, cell.modifier);
+            if last_style != Some(style) {
+                let mut codes = vec!["0".to_string()];
+                codes.extend(modifier_codes(cell.modifier));
+                codes.push(color_code(cell.fg, false));
+                codes.push(color_code(cell.bg, true));
+                let _ = write!(text, "\x1b[{}m", codes.join(";"));
+                last_style = Some(style);
+            }
+            text.push_str(cell.symbol());
+        }
+        text.push_str("\x1b[0m");
+    }
+    text
+}
+
+/// Returns the select graphic rendition codes of the modifiers.
+fn modifier_codes(modifier: Modifier) -> Vec<String> {
+    [
+        (Modifier::BOLD, "1"),
+        (Modifier::DIM, "2"),
+        (Modifier::ITALIC, "3"),
+        (Modifier::UNDERLINED, "4"),
+        (Modifier::REVERSED, "7"),
+        (Modifier::CROSSED_OUT, "9"),
+    ]
+    .into_iter()
+    .filter(|(m, _)| modifier.contains(*m))
+    .map(|(_, code)| code.to_string())
+    .collect()
+}
+
+/// Returns the select graphic rendition code of the color, `background` is true if the color is a background color.
+fn color_code(color: Color, background: bool) -> String {
+    let offset = if background { 10 } else { 0 };
+    let code = match color {
+        Color::Reset => 39,
+        Color::Black => 30,
+        Color::Red => 31,
+        Color::Green => 32,
+        Color::Yellow => 33,
+        Color::Blue => 34,
+        Color::Magenta => 35,
+        Color::Cyan => 36,
+        Color::Gray => 37,
+        Color::DarkGray => 90,
+        Color::LightRed => 91,
+        Color::LightGreen => 92,
+        Color::LightYellow => 93,
+        Color::LightBlue => 94,
+        Color::LightMagenta => 95,
+        Color::LightCyan => 96,
+        Color::White => 97,
+        Color::Rgb(r, g, b) => return format!("{};2;{r};{g};{b}", 38 + offset),
+        Color::Indexed(i) => return format!("{};5;{i}", 38 + offset),
+    };
+    (code + offset).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ratatui::{
+        buffer::Buffer,
+        layout::Rect,
+        style::{Color, Style},
+    };
+
+    use super::{asciinema_cast, frame_to_ansi, frame_to_text, text_frames};
+
+    fn buffer() -> Buffer {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 4, 2));
+        buffer.set_string(0, 0, "ab", Style::default().fg(Color::Red));
+        buffer.set_string(0, 1, "c", Style::default());
+        buffer
+    }
+
+    #[test]
+    fn test_frame_to_text() {
+        assert_eq!(frame_to_text(&buffer()), "ab\nc\n");
+        assert_eq!(
+            text_frames(&["a\n".to_string(), "b\n".to_string()]),
+            "--- frame 1 ---\na\n--- frame 2 ---\nb\n"
+        );
+    }
+
+    #[test]
+    fn test_frame_to_ansi() {
+        assert_eq!(
+            frame_to_ansi(&buffer()),
+            "\x1b[0;31;49mab\x1b[0;39;49m  \x1b[0m\r\n\x1b[0;39;49mc   \x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_asciinema_cast() {
+        let cast = asciinema_cast(
+            &["a".to_string(), "b".to_string()],
+            4,
+            2,
+            Duration::from_millis(500),
+            "test",
+        );
+        let lines = cast.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["width"], 4);
+        let event: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(event[0], 0.5);
+        assert_eq!(event[2], "\x1b[H\x1b[2Jb");
+    }
+}
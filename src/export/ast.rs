@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    instructions::{
+        error_handling::{BuildProgramError, BuildProgramErrorTypes},
+        Instruction,
+    },
+    runtime::{
+        builder::{ProgramBuilder, END_LABELS},
+        Runtime,
+    },
+    utils,
+};
+
+/// The program as it was built by alpha_tui, can be written as JSON so that other tools don't have to parse programs.
+///
+/// Lines are counted like in the rest of alpha_tui: lines starting with `#` are not counted, except in spans.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ast {
+    /// File from which the program was read.
+    pub file: String,
+    /// Line of the instruction that is run first.
+    pub start_line: usize,
+    /// Maps each label to the line it points to, this includes the labels that are generated for structured loops
+    /// and the labels of the end of the program (e.g. `END`), these point to the line after the last line.
+    pub labels: BTreeMap<String, usize>,
+    pub instructions: Vec<AstInstruction>,
+}
+
+/// A single line of the program.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AstInstruction {
+    pub line: usize,
+    /// Location of the instruction in the file, is `None` for lines that don't contain an instruction.
+    pub span: Option<Span>,
+    /// The instruction as it was written, without label and comment.
+    pub source: String,
+    /// Labels that are defined in this line.
+    pub labels: Vec<String>,
+    /// Comment in this line, without `//` or `#`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The built instruction, structured loops are contained as the jumps they were desugared into.
+    pub instruction: Instruction,
+}
+
+/// Location of an instruction in the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Span {
+    /// Line in the file, starting at 1. Lines starting with `#` are counted.
+    pub line: usize,
+    /// Byte offset in the line at which the instruction starts.
+    pub start: usize,
+    /// Byte offset in the line at which the instruction ends (exclusive).
+    pub end: usize,
+}
+
+impl Ast {
+    /// Creates the ast of the program of the runtime, `lines` are the lines of the program that was read from `file`.
+    pub fn new(runtime: &Runtime, lines: &[String], file: &str) -> Self {
+        let labels = &runtime.control_flow().instruction_labels;
+        // lines starting with # are removed when the program is built, so they are skipped here as well
+        let code_lines = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().starts_with('#'));
+        let instructions = runtime
+            .instructions()
+            .iter()
+            .zip(code_lines)
+            .enumerate()
+            .map(|(idx, (instruction, (file_idx, line)))| {
+                let span = instruction_span(line).map(|(start, end)| Span {
+                    line: file_idx + 1,
+                    start,
+                    end,
+                });
+                let mut line_labels = labels
+                    .iter()
+                    .filter(|(_, target)| **target == idx)
+                    .map(|(label, _)| label.clone())
+                    .collect::<Vec<_>>();
+                line_labels.sort_unstable();
+                AstInstruction {
+                    line: idx + 1,
+                    span,
+                    source: span.map_or_else(String::new, |s| line[s.start..s.end].to_string()),
+                    labels: line_labels,
+                    comment: utils::get_comment(line).map(|c| {
+                        c.trim_start_matches("//")
+                            .trim_start_matches('#')
+                            .trim()
+                            .to_string()
+                    }),
+                    instruction: instruction.clone(),
+                }
+            })
+            .collect();
+        Self {
+            file: file.to_string(),
+            start_line: runtime.initial_instruction_index() + 1,
+            labels: labels
+                .iter()
+                .map(|(label, idx)| (label.clone(), idx + 1))
+                .collect(),
+            instructions,
+        }
+    }
+
+    /// Creates the program from the instructions and labels, the program is not parsed again.
+    ///
+    /// The line that is run first is determined by the main label, like in programs that are parsed, `start_line`,
+    /// `span` and `source` are ignored.
+    #[allow(clippy::result_large_err)]
+    pub fn program(&self) -> Result<ProgramBuilder, BuildProgramError> {
+        let invalid = |reason: String| BuildProgramError {
+            reason: BuildProgramErrorTypes::AstInvalid(reason),
+        };
+        let mut labels: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        // the end labels are added to every program when the runtime is built
+        for (label, line) in self
+            .labels
+            .iter()
+            .filter(|(label, _)| !END_LABELS.contains(&label.as_str()))
+        {
+            // labels can point to the line after the last line to end the program
+            if *line == 0 || *line > self.instructions.len() + 1 {
+                return Err(invalid(format!(
+                    "label '{label}' points to line {line}, which does not exist"
+                )));
+            }
+            labels.entry(*line).or_default().push(label);
+        }
+        let mut program = ProgramBuilder::new();
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            if instruction.line != idx + 1 {
+                return Err(invalid(format!(
+                    "expected line {} but found line {}",
+                    idx + 1,
+                    instruction.line
+                )));
+            }
+            for label in labels.get(&instruction.line).into_iter().flatten() {
+                program.label(label);
+            }
+            if let Some(comment) = &instruction.comment {
+                program.comment(comment);
+            }
+            program.instruction(instruction.instruction.clone());
+        }
+        for label in labels
+            .get(&(self.instructions.len() + 1))
+            .into_iter()
+            .flatten()
+        {
+            program.label(label);
+        }
+        Ok(program)
+    }
+}
+
+/// Returns the byte offsets at which the instruction in the line starts and ends, label and comment are not included.
+///
+/// Returns `None` if the line does not contain an instruction.
+fn instruction_span(line: &str) -> Option<(usize, usize)> {
+    let code_end = utils::comment_start(line).unwrap_or(line.len());
+    let code = line[..code_end].trim_end();
+    let mut start = code.len() - code.trim_start().len();
+    let first = code[start..].split_whitespace().next()?;
+    if first.ends_with(':') {
+        start += first.len();
+        start += code[start..].len() - code[start..].trim_start().len();
+    }
+    (start < code.len()).then_some((start, code.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        instructions::{Instruction, TargetType, Value},
+        utils::test_utils,
+    };
+
+    use super::{instruction_span, Ast, Span};
+
+    #[test]
+    fn test_instruction_span() {
+        assert_eq!(instruction_span("a := 5"), Some((0, 6)));
+        assert_eq!(
+            instruction_span("  loop:  a := 5 // comment"),
+            Some((9, 15))
+        );
+        assert_eq!(instruction_span("loop:"), None);
+        assert_eq!(instruction_span("  // comment"), None);
+    }
+
+    #[test]
+    fn test_ast() {
+        let input = "#var sum = p(h1)\na := 5\n\nloop: p(h1) := a # add\ngoto loop";
+        let lines = test_utils::string_literal_to_vec(input);
+        let rt = test_utils::runtime_from_str(input).unwrap();
+        let ast = Ast::new(&rt, &lines, "test");
+        assert_eq!(ast.start_line, 1);
+        assert_eq!(ast.labels["loop"], 3);
+        assert_eq!(ast.labels["END"], 5);
+        assert_eq!(ast.instructions.len(), 4);
+        assert_eq!(ast.instructions[1].span, None);
+        assert_eq!(ast.instructions[1].instruction, Instruction::Noop);
+        let instruction = &ast.instructions[2];
+        assert_eq!(instruction.line, 3);
+        assert_eq!(
+            instruction.span,
+            Some(Span {
+                line: 4,
+                start: 6,
+                end: 16
+            })
+        );
+        assert_eq!(instruction.source, "p(h1) := a");
+        assert_eq!(instruction.labels, vec!["loop"]);
+        assert_eq!(instruction.comment.as_deref(), Some("add"));
+        assert_eq!(
+            instruction.instruction,
+            Instruction::Assign(
+                TargetType::MemoryCell("h1".to_string()),
+                Value::Accumulator(0)
+            )
+        );
+        let json = serde_json::to_value(&ast).unwrap();
+        assert_eq!(
+            json["instructions"][2]["instruction"],
+            serde_json::json!({"kind": "assign", "operands": [{"memory_cell": "h1"}, {"accumulator": 0}]})
+        );
+        assert_eq!(json["instructions"][1]["instruction"]["kind"], "noop");
+        let parsed: Ast = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed, ast);
+        assert_eq!(
+            parsed.program().unwrap().lines(),
+            vec!["a0 := 5", "", "loop: p(h1) := a0 // add", "goto loop"]
+        );
+    }
+
+    #[test]
+    fn test_ast_round_trip() {
+        let input = "f: p(h1) := p(h1) + a\nreturn\nmain: a := 3\np(h1) := 0\nwhile a > 0 do\ncall f\nif a == 2 then a := a - 1\na := a - 1\nend";
+        let lines = test_utils::string_literal_to_vec(input);
+        let mut rt = test_utils::runtime_from_str(input).unwrap();
+        let json = serde_json::to_string(&Ast::new(&rt, &lines, "test")).unwrap();
+        let ast: Ast = serde_json::from_str(&json).unwrap();
+        let mut imported = ast
+            .program()
+            .unwrap()
+            .runtime_builder()
+            .unwrap()
+            .build()
+            .unwrap();
+        assert_eq!(imported.instructions(), rt.instructions());
+        assert_eq!(
+            imported.initial_instruction_index(),
+            rt.initial_instruction_index()
+        );
+        rt.run().unwrap();
+        imported.run().unwrap();
+        assert_eq!(imported.runtime_memory(), rt.runtime_memory());
+    }
+
+    #[test]
+    fn test_ast_invalid() {
+        let input = "a := 1\ngoto END";
+        let lines = test_utils::string_literal_to_vec(input);
+        let rt = test_utils::runtime_from_str(input).unwrap();
+        let mut ast = Ast::new(&rt, &lines, "test");
+        ast.labels.insert("loop".to_string(), 5);
+        assert!(ast.program().is_err());
+        let mut ast = Ast::new(&rt, &lines, "test");
+        ast.instructions[1].line = 3;
+        assert!(ast.program().is_err());
+        let mut ast = Ast::new(&rt, &lines, "test");
+        ast.instructions[0].instruction = Instruction::CondExec(
+            Value::Constant(1),
+            crate::base::Comparison::Eq,
+            Value::Constant(1),
+            Box::new(Instruction::Goto("END".to_string())),
+        );
+        assert!(ast.program().unwrap().runtime_builder().is_err());
+    }
+}
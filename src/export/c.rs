@@ -0,0 +1,530 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    base::Operation,
+    instructions::{IndexMemoryCellIndexType, Instruction, TargetType, Value},
+    runtime::Runtime,
+};
+
+use super::{is_end_label, labels_by_index, start_label};
+
+const INDENT: &str = "    ";
+
+/// Names that are used by the generated code and can therefore not be used as name for a memory cell.
+const RESERVED_NAMES: &[&str] = &[
+    "p",
+    "y",
+    "sp",
+    "csp",
+    "tmp",
+    "stack",
+    "call_stack",
+    "main",
+    "printf",
+    "auto",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "int",
+    "long",
+    "register",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+];
+
+/// Memory that is used by the program and for which variables need to be declared.
+#[derive(Default)]
+struct UsedMemory {
+    /// Accumulators with their initial values
+    accumulators: BTreeMap<usize, Option<i32>>,
+    /// Is set when the gamma accumulator is used, contains its initial value
+    gamma: Option<Option<i32>>,
+    /// Memory cells with their initial values
+    memory_cells: BTreeMap<String, Option<i32>>,
+    /// Is set when index memory cells are used, contains all initial values
+    index_memory_cells: Option<BTreeMap<usize, i32>>,
+    stack: bool,
+    call_stack: bool,
+    rand: bool,
+}
+
+impl UsedMemory {
+    fn from_runtime(runtime: &Runtime) -> Self {
+        let memory = runtime.runtime_memory();
+        let mut used = UsedMemory {
+            accumulators: memory
+                .accumulators
+                .iter()
+                .map(|(idx, a)| (*idx, a.data))
+                .collect(),
+            gamma: memory.gamma,
+            memory_cells: memory
+                .memory_cells
+                .iter()
+                .map(|(name, m)| (name.clone(), m.data))
+                .collect(),
+            ..Default::default()
+        };
+        if !memory.index_memory_cells.is_empty() {
+            used.index_memory_cells = Some(
+                memory
+                    .index_memory_cells
+                    .iter()
+                    .filter_map(|(idx, v)| v.map(|v| (*idx, v)))
+                    .collect(),
+            );
+        }
+        for instruction in runtime.instructions() {
+            used.add_instruction(instruction);
+        }
+        used
+    }
+
+    fn add_instruction(&mut self, instruction: &Instruction) {
+        match instruction {
+            Instruction::Assign(t, v) | Instruction::Abs(t, v) => {
+                self.add_target(t);
+                self.add_value(v);
+            }
+            Instruction::Rand(t, v, v2) => {
+                self.add_target(t);
+                self.add_value(v);
+                self.add_value(v2);
+                self.rand = true;
+            }
+            Instruction::Calc(t, v, _, v2) => {
+                self.add_target(t);
+                self.add_value(v);
+                self.add_value(v2);
+            }
+            Instruction::JumpIf(v, _, v2, _) => {
+                self.add_value(v);
+                self.add_value(v2);
+            }
+            Instruction::CondExec(v, _, v2, instruction) => {
+                self.add_value(v);
+                self.add_value(v2);
+                self.add_instruction(instruction);
+            }
+            Instruction::Push | Instruction::Pop | Instruction::StackOp(_) => {
+                self.accumulators.entry(0).or_default();
+                self.stack = true;
+            }
+            Instruction::Call(_) | Instruction::Return => self.call_stack = true,
+            Instruction::Goto(_) | Instruction::Noop => (),
+        }
+    }
+
+    fn add_target(&mut self, target: &TargetType) {
+        match target {
+            TargetType::Accumulator(idx) => {
+                self.accumulators.entry(*idx).or_default();
+            }
+            TargetType::Gamma => {
+                self.gamma.get_or_insert(None);
+            }
+            TargetType::MemoryCell(name) => {
+                self.memory_cells.entry(name.clone()).or_default();
+            }
+            TargetType::IndexMemoryCell(t) => self.add_index_memory_cell(t),
+        }
+    }
+
+    fn add_value(&mut self, value: &Value) {
+        match value {
+            Value::Accumulator(idx) => {
+                self.accumulators.entry(*idx).or_default();
+            }
+            Value::Gamma => {
+                self.gamma.get_or_insert(None);
+            }
+            Value::MemoryCell(name) => {
+                self.memory_cells.entry(name.clone()).or_default();
+            }
+            Value::Constant(_) => (),
+            Value::IndexMemoryCell(t) => self.add_index_memory_cell(t),
+        }
+    }
+
+    fn add_index_memory_cell(&mut self, t: &IndexMemoryCellIndexType) {
+        self.index_memory_cells.get_or_insert_with(BTreeMap::new);
+        match t {
+            IndexMemoryCellIndexType::Accumulator(idx) => {
+                self.accumulators.entry(*idx).or_default();
+            }
+            IndexMemoryCellIndexType::Gamma => {
+                self.gamma.get_or_insert(None);
+            }
+            IndexMemoryCellIndexType::MemoryCell(name) => {
+                self.memory_cells.entry(name.clone()).or_default();
+            }
+            IndexMemoryCellIndexType::Direct(_) | IndexMemoryCellIndexType::Index(_) => (),
+        }
+    }
+}
+
+/// Converts the program of the runtime into a C program.
+///
+/// Accumulators, the gamma accumulator and memory cells are converted to variables, index memory cells
+/// are stored in the array `p`. Labels are converted to C labels and jumps to `goto` statements.
+/// When the program ends, the values of all variables are printed.
+pub fn convert(runtime: &Runtime) -> Vec<String> {
+    let used = UsedMemory::from_runtime(runtime);
+    let mut lines = vec![
+        "// converted from alpha notation by alpha_tui".to_string(),
+        "#include <stdio.h>".to_string(),
+        String::new(),
+    ];
+    if used.index_memory_cells.is_some() {
+        lines.push("#define MEMORY_SIZE 1024".to_string());
+    }
+    if used.stack || used.call_stack {
+        lines.push("#define STACK_SIZE 1024".to_string());
+    }
+    if used.index_memory_cells.is_some() || used.stack || used.call_stack {
+        lines.push(String::new());
+    }
+    if used.rand {
+        declare_rand(runtime.settings().rng.seed(), &mut lines);
+    }
+    lines.push("int main(void) {".to_string());
+    declare_variables(&used, &mut lines);
+
+    if let Some(label) = start_label(runtime) {
+        lines.push(format!("{INDENT}goto {};", label_name(label)));
+        lines.push(String::new());
+    }
+
+    let labels = labels_by_index(runtime);
+    let mut calls = 0;
+    for (idx, instruction) in runtime.instructions().iter().enumerate() {
+        if let Some(labels) = labels.get(&idx) {
+            for label in labels {
+                lines.push(format!("{}:", label_name(label)));
+            }
+        }
+        let jump = |label: &str| {
+            if is_end_label(runtime, label) {
+                "goto end_of_program;".to_string()
+            } else {
+                format!("goto {};", label_name(label))
+            }
+        };
+        match instruction {
+            Instruction::JumpIf(v, cmp, v2, label) => lines.push(format!(
+                "{INDENT}if ({} {cmp} {}) {}",
+                value(v),
+                value(v2),
+                jump(label)
+            )),
+            Instruction::CondExec(v, cmp, v2, instruction) => {
+                lines.push(format!("{INDENT}if ({} {cmp} {}) {{", value(v), value(v2)));
+                for statement in statements(instruction) {
+                    lines.push(format!("{INDENT}{INDENT}{statement}"));
+                }
+                lines.push(format!("{INDENT}}}"));
+            }
+            Instruction::Goto(label) => lines.push(format!("{INDENT}{}", jump(label))),
+            Instruction::Call(label) => {
+                lines.push(format!("{INDENT}call_stack[csp++] = {calls};"));
+                lines.push(format!("{INDENT}{}", jump(label)));
+                lines.push(format!("return_{calls}:"));
+                calls += 1;
+            }
+            Instruction::Return => {
+                lines.push(format!("{INDENT}if (csp == 0) goto end_of_program;"));
+                lines.push(format!("{INDENT}goto dispatch_return;"));
+            }
+            Instruction::Noop => lines.push(String::new()),
+            _ => {
+                for statement in statements(instruction) {
+                    lines.push(format!("{INDENT}{statement}"));
+                }
+            }
+        }
+    }
+    lines.push(format!("{INDENT}goto end_of_program;"));
+    lines.push(String::new());
+
+    if used.call_stack {
+        // continues execution after the call instruction that was executed last
+        lines.push("dispatch_return:".to_string());
+        lines.push(format!("{INDENT}switch (call_stack[--csp]) {{"));
+        for call in 0..calls {
+            lines.push(format!("{INDENT}{INDENT}case {call}: goto return_{call};"));
+        }
+        lines.push(format!("{INDENT}{INDENT}default: goto end_of_program;"));
+        lines.push(format!("{INDENT}}}"));
+        lines.push(String::new());
+    }
+
+    lines.push("end_of_program:".to_string());
+    for idx in used.accumulators.keys() {
+        lines.push(format!("{INDENT}printf(\"a{idx} = %d\\n\", a{idx});"));
+    }
+    if used.gamma.is_some() {
+        lines.push(format!("{INDENT}printf(\"y = %d\\n\", y);"));
+    }
+    for name in used.memory_cells.keys() {
+        lines.push(format!(
+            "{INDENT}printf(\"{name} = %d\\n\", {});",
+            memory_cell_name(name)
+        ));
+    }
+    lines.push(format!("{INDENT}return 0;"));
+    lines.push("}".to_string());
+    lines
+}
+
+/// Adds a random number generator that generates the same numbers as the one used by alpha_tui.
+fn declare_rand(seed: u64, lines: &mut Vec<String>) {
+    lines.extend([
+        "#include <stdint.h>".to_string(),
+        String::new(),
+        format!("static uint64_t rng_state = {seed}ULL;"),
+        String::new(),
+        "// returns a random number between min and max (both inclusive)".to_string(),
+        "static int rand_range(int min, int max) {".to_string(),
+        format!("{INDENT}uint64_t z = (rng_state += 0x9E3779B97F4A7C15ULL);"),
+        format!("{INDENT}z = (z ^ (z >> 30)) * 0xBF58476D1CE4E5B9ULL;"),
+        format!("{INDENT}z = (z ^ (z >> 27)) * 0x94D049BB133111EBULL;"),
+        format!("{INDENT}z ^= z >> 31;"),
+        format!("{INDENT}uint64_t range = (uint64_t)((int64_t)max - (int64_t)min + 1);"),
+        format!("{INDENT}return (int)((int64_t)min + (int64_t)(z % range));"),
+        "}".to_string(),
+        String::new(),
+    ]);
+}
+
+fn declare_variables(used: &UsedMemory, lines: &mut Vec<String>) {
+    let init = |v: &Option<i32>| v.unwrap_or_default();
+    if !used.accumulators.is_empty() {
+        lines.push(format!("{INDENT}// accumulators"));
+        for (idx, v) in &used.accumulators {
+            lines.push(format!("{INDENT}int a{idx} = {};", init(v)));
+        }
+    }
+    if let Some(v) = &used.gamma {
+        lines.push(format!("{INDENT}// gamma accumulator"));
+        lines.push(format!("{INDENT}int y = {};", init(v)));
+    }
+    if !used.memory_cells.is_empty() {
+        lines.push(format!("{INDENT}// memory cells"));
+        for (name, v) in &used.memory_cells {
+            lines.push(format!(
+                "{INDENT}int {} = {};",
+                memory_cell_name(name),
+                init(v)
+            ));
+        }
+    }
+    if let Some(values) = &used.index_memory_cells {
+        lines.push(format!("{INDENT}// index memory cells"));
+        lines.push(format!("{INDENT}int p[MEMORY_SIZE] = {{0}};"));
+        for (idx, v) in values {
+            lines.push(format!("{INDENT}p[{idx}] = {v};"));
+        }
+    }
+    if used.stack {
+        lines.push(format!("{INDENT}// stack"));
+        lines.push(format!("{INDENT}int stack[STACK_SIZE];"));
+        lines.push(format!("{INDENT}int sp = 0;"));
+    }
+    if used.call_stack {
+        lines.push(format!(
+            "{INDENT}// call stack, stores where to continue after return"
+        ));
+        lines.push(format!("{INDENT}int call_stack[STACK_SIZE];"));
+        lines.push(format!("{INDENT}int csp = 0;"));
+    }
+    lines.push(String::new());
+}
+
+/// Converts an instruction that does not change the control flow into C statements.
+fn statements(instruction: &Instruction) -> Vec<String> {
+    match instruction {
+        Instruction::Assign(t, v) => vec![format!("{} = {};", target(t), value(v))],
+        Instruction::Calc(t, v, op, v2) => vec![format!(
+            "{} = {};",
+            target(t),
+            calc(*op, &value(v), &value(v2))
+        )],
+        Instruction::Abs(t, v) => {
+            let v = value(v);
+            // the value is parenthesized, so that negative constants are not turned into a decrement like `--4`
+            vec![format!("{} = ({v} < 0) ? -({v}) : {v};", target(t))]
+        }
+        Instruction::Rand(t, v, v2) => vec![format!(
+            "{} = rand_range({}, {});",
+            target(t),
+            value(v),
+            value(v2)
+        )],
+        Instruction::Push => vec!["stack[sp++] = a0;".to_string()],
+        Instruction::Pop => vec!["a0 = stack[--sp];".to_string()],
+        Instruction::StackOp(op) => vec![
+            "sp--;".to_string(),
+            format!("a0 = {};", calc(*op, "stack[sp - 1]", "stack[sp]")),
+            "stack[sp - 1] = a0;".to_string(),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the C expression that applies the operation to both values.
+fn calc(op: Operation, x: &str, y: &str) -> String {
+    match op {
+        Operation::Min => format!("{x} < {y} ? {x} : {y}"),
+        Operation::Max => format!("{x} > {y} ? {x} : {y}"),
+        _ => format!("{x} {op} {y}"),
+    }
+}
+
+/// Replaces all characters that are not allowed in C identifiers with `_`.
+fn sanitize(name: &str) -> String {
+    let mut sanitized = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    if sanitized.starts_with(|c: char| c.is_ascii_digit()) {
+        sanitized.insert(0, '_');
+    }
+    sanitized
+}
+
+fn label_name(label: &str) -> String {
+    sanitize(label)
+}
+
+fn memory_cell_name(name: &str) -> String {
+    let name = sanitize(name);
+    let is_accumulator_name = name
+        .strip_prefix('a')
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_digit()));
+    if RESERVED_NAMES.contains(&name.as_str()) || is_accumulator_name {
+        format!("m_{name}")
+    } else {
+        name
+    }
+}
+
+fn target(target: &TargetType) -> String {
+    match target {
+        TargetType::Accumulator(idx) => format!("a{idx}"),
+        TargetType::Gamma => "y".to_string(),
+        TargetType::MemoryCell(name) => memory_cell_name(name),
+        TargetType::IndexMemoryCell(t) => index_memory_cell(t),
+    }
+}
+
+fn value(value: &Value) -> String {
+    match value {
+        Value::Accumulator(idx) => format!("a{idx}"),
+        Value::Gamma => "y".to_string(),
+        Value::MemoryCell(name) => memory_cell_name(name),
+        Value::Constant(c) => c.to_string(),
+        Value::IndexMemoryCell(t) => index_memory_cell(t),
+    }
+}
+
+fn index_memory_cell(t: &IndexMemoryCellIndexType) -> String {
+    match t {
+        IndexMemoryCellIndexType::Accumulator(idx) => format!("p[a{idx}]"),
+        IndexMemoryCellIndexType::Direct(idx) => format!("p[{idx}]"),
+        IndexMemoryCellIndexType::Gamma => "p[y]".to_string(),
+        IndexMemoryCellIndexType::MemoryCell(name) => format!("p[{}]", memory_cell_name(name)),
+        IndexMemoryCellIndexType::Index(idx) => format!("p[p[{idx}]]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process::Command};
+
+    use crate::utils::test_utils;
+
+    use super::{convert, memory_cell_name};
+
+    #[test]
+    fn test_convert_c() {
+        let rt = test_utils::runtime_from_str(
+            "fac: if a1 <= 1 then goto base\npush\na1 := a1 - 1\ncall fac\npop\na := a * p(1)\nreturn\nbase: a := 1\nreturn\nmain: a1 := 3\ncall fac\ngoto end",
+        )
+        .unwrap();
+        let c = convert(&rt).join("\n");
+        assert!(c.contains("int a0 = 0;\n    int a1 = 0;"));
+        assert!(c.contains("int p[MEMORY_SIZE] = {0};"));
+        assert!(c.contains("    goto main;\n"));
+        assert!(c.contains("fac:\n    if (a1 <= 1) goto base;\n    stack[sp++] = a0;"));
+        assert!(c.contains(
+            "    call_stack[csp++] = 0;\n    goto fac;\nreturn_0:\n    a0 = stack[--sp];"
+        ));
+        assert!(c.contains("    if (csp == 0) goto end_of_program;\n    goto dispatch_return;"));
+        assert!(c.contains("        case 1: goto return_1;\n        default: goto end_of_program;"));
+        assert!(c.contains("end_of_program:\n    printf(\"a0 = %d\\n\", a0);"));
+    }
+
+    #[test]
+    fn test_convert_c_abs_negative_constant() {
+        let rt = test_utils::runtime_from_str("a := abs(-4)\na1 := abs(a)").unwrap();
+        let c = convert(&rt).join("\n");
+        assert!(c.contains("a0 = (-4 < 0) ? -(-4) : -4;"));
+        // the program is compiled and run, if a C compiler is available
+        let dir = env::temp_dir().join("alpha_tui_test_convert_c_abs");
+        fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("program.c");
+        let binary = dir.join("program");
+        fs::write(&source, c).unwrap();
+        let Ok(status) = Command::new("cc")
+            .arg(&source)
+            .arg("-o")
+            .arg(&binary)
+            .status()
+        else {
+            return;
+        };
+        assert!(status.success());
+        let output = Command::new(&binary).output().unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("a0 = 4"));
+        assert!(stdout.contains("a1 = 4"));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_memory_cell_name() {
+        assert_eq!(memory_cell_name("h1"), "h1");
+        assert_eq!(memory_cell_name("sp"), "m_sp");
+        assert_eq!(memory_cell_name("a5"), "m_a5");
+        assert_eq!(memory_cell_name("1x"), "_1x");
+    }
+}
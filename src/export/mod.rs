@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+
+use crate::runtime::Runtime;
+
+/// Animations of the execution of a program
+pub mod animation;
+/// The built program as JSON
+pub mod ast;
+/// Conversion into compilable C code
+pub mod c;
+/// Conversion into pseudocode
+pub mod pseudocode;
+
+/// Languages into which alpha notation programs can be converted.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportTarget {
+    /// Structured pseudocode
+    Pseudocode,
+    /// Compilable C code
+    C,
+}
+
+/// Converts the program of the runtime into the language `target`.
+///
+/// Returns the converted program line by line.
+pub fn convert(runtime: &Runtime, target: ExportTarget) -> Vec<String> {
+    match target {
+        ExportTarget::Pseudocode => pseudocode::convert(runtime),
+        ExportTarget::C => c::convert(runtime),
+    }
+}
+
+/// Returns all labels defined in the program, grouped by the index of the instruction they point to.
+///
+/// Labels that point to the end of the program (e.g. `END`) are not included.
+fn labels_by_index(runtime: &Runtime) -> BTreeMap<usize, Vec<&str>> {
+    let mut labels: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+    for (label, idx) in &runtime.control_flow().instruction_labels {
+        if *idx < runtime.instructions().len() {
+            labels.entry(*idx).or_default().push(label);
+        }
+    }
+    for labels in labels.values_mut() {
+        labels.sort_unstable();
+    }
+    labels
+}
+
+/// Returns true if the label points to the end of the program.
+fn is_end_label(runtime: &Runtime, label: &str) -> bool {
+    runtime
+        .control_flow()
+        .instruction_labels
+        .get(label)
+        .is_some_and(|idx| *idx >= runtime.instructions().len())
+}
+
+/// Returns the label of the instruction that is executed first, if the program does not start at the first instruction.
+fn start_label(runtime: &Runtime) -> Option<&str> {
+    ["main", "MAIN"].into_iter().find(|label| {
+        runtime.control_flow().instruction_labels.get(*label)
+            == Some(&runtime.initial_instruction_index())
+            && runtime.initial_instruction_index() != 0
+    })
+}
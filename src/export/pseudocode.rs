@@ -0,0 +1,165 @@
+use crate::{
+    base::{Comparison, Operation},
+    instructions::{IndexMemoryCellIndexType, Instruction, TargetType, Value},
+    runtime::Runtime,
+};
+
+use super::{is_end_label, labels_by_index, start_label};
+
+const INDENT: &str = "    ";
+
+/// Converts the program of the runtime into pseudocode.
+///
+/// Labels are placed on their own line and start a new block, instructions are indented below them.
+/// Index memory cells are written as array `M`, jumps to the end of the program are written as `HALT`.
+pub fn convert(runtime: &Runtime) -> Vec<String> {
+    let labels = labels_by_index(runtime);
+    let mut lines = Vec::new();
+    if let Some(label) = start_label(runtime) {
+        lines.push(format!("START AT {label}"));
+        lines.push(String::new());
+    }
+    for (idx, instruction) in runtime.instructions().iter().enumerate() {
+        if let Some(labels) = labels.get(&idx) {
+            for label in labels {
+                lines.push(format!("{label}:"));
+            }
+        }
+        match instruction {
+            Instruction::Noop => lines.push(String::new()),
+            _ => lines.push(format!(
+                "{INDENT}{}",
+                instruction_to_pseudocode(runtime, instruction)
+            )),
+        }
+    }
+    // remove trailing empty lines
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+    lines
+}
+
+fn instruction_to_pseudocode(runtime: &Runtime, instruction: &Instruction) -> String {
+    let jump = |label: &str| {
+        if is_end_label(runtime, label) {
+            "HALT".to_string()
+        } else {
+            format!("GOTO {label}")
+        }
+    };
+    match instruction {
+        Instruction::Assign(t, v) => format!("{} ← {}", target(t), value(v)),
+        Instruction::Calc(t, v, op, v2) => {
+            format!("{} ← {}", target(t), calc(*op, &value(v), &value(v2)))
+        }
+        Instruction::Abs(t, v) => format!("{} ← ABS({})", target(t), value(v)),
+        Instruction::Rand(t, v, v2) => {
+            format!("{} ← RANDOM({}, {})", target(t), value(v), value(v2))
+        }
+        Instruction::JumpIf(v, cmp, v2, label) => format!(
+            "IF {} {} {} THEN {}",
+            value(v),
+            comparison(cmp),
+            value(v2),
+            jump(label)
+        ),
+        Instruction::CondExec(v, cmp, v2, instruction) => format!(
+            "IF {} {} {} THEN {}",
+            value(v),
+            comparison(cmp),
+            value(v2),
+            instruction_to_pseudocode(runtime, instruction)
+        ),
+        Instruction::Goto(label) => jump(label),
+        Instruction::Push => "PUSH a0".to_string(),
+        Instruction::Pop => "a0 ← POP".to_string(),
+        Instruction::StackOp(op) => {
+            format!("tmp ← POP; a0 ← {}; PUSH a0", calc(*op, "POP", "tmp"))
+        }
+        Instruction::Call(label) => format!("CALL {label}"),
+        Instruction::Return => "RETURN".to_string(),
+        Instruction::Noop => String::new(),
+    }
+}
+
+fn calc(op: Operation, x: &str, y: &str) -> String {
+    match op {
+        Operation::Min => format!("MIN({x}, {y})"),
+        Operation::Max => format!("MAX({x}, {y})"),
+        _ => format!("{x} {op} {y}"),
+    }
+}
+
+fn comparison(cmp: &Comparison) -> &'static str {
+    match cmp {
+        Comparison::Lt => "<",
+        Comparison::Le => "≤",
+        Comparison::Eq => "=",
+        Comparison::Neq => "≠",
+        Comparison::Ge => "≥",
+        Comparison::Gt => ">",
+    }
+}
+
+fn target(target: &TargetType) -> String {
+    match target {
+        TargetType::Accumulator(idx) => format!("a{idx}"),
+        TargetType::Gamma => "y".to_string(),
+        TargetType::MemoryCell(name) => name.clone(),
+        TargetType::IndexMemoryCell(t) => index_memory_cell(t),
+    }
+}
+
+fn value(value: &Value) -> String {
+    match value {
+        Value::Accumulator(idx) => format!("a{idx}"),
+        Value::Gamma => "y".to_string(),
+        Value::MemoryCell(name) => name.clone(),
+        Value::Constant(c) => c.to_string(),
+        Value::IndexMemoryCell(t) => index_memory_cell(t),
+    }
+}
+
+fn index_memory_cell(t: &IndexMemoryCellIndexType) -> String {
+    match t {
+        IndexMemoryCellIndexType::Accumulator(idx) => format!("M[a{idx}]"),
+        IndexMemoryCellIndexType::Direct(idx) => format!("M[{idx}]"),
+        IndexMemoryCellIndexType::Gamma => "M[y]".to_string(),
+        IndexMemoryCellIndexType::MemoryCell(name) => format!("M[{name}]"),
+        IndexMemoryCellIndexType::Index(idx) => format!("M[M[{idx}]]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils;
+
+    use super::convert;
+
+    #[test]
+    fn test_convert_pseudocode() {
+        let rt = test_utils::runtime_from_str(
+            "loop: if p(h1) >= 10 then goto end\np(h1) := p(h1) + 1\n\np(p(1)) := a * p(y)\npush\nstack*\ngoto loop\nmain: p(h1) := 0\ngoto loop",
+        )
+        .unwrap();
+        assert_eq!(
+            convert(&rt),
+            vec![
+                "START AT main",
+                "",
+                "loop:",
+                "    IF h1 ≥ 10 THEN HALT",
+                "    h1 ← h1 + 1",
+                "",
+                "    M[M[1]] ← a0 * M[y]",
+                "    PUSH a0",
+                "    tmp ← POP; a0 ← POP * tmp; PUSH a0",
+                "    GOTO loop",
+                "main:",
+                "    h1 ← 0",
+                "    GOTO loop",
+            ]
+        );
+    }
+}
@@ -0,0 +1,62 @@
+use clap::ValueEnum;
+use miette::Diagnostic;
+use thiserror::Error;
+
+/// Converter for the simple RAM assembler format
+pub mod ram;
+
+/// Formats of other register machine simulators that can be converted into alpha notation.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ImportFormat {
+    /// Simple RAM assembler that uses mnemonics like LOAD, STORE and ADD
+    Ram,
+}
+
+/// Converts the program in `lines`, that is written in the format `format`, into alpha notation.
+///
+/// Returns the converted program line by line, empty lines and comments are preserved, so that
+/// line numbers match between the input and the output.
+pub fn convert(lines: &[String], format: ImportFormat) -> Result<Vec<String>, ImportError> {
+    match format {
+        ImportFormat::Ram => ram::convert(lines),
+    }
+}
+
+#[derive(Debug, Error, Diagnostic, Clone, PartialEq)]
+#[error("unable to import line {line_number}")]
+#[diagnostic(code("E600"))]
+pub struct ImportError {
+    #[diagnostic_source]
+    pub reason: ImportErrorType,
+    pub line_number: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Error, Diagnostic)]
+pub enum ImportErrorType {
+    #[error("Unknown instruction '{0}'")]
+    #[diagnostic(
+        code("E601"),
+        help(
+            "Make sure that the program is written in the format that was selected with '--format'"
+        )
+    )]
+    UnknownInstruction(String),
+
+    #[error("Instruction '{0}' can not be converted, because alpha notation has no equivalent")]
+    #[diagnostic(
+        code("E602"),
+        help("Remove the instruction or replace it manually.\nInput and output are not supported by alpha notation, use a memory config file to provide initial values instead.")
+    )]
+    UnsupportedInstruction(String),
+
+    #[error("Instruction '{0}' requires an operand")]
+    #[diagnostic(code("E603"))]
+    MissingOperand(String),
+
+    #[error("Operand '{1}' is invalid for instruction '{0}'")]
+    #[diagnostic(
+        code("E604"),
+        help("Valid operands are constants ('#5'), addresses ('5') and indirect addresses ('*5').\nConstants can not be used as target of a store instruction.")
+    )]
+    InvalidOperand(String, String),
+}
@@ -0,0 +1,213 @@
+use super::{ImportError, ImportErrorType};
+
+/// An operand of a RAM instruction.
+#[derive(Debug, PartialEq)]
+enum Operand {
+    /// Constant value, written as `#c` or `=c`
+    Constant(i32),
+    /// Memory address, written as `n`
+    Direct(usize),
+    /// Address that is stored in a memory address, written as `*n` or `@n`
+    Indirect(usize),
+}
+
+impl Operand {
+    fn parse(s: &str) -> Option<Self> {
+        if let Some(c) = s.strip_prefix('#').or_else(|| s.strip_prefix('=')) {
+            return c.parse().ok().map(Operand::Constant);
+        }
+        if let Some(n) = s.strip_prefix('*').or_else(|| s.strip_prefix('@')) {
+            return n.parse().ok().map(Operand::Indirect);
+        }
+        s.parse().ok().map(Operand::Direct)
+    }
+
+    /// Returns this operand written in alpha notation.
+    fn to_alpha(&self) -> String {
+        match self {
+            Self::Constant(c) => c.to_string(),
+            Self::Direct(n) => format!("p({n})"),
+            Self::Indirect(n) => format!("p(p({n}))"),
+        }
+    }
+}
+
+/// Converts a program written in simple RAM assembler into alpha notation.
+///
+/// The accumulator of the RAM is mapped to `a`, memory addresses are mapped to index memory cells.
+/// Comments starting with `;` are kept as `//` comments.
+pub fn convert(lines: &[String]) -> Result<Vec<String>, ImportError> {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            convert_line(line).map_err(|reason| ImportError {
+                reason,
+                line_number: idx + 1,
+            })
+        })
+        .collect()
+}
+
+fn convert_line(line: &str) -> Result<String, ImportErrorType> {
+    let (code, comment) = match line.find(';') {
+        Some(idx) => (&line[..idx], Some(line[idx + 1..].trim())),
+        None => (line, None),
+    };
+    let mut parts = code.split_whitespace().collect::<Vec<&str>>();
+    let mut converted = Vec::new();
+    if parts.first().is_some_and(|p| p.ends_with(':')) {
+        converted.push(parts.remove(0).to_string());
+    }
+    if !parts.is_empty() {
+        converted.push(convert_instruction(&parts)?);
+    }
+    if let Some(comment) = comment {
+        converted.push(format!("// {comment}"));
+    }
+    Ok(converted.join(" "))
+}
+
+fn convert_instruction(parts: &[&str]) -> Result<String, ImportErrorType> {
+    let mnemonic = parts[0].to_uppercase();
+    let operand = parts.get(1).copied();
+    let require_operand = || operand.ok_or(ImportErrorType::MissingOperand(mnemonic.clone()));
+    let parse_operand = || {
+        let operand = require_operand()?;
+        Operand::parse(operand).ok_or(ImportErrorType::InvalidOperand(
+            mnemonic.clone(),
+            operand.to_string(),
+        ))
+    };
+    let instruction = match mnemonic.as_str() {
+        "LOAD" | "LDA" => format!("a := {}", parse_operand()?.to_alpha()),
+        "STORE" | "STA" => match parse_operand()? {
+            Operand::Constant(_) => {
+                return Err(ImportErrorType::InvalidOperand(
+                    mnemonic,
+                    operand.unwrap_or_default().to_string(),
+                ))
+            }
+            target => format!("{} := a", target.to_alpha()),
+        },
+        "ADD" => format!("a := a + {}", parse_operand()?.to_alpha()),
+        "SUB" => format!("a := a - {}", parse_operand()?.to_alpha()),
+        "MUL" | "MULT" => format!("a := a * {}", parse_operand()?.to_alpha()),
+        "DIV" => format!("a := a / {}", parse_operand()?.to_alpha()),
+        "MOD" => format!("a := a % {}", parse_operand()?.to_alpha()),
+        "JUMP" | "JMP" | "GOTO" => format!("goto {}", require_operand()?),
+        "JZERO" | "JZ" => format!("if a == 0 then goto {}", require_operand()?),
+        "JNZERO" | "JNZ" => format!("if a != 0 then goto {}", require_operand()?),
+        "JGTZ" => format!("if a > 0 then goto {}", require_operand()?),
+        "JGEZ" => format!("if a >= 0 then goto {}", require_operand()?),
+        "JLTZ" => format!("if a < 0 then goto {}", require_operand()?),
+        "JLEZ" => format!("if a <= 0 then goto {}", require_operand()?),
+        "HALT" | "STOP" => "goto END".to_string(),
+        "NOP" => String::new(),
+        "READ" | "WRITE" => return Err(ImportErrorType::UnsupportedInstruction(mnemonic)),
+        _ => return Err(ImportErrorType::UnknownInstruction(parts[0].to_string())),
+    };
+    Ok(instruction)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        import::{ImportError, ImportErrorType},
+        utils::test_utils,
+    };
+
+    use super::convert;
+
+    fn convert_str(input: &str) -> Result<Vec<String>, ImportError> {
+        convert(&test_utils::string_literal_to_vec(input))
+    }
+
+    #[test]
+    fn test_convert_memory_access() {
+        assert_eq!(
+            convert_str("LOAD #5\nstore 1\nLOAD *1\nSTORE @2\nload =-3"),
+            Ok(vec![
+                "a := 5".to_string(),
+                "p(1) := a".to_string(),
+                "a := p(p(1))".to_string(),
+                "p(p(2)) := a".to_string(),
+                "a := -3".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_convert_calculations() {
+        assert_eq!(
+            convert_str("ADD 1\nSUB #2\nMULT *3\nDIV 4\nMOD #5"),
+            Ok(vec![
+                "a := a + p(1)".to_string(),
+                "a := a - 2".to_string(),
+                "a := a * p(p(3))".to_string(),
+                "a := a / p(4)".to_string(),
+                "a := a % 5".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_convert_jumps_labels_and_comments() {
+        assert_eq!(
+            convert_str("loop: JZERO end ; exit loop\nSUB #1\nJUMP loop\n\nend: HALT"),
+            Ok(vec![
+                "loop: if a == 0 then goto end // exit loop".to_string(),
+                "a := a - 1".to_string(),
+                "goto loop".to_string(),
+                "".to_string(),
+                "end: goto END".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_convert_result_compiles() {
+        let converted = convert_str(
+            "LOAD #3\nSTORE 1\nLOAD #1\nloop: STORE 2\nLOAD 1\nJZERO end\nSUB #1\nSTORE 1\nLOAD 2\nMUL #2\nJUMP loop\nend: HALT",
+        )
+        .unwrap();
+        let mut rt = test_utils::runtime_from_str(&converted.join("\n")).unwrap();
+        rt.run().unwrap();
+        assert_eq!(
+            rt.runtime_memory().index_memory_cells.get(&2),
+            Some(&Some(8))
+        );
+    }
+
+    #[test]
+    fn test_convert_errors() {
+        assert_eq!(
+            convert_str("LOAD #1\nREAD 1"),
+            Err(ImportError {
+                reason: ImportErrorType::UnsupportedInstruction("READ".to_string()),
+                line_number: 2,
+            })
+        );
+        assert_eq!(
+            convert_str("LODA 1"),
+            Err(ImportError {
+                reason: ImportErrorType::UnknownInstruction("LODA".to_string()),
+                line_number: 1,
+            })
+        );
+        assert_eq!(
+            convert_str("ADD"),
+            Err(ImportError {
+                reason: ImportErrorType::MissingOperand("ADD".to_string()),
+                line_number: 1,
+            })
+        );
+        assert_eq!(
+            convert_str("STORE #1"),
+            Err(ImportError {
+                reason: ImportErrorType::InvalidOperand("STORE".to_string(), "#1".to_string()),
+                line_number: 1,
+            })
+        );
+    }
+}
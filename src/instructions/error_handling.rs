@@ -1,15 +1,16 @@
 use miette::{Diagnostic, NamedSource, SourceOffset, SourceSpan};
 use thiserror::Error;
 
+use crate::utils::remove_comment;
+
+use super::suggestions::memory_cell_names;
+
 #[derive(Debug, PartialEq, Diagnostic, Error, Clone)]
 pub enum InstructionParseError {
     /// Indicates that the specified operation does not exist.
     /// Argument specifies the character index at which the error occurred.
     #[error("unknown operation '{1}'")]
-    #[diagnostic(
-        code("parse_instruction::unknown_operation"),
-        help("Did you mean one of these?: + - * / %")
-    )]
+    #[diagnostic(code("E001"), help("Did you mean one of these?: + - * / % min max"))]
     UnknownOperation((usize, usize), String),
 
     /// Indicates that the specified comparison does not exist.
@@ -17,8 +18,8 @@ pub enum InstructionParseError {
     /// and the string that caused it.
     #[error("unknown comparison '{1}'")]
     #[diagnostic(
-        code("parse_instruction::unknown_comparison"),
-        help("Did you mean one of these?: < <= == != >= >")
+        code("E002"),
+        help("Did you mean one of these?: < <= == != >= >\nThese can also be used: =< => = ≤ ≠ ≥")
     )]
     UnknownComparison((usize, usize), String),
 
@@ -26,14 +27,14 @@ pub enum InstructionParseError {
     /// Argument specifies the character index at which the error occurred.
     /// and the string that caused it.
     #[error("'{1}' is not a number")]
-    #[diagnostic(code("parse_instruction::not_a_number"))]
+    #[diagnostic(code("E003"))]
     NotANumber((usize, usize), String),
 
     /// Indicates that the market expression is not valid.
     /// The reason might be a syntax error.
     #[error("invalid expression '{1}'")]
     #[diagnostic(
-        code("parse_instruction::invalid_expression"),
+        code("E004"),
         url("https://github.com/LMH01/alpha_tui/blob/master/docs/instructions.md"),
         help("Make sure that you use a supported instruction.")
     )]
@@ -42,7 +43,7 @@ pub enum InstructionParseError {
     /// Indicates that no instruction was found that matches the input.
     #[error("unknown instruction '{1}'")]
     #[diagnostic(
-        code("parse_instruction::unknown_instruction"),
+        code("E005"),
         url("https://github.com/LMH01/alpha_tui/blob/master/docs/instructions.md"),
         help("Make sure that you use a supported instruction.")
     )]
@@ -50,7 +51,7 @@ pub enum InstructionParseError {
 
     #[error("missing expression")]
     #[diagnostic(
-        code("parse_instruction::missing_expression"),
+        code("E006"),
         url("https://github.com/LMH01/alpha_tui/blob/master/docs/instructions.md")
     )]
     MissingExpression {
@@ -73,6 +74,27 @@ impl InstructionParseError {
         }
     }
 
+    /// Moves the range of the error `offset` characters to the right.
+    ///
+    /// Used when the error occurred in a part of the input that was parsed separately.
+    pub fn shift_range(self, offset: usize) -> Self {
+        let shift = |(start, end): (usize, usize)| (start + offset, end + offset);
+        match self {
+            Self::UnknownOperation(c, s) => Self::UnknownOperation(shift(c), s),
+            Self::UnknownComparison(c, s) => Self::UnknownComparison(shift(c), s),
+            Self::NotANumber(c, s) => Self::NotANumber(shift(c), s),
+            Self::InvalidExpression(c, s) => Self::InvalidExpression(shift(c), s),
+            Self::UnknownInstruction(c, s) => Self::UnknownInstruction(shift(c), s),
+            Self::MissingExpression { range, help } => Self::MissingExpression {
+                range: shift(range),
+                help,
+            },
+        }
+    }
+
+    /// Converts this error into an error that points to `line` in `file_contents`.
+    ///
+    /// Memory cells that are used in `file_contents` are suggested when a memory cell is written without `p()`.
     pub fn into_build_program_error(
         self,
         file_contents: String,
@@ -82,6 +104,18 @@ impl InstructionParseError {
         // Workaround for wrong end_range value depending on error.
         // For the line to be printed when more then one character is affected for some reason the range needs to be increased by one.
         let end_range = self.end_range();
+        let suggestion = file_contents
+            .lines()
+            .nth(line.saturating_sub(1))
+            .and_then(|l| {
+                let instruction = remove_comment(l);
+                // the label is not part of the instruction
+                let instruction = match instruction.split_once(' ') {
+                    Some((label, rest)) if label.ends_with(':') => rest.to_string(),
+                    _ => instruction,
+                };
+                self.suggestion(&instruction, &memory_cell_names(&file_contents))
+            });
         BuildProgramError {
             reason: BuildProgramErrorTypes::ParseError {
                 src: NamedSource::new(file_name, file_contents.clone()),
@@ -90,19 +124,28 @@ impl InstructionParseError {
                     end_range,
                 ),
                 reason: self,
+                suggestion,
             },
         }
     }
 
+    /// Converts this error into an error that points to `line` in `file_contents`.
+    ///
+    /// `memory_cells` are suggested when a memory cell is written without `p()`.
     pub fn into_parse_single_instruction_error(
         self,
         file_contents: String,
         file_name: &str,
         line: usize,
+        memory_cells: &[String],
     ) -> ParseSingleInstructionError {
         // Workaround for wrong end_range value depending on error.
         // For the line to be printed when more then one character is affected for some reason the range needs to be increased by one.
         let end_range = self.end_range();
+        let suggestion = file_contents
+            .lines()
+            .nth(line.saturating_sub(1))
+            .and_then(|l| self.suggestion(l, memory_cells));
         ParseSingleInstructionError {
             src: NamedSource::new(file_name, file_contents.clone()),
             bad_bit: SourceSpan::new(
@@ -110,6 +153,7 @@ impl InstructionParseError {
                 end_range,
             ),
             reason: self,
+            suggestion,
         }
     }
 
@@ -142,7 +186,7 @@ impl InstructionParseError {
 #[derive(Debug, Error, Diagnostic, Clone)]
 pub enum BuildProgramErrorTypes {
     #[error("when parsing instruction")]
-    #[diagnostic(code(build_program::parse_error))]
+    #[diagnostic(code("E115"))]
     ParseError {
         #[source_code]
         src: NamedSource<String>,
@@ -151,18 +195,18 @@ pub enum BuildProgramErrorTypes {
         #[source]
         #[diagnostic_source]
         reason: InstructionParseError,
+        /// Concrete suggestion on how the instruction can be fixed
+        #[help]
+        suggestion: Option<String>,
     },
 
     #[error("label '{0}' is defined multiple times")]
-    #[diagnostic(
-        code("build_program::label_definition_error"),
-        help("Make sure that you define the label only once")
-    )]
+    #[diagnostic(code("E101"), help("Make sure that you define the label only once"))]
     LabelDefinedMultipleTimes(String),
 
     #[error("you have defined at least two main labels 'main' and 'MAIN'")]
     #[diagnostic(
-        code("build_program::main_definition_error"),
+        code("E102"),
         help("Make sure that you define at most one main label, either 'main' or 'MAIN'")
     )]
     MainLabelDefinedMultipleTimes,
@@ -170,24 +214,88 @@ pub enum BuildProgramErrorTypes {
     /// Indicates that this instruction is not allowed because it is not contained in the whitelist
     #[error("instruction '{1}' in line '{0}' is not allowed")]
     #[diagnostic(
-        code("build_program::instruction_not_allowed_error"),
+        code("E103"),
         help("Make sure that you include this type ('{2}') of instruction in the whitelist or use a different instruction.\nThese types of instructions are allowed:\n\n{3}")
     )]
     InstructionNotAllowed(usize, String, String, String),
 
     #[error("comparison '{1}' in line '{0}' is not allowed")]
     #[diagnostic(
-        code("build_program::comparison_not_allowed_error"),
+        code("E104"),
         help("Make sure that you include this comparison ('{1}') in the allowed comparisons or use a different instruction.\nTo mark this comparison as allowed you can use: '--allowed-comparisons \"{2}\"'"),
     )]
     ComparisonNotAllowed(usize, String, String),
 
     #[error("operation '{1}' in line '{0}' is not allowed")]
     #[diagnostic(
-        code("build_program::operation_not_allowed_error"),
+        code("E105"),
         help("Make sure that you include this operation ('{1}') in the allowed operations or use a different instruction.\nTo mark this operation as allowed you can use: '--allowed-operations \"{2}\"'"),
     )]
     OperationNotAllowed(usize, String, String),
+
+    /// Indicates that several instructions, comparisons or operations are not allowed.
+    ///
+    /// 0 = number of violations
+    /// 1 = violations grouped by line
+    #[error("{0} violations of the allowed instructions, comparisons and operations")]
+    #[diagnostic(
+        code("E106"),
+        help("{1}\n\nInclude these instruction types, comparisons and operations in the allowed instructions file or use different instructions.\nComparisons and operations can also be allowed with '--allowed-comparisons' and '--allowed-operations'.")
+    )]
+    InstructionConfigViolations(usize, String),
+
+    #[error("loop '{1}' in line '{0}' is never closed")]
+    #[diagnostic(code("E107"), help("Close the loop with '{2}'"))]
+    LoopNotClosed(usize, String, String),
+
+    #[error("'{1}' in line '{0}' does not close a loop")]
+    #[diagnostic(
+        code("E108"),
+        help("Make sure that every 'end' closes a 'while' loop and every 'until' closes a 'repeat' loop")
+    )]
+    LoopCloseUnexpected(usize, String),
+
+    #[error("structured loop '{1}' in line '{0}' is not allowed")]
+    #[diagnostic(
+        code("E109"),
+        help("Use labels and jumps to write the loop, for example: 'loop: if a >= 10 then goto loop_end'")
+    )]
+    StructuredLoopNotAllowed(usize, String),
+
+    #[error("label '{1}' in line '{0}' is not followed by an instruction")]
+    #[diagnostic(
+        code("E110"),
+        help("Place the label in the same line as the instruction it should jump to")
+    )]
+    LabelOnEmptyLine(usize, String),
+
+    #[error("invalid variable declaration '{1}' in line '{0}': {2}")]
+    #[diagnostic(
+        code("E111"),
+        help("Variables are declared like this: #var sum = p(h1)")
+    )]
+    InvalidVariableDeclaration(usize, String, String),
+
+    #[error("instruction '{1}' in line '{0}' can't be executed conditionally")]
+    #[diagnostic(
+        code("E112"),
+        help("Only instructions that don't change the control flow can be used in 'if ... then <instruction>'")
+    )]
+    InstructionNotConditionallyExecutable(usize, String),
+
+    #[error("instruction in line '{0}' can't be written in alpha notation, it would be read differently: '{1}'")]
+    #[diagnostic(
+        code("E113"),
+        help("Make sure that names of memory cells are not also names of accumulators (e.g. 'a1') or the gamma accumulator ('y') and that labels don't contain whitespace")
+    )]
+    InstructionNotWritable(usize, String),
+
+    #[error("the program can't be read from the JSON file: {0}")]
+    #[diagnostic(
+        code("E114"),
+        help("Make sure that the file was written with 'check --emit'")
+    )]
+    AstInvalid(String),
 }
 
 #[allow(clippy::match_same_arms)]
@@ -199,14 +307,20 @@ impl PartialEq for BuildProgramErrorTypes {
                     src: l_src,
                     bad_bit: l_bad_bit,
                     reason: l_reason,
+                    ..
                 },
                 Self::ParseError {
                     src: r_src,
                     bad_bit: r_bad_bit,
                     reason: r_reason,
+                    ..
                 },
             ) => l_src.name() == r_src.name() && l_bad_bit == r_bad_bit && l_reason == r_reason,
             (Self::LabelDefinedMultipleTimes(l0), Self::LabelDefinedMultipleTimes(r0)) => l0 == r0,
+            (
+                Self::InvalidVariableDeclaration(l0, l1, l2),
+                Self::InvalidVariableDeclaration(r0, r1, r2),
+            ) => l0 == r0 && l1 == r1 && l2 == r2,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
@@ -214,7 +328,7 @@ impl PartialEq for BuildProgramErrorTypes {
 
 #[derive(Debug, Diagnostic, Error, PartialEq, Clone)]
 #[error("when building program")]
-#[diagnostic(code("build_program_error"))]
+#[diagnostic(code("E100"))]
 pub struct BuildProgramError {
     #[diagnostic_source]
     pub reason: BuildProgramErrorTypes,
@@ -223,7 +337,7 @@ pub struct BuildProgramError {
 #[derive(Debug, Diagnostic, Error)]
 #[error("when building allowed instructions")]
 #[diagnostic(
-    code("build_allowed_instructions_error"),
+    code("E116"),
     help("Maybe you wanted to use a token, make sure to use one of these: A, M, C, Y, OP, CMP\nFor more help take a look at the documentation: https://github.com/LMH01/alpha_tui/blob/master/docs/cli.md")
 )]
 pub struct BuildAllowedInstructionsError {
@@ -238,7 +352,7 @@ pub struct BuildAllowedInstructionsError {
 
 #[derive(Debug, Diagnostic, Error, Clone, PartialEq)]
 #[error("when parsing instruction")]
-#[diagnostic(code("parse_single_instruction_error"))]
+#[diagnostic(code("E007"))]
 pub struct ParseSingleInstructionError {
     #[source_code]
     pub src: NamedSource<String>,
@@ -247,6 +361,9 @@ pub struct ParseSingleInstructionError {
     #[source]
     #[diagnostic_source]
     pub reason: InstructionParseError,
+    /// Concrete suggestion on how the instruction can be fixed
+    #[help]
+    pub suggestion: Option<String>,
 }
 
 #[cfg(test)]
@@ -346,6 +463,13 @@ mod tests {
                 "xxx".to_string()
             ))
         );
+        assert_eq!(
+            Instruction::try_from("if a < 3 than"),
+            Err(InstructionParseError::InvalidExpression(
+                (9, 12),
+                "than".to_string()
+            ))
+        );
     }
 
     #[test]
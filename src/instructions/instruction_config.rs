@@ -9,7 +9,7 @@ use crate::{
 };
 
 /// Stores information that is used to limit what instructions should be allowed.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct InstructionConfig {
     /// Stores the ids of instructions that are allowed.
     ///
@@ -19,6 +19,10 @@ pub struct InstructionConfig {
     pub allowed_comparisons: Option<Vec<Comparison>>,
     /// Stores operations that are allowed, if value is `None`, all operations are allowed.
     pub allowed_operations: Option<Vec<Operation>>,
+    /// If set, `while` and `repeat` loops are not allowed and labels and jumps have to be used instead.
+    pub deny_structured_loops: bool,
+    /// If set, labels have to be followed by an instruction in the same line.
+    pub deny_labels_on_empty_lines: bool,
 }
 
 impl InstructionConfig {
@@ -36,6 +40,22 @@ impl InstructionConfig {
             };
         raw.into_instruction_config()
     }
+
+    /// Returns up to `count` allowed instruction identifiers that are closest to `identifier`.
+    ///
+    /// Returns an empty list if all instructions or `identifier` itself are allowed.
+    pub fn closest_allowed_identifiers(&self, identifier: &str, count: usize) -> Vec<String> {
+        let Some(whitelist) = &self.allowed_instruction_identifiers else {
+            return Vec::new();
+        };
+        if whitelist.contains(identifier) {
+            return Vec::new();
+        }
+        super::suggestions::closest(identifier, whitelist.iter().map(String::as_str), count)
+            .into_iter()
+            .map(ToString::to_string)
+            .collect()
+    }
 }
 
 /// Data transfer object to parse the instruction config file.
@@ -47,6 +67,10 @@ struct RawInstructionConfig {
     comparisons: Option<Vec<Comparison>>,
     /// Operations that should be allowed
     operations: Option<Vec<Operation>>,
+    /// If structured loops (`while` and `repeat`) should be allowed, if value is `None` they are allowed
+    structured_loops: Option<bool>,
+    /// If labels are allowed on lines without instruction, if value is `None` they are allowed
+    labels_on_empty_lines: Option<bool>,
 }
 
 impl RawInstructionConfig {
@@ -60,6 +84,42 @@ impl RawInstructionConfig {
             allowed_instruction_identifiers,
             allowed_comparisons: self.comparisons,
             allowed_operations: self.operations,
+            deny_structured_loops: self.structured_loops == Some(false),
+            deny_labels_on_empty_lines: self.labels_on_empty_lines == Some(false),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::utils;
+
+    use super::InstructionConfig;
+
+    #[test]
+    fn test_closest_allowed_identifiers() {
+        let config = InstructionConfig {
+            allowed_instruction_identifiers: Some(
+                utils::build_instruction_whitelist(
+                    vec![
+                        "a := 5".to_string(),
+                        "a := p(h1)".to_string(),
+                        "a := a + p(h1)".to_string(),
+                        "goto loop".to_string(),
+                    ],
+                    "",
+                )
+                .unwrap(),
+            ),
+            ..InstructionConfig::default()
+        };
+        assert_eq!(
+            config.closest_allowed_identifiers("A := A OP C", 2),
+            vec!["A := A OP M".to_string(), "A := C".to_string()]
+        );
+        assert!(config.closest_allowed_identifiers("A := C", 2).is_empty());
+        assert!(InstructionConfig::default()
+            .closest_allowed_identifiers("A := A OP C", 2)
+            .is_empty());
+    }
+}
@@ -1,21 +1,38 @@
 use std::fmt::Display;
 
 use miette::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    base::{Accumulator, Comparison, MemoryCell, Operation},
+    base::{Accumulator, Comparison, Operation},
     instructions::error_handling::InstructionParseError,
-    runtime::{error_handling::RuntimeErrorType, ControlFlow, RuntimeMemory, RuntimeSettings},
+    runtime::{
+        error_handling::{CalcError, RuntimeErrorType},
+        memory_usage::MemoryLocation,
+        ControlFlow, EndBehavior, RuntimeMemory, RuntimeSettings,
+    },
 };
 
-use self::parsing::{parse_alpha, parse_gamma, parse_index_memory_cell, parse_memory_cell};
+use self::parsing::{
+    parse_alpha, parse_char, parse_gamma, parse_index_memory_cell, parse_memory_cell,
+};
 
 pub mod error_handling;
 pub mod instruction_config;
+/// Notes that are displayed when the execution reaches a line
+pub mod notes;
 /// Functions related to instruction parsing
 mod parsing;
+/// Renaming of memory cells and labels in the source code of a program
+pub mod refactor;
+/// Desugaring of `while` and `repeat` loops
+pub mod structured_loops;
+/// Suggestions on how instructions that can't be parsed can be fixed
+mod suggestions;
 #[cfg(test)]
 mod tests;
+/// Human readable names for memory locations
+pub mod variables;
 
 // These constants are used to set the value with which instruction parts can be compared.
 // This is used to get the instruction whitelist to work.
@@ -27,11 +44,20 @@ const CONSTANT_IDENTIFIER: &str = "C";
 pub const OPERATOR_IDENTIFIER: &str = "OP";
 pub const COMPARISON_IDENTIFIER: &str = "CMP";
 
-#[derive(Debug, PartialEq, Clone, Eq, Hash)]
+#[derive(Debug, PartialEq, Clone, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind", content = "operands")]
 pub enum Instruction {
     Assign(TargetType, Value),
     Calc(TargetType, Value, Operation, Value),
+    /// Assigns a random number between the two values (both inclusive) to the target.
+    Rand(TargetType, Value, Value),
+    /// Assigns the absolute value of the value to the target.
+    Abs(TargetType, Value),
     JumpIf(Value, Comparison, Value, String),
+    /// Runs the instruction, if the comparison succeeds.
+    ///
+    /// The instruction is not allowed to change the control flow.
+    CondExec(Value, Comparison, Value, Box<Instruction>),
     Goto(String),
     Push,
     Pop,
@@ -64,15 +90,27 @@ impl Instruction {
                     source_b,
                 )?;
             }
+            Self::Rand(target, min, max) => {
+                run_rand(runtime_memory, runtime_settings, target, min, max)?;
+            }
+            Self::Abs(target, source) => run_abs(runtime_memory, runtime_settings, target, source)?,
             Self::JumpIf(value_a, cmp, value_b, label) => {
                 run_jump_if(runtime_memory, control_flow, value_a, cmp, value_b, label)?;
             }
+            Self::CondExec(value_a, cmp, value_b, instruction) => {
+                if cmp.cmp(
+                    value_a.value(runtime_memory)?,
+                    value_b.value(runtime_memory)?,
+                ) {
+                    instruction.run(runtime_memory, control_flow, runtime_settings)?;
+                }
+            }
             Self::Goto(label) => run_goto(control_flow, label)?,
             Self::Push => run_push(runtime_memory, runtime_settings)?,
             Self::Pop => run_pop(runtime_memory, runtime_settings)?,
             Self::StackOp(op) => run_stack_op(runtime_memory, *op)?,
             Self::Call(label) => run_call(control_flow, label)?,
-            Self::Return => run_return(control_flow)?,
+            Self::Return => run_return(control_flow, runtime_settings.end_behavior)?,
             Self::Noop => (),
         }
         Ok(())
@@ -81,7 +119,7 @@ impl Instruction {
     /// If an comparison is used in this instruction it is returned
     pub fn comparison(&self) -> Option<&Comparison> {
         match self {
-            Self::JumpIf(_, cmp, _, _) => Some(cmp),
+            Self::JumpIf(_, cmp, _, _) | Self::CondExec(_, cmp, _, _) => Some(cmp),
             _ => None,
         }
     }
@@ -90,19 +128,201 @@ impl Instruction {
     pub fn operation(&self) -> Option<&Operation> {
         match self {
             Self::Calc(_, _, op, _) | Self::StackOp(op) => Some(op),
+            Self::CondExec(_, _, _, instruction) => instruction.operation(),
+            _ => None,
+        }
+    }
+
+    /// Checks if this instruction can be executed conditionally with `if _ then instruction`.
+    ///
+    /// Only instructions that don't change the control flow are allowed.
+    pub fn is_conditionally_executable(&self) -> bool {
+        !matches!(
+            self,
+            Self::JumpIf(..)
+                | Self::CondExec(..)
+                | Self::Goto(_)
+                | Self::Call(_)
+                | Self::Return
+                | Self::Noop
+        )
+    }
+
+    /// Returns the name of the kind of this instruction, e.g. `assign` or `jump_if`.
+    ///
+    /// The names are the same that are used when the program is written as JSON.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Assign(..) => "assign",
+            Self::Calc(..) => "calc",
+            Self::Rand(..) => "rand",
+            Self::Abs(..) => "abs",
+            Self::JumpIf(..) => "jump_if",
+            Self::CondExec(..) => "cond_exec",
+            Self::Goto(_) => "goto",
+            Self::Push => "push",
+            Self::Pop => "pop",
+            Self::StackOp(_) => "stack_op",
+            Self::Call(_) => "call",
+            Self::Return => "return",
+            Self::Noop => "noop",
+        }
+    }
+
+    /// Returns the memory locations that are read when this instruction is run, in the order in which they are
+    /// written.
+    ///
+    /// The locations that contain the index of an index memory cell are included, even if the index memory cell
+    /// is only written, e.g. `p(h1)` is read by `p(p(h1)) := 5`.
+    /// A location is contained multiple times, if it is read multiple times.
+    pub fn reads(&self) -> Vec<MemoryRef> {
+        let mut reads = Vec::new();
+        let mut read = |value: &Value| {
+            if let Some(location) = value.memory_ref() {
+                reads.extend(location.index());
+                reads.push(location);
+            }
+        };
+        match self {
+            Self::Assign(_, value) | Self::Abs(_, value) => read(value),
+            Self::Calc(_, value_a, _, value_b)
+            | Self::Rand(_, value_a, value_b)
+            | Self::JumpIf(value_a, _, value_b, _) => {
+                read(value_a);
+                read(value_b);
+            }
+            Self::CondExec(value_a, _, value_b, instruction) => {
+                read(value_a);
+                read(value_b);
+                reads.extend(instruction.reads());
+                return reads;
+            }
+            // the value of the first accumulator is pushed onto the stack
+            Self::Push => reads.push(MemoryRef::Accumulator(0)),
+            Self::Pop
+            | Self::StackOp(_)
+            | Self::Goto(_)
+            | Self::Call(_)
+            | Self::Return
+            | Self::Noop => (),
+        }
+        if let Some(index) = self.writes().as_ref().and_then(MemoryRef::index) {
+            reads.push(index);
+        }
+        reads
+    }
+
+    /// Returns the memory location that is written when this instruction is run.
+    ///
+    /// Conditionally executed instructions return the location that is written when the condition is true.
+    pub fn writes(&self) -> Option<MemoryRef> {
+        match self {
+            Self::Assign(target, _)
+            | Self::Calc(target, ..)
+            | Self::Rand(target, ..)
+            | Self::Abs(target, _) => Some(target.memory_ref()),
+            Self::CondExec(.., instruction) => instruction.writes(),
+            // the value that is popped from the stack is written into the first accumulator
+            Self::Pop => Some(MemoryRef::Accumulator(0)),
+            Self::JumpIf(..)
+            | Self::Goto(_)
+            | Self::Push
+            | Self::StackOp(_)
+            | Self::Call(_)
+            | Self::Return
+            | Self::Noop => None,
+        }
+    }
+}
+
+/// A memory location that is accessed by an instruction, as it is written in the program.
+///
+/// The index of index memory cells is only known when the instruction is run, if it is not written directly,
+/// e.g. `p(a0)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum MemoryRef {
+    Accumulator(usize),
+    Gamma,
+    MemoryCell(String),
+    IndexMemoryCell(IndexMemoryCellIndexType),
+}
+
+impl MemoryRef {
+    /// Returns the memory location that is accessed, when the instruction is run with `runtime_memory`.
+    ///
+    /// Fails if the index of an index memory cell can not be read or is negative.
+    #[allow(clippy::cast_sign_loss)]
+    pub fn resolve(
+        &self,
+        runtime_memory: &RuntimeMemory,
+    ) -> Result<MemoryLocation, RuntimeErrorType> {
+        Ok(match self {
+            Self::Accumulator(idx) => MemoryLocation::Accumulator(*idx),
+            Self::Gamma => MemoryLocation::Gamma,
+            Self::MemoryCell(name) => MemoryLocation::MemoryCell(name.clone()),
+            Self::IndexMemoryCell(t) => {
+                let index = match t {
+                    IndexMemoryCellIndexType::Direct(idx) => {
+                        return Ok(MemoryLocation::IndexMemoryCell(*idx))
+                    }
+                    IndexMemoryCellIndexType::Accumulator(idx) => MemoryLocation::Accumulator(*idx),
+                    IndexMemoryCellIndexType::Gamma => MemoryLocation::Gamma,
+                    IndexMemoryCellIndexType::MemoryCell(name) => {
+                        MemoryLocation::MemoryCell(name.clone())
+                    }
+                    IndexMemoryCellIndexType::Index(idx) => MemoryLocation::IndexMemoryCell(*idx),
+                };
+                let idx = runtime_memory.get(&index)?;
+                if idx.is_negative() {
+                    return Err(RuntimeErrorType::IndexMemoryCellNegativeIndex(idx));
+                }
+                MemoryLocation::IndexMemoryCell(idx as usize)
+            }
+        })
+    }
+
+    /// Returns the memory location that contains the index of this index memory cell.
+    ///
+    /// Returns `None` if this is no index memory cell or if the index is written directly, e.g. `p(3)`.
+    pub fn index(&self) -> Option<MemoryRef> {
+        match self {
+            Self::IndexMemoryCell(t) => match t {
+                IndexMemoryCellIndexType::Accumulator(idx) => Some(Self::Accumulator(*idx)),
+                IndexMemoryCellIndexType::Gamma => Some(Self::Gamma),
+                IndexMemoryCellIndexType::MemoryCell(name) => Some(Self::MemoryCell(name.clone())),
+                IndexMemoryCellIndexType::Index(idx) => Some(Self::IndexMemoryCell(
+                    IndexMemoryCellIndexType::Direct(*idx),
+                )),
+                IndexMemoryCellIndexType::Direct(_) => None,
+            },
             _ => None,
         }
     }
 }
 
+impl Display for MemoryRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accumulator(idx) => write!(f, "a{idx}"),
+            Self::Gamma => write!(f, "y"),
+            Self::MemoryCell(n) => write!(f, "p({n})"),
+            Self::IndexMemoryCell(t) => write!(f, "p({t})"),
+        }
+    }
+}
+
 impl Display for Instruction {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Assign(t, v) => write!(f, "{t} := {v}"),
+            Self::Calc(t, v, op, v2) if op.is_function() => write!(f, "{t} := {op}({v}, {v2})"),
             Self::Calc(t, v, op, v2) => write!(f, "{t} := {v} {op} {v2}"),
+            Self::Rand(t, v, v2) => write!(f, "{t} := rand({v}, {v2})"),
+            Self::Abs(t, v) => write!(f, "{t} := abs({v})"),
             Self::Call(l) => write!(f, "call {l}"),
             Self::Goto(l) => write!(f, "goto {l}"),
             Self::JumpIf(v, cmp, v2, l) => write!(f, "if {v} {cmp} {v2} then goto {l}"),
+            Self::CondExec(v, cmp, v2, i) => write!(f, "if {v} {cmp} {v2} then {i}"),
             Self::Noop => write!(f, ""),
             Self::Pop => write!(f, "pop"),
             Self::Push => write!(f, "push"),
@@ -116,6 +336,12 @@ impl Identifier for Instruction {
     fn identifier(&self) -> String {
         match self {
             Self::Assign(t, v) => format!("{} := {}", t.identifier(), v.identifier()),
+            Self::Calc(t, v, op, v2) if op.is_function() => format!(
+                "{} := {op}({}, {})",
+                t.identifier(),
+                v.identifier(),
+                v2.identifier()
+            ),
             Self::Calc(t, v, op, v2) => format!(
                 "{} := {} {} {}",
                 t.identifier(),
@@ -123,6 +349,13 @@ impl Identifier for Instruction {
                 op.identifier(),
                 v2.identifier()
             ),
+            Self::Rand(t, v, v2) => format!(
+                "{} := rand({}, {})",
+                t.identifier(),
+                v.identifier(),
+                v2.identifier()
+            ),
+            Self::Abs(t, v) => format!("{} := abs({})", t.identifier(), v.identifier()),
             Self::Call(_) => "call".to_string(),
             Self::Goto(_) => "goto".to_string(),
             Self::JumpIf(v, cmp, v2, _) => format!(
@@ -131,6 +364,13 @@ impl Identifier for Instruction {
                 cmp.identifier(),
                 v2.identifier()
             ),
+            Self::CondExec(v, cmp, v2, i) => format!(
+                "if {} {} {} then {}",
+                v.identifier(),
+                cmp.identifier(),
+                v2.identifier(),
+                i.identifier()
+            ),
             Self::Noop => "NOOP".to_string(),
             Self::Pop => "pop".to_string(),
             Self::Push => "push".to_string(),
@@ -146,42 +386,9 @@ fn run_assign(
     target: &TargetType,
     source: &Value,
 ) -> Result<(), RuntimeErrorType> {
-    match target {
-        TargetType::Accumulator(a) => {
-            assert_accumulator_exists(runtime_args, runtime_settings, *a)?;
-            runtime_args.accumulators.get_mut(a).unwrap().data = Some(source.value(runtime_args)?);
-        }
-        TargetType::Gamma => {
-            assert_gamma_exists(runtime_args, runtime_settings)?;
-            runtime_args.gamma = Some(Some(source.value(runtime_args)?));
-        }
-        TargetType::MemoryCell(a) => {
-            assert_memory_cell_exists(runtime_args, runtime_settings, a)?;
-            runtime_args.memory_cells.get_mut(a).unwrap().data = Some(source.value(runtime_args)?);
-        }
-        TargetType::IndexMemoryCell(t) => match t {
-            IndexMemoryCellIndexType::Accumulator(idx) => {
-                let idx = index_from_accumulator(runtime_args, *idx)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
-            }
-            IndexMemoryCellIndexType::Direct(idx) => {
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, *idx, source)?;
-            }
-            IndexMemoryCellIndexType::Gamma => {
-                let idx = index_from_gamma(runtime_args)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
-            }
-            IndexMemoryCellIndexType::MemoryCell(name) => {
-                let idx = index_from_memory_cell(runtime_args, name)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
-            }
-            IndexMemoryCellIndexType::Index(idx) => {
-                let idx = index_from_index_memory_cell(runtime_args, *idx)?;
-                assign_index_memory_cell_from_value(runtime_args, runtime_settings, idx, source)?;
-            }
-        },
-    }
-    Ok(())
+    let location = target.memory_ref().resolve(runtime_args)?;
+    let value = source.value(runtime_args)?;
+    runtime_args.assign(&location, value, runtime_settings)
 }
 
 fn run_calc(
@@ -192,49 +399,52 @@ fn run_calc(
     op: Operation,
     source_b: &Value,
 ) -> Result<(), RuntimeErrorType> {
-    match target {
-        TargetType::Accumulator(a) => {
-            assert_accumulator_exists(runtime_args, runtime_settings, *a)?;
-            runtime_args.accumulators.get_mut(a).unwrap().data =
-                Some(op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?);
-        }
-        TargetType::Gamma => {
-            assert_gamma_exists(runtime_args, runtime_settings)?;
-            runtime_args.gamma = Some(Some(
-                op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?,
-            ));
-        }
-        TargetType::MemoryCell(a) => {
-            assert_memory_cell_exists(runtime_args, runtime_settings, a)?;
-            runtime_args.memory_cells.get_mut(a).unwrap().data =
-                Some(op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?);
-        }
-        TargetType::IndexMemoryCell(t) => {
-            let res = op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?;
-            match t {
-                IndexMemoryCellIndexType::Accumulator(idx) => {
-                    let idx = index_from_accumulator(runtime_args, *idx)?;
-                    assign_index_memory_cell(runtime_args, runtime_settings, idx, res)?;
-                }
-                IndexMemoryCellIndexType::Direct(idx) => {
-                    assign_index_memory_cell(runtime_args, runtime_settings, *idx, res)?;
-                }
-                IndexMemoryCellIndexType::Gamma => {
-                    let idx = index_from_gamma(runtime_args)?;
-                    assign_index_memory_cell(runtime_args, runtime_settings, idx, res)?;
-                }
-                IndexMemoryCellIndexType::MemoryCell(name) => {
-                    let idx = index_from_memory_cell(runtime_args, name)?;
-                    assign_index_memory_cell(runtime_args, runtime_settings, idx, res)?;
-                }
-                IndexMemoryCellIndexType::Index(idx) => {
-                    let idx = index_from_index_memory_cell(runtime_args, *idx)?;
-                    assign_index_memory_cell(runtime_args, runtime_settings, idx, res)?;
-                }
-            }
-        }
+    let location = target.memory_ref().resolve(runtime_args)?;
+    let value = op.calc(source_a.value(runtime_args)?, source_b.value(runtime_args)?)?;
+    runtime_args.assign(&location, value, runtime_settings)
+}
+
+fn run_rand(
+    runtime_args: &mut RuntimeMemory,
+    runtime_settings: &RuntimeSettings,
+    target: &TargetType,
+    min: &Value,
+    max: &Value,
+) -> Result<(), RuntimeErrorType> {
+    let min = min.value(runtime_args)?;
+    let max = max.value(runtime_args)?;
+    if min > max {
+        return Err(RuntimeErrorType::RandInvalidRange(min, max));
     }
-    Ok(())
+    let res = runtime_settings.rng.next_in_range(min, max);
+    run_assign(
+        runtime_args,
+        runtime_settings,
+        target,
+        &Value::Constant(res),
+    )
+}
+
+fn run_abs(
+    runtime_args: &mut RuntimeMemory,
+    runtime_settings: &RuntimeSettings,
+    target: &TargetType,
+    source: &Value,
+) -> Result<(), RuntimeErrorType> {
+    let Some(res) = source.value(runtime_args)?.checked_abs() else {
+        return Err(RuntimeErrorType::IllegalCalculation {
+            cause: CalcError::AttemptToOverflow(
+                "calculate the absolute value".to_string(),
+                "Absolute value".to_string(),
+            ),
+        });
+    };
+    run_assign(
+        runtime_args,
+        runtime_settings,
+        target,
+        &Value::Constant(res),
+    )
 }
 
 fn run_jump_if(
@@ -283,31 +493,42 @@ fn run_pop(
 }
 
 /// Causes runtime error if stack does not contain two values.
+///
+/// The values are only removed from the stack if the calculation succeeds, so that the instruction can be run again.
 fn run_stack_op(runtime_memory: &mut RuntimeMemory, op: Operation) -> Result<(), RuntimeErrorType> {
-    match runtime_memory.stack.pop() {
-        Some(a) => match runtime_memory.stack.pop() {
-            Some(b) => {
-                // place result of calculation in a0, because value is calculated using that accumulator in alpha notation
-                // so value needs to be placed manually in it
-                let res = op.calc(b, a)?;
-                runtime_memory.accumulators.get_mut(&0).unwrap().data = Some(res);
-                runtime_memory.stack.push(res);
-                Ok(())
-            }
-            None => Err(RuntimeErrorType::StackOpFail(op)),
-        },
-        None => Err(RuntimeErrorType::StackOpFail(op)),
-    }
+    let [b, a] = match runtime_memory.stack.as_slice() {
+        [.., b, a] => [*b, *a],
+        _ => return Err(RuntimeErrorType::StackOpFail(op)),
+    };
+    // place result of calculation in a0, because value is calculated using that accumulator in alpha notation
+    // so value needs to be placed manually in it
+    let res = op.calc(b, a)?;
+    runtime_memory
+        .stack
+        .truncate(runtime_memory.stack.len() - 2);
+    runtime_memory.accumulators.get_mut(&0).unwrap().data = Some(res);
+    runtime_memory.stack.push(res);
+    Ok(())
 }
 
 fn run_call(control_flow: &mut ControlFlow, label: &str) -> Result<(), RuntimeErrorType> {
     control_flow.call_function(label)
 }
 
-fn run_return(control_flow: &mut ControlFlow) -> Result<(), RuntimeErrorType> {
-    match control_flow.call_stack.pop() {
-        Some(i) => control_flow.next_instruction_index = i,
-        None => run_goto(control_flow, "END")?,
+/// Returns from the current function, `end_behavior` determines what happens if the call stack is empty.
+fn run_return(
+    control_flow: &mut ControlFlow,
+    end_behavior: EndBehavior,
+) -> Result<(), RuntimeErrorType> {
+    if control_flow.call_stack.is_empty() && end_behavior == EndBehavior::Error {
+        return Err(RuntimeErrorType::ReturnWithEmptyCallStack);
+    }
+    control_flow.tail_calls.pop();
+    match (control_flow.call_stack.pop(), end_behavior) {
+        (Some(i), _) => control_flow.next_instruction_index = i,
+        (None, EndBehavior::JumpToEnd) => run_goto(control_flow, "END")?,
+        (None, EndBehavior::Halt) => control_flow.halted = true,
+        (None, EndBehavior::Error) => return Err(RuntimeErrorType::ReturnWithEmptyCallStack),
     }
     Ok(())
 }
@@ -330,156 +551,6 @@ fn assert_accumulator_exists(
     }
 }
 
-/// Tests if the accumulator with **index** exists and contains a value.
-///
-/// Ok(i32) contains the accumulator value.
-///
-/// Err(String) contains error message.
-fn assert_accumulator_contains_value(
-    runtime_memory: &RuntimeMemory,
-    index: usize,
-) -> Result<i32, RuntimeErrorType> {
-    if let Some(value) = runtime_memory.accumulators.get(&index) {
-        if value.data.is_some() {
-            Ok(runtime_memory
-                .accumulators
-                .get(&index)
-                .unwrap()
-                .data
-                .unwrap())
-        } else {
-            Err(RuntimeErrorType::AccumulatorUninitialized(index))
-        }
-    } else {
-        Err(RuntimeErrorType::AccumulatorDoesNotExist(index))
-    }
-}
-
-/// Tests if gamma exists.
-///
-/// If `RuntimeSettings::autodetect_gamma` is enabled, and gamma does not exist, it is created.
-fn assert_gamma_exists(
-    runtime_memory: &mut RuntimeMemory,
-    runtime_settings: &RuntimeSettings,
-) -> Result<(), RuntimeErrorType> {
-    if runtime_memory.gamma.is_some() {
-        return Ok(());
-    } else if runtime_settings.autodetect_gamma_accumulator {
-        runtime_memory.gamma = Some(None);
-        return Ok(());
-    }
-    Err(RuntimeErrorType::GammaDoesNotExist)
-}
-
-/// Tests if gamma contains a value.
-fn assert_gamma_contains_value(runtime_memory: &RuntimeMemory) -> Result<i32, RuntimeErrorType> {
-    if let Some(value) = runtime_memory.gamma {
-        if let Some(value) = value {
-            return Ok(value);
-        }
-        return Err(RuntimeErrorType::GammaUninitialized);
-    }
-    Err(RuntimeErrorType::GammaDoesNotExist)
-}
-
-/// Tests if the memory cell with **label** exists.
-///
-/// If it does not exist and `memory_on_demand` is enabled, it is created.
-fn assert_memory_cell_exists(
-    runtime_memory: &mut RuntimeMemory,
-    runtime_settings: &RuntimeSettings,
-    label: &str,
-) -> Result<(), RuntimeErrorType> {
-    if let Some(_value) = runtime_memory.memory_cells.get(label) {
-        Ok(())
-    } else if runtime_settings.autodetect_memory_cells {
-        runtime_memory
-            .memory_cells
-            .insert(label.to_string(), MemoryCell::new(label));
-        Ok(())
-    } else {
-        Err(RuntimeErrorType::MemoryCellDoesNotExist(label.to_string()))
-    }
-}
-
-/// Tests if the memory cell with **label** exists and contains a value.
-///
-/// Ok(i32) contains the memory cell value.
-///
-/// Err(String) contains error message.
-fn assert_memory_cell_contains_value(
-    runtime_memory: &RuntimeMemory,
-    label: &str,
-) -> Result<i32, RuntimeErrorType> {
-    if let Some(value) = runtime_memory.memory_cells.get(label) {
-        if value.data.is_some() {
-            Ok(runtime_memory
-                .memory_cells
-                .get(label)
-                .unwrap()
-                .data
-                .unwrap())
-        } else {
-            Err(RuntimeErrorType::MemoryCellUninitialized(label.to_string()))
-        }
-    } else {
-        Err(RuntimeErrorType::MemoryCellDoesNotExist(label.to_string()))
-    }
-}
-
-fn assert_index_memory_cell_contains_value(
-    runtime_memory: &RuntimeMemory,
-    index: usize,
-) -> Result<i32, RuntimeErrorType> {
-    if let Some(value) = runtime_memory.index_memory_cells.get(&index) {
-        if let Some(value) = value {
-            Ok(*value)
-        } else {
-            Err(RuntimeErrorType::IndexMemoryCellUninitialized(index))
-        }
-    } else {
-        Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(index))
-    }
-}
-
-/// Tries to assign a value to the memory cell, if the imc does not exist and `runtime_args.enable_imc_auto_creation` is true, the memory cell is created and the value is assigned.
-/// Otherwise returns an runtime error.
-fn assign_index_memory_cell(
-    runtime_memory: &mut RuntimeMemory,
-    runtime_settings: &RuntimeSettings,
-    idx: usize,
-    value: i32,
-) -> Result<(), RuntimeErrorType> {
-    if runtime_memory.index_memory_cells.contains_key(&idx)
-        || runtime_settings.autodetect_index_memory_cells
-    {
-        runtime_memory.index_memory_cells.insert(idx, Some(value));
-    } else {
-        return Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(idx));
-    }
-    Ok(())
-}
-
-/// Tries to assign a value to the memory cell, if the imc does not exist and `runtime_args.enable_imc_auto_creation` is true, the memory cell is created and the value is assigned.
-/// Otherwise returns an runtime error.
-fn assign_index_memory_cell_from_value(
-    runtime_memory: &mut RuntimeMemory,
-    runtime_settings: &RuntimeSettings,
-    idx: usize,
-    source: &Value,
-) -> Result<(), RuntimeErrorType> {
-    if runtime_memory.index_memory_cells.contains_key(&idx)
-        || runtime_settings.autodetect_index_memory_cells
-    {
-        runtime_memory
-            .index_memory_cells
-            .insert(idx, Some(source.value(runtime_memory)?));
-    } else {
-        return Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(idx));
-    }
-    Ok(())
-}
-
 /// This trait is used to be easily able to compare instructions with one another.
 ///
 /// This is needed when checking if instructions are allowed because the `Eq` implementation determines that `TargetType::Accumulator(0)`
@@ -491,7 +562,8 @@ pub trait Identifier {
 }
 
 /// Specifies the location where the index memory cell should look for the value of the index of the index memory cell
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum IndexMemoryCellIndexType {
     /// Indicates that this index memory cell uses the value of an accumulator as index where the data is accessed.
     Accumulator(usize),
@@ -535,7 +607,8 @@ impl Identifier for IndexMemoryCellIndexType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum TargetType {
     Accumulator(usize),
     Gamma,
@@ -561,6 +634,16 @@ impl TryFrom<(&String, (usize, usize))> for TargetType {
 }
 
 impl TargetType {
+    /// Returns the memory location that is written by this target.
+    pub fn memory_ref(&self) -> MemoryRef {
+        match self {
+            Self::Accumulator(idx) => MemoryRef::Accumulator(*idx),
+            Self::Gamma => MemoryRef::Gamma,
+            Self::MemoryCell(name) => MemoryRef::MemoryCell(name.clone()),
+            Self::IndexMemoryCell(t) => MemoryRef::IndexMemoryCell(t.clone()),
+        }
+    }
+
     /// Returns true if this target type is `IndexMemoryCell(IndexMemoryCellIndexType::Gamma)`.
     pub fn is_imc_gamma(&self) -> bool {
         matches!(
@@ -594,7 +677,8 @@ impl Identifier for TargetType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Value {
     Accumulator(usize),
     Gamma,
@@ -604,42 +688,27 @@ pub enum Value {
 }
 
 impl Value {
-    fn value(&self, runtime_args: &RuntimeMemory) -> Result<i32, RuntimeErrorType> {
-        match self {
-            Self::Accumulator(a) => {
-                assert_accumulator_contains_value(runtime_args, *a)?;
-                Ok(runtime_args.accumulators.get(a).unwrap().data.unwrap())
-            }
-            Self::Gamma => assert_gamma_contains_value(runtime_args),
-            Self::Constant(a) => Ok(*a),
-            Self::MemoryCell(a) => {
-                assert_memory_cell_contains_value(runtime_args, a)?;
-                Ok(runtime_args.memory_cells.get(a).unwrap().data.unwrap())
+    pub fn value(&self, runtime_args: &RuntimeMemory) -> Result<i32, RuntimeErrorType> {
+        let location = match self {
+            Self::Constant(c) => return Ok(*c),
+            Self::Accumulator(idx) => MemoryLocation::Accumulator(*idx),
+            Self::Gamma => MemoryLocation::Gamma,
+            Self::MemoryCell(name) => MemoryLocation::MemoryCell(name.clone()),
+            Self::IndexMemoryCell(t) => {
+                MemoryRef::IndexMemoryCell(t.clone()).resolve(runtime_args)?
             }
-            Self::IndexMemoryCell(t) => match t {
-                IndexMemoryCellIndexType::Accumulator(idx) => {
-                    let idx = index_from_accumulator(runtime_args, *idx)?;
-                    Ok(assert_index_memory_cell_contains_value(
-                        runtime_args,
-                        idx as usize,
-                    )?)
-                }
-                IndexMemoryCellIndexType::Direct(idx) => {
-                    Ok(assert_index_memory_cell_contains_value(runtime_args, *idx)?)
-                }
-                IndexMemoryCellIndexType::Gamma => {
-                    let idx = index_from_gamma(runtime_args)?;
-                    Ok(assert_index_memory_cell_contains_value(runtime_args, idx)?)
-                }
-                IndexMemoryCellIndexType::Index(idx) => {
-                    let idx = index_from_index_memory_cell(runtime_args, *idx)?;
-                    Ok(assert_index_memory_cell_contains_value(runtime_args, idx)?)
-                }
-                IndexMemoryCellIndexType::MemoryCell(name) => {
-                    let idx = index_from_memory_cell(runtime_args, name)?;
-                    Ok(assert_index_memory_cell_contains_value(runtime_args, idx)?)
-                }
-            },
+        };
+        runtime_args.get(&location)
+    }
+
+    /// Returns the memory location from which this value is read, `None` for constants.
+    pub fn memory_ref(&self) -> Option<MemoryRef> {
+        match self {
+            Self::Accumulator(idx) => Some(MemoryRef::Accumulator(*idx)),
+            Self::Gamma => Some(MemoryRef::Gamma),
+            Self::MemoryCell(name) => Some(MemoryRef::MemoryCell(name.clone())),
+            Self::IndexMemoryCell(t) => Some(MemoryRef::IndexMemoryCell(t.clone())),
+            Self::Constant(_) => None,
         }
     }
 
@@ -665,6 +734,9 @@ impl TryFrom<(&String, (usize, usize))> for Value {
         if let Ok(v) = value.0.parse::<i32>() {
             return Ok(Self::Constant(v));
         }
+        if let Some(v) = parse_char(value.0) {
+            return Ok(Self::Constant(v));
+        }
         if parse_gamma(value.0, value.1).is_ok() {
             return Ok(Self::Gamma);
         }
@@ -702,56 +774,3 @@ impl Identifier for Value {
         }
     }
 }
-
-/// Gets the content from the accumulator with the index `idx` and checks if this value is positive,
-/// return the value if it is.
-#[allow(clippy::cast_sign_loss)]
-fn index_from_accumulator(
-    runtime_args: &RuntimeMemory,
-    idx: usize,
-) -> Result<usize, RuntimeErrorType> {
-    let idx = assert_accumulator_contains_value(runtime_args, idx)?;
-    if idx.is_negative() {
-        return Err(RuntimeErrorType::IndexMemoryCellNegativeIndex(idx));
-    }
-    Ok(idx as usize)
-}
-
-/// Gets the content from the gamma accumulator and checks if the value is positive,
-/// return the value if it is.
-#[allow(clippy::cast_sign_loss)]
-fn index_from_gamma(runtime_args: &RuntimeMemory) -> Result<usize, RuntimeErrorType> {
-    let idx = assert_gamma_contains_value(runtime_args)?;
-    if idx.is_negative() {
-        return Err(RuntimeErrorType::IndexMemoryCellNegativeIndex(idx));
-    }
-    Ok(idx as usize)
-}
-
-/// Gets the content of the memory cell with name `name` and check if this value is positive,
-/// returns the value if it is.
-#[allow(clippy::cast_sign_loss)]
-fn index_from_memory_cell(
-    runtime_args: &RuntimeMemory,
-    name: &str,
-) -> Result<usize, RuntimeErrorType> {
-    let idx = assert_memory_cell_contains_value(runtime_args, name)?;
-    if idx.is_negative() {
-        return Err(RuntimeErrorType::IndexMemoryCellNegativeIndex(idx));
-    }
-    Ok(idx as usize)
-}
-
-/// Gets the content of the index memory cell with index `idx` and checks if this value is positive,
-/// returns the value if it is.
-#[allow(clippy::cast_sign_loss)]
-fn index_from_index_memory_cell(
-    runtime_args: &RuntimeMemory,
-    idx: usize,
-) -> Result<usize, RuntimeErrorType> {
-    let idx = assert_index_memory_cell_contains_value(runtime_args, idx)?;
-    if idx.is_negative() {
-        return Err(RuntimeErrorType::IndexMemoryCellNegativeIndex(idx));
-    }
-    Ok(idx as usize)
-}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+use crate::utils;
+
+/// Notes that are displayed when the execution reaches a line, used to prepare guided walkthroughs.
+///
+/// Notes are written in comment lines of the program, e.g. `#note: the sum is calculated here`,
+/// and belong to the next line that contains an instruction.
+/// They don't change how the program is run.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Notes {
+    /// Maps the index of an instruction to its note.
+    notes: HashMap<usize, String>,
+}
+
+impl Notes {
+    /// Parses all notes in the lines of the program.
+    ///
+    /// Consecutive notes that belong to the same line are joined with line breaks.
+    /// Notes that are not followed by a line are ignored.
+    pub fn parse(lines: &[String]) -> Self {
+        let mut notes = HashMap::new();
+        let mut pending: Vec<&str> = Vec::new();
+        // lines starting with # don't count towards the instruction indices, as they are not displayed in the program
+        let mut index = 0;
+        for line in lines {
+            if let Some(comment) = line.trim().strip_prefix('#') {
+                if let Some(note) = comment.trim_start().strip_prefix("note:") {
+                    pending.push(note.trim());
+                }
+                continue;
+            }
+            // empty lines are skipped when the program is run, so the note belongs to the next instruction
+            if !pending.is_empty() && !utils::remove_comment(line).is_empty() {
+                notes.insert(index, pending.join("\n"));
+                pending.clear();
+            }
+            index += 1;
+        }
+        Self { notes }
+    }
+
+    /// Returns the note that belongs to the instruction with index `idx`.
+    pub fn note(&self, idx: usize) -> Option<&str> {
+        self.notes.get(&idx).map(String::as_str)
+    }
+
+    /// Returns true if the program does not contain any notes.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils;
+
+    use super::Notes;
+
+    #[test]
+    fn test_parse() {
+        let notes = Notes::parse(&test_utils::string_literal_to_vec(
+            "#note: first the accumulator is set\na := 1\n#var sum = p(h1)\n# note: then the value\n#note:  is stored\n\n// comment\np(h1) := a\n#note: not followed by a line",
+        ));
+        assert_eq!(notes.note(0), Some("first the accumulator is set"));
+        assert_eq!(notes.note(1), None);
+        assert_eq!(notes.note(2), None);
+        assert_eq!(notes.note(3), Some("then the value\nis stored"));
+        assert!(!notes.is_empty());
+        assert!(
+            Notes::parse(&test_utils::string_literal_to_vec("a := 1 # note: inline")).is_empty()
+        );
+    }
+}
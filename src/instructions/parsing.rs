@@ -1,6 +1,7 @@
 use crate::{
     base::{Comparison, Operation},
     instructions::error_handling::InstructionParseError,
+    utils,
 };
 
 use super::{IndexMemoryCellIndexType, Instruction, TargetType, Value};
@@ -24,6 +25,12 @@ impl TryFrom<&Vec<&str>> for Instruction {
                 None => (*s).to_string(),
             })
             .collect();
+        if parts.is_empty() {
+            return Err(InstructionParseError::UnknownInstruction(
+                (0, 0),
+                String::new(),
+            ));
+        }
 
         // Check if instruction is comparison
         if parts[0] == "if" {
@@ -36,18 +43,31 @@ impl TryFrom<&Vec<&str>> for Instruction {
             if parts[4] != "then" {
                 return Err(InstructionParseError::InvalidExpression(
                     part_range(&parts, 4),
-                    parts[5].to_string(),
+                    parts[4].to_string(),
                 ));
             }
             check_expression_missing(&parts, 5, Some("goto"))?;
+            let value_b = Value::try_from((&parts[3], part_range(&parts, 3)))?;
             if parts[5] != "goto" {
-                return Err(InstructionParseError::InvalidExpression(
-                    part_range(&parts, 5),
-                    parts[5].to_string(),
+                // instruction is of type if a cmp b then instruction
+                let offset = part_range(&parts, 5).0;
+                let instruction_parts: Vec<&str> = parts[5..].iter().map(String::as_str).collect();
+                let instruction =
+                    Instruction::try_from(&instruction_parts).map_err(|e| e.shift_range(offset))?;
+                if !instruction.is_conditionally_executable() {
+                    return Err(InstructionParseError::InvalidExpression(
+                        (offset, whole_range(&parts).1),
+                        parts[5..].join(" "),
+                    ));
+                }
+                return Ok(Instruction::CondExec(
+                    value_a,
+                    cmp,
+                    value_b,
+                    Box::new(instruction),
                 ));
             }
             check_expression_missing(&parts, 6, Some("a label"))?;
-            let value_b = Value::try_from((&parts[3], part_range(&parts, 3)))?;
             return Ok(Instruction::JumpIf(
                 value_a,
                 cmp,
@@ -129,6 +149,31 @@ impl TryFrom<&Vec<&str>> for Instruction {
                 help: "Try inserting an accumulator or a memory cell".to_string(),
             });
         }
+        if let Some((name, _)) = parts[2].split_once('(') {
+            // instruction is of type a := f(b, c) or a := f(b)
+            let expression = parts[2..].join(" ");
+            let start = part_range(&parts, 2).0;
+            match name {
+                "rand" => {
+                    let mut values = parse_function_args(&expression, name, 2, start)?;
+                    let max = values.pop().unwrap();
+                    let min = values.pop().unwrap();
+                    return Ok(Instruction::Rand(target, min, max));
+                }
+                "min" | "max" => {
+                    let op = parse_operation(name, (start, start + name.len() - 1))?;
+                    let mut values = parse_function_args(&expression, name, 2, start)?;
+                    let source_b = values.pop().unwrap();
+                    let source_a = values.pop().unwrap();
+                    return Ok(Instruction::Calc(target, source_a, op, source_b));
+                }
+                "abs" => {
+                    let mut values = parse_function_args(&expression, name, 1, start)?;
+                    return Ok(Instruction::Abs(target, values.pop().unwrap()));
+                }
+                _ => (),
+            }
+        }
         let source_a = Value::try_from((&parts[2], part_range(&parts, 2)))?;
         if parts.len() == 3 {
             // instruction is of type a := b
@@ -156,7 +201,7 @@ impl TryFrom<&str> for Instruction {
 
     /// Tries to parse an instruction from the input string.
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Self::try_from(&value.split_whitespace().collect::<Vec<&str>>())
+        Self::try_from(&utils::split_tokens(value))
     }
 }
 
@@ -207,6 +252,20 @@ pub fn parse_gamma(s: &str, part_range: (usize, usize)) -> Result<(), Instructio
     }
 }
 
+/// Tries to parse a condition of the form `value cmp value` that starts at part `idx`.
+pub fn parse_condition(
+    parts: &[String],
+    idx: usize,
+) -> Result<(Value, Comparison, Value), InstructionParseError> {
+    check_expression_missing(parts, idx, Some("an accumulator"))?;
+    let value_a = Value::try_from((&parts[idx], part_range(parts, idx)))?;
+    check_expression_missing(parts, idx + 1, Some("a comparison"))?;
+    let cmp = parse_comparison(&parts[idx + 1], part_range(parts, idx + 1))?;
+    check_expression_missing(parts, idx + 2, None)?;
+    let value_b = Value::try_from((&parts[idx + 2], part_range(parts, idx + 2)))?;
+    Ok((value_a, cmp, value_b))
+}
+
 /// Tries to parse the operation.
 ///
 /// `part_range` indicates the area that is affected.
@@ -352,6 +411,60 @@ pub fn parse_index_memory_cell(
     }
 }
 
+/// Tries to parse a character literal like `'A'`.
+///
+/// Returns the numeric code of the character, if `s` is a character literal.
+pub fn parse_char(s: &str) -> Option<i32> {
+    let mut chars = s.strip_prefix('\'')?.strip_suffix('\'')?.chars();
+    let c = chars.next()?;
+    if chars.next().is_some() {
+        return None;
+    }
+    i32::try_from(u32::from(c)).ok()
+}
+
+/// Tries to parse the arguments of a function call expression like `rand(1, 6)` or `abs(a)`.
+///
+/// `s` contains the whole expression, `name` the name of the function and `start` is the character index at which the expression starts.
+/// Returns an error if the number of arguments is not `arg_count`.
+pub fn parse_function_args(
+    s: &str,
+    name: &str,
+    arg_count: usize,
+    start: usize,
+) -> Result<Vec<Value>, InstructionParseError> {
+    let range = (start, start + s.len() - 1);
+    let Some(inner) = s
+        .strip_prefix(name)
+        .and_then(|rest| rest.strip_prefix('('))
+        .and_then(|inner| inner.strip_suffix(')'))
+    else {
+        return Err(InstructionParseError::InvalidExpression(
+            range,
+            s.to_string(),
+        ));
+    };
+    let args = inner.split(',').collect::<Vec<&str>>();
+    if args.len() != arg_count {
+        return Err(InstructionParseError::InvalidExpression(
+            range,
+            s.to_string(),
+        ));
+    }
+    let mut values = Vec::new();
+    // skip "name("
+    let mut offset = start + name.len() + 1;
+    for arg in args {
+        let value = arg.trim().to_string();
+        let value_start = offset + arg.len() - arg.trim_start().len();
+        let value_range = (value_start, value_start + value.len().max(1) - 1);
+        values.push(Value::try_from((&value, value_range))?);
+        // skip ","
+        offset += arg.len() + 1;
+    }
+    Ok(values)
+}
+
 /// Calculates the character index range of a part.
 ///
 /// `part_idx` specifies in what part the error occurs.
@@ -397,10 +510,23 @@ fn check_expression_missing(
 mod tests {
     use crate::instructions::{
         error_handling::InstructionParseError,
-        parsing::{parse_alpha, parse_gamma, parse_index_memory_cell, parse_memory_cell},
+        parsing::{
+            parse_alpha, parse_char, parse_gamma, parse_index_memory_cell, parse_memory_cell,
+        },
         IndexMemoryCellIndexType,
     };
 
+    #[test]
+    fn test_parse_char() {
+        assert_eq!(parse_char("'A'"), Some(65));
+        assert_eq!(parse_char("'z'"), Some(122));
+        assert_eq!(parse_char("'ä'"), Some(228));
+        assert_eq!(parse_char("'AB'"), None);
+        assert_eq!(parse_char("''"), None);
+        assert_eq!(parse_char("A"), None);
+        assert_eq!(parse_char("'A"), None);
+    }
+
     #[test]
     fn test_parse_memory_cell() {
         assert_eq!(parse_memory_cell("p(h1)", (0, 4)), Ok("h1".to_string()));
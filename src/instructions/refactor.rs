@@ -0,0 +1,1203 @@
+use std::{collections::HashMap, fmt::Display};
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{base::IdentifierNotation, utils};
+
+use super::{
+    structured_loops::LoopKeyword, IndexMemoryCellIndexType, Instruction, TargetType, Value,
+};
+
+/// Labels that have a special meaning, they are neither renamed nor used as new name.
+const RESERVED_LABELS: [&str; 8] = ["main", "MAIN", "END", "ENDE", "end", "ende", "End", "Ende"];
+
+/// What is renamed by a [`Rename`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenameKind {
+    MemoryCell,
+    Label,
+}
+
+impl Display for RenameKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MemoryCell => write!(f, "memory cell"),
+            Self::Label => write!(f, "label"),
+        }
+    }
+}
+
+#[derive(Debug, Error, Diagnostic, Clone, PartialEq)]
+pub enum RefactorError {
+    #[error("'{0}' is neither used as memory cell nor as label")]
+    #[diagnostic(code("E501"), help("Make sure that the name is spelled correctly"))]
+    NotFound(String),
+
+    #[error("{0} '{1}' is not used in the program")]
+    #[diagnostic(code("E502"), help("Make sure that the name is spelled correctly"))]
+    NotUsed(RenameKind, String),
+
+    #[error("'{0}' is used as memory cell and as label")]
+    #[diagnostic(
+        code("E503"),
+        help("Write the memory cell as 'p({0})' or use '--memory-cell' or '--label' to specify what should be renamed")
+    )]
+    Ambiguous(String),
+
+    #[error("'{1}' is not a valid {0} name")]
+    #[diagnostic(
+        code("E504"),
+        help("Memory cell names may only contain letters, digits, '_' and '-' and have to contain at least one letter.\nLabels must not contain whitespaces or the characters ':', ';' and '#'.")
+    )]
+    InvalidName(RenameKind, String),
+
+    #[error("label '{0}' has a special meaning")]
+    #[diagnostic(
+        code("E505"),
+        help("The labels 'main' and 'MAIN' mark the start of the program and the labels {} mark its end, they can not be renamed", RESERVED_LABELS[2..].iter().map(|l| format!("'{l}'")).collect::<Vec<_>>().join(", "))
+    )]
+    ReservedLabel(String),
+
+    #[error("{0} '{1}' already exists")]
+    #[diagnostic(code("E506"), help("Choose a name that is not used yet"))]
+    AlreadyExists(RenameKind, String),
+
+    #[error("line {0} can not be parsed: '{1}'")]
+    #[diagnostic(
+        code("E507"),
+        help("Make sure that the program compiles before it is refactored")
+    )]
+    ParseError(usize, String),
+
+    #[error("line {0} can not be rewritten: '{1}'")]
+    #[diagnostic(code("E508"), help("Rename this occurrence manually"))]
+    RewriteError(usize, String),
+
+    #[error("lines {0} to {1} are not part of the program")]
+    #[diagnostic(code("E509"), help("Lines that start with '#' are not counted"))]
+    InvalidRange(usize, usize),
+
+    #[error("the selected lines don't contain an instruction")]
+    #[diagnostic(
+        code("E510"),
+        help("Select the lines that should be moved into the subroutine")
+    )]
+    EmptySelection,
+
+    #[error("line {0} returns from a subroutine")]
+    #[diagnostic(
+        code("E511"),
+        help("Lines that contain 'return' can not be moved into a subroutine, they would return from the new subroutine instead")
+    )]
+    ReturnInSelection(usize),
+
+    #[error("line {0} jumps to label '{1}' that is not defined in the selected lines")]
+    #[diagnostic(
+        code("E512"),
+        help("The subroutine has to return to the call, so the selected lines may only jump to labels that are defined in the selected lines after the first line")
+    )]
+    JumpOutOfSelection(usize, String),
+
+    #[error("line {0} jumps to label '{1}' that is defined in the selected lines")]
+    #[diagnostic(
+        code("E513"),
+        help("Only the label of the first selected line may be used outside of the selected lines, it is placed in front of the call")
+    )]
+    JumpIntoSelection(usize, String),
+
+    #[error("loop '{1}' in line {0} is only partially selected")]
+    #[diagnostic(
+        code("E514"),
+        help("Select the whole loop or only lines inside of the loop")
+    )]
+    LoopSplit(usize, String),
+
+    #[error("the refactored program could not be built: {0}")]
+    #[diagnostic(code("E515"))]
+    BuildError(String),
+}
+
+/// Renames a memory cell or a label in a program.
+///
+/// Occurrences are located using the parsed instructions, only the parts of a line that refer to the
+/// renamed memory cell or label are replaced, so that the formatting and comments of the program are preserved.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rename {
+    pub kind: RenameKind,
+    pub old: String,
+    pub new: String,
+}
+
+impl Rename {
+    /// Creates a new rename of `old` to `new` for the program in `lines`.
+    ///
+    /// If `kind` is not set, it is determined by how `old` is used in the program.
+    /// Memory cells can also be written as `p(name)`.
+    ///
+    /// Returns an error if `old` is not used, if `new` is not a valid name or if `new` is already used.
+    pub fn new(
+        lines: &[String],
+        old: &str,
+        new: &str,
+        kind: Option<RenameKind>,
+    ) -> Result<Self, RefactorError> {
+        let (old, kind) = match (strip_memory_cell(old), kind) {
+            (Some(_), Some(RenameKind::Label)) => {
+                return Err(RefactorError::InvalidName(
+                    RenameKind::Label,
+                    old.to_string(),
+                ))
+            }
+            (Some(name), _) => (name, Some(RenameKind::MemoryCell)),
+            (None, kind) => (old, kind),
+        };
+        let parsed = parse_lines(lines)?;
+        let is_used = |kind, name| {
+            parsed
+                .iter()
+                .any(|line| line.clone().rename(kind, name, name))
+        };
+        let kind = match kind {
+            Some(kind) if is_used(kind, old) => kind,
+            Some(kind) => return Err(RefactorError::NotUsed(kind, old.to_string())),
+            None => match (
+                is_used(RenameKind::MemoryCell, old),
+                is_used(RenameKind::Label, old),
+            ) {
+                (true, true) => return Err(RefactorError::Ambiguous(old.to_string())),
+                (true, false) => RenameKind::MemoryCell,
+                (false, true) => RenameKind::Label,
+                (false, false) => return Err(RefactorError::NotFound(old.to_string())),
+            },
+        };
+        let new = match kind {
+            RenameKind::MemoryCell => strip_memory_cell(new).unwrap_or(new),
+            RenameKind::Label => new,
+        };
+        if kind == RenameKind::Label {
+            if let Some(label) = [old, new].into_iter().find(|l| RESERVED_LABELS.contains(l)) {
+                return Err(RefactorError::ReservedLabel(label.to_string()));
+            }
+        }
+        if !is_valid_name(kind, new) {
+            return Err(RefactorError::InvalidName(kind, new.to_string()));
+        }
+        if is_used(kind, new) {
+            return Err(RefactorError::AlreadyExists(kind, new.to_string()));
+        }
+        Ok(Self {
+            kind,
+            old: old.to_string(),
+            new: new.to_string(),
+        })
+    }
+
+    /// Applies this rename to the lines of the program.
+    ///
+    /// Lines that don't refer to the renamed memory cell or label are returned unchanged.
+    pub fn apply(&self, lines: &[String]) -> Result<Vec<String>, RefactorError> {
+        lines
+            .iter()
+            .zip(line_numbers(lines))
+            .map(|(line, line_number)| self.apply_to_line(line_number, line))
+            .collect()
+    }
+
+    /// Renames the memory cell or label in the instruction.
+    ///
+    /// Returns true if the instruction referred to the renamed memory cell or label.
+    pub fn rename_instruction(&self, instruction: &mut Instruction) -> bool {
+        match self.kind {
+            RenameKind::MemoryCell => instruction.rename_memory_cell(&self.old, &self.new),
+            RenameKind::Label => instruction.rename_label(&self.old, &self.new),
+        }
+    }
+
+    fn apply_to_line(&self, line_number: usize, line: &str) -> Result<String, RefactorError> {
+        let parsed = ParsedLine::parse(line_number, line)?;
+        let mut expected = parsed.clone();
+        if !expected.rename(self.kind, &self.old, &self.new) {
+            return Ok(line.to_string());
+        }
+        let code_end = match parsed {
+            ParsedLine::Declaration(_) => line.len(),
+            _ => comment_start(line),
+        };
+        let tokens = tokens(&line[..code_end]);
+        let mut rewritten = line[..code_end].to_string();
+        // replace from the back, so that the positions of the remaining tokens stay valid
+        for (token_idx, &(start, end)) in tokens.iter().enumerate().rev() {
+            let token = &line[start..end];
+            let is_label_definition = token_idx == 0 && token.ends_with(':');
+            let replacement = match self.kind {
+                RenameKind::MemoryCell if is_label_definition => None,
+                RenameKind::MemoryCell => self.rename_memory_cell_token(token),
+                RenameKind::Label if is_label_definition => {
+                    (token == format!("{}:", self.old)).then(|| format!("{}:", self.new))
+                }
+                // labels are only referenced as the last part of jump instructions
+                RenameKind::Label if token_idx + 1 == tokens.len() => {
+                    let (name, rest) = match token.strip_suffix(';') {
+                        Some(name) => (name, ";"),
+                        None => (token, ""),
+                    };
+                    (name == self.old).then(|| format!("{}{rest}", self.new))
+                }
+                RenameKind::Label => None,
+            };
+            let Some(mut replacement) = replacement else {
+                continue;
+            };
+            let mut end = end;
+            if is_label_definition {
+                // keep instructions aligned that are placed behind labels
+                if let Some(&(next_start, _)) = tokens.get(1) {
+                    let padding = &line[end..next_start];
+                    if padding.chars().all(|c| c == ' ') {
+                        let width = (padding.len() + token.chars().count())
+                            .saturating_sub(replacement.chars().count())
+                            .max(1);
+                        replacement.push_str(&" ".repeat(width));
+                        end = next_start;
+                    }
+                }
+            }
+            rewritten.replace_range(start..end, &replacement);
+        }
+        rewritten.push_str(&line[code_end..]);
+        // make sure that only the renamed memory cell or label was changed
+        match ParsedLine::parse(line_number, &rewritten) {
+            Ok(parsed) if parsed == expected => Ok(rewritten),
+            _ => Err(RefactorError::RewriteError(
+                line_number,
+                line.trim().to_string(),
+            )),
+        }
+    }
+
+    /// Replaces all occurrences of the memory cell in `token`, e.g. `p(h1)` or `ρ(h1)`.
+    fn rename_memory_cell_token(&self, token: &str) -> Option<String> {
+        let mut rewritten = token.to_string();
+        for prefix in ["p(", "ρ("] {
+            rewritten = rewritten.replace(
+                &format!("{prefix}{})", self.old),
+                &format!("{prefix}{})", self.new),
+            );
+        }
+        (rewritten != token).then_some(rewritten)
+    }
+}
+
+/// Moves lines into a new subroutine and replaces them with a call of the subroutine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractSubroutine {
+    /// Index of the first line that is moved into the subroutine.
+    pub start: usize,
+    /// Index of the last line that is moved into the subroutine.
+    pub end: usize,
+    /// Label of the new subroutine.
+    pub label: String,
+}
+
+impl ExtractSubroutine {
+    /// Creates a new refactoring that moves the lines from `start` to `end` (inclusive) into a subroutine.
+    ///
+    /// `start` and `end` are line numbers, as they are displayed and used in error messages when the program is built.
+    pub fn new(
+        lines: &[String],
+        start: usize,
+        end: usize,
+        label: &str,
+    ) -> Result<Self, RefactorError> {
+        let indices = line_numbers(lines)
+            .into_iter()
+            .zip(lines)
+            .enumerate()
+            .filter(|(_, (_, line))| !line.trim().starts_with('#'))
+            .map(|(idx, (line_number, _))| (line_number, idx))
+            .collect::<HashMap<_, _>>();
+        match (indices.get(&start), indices.get(&end)) {
+            (Some(&start_idx), Some(&end_idx)) if start_idx <= end_idx => Ok(Self {
+                start: start_idx,
+                end: end_idx,
+                label: label.to_string(),
+            }),
+            _ => Err(RefactorError::InvalidRange(start, end)),
+        }
+    }
+
+    /// Applies the refactoring to the lines of the program.
+    ///
+    /// The subroutine is placed at the end of the program. If the end of the program could be reached
+    /// without a jump, `goto END` is inserted in front of the subroutine.
+    ///
+    /// Returns an error if the label is invalid or already used or if jump targets would change.
+    pub fn apply(&self, lines: &[String]) -> Result<Vec<String>, RefactorError> {
+        let parsed = parse_lines(lines)?;
+        if RESERVED_LABELS.contains(&self.label.as_str()) {
+            return Err(RefactorError::ReservedLabel(self.label.clone()));
+        }
+        if !is_valid_name(RenameKind::Label, &self.label) {
+            return Err(RefactorError::InvalidName(
+                RenameKind::Label,
+                self.label.clone(),
+            ));
+        }
+        if parsed.iter().any(|line| {
+            line.clone()
+                .rename(RenameKind::Label, &self.label, &self.label)
+        }) {
+            return Err(RefactorError::AlreadyExists(
+                RenameKind::Label,
+                self.label.clone(),
+            ));
+        }
+        self.check_jumps(&parsed, &line_numbers(lines))?;
+
+        // new lines are aligned with the instructions that don't have a label,
+        // if all instructions have a label, they are aligned with the first selected instruction
+        let has_instruction = |line: &ParsedLine, with_label: bool| {
+            matches!(
+                line,
+                ParsedLine::Code(label, Code::Instruction(_) | Code::LoopKeyword(_))
+                    if with_label || label.is_none()
+            )
+        };
+        let column = parsed
+            .iter()
+            .zip(lines)
+            .filter(|(line, _)| has_instruction(line, false))
+            .chain(
+                parsed[self.start..=self.end]
+                    .iter()
+                    .zip(&lines[self.start..=self.end])
+                    .filter(|(line, _)| has_instruction(line, true)),
+            )
+            .find_map(|(_, line)| instruction_column(line))
+            .unwrap_or_default();
+        let indented = |instruction: &str| format!("{}{instruction}", " ".repeat(column));
+        let call = format!("call {}", self.label);
+        let mut result = lines[..self.start].to_vec();
+        result.push(match &parsed[self.start] {
+            // jumps to the label of the first line now run the subroutine
+            ParsedLine::Code(Some(label), _) => with_label(&call, label, column),
+            _ => indented(&call),
+        });
+        result.extend_from_slice(&lines[self.end + 1..]);
+        let last_instruction = parsed[self.end + 1..]
+            .iter()
+            .rev()
+            .find_map(|line| match line {
+                ParsedLine::Code(_, Code::Instruction(instruction)) => Some(instruction),
+                _ => None,
+            });
+        if !matches!(
+            last_instruction,
+            Some(Instruction::Goto(_) | Instruction::Return)
+        ) {
+            result.push(indented("goto END"));
+        }
+        result.push(String::new());
+        result.push(with_label(&lines[self.start], &self.label, column));
+        result.extend_from_slice(&lines[self.start + 1..=self.end]);
+        result.push(indented("return"));
+        Ok(result)
+    }
+
+    /// Checks that all jumps still reach the same instructions, when the selected lines are moved into a subroutine.
+    fn check_jumps(
+        &self,
+        parsed: &[ParsedLine],
+        line_numbers: &[usize],
+    ) -> Result<(), RefactorError> {
+        let selected = self.start..=self.end;
+        let label = |line: &ParsedLine| match line {
+            ParsedLine::Code(Some(label), _) => Some(label.clone()),
+            _ => None,
+        };
+        // the label of the first line is placed in front of the call
+        let entry_label = label(&parsed[self.start]);
+        let inner_labels = parsed[self.start + 1..=self.end]
+            .iter()
+            .filter_map(label)
+            .collect::<Vec<_>>();
+        let mut contains_instruction = false;
+        let mut open_loops = Vec::new();
+        for (idx, line) in parsed.iter().enumerate() {
+            let ParsedLine::Code(_, code) = line else {
+                continue;
+            };
+            let is_selected = selected.contains(&idx);
+            let line_number = line_numbers[idx];
+            match code {
+                Code::Instruction(instruction) => {
+                    contains_instruction |= is_selected;
+                    let (target, is_call) = match instruction {
+                        Instruction::Goto(target) | Instruction::JumpIf(_, _, _, target) => {
+                            (target, false)
+                        }
+                        Instruction::Call(target) => (target, true),
+                        Instruction::Return if is_selected => {
+                            return Err(RefactorError::ReturnInSelection(line_number))
+                        }
+                        _ => continue,
+                    };
+                    let is_inner = inner_labels.contains(target);
+                    // jumps to the end of the program still end the program
+                    let is_allowed_outside = (is_call
+                        || RESERVED_LABELS[2..].contains(&target.as_str()))
+                        && entry_label.as_ref() != Some(target);
+                    if is_selected && !is_inner && !is_allowed_outside {
+                        return Err(RefactorError::JumpOutOfSelection(
+                            line_number,
+                            target.clone(),
+                        ));
+                    }
+                    if !is_selected && is_inner {
+                        return Err(RefactorError::JumpIntoSelection(
+                            line_number,
+                            target.clone(),
+                        ));
+                    }
+                }
+                Code::LoopKeyword(keyword) if is_selected => {
+                    contains_instruction = true;
+                    match keyword {
+                        LoopKeyword::While(_, _, _) | LoopKeyword::Repeat => {
+                            open_loops.push((line_number, keyword));
+                        }
+                        LoopKeyword::End | LoopKeyword::Until(_, _, _) => match open_loops.pop() {
+                            Some((_, LoopKeyword::While(_, _, _)))
+                                if *keyword == LoopKeyword::End => {}
+                            Some((_, LoopKeyword::Repeat))
+                                if matches!(keyword, LoopKeyword::Until(_, _, _)) => {}
+                            _ => {
+                                return Err(RefactorError::LoopSplit(
+                                    line_number,
+                                    keyword.to_string(),
+                                ))
+                            }
+                        },
+                    }
+                }
+                _ => (),
+            }
+        }
+        if let Some((line_number, keyword)) = open_loops.first() {
+            return Err(RefactorError::LoopSplit(*line_number, keyword.to_string()));
+        }
+        if !contains_instruction {
+            return Err(RefactorError::EmptySelection);
+        }
+        Ok(())
+    }
+}
+
+/// Symbols that can be written in ascii or in unicode notation, e.g. `p(h1)` and `ρ(h1)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotationSymbol {
+    MemoryCell,
+    Gamma,
+}
+
+impl NotationSymbol {
+    pub const ALL: [Self; 2] = [Self::MemoryCell, Self::Gamma];
+
+    /// Returns how the symbol is written in the program.
+    fn written(self, notation: IdentifierNotation) -> &'static str {
+        match (self, notation) {
+            (Self::MemoryCell, IdentifierNotation::Ascii) => "p(",
+            (Self::MemoryCell, IdentifierNotation::Unicode) => "ρ(",
+            (Self::Gamma, IdentifierNotation::Ascii) => "y",
+            (Self::Gamma, IdentifierNotation::Unicode) => "γ",
+        }
+    }
+
+    /// Returns how the symbol is displayed in messages, e.g. `ρ(...)`.
+    pub fn display(self, notation: IdentifierNotation) -> String {
+        match self {
+            Self::MemoryCell => format!("{}...)", self.written(notation)),
+            Self::Gamma => self.written(notation).to_string(),
+        }
+    }
+}
+
+/// Occurrence of a symbol that can be written in ascii or in unicode notation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotationOccurrence {
+    /// Index of the line in the program.
+    pub line_idx: usize,
+    /// Line number as it is displayed, lines that start with `#` are not counted.
+    pub line_number: usize,
+    /// Index in the line at which the symbol starts.
+    start: usize,
+    pub symbol: NotationSymbol,
+    pub notation: IdentifierNotation,
+}
+
+/// Returns all memory cells and gamma accumulators in the program, together with the notation in which they are written.
+///
+/// Only occurrences in instructions and variable declarations are returned, comments and labels are skipped.
+pub fn notation_occurrences(lines: &[String]) -> Vec<NotationOccurrence> {
+    let mut occurrences = Vec::new();
+    for (line_idx, (line, line_number)) in lines.iter().zip(line_numbers(lines)).enumerate() {
+        let Ok(parsed) = ParsedLine::parse(line_number, line) else {
+            continue;
+        };
+        let code_end = match parsed {
+            ParsedLine::Declaration(_) => line.len(),
+            ParsedLine::Code(_, _) => comment_start(line),
+            ParsedLine::Empty => continue,
+        };
+        for symbol in NotationSymbol::ALL {
+            for notation in [IdentifierNotation::Ascii, IdentifierNotation::Unicode] {
+                let written = symbol.written(notation);
+                for (start, _) in line[..code_end].match_indices(written) {
+                    let end = start + written.len();
+                    let is_name_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-');
+                    if line[..start].chars().next_back().is_some_and(is_name_char)
+                        || (symbol == NotationSymbol::Gamma
+                            && line[end..].chars().next().is_some_and(is_name_char))
+                    {
+                        continue;
+                    }
+                    // only symbols whose notation can be changed without changing the meaning of the line count,
+                    // e.g. `y` in `goto y` is a label
+                    let switched = format!(
+                        "{}{}{}",
+                        &line[..start],
+                        symbol.written(notation.other()),
+                        &line[end..]
+                    );
+                    if ParsedLine::parse(line_number, &switched).as_ref() == Ok(&parsed) {
+                        occurrences.push(NotationOccurrence {
+                            line_idx,
+                            line_number,
+                            start,
+                            symbol,
+                            notation,
+                        });
+                    }
+                }
+            }
+        }
+    }
+    occurrences.sort_by_key(|o| (o.line_idx, o.start));
+    occurrences
+}
+
+/// Returns the notation in which most occurrences of `symbol` are written, ascii if both notations are used equally often.
+///
+/// Returns `None` if the symbol is not used.
+pub fn prevailing_notation(
+    occurrences: &[NotationOccurrence],
+    symbol: NotationSymbol,
+) -> Option<IdentifierNotation> {
+    let (ascii, unicode) = occurrences.iter().filter(|o| o.symbol == symbol).fold(
+        (0, 0),
+        |(ascii, unicode), o| match o.notation {
+            IdentifierNotation::Ascii => (ascii + 1, unicode),
+            IdentifierNotation::Unicode => (ascii, unicode + 1),
+        },
+    );
+    match (ascii, unicode) {
+        (0, 0) => None,
+        _ if unicode > ascii => Some(IdentifierNotation::Unicode),
+        _ => Some(IdentifierNotation::Ascii),
+    }
+}
+
+/// Writes all memory cells and gamma accumulators in the program in the same notation.
+///
+/// If `notation` is not set, each symbol is written in the notation that is used for most of its occurrences.
+/// Only the symbols are replaced, so that the formatting and comments of the program are preserved.
+pub fn normalize_notation(lines: &[String], notation: Option<IdentifierNotation>) -> Vec<String> {
+    let occurrences = notation_occurrences(lines);
+    let mut normalized = lines.to_vec();
+    // replace from the back, so that the positions of the remaining symbols stay valid
+    for occurrence in occurrences.iter().rev() {
+        let Some(target) =
+            notation.or_else(|| prevailing_notation(&occurrences, occurrence.symbol))
+        else {
+            continue;
+        };
+        if occurrence.notation == target {
+            continue;
+        }
+        let start = occurrence.start;
+        let end = start + occurrence.symbol.written(occurrence.notation).len();
+        normalized[occurrence.line_idx]
+            .replace_range(start..end, occurrence.symbol.written(target));
+    }
+    normalized
+}
+
+/// A line of the program, as it is relevant for refactoring.
+#[derive(Debug, Clone, PartialEq)]
+enum ParsedLine {
+    /// Line that contains code, with an optional label.
+    Code(Option<String>, Code),
+    /// Variable declaration with the declared memory location.
+    Declaration(TargetType),
+    /// Line without code, for example a comment.
+    Empty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Code {
+    Instruction(Instruction),
+    LoopKeyword(LoopKeyword),
+    None,
+}
+
+impl ParsedLine {
+    /// Parses the line in the same way as the line is parsed when the program is built.
+    ///
+    /// `line_number` is only used for error messages.
+    fn parse(line_number: usize, line: &str) -> Result<Self, RefactorError> {
+        let error = || RefactorError::ParseError(line_number, line.trim().to_string());
+        if let Some(comment) = line.trim().strip_prefix('#') {
+            // invalid declarations are already reported when the program is built
+            return Ok(comment
+                .trim_start()
+                .strip_prefix("var")
+                .and_then(|s| s.split_once('='))
+                .map(|(_, location)| location.trim().to_string())
+                .and_then(|location| TargetType::try_from((&location, (0, location.len()))).ok())
+                .map_or(Self::Empty, Self::Declaration));
+        }
+        let mut splits = utils::split_tokens(&line[..comment_start(line)]);
+        if splits.is_empty() {
+            return Ok(Self::Empty);
+        }
+        let label = if splits[0].ends_with(':') {
+            Some(splits.remove(0).replace(':', ""))
+        } else {
+            None
+        };
+        if splits.is_empty() {
+            return Ok(Self::Code(label, Code::None));
+        }
+        let code = match LoopKeyword::parse(&splits) {
+            Some(keyword) => Code::LoopKeyword(keyword.map_err(|_| error())?),
+            None => Code::Instruction(Instruction::try_from(&splits).map_err(|_| error())?),
+        };
+        Ok(Self::Code(label, code))
+    }
+
+    /// Renames the memory cell or label `old` to `new`.
+    ///
+    /// Returns true if this line referred to `old`.
+    fn rename(&mut self, kind: RenameKind, old: &str, new: &str) -> bool {
+        match (self, kind) {
+            (Self::Code(label, code), RenameKind::Label) => {
+                let mut renamed = false;
+                if label.as_deref() == Some(old) {
+                    *label = Some(new.to_string());
+                    renamed = true;
+                }
+                if let Code::Instruction(instruction) = code {
+                    renamed |= instruction.rename_label(old, new);
+                }
+                renamed
+            }
+            (Self::Code(_, Code::Instruction(instruction)), RenameKind::MemoryCell) => {
+                instruction.rename_memory_cell(old, new)
+            }
+            (Self::Code(_, Code::LoopKeyword(keyword)), RenameKind::MemoryCell) => {
+                keyword.rename_memory_cell(old, new)
+            }
+            (Self::Declaration(target), RenameKind::MemoryCell) => {
+                target.rename_memory_cell(old, new)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Instruction {
+    /// Renames all occurrences of the memory cell `old` to `new`.
+    ///
+    /// Returns true if the instruction uses the memory cell.
+    pub fn rename_memory_cell(&mut self, old: &str, new: &str) -> bool {
+        match self {
+            Self::Assign(target, value) | Self::Abs(target, value) => {
+                target.rename_memory_cell(old, new) | value.rename_memory_cell(old, new)
+            }
+            Self::Calc(target, value_a, _, value_b) | Self::Rand(target, value_a, value_b) => {
+                target.rename_memory_cell(old, new)
+                    | value_a.rename_memory_cell(old, new)
+                    | value_b.rename_memory_cell(old, new)
+            }
+            Self::JumpIf(value_a, _, value_b, _) => {
+                value_a.rename_memory_cell(old, new) | value_b.rename_memory_cell(old, new)
+            }
+            Self::CondExec(value_a, _, value_b, instruction) => {
+                value_a.rename_memory_cell(old, new)
+                    | value_b.rename_memory_cell(old, new)
+                    | instruction.rename_memory_cell(old, new)
+            }
+            Self::Goto(_)
+            | Self::Push
+            | Self::Pop
+            | Self::StackOp(_)
+            | Self::Call(_)
+            | Self::Return
+            | Self::Noop => false,
+        }
+    }
+
+    /// Renames all jumps to the label `old` to jumps to `new`.
+    ///
+    /// Returns true if the instruction jumps to the label.
+    pub fn rename_label(&mut self, old: &str, new: &str) -> bool {
+        match self {
+            Self::JumpIf(_, _, _, label) | Self::Goto(label) | Self::Call(label)
+                if label == old =>
+            {
+                *label = new.to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl LoopKeyword {
+    /// Renames all occurrences of the memory cell `old` in the condition to `new`.
+    ///
+    /// Returns true if the condition uses the memory cell.
+    pub fn rename_memory_cell(&mut self, old: &str, new: &str) -> bool {
+        match self {
+            Self::While(value_a, _, value_b) | Self::Until(value_a, _, value_b) => {
+                value_a.rename_memory_cell(old, new) | value_b.rename_memory_cell(old, new)
+            }
+            Self::End | Self::Repeat => false,
+        }
+    }
+}
+
+impl TargetType {
+    fn rename_memory_cell(&mut self, old: &str, new: &str) -> bool {
+        match self {
+            Self::MemoryCell(name) => rename_name(name, old, new),
+            Self::IndexMemoryCell(t) => t.rename_memory_cell(old, new),
+            Self::Accumulator(_) | Self::Gamma => false,
+        }
+    }
+}
+
+impl Value {
+    fn rename_memory_cell(&mut self, old: &str, new: &str) -> bool {
+        match self {
+            Self::MemoryCell(name) => rename_name(name, old, new),
+            Self::IndexMemoryCell(t) => t.rename_memory_cell(old, new),
+            Self::Accumulator(_) | Self::Gamma | Self::Constant(_) => false,
+        }
+    }
+}
+
+impl IndexMemoryCellIndexType {
+    fn rename_memory_cell(&mut self, old: &str, new: &str) -> bool {
+        match self {
+            Self::MemoryCell(name) => rename_name(name, old, new),
+            _ => false,
+        }
+    }
+}
+
+fn rename_name(name: &mut String, old: &str, new: &str) -> bool {
+    if name != old {
+        return false;
+    }
+    *name = new.to_string();
+    true
+}
+
+/// Parses all lines of the program.
+fn parse_lines(lines: &[String]) -> Result<Vec<ParsedLine>, RefactorError> {
+    lines
+        .iter()
+        .zip(line_numbers(lines))
+        .map(|(line, line_number)| ParsedLine::parse(line_number, line))
+        .collect()
+}
+
+/// Returns the line number of every line, as it is displayed and used in error messages when the program is built.
+///
+/// Lines that start with `#` are not counted, they get the number of the next line.
+fn line_numbers(lines: &[String]) -> Vec<usize> {
+    let mut line_number = 1;
+    lines
+        .iter()
+        .map(|line| {
+            if line.trim().starts_with('#') {
+                line_number
+            } else {
+                line_number += 1;
+                line_number - 1
+            }
+        })
+        .collect()
+}
+
+/// Returns the name of the memory cell, if `s` is written as memory cell, e.g. `p(h1)`.
+fn strip_memory_cell(s: &str) -> Option<&str> {
+    s.strip_prefix("p(")
+        .or_else(|| s.strip_prefix("ρ("))
+        .and_then(|s| s.strip_suffix(')'))
+}
+
+fn is_valid_name(kind: RenameKind, name: &str) -> bool {
+    match kind {
+        RenameKind::MemoryCell => {
+            let location = format!("p({name})");
+            TargetType::try_from((&location, (0, location.len())))
+                == Ok(TargetType::MemoryCell(name.to_string()))
+        }
+        RenameKind::Label => {
+            !name.is_empty()
+                && !name.contains(|c: char| c.is_whitespace() || matches!(c, ':' | ';' | '#'))
+                && !name.contains("//")
+        }
+    }
+}
+
+/// Column at which the instruction in the line starts, labels are skipped.
+///
+/// Returns `None` if the line does not contain an instruction.
+fn instruction_column(line: &str) -> Option<usize> {
+    let code = &line[..comment_start(line)];
+    tokens(code)
+        .into_iter()
+        .enumerate()
+        .find(|(idx, (start, end))| *idx > 0 || !code[*start..*end].ends_with(':'))
+        .map(|(_, (start, _))| code[..start].chars().count())
+}
+
+/// Places `label` in front of the line, a label that is already defined in the line is replaced.
+///
+/// The rest of the line is moved to `column`, if there is enough space.
+fn with_label(line: &str, label: &str, column: usize) -> String {
+    let code = &line[..comment_start(line)];
+    let rest = match tokens(code).first() {
+        Some(&(_, end)) if code[..end].ends_with(':') => &line[end..],
+        _ => line,
+    }
+    .trim_start();
+    if rest.is_empty() {
+        return format!("{label}:");
+    }
+    let label_width = label.chars().count() + 1;
+    format!(
+        "{label}:{}{rest}",
+        " ".repeat(column.saturating_sub(label_width).max(1))
+    )
+}
+
+/// Index at which the comment in the line starts, comments start with `//` or `#`.
+///
+/// Returns the length of the line, if the line does not contain a comment.
+fn comment_start(line: &str) -> usize {
+    utils::comment_start(line).unwrap_or(line.len())
+}
+
+/// Start and end index of all whitespace separated tokens in `s`.
+fn tokens(s: &str) -> Vec<(usize, usize)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (idx, c) in s.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(token_start)) => {
+                tokens.push((token_start, idx));
+                start = None;
+            }
+            (false, None) => start = Some(idx),
+            _ => (),
+        }
+    }
+    if let Some(token_start) = start {
+        tokens.push((token_start, s.len()));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        base::{IdentifierNotation, MemoryCell},
+        utils::test_utils,
+    };
+
+    use super::{normalize_notation, ExtractSubroutine, RefactorError, Rename, RenameKind};
+
+    fn rename(
+        input: &str,
+        old: &str,
+        new: &str,
+        kind: Option<RenameKind>,
+    ) -> Result<String, RefactorError> {
+        let lines = test_utils::string_literal_to_vec(input);
+        Ok(Rename::new(&lines, old, new, kind)?
+            .apply(&lines)?
+            .join("\n"))
+    }
+
+    #[test]
+    fn test_rename_memory_cell() {
+        let input = "#var total = p(h1)\nmain:  p(h1)  := 0   // p(h1) is the sum\n       p(h10) := p(h1) + 1\nloop:  p(p(h1)) := max(a, ρ(h1));\n       while p(h1) < 3 do\n       end";
+        assert_eq!(
+            rename(input, "h1", "sum", None),
+            Ok("#var total = p(sum)\nmain:  p(sum)  := 0   // p(h1) is the sum\n       p(h10) := p(sum) + 1\nloop:  p(p(sum)) := max(a, ρ(sum));\n       while p(sum) < 3 do\n       end".to_string())
+        );
+    }
+
+    #[test]
+    fn test_rename_label() {
+        let input = "loop:   a := a + 1\n        if a < 10 then goto loop; // goto loop\n        call loop\nloop2:  goto loop2";
+        assert_eq!(
+            rename(input, "loop", "again", None),
+            Ok("again:  a := a + 1\n        if a < 10 then goto again; // goto loop\n        call again\nloop2:  goto loop2".to_string())
+        );
+        // instructions stay aligned if possible
+        assert_eq!(
+            rename("a:   goto a\n     goto a", "a", "a_very_long_label", None),
+            Ok(
+                "a_very_long_label: goto a_very_long_label\n     goto a_very_long_label"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_rename_kind() {
+        let input = "h1: p(h1) := 1\ngoto h1";
+        assert_eq!(
+            rename(input, "h1", "x", None),
+            Err(RefactorError::Ambiguous("h1".to_string()))
+        );
+        assert_eq!(
+            rename(input, "p(h1)", "p(x)", None),
+            Ok("h1: p(x) := 1\ngoto h1".to_string())
+        );
+        assert_eq!(
+            rename(input, "h1", "x", Some(RenameKind::Label)),
+            Ok("x:  p(h1) := 1\ngoto x".to_string())
+        );
+        assert_eq!(
+            rename(input, "p(h1)", "x", Some(RenameKind::Label)),
+            Err(RefactorError::InvalidName(
+                RenameKind::Label,
+                "p(h1)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_rename_errors() {
+        let input = "main: p(h1) := 1\np(h2) := 2\nloop: goto loop";
+        assert_eq!(
+            rename(input, "h3", "x", None),
+            Err(RefactorError::NotFound("h3".to_string()))
+        );
+        assert_eq!(
+            rename(input, "loop", "x", Some(RenameKind::MemoryCell)),
+            Err(RefactorError::NotUsed(
+                RenameKind::MemoryCell,
+                "loop".to_string()
+            ))
+        );
+        assert_eq!(
+            rename(input, "h1", "h2", None),
+            Err(RefactorError::AlreadyExists(
+                RenameKind::MemoryCell,
+                "h2".to_string()
+            ))
+        );
+        for name in ["a1", "12", "y", "x y"] {
+            assert_eq!(
+                rename(input, "h1", name, None),
+                Err(RefactorError::InvalidName(
+                    RenameKind::MemoryCell,
+                    name.to_string()
+                ))
+            );
+        }
+        assert_eq!(
+            rename(input, "loop", "x:", None),
+            Err(RefactorError::InvalidName(
+                RenameKind::Label,
+                "x:".to_string()
+            ))
+        );
+        assert_eq!(
+            rename(input, "main", "start", None),
+            Err(RefactorError::ReservedLabel("main".to_string()))
+        );
+        assert_eq!(
+            rename(input, "loop", "END", None),
+            Err(RefactorError::ReservedLabel("END".to_string()))
+        );
+        assert_eq!(
+            rename("p(h1) := 1\na := x", "h1", "x", None),
+            Err(RefactorError::ParseError(2, "a := x".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_rename_runtime() {
+        let input = test_utils::string_literal_to_vec(
+            "loop: p(h1) := p(h1) + 1\nif p(h1) < 3 then goto loop",
+        );
+        let mut rt = test_utils::runtime_from_str(
+            "p(h1) := 0\nloop: p(h1) := p(h1) + 1\nif p(h1) < 3 then goto loop",
+        )
+        .unwrap();
+        let rename = Rename::new(&input, "h1", "counter", None).unwrap();
+        rt.rename(&rename).unwrap();
+        let rename = Rename::new(&input, "loop", "again", None).unwrap();
+        rt.rename(&rename).unwrap();
+        rt.run().unwrap();
+        assert_eq!(
+            rt.runtime_memory().memory_cells.get("counter"),
+            Some(&MemoryCell {
+                label: "counter".to_string(),
+                data: Some(3)
+            })
+        );
+        assert!(!rt.runtime_memory().memory_cells.contains_key("h1"));
+        assert!(rt.control_flow().instruction_labels.contains_key("again"));
+        let rename = Rename {
+            kind: RenameKind::MemoryCell,
+            old: "h2".to_string(),
+            new: "counter".to_string(),
+        };
+        assert_eq!(
+            rt.rename(&rename),
+            Err(RefactorError::AlreadyExists(
+                RenameKind::MemoryCell,
+                "counter".to_string()
+            ))
+        );
+    }
+
+    fn extract(input: &str, start: usize, end: usize) -> Result<String, RefactorError> {
+        let lines = test_utils::string_literal_to_vec(input);
+        Ok(ExtractSubroutine {
+            start,
+            end,
+            label: "sub".to_string(),
+        }
+        .apply(&lines)?
+        .join("\n"))
+    }
+
+    #[test]
+    fn test_extract_subroutine() {
+        let input = "        a := 1\nstart:  a := a + 1 // increment\n        p(h1) := a\n        p(h2) := a * 2";
+        assert_eq!(
+            extract(input, 1, 2),
+            Ok("        a := 1\nstart:  call sub\n        p(h2) := a * 2\n        goto END\n\nsub:    a := a + 1 // increment\n        p(h1) := a\n        return".to_string())
+        );
+        // new lines are aligned with the lines without labels
+        let input = "a := 1\nloop: a := a + 1\np(h1) := a";
+        assert_eq!(
+            extract(input, 1, 1),
+            Ok(
+                "a := 1\nloop: call sub\np(h1) := a\ngoto END\n\nsub: a := a + 1\nreturn"
+                    .to_string()
+            )
+        );
+        // no goto END needed if the program ends with a jump
+        let input = "a := 1\na := 2\ngoto END";
+        assert_eq!(
+            extract(input, 1, 1),
+            Ok("a := 1\ncall sub\ngoto END\n\nsub: a := 2\nreturn".to_string())
+        );
+        // inner jumps, loops and calls are allowed
+        let input = "a := 1\nloop: a := a + 1\nif a < 5 then goto loop\nwhile a > 0 do\na := a - 1\nend\ncall f\ngoto END\nf: return";
+        assert!(extract(input, 0, 6).is_ok());
+        let res = extract(input, 3, 5).unwrap();
+        let mut rt = test_utils::runtime_from_str(&res).unwrap();
+        rt.run().unwrap();
+        assert_eq!(
+            rt.runtime_memory().accumulators.get(&0).unwrap().data,
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_extract_subroutine_new() {
+        let lines = test_utils::string_literal_to_vec("# comment\na := 1\n#var x = p(h1)\na := 2");
+        let extract = ExtractSubroutine::new(&lines, 2, 2, "sub").unwrap();
+        assert_eq!((extract.start, extract.end), (3, 3));
+        let extract = ExtractSubroutine::new(&lines, 1, 2, "sub").unwrap();
+        assert_eq!((extract.start, extract.end), (1, 3));
+        assert_eq!(
+            ExtractSubroutine::new(&lines, 2, 3, "sub"),
+            Err(RefactorError::InvalidRange(2, 3))
+        );
+        assert_eq!(
+            ExtractSubroutine::new(&lines, 2, 1, "sub"),
+            Err(RefactorError::InvalidRange(2, 1))
+        );
+    }
+
+    #[test]
+    fn test_normalize_notation() {
+        let lines = test_utils::string_literal_to_vec(
+            "#var sum = ρ(h1)\ny:     p(h1) := γ // ρ(h1)\n       ρ(p(h2)) := p(h1) + y\n       if γ < 3 then goto y",
+        );
+        assert_eq!(
+            normalize_notation(&lines, None).join("\n"),
+            "#var sum = p(h1)\ny:     p(h1) := γ // ρ(h1)\n       p(p(h2)) := p(h1) + γ\n       if γ < 3 then goto y"
+        );
+        assert_eq!(
+            normalize_notation(&lines, Some(IdentifierNotation::Unicode)).join("\n"),
+            "#var sum = ρ(h1)\ny:     ρ(h1) := γ // ρ(h1)\n       ρ(ρ(h2)) := ρ(h1) + γ\n       if γ < 3 then goto y"
+        );
+        assert_eq!(
+            normalize_notation(&lines, Some(IdentifierNotation::Ascii)).join("\n"),
+            "#var sum = p(h1)\ny:     p(h1) := y // ρ(h1)\n       p(p(h2)) := p(h1) + y\n       if y < 3 then goto y"
+        );
+    }
+
+    #[test]
+    fn test_extract_subroutine_errors() {
+        let input = "a := 1\nloop: a := a + 1\nif a < 5 then goto loop\nwhile a > 0 do\na := a - 1\nend\nreturn";
+        // the label of the first line stays in front of the call
+        assert!(extract(input, 1, 1).is_ok());
+        assert_eq!(
+            extract(input, 2, 2),
+            Err(RefactorError::JumpOutOfSelection(3, "loop".to_string()))
+        );
+        assert_eq!(
+            extract("a := 1\nloop: a := 2\ngoto loop", 0, 1),
+            Err(RefactorError::JumpIntoSelection(3, "loop".to_string()))
+        );
+        assert_eq!(
+            extract(input, 3, 4),
+            Err(RefactorError::LoopSplit(4, "while a0 > 0 do".to_string()))
+        );
+        assert_eq!(
+            extract(input, 4, 5),
+            Err(RefactorError::LoopSplit(6, "end".to_string()))
+        );
+        assert_eq!(
+            extract(input, 6, 6),
+            Err(RefactorError::ReturnInSelection(7))
+        );
+        assert_eq!(
+            extract("a := 1\n// comment\na := 2", 1, 1),
+            Err(RefactorError::EmptySelection)
+        );
+        assert_eq!(
+            extract("a := 1\nsub: a := 2", 0, 0),
+            Err(RefactorError::AlreadyExists(
+                RenameKind::Label,
+                "sub".to_string()
+            ))
+        );
+    }
+}
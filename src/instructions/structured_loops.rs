@@ -0,0 +1,372 @@
+use std::fmt::Display;
+
+use crate::{base::Comparison, runtime::ControlFlow};
+
+use super::{
+    error_handling::{BuildProgramError, BuildProgramErrorTypes, InstructionParseError},
+    parsing::{parse_condition, part_range, whole_range},
+    Instruction, Value,
+};
+
+/// A line that starts or closes a structured loop.
+///
+/// Structured loops are syntactic sugar, they are desugared into labels and jumps when the program is built.
+#[derive(Debug, PartialEq, Clone)]
+pub enum LoopKeyword {
+    /// `while a < 10 do`, the loop is executed as long as the condition holds.
+    While(Value, Comparison, Value),
+    /// `end`, closes a while loop.
+    End,
+    /// `repeat`, starts a loop that is executed at least once.
+    Repeat,
+    /// `until a >= 10`, the loop is repeated until the condition holds.
+    Until(Value, Comparison, Value),
+}
+
+impl LoopKeyword {
+    /// Tries to parse a loop keyword from the parts of a line.
+    ///
+    /// Returns `None` if the line does not start with a loop keyword.
+    pub fn parse(parts: &[&str]) -> Option<Result<Self, InstructionParseError>> {
+        let parts = parts.iter().map(|s| (*s).to_string()).collect::<Vec<_>>();
+        match parts.first()?.as_str() {
+            "while" => Some(Self::parse_while(&parts)),
+            "until" => Some(Self::parse_until(&parts)),
+            "end" => Some(Self::parse_single(&parts, Self::End)),
+            "repeat" => Some(Self::parse_single(&parts, Self::Repeat)),
+            _ => None,
+        }
+    }
+
+    fn parse_while(parts: &[String]) -> Result<Self, InstructionParseError> {
+        let (value_a, cmp, value_b) = parse_condition(parts, 1)?;
+        if parts.len() < 5 {
+            return Err(InstructionParseError::MissingExpression {
+                range: (whole_range(parts).1 + 1, whole_range(parts).1 + 1),
+                help: "You might be missing 'do'".to_string(),
+            });
+        }
+        if parts[4] != "do" || parts.len() > 5 {
+            return Err(InstructionParseError::InvalidExpression(
+                (part_range(parts, 4).0, whole_range(parts).1),
+                parts[4..].join(" "),
+            ));
+        }
+        Ok(Self::While(value_a, cmp, value_b))
+    }
+
+    fn parse_until(parts: &[String]) -> Result<Self, InstructionParseError> {
+        let (value_a, cmp, value_b) = parse_condition(parts, 1)?;
+        if parts.len() > 4 {
+            return Err(InstructionParseError::InvalidExpression(
+                (part_range(parts, 4).0, whole_range(parts).1),
+                parts[4..].join(" "),
+            ));
+        }
+        Ok(Self::Until(value_a, cmp, value_b))
+    }
+
+    fn parse_single(parts: &[String], keyword: Self) -> Result<Self, InstructionParseError> {
+        if parts.len() > 1 {
+            return Err(InstructionParseError::InvalidExpression(
+                (part_range(parts, 1).0, whole_range(parts).1),
+                parts[1..].join(" "),
+            ));
+        }
+        Ok(keyword)
+    }
+
+    /// The keyword that has to be used to close the loop started by this keyword.
+    fn closing_keyword(&self) -> Option<&'static str> {
+        match self {
+            Self::While(_, _, _) => Some("end"),
+            Self::Repeat => Some("until"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for LoopKeyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::While(v, cmp, v2) => write!(f, "while {v} {cmp} {v2} do"),
+            Self::End => write!(f, "end"),
+            Self::Repeat => write!(f, "repeat"),
+            Self::Until(v, cmp, v2) => write!(f, "until {v} {cmp} {v2}"),
+        }
+    }
+}
+
+/// A structured loop that was desugared into labels and jumps.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StructuredLoop {
+    /// Index of the instruction that starts the loop (`while` or `repeat`).
+    pub start: usize,
+    /// Index of the instruction that closes the loop (`end` or `until`).
+    pub end: usize,
+    /// The keyword that contains the condition of the loop as it was written (`while` or `until`).
+    pub keyword: LoopKeyword,
+    /// Label that was generated for the start of the loop.
+    pub start_label: String,
+    /// Label that was generated for the instruction after a while loop.
+    pub exit_label: Option<String>,
+}
+
+impl StructuredLoop {
+    /// Index and comparison of the instruction that contains the condition of the loop.
+    ///
+    /// The comparison is returned as written in the loop, the desugared instruction uses the negated comparison.
+    pub fn condition(&self) -> (usize, Comparison) {
+        match self.keyword {
+            LoopKeyword::While(_, cmp, _) => (self.start, cmp),
+            LoopKeyword::Until(_, cmp, _) => (self.end, cmp),
+            _ => unreachable!("the condition of a structured loop is stored in while or until"),
+        }
+    }
+}
+
+/// Replaces the placeholders of loop keywords in `instructions` with labels and jumps.
+///
+/// `keywords` contains the loop keywords together with the index of the instruction they are placed at,
+/// these instructions are expected to be `Noop` placeholders.
+///
+/// `while cond do ... end` is desugared into `while_n: if !cond then goto while_n_end ... goto while_n`
+/// where `while_n_end` points to the instruction after `end`.
+/// `repeat ... until cond` is desugared into `repeat_n: ... if !cond then goto repeat_n`.
+/// Generated labels never collide with labels that are defined in the program.
+///
+/// Returns an error if a loop is not closed or if a closing keyword does not belong to a loop.
+pub fn desugar(
+    keywords: Vec<(usize, LoopKeyword)>,
+    instructions: &mut [Instruction],
+    control_flow: &mut ControlFlow,
+) -> Result<Vec<StructuredLoop>, Box<BuildProgramError>> {
+    let mut loops = Vec::new();
+    let mut open: Vec<(usize, LoopKeyword)> = Vec::new();
+    for (idx, keyword) in keywords {
+        match keyword {
+            LoopKeyword::While(_, _, _) | LoopKeyword::Repeat => open.push((idx, keyword)),
+            LoopKeyword::End | LoopKeyword::Until(_, _, _) => {
+                let Some((start, start_keyword)) = open.pop() else {
+                    return Err(loop_close_unexpected(idx, &keyword));
+                };
+                match (&start_keyword, &keyword) {
+                    (LoopKeyword::While(v, cmp, v2), LoopKeyword::End) => {
+                        let start_label = free_label(control_flow, "while", loops.len());
+                        let exit_label = format!("{start_label}_end");
+                        control_flow
+                            .instruction_labels
+                            .insert(start_label.clone(), start);
+                        control_flow
+                            .instruction_labels
+                            .insert(exit_label.clone(), idx + 1);
+                        instructions[start] = Instruction::JumpIf(
+                            v.clone(),
+                            cmp.negate(),
+                            v2.clone(),
+                            exit_label.clone(),
+                        );
+                        instructions[idx] = Instruction::Goto(start_label.clone());
+                        loops.push(StructuredLoop {
+                            start,
+                            end: idx,
+                            keyword: start_keyword,
+                            start_label,
+                            exit_label: Some(exit_label),
+                        });
+                    }
+                    (LoopKeyword::Repeat, LoopKeyword::Until(v, cmp, v2)) => {
+                        let start_label = free_label(control_flow, "repeat", loops.len());
+                        control_flow
+                            .instruction_labels
+                            .insert(start_label.clone(), start);
+                        instructions[idx] = Instruction::JumpIf(
+                            v.clone(),
+                            cmp.negate(),
+                            v2.clone(),
+                            start_label.clone(),
+                        );
+                        loops.push(StructuredLoop {
+                            start,
+                            end: idx,
+                            keyword: LoopKeyword::Until(v.clone(), *cmp, v2.clone()),
+                            start_label,
+                            exit_label: None,
+                        });
+                    }
+                    _ => return Err(loop_close_unexpected(idx, &keyword)),
+                }
+            }
+        }
+    }
+    if let Some((idx, keyword)) = open.pop() {
+        return Err(Box::new(BuildProgramError {
+            reason: BuildProgramErrorTypes::LoopNotClosed(
+                idx + 1,
+                keyword.to_string(),
+                keyword.closing_keyword().unwrap_or_default().to_string(),
+            ),
+        }));
+    }
+    loops.sort_by_key(|l| l.start);
+    Ok(loops)
+}
+
+fn loop_close_unexpected(idx: usize, keyword: &LoopKeyword) -> Box<BuildProgramError> {
+    Box::new(BuildProgramError {
+        reason: BuildProgramErrorTypes::LoopCloseUnexpected(idx + 1, keyword.to_string()),
+    })
+}
+
+/// Returns a label of the form `prefix_n` that is not used in the program, neither with nor without `_end` suffix.
+fn free_label(control_flow: &ControlFlow, prefix: &str, mut n: usize) -> String {
+    loop {
+        let label = format!("{prefix}_{n}");
+        if !control_flow.instruction_labels.contains_key(&label)
+            && !control_flow
+                .instruction_labels
+                .contains_key(&format!("{label}_end"))
+        {
+            return label;
+        }
+        n += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        base::Comparison,
+        instructions::{
+            error_handling::{BuildProgramError, BuildProgramErrorTypes},
+            Instruction, Value,
+        },
+        runtime::ControlFlow,
+        utils::test_utils,
+    };
+
+    use super::{desugar, LoopKeyword};
+
+    #[test]
+    fn test_parse_loop_keyword() {
+        assert_eq!(
+            LoopKeyword::parse(&["while", "a", "<", "10", "do"]),
+            Some(Ok(LoopKeyword::While(
+                Value::Accumulator(0),
+                Comparison::Lt,
+                Value::Constant(10)
+            )))
+        );
+        assert_eq!(
+            LoopKeyword::parse(&["until", "p(h1)", "=", "0"]),
+            Some(Ok(LoopKeyword::Until(
+                Value::MemoryCell("h1".to_string()),
+                Comparison::Eq,
+                Value::Constant(0)
+            )))
+        );
+        assert_eq!(LoopKeyword::parse(&["end"]), Some(Ok(LoopKeyword::End)));
+        assert_eq!(
+            LoopKeyword::parse(&["repeat"]),
+            Some(Ok(LoopKeyword::Repeat))
+        );
+        assert!(LoopKeyword::parse(&["while", "a", "<", "10"])
+            .unwrap()
+            .is_err());
+        assert!(LoopKeyword::parse(&["while", "a", "<", "10", "then"])
+            .unwrap()
+            .is_err());
+        assert!(LoopKeyword::parse(&["repeat", "a"]).unwrap().is_err());
+        assert_eq!(LoopKeyword::parse(&["a", ":=", "5"]), None);
+    }
+
+    #[test]
+    fn test_desugar() {
+        let mut control_flow = ControlFlow::new();
+        control_flow
+            .instruction_labels
+            .insert("while_0".to_string(), 5);
+        let mut instructions = vec![Instruction::Noop; 5];
+        let loops = desugar(
+            vec![
+                (
+                    0,
+                    LoopKeyword::While(Value::Accumulator(0), Comparison::Lt, Value::Constant(3)),
+                ),
+                (1, LoopKeyword::Repeat),
+                (
+                    2,
+                    LoopKeyword::Until(Value::Accumulator(1), Comparison::Ge, Value::Constant(3)),
+                ),
+                (3, LoopKeyword::End),
+            ],
+            &mut instructions,
+            &mut control_flow,
+        )
+        .unwrap();
+        assert_eq!(
+            instructions,
+            vec![
+                Instruction::JumpIf(
+                    Value::Accumulator(0),
+                    Comparison::Ge,
+                    Value::Constant(3),
+                    "while_1_end".to_string()
+                ),
+                Instruction::Noop,
+                Instruction::JumpIf(
+                    Value::Accumulator(1),
+                    Comparison::Lt,
+                    Value::Constant(3),
+                    "repeat_0".to_string()
+                ),
+                Instruction::Goto("while_1".to_string()),
+                Instruction::Noop,
+            ]
+        );
+        assert_eq!(control_flow.instruction_labels["while_1"], 0);
+        assert_eq!(control_flow.instruction_labels["while_1_end"], 4);
+        assert_eq!(control_flow.instruction_labels["repeat_0"], 1);
+        assert_eq!(loops.len(), 2);
+        assert_eq!(loops[0].condition(), (0, Comparison::Lt));
+        assert_eq!(loops[1].condition(), (2, Comparison::Ge));
+    }
+
+    #[test]
+    fn test_desugar_unmatched_keywords() {
+        for (keywords, reason) in [
+            (
+                vec![(0, LoopKeyword::Repeat)],
+                BuildProgramErrorTypes::LoopNotClosed(1, "repeat".to_string(), "until".to_string()),
+            ),
+            (
+                vec![(0, LoopKeyword::End)],
+                BuildProgramErrorTypes::LoopCloseUnexpected(1, "end".to_string()),
+            ),
+            (
+                vec![(0, LoopKeyword::Repeat), (1, LoopKeyword::End)],
+                BuildProgramErrorTypes::LoopCloseUnexpected(2, "end".to_string()),
+            ),
+        ] {
+            assert_eq!(
+                desugar(
+                    keywords,
+                    &mut vec![Instruction::Noop; 2],
+                    &mut ControlFlow::new()
+                ),
+                Err(Box::new(BuildProgramError { reason }))
+            );
+        }
+    }
+
+    #[test]
+    fn test_run_structured_loops() {
+        let mut rt = test_utils::runtime_from_str(
+            "a1 := 0\na2 := 0\nwhile a1 < 5 do\na1 := a1 + 1\nend\nrepeat\na2 := a2 + a1\na1 := a1 - 1\nuntil a1 == 0",
+        )
+        .unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&1].data, Some(0));
+        assert_eq!(rt.runtime_memory().accumulators[&2].data, Some(15));
+    }
+}
@@ -0,0 +1,229 @@
+use super::error_handling::InstructionParseError;
+
+/// Keywords that can be suggested when a word is misspelled.
+const KEYWORDS: &[&str] = &["if", "then", "goto", "push", "pop", "call", "return"];
+
+impl InstructionParseError {
+    /// Returns a concrete suggestion on how `instruction` can be fixed, if one is found.
+    ///
+    /// `instruction` is the instruction without label and comment that caused this error and `memory_cells` are the
+    /// names of the memory cells that are known, they are suggested when a memory cell is written without `p()`.
+    pub fn suggestion(&self, instruction: &str, memory_cells: &[String]) -> Option<String> {
+        let parts = instruction.split_whitespace().collect::<Vec<&str>>();
+        match self {
+            Self::InvalidExpression(_, expression) => missing_then(&parts, expression)
+                .or_else(|| keyword(expression))
+                .or_else(|| memory_cell(expression, memory_cells)),
+            Self::UnknownInstruction(_, instruction) => {
+                instruction.split_whitespace().next().and_then(keyword)
+            }
+            // single words like 'retrun' are expected to be followed by ':='
+            Self::MissingExpression { .. } if parts.len() == 1 => keyword(parts[0]),
+            _ => None,
+        }
+    }
+}
+
+/// Suggests to insert or fix `then` in an `if` instruction, when `expression` is placed where `then` is expected.
+fn missing_then(parts: &[&str], expression: &str) -> Option<String> {
+    if parts.len() < 5 || parts[0] != "if" || parts[4] != expression {
+        return None;
+    }
+    if is_close(expression, "then") {
+        return Some("Did you mean 'then'?".to_string());
+    }
+    let fixed = [&parts[..4], &["then"], &parts[4..]].concat().join(" ");
+    Some(format!("You might be missing 'then', try '{fixed}'"))
+}
+
+/// Suggests the keyword that is closest to `word`.
+fn keyword(word: &str) -> Option<String> {
+    nearest(word, KEYWORDS.iter().copied())
+        .filter(|keyword| *keyword != word)
+        .map(|keyword| format!("Did you mean '{keyword}'?"))
+}
+
+/// Suggests the memory cell that is closest to `expression`, when the memory cell is not written as `p(name)`.
+fn memory_cell(expression: &str, memory_cells: &[String]) -> Option<String> {
+    nearest(expression, memory_cells.iter().map(String::as_str))
+        .map(|name| format!("Did you mean 'p({name})'?"))
+}
+
+/// Returns the candidate with the smallest edit distance to `word`, if it is close enough to be a typo.
+fn nearest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .filter(|candidate| is_close(word, candidate))
+        .min_by_key(|candidate| edit_distance(word, candidate))
+}
+
+/// Returns up to `count` candidates that have the smallest edit distance to `word`, the closest candidate first.
+///
+/// Candidates with the same distance are sorted alphabetically.
+pub fn closest<'a>(
+    word: &str,
+    candidates: impl Iterator<Item = &'a str>,
+    count: usize,
+) -> Vec<&'a str> {
+    let mut candidates = candidates
+        .map(|candidate| (edit_distance(word, candidate), candidate))
+        .collect::<Vec<_>>();
+    candidates.sort_unstable();
+    candidates
+        .into_iter()
+        .take(count)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Checks if `word` is a likely typo of `target`.
+///
+/// Short words may contain one mistake, longer words two.
+fn is_close(word: &str, target: &str) -> bool {
+    let len = word.chars().count();
+    let allowed = if len <= 4 { 1 } else { 2 };
+    let distance = edit_distance(word, target);
+    distance <= allowed && distance < len
+}
+
+/// Calculates the number of insertions, deletions, substitutions and transpositions of adjacent characters that
+/// are needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    // d[i][j] is the distance between the first i characters of a and the first j characters of b
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Returns the names of all memory cells that are used in `source`, e.g. `h1` for `p(h1)`.
+pub fn memory_cell_names(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for part in source.split(['p', 'ρ']).skip(1) {
+        let Some(rest) = part.strip_prefix('(') else {
+            continue;
+        };
+        let Some((name, _)) = rest.split_once(')') else {
+            continue;
+        };
+        if !name.is_empty()
+            && name.chars().any(|c| c.is_ascii_alphabetic())
+            && name
+                .chars()
+                .all(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-'))
+            && !names.iter().any(|n| n == name)
+        {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instructions::Instruction;
+
+    use super::{closest, edit_distance, memory_cell_names};
+
+    fn suggestion(instruction: &str, memory_cells: &[&str]) -> Option<String> {
+        let memory_cells = memory_cells
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        Instruction::try_from(instruction)
+            .unwrap_err()
+            .suggestion(instruction, &memory_cells)
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("goto", "goto"), 0);
+        assert_eq!(edit_distance("goot", "goto"), 1);
+        assert_eq!(edit_distance("gto", "goto"), 1);
+        assert_eq!(edit_distance("retrun", "return"), 1);
+        assert_eq!(edit_distance("h1", "hl"), 1);
+        assert_eq!(edit_distance("", "pop"), 3);
+    }
+
+    #[test]
+    fn test_closest() {
+        let candidates = ["A := C", "A := M", "A := A OP M", "M := A"];
+        assert_eq!(
+            closest("A := A OP C", candidates.into_iter(), 2),
+            vec!["A := A OP M", "A := C"]
+        );
+        assert_eq!(
+            closest("M := C", candidates.into_iter(), 3),
+            vec!["A := C", "M := A", "A := M"]
+        );
+        assert!(closest("A := C", std::iter::empty(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_suggest_keyword() {
+        assert_eq!(
+            suggestion("goot loop", &[]),
+            Some("Did you mean 'goto'?".to_string())
+        );
+        assert_eq!(
+            suggestion("if a < 3 then gotoo loop", &[]),
+            Some("Did you mean 'goto'?".to_string())
+        );
+        assert_eq!(
+            suggestion("retrun", &[]),
+            Some("Did you mean 'return'?".to_string())
+        );
+        assert_eq!(suggestion("jump loop", &[]), None);
+    }
+
+    #[test]
+    fn test_suggest_then() {
+        assert_eq!(
+            suggestion("if a < 3 goto loop", &[]),
+            Some("You might be missing 'then', try 'if a < 3 then goto loop'".to_string())
+        );
+        assert_eq!(
+            suggestion("if a < 3 thne goto loop", &[]),
+            Some("Did you mean 'then'?".to_string())
+        );
+    }
+
+    #[test]
+    fn test_suggest_memory_cell() {
+        assert_eq!(
+            suggestion("a := h1", &["h1", "h2"]),
+            Some("Did you mean 'p(h1)'?".to_string())
+        );
+        assert_eq!(
+            suggestion("a := hl + 1", &["h1", "sum"]),
+            Some("Did you mean 'p(h1)'?".to_string())
+        );
+        assert_eq!(
+            suggestion("sun := 1", &["h1", "sum"]),
+            Some("Did you mean 'p(sum)'?".to_string())
+        );
+        assert_eq!(suggestion("a := total", &["h1", "sum"]), None);
+    }
+
+    #[test]
+    fn test_memory_cell_names() {
+        assert_eq!(
+            memory_cell_names("p(h1) := 1 // push\na := p(h1) + ρ(sum)\np(5) := p(a)\nloop: pop"),
+            vec!["h1".to_string(), "sum".to_string(), "a".to_string()]
+        );
+    }
+}
@@ -1,14 +1,17 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::{
     base::{Accumulator, Comparison, MemoryCell, Operation},
     instructions::{
-        assign_index_memory_cell, assign_index_memory_cell_from_value, Identifier,
-        IndexMemoryCellIndexType, Instruction, TargetType, Value, ACCUMULATOR_IDENTIFIER,
-        COMPARISON_IDENTIFIER, CONSTANT_IDENTIFIER, GAMMA_IDENTIFIER, INDEX_MEMORY_CELL_IDENTIFIER,
+        error_handling::BuildProgramErrorTypes, Identifier, IndexMemoryCellIndexType, Instruction,
+        MemoryRef, TargetType, Value, ACCUMULATOR_IDENTIFIER, COMPARISON_IDENTIFIER,
+        CONSTANT_IDENTIFIER, GAMMA_IDENTIFIER, INDEX_MEMORY_CELL_IDENTIFIER,
         MEMORY_CELL_IDENTIFIER, OPERATOR_IDENTIFIER,
     },
-    runtime::{error_handling::RuntimeErrorType, ControlFlow, RuntimeMemory, RuntimeSettings},
+    runtime::{
+        builder::ProgramBuilder, error_handling::RuntimeErrorType, memory_usage::MemoryLocation,
+        ControlFlow, RuntimeMemory, RuntimeSettings,
+    },
     utils::test_utils,
 };
 
@@ -239,6 +242,228 @@ fn test_run_calc_gamma() {
     assert_eq!(runtime_memory.gamma, Some(Some(20)));
 }
 
+#[test]
+fn test_parse_char_literal() {
+    assert_eq!(
+        Instruction::try_from("a0 := 'A'"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(0),
+            Value::Constant(65)
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("if p(h1) == 'z' then goto loop"),
+        Ok(Instruction::JumpIf(
+            Value::MemoryCell("h1".to_string()),
+            Comparison::Eq,
+            Value::Constant(122),
+            "loop".to_string()
+        ))
+    );
+    assert!(Instruction::try_from("a0 := 'AB'").is_err());
+    // whitespace and comment delimiters are part of the literal
+    assert_eq!(
+        Instruction::try_from("a1 := ' '"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(1),
+            Value::Constant(32)
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("a0 := '#'"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(0),
+            Value::Constant(35)
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("a0 := '/'"),
+        Ok(Instruction::Assign(
+            TargetType::Accumulator(0),
+            Value::Constant(47)
+        ))
+    );
+}
+
+#[test]
+fn test_char_literal_program() {
+    let program = "a1 := ' '\na2 := '#' # comment\nloop: a3 := '/' // comment\nif a1 == ' ' then goto end\nend:";
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
+    rt.run().unwrap();
+    let accumulators = &rt.runtime_memory().accumulators;
+    assert_eq!(accumulators.get(&1).unwrap().data, Some(32));
+    assert_eq!(accumulators.get(&2).unwrap().data, Some(35));
+    assert_eq!(accumulators.get(&3).unwrap().data, Some(47));
+}
+
+#[test]
+fn test_parse_rand() {
+    assert_eq!(
+        Instruction::try_from("a0 := rand(1, 6)"),
+        Ok(Instruction::Rand(
+            TargetType::Accumulator(0),
+            Value::Constant(1),
+            Value::Constant(6)
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("p(h1) := rand(a1,p(h2))"),
+        Ok(Instruction::Rand(
+            TargetType::MemoryCell("h1".to_string()),
+            Value::Accumulator(1),
+            Value::MemoryCell("h2".to_string())
+        ))
+    );
+    assert!(Instruction::try_from("a := rand(1)").is_err());
+    assert!(Instruction::try_from("a := rand(1, 2, 3)").is_err());
+    assert!(Instruction::try_from("a := rand(1, 6").is_err());
+    assert!(Instruction::try_from("a := rand(1, x)").is_err());
+}
+
+#[test]
+fn test_run_rand() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    let instruction = Instruction::try_from("a := rand(-2, 3)").unwrap();
+    let mut values = Vec::new();
+    for _ in 0..50 {
+        instruction
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .unwrap();
+        let value = runtime_memory.accumulators[&0].data.unwrap();
+        assert!((-2..=3).contains(&value));
+        values.push(value);
+    }
+    // same seed generates the same numbers
+    let runtime_settings = setup_runtime_settings();
+    for value in values {
+        instruction
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .unwrap();
+        assert_eq!(runtime_memory.accumulators[&0].data, Some(value));
+    }
+    assert_eq!(
+        Instruction::try_from("a := rand(3, 2)").unwrap().run(
+            &mut runtime_memory,
+            &mut control_flow,
+            &runtime_settings
+        ),
+        Err(RuntimeErrorType::RandInvalidRange(3, 2))
+    );
+}
+
+#[test]
+fn test_parse_min_max_abs() {
+    assert_eq!(
+        Instruction::try_from("a0 := min(a1, p(h1))"),
+        Ok(Instruction::Calc(
+            TargetType::Accumulator(0),
+            Value::Accumulator(1),
+            Operation::Min,
+            Value::MemoryCell("h1".to_string())
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("p(h1) := max(5,y)"),
+        Ok(Instruction::Calc(
+            TargetType::MemoryCell("h1".to_string()),
+            Value::Constant(5),
+            Operation::Max,
+            Value::Gamma
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("y := abs(p(1))"),
+        Ok(Instruction::Abs(
+            TargetType::Gamma,
+            Value::IndexMemoryCell(IndexMemoryCellIndexType::Direct(1))
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("stackmax"),
+        Ok(Instruction::StackOp(Operation::Max))
+    );
+    assert!(Instruction::try_from("a := min(1)").is_err());
+    assert!(Instruction::try_from("a := abs(1, 2)").is_err());
+    assert!(Instruction::try_from("a := abs(x)").is_err());
+}
+
+#[test]
+fn test_run_min_max_abs() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    for (instruction, res) in [
+        ("a := min(-3, 2)", -3),
+        ("a := max(-3, 2)", 2),
+        ("a := abs(-3)", 3),
+        ("a := abs(a)", 3),
+    ] {
+        Instruction::try_from(instruction)
+            .unwrap()
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .unwrap();
+        assert_eq!(runtime_memory.accumulators[&0].data, Some(res));
+    }
+    assert!(
+        Instruction::try_from(format!("a := abs({})", i32::MIN).as_str())
+            .unwrap()
+            .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+            .is_err()
+    );
+}
+
+#[test]
+fn test_parse_cond_exec() {
+    assert_eq!(
+        Instruction::try_from("if a0 > 0 then a1 := 5"),
+        Ok(Instruction::CondExec(
+            Value::Accumulator(0),
+            Comparison::Gt,
+            Value::Constant(0),
+            Box::new(Instruction::Assign(
+                TargetType::Accumulator(1),
+                Value::Constant(5)
+            ))
+        ))
+    );
+    assert_eq!(
+        Instruction::try_from("if p(h1) == 1 then push"),
+        Ok(Instruction::CondExec(
+            Value::MemoryCell("h1".to_string()),
+            Comparison::Eq,
+            Value::Constant(1),
+            Box::new(Instruction::Push)
+        ))
+    );
+    assert!(Instruction::try_from("if a > 0 then return").is_err());
+    assert!(Instruction::try_from("if a > 0 then call loop").is_err());
+    assert!(Instruction::try_from("if a > 0 then if a > 1 then a := 1").is_err());
+    assert_eq!(
+        Instruction::try_from("if a > 0 then a := x").map_err(|e| e.range()),
+        Err((19, 19))
+    );
+}
+
+#[test]
+fn test_run_cond_exec() {
+    let mut runtime_memory = setup_runtime_memory();
+    let mut control_flow = ControlFlow::new();
+    let runtime_settings = setup_runtime_settings();
+    runtime_memory.accumulators.get_mut(&0).unwrap().data = Some(1);
+    Instruction::try_from("if a0 > 0 then a1 := 5")
+        .unwrap()
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    assert_eq!(runtime_memory.accumulators[&1].data, Some(5));
+    Instruction::try_from("if a0 < 0 then a1 := 10")
+        .unwrap()
+        .run(&mut runtime_memory, &mut control_flow, &runtime_settings)
+        .unwrap();
+    assert_eq!(runtime_memory.accumulators[&1].data, Some(5));
+}
+
 #[test]
 fn test_parse_assign_index_memory_cell() {
     assert_eq!(
@@ -974,7 +1199,7 @@ p(h4) := p(d) * p(z)
 p(c) := p(h1) + p(h2)
 p(d) := p(h3) + p(h4)
     "#;
-    let mut runtime = test_utils::runtime_from_str(&instructions).unwrap();
+    let mut runtime = test_utils::runtime_from_str(instructions).unwrap();
     runtime.run().unwrap();
     assert_eq!(
         runtime
@@ -1028,7 +1253,7 @@ p(a) := p(a) - 1
 a1 := p(a)
 if a1 > 0 then goto loop
     "#;
-    let mut rt = test_utils::runtime_from_str(&program).unwrap();
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
     rt.run().unwrap();
     assert_eq!(
         rt.runtime_memory()
@@ -1055,7 +1280,7 @@ call func
 a := p(h3)
 return
     "#;
-    let mut rt = test_utils::runtime_from_str(&program).unwrap();
+    let mut rt = test_utils::runtime_from_str(program).unwrap();
     rt.run().unwrap();
     assert_eq!(
         rt.runtime_memory()
@@ -1071,14 +1296,14 @@ return
 /// Sets up runtime runtime_memory in a consistent way because the default implementation for memory cells and accumulators is configgurable.
 fn setup_runtime_memory() -> RuntimeMemory {
     let mut runtime_memory = RuntimeMemory::new_debug(TEST_MEMORY_CELL_LABELS);
-    runtime_memory.memory_cells = HashMap::new();
+    runtime_memory.memory_cells = BTreeMap::new();
     runtime_memory
         .memory_cells
         .insert("h1".to_string(), MemoryCell::new("h1"));
     runtime_memory
         .memory_cells
         .insert("h2".to_string(), MemoryCell::new("h2"));
-    runtime_memory.accumulators = HashMap::new();
+    runtime_memory.accumulators = BTreeMap::new();
     runtime_memory.accumulators.insert(0, Accumulator::new(0));
     runtime_memory.accumulators.insert(1, Accumulator::new(1));
     runtime_memory.accumulators.insert(2, Accumulator::new(2));
@@ -1088,8 +1313,8 @@ fn setup_runtime_memory() -> RuntimeMemory {
 /// Sets up runtime runtime_memory where no memory cells or accumulators are set.
 fn setup_empty_runtime_memory() -> RuntimeMemory {
     let mut runtime_memory = RuntimeMemory::new_debug(TEST_MEMORY_CELL_LABELS);
-    runtime_memory.accumulators = HashMap::new();
-    runtime_memory.memory_cells = HashMap::new();
+    runtime_memory.accumulators = BTreeMap::new();
+    runtime_memory.memory_cells = BTreeMap::new();
     runtime_memory
 }
 
@@ -1155,43 +1380,55 @@ fn test_try_value_from_string_usize_usize_tuple() {
 
 #[test]
 fn test_assign_index_memory_cell() {
-    let mut runtime_memory = RuntimeMemory::new_debug(&[&""]);
+    let mut runtime_memory = RuntimeMemory::new_debug(&[""]);
     let mut runtime_settings = setup_runtime_settings();
     runtime_settings.autodetect_index_memory_cells = true;
     assert_eq!(
-        assign_index_memory_cell(&mut runtime_memory, &runtime_settings, 0, 5),
+        runtime_memory.assign(&MemoryLocation::IndexMemoryCell(0), 5, &runtime_settings),
         Ok(())
     );
+    assert_eq!(
+        runtime_memory.get(&MemoryLocation::IndexMemoryCell(0)),
+        Ok(5)
+    );
     runtime_settings.autodetect_index_memory_cells = false;
     assert_eq!(
-        assign_index_memory_cell(&mut runtime_memory, &runtime_settings, 1, 5),
+        runtime_memory.assign(&MemoryLocation::IndexMemoryCell(1), 5, &runtime_settings),
         Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(1))
     );
 }
 
 #[test]
-fn test_assign_index_memory_cell_from_value() {
-    let mut runtime_memory = RuntimeMemory::new_debug(&[&""]);
-    let mut runtime_settings = setup_runtime_settings();
-    runtime_settings.autodetect_index_memory_cells = true;
+fn test_resolve_memory_ref() {
+    let mut runtime_memory = RuntimeMemory::new_debug(&["h1"]);
+    runtime_memory.accumulators.get_mut(&0).unwrap().data = Some(3);
+    runtime_memory.index_memory_cells.insert(3, Some(-1));
+    runtime_memory.memory_cells.get_mut("h1").unwrap().data = Some(3);
+    let resolve =
+        |t: IndexMemoryCellIndexType| MemoryRef::IndexMemoryCell(t).resolve(&runtime_memory);
     assert_eq!(
-        assign_index_memory_cell_from_value(
-            &mut runtime_memory,
-            &runtime_settings,
-            0,
-            &Value::Constant(5)
-        ),
-        Ok(())
+        resolve(IndexMemoryCellIndexType::Direct(7)),
+        Ok(MemoryLocation::IndexMemoryCell(7))
     );
-    runtime_settings.autodetect_index_memory_cells = false;
     assert_eq!(
-        assign_index_memory_cell_from_value(
-            &mut runtime_memory,
-            &runtime_settings,
-            1,
-            &Value::Constant(5)
-        ),
-        Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(1))
+        resolve(IndexMemoryCellIndexType::Accumulator(0)),
+        Ok(MemoryLocation::IndexMemoryCell(3))
+    );
+    assert_eq!(
+        resolve(IndexMemoryCellIndexType::MemoryCell("h1".to_string())),
+        Ok(MemoryLocation::IndexMemoryCell(3))
+    );
+    assert_eq!(
+        resolve(IndexMemoryCellIndexType::Index(3)),
+        Err(RuntimeErrorType::IndexMemoryCellNegativeIndex(-1))
+    );
+    assert_eq!(
+        resolve(IndexMemoryCellIndexType::Gamma),
+        Err(RuntimeErrorType::GammaUninitialized)
+    );
+    assert_eq!(
+        MemoryRef::MemoryCell("h2".to_string()).resolve(&runtime_memory),
+        Ok(MemoryLocation::MemoryCell("h2".to_string()))
     );
 }
 
@@ -1526,3 +1763,280 @@ fn test_instruction_identifier_imc() {
         "M(M) := A".to_string()
     );
 }
+
+#[test]
+fn test_rand_instruction_identifier() {
+    assert_eq!(
+        Instruction::try_from("a := rand(1, p(h1))")
+            .unwrap()
+            .identifier(),
+        "A := rand(C, M)".to_string()
+    );
+    assert_eq!(
+        Instruction::try_from("a := rand(1, p(h1))")
+            .unwrap()
+            .to_string(),
+        "a0 := rand(1, p(h1))".to_string()
+    );
+}
+
+#[test]
+fn test_cond_exec_instruction_identifier() {
+    let instruction = Instruction::try_from("if a0 >= p(h1) then a1 := a1 + 1").unwrap();
+    assert_eq!(
+        instruction.identifier(),
+        "if A CMP M then A := A OP C".to_string()
+    );
+    assert_eq!(
+        instruction.to_string(),
+        "if a0 >= p(h1) then a1 := a1 + 1".to_string()
+    );
+    assert_eq!(instruction.operation(), Some(&Operation::Add));
+    assert_eq!(instruction.comparison(), Some(&Comparison::Ge));
+}
+
+#[test]
+fn test_min_max_abs_instruction_identifier() {
+    assert_eq!(
+        Instruction::try_from("a := min(a, p(h1))")
+            .unwrap()
+            .identifier(),
+        "A := min(A, M)".to_string()
+    );
+    assert_eq!(
+        Instruction::try_from("a := max(a, 1)").unwrap().to_string(),
+        "a0 := max(a0, 1)".to_string()
+    );
+    assert_eq!(
+        Instruction::try_from("p(h1) := abs(a)")
+            .unwrap()
+            .identifier(),
+        "M := abs(A)".to_string()
+    );
+    assert_eq!(
+        Instruction::try_from("p(h1) := abs(a)")
+            .unwrap()
+            .to_string(),
+        "p(h1) := abs(a0)".to_string()
+    );
+}
+
+/// Returns values of all kinds, that can be written in alpha notation.
+fn round_trip_values() -> Vec<Value> {
+    let index_types = [
+        IndexMemoryCellIndexType::Accumulator(0),
+        IndexMemoryCellIndexType::Accumulator(3),
+        IndexMemoryCellIndexType::Direct(0),
+        IndexMemoryCellIndexType::Direct(12),
+        IndexMemoryCellIndexType::Gamma,
+        IndexMemoryCellIndexType::MemoryCell("h1".to_string()),
+        // p(p(a1)) uses the memory cell a1 as index, while p(a1) uses the accumulator
+        IndexMemoryCellIndexType::MemoryCell("a1".to_string()),
+        IndexMemoryCellIndexType::Index(0),
+        IndexMemoryCellIndexType::Index(2),
+    ];
+    let mut values = vec![
+        Value::Accumulator(0),
+        Value::Accumulator(2),
+        Value::Gamma,
+        Value::Constant(0),
+        Value::Constant(-5),
+        Value::Constant(i32::MAX),
+        Value::Constant(i32::MIN),
+        Value::MemoryCell("h1".to_string()),
+        Value::MemoryCell("a".to_string()),
+        Value::MemoryCell("sum_2".to_string()),
+        Value::MemoryCell("min".to_string()),
+    ];
+    values.extend(index_types.into_iter().map(Value::IndexMemoryCell));
+    values
+}
+
+/// Returns instructions of all kinds, that use all kinds of values, operations and comparisons.
+fn round_trip_instructions() -> Vec<Instruction> {
+    let values = round_trip_values();
+    let targets = values
+        .iter()
+        .filter_map(|v| match v {
+            Value::Accumulator(idx) => Some(TargetType::Accumulator(*idx)),
+            Value::Gamma => Some(TargetType::Gamma),
+            Value::MemoryCell(name) => Some(TargetType::MemoryCell(name.clone())),
+            Value::IndexMemoryCell(t) => Some(TargetType::IndexMemoryCell(t.clone())),
+            Value::Constant(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let operations = [
+        Operation::Add,
+        Operation::Sub,
+        Operation::Mul,
+        Operation::Div,
+        Operation::Mod,
+        Operation::Min,
+        Operation::Max,
+    ];
+    let comparisons = [
+        Comparison::Lt,
+        Comparison::Le,
+        Comparison::Eq,
+        Comparison::Neq,
+        Comparison::Ge,
+        Comparison::Gt,
+    ];
+    let mut instructions = vec![
+        Instruction::Push,
+        Instruction::Pop,
+        Instruction::Return,
+        Instruction::Call("f".to_string()),
+        Instruction::Goto("END".to_string()),
+    ];
+    instructions.extend(operations.iter().map(|op| Instruction::StackOp(*op)));
+    let mut assignments = Vec::new();
+    for target in &targets {
+        for value in &values {
+            assignments.push(Instruction::Assign(target.clone(), value.clone()));
+            assignments.push(Instruction::Abs(target.clone(), value.clone()));
+            assignments.push(Instruction::Rand(
+                target.clone(),
+                value.clone(),
+                Value::Constant(-1),
+            ));
+            for op in operations {
+                assignments.push(Instruction::Calc(
+                    target.clone(),
+                    value.clone(),
+                    op,
+                    Value::Accumulator(1),
+                ));
+                assignments.push(Instruction::Calc(
+                    target.clone(),
+                    Value::Constant(3),
+                    op,
+                    value.clone(),
+                ));
+            }
+        }
+    }
+    for value in &values {
+        for cmp in comparisons {
+            instructions.push(Instruction::JumpIf(
+                value.clone(),
+                cmp,
+                Value::Gamma,
+                "loop".to_string(),
+            ));
+            instructions.push(Instruction::JumpIf(
+                Value::Constant(-1),
+                cmp,
+                value.clone(),
+                "loop".to_string(),
+            ));
+        }
+    }
+    // every conditionally executable instruction is combined with some conditions
+    let conditional = assignments
+        .iter()
+        .chain(&instructions)
+        .filter(|i| i.is_conditionally_executable())
+        .enumerate()
+        .map(|(idx, i)| {
+            Instruction::CondExec(
+                values[idx % values.len()].clone(),
+                comparisons[idx % comparisons.len()],
+                values[(idx * 7 + 3) % values.len()].clone(),
+                Box::new(i.clone()),
+            )
+        })
+        .collect::<Vec<_>>();
+    instructions.extend(assignments);
+    instructions.extend(conditional);
+    instructions
+}
+
+#[test]
+fn test_display_round_trip() {
+    let instructions = round_trip_instructions();
+    assert!(instructions.len() > 1000);
+    for instruction in instructions {
+        let written = instruction.to_string();
+        assert_eq!(
+            Instruction::try_from(written.as_str()),
+            Ok(instruction),
+            "'{written}' is not read as the instruction it was written from"
+        );
+    }
+    // empty lines are not parsed as instruction, the runtime builder inserts noop instructions for them
+    assert!(Instruction::try_from("").is_err());
+}
+
+#[test]
+fn test_display_round_trip_unwritable_names() {
+    for name in ["a1", "y", "α2", "h 1"] {
+        let mut program = ProgramBuilder::new();
+        program.instruction(Instruction::Assign(
+            TargetType::MemoryCell(name.to_string()),
+            Value::Constant(1),
+        ));
+        assert_eq!(
+            program.runtime_builder().err().map(|e| e.reason),
+            Some(BuildProgramErrorTypes::InstructionNotWritable(
+                1,
+                format!("p({name}) := 1")
+            ))
+        );
+    }
+}
+
+#[test]
+fn test_reads_writes() {
+    let accesses = |instruction: &str| {
+        let instruction = Instruction::try_from(instruction).unwrap();
+        (
+            instruction
+                .reads()
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            instruction.writes().map(|w| w.to_string()),
+        )
+    };
+    assert_eq!(
+        accesses("p(h1) := a1 + p(3)"),
+        (vec!["a1".into(), "p(3)".into()], Some("p(h1)".into()))
+    );
+    assert_eq!(
+        accesses("p(p(h2)) := p(y) * 2"),
+        (
+            vec!["y".into(), "p(y)".into(), "p(h2)".into()],
+            Some("p(p(h2))".into())
+        )
+    );
+    assert_eq!(
+        accesses("p(a2) := rand(p(p(4)), 10)"),
+        (
+            vec!["p(4)".into(), "p(p(4))".into(), "a2".into()],
+            Some("p(a2)".into())
+        )
+    );
+    assert_eq!(
+        accesses("if a0 < y then goto loop"),
+        (vec!["a0".into(), "y".into()], None)
+    );
+    assert_eq!(
+        accesses("if p(h1) == 0 then p(a1) := a0"),
+        (
+            vec!["p(h1)".into(), "a0".into(), "a1".into()],
+            Some("p(a1)".into())
+        )
+    );
+    assert_eq!(accesses("push"), (vec!["a0".into()], None));
+    assert_eq!(accesses("pop"), (vec![], Some("a0".into())));
+    assert_eq!(accesses("stack*"), (vec![], None));
+    assert_eq!(accesses("goto loop"), (vec![], None));
+    assert_eq!(
+        MemoryRef::IndexMemoryCell(IndexMemoryCellIndexType::Index(2)).index(),
+        Some(MemoryRef::IndexMemoryCell(
+            IndexMemoryCellIndexType::Direct(2)
+        ))
+    );
+    assert_eq!(MemoryRef::Gamma.index(), None);
+}
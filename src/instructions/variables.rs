@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use super::{
+    error_handling::{BuildProgramError, BuildProgramErrorTypes},
+    IndexMemoryCellIndexType, TargetType,
+};
+
+/// Human readable names for memory locations.
+///
+/// Variables are declared in comment lines of the program, e.g. `#var sum = p(h1)`.
+/// They don't change how the program is run, they are only used to display the memory locations.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Variables {
+    /// Maps memory locations in alpha notation, e.g. `a0`, `y`, `p(h1)` or `p(3)`, to the variable name.
+    names: HashMap<String, String>,
+}
+
+impl Variables {
+    /// Parses all variable declarations in the lines of the program.
+    ///
+    /// Returns an error if a declaration is invalid or if a name or memory location is declared multiple times.
+    pub fn parse(lines: &[String]) -> Result<Self, Box<BuildProgramError>> {
+        let mut names = HashMap::new();
+        for (idx, line) in lines.iter().enumerate() {
+            let Some(declaration) = line
+                .trim()
+                .strip_prefix('#')
+                .map(str::trim_start)
+                .and_then(|s| s.strip_prefix("var"))
+                .filter(|s| s.starts_with(char::is_whitespace))
+            else {
+                continue;
+            };
+            let error = |reason: &str| {
+                Box::new(BuildProgramError {
+                    reason: BuildProgramErrorTypes::InvalidVariableDeclaration(
+                        idx + 1,
+                        line.trim().to_string(),
+                        reason.to_string(),
+                    ),
+                })
+            };
+            let Some((name, location)) = declaration.split_once('=') else {
+                return Err(error("missing '='"));
+            };
+            let (name, location) = (name.trim(), location.trim().to_string());
+            if !is_valid_name(name) {
+                return Err(error(
+                    "names have to start with a letter and may only contain letters, digits and '_'",
+                ));
+            }
+            let location = match TargetType::try_from((&location, (0, location.len()))) {
+                Ok(TargetType::IndexMemoryCell(t))
+                    if !matches!(t, IndexMemoryCellIndexType::Direct(_)) =>
+                {
+                    return Err(error("index memory cells need a fixed index"));
+                }
+                Ok(target) => target.to_string(),
+                Err(_) => return Err(error("not a memory location")),
+            };
+            if names.values().any(|n| n == name) {
+                return Err(error("name is already declared"));
+            }
+            if names.insert(location, name.to_string()).is_some() {
+                return Err(error("memory location already has a name"));
+            }
+        }
+        Ok(Self { names })
+    }
+
+    /// Returns the name of the variable that is declared for `location`.
+    ///
+    /// `location` is in alpha notation, e.g. `a0`, `y`, `p(h1)` or `p(3)`.
+    pub fn name(&self, location: &str) -> Option<&str> {
+        self.names.get(location).map(String::as_str)
+    }
+
+    /// Moves the name that is declared for the memory location `old` to the memory location `new`.
+    pub fn rename_location(&mut self, old: &str, new: &str) {
+        if let Some(name) = self.names.remove(old) {
+            self.names.insert(new.to_string(), name);
+        }
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_alphabetic())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{instructions::error_handling::BuildProgramErrorTypes, utils::test_utils};
+
+    use super::Variables;
+
+    #[allow(clippy::result_large_err)]
+    fn parse(input: &str) -> Result<Variables, BuildProgramErrorTypes> {
+        Variables::parse(&test_utils::string_literal_to_vec(input)).map_err(|e| e.reason)
+    }
+
+    #[test]
+    fn test_parse_variables() {
+        let variables =
+            parse("#var sum = p(h1)\n# var counter=α\n#var idx = p(3)\n#variable = a\na := 1")
+                .unwrap();
+        assert_eq!(variables.name("p(h1)"), Some("sum"));
+        assert_eq!(variables.name("a0"), Some("counter"));
+        assert_eq!(variables.name("p(3)"), Some("idx"));
+        assert_eq!(variables.name("a1"), None);
+    }
+
+    #[test]
+    fn test_parse_variables_invalid() {
+        for input in [
+            "#var sum p(h1)",
+            "#var 1sum = p(h1)",
+            "#var sum = 5",
+            "#var sum = p(a0)",
+            "#var sum = p(h1)\n#var sum = p(h2)",
+            "#var sum = p(h1)\n#var total = p(h1)",
+        ] {
+            assert!(
+                matches!(
+                    parse(input),
+                    Err(BuildProgramErrorTypes::InvalidVariableDeclaration(_, _, _))
+                ),
+                "{input}"
+            );
+        }
+        assert_eq!(
+            parse("a := 1\n#var sum = 5"),
+            Err(BuildProgramErrorTypes::InvalidVariableDeclaration(
+                2,
+                "#var sum = 5".to_string(),
+                "not a memory location".to_string()
+            ))
+        );
+    }
+}
@@ -2,8 +2,12 @@ use app::commands;
 use clap::Parser;
 use cli::Cli;
 use miette::Result;
+use preset::ConfigFile;
 
-use crate::cli::Command;
+use crate::{
+    cli::{BundleArgs, BundleCommand, Command, RefactorArgs, RefactorCommand},
+    exit_code::ExitCode,
+};
 
 /// The application itself
 mod app;
@@ -11,25 +15,87 @@ mod app;
 mod base;
 /// Command line parsing
 mod cli;
+/// Source of the current time
+mod clock;
+/// Options that are set in the header of program files
+mod directives;
+/// Stable codes of the errors and their explanations
+mod error_codes;
+/// Exit codes of the commands
+mod exit_code;
+/// Conversion of alpha notation programs into other languages
+mod export;
+/// Conversion of programs written for other register machines
+mod import;
 /// Supported instructions
 mod instructions;
+/// Presets of command line options
+mod preset;
 /// Program execution
 mod runtime;
 /// Utility functions
 mod utils;
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let mut cli = Cli::parse();
 
-    // perform additional validation checks on provided cli arguments
-    cli::validate_arguments(&cli)?;
+    if cli.global_args.offline {
+        utils::disable_network_access();
+    }
 
     let input_file = match cli.command {
         Command::Load(ref args) => Some(args.file.clone()),
         Command::Check(ref args) => Some(args.file.clone()),
-        Command::Playground(_) => None,
+        Command::Test(ref args) => Some(args.file.clone()),
+        Command::Grade(ref args) => Some(args.file.clone()),
+        Command::Bundle(ref args) => args.file.clone(),
+        Command::Refactor(RefactorArgs {
+            command:
+                RefactorCommand::Rename { ref file, .. }
+                | RefactorCommand::Extract { ref file, .. }
+                | RefactorCommand::Notation { ref file, .. },
+        }) => Some(file.clone()),
+        Command::Import(ref args) => Some(args.file.clone()),
+        Command::Export(ref args) => Some(args.file.clone()),
+        Command::Playground(_)
+        | Command::Follow(_)
+        | Command::Explain(_)
+        | Command::Update(_)
+        | Command::BenchInternal(_) => None,
+    }
+    // - reads the program from stdin
+    .map(|file| {
+        if file == "-" {
+            utils::STDIN_FILENAME.to_string()
+        } else {
+            file
+        }
+    });
+
+    // the program is read before the options are validated, because it can contain directives that set options
+    let instructions = match &cli.command {
+        command if cli::stdin_unsupported(command).is_some() => None,
+        _ => input_file
+            .as_deref()
+            .map(read_file)
+            .transpose()
+            .unwrap_or_else(|e| ExitCode::IoError.exit_with_error(&e)),
     };
 
+    // apply options of the directives in the program that are not set on the command line
+    if let (Some(instructions), Some(file)) = (&instructions, &input_file) {
+        directives::parse(instructions, file)?.apply(&mut cli);
+    }
+
+    // apply options of the selected preset that are not set on the command line
+    if let Some(name) = &cli.global_args.preset {
+        let preset = ConfigFile::load(&cli.global_args)?.preset(name)?;
+        preset.apply(&mut cli);
+    }
+
+    // perform additional validation checks on provided cli arguments
+    cli::validate_arguments(&cli)?;
+
     if cli.global_args.disable_instruction_limit {
         println!(
             "Warning: instruction limit is disabled, this might lead to performance problems!"
@@ -37,26 +103,77 @@ fn main() -> Result<()> {
     }
 
     match &cli.command {
+        Command::Check(check_args) if check_args.watch => {
+            commands::check::watch(&cli.global_args, check_args, &input_file.unwrap())
+        }
         Command::Check(check_args) => commands::check::check(
             &cli.global_args,
             check_args,
-            read_file(input_file.as_ref().unwrap())?,
+            instructions.unwrap(),
             &input_file.unwrap(),
         ),
+        Command::Test(test_args) => commands::test::test(
+            &cli.global_args,
+            test_args,
+            instructions.unwrap(),
+            &input_file.unwrap(),
+        )?,
+        Command::Grade(grade_args) => commands::grade::grade(
+            &cli.global_args,
+            grade_args,
+            instructions.unwrap(),
+            &input_file.unwrap(),
+        )?,
+        Command::Bundle(BundleArgs {
+            command:
+                Some(BundleCommand::Verify {
+                    archive,
+                    content_hash,
+                }),
+            ..
+        }) => commands::bundle::verify(archive, content_hash.as_deref())?,
+        Command::Bundle(bundle_args) => commands::bundle::bundle(
+            &cli.global_args,
+            bundle_args,
+            instructions.unwrap(),
+            &input_file.unwrap(),
+        )?,
+        Command::Refactor(refactor_args) => commands::refactor::refactor(
+            refactor_args,
+            instructions.unwrap(),
+            &input_file.unwrap(),
+        )?,
         Command::Load(load_args) => commands::load::load(
             &cli.global_args,
             load_args,
-            read_file(input_file.as_ref().unwrap())?,
+            instructions.unwrap(),
             input_file.unwrap(),
         )?,
+        Command::Import(import_args) => {
+            commands::import::import(import_args, instructions.unwrap())?
+        }
+        Command::Export(export_args) => commands::export::export(
+            &cli.global_args,
+            export_args,
+            instructions.unwrap(),
+            &input_file.unwrap(),
+        )?,
         Command::Playground(playground_args) => {
             commands::playground::playground(&cli.global_args, playground_args)?
         }
+        Command::Follow(follow_args) => commands::follow::follow(follow_args)?,
+        Command::Explain(explain_args) => commands::explain::explain(explain_args)?,
+        Command::Update(update_args) => commands::update::update(update_args)?,
+        Command::BenchInternal(bench_args) => commands::bench::bench(bench_args)?,
     }
     Ok(())
 }
 
 fn read_file(path: &str) -> Result<Vec<String>> {
+    if path == utils::STDIN_FILENAME {
+        return utils::read_stdin()
+            .map_err(|e| miette::miette!("Unable to read program from stdin: {e}"));
+    }
     match utils::read_file(path) {
         Ok(i) => Ok(i),
         Err(e) => Err(miette::miette!("Unable to read file [{}]: {}", &path, e)),
@@ -0,0 +1,675 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use directories::UserDirs;
+use serde::Deserialize;
+
+use crate::{
+    app::value_format::LOCALE_SEPARATOR,
+    base::{Comparison, Operation},
+    cli::{
+        CheckLoadArgs, Cli, CliError, CliErrorType, Command, GlobalArgs, InstructionLimitingArgs,
+        MemoryArgs,
+    },
+    runtime::EndBehavior,
+    utils,
+};
+
+/// Instruction limit of the strict preset.
+const STRICT_INSTRUCTION_LIMIT: usize = 100_000;
+
+/// Default number of seconds between two automatic saves of the session.
+const DEFAULT_AUTOSAVE_INTERVAL: u64 = 60;
+
+/// Names of the build-in presets.
+const BUILD_IN_PRESETS: [&str; 1] = ["strict"];
+
+/// A set of options that can be applied with `--preset`.
+///
+/// Options that are set on the command line take precedence over the options of the preset. Options that are not set
+/// in the preset are taken from the preset it extends, if it extends one.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Preset {
+    /// Name of the preset whose options are used, if they are not set in this preset.
+    pub extends: Option<String>,
+    /// If set, memory locations are not created automatically.
+    pub disable_memory_detection: Option<bool>,
+    /// If set, the gamma accumulator is enabled.
+    pub enable_gamma_accumulator: Option<bool>,
+    /// Number of available accumulators.
+    pub accumulators: Option<u8>,
+    /// Available memory cells.
+    pub memory_cells: Option<Vec<String>>,
+    /// Available index memory cells.
+    pub index_memory_cells: Option<Vec<usize>>,
+    /// Path to the memory config file.
+    pub memory_config_file: Option<String>,
+    /// Instructions that are allowed, in the format of the allowed instructions file.
+    pub allowed_instructions: Option<Vec<String>>,
+    /// Path to the allowed instructions file.
+    pub allowed_instructions_file: Option<String>,
+    /// Comparisons that are allowed.
+    pub allowed_comparisons: Option<Vec<Comparison>>,
+    /// Operations that are allowed.
+    pub allowed_operations: Option<Vec<Operation>>,
+    /// If set, `while` and `repeat` loops are not allowed.
+    pub deny_structured_loops: Option<bool>,
+    /// If set, empty lines don't consume an execution step.
+    pub skip_empty_lines: Option<bool>,
+    /// If set, labels have to be followed by an instruction in the same line.
+    pub deny_labels_on_empty_lines: Option<bool>,
+    /// What happens when `return` is run while the call stack is empty.
+    pub end_behavior: Option<EndBehavior>,
+    /// If set, labels that only differ by case are the same label.
+    pub case_insensitive_labels: Option<bool>,
+    /// Maximum number of instructions that may be run.
+    pub instruction_limit: Option<usize>,
+    /// Seed for the random number generator.
+    pub seed: Option<u64>,
+}
+
+/// Features of the tui that can be restricted, e.g. to give students a safer set of features than instructors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+    /// Running custom instructions, which can change the memory.
+    CustomInstructions,
+    /// Jumping to a line in debug select mode and skipping instructions.
+    JumpToLine,
+    /// Renaming and extracting lines, which change the program file.
+    Refactoring,
+    /// Restoring the memory and the position in the program of a saved session.
+    Snapshots,
+}
+
+impl Feature {
+    /// Returns the labels of the keybinding hints of the actions that belong to this feature.
+    pub fn actions(self) -> &'static [&'static str] {
+        match self {
+            Self::CustomInstructions => &["Run custom instruction"],
+            Self::JumpToLine => &["Jump to line", "Skip instruction"],
+            Self::Refactoring => &["Rename", "Extract subroutine", "Mark range start"],
+            Self::Snapshots => &[],
+        }
+    }
+}
+
+/// Content of the config file.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Named presets that can be selected with `--preset`.
+    pub presets: HashMap<String, Preset>,
+    /// Features that are only available when a program is loaded with `--instructor`.
+    pub instructor_only: Vec<Feature>,
+    /// If set to false, the program is reset without confirmation, even if many instructions were run.
+    pub confirm_reset: Option<bool>,
+    /// If set to false, the app is closed without confirmation, even if the trace of many instructions was not saved.
+    pub confirm_quit: Option<bool>,
+    /// Settings of the automatic saving of sessions.
+    pub autosave: AutosaveConfig,
+    /// Format of the values in the memory lists.
+    pub value_format: ValueFormatConfig,
+    /// Settings of the local usage statistics.
+    pub statistics: StatisticsConfig,
+}
+
+/// Settings of the automatic saving of sessions, set in the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AutosaveConfig {
+    /// Number of seconds between two saves, sessions are not saved if this is 0.
+    pub interval: u64,
+    /// Directory into which the sessions are saved.
+    ///
+    /// If not set, `sessions` in the config directory is used.
+    pub directory: Option<String>,
+    /// If set, the memory and the position in the program are saved too.
+    pub snapshot: bool,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_AUTOSAVE_INTERVAL,
+            directory: None,
+            snapshot: true,
+        }
+    }
+}
+
+impl AutosaveConfig {
+    /// Returns the directory into which sessions are saved, `None` if it can not be determined.
+    pub fn directory(&self) -> Option<PathBuf> {
+        match &self.directory {
+            Some(directory) => Some(PathBuf::from(directory)),
+            None => UserDirs::new()
+                .map(|user_dirs| user_dirs.home_dir().join(".config/alpha_tui/sessions")),
+        }
+    }
+}
+
+/// Format of the values in the memory lists, set in the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ValueFormatConfig {
+    /// Separator between groups of three digits, `locale` selects the separator of the system locale.
+    ///
+    /// No separator is used if this is empty.
+    pub thousands_separator: String,
+    /// If set, the values are right aligned in a column.
+    pub align: bool,
+    /// Minimal width of the value column.
+    pub width: usize,
+}
+
+impl Default for ValueFormatConfig {
+    fn default() -> Self {
+        Self {
+            thousands_separator: LOCALE_SEPARATOR.to_string(),
+            align: true,
+            width: 0,
+        }
+    }
+}
+
+/// Settings of the local usage statistics, set in the config file.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatisticsConfig {
+    /// If set, the statistics of each session of the tui are appended to the statistics file.
+    pub enabled: bool,
+    /// File to which the statistics are appended.
+    ///
+    /// If not set, `statistics.jsonl` in the config directory is used.
+    pub file: Option<String>,
+}
+
+impl StatisticsConfig {
+    /// Returns the file to which the statistics are appended, `None` if it can not be determined.
+    pub fn file(&self) -> Option<PathBuf> {
+        match &self.file {
+            Some(file) => Some(PathBuf::from(file)),
+            None => UserDirs::new().map(|user_dirs| {
+                user_dirs
+                    .home_dir()
+                    .join(".config/alpha_tui/statistics.jsonl")
+            }),
+        }
+    }
+}
+
+impl ConfigFile {
+    /// Loads the config file.
+    ///
+    /// The file set with `--config-file` is used, if it is not set, `config.json` in the config directory
+    /// (`~/.config/alpha_tui` in linux or `%APPDATA%/alpha_tui` in windows) is used, if it exists.
+    ///
+    /// Returns an empty config if no config file exists.
+    pub fn load(global_args: &GlobalArgs) -> Result<Self, CliError> {
+        if let Some(path) = &global_args.config_file {
+            return Self::try_from_file(path);
+        }
+        if let Some(user_dirs) = UserDirs::new() {
+            let path = user_dirs.home_dir().join(".config/alpha_tui/config.json");
+            if path.is_file() {
+                return Self::try_from_file(&path.to_string_lossy());
+            }
+        }
+        Ok(Self::default())
+    }
+
+    /// Loads the content of the file located at `path` and tries to parse it into a config file.
+    pub fn try_from_file(path: &str) -> Result<Self, CliError> {
+        let content = match utils::read_file(path) {
+            Ok(content) => content.join("\n"),
+            Err(e) => {
+                return Err(CliError::new(CliErrorType::ConfigFileInvalid(
+                    path.to_string(),
+                    e.to_string(),
+                )))
+            }
+        };
+        serde_json::from_str(&content).map_err(|e| {
+            CliError::new(CliErrorType::ConfigFileInvalid(
+                path.to_string(),
+                e.to_string(),
+            ))
+        })
+    }
+
+    /// Returns the preset with the name `name`, with the options of the presets it extends applied.
+    ///
+    /// Presets in the config file take precedence over build-in presets with the same name.
+    pub fn preset(&self, name: &str) -> Result<Preset, CliError> {
+        let mut names = vec![name.to_string()];
+        let mut preset = self.preset_without_base(name)?;
+        while let Some(base) = preset.extends.take() {
+            if names.contains(&base) {
+                names.push(base);
+                return Err(CliError::new(CliErrorType::PresetCycle(names.join(" -> "))));
+            }
+            preset = preset.merge(self.preset_without_base(&base)?);
+            names.push(base);
+        }
+        Ok(preset)
+    }
+
+    /// Returns the preset with the name `name`, without applying the preset it extends.
+    fn preset_without_base(&self, name: &str) -> Result<Preset, CliError> {
+        if let Some(preset) = self.presets.get(name) {
+            return Ok(preset.clone());
+        }
+        match name {
+            "strict" => Ok(Preset::strict()),
+            _ => {
+                let mut available = BUILD_IN_PRESETS
+                    .iter()
+                    .map(ToString::to_string)
+                    .chain(self.presets.keys().cloned())
+                    .collect::<Vec<_>>();
+                available.sort();
+                available.dedup();
+                Err(CliError::new(CliErrorType::UnknownPreset(
+                    name.to_string(),
+                    available.join(", "),
+                )))
+            }
+        }
+    }
+}
+
+impl Preset {
+    /// Preset that matches the course definition.
+    ///
+    /// Memory has to be declared, only the instructions of the official instruction set can be used and the instruction
+    /// limit is lowered. The gamma accumulator can only be used when it is declared.
+    pub fn strict() -> Self {
+        Self {
+            disable_memory_detection: Some(true),
+            allowed_instructions: Some(official_instructions()),
+            allowed_operations: Some(vec![
+                Operation::Add,
+                Operation::Sub,
+                Operation::Mul,
+                Operation::Div,
+                Operation::Mod,
+            ]),
+            deny_structured_loops: Some(true),
+            instruction_limit: Some(STRICT_INSTRUCTION_LIMIT),
+            ..Default::default()
+        }
+    }
+
+    /// Returns this preset where options that are not set are taken from `base`.
+    fn merge(self, base: Preset) -> Self {
+        Self {
+            extends: base.extends,
+            disable_memory_detection: self
+                .disable_memory_detection
+                .or(base.disable_memory_detection),
+            enable_gamma_accumulator: self
+                .enable_gamma_accumulator
+                .or(base.enable_gamma_accumulator),
+            accumulators: self.accumulators.or(base.accumulators),
+            memory_cells: self.memory_cells.or(base.memory_cells),
+            index_memory_cells: self.index_memory_cells.or(base.index_memory_cells),
+            memory_config_file: self.memory_config_file.or(base.memory_config_file),
+            // an allowed instructions file replaces the allowed instructions of the base
+            allowed_instructions: if self.allowed_instructions_file.is_some() {
+                self.allowed_instructions
+            } else {
+                self.allowed_instructions.or(base.allowed_instructions)
+            },
+            allowed_instructions_file: self
+                .allowed_instructions_file
+                .or(base.allowed_instructions_file),
+            allowed_comparisons: self.allowed_comparisons.or(base.allowed_comparisons),
+            allowed_operations: self.allowed_operations.or(base.allowed_operations),
+            deny_structured_loops: self.deny_structured_loops.or(base.deny_structured_loops),
+            skip_empty_lines: self.skip_empty_lines.or(base.skip_empty_lines),
+            deny_labels_on_empty_lines: self
+                .deny_labels_on_empty_lines
+                .or(base.deny_labels_on_empty_lines),
+            end_behavior: self.end_behavior.or(base.end_behavior),
+            case_insensitive_labels: self
+                .case_insensitive_labels
+                .or(base.case_insensitive_labels),
+            instruction_limit: self.instruction_limit.or(base.instruction_limit),
+            seed: self.seed.or(base.seed),
+        }
+    }
+
+    /// Applies this preset to the cli arguments, values that are already set are not changed.
+    pub fn apply(&self, cli: &mut Cli) {
+        let global_args = &mut cli.global_args;
+        if global_args.instruction_limit.is_none() {
+            global_args.instruction_limit = self.instruction_limit;
+        }
+        if global_args.seed.is_none() {
+            global_args.seed = self.seed;
+        }
+        // memory that is declared on the command line replaces the memory of the preset
+        let memory_declared =
+            global_args.memory_config_file.is_some() || declares_memory(&cli.command);
+        if !memory_declared {
+            global_args
+                .memory_config_file
+                .clone_from(&self.memory_config_file);
+        }
+        match &mut cli.command {
+            Command::Load(args) => {
+                self.apply_check_load_args(&mut args.check_load_args, memory_declared);
+            }
+            Command::Check(args) => {
+                self.apply_check_load_args(&mut args.check_load_args, memory_declared);
+            }
+            Command::Playground(args) => {
+                self.apply_memory_args(&mut args.memory_args, memory_declared);
+                self.apply_instruction_limiting_args(&mut args.instruction_limiting_args);
+            }
+            Command::Test(_)
+            | Command::Grade(_)
+            | Command::Bundle(_)
+            | Command::Refactor(_)
+            | Command::Follow(_)
+            | Command::Import(_)
+            | Command::Export(_)
+            | Command::Explain(_)
+            | Command::Update(_)
+            | Command::BenchInternal(_) => (),
+        }
+    }
+
+    /// Applies this preset to the memory arguments of the `check`, `load` and `playground` commands.
+    ///
+    /// The memory of the preset is only applied, if no memory is declared on the command line.
+    fn apply_memory_args(&self, args: &mut MemoryArgs, memory_declared: bool) {
+        // memory can either be declared with a memory config file or with single arguments
+        if !memory_declared && self.memory_config_file.is_none() {
+            args.accumulators = self.accumulators;
+            args.memory_cells.clone_from(&self.memory_cells);
+            args.index_memory_cells.clone_from(&self.index_memory_cells);
+            args.enable_gamma_accumulator = self.enable_gamma_accumulator == Some(true);
+        }
+        // autodetection of single memory types can't be combined with disabled memory detection
+        if args.autodetect.is_none() {
+            args.disable_memory_detection |= self.disable_memory_detection == Some(true);
+        }
+    }
+
+    /// Applies this preset to the arguments of the `check` and `load` commands.
+    ///
+    /// The memory of the preset is only applied, if no memory is declared on the command line.
+    fn apply_check_load_args(&self, args: &mut CheckLoadArgs, memory_declared: bool) {
+        self.apply_memory_args(&mut args.memory_args, memory_declared);
+        args.skip_empty_lines |= self.skip_empty_lines == Some(true);
+        args.deny_labels_on_empty_lines |= self.deny_labels_on_empty_lines == Some(true);
+        if args.end_behavior.is_none() {
+            args.end_behavior = self.end_behavior;
+        }
+        args.case_insensitive_labels |= self.case_insensitive_labels == Some(true);
+        self.apply_instruction_limiting_args(&mut args.instruction_limiting_args);
+    }
+
+    /// Applies the allowed instructions, comparisons and operations of this preset, if they are not set on the
+    /// command line.
+    fn apply_instruction_limiting_args(&self, args: &mut InstructionLimitingArgs) {
+        if args.allowed_instructions_file.is_none() && args.allowed_instructions.is_none() {
+            args.allowed_instructions_file
+                .clone_from(&self.allowed_instructions_file);
+            args.allowed_instructions
+                .clone_from(&self.allowed_instructions);
+        }
+        if args.allowed_comparisons.is_none() {
+            args.allowed_comparisons
+                .clone_from(&self.allowed_comparisons);
+        }
+        if args.allowed_operations.is_none() {
+            args.allowed_operations.clone_from(&self.allowed_operations);
+        }
+        args.deny_structured_loops |= self.deny_structured_loops == Some(true);
+    }
+}
+
+/// Returns true if memory is declared with the arguments of the command.
+fn declares_memory(command: &Command) -> bool {
+    let args = match command {
+        Command::Load(args) => &args.check_load_args.memory_args,
+        Command::Check(args) => &args.check_load_args.memory_args,
+        Command::Playground(args) => &args.memory_args,
+        _ => return false,
+    };
+    args.accumulators.is_some()
+        || args.memory_cells.is_some()
+        || args.index_memory_cells.is_some()
+        || args.enable_gamma_accumulator
+}
+
+/// Returns the instructions of the official instruction set, in the format of the allowed instructions file.
+///
+/// These are all assignments, calculations with infix operations, jumps, calls and stack instructions. Instructions
+/// that are provided by this tool in addition (e.g. `rand`, `abs`, `min` or conditional execution of instructions)
+/// are not included.
+fn official_instructions() -> Vec<String> {
+    let targets = ["A", "Y", "M", "M(A)", "M(C)", "M(Y)", "M(M)", "M(M(C))"];
+    let values = ["A", "C", "Y", "M"];
+    let mut instructions = Vec::new();
+    for target in targets {
+        for value in values {
+            instructions.push(format!("{target} := {value}"));
+            for value_b in values {
+                instructions.push(format!("{target} := {value} OP {value_b}"));
+            }
+        }
+    }
+    for value in values {
+        for value_b in values {
+            instructions.push(format!("if {value} CMP {value_b} then goto"));
+        }
+    }
+    for instruction in ["goto", "call", "return", "push", "pop", "stackOP"] {
+        instructions.push(instruction.to_string());
+    }
+    instructions
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use crate::{
+        base::Comparison,
+        cli::{Cli, Command},
+        runtime::EndBehavior,
+        utils,
+    };
+
+    use super::{
+        official_instructions, AutosaveConfig, ConfigFile, Feature, Preset, StatisticsConfig,
+        ValueFormatConfig,
+    };
+
+    fn config_file(json: &str) -> ConfigFile {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_official_instructions_are_valid() {
+        assert!(utils::build_instruction_whitelist(official_instructions(), "strict").is_ok());
+    }
+
+    #[test]
+    fn test_apply_strict_preset() {
+        let mut cli = Cli::parse_from(["alpha_tui", "check", "test.alpha", "compile"]);
+        Preset::strict().apply(&mut cli);
+        assert_eq!(cli.global_args.instruction_limit, Some(100_000));
+        let Command::Check(args) = cli.command else {
+            panic!("check command expected");
+        };
+        assert!(args.check_load_args.memory_args.disable_memory_detection);
+        let args = args.check_load_args.instruction_limiting_args;
+        assert!(args.deny_structured_loops);
+        assert!(args.allowed_instructions.is_some());
+    }
+
+    #[test]
+    fn test_apply_strict_preset_playground() {
+        let mut cli = Cli::parse_from(["alpha_tui", "playground", "-a", "2"]);
+        Preset::strict().apply(&mut cli);
+        let Command::Playground(args) = cli.command else {
+            panic!("playground command expected");
+        };
+        assert!(args.memory_args.disable_memory_detection);
+        assert_eq!(args.memory_args.accumulators, Some(2));
+    }
+
+    #[test]
+    fn test_apply_strict_preset_cli_precedence() {
+        let mut cli = Cli::parse_from([
+            "alpha_tui",
+            "check",
+            "test.alpha",
+            "compile",
+            "--instruction-limit",
+            "10",
+            "--autodetect",
+            "mc=on",
+            "--allowed-instructions-file",
+            "instructions.json",
+        ]);
+        Preset::strict().apply(&mut cli);
+        assert_eq!(cli.global_args.instruction_limit, Some(10));
+        let Command::Check(args) = cli.command else {
+            panic!("check command expected");
+        };
+        assert!(!args.check_load_args.memory_args.disable_memory_detection);
+        let args = args.check_load_args.instruction_limiting_args;
+        assert!(args.allowed_instructions.is_none());
+    }
+
+    #[test]
+    fn test_config_file_preset() {
+        let config = config_file(
+            r#"{"presets": {"exam1": {"extends": "strict", "instruction_limit": 500, "memory_cells": ["h1"], "allowed_comparisons": ["eq"]}}}"#,
+        );
+        let preset = config.preset("exam1").unwrap();
+        assert_eq!(preset.instruction_limit, Some(500));
+        assert_eq!(preset.disable_memory_detection, Some(true));
+        assert_eq!(preset.allowed_comparisons, Some(vec![Comparison::Eq]));
+        // memory that is declared on the command line replaces the memory of the preset
+        let mut cli = Cli::parse_from(["alpha_tui", "check", "test.alpha", "compile"]);
+        preset.apply(&mut cli);
+        let Command::Check(args) = cli.command else {
+            panic!("check command expected");
+        };
+        assert_eq!(
+            args.check_load_args.memory_args.memory_cells,
+            Some(vec!["h1".to_string()])
+        );
+        let mut cli = Cli::parse_from(["alpha_tui", "check", "test.alpha", "compile", "-a", "1"]);
+        preset.apply(&mut cli);
+        let Command::Check(args) = cli.command else {
+            panic!("check command expected");
+        };
+        assert_eq!(args.check_load_args.memory_args.memory_cells, None);
+    }
+
+    #[test]
+    fn test_config_file_preset_end_behavior() {
+        let config = config_file(r#"{"presets": {"course": {"end_behavior": "halt"}}}"#);
+        let preset = config.preset("course").unwrap();
+        let mut cli = Cli::parse_from(["alpha_tui", "check", "test.alpha", "compile"]);
+        preset.apply(&mut cli);
+        let Command::Check(args) = cli.command else {
+            panic!("check command expected");
+        };
+        assert_eq!(args.check_load_args.end_behavior, Some(EndBehavior::Halt));
+        // the behavior that is set on the command line is used
+        let mut cli = Cli::parse_from([
+            "alpha_tui",
+            "check",
+            "test.alpha",
+            "compile",
+            "--end-behavior",
+            "error",
+        ]);
+        preset.apply(&mut cli);
+        let Command::Check(args) = cli.command else {
+            panic!("check command expected");
+        };
+        assert_eq!(args.check_load_args.end_behavior, Some(EndBehavior::Error));
+    }
+
+    #[test]
+    fn test_config_file_confirm_reset() {
+        assert_eq!(config_file("{}").confirm_reset, None);
+        assert_eq!(
+            config_file(r#"{"confirm_reset": false}"#).confirm_reset,
+            Some(false)
+        );
+        assert_eq!(config_file("{}").confirm_quit, None);
+        assert_eq!(
+            config_file(r#"{"confirm_quit": false}"#).confirm_quit,
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_config_file_autosave() {
+        assert_eq!(config_file("{}").autosave, AutosaveConfig::default());
+        let autosave =
+            config_file(r#"{"autosave": {"interval": 0, "directory": "sessions"}}"#).autosave;
+        assert_eq!(autosave.interval, 0);
+        assert_eq!(autosave.directory(), Some("sessions".into()));
+        assert!(autosave.snapshot);
+        assert!(serde_json::from_str::<ConfigFile>(r#"{"autosave": {"every": 5}}"#).is_err());
+    }
+
+    #[test]
+    fn test_config_file_value_format() {
+        assert_eq!(config_file("{}").value_format, ValueFormatConfig::default());
+        let value_format =
+            config_file(r#"{"value_format": {"thousands_separator": "'", "width": 8}}"#)
+                .value_format;
+        assert_eq!(value_format.thousands_separator, "'");
+        assert!(value_format.align);
+        assert_eq!(value_format.width, 8);
+        assert!(
+            serde_json::from_str::<ConfigFile>(r#"{"value_format": {"separator": ","}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn test_config_file_statistics() {
+        assert_eq!(config_file("{}").statistics, StatisticsConfig::default());
+        assert!(!config_file("{}").statistics.enabled);
+        let statistics =
+            config_file(r#"{"statistics": {"enabled": true, "file": "stats.jsonl"}}"#).statistics;
+        assert!(statistics.enabled);
+        assert_eq!(statistics.file().unwrap().to_str(), Some("stats.jsonl"));
+    }
+
+    #[test]
+    fn test_config_file_instructor_only() {
+        assert!(config_file("{}").instructor_only.is_empty());
+        assert_eq!(
+            config_file(r#"{"instructor_only": ["custom_instructions", "snapshots"]}"#)
+                .instructor_only,
+            vec![Feature::CustomInstructions, Feature::Snapshots]
+        );
+        assert!(serde_json::from_str::<ConfigFile>(r#"{"instructor_only": ["grading"]}"#).is_err());
+    }
+
+    #[test]
+    fn test_config_file_errors() {
+        assert!(serde_json::from_str::<ConfigFile>(r#"{"presets": {"a": {"limit": 5}}}"#).is_err());
+        let config = config_file(
+            r#"{"presets": {"a": {"extends": "b"}, "b": {"extends": "a"}, "c": {"extends": "d"}}}"#,
+        );
+        assert!(config.preset("a").is_err());
+        assert!(config.preset("c").is_err());
+        assert!(config.preset("e").is_err());
+        assert!(config.preset("strict").is_ok());
+    }
+}
@@ -1,20 +1,38 @@
+use std::collections::HashMap;
+
 use crate::{
     base::{Accumulator, MemoryCell},
-    cli::{CheckLoadArgs, CliHint, GlobalArgs, InstructionLimitingArgs},
+    cli::{CheckLoadArgs, CliHint, GlobalArgs, InstructionLimitingArgs, MemoryArgs},
     instructions::{
         error_handling::{BuildProgramError, BuildProgramErrorTypes},
         instruction_config::InstructionConfig,
+        notes::Notes,
+        structured_loops::{self, LoopKeyword, StructuredLoop},
+        variables::Variables,
         Identifier, IndexMemoryCellIndexType, Instruction, TargetType, Value,
     },
+    utils,
 };
 
 use super::{
-    error_handling::RuntimeBuildError, memory_config::MemoryConfig, ControlFlow, Runtime,
-    RuntimeMemory, RuntimeSettings,
+    error_handling::RuntimeBuildError,
+    memory_config::{MemoryConfig, MemoryType},
+    memory_usage::memory_locations,
+    timing::InstructionTimings,
+    ControlFlow, Rng, Runtime, RuntimeMemory, RuntimeSettings,
 };
 
+/// Labels that are added to every program, so that it can be ended with `goto END`.
+pub const END_LABELS: [&str; 6] = ["END", "ENDE", "end", "ende", "End", "Ende"];
+
 pub struct RuntimeBuilder {
     instructions: Vec<Instruction>,
+    /// Structured loops that were desugared into labels and jumps
+    structured_loops: Vec<StructuredLoop>,
+    /// Names of memory locations that are declared in the program
+    variables: Variables,
+    /// Notes that are displayed when the execution reaches a line
+    notes: Notes,
     control_flow: ControlFlow,
     memory_config: Option<MemoryConfig>,
     runtime_settings: Option<RuntimeSettings>,
@@ -33,17 +51,23 @@ impl RuntimeBuilder {
         let mut control_flow = ControlFlow::new();
 
         // build instructions (also updated control flow with detected labels)
-        let instructions = match build_instructions(
+        let (instructions, structured_loops) = match build_instructions(
             instructions_input,
             instructions_input_file_name,
             &mut control_flow,
         ) {
-            Ok(instructions) => instructions,
+            Ok(res) => res,
             Err(e) => return Err(*e),
         };
 
+        let variables = Variables::parse(instructions_input).map_err(|e| *e)?;
+        let notes = Notes::parse(instructions_input);
+
         Ok(Self {
             instructions,
+            structured_loops,
+            variables,
+            notes,
             control_flow,
             memory_config: None,
             runtime_settings: None,
@@ -51,6 +75,38 @@ impl RuntimeBuilder {
         })
     }
 
+    /// Sets the memory config that is used to build the runtime.
+    ///
+    /// A memory config file set in global args is ignored when the memory config was set using this function.
+    pub fn set_memory_config(&mut self, memory_config: MemoryConfig) -> &mut Self {
+        self.memory_config = Some(memory_config);
+        self
+    }
+
+    /// Sets the clock that is used by the runtime, the system clock is used if no clock is set.
+    #[cfg(test)]
+    pub fn set_clock(&mut self, clock: crate::clock::SharedClock) -> &mut Self {
+        self.runtime_settings
+            .get_or_insert_with(RuntimeSettings::default)
+            .clock = clock;
+        self
+    }
+
+    /// Returns the memory config that is used to build the runtime, if it is set.
+    pub fn memory_config(&self) -> Option<&MemoryConfig> {
+        self.memory_config.as_ref()
+    }
+
+    /// Returns the settings that are used to build the runtime, if they are set.
+    pub fn runtime_settings(&self) -> Option<&RuntimeSettings> {
+        self.runtime_settings.as_ref()
+    }
+
+    /// Returns the config that limits which instructions are allowed in the program.
+    pub fn instruction_config(&self) -> &InstructionConfig {
+        &self.instruction_config
+    }
+
     /// Applies the parameters in global args to this runtime builder.
     ///
     /// Already existing values in the `MemoryConfig` and `RuntimeSettings` will be overwritten, if the provided value is not `None`.
@@ -63,6 +119,19 @@ impl RuntimeBuilder {
         // set disable instruction limit value
         let mut settings = self.runtime_settings.take().unwrap_or_default();
         settings.disable_instruction_limit = global_args.disable_instruction_limit;
+        if let Some(limit) = global_args.instruction_limit {
+            settings.instruction_limit = limit;
+        }
+        if let Some(seed) = global_args.seed {
+            settings.rng = Rng::new(seed);
+        }
+        if let Some(limit) = global_args.stack_limit {
+            settings.stack_limit = Some(limit);
+        }
+        if let Some(limit) = global_args.call_stack_limit {
+            settings.call_stack_limit = limit;
+        }
+        settings.tail_call_optimization = global_args.tail_call_optimization;
         self.runtime_settings = Some(settings);
 
         let memory_config = match self.memory_config.take() {
@@ -109,6 +178,23 @@ impl RuntimeBuilder {
     /// is set/updated.
     /// If a memory config already exists, the values supplemented.
     pub fn apply_check_load_args(&mut self, args: &CheckLoadArgs) -> miette::Result<&mut Self> {
+        self.apply_memory_args(&args.memory_args)?;
+        // set how empty lines are handled
+        let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+        runtime_settings.skip_empty_lines = args.skip_empty_lines;
+        runtime_settings.end_behavior = args.end_behavior.unwrap_or_default();
+        runtime_settings.measure = args.measure;
+        self.runtime_settings = Some(runtime_settings);
+        self.control_flow.case_insensitive_labels = args.case_insensitive_labels;
+        self.instruction_config.deny_labels_on_empty_lines |= args.deny_labels_on_empty_lines;
+        Ok(self)
+    }
+
+    /// Applies the provided memory args to this runtime builder.
+    ///
+    /// The declared accumulators and memory cells are added to the memory config, if `--disable-memory-detection` is
+    /// set, the autodetection values of the memory config are overwritten to false.
+    pub fn apply_memory_args(&mut self, args: &MemoryArgs) -> miette::Result<&mut Self> {
         let mut memory_config = self.memory_config.take().unwrap_or_default();
         // set/overwrite memory config values
         // set accumulator config
@@ -136,14 +222,65 @@ impl RuntimeBuilder {
             }
         }
         self.memory_config = Some(memory_config);
+        // set/override memory autodetection values to false, if `--disable-memory-detection` is set
+        if args.disable_memory_detection {
+            let mut memory_config = self.memory_config.take().unwrap_or_default();
+            memory_config.accumulators.autodetection = Some(false);
+            memory_config.gamma_accumulator.autodetection = Some(false);
+            memory_config.memory_cells.autodetection = Some(false);
+            memory_config.index_memory_cells.autodetection = Some(false);
+            // enable gamma accumulator, if specified
+            if args.enable_gamma_accumulator {
+                memory_config.gamma_accumulator.enabled = true;
+            }
+            self.memory_config = Some(memory_config);
+            // update runtime settings
+            let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+            runtime_settings.autodetect_accumulators = false;
+            runtime_settings.autodetect_gamma_accumulator = false;
+            runtime_settings.autodetect_memory_cells = false;
+            runtime_settings.autodetect_index_memory_cells = false;
+            self.runtime_settings = Some(runtime_settings);
+        }
+        if let Some(autodetect) = &args.autodetect {
+            let mut memory_config = self.memory_config.take().unwrap_or_default();
+            let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
+            for arg in autodetect {
+                let (autodetection, setting) = match arg.memory_type {
+                    MemoryType::Accumulators => (
+                        &mut memory_config.accumulators.autodetection,
+                        &mut runtime_settings.autodetect_accumulators,
+                    ),
+                    MemoryType::Gamma => (
+                        &mut memory_config.gamma_accumulator.autodetection,
+                        &mut runtime_settings.autodetect_gamma_accumulator,
+                    ),
+                    MemoryType::MemoryCells => (
+                        &mut memory_config.memory_cells.autodetection,
+                        &mut runtime_settings.autodetect_memory_cells,
+                    ),
+                    MemoryType::IndexMemoryCells => (
+                        &mut memory_config.index_memory_cells.autodetection,
+                        &mut runtime_settings.autodetect_index_memory_cells,
+                    ),
+                };
+                // values set in the memory config take precedence
+                if autodetection.is_none() {
+                    *autodetection = Some(arg.enabled);
+                    *setting = arg.enabled;
+                }
+            }
+            if args.enable_gamma_accumulator {
+                memory_config.gamma_accumulator.enabled = true;
+            }
+            self.memory_config = Some(memory_config);
+            self.runtime_settings = Some(runtime_settings);
+        }
         Ok(self)
     }
 
     /// Applies the provided instruction limiting args to this runtime builder.
     ///
-    /// If `MemoryConfig` is already set, the values for `autodetection` are overwritten to false,
-    /// otherwise a new `MemoryConfig` is created where the values are set to false.
-    ///
     /// All values previously set in `InstructionConfig` struct are replaced by the new values.
     pub fn apply_instruction_limiting_args(
         &mut self,
@@ -163,12 +300,30 @@ impl RuntimeBuilder {
                     if let Some(operations) = config.allowed_operations {
                         self.instruction_config.allowed_operations = Some(operations);
                     }
+                    self.instruction_config.deny_structured_loops |= config.deny_structured_loops;
+                    self.instruction_config.deny_labels_on_empty_lines |=
+                        config.deny_labels_on_empty_lines;
                 }
                 Err(e) => {
                     return Err(e);
                 }
             }
         }
+        if let Some(instructions) = &instruction_limiting_args.allowed_instructions {
+            let mut allowed_instruction_identifiers = self
+                .instruction_config
+                .allowed_instruction_identifiers
+                .take()
+                .unwrap_or_default();
+            allowed_instruction_identifiers.extend(utils::build_instruction_whitelist(
+                instructions.clone(),
+                "preset",
+            )?);
+            self.instruction_config.allowed_instruction_identifiers =
+                Some(allowed_instruction_identifiers);
+        }
+        self.instruction_config.deny_structured_loops |=
+            instruction_limiting_args.deny_structured_loops;
         if let Some(ac) = &instruction_limiting_args.allowed_comparisons {
             // if allowed_comparisons are already set, merge with additional allowed comparisons
             let mut allowed_comparisons = self
@@ -188,26 +343,6 @@ impl RuntimeBuilder {
             allowed_operations.append(&mut ao.clone());
             self.instruction_config.allowed_operations = Some(allowed_operations);
         }
-        // set/override memory autodetection values to false, if `--disable-memory-detection` is set
-        if instruction_limiting_args.disable_memory_detection {
-            let mut memory_config = self.memory_config.take().unwrap_or_default();
-            memory_config.accumulators.autodetection = Some(false);
-            memory_config.gamma_accumulator.autodetection = Some(false);
-            memory_config.memory_cells.autodetection = Some(false);
-            memory_config.index_memory_cells.autodetection = Some(false);
-            // enable gamma accumulator, if specified
-            if instruction_limiting_args.enable_gamma_accumulator {
-                memory_config.gamma_accumulator.enabled = true;
-            }
-            self.memory_config = Some(memory_config);
-            // update runtime settings
-            let mut runtime_settings = self.runtime_settings.take().unwrap_or_default();
-            runtime_settings.autodetect_accumulators = false;
-            runtime_settings.autodetect_gamma_accumulator = false;
-            runtime_settings.autodetect_memory_cells = false;
-            runtime_settings.autodetect_index_memory_cells = false;
-            self.runtime_settings = Some(runtime_settings);
-        }
         Ok(self)
     }
 
@@ -235,17 +370,47 @@ impl RuntimeBuilder {
         }
 
         // check if instructions are used that are not allowed
-        if let Err(e) = check_instructions(&self.instructions, &self.instruction_config) {
+        if let Err(e) = check_structured_loops(&self.structured_loops, &self.instruction_config) {
+            return Err(miette::Report::new(*e));
+        }
+        if let Err(e) = check_instructions(
+            &with_written_loop_conditions(&self.instructions, &self.structured_loops),
+            &self.instruction_config,
+        ) {
+            return Err(miette::Report::new(*e));
+        }
+        if let Err(e) = check_labels_on_empty_lines(
+            &self.control_flow,
+            &self.instructions,
+            &self.structured_loops,
+            &self.instruction_config,
+        ) {
             return Err(miette::Report::new(*e));
         }
 
+        if self.control_flow.case_insensitive_labels {
+            if let Err(e) = check_label_case(&self.control_flow) {
+                return Err(miette::Report::new(*e));
+            }
+        }
+
         // inject end labels to give option to end program using goto END
         inject_end_labels(&mut self.control_flow, self.instructions.len());
 
+        if self.control_flow.case_insensitive_labels {
+            use_defined_labels(&mut self.instructions, &self.control_flow);
+        }
+
         if let Err(e) = check_labels(&self.control_flow, &self.instructions) {
             return Err(miette::Report::new(RuntimeBuildError::LabelUndefined(e)));
         }
 
+        // memory is only declared when a memory config is set, otherwise default memory is used
+        let declared_memory = self
+            .memory_config
+            .as_ref()
+            .map(|_| memory_locations(&memory))
+            .filter(|l| !l.is_empty());
         // Check if all used accumulators and memory_cells exist
         check_missing_vars(
             self.memory_config
@@ -256,23 +421,30 @@ impl RuntimeBuilder {
         )?;
 
         // check if main label is set and update instruction pointer if found
-        if let Some(i) = self.control_flow.instruction_labels.get("main") {
-            self.control_flow.next_instruction_index = *i;
-            self.control_flow.initial_instruction = *i;
+        if let Some(i) = self.control_flow.label_index("main") {
+            self.control_flow.next_instruction_index = i;
+            self.control_flow.initial_instruction = i;
         }
-        if let Some(i) = self.control_flow.instruction_labels.get("MAIN") {
-            self.control_flow.next_instruction_index = *i;
-            self.control_flow.initial_instruction = *i;
+        if let Some(i) = self.control_flow.label_index("MAIN") {
+            self.control_flow.next_instruction_index = i;
+            self.control_flow.initial_instruction = i;
         }
 
-        Ok(Runtime {
+        let mut runtime = Runtime {
             memory: memory.clone(),
             initial_memory: memory,
             instructions: self.instructions,
             control_flow: self.control_flow,
             instruction_runs: 0,
+            timings: settings.measure.then(InstructionTimings::default),
             settings,
-        })
+            structured_loops: self.structured_loops,
+            variables: self.variables,
+            notes: self.notes,
+            declared_memory,
+        };
+        runtime.skip_empty_lines();
+        Ok(runtime)
     }
 }
 
@@ -285,8 +457,9 @@ fn build_instructions(
     instructions_input: &[String],
     file_name: &str,
     control_flow: &mut ControlFlow,
-) -> Result<Vec<Instruction>, Box<BuildProgramError>> {
+) -> Result<(Vec<Instruction>, Vec<StructuredLoop>), Box<BuildProgramError>> {
     let mut instructions = Vec::new();
+    let mut loop_keywords = Vec::new();
     // remove lines starting with # to not make them count towards jump addresses, as they are not displayed in the program
     let instructions_input = instructions_input
         .iter()
@@ -298,9 +471,9 @@ fn build_instructions(
             continue;
         }
         // Remove comments
-        let instruction = remove_comment(instruction);
+        let instruction = utils::remove_comment(instruction);
         // Check for labels
-        let mut splits = instruction.split_whitespace().collect::<Vec<&str>>();
+        let mut splits = utils::split_tokens(&instruction);
         if splits.is_empty() {
             // Line is empty / line contains comment, add dummy instruction
             instructions.push(Instruction::Noop);
@@ -331,7 +504,15 @@ fn build_instructions(
             }
         }
 
-        match Instruction::try_from(&splits) {
+        // loop keywords are replaced with jumps once all loops are known
+        let parsed = match LoopKeyword::parse(&splits) {
+            Some(keyword) => keyword.map(|keyword| {
+                loop_keywords.push((index, keyword));
+                Instruction::Noop
+            }),
+            None => Instruction::try_from(&splits),
+        };
+        match parsed {
             Ok(i) => instructions.push(i),
             Err(e) => {
                 Err(e.into_build_program_error(
@@ -349,24 +530,78 @@ fn build_instructions(
             reason: BuildProgramErrorTypes::MainLabelDefinedMultipleTimes,
         }));
     }
-    Ok(instructions)
+    let structured_loops =
+        structured_loops::desugar(loop_keywords, &mut instructions, control_flow)?;
+    Ok((instructions, structured_loops))
 }
 
-/// Removes everything behind # or // from the string
-pub fn remove_comment(instruction: &str) -> String {
-    instruction
-        .lines()
-        .map(|line| {
-            if let Some(index) = line.find("//") {
-                line[..index].trim()
-            } else if let Some(index) = line.find('#') {
-                line[..index].trim()
-            } else {
-                line.trim()
-            }
+/// Checks if structured loops are used, even though the instruction config does not allow them.
+fn check_structured_loops(
+    structured_loops: &[StructuredLoop],
+    instruction_config: &InstructionConfig,
+) -> Result<(), Box<BuildProgramError>> {
+    if !instruction_config.deny_structured_loops {
+        return Ok(());
+    }
+    match structured_loops.first() {
+        Some(structured_loop) => Err(Box::new(BuildProgramError {
+            reason: BuildProgramErrorTypes::StructuredLoopNotAllowed(
+                structured_loop.start + 1,
+                structured_loop.keyword.to_string(),
+            ),
+        })),
+        None => Ok(()),
+    }
+}
+
+/// Checks if labels are placed on lines without instruction, even though the instruction config does not allow that.
+///
+/// Labels that were generated for structured loops are ignored, as is a label in front of `repeat`.
+fn check_labels_on_empty_lines(
+    control_flow: &ControlFlow,
+    instructions: &[Instruction],
+    structured_loops: &[StructuredLoop],
+    instruction_config: &InstructionConfig,
+) -> Result<(), Box<BuildProgramError>> {
+    if !instruction_config.deny_labels_on_empty_lines {
+        return Ok(());
+    }
+    let first = control_flow
+        .instruction_labels
+        .iter()
+        .filter(|(label, idx)| {
+            instructions.get(**idx) == Some(&Instruction::Noop)
+                && !structured_loops.iter().any(|l| {
+                    l.start == **idx
+                        || l.start_label == **label
+                        || l.exit_label.as_ref() == Some(*label)
+                })
         })
-        .collect::<Vec<_>>()
-        .join("\n")
+        .min_by_key(|(label, idx)| (**idx, label.to_string()));
+    match first {
+        Some((label, idx)) => Err(Box::new(BuildProgramError {
+            reason: BuildProgramErrorTypes::LabelOnEmptyLine(idx + 1, label.clone()),
+        })),
+        None => Ok(()),
+    }
+}
+
+/// Returns the instructions where the conditions of desugared loops use the comparison that was written in the loop.
+///
+/// The desugared jumps use the negated comparison, this makes sure that only the comparisons
+/// written by the user are checked against the allowed comparisons.
+fn with_written_loop_conditions(
+    instructions: &[Instruction],
+    structured_loops: &[StructuredLoop],
+) -> Vec<Instruction> {
+    let mut instructions = instructions.to_vec();
+    for structured_loop in structured_loops {
+        let (idx, written_cmp) = structured_loop.condition();
+        if let Instruction::JumpIf(_, cmp, _, _) = &mut instructions[idx] {
+            *cmp = written_cmp;
+        }
+    }
+    instructions
 }
 
 /// Checks instructions that are set by comparing them with the provided whitelist of instructions.
@@ -376,66 +611,114 @@ pub fn remove_comment(instruction: &str) -> String {
 /// It is also checked if any comparisons or operations are used that are not allowed.
 /// If `instructions` contains instructions, comparisons or operations that are not included in `instruction_config`,
 /// an error is returned. If values are not set in `instruction_config` everything of that type is allowed.
+///
+/// If more than one violation is found, all violations are returned together, grouped by line.
 pub fn check_instructions(
     instructions: &[Instruction],
     instruction_config: &InstructionConfig,
 ) -> Result<(), Box<BuildProgramError>> {
+    let mut violations = Vec::new();
     for (idx, i) in instructions.iter().enumerate() {
-        if let Some(whitelist) = &instruction_config.allowed_instruction_identifiers {
-            if !whitelist.contains(&i.identifier()) && i.identifier() != "NOOP" {
-                // Instruction found, that is forbidden
-                let mut allowed_instructions = whitelist
-                    .iter()
-                    .map(String::to_string)
-                    .collect::<Vec<String>>();
-                allowed_instructions.sort();
-                return Err(Box::new(BuildProgramError {
-                    reason: BuildProgramErrorTypes::InstructionNotAllowed(
-                        idx + 1,
-                        format!("{i}"),
-                        i.identifier(),
-                        allowed_instructions.join("\n").to_string(),
-                    ),
-                }));
+        violations.extend(
+            instruction_violations(idx, i, instruction_config)
+                .into_iter()
+                .map(|reason| (format!("{i}"), reason)),
+        );
+    }
+    let reason = match violations.len() {
+        0 => return Ok(()),
+        1 => violations.remove(0).1,
+        count => {
+            let mut grouped: Vec<String> = Vec::new();
+            let mut last_line = None;
+            for (instruction, reason) in &violations {
+                let (line, description) = violation_description(reason);
+                if last_line != Some(line) {
+                    grouped.push(format!("line {line} '{instruction}':"));
+                    last_line = Some(line);
+                }
+                grouped.push(format!("  - {description}"));
             }
+            BuildProgramErrorTypes::InstructionConfigViolations(count, grouped.join("\n"))
         }
-        // Check if all comparisons are allowed
-        if let Some(ac) = &instruction_config.allowed_comparisons {
-            if let Some(c) = i.comparison() {
-                if !ac.contains(c) {
-                    return Err(Box::new(BuildProgramError {
-                        reason: BuildProgramErrorTypes::ComparisonNotAllowed(
-                            idx + 1,
-                            c.to_string(),
-                            c.cli_hint(),
-                        ),
-                    }));
-                }
+    };
+    Err(Box::new(BuildProgramError { reason }))
+}
+
+/// Returns the violations of the instruction config by the instruction `i` with index `idx`.
+///
+/// The type of the instruction, its comparison and its operation are checked separately.
+fn instruction_violations(
+    idx: usize,
+    i: &Instruction,
+    instruction_config: &InstructionConfig,
+) -> Vec<BuildProgramErrorTypes> {
+    let mut violations = Vec::new();
+    if let Some(whitelist) = &instruction_config.allowed_instruction_identifiers {
+        if !whitelist.contains(&i.identifier()) && i.identifier() != "NOOP" {
+            // Instruction found, that is forbidden
+            let mut allowed_instructions = whitelist
+                .iter()
+                .map(String::to_string)
+                .collect::<Vec<String>>();
+            allowed_instructions.sort();
+            violations.push(BuildProgramErrorTypes::InstructionNotAllowed(
+                idx + 1,
+                format!("{i}"),
+                i.identifier(),
+                allowed_instructions.join("\n").to_string(),
+            ));
+        }
+    }
+    // Check if all comparisons are allowed
+    if let Some(ac) = &instruction_config.allowed_comparisons {
+        if let Some(c) = i.comparison() {
+            if !ac.contains(c) {
+                violations.push(BuildProgramErrorTypes::ComparisonNotAllowed(
+                    idx + 1,
+                    c.to_string(),
+                    c.cli_hint(),
+                ));
             }
         }
-        // Check if all operations are allowed
-        if let Some(ao) = &instruction_config.allowed_operations {
-            if let Some(o) = i.operation() {
-                if !ao.contains(o) {
-                    return Err(Box::new(BuildProgramError {
-                        reason: BuildProgramErrorTypes::OperationNotAllowed(
-                            idx + 1,
-                            o.to_string(),
-                            o.cli_hint(),
-                        ),
-                    }));
-                }
+    }
+    // Check if all operations are allowed
+    if let Some(ao) = &instruction_config.allowed_operations {
+        if let Some(o) = i.operation() {
+            if !ao.contains(o) {
+                violations.push(BuildProgramErrorTypes::OperationNotAllowed(
+                    idx + 1,
+                    o.to_string(),
+                    o.cli_hint(),
+                ));
             }
         }
     }
-    Ok(())
+    violations
+}
+
+/// Returns the line and a short description of a violation of the instruction config.
+fn violation_description(reason: &BuildProgramErrorTypes) -> (usize, String) {
+    match reason {
+        BuildProgramErrorTypes::InstructionNotAllowed(line, _, identifier, _) => (
+            *line,
+            format!("instruction type '{identifier}' is not allowed"),
+        ),
+        BuildProgramErrorTypes::ComparisonNotAllowed(line, comparison, _) => {
+            (*line, format!("comparison '{comparison}' is not allowed"))
+        }
+        BuildProgramErrorTypes::OperationNotAllowed(line, operation, _) => {
+            (*line, format!("operation '{operation}' is not allowed"))
+        }
+        reason => (0, reason.to_string()),
+    }
 }
 
 /// Replaces all index accesses with gamma for memory cells with normal memory cell access.
 ///
 /// So `p(y)` (where y is used as index for the index memory cell) is now changed to a normal
 /// memory cell access, where `y` is the label of a specific memory cell.
-fn replace_gamma_as_index_instructions(instructions: &mut Vec<Instruction>) {
+fn replace_gamma_as_index_instructions(instructions: &mut [Instruction]) {
     for instruction in instructions {
         match instruction {
             Instruction::Assign(target, value) => {
@@ -469,6 +752,37 @@ fn replace_gamma_as_index_instructions(instructions: &mut Vec<Instruction>) {
                 };
                 *instruction = Instruction::Calc(target, value_a, *op, value_b);
             }
+            Instruction::Rand(target, value_a, value_b) => {
+                let target = if target.is_imc_gamma() {
+                    TargetType::MemoryCell("y".to_string())
+                } else {
+                    target.clone()
+                };
+                let value_a = if value_a.is_imc_gamma() {
+                    Value::MemoryCell("y".to_string())
+                } else {
+                    value_a.clone()
+                };
+                let value_b = if value_b.is_imc_gamma() {
+                    Value::MemoryCell("y".to_string())
+                } else {
+                    value_b.clone()
+                };
+                *instruction = Instruction::Rand(target, value_a, value_b);
+            }
+            Instruction::Abs(target, value) => {
+                let target = if target.is_imc_gamma() {
+                    TargetType::MemoryCell("y".to_string())
+                } else {
+                    target.clone()
+                };
+                let value = if value.is_imc_gamma() {
+                    Value::MemoryCell("y".to_string())
+                } else {
+                    value.clone()
+                };
+                *instruction = Instruction::Abs(target, value);
+            }
             Instruction::JumpIf(value_a, cmp, value_b, label) => {
                 let value_a = if value_a.is_imc_gamma() {
                     Value::MemoryCell("y".to_string())
@@ -482,34 +796,230 @@ fn replace_gamma_as_index_instructions(instructions: &mut Vec<Instruction>) {
                 };
                 *instruction = Instruction::JumpIf(value_a, *cmp, value_b, label.clone());
             }
+            Instruction::CondExec(value_a, cmp, value_b, conditional_instruction) => {
+                replace_gamma_as_index_instructions(std::slice::from_mut(
+                    conditional_instruction.as_mut(),
+                ));
+                let value_a = if value_a.is_imc_gamma() {
+                    Value::MemoryCell("y".to_string())
+                } else {
+                    value_a.clone()
+                };
+                let value_b = if value_b.is_imc_gamma() {
+                    Value::MemoryCell("y".to_string())
+                } else {
+                    value_b.clone()
+                };
+                *instruction =
+                    Instruction::CondExec(value_a, *cmp, value_b, conditional_instruction.clone());
+            }
             _ => (),
         }
     }
 }
 
+/// Builds a program from instructions, labels and comments, instead of parsing the lines of a program.
+///
+/// Every instruction that is added is a line of the program, empty lines are added as [`Instruction::Noop`].
+/// The program is checked against the memory config and the instruction config when the runtime is built.
+///
+/// ```ignore
+/// let mut program = ProgramBuilder::new();
+/// program
+///     .instruction(Instruction::Assign(TargetType::Accumulator(0), Value::Constant(3)))
+///     .label("loop")
+///     .comment("counts down to 0")
+///     .instruction(Instruction::Calc(
+///         TargetType::Accumulator(0),
+///         Value::Accumulator(0),
+///         Operation::Sub,
+///         Value::Constant(1),
+///     ))
+///     .instruction(Instruction::JumpIf(
+///         Value::Accumulator(0),
+///         Comparison::Gt,
+///         Value::Constant(0),
+///         "loop".to_string(),
+///     ));
+/// let runtime = program.runtime_builder()?.build()?;
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProgramBuilder {
+    instructions: Vec<Instruction>,
+    /// Labels in the order in which they were defined and the index of the instruction they point to.
+    labels: Vec<(String, usize)>,
+    /// Maps the index of an instruction to the comment in its line.
+    comments: HashMap<usize, String>,
+}
+
+impl ProgramBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a line that contains the instruction.
+    pub fn instruction(&mut self, instruction: Instruction) -> &mut Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Defines a label in the line of the next instruction that is added.
+    ///
+    /// Labels that are defined after the last instruction point to the end of the program.
+    pub fn label(&mut self, label: &str) -> &mut Self {
+        self.labels
+            .push((label.to_string(), self.instructions.len()));
+        self
+    }
+
+    /// Sets the comment of the line of the next instruction that is added, without `//`.
+    pub fn comment(&mut self, comment: &str) -> &mut Self {
+        self.comments
+            .insert(self.instructions.len(), comment.to_string());
+        self
+    }
+
+    /// Returns the lines of the program in alpha notation.
+    ///
+    /// If labels or a comment are defined after the last instruction, they are written in an additional empty line.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for idx in 0..=self.instructions.len() {
+            let mut parts = self
+                .labels
+                .iter()
+                .filter(|(_, target)| *target == idx)
+                .map(|(label, _)| format!("{label}:"))
+                .collect::<Vec<_>>();
+            match self.instructions.get(idx) {
+                Some(Instruction::Noop) | None => (),
+                Some(instruction) => parts.push(instruction.to_string()),
+            }
+            if let Some(comment) = self.comments.get(&idx) {
+                parts.push(format!("// {comment}"));
+            }
+            if idx < self.instructions.len() || !parts.is_empty() {
+                lines.push(parts.join(" "));
+            }
+        }
+        lines
+    }
+
+    /// Checks the labels and instructions of the program and returns a runtime builder for it.
+    #[allow(clippy::result_large_err)]
+    pub fn runtime_builder(&self) -> Result<RuntimeBuilder, BuildProgramError> {
+        let mut control_flow = ControlFlow::new();
+        for (label, idx) in &self.labels {
+            if control_flow
+                .instruction_labels
+                .insert(label.clone(), *idx)
+                .is_some()
+            {
+                let reason = if label == "main" || label == "MAIN" {
+                    BuildProgramErrorTypes::MainLabelDefinedMultipleTimes
+                } else {
+                    BuildProgramErrorTypes::LabelDefinedMultipleTimes(label.clone())
+                };
+                return Err(BuildProgramError { reason });
+            }
+        }
+        if control_flow.instruction_labels.contains_key("main")
+            && control_flow.instruction_labels.contains_key("MAIN")
+        {
+            return Err(BuildProgramError {
+                reason: BuildProgramErrorTypes::MainLabelDefinedMultipleTimes,
+            });
+        }
+        for (idx, instruction) in self.instructions.iter().enumerate() {
+            if let Instruction::CondExec(_, _, _, i) = instruction {
+                if !i.is_conditionally_executable() {
+                    return Err(BuildProgramError {
+                        reason: BuildProgramErrorTypes::InstructionNotConditionallyExecutable(
+                            idx + 1,
+                            i.to_string(),
+                        ),
+                    });
+                }
+            }
+            // the program has to be displayed and exported as text, e.g. a memory cell named a1 is read as p(a1)
+            let written = instruction.to_string();
+            if *instruction != Instruction::Noop
+                && Instruction::try_from(written.as_str()).as_ref() != Ok(instruction)
+            {
+                return Err(BuildProgramError {
+                    reason: BuildProgramErrorTypes::InstructionNotWritable(idx + 1, written),
+                });
+            }
+        }
+        Ok(RuntimeBuilder {
+            instructions: self.instructions.clone(),
+            structured_loops: Vec::new(),
+            variables: Variables::default(),
+            notes: Notes::default(),
+            control_flow,
+            memory_config: None,
+            runtime_settings: None,
+            instruction_config: InstructionConfig::default(),
+        })
+    }
+}
+
 fn inject_end_labels(control_flow: &mut ControlFlow, last_instruction_index: usize) {
-    control_flow
-        .instruction_labels
-        .insert("END".to_string(), last_instruction_index);
-    control_flow
-        .instruction_labels
-        .insert("ENDE".to_string(), last_instruction_index);
-    control_flow
-        .instruction_labels
-        .insert("end".to_string(), last_instruction_index);
-    control_flow
-        .instruction_labels
-        .insert("ende".to_string(), last_instruction_index);
-    control_flow
-        .instruction_labels
-        .insert("End".to_string(), last_instruction_index);
-    control_flow
+    for label in END_LABELS {
+        control_flow
+            .instruction_labels
+            .insert(label.to_string(), last_instruction_index);
+    }
+}
+
+/// Checks that no labels are defined that only differ by case, used when labels are case insensitive.
+///
+/// Must be called before the end labels are injected, as they are defined in different cases.
+fn check_label_case(control_flow: &ControlFlow) -> Result<(), Box<BuildProgramError>> {
+    let mut labels = control_flow
         .instruction_labels
-        .insert("Ende".to_string(), last_instruction_index);
+        .iter()
+        .map(|(label, idx)| (*idx, label))
+        .collect::<Vec<_>>();
+    labels.sort();
+    let mut defined: Vec<&String> = Vec::new();
+    for (_, label) in labels {
+        if defined.iter().any(|d| d.eq_ignore_ascii_case(label)) {
+            let reason = if label.eq_ignore_ascii_case("main") {
+                BuildProgramErrorTypes::MainLabelDefinedMultipleTimes
+            } else {
+                BuildProgramErrorTypes::LabelDefinedMultipleTimes(label.clone())
+            };
+            return Err(Box::new(BuildProgramError { reason }));
+        }
+        defined.push(label);
+    }
+    Ok(())
+}
+
+/// Replaces the labels that are jumped to or called with the labels as they are defined, when labels are case
+/// insensitive, e.g. `goto LOOP` is replaced with `goto loop` if the label is defined as `loop:`.
+///
+/// This makes sure that the labels of the instructions can be looked up directly in the control flow.
+fn use_defined_labels(instructions: &mut [Instruction], control_flow: &ControlFlow) {
+    for instruction in instructions {
+        let instruction = match instruction {
+            Instruction::CondExec(_, _, _, instruction) => instruction.as_mut(),
+            instruction => instruction,
+        };
+        if let Instruction::Goto(label)
+        | Instruction::JumpIf(_, _, _, label)
+        | Instruction::Call(label) = instruction
+        {
+            if let Some(defined) = control_flow.defined_label(label) {
+                *label = defined.to_string();
+            }
+        }
+    }
 }
 
 fn check_label(control_flow: &ControlFlow, label: &str) -> Result<(), String> {
-    if !control_flow.instruction_labels.contains_key(label) {
+    if control_flow.label_index(label).is_none() {
         return Err(label.to_string());
     }
     Ok(())
@@ -535,22 +1045,30 @@ fn check_labels(control_flow: &ControlFlow, instructions: &Vec<Instruction>) ->
 /// If something missing is found, a runtime build error is returned.
 ///
 /// If `add_missing` is true, the missing `accumulator/memory_cell` is added with empty value to the runtime args instead of returning an error.
-fn check_missing_vars(
+pub fn check_missing_vars(
     memory_config: &MemoryConfig,
-    instructions: &Vec<Instruction>,
+    instructions: &[Instruction],
     runtime_memory: &mut RuntimeMemory,
 ) -> Result<(), RuntimeBuildError> {
     for instruction in instructions {
         match instruction {
-            Instruction::Assign(target, source) => {
+            Instruction::Assign(target, source) | Instruction::Abs(target, source) => {
                 target.check_new(runtime_memory, memory_config)?;
                 source.check_new(runtime_memory, memory_config)?;
             }
-            Instruction::Calc(target, value_a, _, value_b) => {
+            Instruction::Calc(target, value_a, _, value_b)
+            | Instruction::Rand(target, value_a, value_b) => {
                 target.check_new(runtime_memory, memory_config)?;
                 value_a.check_new(runtime_memory, memory_config)?;
                 value_b.check_new(runtime_memory, memory_config)?;
             }
+            Instruction::CondExec(_, _, _, instruction) => {
+                check_missing_vars(
+                    memory_config,
+                    std::slice::from_ref(instruction.as_ref()),
+                    runtime_memory,
+                )?;
+            }
             _ => (),
         }
     }
@@ -596,6 +1114,9 @@ pub fn check_memory_cell(
 }
 
 /// Checks if the accumulator or `memory_cell` exists that is used inside an `index_memory_cell`.
+///
+/// Directly indexed cells (e.g. `p(5)`) have to exist, if `add_missing` is not set.
+/// They are not added when `add_missing` is set, as they are created when they are first assigned a value.
 pub fn check_index_memory_cell(
     runtime_args: &mut RuntimeMemory,
     t: &IndexMemoryCellIndexType,
@@ -605,7 +1126,13 @@ pub fn check_index_memory_cell(
         IndexMemoryCellIndexType::Accumulator(idx) => {
             check_accumulator(runtime_args, *idx, add_missing)
         }
-        IndexMemoryCellIndexType::Direct(_) | IndexMemoryCellIndexType::Index(_) => Ok(()),
+        IndexMemoryCellIndexType::Direct(idx) => {
+            if !add_missing && !runtime_args.index_memory_cells.contains_key(idx) {
+                return Err(RuntimeBuildError::IndexMemoryCellMissing(*idx));
+            }
+            Ok(())
+        }
+        IndexMemoryCellIndexType::Index(_) => Ok(()),
         IndexMemoryCellIndexType::Gamma => check_gamma(runtime_args, add_missing),
         IndexMemoryCellIndexType::MemoryCell(name) => {
             check_memory_cell(runtime_args, name, add_missing)
@@ -713,19 +1240,24 @@ impl Value {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
+    use std::collections::{BTreeSet, HashSet};
 
     use crate::{
+        base::{Comparison, Operation},
+        cli::{GlobalArgs, MemoryArgs},
         instructions::{
             error_handling::{BuildProgramError, BuildProgramErrorTypes},
-            IndexMemoryCellIndexType, Instruction,
+            IndexMemoryCellIndexType, Instruction, TargetType, Value,
         },
         runtime::{
             builder::{
                 build_instructions, check_index_memory_cell, check_instructions, InstructionConfig,
+                ProgramBuilder, RuntimeBuilder,
             },
-            error_handling::RuntimeBuildError,
-            ControlFlow, RuntimeMemory,
+            error_handling::{RuntimeBuildError, RuntimeErrorType},
+            memory_config::{AutodetectionArg, MemoryConfig, MemoryType},
+            memory_usage::MemoryLocation,
+            ControlFlow, EndBehavior, RuntimeMemory, RuntimeSettings,
         },
         utils::test_utils,
     };
@@ -774,6 +1306,91 @@ mod tests {
         assert!(!rt.memory.accumulators.contains_key(&4));
     }
 
+    #[test]
+    fn test_memory_args_playground() {
+        // the playground starts with an empty program, its memory is only declared with the memory args
+        let mut rb = ProgramBuilder::new().runtime_builder().unwrap();
+        rb.apply_global_cli_args(&GlobalArgs::default())
+            .unwrap()
+            .apply_memory_args(&MemoryArgs {
+                accumulators: Some(1),
+                memory_cells: Some(vec!["h1".to_string()]),
+                disable_memory_detection: true,
+                ..MemoryArgs::default()
+            })
+            .unwrap();
+        let mut rt = rb.build().unwrap();
+        assert!(rt
+            .run_foreign_instruction(Instruction::try_from("p(h1) := 5").unwrap())
+            .is_ok());
+        assert!(rt
+            .run_foreign_instruction(Instruction::try_from("a0 := p(h1)").unwrap())
+            .is_ok());
+        assert!(rt
+            .run_foreign_instruction(Instruction::try_from("a1 := 1").unwrap())
+            .is_err());
+        assert!(rt
+            .run_foreign_instruction(Instruction::try_from("p(h2) := 1").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_autodetect_args() {
+        let args = MemoryArgs {
+            autodetect: Some(vec![
+                AutodetectionArg {
+                    memory_type: MemoryType::MemoryCells,
+                    enabled: false,
+                },
+                AutodetectionArg {
+                    memory_type: MemoryType::Accumulators,
+                    enabled: false,
+                },
+            ]),
+            ..Default::default()
+        };
+        let build = |memory_config: MemoryConfig| {
+            let mut rb = RuntimeBuilder::new(&["p(h1) := 5".to_string()], "test").unwrap();
+            rb.set_memory_config(memory_config);
+            rb.apply_global_cli_args(&GlobalArgs::default()).unwrap();
+            rb.apply_memory_args(&args).unwrap();
+            rb.build()
+        };
+        let rt = build(MemoryConfig::default());
+        assert!(rt.is_err());
+        // the memory config takes precedence
+        let mut memory_config = MemoryConfig::default();
+        memory_config.memory_cells.autodetection = Some(true);
+        let rt = build(memory_config).unwrap();
+        assert!(rt.settings.autodetect_memory_cells);
+        assert!(!rt.settings.autodetect_accumulators);
+        assert!(rt.settings.autodetect_index_memory_cells);
+    }
+
+    #[test]
+    fn test_autodetected_memory() {
+        let mut rb = RuntimeBuilder::new(
+            &["p(h11) := 5".to_string(), "p(h1) := 1".to_string()],
+            "test",
+        )
+        .unwrap();
+        let mut memory_config = MemoryConfig::default();
+        memory_config
+            .memory_cells
+            .values
+            .insert("h1".to_string(), None);
+        rb.set_memory_config(memory_config);
+        let mut rt = rb.build().unwrap();
+        rt.run().unwrap();
+        assert_eq!(
+            rt.autodetected_memory(),
+            BTreeSet::from([MemoryLocation::MemoryCell("h11".to_string())])
+        );
+        // nothing is reported when no memory is declared
+        let rt = test_utils::runtime_from_str("p(h11) := 5").unwrap();
+        assert!(rt.autodetected_memory().is_empty());
+    }
+
     #[test]
     fn test_check_index_memory_cell() {
         let mut args = RuntimeMemory::new_empty();
@@ -793,6 +1410,14 @@ mod tests {
             ),
             Err(RuntimeBuildError::MemoryCellMissing("h1".to_string()))
         );
+        assert_eq!(
+            check_index_memory_cell(&mut args, &IndexMemoryCellIndexType::Direct(5), false),
+            Err(RuntimeBuildError::IndexMemoryCellMissing(5))
+        );
+        assert_eq!(
+            check_index_memory_cell(&mut args, &IndexMemoryCellIndexType::Direct(5), true),
+            Ok(())
+        );
         assert_eq!(
             check_index_memory_cell(&mut args, &IndexMemoryCellIndexType::Accumulator(0), true),
             Ok(())
@@ -820,7 +1445,7 @@ mod tests {
             .split('\n')
             .map(|f| f.to_string())
             .collect::<Vec<String>>();
-        build_instructions(&lines, "test", &mut ControlFlow::new())
+        build_instructions(&lines, "test", &mut ControlFlow::new()).map(|res| res.0)
     }
 
     #[test]
@@ -861,6 +1486,8 @@ mod tests {
             allowed_instruction_identifiers: Some(allowed_instruction_identifiers),
             allowed_comparisons: None,
             allowed_operations: None,
+            deny_structured_loops: false,
+            deny_labels_on_empty_lines: false,
         };
         let res = check_instructions(&instructions, &allowed_instructions);
         assert_eq!(
@@ -883,10 +1510,168 @@ mod tests {
             allowed_instruction_identifiers: None,
             allowed_comparisons: Some(Vec::new()),
             allowed_operations: None,
+            deny_structured_loops: false,
+            deny_labels_on_empty_lines: false,
         };
         assert!(check_instructions(&instructions, &allowed_instructions).is_err());
     }
 
+    #[test]
+    fn test_comparison_aliases_limited_individually() {
+        let allowed_instructions = InstructionConfig {
+            allowed_instruction_identifiers: None,
+            allowed_comparisons: Some(vec![Comparison::Neq]),
+            allowed_operations: None,
+            deny_structured_loops: false,
+            deny_labels_on_empty_lines: false,
+        };
+        for cmp in ["!=", "≠"] {
+            let instructions =
+                build_instructions_test(&format!("if a {cmp} a then goto loop")).unwrap();
+            assert!(check_instructions(&instructions, &allowed_instructions).is_ok());
+        }
+        for cmp in ["=", "==", "<=", "≤", ">=", "≥"] {
+            let instructions =
+                build_instructions_test(&format!("if a {cmp} a then goto loop")).unwrap();
+            assert!(check_instructions(&instructions, &allowed_instructions).is_err());
+        }
+    }
+
+    #[test]
+    fn test_structured_loops_instruction_config() {
+        let instructions = test_utils::string_literal_to_vec("while a < 10 do\na := a + 1\nend");
+        // the comparison written in the loop is checked, not the negated comparison of the jump
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.instruction_config.allowed_comparisons = Some(vec![Comparison::Lt]);
+        assert!(rb.build().is_ok());
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.instruction_config.deny_structured_loops = true;
+        assert!(rb.build().is_err());
+    }
+
+    #[test]
+    fn test_labels_on_empty_lines() {
+        let instructions =
+            test_utils::string_literal_to_vec("a := 1\nloop:\nl: repeat\na := a - 1\nuntil a == 0");
+        let rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        assert!(rb.build().is_ok());
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.instruction_config.deny_labels_on_empty_lines = true;
+        let err = rb.build().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BuildProgramError>().map(|e| &e.reason),
+            Some(&BuildProgramErrorTypes::LabelOnEmptyLine(
+                2,
+                "loop".to_string()
+            ))
+        );
+        // labels generated for loops and labels in front of repeat are allowed
+        let instructions = test_utils::string_literal_to_vec(
+            "a := 1\nl: repeat\na := a - 1\nuntil a == 0\nwhile a < 2 do\na := a + 1\nend",
+        );
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.instruction_config.deny_labels_on_empty_lines = true;
+        assert!(rb.build().is_ok());
+    }
+
+    #[test]
+    fn test_skip_empty_lines() {
+        let instructions = test_utils::string_literal_to_vec("\na := 1\n// comment\nl:\na := 2\n");
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.runtime_settings = Some(RuntimeSettings {
+            skip_empty_lines: true,
+            ..Default::default()
+        });
+        let mut rt = rb.build().unwrap();
+        assert_eq!(rt.next_instruction_index(), 1);
+        rt.step().unwrap();
+        assert_eq!(rt.next_instruction_index(), 4);
+        rt.step().unwrap();
+        assert!(rt.finished());
+    }
+
+    #[test]
+    fn test_end_behavior() {
+        let instructions = test_utils::string_literal_to_vec("a := 1\nreturn\na := 2");
+        let runtime = |end_behavior| {
+            let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+            rb.runtime_settings = Some(RuntimeSettings {
+                end_behavior,
+                ..Default::default()
+            });
+            rb.build().unwrap()
+        };
+        let mut rt = runtime(EndBehavior::JumpToEnd);
+        rt.run().unwrap();
+        assert_eq!(rt.next_instruction_index(), 3);
+        // the program stays at the return instruction
+        let mut rt = runtime(EndBehavior::Halt);
+        rt.run().unwrap();
+        assert!(rt.finished());
+        assert_eq!(rt.next_instruction_index(), 1);
+        assert!(rt.step().unwrap());
+        rt.reset();
+        assert!(!rt.finished());
+        let mut rt = runtime(EndBehavior::Error);
+        assert_eq!(
+            rt.run().unwrap_err().reason,
+            RuntimeErrorType::ReturnWithEmptyCallStack
+        );
+        // return inside of a function is not affected
+        let instructions = test_utils::string_literal_to_vec("call f\ngoto END\nf: return");
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.runtime_settings = Some(RuntimeSettings {
+            end_behavior: EndBehavior::Error,
+            ..Default::default()
+        });
+        assert!(rb.build().unwrap().run().is_ok());
+    }
+
+    #[test]
+    fn test_case_insensitive_labels() {
+        let runtime = |program: &str| {
+            let mut rb =
+                RuntimeBuilder::new(&test_utils::string_literal_to_vec(program), "test").unwrap();
+            rb.control_flow.case_insensitive_labels = true;
+            rb.build()
+        };
+        let mut rt = runtime(
+            "a := 0\nLoop: a := a + 1\nif a < 3 then goto LOOP\ncall F\ngoto end\nf: return",
+        )
+        .unwrap();
+        // the labels are replaced with the labels as they are defined
+        assert_eq!(rt.instructions()[2].to_string(), "if a0 < 3 then goto Loop");
+        assert_eq!(rt.instructions()[3], Instruction::Call("f".to_string()));
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(3));
+        // labels that only differ by case are defined multiple times
+        let reason = |program| {
+            runtime(program)
+                .unwrap_err()
+                .downcast::<BuildProgramError>()
+                .unwrap()
+                .reason
+        };
+        assert_eq!(
+            reason("loop: a := 1\nLOOP: a := 2"),
+            BuildProgramErrorTypes::LabelDefinedMultipleTimes("LOOP".to_string())
+        );
+        assert_eq!(
+            reason("Main: a := 1\nmain: a := 2"),
+            BuildProgramErrorTypes::MainLabelDefinedMultipleTimes
+        );
+        // the main label is found in every case
+        let rt = runtime("a := 1\nMain: a := 2").unwrap();
+        assert_eq!(rt.next_instruction_index(), 1);
+        // labels are case sensitive by default
+        let rb = RuntimeBuilder::new(
+            &test_utils::string_literal_to_vec("loop: a := 1\ngoto LOOP"),
+            "test",
+        )
+        .unwrap();
+        assert!(rb.build().is_err());
+    }
+
     #[test]
     fn test_bpe_operation_not_allowed() {
         let instructions = build_instructions_test("a := a + p(h1)").unwrap();
@@ -894,7 +1679,86 @@ mod tests {
             allowed_instruction_identifiers: None,
             allowed_comparisons: None,
             allowed_operations: Some(Vec::new()),
+            deny_structured_loops: false,
+            deny_labels_on_empty_lines: false,
         };
         assert!(check_instructions(&instructions, &allowed_instructions).is_err());
     }
+
+    #[test]
+    fn test_program_builder() {
+        let mut program = ProgramBuilder::new();
+        program
+            .instruction(Instruction::Assign(
+                TargetType::Accumulator(0),
+                Value::Constant(3),
+            ))
+            .label("loop")
+            .comment("counts down to 0")
+            .instruction(Instruction::Calc(
+                TargetType::Accumulator(0),
+                Value::Accumulator(0),
+                Operation::Sub,
+                Value::Constant(1),
+            ))
+            .instruction(Instruction::Noop)
+            .instruction(Instruction::JumpIf(
+                Value::Accumulator(0),
+                Comparison::Gt,
+                Value::Constant(0),
+                "loop".to_string(),
+            ))
+            .label("exit");
+        assert_eq!(
+            program.lines(),
+            vec![
+                "a0 := 3",
+                "loop: a0 := a0 - 1 // counts down to 0",
+                "",
+                "if a0 > 0 then goto loop",
+                "exit:",
+            ]
+        );
+        let mut rt = program.runtime_builder().unwrap().build().unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(0));
+        assert_eq!(rt.control_flow().instruction_labels["exit"], 4);
+    }
+
+    #[test]
+    fn test_program_builder_invalid() {
+        let mut program = ProgramBuilder::new();
+        program
+            .label("loop")
+            .instruction(Instruction::Noop)
+            .label("loop")
+            .instruction(Instruction::Noop);
+        assert_eq!(
+            program.runtime_builder().err(),
+            Some(BuildProgramError {
+                reason: BuildProgramErrorTypes::LabelDefinedMultipleTimes("loop".to_string())
+            })
+        );
+        let mut program = ProgramBuilder::new();
+        program.instruction(Instruction::CondExec(
+            Value::Constant(1),
+            Comparison::Eq,
+            Value::Constant(1),
+            Box::new(Instruction::Return),
+        ));
+        assert!(program.runtime_builder().is_err());
+        // the program is checked against the memory config when the runtime is built
+        let mut program = ProgramBuilder::new();
+        program.instruction(Instruction::Assign(
+            TargetType::MemoryCell("h1".to_string()),
+            Value::Constant(1),
+        ));
+        let mut rb = program.runtime_builder().unwrap();
+        rb.apply_memory_args(&MemoryArgs {
+            disable_memory_detection: true,
+            ..MemoryArgs::default()
+        })
+        .unwrap();
+        assert!(rb.build().is_err());
+    }
 }
@@ -9,31 +9,38 @@ pub enum RuntimeBuildError {
     /// Indicates that a label is used in an instruction that does not exist in the control flow.
     /// This would lead to a runtime error.
     #[error("Label '{0}' should be used but is not defined")]
-    #[diagnostic(code("runtime_build_error::label_undefined"), help("Make sure that you include the label somewhere before an instruction.\nExample: '{0}: a0 := 5'"))]
+    #[diagnostic(code("E201"), help("Make sure that you include the label somewhere before an instruction.\nExample: '{0}: a0 := 5'"))]
     LabelUndefined(String),
 
     #[error("Memory cell '{0}' should be used but is missing")]
-    #[diagnostic(code("runtime_build_error::memory_cell_missing"), help("Make sure to include the memory cell '{0}' in the available memory cells.\nExample: alpha_tui -i FILE -m {0}"))]
+    #[diagnostic(code("E202"), help("Make sure to include the memory cell '{0}' in the available memory cells.\nExample: alpha_tui -i FILE -m {0}"))]
     MemoryCellMissing(String),
 
     #[error("Accumulator with id '{0}' should be used but is missing")]
     #[diagnostic(
-        code("runtime_build_error::accumulator_missing"),
+        code("E203"),
         help("Make sure to have the number of available accumulators set to at least {0}+1")
     )]
     AccumulatorMissing(String),
 
     #[error("Gamma accumulator is used in the program but is disabled")]
     #[diagnostic(
-        code("runtime_build_error::gamma_disabled"),
+        code("E204"),
         help("You can't use the gamma accumulator when it is disabled, to enable it you can either enable automatic memory detection\nby removing the \"--disable-memory-detection\" flag or you can explicitly enable it by using the \"--enable-gamma-accumulator\" flag.")
     )]
     GammaDisabled,
 
+    #[error("Index memory cell with index '{0}' should be used but is missing")]
+    #[diagnostic(
+        code("E205"),
+        help("Make sure to include the index memory cell '{0}' in the available index memory cells.\nExample: alpha_tui -i FILE --index-memory-cells {0}")
+    )]
+    IndexMemoryCellMissing(usize),
+
     // TODO add test for this variant
     #[error("Memory config file '{0}' is invalid: {1}")]
     #[diagnostic(
-        code("runtime_build_error::memory_config_file_invalid"),
+        code("E206"),
         help("Make sure that the provided file is formatted correctly.\nSee https://github.com/LMH01/alpha_tui/blob/master/examples/memory_config.json for an example.")
     )]
     MemoryConfigFileInvalid(String, String),
@@ -47,46 +54,68 @@ pub struct RuntimeError {
     pub line_number: usize,
 }
 
+impl RuntimeError {
+    /// Returns true if the instruction that caused this error can be run again.
+    ///
+    /// This is not the case if the instruction was run, but the limits of the runtime were exceeded afterwards.
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self.reason,
+            RuntimeErrorType::StackOverflowError | RuntimeErrorType::DesignLimitReached(_)
+        )
+    }
+
+    /// Returns true if this error was caused by a division or modulo by zero.
+    pub fn is_division_by_zero(&self) -> bool {
+        matches!(
+            self.reason,
+            RuntimeErrorType::IllegalCalculation {
+                cause: CalcError::AttemptToDivideByZero()
+            }
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Error, Diagnostic)]
 pub enum RuntimeErrorType {
     #[error("Attempt to use value of accumulator with index '{0}' while value is not initialized")]
     #[diagnostic(
-        code("runtime_error::accumulator_uninitialized"),
+        code("E301"),
         help("Try assigning a value before accessing it.\nExample: a{0} := 5")
     )]
     AccumulatorUninitialized(usize),
 
     #[error("Attempt to use accumulator with index '{0}' while it does not exist")]
     #[diagnostic(
-        code("runtime_error::accumulator_does_not_exist"),
+        code("E302"),
         help("Make sure to tell the program to use enough accumulators by using the '-a' flag or by specifying the value using a memory config file")
     )]
     AccumulatorDoesNotExist(usize),
 
     #[error("Attempt to use value of accumulator gamma while value is not initialized")]
     #[diagnostic(
-        code("runtime_error::gamma_uninitialized"),
+        code("E303"),
         help("Try assigning a value before accessing it.\nExample: y := 5")
     )]
     GammaUninitialized,
 
     #[error("Attempt to use accumulator gamma while it does not exist")]
     #[diagnostic(
-        code("runtime_error::gamma_does_not_exist"),
+        code("E304"),
         help("Make sure to tell the program to use the gamma accumulator by using the '--enable-gamma-accumulator' flag or by enabling it using a memory config file")
     )]
     GammaDoesNotExist,
 
     #[error("Attempt to use value of memory cell named '{0}' while value is not initialized")]
     #[diagnostic(
-        code("runtime_error::memory_cell_uninitialized"),
+        code("E305"),
         help("Try assigning a value before accessing it.\nExample p({0}) := 5")
     )]
     MemoryCellUninitialized(String),
 
     #[error("Attempt to use value of memory cell named '{0}' that does not exist")]
     #[diagnostic(
-        code("runtime_error::memory_cell_uninitialized"),
+        code("E306"),
         help("Make sure to tell the progrmm to use this memory cell by using the '-m' flag or by enabling it using a memory config file")
     )]
     MemoryCellDoesNotExist(String),
@@ -95,7 +124,7 @@ pub enum RuntimeErrorType {
         "Attempt to use value of index memory cell with index '{0}' while value is not initialized"
     )]
     #[diagnostic(
-        code("runtime_error::index_memory_cell_uninitialized"),
+        code("E307"),
         help("Try assigning a value before accessing it.\nExample p({0}) := 5")
     )]
     IndexMemoryCellUninitialized(usize),
@@ -104,49 +133,49 @@ pub enum RuntimeErrorType {
         "Attempt to use/assign value of index memory cell with index '{0}' that does not exist."
     )]
     #[diagnostic(
-        code("runtime_error::index_memory_cell_does_not_exist"),
+        code("E308"),
         help("Make sure to tell the progrmm to use this memory cell by using the '--index-memory-cells' flag or by enabling it using a memory config file.\nExample: --index-memory-cells {0}")
     )]
     IndexMemoryCellDoesNotExist(usize),
 
     #[error("Attempt to access index memory cell with negative index, '{0}'")]
     #[diagnostic(
-        code("runtime_error::index_memory_cell_negative_index"),
+        code("E309"),
         help("Make sure that the value with which you try to access the index memory cell is positive")
     )]
     IndexMemoryCellNegativeIndex(i32),
 
     #[error("Attempt to push value of a0 onto stack while a0 is not initialized")]
     #[diagnostic(
-        code("runtime_error::push_fail"),
+        code("E310"),
         help("Try assigning a value before accessing it.\nExample: a0 := 5")
     )]
     PushFail,
 
     #[error("Attempt to pop value from stack while stack is empty")]
     #[diagnostic(
-        code("runtime_error::stack_empty"),
+        code("E311"),
         help("Make sure to only use pop when you know that the stack contains at least one value")
     )]
     PopFail,
 
     #[error("Attempt to perform Operation '{0}' on stack while stack does not contain two values")]
     #[diagnostic(
-        code("runtime_error::stack_op_fail"),
+        code("E312"),
         help("Make sure to only use a stack Operation (stack{0}) when you know that the stack contains at least two values")
     )]
     StackOpFail(Operation),
 
     #[error("Stack Overflow")]
     #[diagnostic(
-        code("runtime_error::stack_overflow_error"),
-        help("This error is usually caused by an infinite recursion. Make sure that all of your recursive functions return properly.")
+        code("E313"),
+        help("This error is usually caused by an infinite recursion or by pushing values in an infinite loop. Make sure that all of your recursive functions return properly.\nThe limits can be changed with '--stack-limit' and '--call-stack-limit'.")
     )]
     StackOverflowError,
 
     #[error("Attempt to jump to label '{0}' that does not exist")]
     #[diagnostic(
-        code("runtime_error::label_missing"),
+        code("E314"),
         help("Try to create the label.\nExample: '{0}: a0 := 5' or '{0}:'")
     )]
     LabelMissing(String),
@@ -158,32 +187,45 @@ pub enum RuntimeErrorType {
     //)]
     //AttemptToDivideByZero(),
     #[error("Illegal calculation")]
-    #[diagnostic(code(runtime_error::illegal_calculation))]
+    #[diagnostic(code("E320"))]
     IllegalCalculation {
         #[diagnostic_source]
         cause: CalcError,
     },
 
+    #[error(
+        "Attempt to generate a random number between {0} and {1}, while {0} is larger than {1}"
+    )]
+    #[diagnostic(
+        code("E315"),
+        help("Make sure that the first value of rand is smaller than or equal to the second value.\nExample: a := rand(1, 6)")
+    )]
+    RandInvalidRange(i32, i32),
+
     #[error("Design limit reached")]
     #[diagnostic(
-        code("runtime_error::design_limit_reached"),
-        help("You have run over {0} instructions, this tool is not build for that.\nIf you know exactly what you are doing and would like to circumvent this limit use the '--disable-instruction-limit' option\nWarning: This can cause the progrmm to freeze!")
+        code("E316"),
+        help("You have run over {0} instructions, this tool is not build for that.\nThe limit can be changed with the '--instruction-limit' option.\nIf you know exactly what you are doing and would like to circumvent this limit use the '--disable-instruction-limit' option\nWarning: This can cause the progrmm to freeze!")
     )]
     DesignLimitReached(usize),
+
+    #[error("Attempt to return while the call stack is empty")]
+    #[diagnostic(
+        code("E319"),
+        help("The program is ended with 'return' outside of a function, but '--end-behavior error' is set.\nUse 'goto END' to end the program instead, or change the behavior with '--end-behavior'.")
+    )]
+    ReturnWithEmptyCallStack,
 }
 
 #[derive(Debug, Clone, PartialEq, Error, Diagnostic)]
 pub enum CalcError {
     #[error("Attempt to divide by zero")]
-    #[diagnostic(
-        code("calc_error::attempt_to_divide_by_zero"),
-        help("Division by zero is undefined in mathematics")
-    )]
+    #[diagnostic(code("E317"), help("Division by zero is undefined in mathematics"))]
     AttemptToDivideByZero(),
 
     #[error("Attempt to {0} with overflow")]
     #[diagnostic(
-        code("calc_error::attempt_to_overflow"),
+        code("E318"),
         help("{1} would have resulted in an overflow leading to a wrong value.\nMake sure the integer never leaves the following rmnge: [{},{}]", i32::MIN, i32::MAX)
     )]
     AttemptToOverflow(String, String),
@@ -193,9 +235,11 @@ pub enum CalcError {
 mod tests {
     use crate::{
         base::{MemoryCell, Operation},
+        cli::GlobalArgs,
         instructions::{IndexMemoryCellIndexType, Instruction, TargetType, Value},
         runtime::{
-            error_handling::{CalcError, RuntimeBuildError, RuntimeErrorType},
+            builder::RuntimeBuilder,
+            error_handling::{CalcError, RuntimeBuildError, RuntimeError, RuntimeErrorType},
             ControlFlow, RuntimeMemory, RuntimeSettings, MAX_INSTRUCTION_RUNS,
         },
         utils::test_utils,
@@ -247,6 +291,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rbe_check_memory_exists() {
+        let instruction = Instruction::try_from("p(h1) := a").unwrap();
+        let rt = test_utils::runtime_from_str_with_disable_memory_detection("").unwrap();
+        assert_eq!(
+            rt.check_memory_exists(&instruction),
+            Err(RuntimeBuildError::MemoryCellMissing("h1".to_string()))
+        );
+        // missing memory is created by autodetection
+        let rt = test_utils::runtime_from_str("").unwrap();
+        assert_eq!(rt.check_memory_exists(&instruction), Ok(()));
+    }
+
+    #[test]
+    fn test_rbe_index_memory_cell_missing() {
+        let rt =
+            test_utils::runtime_from_str_with_disable_memory_detection("p(5) := 10").unwrap_err();
+        assert_eq!(
+            format!("{:?}", rt.root_cause()),
+            format!("{:?}", RuntimeBuildError::IndexMemoryCellMissing(5)),
+        );
+    }
+
     #[test]
     fn test_re_accumulator_uninitialized() {
         let mut rm = RuntimeMemory::new(1, vec!["h1".to_string()], None, true);
@@ -456,6 +523,138 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_re_retry_failed_instruction() {
+        let mut rt = test_utils::runtime_from_str("a := 10\na1 := 0\na := a / a1").unwrap();
+        let e = rt.run().unwrap_err();
+        assert!(e.is_division_by_zero());
+        assert_eq!(e.line_number, 3);
+        // the failed instruction is run again after the memory was fixed
+        assert_eq!(rt.next_instruction_index(), 2);
+        rt.run_foreign_instruction(Instruction::Assign(
+            TargetType::Accumulator(1),
+            Value::Constant(2),
+        ))
+        .unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(5));
+        let e = test_utils::runtime_from_str("pop")
+            .unwrap()
+            .run()
+            .unwrap_err();
+        assert!(!e.is_division_by_zero());
+        assert!(e.is_retryable());
+        assert!(!RuntimeError {
+            reason: RuntimeErrorType::StackOverflowError,
+            line_number: 1
+        }
+        .is_retryable());
+    }
+
+    #[test]
+    fn test_re_retry_failed_stack_op() {
+        let mut rt = test_utils::runtime_from_str("a := 10\npush\na := 0\npush\nstack/").unwrap();
+        let e = rt.run().unwrap_err();
+        assert!(e.is_division_by_zero());
+        // the operands are still on the stack
+        assert_eq!(rt.runtime_memory().stack, vec![10, 0]);
+        for instruction in ["pop", "a := 2", "push"] {
+            rt.run_foreign_instruction(Instruction::try_from(instruction).unwrap())
+                .unwrap();
+        }
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().stack, vec![5]);
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(5));
+    }
+
+    #[test]
+    fn test_re_failed_call_keeps_call_stack() {
+        let mut rt = test_utils::runtime_from_str("a := 1").unwrap();
+        assert!(rt
+            .run_foreign_instruction(Instruction::Call("missing".to_string()))
+            .is_err());
+        assert!(rt.control_flow().call_stack.is_empty());
+    }
+
+    #[test]
+    fn test_re_stack_limits() {
+        let mut rb = RuntimeBuilder::new(
+            &test_utils::string_literal_to_vec("a := 1\nloop: push\ngoto loop"),
+            "test",
+        )
+        .unwrap();
+        rb.apply_global_cli_args(&GlobalArgs {
+            stack_limit: Some(3),
+            ..GlobalArgs::default()
+        })
+        .unwrap();
+        let mut rt = rb.build().unwrap();
+        let e = rt.run().unwrap_err();
+        assert_eq!(e.reason, RuntimeErrorType::StackOverflowError);
+        assert_eq!(rt.runtime_memory().stack.len(), 4);
+        let mut rb = RuntimeBuilder::new(
+            &test_utils::string_literal_to_vec("loop: call loop"),
+            "test",
+        )
+        .unwrap();
+        rb.apply_global_cli_args(&GlobalArgs {
+            call_stack_limit: Some(5),
+            ..GlobalArgs::default()
+        })
+        .unwrap();
+        let mut rt = rb.build().unwrap();
+        let e = rt.run().unwrap_err();
+        assert_eq!(e.reason, RuntimeErrorType::StackOverflowError);
+        assert_eq!(rt.control_flow().call_stack.len(), 6);
+    }
+
+    #[test]
+    fn test_re_tail_call_optimization() {
+        let program = test_utils::string_literal_to_vec(
+            "a := 5\ncall count\ngoto END\ncount: a := a - 1\nif a == 0 then goto done\ncall count\n\nreturn\ndone: return",
+        );
+        let runtime = |tail_call_optimization: bool| {
+            let mut rb = RuntimeBuilder::new(&program, "test").unwrap();
+            rb.apply_global_cli_args(&GlobalArgs {
+                call_stack_limit: Some(2),
+                tail_call_optimization,
+                ..GlobalArgs::default()
+            })
+            .unwrap();
+            rb.build().unwrap()
+        };
+        let e = runtime(false).run().unwrap_err();
+        assert_eq!(e.reason, RuntimeErrorType::StackOverflowError);
+        // the frame of count is reused by the recursive call
+        let mut rt = runtime(true);
+        for _ in 0..9 {
+            rt.step().unwrap();
+        }
+        assert_eq!(rt.control_flow().call_stack, vec![2]);
+        assert_eq!(rt.control_flow().tail_calls, vec![2]);
+        rt.run().unwrap();
+        assert_eq!(rt.runtime_memory().accumulators[&0].data, Some(0));
+        assert!(rt.control_flow().call_stack.is_empty());
+    }
+
+    #[test]
+    fn test_re_keep_going() {
+        let mut rt = test_utils::runtime_from_str("a := 10\na := a / a1\npop\np(h1) := a").unwrap();
+        let errors = rt.run_keep_going(&mut |_: &_| ());
+        assert_eq!(
+            errors.iter().map(|e| e.line_number).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+        assert!(rt.finished());
+        assert_eq!(rt.runtime_memory().memory_cells["h1"].data, Some(10));
+        // execution stops if the error can not be skipped
+        let mut rt = test_utils::runtime_from_str("pop\nloop: call loop\na := 1").unwrap();
+        let errors = rt.run_keep_going(&mut |_: &_| ());
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[1].reason, RuntimeErrorType::StackOverflowError);
+        assert!(!rt.finished());
+    }
+
     #[test]
     fn test_re_ce_attempt_to_overflow_add() {
         let mut rm = RuntimeMemory::new(2, vec![], None, true);
@@ -0,0 +1,706 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use miette::Diagnostic;
+use thiserror::Error;
+
+use crate::{
+    instructions::{
+        refactor::{notation_occurrences, prevailing_notation, NotationSymbol},
+        Instruction,
+    },
+    utils,
+};
+
+use super::{builder::END_LABELS, Runtime};
+
+/// Stack sizes above this value are no longer tracked exactly, this makes sure that the analysis terminates.
+const MAX_TRACKED_STACK_SIZE: i32 = 64;
+
+/// Names of all lints, used to set their level and to suppress them.
+pub const LINT_NAMES: [&str; 5] = [
+    "pop_from_empty_stack",
+    "stack_op_on_short_stack",
+    "unbalanced_subroutine",
+    "mixed_notation",
+    "label_case",
+];
+
+/// Prefix of comments that suppress lints, e.g. `# alpha_tui: allow(pop_from_empty_stack)`.
+const SUPPRESSION_PREFIX: &str = "alpha_tui:";
+
+/// Warnings about code that is likely to cause an error when the program is run.
+///
+/// In contrast to build errors, the program can still be run when warnings are found.
+#[derive(Debug, Clone, PartialEq, Eq, Error, Diagnostic)]
+pub enum LintWarning {
+    #[error("'{1}' in line {0} can be run while the stack is empty")]
+    #[diagnostic(
+        code("E401"),
+        severity(Warning),
+        help(
+            "Make sure that a value is pushed onto the stack on every path that leads to this line"
+        )
+    )]
+    PopFromEmptyStack(usize, String),
+
+    #[error("'{1}' in line {0} can be run while the stack contains less than two values")]
+    #[diagnostic(
+        code("E402"),
+        severity(Warning),
+        help("Make sure that two values are pushed onto the stack on every path that leads to this line")
+    )]
+    StackOpOnShortStack(usize, String),
+
+    #[error("subroutine '{0}' changes the size of the stack by {2:+} when it returns in line {1}")]
+    #[diagnostic(
+        code("E403"),
+        severity(Warning),
+        help("Make sure that every value that is pushed onto the stack in the subroutine is also popped before it returns")
+    )]
+    UnbalancedSubroutine(String, usize, i32),
+
+    /// The program writes a symbol in ascii and in unicode notation, e.g. `p(h1)` and `ρ(h2)`.
+    ///
+    /// 0 = line, 1 = notation used in the line, 2 = notation used in the rest of the program
+    #[error("'{1}' in line {0} is written in a different notation than in the rest of the program, which uses '{2}'")]
+    #[diagnostic(
+        code("E404"),
+        severity(Warning),
+        help("Use the same notation in the whole program, 'alpha_tui refactor notation <FILE>' changes it automatically")
+    )]
+    MixedNotation(usize, String, String),
+
+    /// A label is defined that only differs by case from a label that is defined in an earlier line.
+    ///
+    /// 0 = line, 1 = label defined in the line, 2 = label defined earlier
+    #[error("label '{1}' in line {0} only differs by case from label '{2}'")]
+    #[diagnostic(
+        code("E405"),
+        severity(Warning),
+        help("Labels are case sensitive, rename one of the labels or use '--case-insensitive-labels' if they should be the same label")
+    )]
+    LabelCase(usize, String, String),
+}
+
+impl LintWarning {
+    /// Returns the name of the lint that caused this warning.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PopFromEmptyStack(_, _) => LINT_NAMES[0],
+            Self::StackOpOnShortStack(_, _) => LINT_NAMES[1],
+            Self::UnbalancedSubroutine(_, _, _) => LINT_NAMES[2],
+            Self::MixedNotation(_, _, _) => LINT_NAMES[3],
+            Self::LabelCase(_, _, _) => LINT_NAMES[4],
+        }
+    }
+
+    /// Returns the line in which the code is located that caused this warning.
+    pub fn line(&self) -> usize {
+        match self {
+            Self::PopFromEmptyStack(line, _)
+            | Self::StackOpOnShortStack(line, _)
+            | Self::UnbalancedSubroutine(_, line, _)
+            | Self::MixedNotation(line, _, _)
+            | Self::LabelCase(line, _, _) => *line,
+        }
+    }
+}
+
+/// Analyzes the program of the runtime and returns warnings about code that is likely to cause an error
+/// or that is written inconsistently.
+///
+/// `lines` are the lines of the program, they are used for warnings about how the program is written.
+/// The returned warnings are sorted by line.
+pub fn lint(runtime: &Runtime, lines: &[String]) -> Vec<LintWarning> {
+    let mut warnings = stack_warnings(runtime);
+    warnings.extend(notation_warnings(lines));
+    warnings.extend(label_case_warnings(runtime));
+    warnings.sort_by_key(LintWarning::line);
+    warnings
+}
+
+/// Returns warnings for labels that only differ by case from a label that is defined in an earlier line.
+///
+/// The end labels are defined in different cases on purpose and are not reported.
+fn label_case_warnings(runtime: &Runtime) -> Vec<LintWarning> {
+    let mut labels = runtime
+        .control_flow
+        .instruction_labels
+        .iter()
+        .filter(|(label, _)| !END_LABELS.contains(&label.as_str()))
+        .map(|(label, idx)| (*idx, label))
+        .collect::<Vec<_>>();
+    labels.sort();
+    let mut warnings = Vec::new();
+    for (pos, (idx, label)) in labels.iter().enumerate() {
+        if let Some((_, earlier)) = labels[..pos]
+            .iter()
+            .find(|(_, earlier)| earlier.eq_ignore_ascii_case(label))
+        {
+            warnings.push(LintWarning::LabelCase(
+                idx + 1,
+                (*label).clone(),
+                (*earlier).clone(),
+            ));
+        }
+    }
+    warnings
+}
+
+/// Determines what happens when a lint finds a problem.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum LintLevel {
+    /// The warning is not reported.
+    Allow,
+    /// The warning is reported, but the check is still successful.
+    #[default]
+    Warn,
+    /// The warning is reported as error and the check fails, it can't be suppressed with comments.
+    Deny,
+}
+
+/// Levels of the lints and the lints that are suppressed in single lines.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintLevels {
+    /// Levels of lints that don't use the default level.
+    levels: HashMap<String, LintLevel>,
+    /// Maps the index of an instruction to the lints that are suppressed in its line.
+    suppressed: HashMap<usize, HashSet<String>>,
+}
+
+impl LintLevels {
+    /// Creates the lint levels from the names of the lints that are allowed, warned about and denied.
+    ///
+    /// If a lint is listed more than once, the strictest level is used.
+    pub fn new(allow: &[String], warn: &[String], deny: &[String]) -> Self {
+        let mut levels = HashMap::new();
+        for (names, level) in [
+            (allow, LintLevel::Allow),
+            (warn, LintLevel::Warn),
+            (deny, LintLevel::Deny),
+        ] {
+            for name in names {
+                let entry = levels.entry(name.clone()).or_insert(level);
+                *entry = (*entry).max(level);
+            }
+        }
+        Self {
+            levels,
+            suppressed: HashMap::new(),
+        }
+    }
+
+    /// Reads the comments in the lines of the program that suppress lints, e.g. `# alpha_tui: allow(pop_from_empty_stack)`.
+    ///
+    /// A comment suppresses the lints in its line, or in the next line that contains an instruction, if it is
+    /// written in a line without instruction.
+    pub fn with_suppressions(mut self, lines: &[String]) -> Self {
+        let mut pending: HashSet<String> = HashSet::new();
+        // lines starting with # don't count towards the instruction indices, as they are not displayed in the program
+        let mut index = 0;
+        for line in lines {
+            if let Some(comment) = utils::get_comment(line) {
+                pending.extend(suppressed_lints(&comment));
+            }
+            if line.trim().starts_with('#') {
+                continue;
+            }
+            if !pending.is_empty() && !utils::remove_comment(line).is_empty() {
+                self.suppressed
+                    .entry(index)
+                    .or_default()
+                    .extend(pending.drain());
+            }
+            index += 1;
+        }
+        self
+    }
+
+    /// Returns the level of the warning, `Allow` if it is suppressed in its line.
+    pub fn level(&self, warning: &LintWarning) -> LintLevel {
+        let level = self.levels.get(warning.name()).copied().unwrap_or_default();
+        let suppressed = self
+            .suppressed
+            .get(&(warning.line() - 1))
+            .is_some_and(|names| names.contains(warning.name()));
+        if suppressed && level == LintLevel::Warn {
+            LintLevel::Allow
+        } else {
+            level
+        }
+    }
+}
+
+/// Returns the names of the lints that are suppressed by the comment, e.g. `# alpha_tui: allow(a, b)`.
+fn suppressed_lints(comment: &str) -> Vec<String> {
+    let comment = comment
+        .trim_start_matches('#')
+        .trim_start_matches("//")
+        .trim();
+    comment
+        .strip_prefix(SUPPRESSION_PREFIX)
+        .and_then(|c| c.trim().strip_prefix("allow("))
+        .and_then(|c| c.trim_end().strip_suffix(')'))
+        .map(|names| {
+            names
+                .split(',')
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Finds lines that write memory cells or the gamma accumulator in a different notation than the rest of the program.
+///
+/// Each symbol is expected to be written in the notation that is used for most of its occurrences.
+fn notation_warnings(lines: &[String]) -> Vec<LintWarning> {
+    let occurrences = notation_occurrences(lines);
+    let mut warnings: Vec<LintWarning> = Vec::new();
+    for symbol in NotationSymbol::ALL {
+        let Some(prevailing) = prevailing_notation(&occurrences, symbol) else {
+            continue;
+        };
+        for occurrence in occurrences
+            .iter()
+            .filter(|o| o.symbol == symbol && o.notation != prevailing)
+        {
+            let warning = LintWarning::MixedNotation(
+                occurrence.line_number,
+                symbol.display(occurrence.notation),
+                symbol.display(prevailing),
+            );
+            // one warning per line is enough
+            if !warnings.contains(&warning) {
+                warnings.push(warning);
+            }
+        }
+    }
+    warnings
+}
+
+/// Size of the stack at a specific point in the program.
+///
+/// `None` indicates that the size is unknown, for example because a loop pushes values onto the stack.
+type StackSize = Option<i32>;
+
+/// Change of the stack size caused by calling a subroutine.
+///
+/// `None` indicates that the subroutine never returns, `Some(None)` indicates that the change is unknown.
+type SubroutineEffect = Option<StackSize>;
+
+/// Tracks the size of the stack along all control-flow paths to find pops from an empty stack and
+/// subroutines that leave the stack unbalanced.
+///
+/// Conditions are not evaluated, this means that every branch is assumed to be possible.
+fn stack_warnings(runtime: &Runtime) -> Vec<LintWarning> {
+    let effects = subroutine_effects(runtime);
+    let mut warnings = Vec::new();
+
+    // pops from an empty stack, the size of the stack is tracked from the start of the program
+    let initial_size = runtime.initial_memory.stack.len() as i32;
+    let sizes = stack_sizes(
+        runtime,
+        runtime.initial_instruction_index(),
+        Some(initial_size.min(MAX_TRACKED_STACK_SIZE + 1)),
+        &effects,
+        false,
+    );
+    for (idx, instruction) in runtime.instructions().iter().enumerate() {
+        let Some(sizes) = sizes.get(&idx) else {
+            continue;
+        };
+        let instruction = match instruction {
+            Instruction::CondExec(_, _, _, instruction) => instruction.as_ref(),
+            _ => instruction,
+        };
+        match instruction {
+            Instruction::Pop if sizes.contains(&Some(0)) => {
+                warnings.push(LintWarning::PopFromEmptyStack(
+                    idx + 1,
+                    instruction.to_string(),
+                ));
+            }
+            Instruction::StackOp(_) if sizes.contains(&Some(0)) || sizes.contains(&Some(1)) => {
+                warnings.push(LintWarning::StackOpOnShortStack(
+                    idx + 1,
+                    instruction.to_string(),
+                ));
+            }
+            _ => (),
+        }
+    }
+
+    // unbalanced subroutines, the size of the stack is tracked relative to the size when the subroutine was called
+    for label in called_labels(runtime) {
+        let Some(start) = runtime.control_flow.instruction_labels.get(&label) else {
+            continue;
+        };
+        let sizes = stack_sizes(runtime, *start, Some(0), &effects, true);
+        let mut returns = sizes
+            .iter()
+            .filter(|(idx, _)| runtime.instructions().get(**idx) == Some(&Instruction::Return))
+            .collect::<Vec<_>>();
+        returns.sort_by_key(|(idx, _)| **idx);
+        for (idx, sizes) in returns {
+            if let Some(change) = sizes.iter().flatten().find(|change| **change != 0) {
+                warnings.push(LintWarning::UnbalancedSubroutine(
+                    label.clone(),
+                    idx + 1,
+                    *change,
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+/// Returns the labels that are called by `call` instructions, sorted by name.
+fn called_labels(runtime: &Runtime) -> BTreeSet<String> {
+    runtime
+        .instructions()
+        .iter()
+        .filter_map(|instruction| match instruction {
+            Instruction::Call(label) => Some(label.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Calculates how calling each subroutine changes the size of the stack.
+///
+/// As subroutines can call other subroutines, the effects are updated until they no longer change.
+fn subroutine_effects(runtime: &Runtime) -> HashMap<String, SubroutineEffect> {
+    let labels = called_labels(runtime);
+    let mut effects: HashMap<String, SubroutineEffect> = labels
+        .iter()
+        .map(|label| (label.clone(), Some(None)))
+        .collect();
+    for _ in 0..=labels.len() {
+        let mut changed = false;
+        for label in &labels {
+            let effect = match runtime.control_flow.instruction_labels.get(label) {
+                Some(start) => {
+                    let sizes = stack_sizes(runtime, *start, Some(0), &effects, true);
+                    let changes = sizes
+                        .iter()
+                        .filter(|(idx, _)| {
+                            runtime.instructions().get(**idx) == Some(&Instruction::Return)
+                        })
+                        .flat_map(|(_, sizes)| sizes.iter().copied())
+                        .collect::<HashSet<_>>();
+                    match changes.len() {
+                        0 => None,
+                        1 => changes.into_iter().next(),
+                        _ => Some(None),
+                    }
+                }
+                None => Some(None),
+            };
+            if effects.insert(label.clone(), effect) != Some(effect) {
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    effects
+}
+
+/// How the size of the stack changes when the program continues from one instruction to another.
+enum Transfer<'a> {
+    /// The size does not change.
+    Keep,
+    /// The size changes as `instruction` is run.
+    Run(&'a Instruction),
+    /// The size changes by the effect of a called subroutine.
+    Change(StackSize),
+}
+
+/// Returns the edges of the control-flow graph, each edge contains the source and target index and how the size of the stack changes.
+///
+/// If `enter_calls` is set, `call` instructions have an edge to the called subroutine.
+fn control_flow_edges<'a>(
+    runtime: &'a Runtime,
+    effects: &HashMap<String, SubroutineEffect>,
+    enter_calls: bool,
+) -> Vec<(usize, usize, Transfer<'a>)> {
+    let labels = &runtime.control_flow.instruction_labels;
+    let mut edges = Vec::new();
+    for (idx, instruction) in runtime.instructions().iter().enumerate() {
+        match instruction {
+            Instruction::Goto(label) => {
+                if let Some(target) = labels.get(label) {
+                    edges.push((idx, *target, Transfer::Keep));
+                }
+            }
+            Instruction::JumpIf(_, _, _, label) => {
+                edges.push((idx, idx + 1, Transfer::Keep));
+                if let Some(target) = labels.get(label) {
+                    edges.push((idx, *target, Transfer::Keep));
+                }
+            }
+            Instruction::Call(label) => {
+                if enter_calls {
+                    if let Some(target) = labels.get(label) {
+                        edges.push((idx, *target, Transfer::Keep));
+                    }
+                }
+                if let Some(change) = effects.get(label).copied().unwrap_or(Some(None)) {
+                    edges.push((idx, idx + 1, Transfer::Change(change)));
+                }
+            }
+            Instruction::Return => (),
+            Instruction::CondExec(_, _, _, instruction) => {
+                edges.push((idx, idx + 1, Transfer::Keep));
+                edges.push((idx, idx + 1, Transfer::Run(instruction)));
+            }
+            _ => edges.push((idx, idx + 1, Transfer::Run(instruction))),
+        }
+    }
+    edges
+}
+
+/// Returns the possible sizes of the stack before each instruction that can be reached from `start`.
+///
+/// If `relative` is set, sizes are relative to the size at `start` and can be negative, subroutines that are called are not entered.
+/// Otherwise, paths on which a value is popped from an empty stack end at that instruction, as the program would fail there,
+/// and subroutines that are called are entered.
+///
+/// How often a loop runs is not known, so the size becomes unknown at the start of a loop that changes it.
+fn stack_sizes(
+    runtime: &Runtime,
+    start: usize,
+    start_size: StackSize,
+    effects: &HashMap<String, SubroutineEffect>,
+    relative: bool,
+) -> HashMap<usize, BTreeSet<StackSize>> {
+    let len = runtime.instructions().len();
+    let edges = control_flow_edges(runtime, effects, !relative);
+    let loop_heads = loop_heads(runtime);
+    let mut sizes: Vec<BTreeSet<StackSize>> = vec![BTreeSet::new(); len];
+    // every iteration either reaches a new instruction or makes a size unknown, so this limit is never reached
+    for _ in 0..=2 * len + 1 {
+        let mut next: Vec<BTreeSet<StackSize>> = vec![BTreeSet::new(); len];
+        if start < len {
+            next[start].insert(start_size);
+        }
+        for (from, to, transfer) in &edges {
+            if *to >= len {
+                continue;
+            }
+            for size in &sizes[*from] {
+                let size = match transfer {
+                    Transfer::Keep => Some(*size),
+                    Transfer::Run(instruction) => apply(instruction, *size, relative),
+                    Transfer::Change(change) => Some(change.and_then(|c| change_size(*size, c))),
+                };
+                if let Some(size) = size {
+                    next[*to].insert(size);
+                }
+            }
+        }
+        for idx in &loop_heads {
+            if next[*idx].len() > 1 {
+                next[*idx] = BTreeSet::from([None]);
+            }
+        }
+        if next == sizes {
+            break;
+        }
+        sizes = next;
+    }
+    sizes
+        .into_iter()
+        .enumerate()
+        .filter(|(_, sizes)| !sizes.is_empty())
+        .collect()
+}
+
+/// Returns the indices of instructions that are jumped to or called from the same or a later instruction.
+fn loop_heads(runtime: &Runtime) -> HashSet<usize> {
+    runtime
+        .instructions()
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, instruction)| match instruction {
+            Instruction::Goto(label)
+            | Instruction::JumpIf(_, _, _, label)
+            | Instruction::Call(label) => runtime
+                .control_flow
+                .instruction_labels
+                .get(label)
+                .filter(|target| **target <= idx)
+                .copied(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns the size of the stack after `instruction` is run.
+///
+/// Returns `None` if the instruction would fail because the stack does not contain enough values, unless `relative` is set.
+fn apply(instruction: &Instruction, size: StackSize, relative: bool) -> Option<StackSize> {
+    let required = match instruction {
+        Instruction::Push => return Some(change_size(size, 1)),
+        Instruction::Pop => 1,
+        Instruction::StackOp(_) => 2,
+        _ => return Some(size),
+    };
+    if !relative && size.is_some_and(|size| size < required) {
+        return None;
+    }
+    Some(change_size(size, -1))
+}
+
+/// Changes the stack size by `change`, the size becomes unknown if it can no longer be tracked exactly.
+fn change_size(size: StackSize, change: i32) -> StackSize {
+    size.map(|size| size + change)
+        .filter(|size| size.abs() <= MAX_TRACKED_STACK_SIZE)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils;
+
+    use super::{lint, LintLevel, LintLevels, LintWarning};
+
+    fn lint_str(input: &str) -> Vec<LintWarning> {
+        lint(
+            &test_utils::runtime_from_str(input).unwrap(),
+            &test_utils::string_literal_to_vec(input),
+        )
+    }
+
+    #[test]
+    fn test_lint_stack_balanced() {
+        assert!(lint_str("a := 1\npush\npush\nstack+\npop").is_empty());
+        assert!(
+            lint_str("a := 0\nloop: push\na := a + 1\npop\nif a < 5 then goto loop").is_empty()
+        );
+        assert!(lint_str("a := 1\npush\ncall f\npop\ngoto END\nf: push\npop\nreturn").is_empty());
+    }
+
+    #[test]
+    fn test_lint_pop_from_empty_stack() {
+        assert_eq!(
+            lint_str("a := 1\nif a > 0 then goto skip\npush\nskip: pop"),
+            vec![LintWarning::PopFromEmptyStack(4, "pop".to_string())]
+        );
+        assert_eq!(
+            lint_str("a := 1\npush\nstack*"),
+            vec![LintWarning::StackOpOnShortStack(3, "stack*".to_string())]
+        );
+        assert_eq!(
+            lint_str("a := 1\nif a > 0 then pop"),
+            vec![LintWarning::PopFromEmptyStack(2, "pop".to_string())]
+        );
+        // the value pushed by the subroutine is popped after the call
+        assert_eq!(
+            lint_str("call f\npop\npop\ngoto END\nf: push\nreturn"),
+            vec![
+                LintWarning::PopFromEmptyStack(3, "pop".to_string()),
+                LintWarning::UnbalancedSubroutine("f".to_string(), 6, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_pop_in_subroutine() {
+        assert_eq!(
+            lint_str("a := 1\ncall f\npush\ncall f\ngoto END\nf: pop\npush\nreturn"),
+            vec![LintWarning::PopFromEmptyStack(6, "pop".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_lint_unbalanced_subroutine() {
+        assert_eq!(
+            lint_str("a := 1\npush\npush\ncall f\ngoto END\nf: pop\nif a > 0 then goto f_end\npop\nf_end: return"),
+            vec![LintWarning::UnbalancedSubroutine("f".to_string(), 9, -2)]
+        );
+        assert_eq!(
+            LintWarning::UnbalancedSubroutine("f".to_string(), 9, -1).to_string(),
+            "subroutine 'f' changes the size of the stack by -1 when it returns in line 9"
+        );
+    }
+
+    #[test]
+    fn test_lint_mixed_notation() {
+        assert!(lint_str("p(h1) := 1\ny := p(h1)\na := 3 // ρ(h1)").is_empty());
+        assert_eq!(
+            lint_str("p(h1) := 1\n#var sum = p(h2)\nρ(h2) := γ\np(h2) := ρ(h1) + y\ngoto END"),
+            vec![
+                LintWarning::MixedNotation(2, "ρ(...)".to_string(), "p(...)".to_string()),
+                // both notations are used equally often, ascii is preferred
+                LintWarning::MixedNotation(2, "γ".to_string(), "y".to_string()),
+                LintWarning::MixedNotation(3, "ρ(...)".to_string(), "p(...)".to_string()),
+            ]
+        );
+        // gamma is written as γ more often
+        assert_eq!(
+            lint_str("γ := 1\nγ := γ + 1\ny := 2"),
+            vec![LintWarning::MixedNotation(
+                3,
+                "y".to_string(),
+                "γ".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_lint_label_case() {
+        assert!(lint_str("loop: a := 1\ngoto END\nend: a := 2").is_empty());
+        assert_eq!(
+            lint_str("loop: a := 1\nLoop: a := 2\nLOOP: a := 3\ngoto loop"),
+            vec![
+                LintWarning::LabelCase(2, "Loop".to_string(), "loop".to_string()),
+                LintWarning::LabelCase(3, "LOOP".to_string(), "loop".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lint_levels() {
+        let warning = LintWarning::PopFromEmptyStack(2, "pop".to_string());
+        assert_eq!(LintLevels::default().level(&warning), LintLevel::Warn);
+        let levels = LintLevels::new(
+            &["pop_from_empty_stack".to_string()],
+            &[],
+            &["unbalanced_subroutine".to_string()],
+        );
+        assert_eq!(levels.level(&warning), LintLevel::Allow);
+        assert_eq!(
+            levels.level(&LintWarning::UnbalancedSubroutine("f".to_string(), 2, 1)),
+            LintLevel::Deny
+        );
+        // the strictest level is used
+        let levels = LintLevels::new(
+            &["pop_from_empty_stack".to_string()],
+            &["pop_from_empty_stack".to_string()],
+            &[],
+        );
+        assert_eq!(levels.level(&warning), LintLevel::Warn);
+    }
+
+    #[test]
+    fn test_lint_suppressions() {
+        let lines = test_utils::string_literal_to_vec(
+            "a := 1\n# alpha_tui: allow(pop_from_empty_stack)\npop\npop // alpha_tui: allow(stack_op_on_short_stack, pop_from_empty_stack)\n// alpha_tui: allow(unbalanced_subroutine)\n\npop",
+        );
+        let levels = LintLevels::default().with_suppressions(&lines);
+        let pop = |line| LintWarning::PopFromEmptyStack(line, "pop".to_string());
+        assert_eq!(levels.level(&pop(2)), LintLevel::Allow);
+        assert_eq!(levels.level(&pop(3)), LintLevel::Allow);
+        assert_eq!(levels.level(&pop(6)), LintLevel::Warn);
+        assert_eq!(
+            levels.level(&LintWarning::UnbalancedSubroutine("f".to_string(), 6, 1)),
+            LintLevel::Allow
+        );
+        // denied lints can't be suppressed
+        let levels = LintLevels::new(&[], &[], &["pop_from_empty_stack".to_string()])
+            .with_suppressions(&lines);
+        assert_eq!(levels.level(&pop(2)), LintLevel::Deny);
+    }
+}
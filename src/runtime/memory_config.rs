@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::BTreeMap, str::FromStr};
 
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 
 use crate::utils;
@@ -18,8 +19,9 @@ pub struct MemoryConfig {
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
 pub struct AccumulatorConfig {
-    pub values: HashMap<usize, Option<i32>>,
+    pub values: BTreeMap<usize, Option<i32>>,
     pub autodetection: Option<bool>,
+    pub visibility: Option<MemoryVisibility>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
@@ -27,18 +29,143 @@ pub struct GammaAccumulatorConfig {
     pub enabled: bool,
     pub value: Option<i32>,
     pub autodetection: Option<bool>,
+    pub visibility: Option<MemoryVisibility>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
 pub struct MemoryCellConfig {
-    pub values: HashMap<String, Option<i32>>,
+    pub values: BTreeMap<String, Option<i32>>,
     pub autodetection: Option<bool>,
+    pub visibility: Option<MemoryVisibility>,
 }
 
 #[derive(PartialEq, Debug, Deserialize, Serialize, Default, Clone)]
 pub struct IndexMemoryCellConfig {
-    pub values: HashMap<usize, Option<i32>>,
+    pub values: BTreeMap<usize, Option<i32>>,
     pub autodetection: Option<bool>,
+    pub visibility: Option<MemoryVisibility>,
+}
+
+/// Determines when values of a memory type are displayed in the tui.
+#[derive(PartialEq, Eq, Debug, Deserialize, Serialize, Default, Clone, Copy, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemoryVisibility {
+    /// Values are always displayed, the gamma accumulator is displayed even if it is disabled.
+    Always,
+    /// Values are displayed when they exist, because they are declared or automatically detected.
+    #[default]
+    WhenDeclared,
+    /// Values are displayed when they contain a value.
+    WhenUsed,
+}
+
+impl MemoryVisibility {
+    /// Returns true if a value of a memory type with this visibility should be displayed.
+    ///
+    /// `has_value` indicates if the value is currently set.
+    pub fn is_visible(self, has_value: bool) -> bool {
+        match self {
+            Self::Always | Self::WhenDeclared => true,
+            Self::WhenUsed => has_value,
+        }
+    }
+}
+
+/// Memory types for which the visibility and the automatic detection can be configured.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, ValueEnum)]
+pub enum MemoryType {
+    Accumulators,
+    Gamma,
+    #[value(alias = "mc")]
+    MemoryCells,
+    #[value(alias = "imc")]
+    IndexMemoryCells,
+}
+
+/// Visibility of a single memory type, is parsed from `<memory type>=<visibility>`, e.g. `gamma=always`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct MemoryVisibilityArg {
+    pub memory_type: MemoryType,
+    pub visibility: MemoryVisibility,
+}
+
+impl FromStr for MemoryVisibilityArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((memory_type, visibility)) = s.split_once('=') else {
+            return Err(format!(
+                "'{s}' is not of the form <memory type>=<visibility>, e.g. gamma=always"
+            ));
+        };
+        Ok(Self {
+            memory_type: MemoryType::from_str(memory_type.trim(), true)?,
+            visibility: MemoryVisibility::from_str(visibility.trim(), true)?,
+        })
+    }
+}
+
+/// Automatic detection of a single memory type, is parsed from `<memory type>=<on|off>`, e.g. `gamma=off`.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub struct AutodetectionArg {
+    pub memory_type: MemoryType,
+    pub enabled: bool,
+}
+
+impl FromStr for AutodetectionArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((memory_type, enabled)) = s.split_once('=') else {
+            return Err(format!(
+                "'{s}' is not of the form <memory type>=<on|off>, e.g. gamma=off"
+            ));
+        };
+        let enabled = match enabled.trim() {
+            "on" => true,
+            "off" => false,
+            value => return Err(format!("invalid value '{value}', expected on or off")),
+        };
+        Ok(Self {
+            memory_type: MemoryType::from_str(memory_type.trim(), true)?,
+            enabled,
+        })
+    }
+}
+
+/// Visibility of each memory type in the tui.
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct MemoryVisibilities {
+    pub accumulators: MemoryVisibility,
+    pub gamma: MemoryVisibility,
+    pub memory_cells: MemoryVisibility,
+    pub index_memory_cells: MemoryVisibility,
+}
+
+impl MemoryVisibilities {
+    /// Overwrites the visibilities with the values set in `args`.
+    pub fn apply_args(&mut self, args: &[MemoryVisibilityArg]) {
+        for arg in args {
+            let visibility = match arg.memory_type {
+                MemoryType::Accumulators => &mut self.accumulators,
+                MemoryType::Gamma => &mut self.gamma,
+                MemoryType::MemoryCells => &mut self.memory_cells,
+                MemoryType::IndexMemoryCells => &mut self.index_memory_cells,
+            };
+            *visibility = arg.visibility;
+        }
+    }
+}
+
+impl From<&MemoryConfig> for MemoryVisibilities {
+    fn from(value: &MemoryConfig) -> Self {
+        Self {
+            accumulators: value.accumulators.visibility.unwrap_or_default(),
+            gamma: value.gamma_accumulator.visibility.unwrap_or_default(),
+            memory_cells: value.memory_cells.visibility.unwrap_or_default(),
+            index_memory_cells: value.index_memory_cells.visibility.unwrap_or_default(),
+        }
+    }
 }
 
 impl MemoryConfig {
@@ -56,3 +183,83 @@ impl MemoryConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{
+        AutodetectionArg, MemoryConfig, MemoryType, MemoryVisibilities, MemoryVisibility,
+        MemoryVisibilityArg,
+    };
+
+    #[test]
+    fn test_memory_visibility_arg_from_str() {
+        assert_eq!(
+            MemoryVisibilityArg::from_str("index-memory-cells=when-used"),
+            Ok(MemoryVisibilityArg {
+                memory_type: MemoryType::IndexMemoryCells,
+                visibility: MemoryVisibility::WhenUsed,
+            })
+        );
+        assert!(MemoryVisibilityArg::from_str("gamma").is_err());
+        assert!(MemoryVisibilityArg::from_str("gamma=sometimes").is_err());
+    }
+
+    #[test]
+    fn test_autodetection_arg_from_str() {
+        assert_eq!(
+            AutodetectionArg::from_str("accumulators=off"),
+            Ok(AutodetectionArg {
+                memory_type: MemoryType::Accumulators,
+                enabled: false,
+            })
+        );
+        assert_eq!(
+            AutodetectionArg::from_str("imc=on"),
+            Ok(AutodetectionArg {
+                memory_type: MemoryType::IndexMemoryCells,
+                enabled: true,
+            })
+        );
+        assert!(AutodetectionArg::from_str("gamma").is_err());
+        assert!(AutodetectionArg::from_str("gamma=maybe").is_err());
+        assert!(AutodetectionArg::from_str("stack=on").is_err());
+    }
+
+    #[test]
+    fn test_memory_visibilities() {
+        let mut config = MemoryConfig::default();
+        config.gamma_accumulator.visibility = Some(MemoryVisibility::Always);
+        config.memory_cells.visibility = Some(MemoryVisibility::WhenUsed);
+        let mut visibilities = MemoryVisibilities::from(&config);
+        assert_eq!(visibilities.gamma, MemoryVisibility::Always);
+        assert_eq!(visibilities.accumulators, MemoryVisibility::WhenDeclared);
+        // cli args overwrite the memory config
+        visibilities.apply_args(&[MemoryVisibilityArg {
+            memory_type: MemoryType::Gamma,
+            visibility: MemoryVisibility::WhenUsed,
+        }]);
+        assert_eq!(visibilities.gamma, MemoryVisibility::WhenUsed);
+        assert_eq!(visibilities.memory_cells, MemoryVisibility::WhenUsed);
+        assert!(!MemoryVisibility::WhenUsed.is_visible(false));
+        assert!(MemoryVisibility::WhenDeclared.is_visible(false));
+    }
+
+    #[test]
+    fn test_memory_config_serialization_is_sorted() {
+        let mut config = MemoryConfig::default();
+        for idx in [10, 2, 7] {
+            config
+                .index_memory_cells
+                .values
+                .insert(idx, Some(idx as i32));
+        }
+        for name in ["h2", "b", "h1"] {
+            config.memory_cells.values.insert(name.to_string(), None);
+        }
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#"{"2":2,"7":7,"10":10}"#));
+        assert!(json.contains(r#"{"b":null,"h1":null,"h2":null}"#));
+    }
+}
@@ -0,0 +1,250 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::instructions::{IndexMemoryCellIndexType, Instruction, MemoryRef};
+
+use super::{memory_config::MemoryConfig, RuntimeMemory};
+
+/// A memory location that can be declared in a memory config or used by a program.
+///
+/// Locations are ordered by type and then by index or name.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MemoryLocation {
+    Accumulator(usize),
+    Gamma,
+    MemoryCell(String),
+    IndexMemoryCell(usize),
+}
+
+impl Display for MemoryLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accumulator(idx) => write!(f, "a{idx}"),
+            Self::Gamma => write!(f, "y"),
+            Self::MemoryCell(name) => write!(f, "p({name})"),
+            Self::IndexMemoryCell(idx) => write!(f, "p({idx})"),
+        }
+    }
+}
+
+/// Returns all memory locations that exist in `memory`.
+pub fn memory_locations(memory: &RuntimeMemory) -> BTreeSet<MemoryLocation> {
+    let mut locations = BTreeSet::new();
+    for idx in memory.accumulators.keys() {
+        locations.insert(MemoryLocation::Accumulator(*idx));
+    }
+    if memory.gamma.is_some() {
+        locations.insert(MemoryLocation::Gamma);
+    }
+    for name in memory.memory_cells.keys() {
+        locations.insert(MemoryLocation::MemoryCell(name.clone()));
+    }
+    for idx in memory.index_memory_cells.keys() {
+        locations.insert(MemoryLocation::IndexMemoryCell(*idx));
+    }
+    locations
+}
+
+/// Returns the memory locations that are used by the instructions.
+///
+/// Index memory cells are only included if their index is written directly in the program, e.g. `p(3)`.
+pub fn used_memory_locations(instructions: &[Instruction]) -> BTreeSet<MemoryLocation> {
+    let mut usage = MemoryUsage::default();
+    let mut used = BTreeSet::new();
+    for instruction in instructions {
+        usage.add_used(instruction, &mut used);
+    }
+    used
+}
+
+/// Compares the memory locations that are declared with the memory locations that are used by a program.
+///
+/// The program is not run, index memory cells are only included if their index is written directly in the program,
+/// e.g. `p(3)`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemoryUsage {
+    /// Memory locations that are declared and used by the program.
+    pub declared_used: BTreeSet<MemoryLocation>,
+    /// Memory locations that are declared but never used by the program.
+    pub declared_unused: BTreeSet<MemoryLocation>,
+    /// Memory locations that are used by the program but not declared, they are created by autodetection.
+    pub undeclared_used: BTreeSet<MemoryLocation>,
+    /// Determines if the program accesses index memory cells with an index that is calculated when the program is run,
+    /// e.g. `p(a)`.
+    pub computed_index: bool,
+}
+
+impl MemoryUsage {
+    /// Analyzes which of the memory locations declared in `memory_config` are used by the instructions.
+    pub fn new(memory_config: &MemoryConfig, instructions: &[Instruction]) -> Self {
+        let mut declared = BTreeSet::new();
+        for idx in memory_config.accumulators.values.keys() {
+            declared.insert(MemoryLocation::Accumulator(*idx));
+        }
+        if memory_config.gamma_accumulator.enabled {
+            declared.insert(MemoryLocation::Gamma);
+        }
+        for name in memory_config.memory_cells.values.keys() {
+            declared.insert(MemoryLocation::MemoryCell(name.clone()));
+        }
+        for idx in memory_config.index_memory_cells.values.keys() {
+            declared.insert(MemoryLocation::IndexMemoryCell(*idx));
+        }
+        let mut usage = Self::default();
+        let mut used = BTreeSet::new();
+        for instruction in instructions {
+            usage.add_used(instruction, &mut used);
+        }
+        for location in declared {
+            if used.remove(&location) {
+                usage.declared_used.insert(location);
+            } else {
+                usage.declared_unused.insert(location);
+            }
+        }
+        usage.undeclared_used = used;
+        usage
+    }
+
+    /// Adds the memory locations that are used by the instruction to `used`.
+    fn add_used(&mut self, instruction: &Instruction, used: &mut BTreeSet<MemoryLocation>) {
+        for location in instruction.reads().into_iter().chain(instruction.writes()) {
+            let location = match location {
+                MemoryRef::Accumulator(idx) => MemoryLocation::Accumulator(idx),
+                MemoryRef::Gamma => MemoryLocation::Gamma,
+                MemoryRef::MemoryCell(name) => MemoryLocation::MemoryCell(name),
+                MemoryRef::IndexMemoryCell(IndexMemoryCellIndexType::Direct(idx)) => {
+                    MemoryLocation::IndexMemoryCell(idx)
+                }
+                // the location that contains the index is also read by the instruction
+                MemoryRef::IndexMemoryCell(_) => {
+                    self.computed_index = true;
+                    continue;
+                }
+            };
+            used.insert(location);
+        }
+        // the stack is accessed through the first accumulator
+        let instruction = match instruction {
+            Instruction::CondExec(.., instruction) => instruction.as_ref(),
+            instruction => instruction,
+        };
+        if matches!(instruction, Instruction::StackOp(_)) {
+            used.insert(MemoryLocation::Accumulator(0));
+        }
+    }
+}
+
+impl Display for MemoryUsage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |locations: &BTreeSet<MemoryLocation>| {
+            if locations.is_empty() {
+                "-".to_string()
+            } else {
+                locations
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        writeln!(f, "Memory usage:")?;
+        writeln!(f, "  declared and used:     {}", list(&self.declared_used))?;
+        writeln!(
+            f,
+            "  declared but not used: {}",
+            list(&self.declared_unused)
+        )?;
+        write!(
+            f,
+            "  used but not declared: {}",
+            list(&self.undeclared_used)
+        )?;
+        if !self.undeclared_used.is_empty() {
+            write!(f, " (created by autodetection)")?;
+        }
+        if self.computed_index {
+            write!(
+                f,
+                "\n  index memory cells are accessed with a computed index, these accesses are not included"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use crate::{runtime::memory_config::MemoryConfig, utils::test_utils};
+
+    use super::{MemoryLocation, MemoryUsage};
+
+    fn usage(input: &str, memory_config: &MemoryConfig) -> MemoryUsage {
+        let rt = test_utils::runtime_from_str(input).unwrap();
+        MemoryUsage::new(memory_config, rt.instructions())
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let mut memory_config = MemoryConfig::default();
+        for idx in 0..3 {
+            memory_config.accumulators.values.insert(idx, None);
+        }
+        memory_config
+            .memory_cells
+            .values
+            .insert("h1".to_string(), None);
+        memory_config
+            .memory_cells
+            .values
+            .insert("h2".to_string(), None);
+        memory_config.index_memory_cells.values.insert(1, None);
+        let usage = usage(
+            "a := p(h1) + 1\nif a1 < p(h3) then goto END\np(2) := a\npush",
+            &memory_config,
+        );
+        assert_eq!(
+            usage.declared_used,
+            BTreeSet::from([
+                MemoryLocation::Accumulator(0),
+                MemoryLocation::Accumulator(1),
+                MemoryLocation::MemoryCell("h1".to_string()),
+            ])
+        );
+        assert_eq!(
+            usage.declared_unused,
+            BTreeSet::from([
+                MemoryLocation::Accumulator(2),
+                MemoryLocation::MemoryCell("h2".to_string()),
+                MemoryLocation::IndexMemoryCell(1),
+            ])
+        );
+        assert_eq!(
+            usage.undeclared_used,
+            BTreeSet::from([
+                MemoryLocation::MemoryCell("h3".to_string()),
+                MemoryLocation::IndexMemoryCell(2),
+            ])
+        );
+        assert!(!usage.computed_index);
+        assert_eq!(
+            usage.to_string(),
+            "Memory usage:\n  declared and used:     a0, a1, p(h1)\n  declared but not used: a2, p(h2), p(1)\n  used but not declared: p(h3), p(2) (created by autodetection)"
+        );
+    }
+
+    #[test]
+    fn test_memory_usage_computed_index() {
+        let usage = usage("p(a1) := p(p(h1))", &MemoryConfig::default());
+        assert_eq!(
+            usage.undeclared_used,
+            BTreeSet::from([
+                MemoryLocation::Accumulator(1),
+                MemoryLocation::MemoryCell("h1".to_string()),
+            ])
+        );
+        assert!(usage.computed_index);
+        assert!(usage.to_string().ends_with("accesses are not included"));
+    }
+}
@@ -1,23 +1,54 @@
-use std::collections::HashMap;
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet, HashMap},
+};
 
+use clap::ValueEnum;
 use miette::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     base::{Accumulator, MemoryCell},
-    instructions::Instruction,
+    clock::SharedClock,
+    instructions::{
+        notes::Notes,
+        refactor::{RefactorError, Rename, RenameKind},
+        structured_loops::StructuredLoop,
+        variables::Variables,
+        Instruction,
+    },
 };
 
 use self::{
-    error_handling::{RuntimeError, RuntimeErrorType},
-    memory_config::MemoryConfig,
+    error_handling::{RuntimeBuildError, RuntimeError, RuntimeErrorType},
+    memory_config::{
+        AccumulatorConfig, GammaAccumulatorConfig, IndexMemoryCellConfig, MemoryCellConfig,
+        MemoryConfig,
+    },
+    memory_usage::{memory_locations, MemoryLocation},
+    observer::{ExecutionEvent, RuntimeObserver},
+    timing::InstructionTimings,
+    watchpoints::{WatchpointHit, Watchpoints},
 };
 
 /// Structs related to building a runtime
 pub mod builder;
 pub mod error_handling;
+pub mod lints;
 pub mod memory_config;
+/// Analysis of the memory locations that are declared and used by a program
+pub mod memory_usage;
+pub mod observer;
+/// Metrics that give an overview of the structure of a program
+pub mod statistics;
+/// Measurement of the time that is spent running instructions
+pub mod timing;
+/// Conditions on the memory at which the execution is stopped
+pub mod watchpoints;
 
+/// Default value of the maximum depth of the call stack.
 const MAX_CALL_STACK_SIZE: usize = u16::MAX as usize;
+/// Default value of the maximum number of instructions that may be run.
 const MAX_INSTRUCTION_RUNS: usize = 1_000_000;
 
 #[derive(Debug, PartialEq)]
@@ -31,58 +62,218 @@ pub struct Runtime {
     control_flow: ControlFlow,
     /// Used to count how many instructions where executed.
     ///
-    /// If the `MAX_INSTRUCTION_RUNS` instruction has been executed a runtime error is thrown to indicate
+    /// If the instruction limit of the settings has been reached a runtime error is thrown to indicate
     /// that the runtime has reached its design limit. This is among other things to protect from misuse and infinite loops.
     instruction_runs: usize,
+    /// Time that was spent running each kind of instruction, is only measured if `settings.measure` is set.
+    timings: Option<InstructionTimings>,
     settings: RuntimeSettings,
+    /// Structured loops that were desugared into labels and jumps.
+    structured_loops: Vec<StructuredLoop>,
+    /// Names of memory locations that are declared in the program.
+    variables: Variables,
+    /// Notes that are displayed when the execution reaches a line.
+    notes: Notes,
+    /// Memory locations that are declared with cli arguments or a memory config file.
+    ///
+    /// Is `None` if no memory location is declared, in that case all memory locations are created by autodetection.
+    declared_memory: Option<BTreeSet<MemoryLocation>>,
 }
 
 impl Runtime {
     /// Runs the complete program.
     pub fn run(&mut self) -> Result<bool, RuntimeError> {
-        while self.control_flow.next_instruction_index < self.instructions.len() {
+        while !self.finished() {
             self.step()?;
         }
         Ok(true)
     }
 
+    /// Runs the complete program, `observer` receives an event for every instruction that is executed.
+    pub fn run_observed(
+        &mut self,
+        observer: &mut impl RuntimeObserver,
+    ) -> Result<bool, RuntimeError> {
+        while !self.finished() {
+            self.step_observed(observer)?;
+        }
+        Ok(true)
+    }
+
+    /// Runs the program until a watchpoint is triggered, `observer` receives an event for every instruction that is
+    /// executed.
+    ///
+    /// Returns the triggered watchpoints or `None` if the program has finished.
+    pub fn run_until_watchpoint(
+        &mut self,
+        observer: &mut impl RuntimeObserver,
+        watchpoints: &mut Watchpoints,
+    ) -> Result<Option<WatchpointHit>, RuntimeError> {
+        while !self.finished() {
+            let mut last_event = None;
+            self.step_observed(&mut |event: &ExecutionEvent| {
+                observer.instruction_executed(event);
+                last_event = Some(event.clone());
+            })?;
+            if let Some(hit) = last_event.and_then(|event| watchpoints.check(&event, &self.memory))
+            {
+                return Ok(Some(hit));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Runs the complete program, instructions that cause a runtime error are skipped.
+    ///
+    /// Returns all runtime errors that occurred. Execution only stops early, if an error can not be skipped,
+    /// e.g. when the maximum number of instruction runs is reached.
+    pub fn run_keep_going(&mut self, observer: &mut impl RuntimeObserver) -> Vec<RuntimeError> {
+        let mut errors = Vec::new();
+        while !self.finished() {
+            if let Err(e) = self.step_observed(observer) {
+                let skippable = e.is_retryable();
+                // continue with the instruction after the failed one
+                self.control_flow.next_instruction_index = e.line_number;
+                errors.push(e);
+                if !skippable {
+                    break;
+                }
+            }
+        }
+        errors
+    }
+
+    /// Runs the next instruction only, `observer` receives an event if an instruction was executed.
+    ///
+    /// Returns true when no instruction was run because the last instruction was already run.
+    pub fn step_observed(
+        &mut self,
+        observer: &mut impl RuntimeObserver,
+    ) -> Result<bool, RuntimeError> {
+        self.skip_empty_lines();
+        let current_instruction = self.control_flow.next_instruction_index;
+        let before = self.memory.clone();
+        let Some(jumped) = self.run_next_instruction()? else {
+            return Ok(true);
+        };
+        observer.instruction_executed(&ExecutionEvent::new(
+            self.instruction_runs,
+            current_instruction + 1,
+            self.instructions[current_instruction].to_string(),
+            &before,
+            &self.memory,
+            &self.variables,
+            jumped.then_some(self.control_flow.next_instruction_index + 1),
+            self.finished(),
+        ));
+        Ok(false)
+    }
+
     /// Runs the next instruction only.
     ///
     /// Returns true when no instruction was run because the last instruction was already run.
+    ///
+    /// If the instruction fails, the next instruction is not advanced, so that the failed instruction can be
+    /// run again, for example after the memory was fixed.
     pub fn step(&mut self) -> Result<bool, RuntimeError> {
+        Ok(self.run_next_instruction()?.is_none())
+    }
+
+    /// Runs the next instruction, see [`Runtime::step`].
+    ///
+    /// Returns `None` when no instruction was run, otherwise returns true if the instruction jumped to another
+    /// instruction than the following one, this includes jumps to `END`.
+    fn run_next_instruction(&mut self) -> Result<Option<bool>, RuntimeError> {
+        if self.control_flow.halted {
+            return Ok(None);
+        }
+        self.skip_empty_lines();
         let current_instruction = self.control_flow.next_instruction_index;
         self.control_flow.next_instruction_index += 1;
         if let Some(i) = self.instructions.get(current_instruction) {
-            if let Err(e) = i.run(&mut self.memory, &mut self.control_flow, &self.settings) {
+            let start = self.timings.is_some().then(|| self.settings.clock.now());
+            let call_depth = self.control_flow.call_stack.len();
+            let res = i.run(&mut self.memory, &mut self.control_flow, &self.settings);
+            if let (Some(timings), Some(start)) = (&mut self.timings, start) {
+                timings.record(i.kind(), self.settings.clock.elapsed(start));
+            }
+            if let Err(e) = res {
+                self.control_flow.next_instruction_index = current_instruction;
                 return Err(RuntimeError {
                     reason: e,
                     line_number: current_instruction + 1,
                 })?;
             }
+            let jumped = !self.control_flow.halted
+                && self.control_flow.next_instruction_index != current_instruction + 1;
+            if self.control_flow.halted {
+                // the program stops at the return instruction that halted it
+                self.control_flow.next_instruction_index = current_instruction;
+            }
+            if self.settings.tail_call_optimization
+                && self.control_flow.call_stack.len() > call_depth
+                && self.is_tail_call()
+            {
+                self.control_flow.reuse_frame();
+            }
             self.verify(current_instruction + 1)?;
             self.instruction_runs += 1;
+            self.skip_empty_lines();
+            Ok(Some(jumped))
         } else {
-            return Ok(true);
+            Ok(None)
+        }
+    }
+
+    /// Checks if the function that was just called returns to a `return` instruction, empty lines are ignored.
+    ///
+    /// In that case the frame of the call is not needed, because the function can return directly to the caller.
+    fn is_tail_call(&self) -> bool {
+        let Some(return_index) = self.control_flow.call_stack.last() else {
+            return false;
+        };
+        self.instructions[*return_index..]
+            .iter()
+            .find(|instruction| **instruction != Instruction::Noop)
+            == Some(&Instruction::Return)
+    }
+
+    /// Moves the next instruction past empty lines, if `skip_empty_lines` is set.
+    ///
+    /// This makes sure that empty lines don't consume an execution step.
+    fn skip_empty_lines(&mut self) {
+        if !self.settings.skip_empty_lines {
+            return;
+        }
+        while let Some(Instruction::Noop) = self
+            .instructions
+            .get(self.control_flow.next_instruction_index)
+        {
+            self.control_flow.next_instruction_index += 1;
         }
-        Ok(false)
     }
 
     /// Verifies that the current runtime is legal.
     ///
     /// The runtime is illegal, if specific conditions are met:
-    /// - The maximum stack size is exceeded
-    /// - 1mil instructions where executed (this is to protect from infinite loops and because the runtime is to build to run so many instructions)
+    /// - The maximum stack size or call stack size is exceeded
+    /// - more instructions than the instruction limit where executed (this is to protect from infinite loops and because the runtime is to build to run so many instructions)
     fn verify(&self, line_number: usize) -> Result<(), RuntimeError> {
-        if self.control_flow.call_stack.len() >= MAX_CALL_STACK_SIZE {
+        let stack_overflow = self
+            .settings
+            .stack_limit
+            .is_some_and(|limit| self.memory.stack.len() > limit);
+        if stack_overflow || self.control_flow.call_stack.len() > self.settings.call_stack_limit {
             return Err(RuntimeError {
                 reason: RuntimeErrorType::StackOverflowError,
                 line_number,
             });
         }
-        if !self.settings.disable_instruction_limit && self.instruction_runs > MAX_INSTRUCTION_RUNS
+        if !self.settings.disable_instruction_limit
+            && self.instruction_runs > self.settings.instruction_limit
         {
             return Err(RuntimeError {
-                reason: RuntimeErrorType::DesignLimitReached(MAX_INSTRUCTION_RUNS),
+                reason: RuntimeErrorType::DesignLimitReached(self.settings.instruction_limit),
                 line_number,
             });
         }
@@ -96,9 +287,39 @@ impl Runtime {
         self.control_flow.next_instruction_index = idx;
     }
 
+    /// Continues with the instruction after the next instruction, the next instruction is not run.
+    ///
+    /// Returns false if the program has finished and no instruction can be skipped.
+    pub fn skip_instruction(&mut self) -> bool {
+        if self.finished() {
+            return false;
+        }
+        self.control_flow.next_instruction_index += 1;
+        self.skip_empty_lines();
+        true
+    }
+
+    /// Continues the execution with the memory and at the position of an earlier execution.
+    ///
+    /// `instruction_runs` is the number of instructions that the earlier execution had run.
+    pub fn restore_execution(
+        &mut self,
+        memory: RuntimeMemory,
+        next_instruction_index: usize,
+        call_stack: Vec<usize>,
+        instruction_runs: usize,
+    ) {
+        self.memory = memory;
+        self.control_flow.next_instruction_index = next_instruction_index;
+        self.control_flow.tail_calls = vec![0; call_stack.len()];
+        self.control_flow.call_stack = call_stack;
+        self.instruction_runs = instruction_runs;
+    }
+
     /// Returns true when the execution is finished,
     pub fn finished(&self) -> bool {
-        self.control_flow.next_instruction_index >= self.instructions.len()
+        self.control_flow.halted
+            || self.control_flow.next_instruction_index >= self.instructions.len()
     }
 
     /// Returns the index of the current instruction
@@ -106,6 +327,16 @@ impl Runtime {
         self.control_flow.next_instruction_index
     }
 
+    /// Returns how many instructions were executed.
+    pub fn instruction_runs(&self) -> usize {
+        self.instruction_runs
+    }
+
+    /// Returns the time that was spent running each kind of instruction, `None` if it is not measured.
+    pub fn timings(&self) -> Option<&InstructionTimings> {
+        self.timings.as_ref()
+    }
+
     /// Returns reference to **`runtime_args`**.
     pub fn runtime_memory(&self) -> &RuntimeMemory {
         &self.memory
@@ -116,11 +347,94 @@ impl Runtime {
         &self.control_flow
     }
 
+    /// Returns a reference to **`settings`**.
+    pub fn settings(&self) -> &RuntimeSettings {
+        &self.settings
+    }
+
+    /// Returns the instructions of this runtime.
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.instructions
+    }
+
+    /// Returns the structured loops that were desugared into labels and jumps.
+    pub fn structured_loops(&self) -> &[StructuredLoop] {
+        &self.structured_loops
+    }
+
+    /// Returns the memory locations that were not declared but created by autodetection, e.g. because of a typo.
+    ///
+    /// Is empty if no memory location is declared.
+    pub fn autodetected_memory(&self) -> BTreeSet<MemoryLocation> {
+        match &self.declared_memory {
+            Some(declared) => memory_locations(&self.memory)
+                .difference(declared)
+                .cloned()
+                .collect(),
+            None => BTreeSet::new(),
+        }
+    }
+
+    /// Returns the names of memory locations that are declared in the program.
+    pub fn variables(&self) -> &Variables {
+        &self.variables
+    }
+
+    /// Returns the notes that are displayed when the execution reaches a line.
+    pub fn notes(&self) -> &Notes {
+        &self.notes
+    }
+
+    /// Applies the rename to the instructions, the memory and the labels of this runtime.
+    ///
+    /// Returns an error if a memory cell or label with the new name already exists.
+    pub fn rename(&mut self, rename: &Rename) -> Result<(), RefactorError> {
+        let (old, new) = (rename.old.as_str(), rename.new.as_str());
+        match rename.kind {
+            RenameKind::MemoryCell => {
+                if self.memory.memory_cells.contains_key(new) {
+                    return Err(RefactorError::AlreadyExists(rename.kind, new.to_string()));
+                }
+                for memory in [&mut self.memory, &mut self.initial_memory] {
+                    if let Some(mut memory_cell) = memory.memory_cells.remove(old) {
+                        memory_cell.label = new.to_string();
+                        memory.memory_cells.insert(new.to_string(), memory_cell);
+                    }
+                }
+                for structured_loop in &mut self.structured_loops {
+                    structured_loop.keyword.rename_memory_cell(old, new);
+                }
+                self.variables
+                    .rename_location(&format!("p({old})"), &format!("p({new})"));
+                if let Some(declared) = &mut self.declared_memory {
+                    if declared.remove(&MemoryLocation::MemoryCell(old.to_string())) {
+                        declared.insert(MemoryLocation::MemoryCell(new.to_string()));
+                    }
+                }
+            }
+            RenameKind::Label => {
+                let labels = &mut self.control_flow.instruction_labels;
+                if labels.contains_key(new) {
+                    return Err(RefactorError::AlreadyExists(rename.kind, new.to_string()));
+                }
+                if let Some(idx) = labels.remove(old) {
+                    labels.insert(new.to_string(), idx);
+                }
+            }
+        }
+        for instruction in &mut self.instructions {
+            rename.rename_instruction(instruction);
+        }
+        Ok(())
+    }
+
     /// Resets the current runtime to defaults, resets instruction pointer
     /// and restores the initial memory state.
     pub fn reset(&mut self) {
         self.control_flow.reset_soft();
         self.memory = self.initial_memory.clone();
+        self.settings.rng.reset();
+        self.skip_empty_lines();
     }
 
     /// Returns the index of the instruction that is executed first
@@ -144,6 +458,52 @@ impl Runtime {
         Ok(())
     }
 
+    /// Returns what running the provided instruction with [`Runtime::run_foreign_instruction`] would change,
+    /// without changing this runtime.
+    ///
+    /// The line of the returned event is the line of the instruction that is run next.
+    pub fn preview_foreign_instruction(
+        &self,
+        instruction: &Instruction,
+    ) -> Result<ExecutionEvent, RuntimeError> {
+        let mut memory = self.memory.clone();
+        let mut control_flow = self.control_flow.clone();
+        // the settings are cloned as well, so that the state of the random number generator is not advanced
+        let settings = self.settings.clone();
+        if let Err(e) = instruction.run(&mut memory, &mut control_flow, &settings) {
+            return Err(RuntimeError {
+                reason: e,
+                line_number: self.control_flow.next_instruction_index,
+            });
+        }
+        // the next instruction only changes if the instruction jumps
+        let jump = (control_flow.next_instruction_index
+            != self.control_flow.next_instruction_index)
+            .then_some(control_flow.next_instruction_index + 1);
+        Ok(ExecutionEvent::new(
+            self.instruction_runs,
+            self.control_flow.next_instruction_index + 1,
+            instruction.to_string(),
+            &self.memory,
+            &memory,
+            &self.variables,
+            jump,
+            false,
+        ))
+    }
+
+    /// Checks if the memory locations that are used by the provided instruction exist.
+    ///
+    /// Memory locations that are missing are no error, if they are created by autodetection.
+    pub fn check_memory_exists(&self, instruction: &Instruction) -> Result<(), RuntimeBuildError> {
+        let memory_config = MemoryConfig::from_runtime_memory(&self.memory, &self.settings);
+        builder::check_missing_vars(
+            &memory_config,
+            std::slice::from_ref(instruction),
+            &mut self.memory.clone(),
+        )
+    }
+
     /// Checks if this runtime contains at least one call instruction.
     pub fn contains_call_instruction(&self) -> bool {
         let mut res = false;
@@ -169,6 +529,12 @@ pub struct ControlFlow {
     pub instruction_labels: HashMap<String, usize>,
     /// Stores the index of the next instruction after a function returns
     pub call_stack: Vec<usize>,
+    /// Number of tail calls that reused each frame of the call stack, has the same length as `call_stack`.
+    pub tail_calls: Vec<usize>,
+    /// Set when `return` was run with an empty call stack and [`EndBehavior::Halt`] is used, the program is finished.
+    pub halted: bool,
+    /// If set, labels are compared without regard to case, e.g. `Loop` and `loop` are the same label.
+    pub case_insensitive_labels: bool,
     initial_instruction: usize,
 }
 
@@ -178,6 +544,9 @@ impl ControlFlow {
             next_instruction_index: 0,
             instruction_labels: HashMap::new(),
             call_stack: Vec::new(),
+            tail_calls: Vec::new(),
+            halted: false,
+            case_insensitive_labels: false,
             initial_instruction: 0,
         }
     }
@@ -185,27 +554,70 @@ impl ControlFlow {
     /// Updates **`next_instruction_index`** if **label** is contained in **`instruction_labels`**,
     /// otherwise returns an error.
     pub fn next_instruction_index(&mut self, label: &str) -> Result<(), RuntimeErrorType> {
-        if let Some(index) = self.instruction_labels.get(label) {
-            self.next_instruction_index = *index;
+        if let Some(index) = self.label_index(label) {
+            self.next_instruction_index = index;
             Ok(())
         } else {
             Err(RuntimeErrorType::LabelMissing(label.to_string()))
         }
     }
 
+    /// Returns the index of the instruction to which `label` points, if the label is defined.
+    pub fn label_index(&self, label: &str) -> Option<usize> {
+        self.defined_label(label)
+            .map(|defined| self.instruction_labels[defined])
+    }
+
+    /// Returns `label` as it is defined in **`instruction_labels`**.
+    ///
+    /// If labels are case insensitive, a label that only differs by case is returned when `label` is not defined
+    /// exactly as written.
+    pub fn defined_label(&self, label: &str) -> Option<&str> {
+        if let Some((defined, _)) = self.instruction_labels.get_key_value(label) {
+            return Some(defined);
+        }
+        if !self.case_insensitive_labels {
+            return None;
+        }
+        // the smallest label is used, so that the result does not depend on the order of the map
+        self.instruction_labels
+            .keys()
+            .filter(|defined| defined.eq_ignore_ascii_case(label))
+            .min()
+            .map(String::as_str)
+    }
+
     /// Updates the call stack with the instruction index from which the function was called
     /// and sets the next instruction index.
-    /// Returns `StackOverflowError` when call stack exceeds size of `i16::max` elements (= the maximum size is ~2MB).
     pub fn call_function(&mut self, label: &str) -> Result<(), RuntimeErrorType> {
+        // the label is checked first, so that the call stack is unchanged if the call fails
+        let Some(index) = self.label_index(label) else {
+            return Err(RuntimeErrorType::LabelMissing(label.to_string()));
+        };
         self.call_stack.push(self.next_instruction_index);
-        self.next_instruction_index(label)?;
+        self.tail_calls.push(0);
+        self.next_instruction_index = index;
         Ok(())
     }
 
+    /// Removes the frame that was pushed by the last call, the function returns directly to the caller of the
+    /// current function instead.
+    ///
+    /// The tail call is counted for the frame of the current function, if the current function is not the main program.
+    pub fn reuse_frame(&mut self) {
+        self.call_stack.pop();
+        self.tail_calls.pop();
+        if let Some(tail_calls) = self.tail_calls.last_mut() {
+            *tail_calls += 1;
+        }
+    }
+
     /// Resets the `next_instruction_index` to 0 and clears the call stack.
     pub fn reset_soft(&mut self) {
         self.next_instruction_index = self.initial_instruction;
         self.call_stack.clear();
+        self.tail_calls.clear();
+        self.halted = false;
     }
 }
 
@@ -214,17 +626,17 @@ impl ControlFlow {
 #[allow(clippy::module_name_repetitions, clippy::option_option)]
 pub struct RuntimeMemory {
     /// Current values stored in accumulators
-    pub accumulators: HashMap<usize, Accumulator>,
+    pub accumulators: BTreeMap<usize, Accumulator>,
     /// The value of the gamma accumulator
     ///
     /// First option determines if gamma is active.
     /// Inner option determine if gamma contains a value.
     pub gamma: Option<Option<i32>>,
     /// All registers that are used to store data
-    pub memory_cells: HashMap<String, MemoryCell>,
+    pub memory_cells: BTreeMap<String, MemoryCell>,
     /// All index registers that are used to store data,
     /// key is the index, value is the value of that register
-    pub index_memory_cells: HashMap<usize, Option<i32>>,
+    pub index_memory_cells: BTreeMap<usize, Option<i32>>,
     /// The stack of the runner
     pub stack: Vec<i32>,
 }
@@ -232,11 +644,11 @@ pub struct RuntimeMemory {
 impl Default for RuntimeMemory {
     /// Creates a runtime memory with 4 accumulators and 4 memory cells.
     fn default() -> Self {
-        let mut accumulators = HashMap::new();
+        let mut accumulators = BTreeMap::new();
         for i in 0..4 {
             accumulators.insert(i, Accumulator::new(i));
         }
-        let mut memory_cells: HashMap<String, MemoryCell> = HashMap::new();
+        let mut memory_cells: BTreeMap<String, MemoryCell> = BTreeMap::new();
         for i in 0..4 {
             let label = format!("h{i}");
             memory_cells.insert(label.clone(), MemoryCell::new(&label));
@@ -245,7 +657,7 @@ impl Default for RuntimeMemory {
             accumulators,
             gamma: None,
             memory_cells,
-            index_memory_cells: HashMap::new(),
+            index_memory_cells: BTreeMap::new(),
             stack: Vec::new(),
         }
     }
@@ -261,11 +673,86 @@ impl RuntimeMemory {
         }
         false
     }
+
+    /// Returns the value of the memory location.
+    ///
+    /// Fails if the memory location does not exist or does not contain a value.
+    pub fn get(&self, location: &MemoryLocation) -> Result<i32, RuntimeErrorType> {
+        match location {
+            MemoryLocation::Accumulator(idx) => match self.accumulators.get(idx) {
+                Some(acc) => acc
+                    .data
+                    .ok_or(RuntimeErrorType::AccumulatorUninitialized(*idx)),
+                None => Err(RuntimeErrorType::AccumulatorDoesNotExist(*idx)),
+            },
+            MemoryLocation::Gamma => match self.gamma {
+                Some(gamma) => gamma.ok_or(RuntimeErrorType::GammaUninitialized),
+                None => Err(RuntimeErrorType::GammaDoesNotExist),
+            },
+            MemoryLocation::MemoryCell(name) => match self.memory_cells.get(name) {
+                Some(cell) => cell
+                    .data
+                    .ok_or_else(|| RuntimeErrorType::MemoryCellUninitialized(name.clone())),
+                None => Err(RuntimeErrorType::MemoryCellDoesNotExist(name.clone())),
+            },
+            MemoryLocation::IndexMemoryCell(idx) => match self.index_memory_cells.get(idx) {
+                Some(value) => value.ok_or(RuntimeErrorType::IndexMemoryCellUninitialized(*idx)),
+                None => Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(*idx)),
+            },
+        }
+    }
+
+    /// Assigns the value to the memory location.
+    ///
+    /// If the memory location does not exist, it is created when autodetection is enabled for its type in `settings`,
+    /// otherwise an error is returned.
+    pub fn assign(
+        &mut self,
+        location: &MemoryLocation,
+        value: i32,
+        settings: &RuntimeSettings,
+    ) -> Result<(), RuntimeErrorType> {
+        match location {
+            MemoryLocation::Accumulator(idx) => {
+                if !self.accumulators.contains_key(idx) && !settings.autodetect_accumulators {
+                    return Err(RuntimeErrorType::AccumulatorDoesNotExist(*idx));
+                }
+                self.accumulators
+                    .entry(*idx)
+                    .or_insert_with(|| Accumulator::new(*idx))
+                    .data = Some(value);
+            }
+            MemoryLocation::Gamma => {
+                if self.gamma.is_none() && !settings.autodetect_gamma_accumulator {
+                    return Err(RuntimeErrorType::GammaDoesNotExist);
+                }
+                self.gamma = Some(Some(value));
+            }
+            MemoryLocation::MemoryCell(name) => {
+                if !self.memory_cells.contains_key(name) && !settings.autodetect_memory_cells {
+                    return Err(RuntimeErrorType::MemoryCellDoesNotExist(name.clone()));
+                }
+                self.memory_cells
+                    .entry(name.clone())
+                    .or_insert_with(|| MemoryCell::new(name))
+                    .data = Some(value);
+            }
+            MemoryLocation::IndexMemoryCell(idx) => {
+                if !self.index_memory_cells.contains_key(idx)
+                    && !settings.autodetect_index_memory_cells
+                {
+                    return Err(RuntimeErrorType::IndexMemoryCellDoesNotExist(*idx));
+                }
+                self.index_memory_cells.insert(*idx, Some(value));
+            }
+        }
+        Ok(())
+    }
 }
 
 impl From<MemoryConfig> for RuntimeMemory {
     fn from(value: MemoryConfig) -> Self {
-        let mut accumulators = HashMap::new();
+        let mut accumulators = BTreeMap::new();
         for (idx, value) in value.accumulators.values {
             accumulators.insert(
                 idx,
@@ -275,7 +762,7 @@ impl From<MemoryConfig> for RuntimeMemory {
                 },
             );
         }
-        let mut memory_cells = HashMap::new();
+        let mut memory_cells = BTreeMap::new();
         for (label, value) in value.memory_cells.values {
             memory_cells.insert(label.clone(), MemoryCell { label, data: value });
         }
@@ -298,10 +785,53 @@ impl From<MemoryConfig> for RuntimeMemory {
     }
 }
 
+impl MemoryConfig {
+    /// Creates a memory config that contains the current values of `memory`.
+    ///
+    /// The autodetection values are taken from `settings`, so that a runtime build with this config behaves the same.
+    pub fn from_runtime_memory(memory: &RuntimeMemory, settings: &RuntimeSettings) -> Self {
+        Self {
+            accumulators: AccumulatorConfig {
+                values: memory
+                    .accumulators
+                    .iter()
+                    .map(|(idx, acc)| (*idx, acc.data))
+                    .collect(),
+                autodetection: Some(settings.autodetect_accumulators),
+                visibility: None,
+            },
+            gamma_accumulator: GammaAccumulatorConfig {
+                enabled: memory.gamma.is_some(),
+                value: memory.gamma.flatten(),
+                autodetection: Some(settings.autodetect_gamma_accumulator),
+                visibility: None,
+            },
+            memory_cells: MemoryCellConfig {
+                values: memory
+                    .memory_cells
+                    .iter()
+                    .map(|(label, cell)| (label.clone(), cell.data))
+                    .collect(),
+                autodetection: Some(settings.autodetect_memory_cells),
+                visibility: None,
+            },
+            index_memory_cells: IndexMemoryCellConfig {
+                values: memory.index_memory_cells.clone(),
+                autodetection: Some(settings.autodetect_index_memory_cells),
+                visibility: None,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 /// Settings that may be required during runtime
 pub struct RuntimeSettings {
     pub disable_instruction_limit: bool,
+    // Maximum number of instructions that may be run, if the instruction limit is not disabled.
+    pub instruction_limit: usize,
+    // If true, empty lines are skipped and don't consume an execution step.
+    pub skip_empty_lines: bool,
     // If true, accumulators will be created automatically, if they are accessed and the don't already exist.
     pub autodetect_accumulators: bool,
     // If true, accumulator gamma will be created automatically, if it is accessed it does not already exist.
@@ -310,23 +840,128 @@ pub struct RuntimeSettings {
     pub autodetect_memory_cells: bool,
     // If true, index memory cells will be created automatically, if they are accessed and the don't already exist.
     pub autodetect_index_memory_cells: bool,
+    // Random number generator that is used by the rand instruction.
+    pub rng: Rng,
+    // If true, the time that is spent running each kind of instruction is measured.
+    pub measure: bool,
+    // Clock that is used to measure the time that is spent running instructions.
+    pub clock: SharedClock,
+    // Maximum number of values on the stack, the size of the stack is not limited if this is `None`.
+    pub stack_limit: Option<usize>,
+    // Maximum depth of the call stack.
+    pub call_stack_limit: usize,
+    // If true, calls that are directly followed by return reuse the frame of the current function.
+    pub tail_call_optimization: bool,
+    // Determines what happens when return is run while the call stack is empty.
+    pub end_behavior: EndBehavior,
 }
 
 impl Default for RuntimeSettings {
     fn default() -> Self {
         Self {
             disable_instruction_limit: false,
+            instruction_limit: MAX_INSTRUCTION_RUNS,
+            skip_empty_lines: false,
             autodetect_accumulators: true,
             autodetect_gamma_accumulator: true,
             autodetect_memory_cells: true,
             autodetect_index_memory_cells: true,
+            rng: Rng::default(),
+            measure: false,
+            clock: SharedClock::default(),
+            stack_limit: None,
+            call_stack_limit: MAX_CALL_STACK_SIZE,
+            tail_call_optimization: false,
+            end_behavior: EndBehavior::default(),
+        }
+    }
+}
+
+/// Determines what happens when `return` is run while the call stack is empty.
+///
+/// Course materials define the end of a program differently, so the behavior can be configured.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Deserialize, Serialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum EndBehavior {
+    /// The program jumps to the `END` label, which is located after the last instruction.
+    #[default]
+    JumpToEnd,
+    /// The program stops at the `return` instruction.
+    Halt,
+    /// A runtime error is raised.
+    Error,
+}
+
+impl EndBehavior {
+    /// Returns a description of what happens when `return` is run while the call stack is empty.
+    pub fn describe(self) -> &'static str {
+        match self {
+            Self::JumpToEnd => "jumps to the END label",
+            Self::Halt => "stops the program at the return instruction",
+            Self::Error => "causes a runtime error",
         }
     }
 }
 
+/// Seed that is used for the random number generator, if no seed is set.
+pub const DEFAULT_RNG_SEED: u64 = 0;
+
+/// Seeded pseudo random number generator.
+///
+/// The same seed always produces the same numbers, this makes runs that contain random numbers reproducible.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rng {
+    seed: u64,
+    state: Cell<u64>,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            state: Cell::new(seed),
+        }
+    }
+
+    /// Returns the seed of this random number generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Resets the random number generator to its seed, so that the same numbers are generated again.
+    pub fn reset(&self) {
+        self.state.set(self.seed);
+    }
+
+    /// Returns a random number in the range `min..=max`.
+    ///
+    /// `min` has to be smaller or equal to `max`.
+    pub fn next_in_range(&self, min: i32, max: i32) -> i32 {
+        let range = (i64::from(max) - i64::from(min) + 1) as u64;
+        let offset = (self.next_u64() % range) as i64;
+        (i64::from(min) + offset) as i32
+    }
+
+    /// Generates the next number using the splitmix64 algorithm.
+    fn next_u64(&self) -> u64 {
+        let state = self.state.get().wrapping_add(0x9E37_79B9_7F4A_7C15);
+        self.state.set(state);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        Self::new(DEFAULT_RNG_SEED)
+    }
+}
+
 #[cfg(test)]
 pub mod test_utils {
-    use std::collections::HashMap;
+    use std::collections::BTreeMap;
 
     use crate::base::{Accumulator, MemoryCell};
 
@@ -345,10 +980,10 @@ pub mod test_utils {
         #[allow(dead_code)]
         pub fn new_empty() -> Self {
             Self {
-                accumulators: HashMap::new(),
+                accumulators: BTreeMap::new(),
                 gamma: None,
-                memory_cells: HashMap::new(),
-                index_memory_cells: HashMap::new(),
+                memory_cells: BTreeMap::new(),
+                index_memory_cells: BTreeMap::new(),
                 stack: Vec::new(),
             }
         }
@@ -359,16 +994,16 @@ pub mod test_utils {
             idx_m_cells: Option<Vec<usize>>,
             enable_gamma: bool,
         ) -> Self {
-            let mut accumulators = HashMap::new();
+            let mut accumulators = BTreeMap::new();
             for i in 0..acc {
                 accumulators.insert(i, Accumulator::new(i));
             }
-            let mut memory_cells: HashMap<String, MemoryCell> = HashMap::new();
+            let mut memory_cells: BTreeMap<String, MemoryCell> = BTreeMap::new();
             for i in m_cells {
                 memory_cells.insert(i.clone(), MemoryCell::new(i.as_str()));
             }
             let gamma = if enable_gamma { Some(None) } else { None };
-            let mut index_memory_cells = HashMap::new();
+            let mut index_memory_cells = BTreeMap::new();
             if let Some(cells) = idx_m_cells {
                 for c in cells {
                     index_memory_cells.insert(c, None);
@@ -0,0 +1,264 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::instructions::variables::Variables;
+
+use super::RuntimeMemory;
+
+/// Receives an event every time the runtime has executed an instruction.
+///
+/// Can be used to follow the execution of a program, for example to print it or to forward it to an external visualizer.
+pub trait RuntimeObserver {
+    fn instruction_executed(&mut self, event: &ExecutionEvent);
+}
+
+impl<F: FnMut(&ExecutionEvent)> RuntimeObserver for F {
+    fn instruction_executed(&mut self, event: &ExecutionEvent) {
+        self(event);
+    }
+}
+
+/// Describes what happened when a single instruction was executed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionEvent {
+    /// Number of instructions that were executed, including this one.
+    pub step: usize,
+    /// Line of the instruction that was executed.
+    pub line: usize,
+    /// The instruction that was executed.
+    pub instruction: String,
+    /// Memory values that were changed by the instruction.
+    pub writes: Vec<MemoryWrite>,
+    /// Values that were popped from the stack, the first value was on top of the stack.
+    pub popped: Vec<i32>,
+    /// Values that were pushed onto the stack, the last value is now on top of the stack.
+    pub pushed: Vec<i32>,
+    /// Line that is executed next, if the instruction jumped to another line than the following line.
+    ///
+    /// A jump to `END` is reported as the line after the last instruction.
+    pub jump: Option<usize>,
+    /// True if the program has finished after this instruction.
+    pub finished: bool,
+}
+
+/// A value that was written to a memory location.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MemoryWrite {
+    /// Memory location in alpha notation, e.g. `a0`, `y`, `p(h1)` or `p(3)`.
+    pub target: String,
+    /// Name of the variable that is declared for the memory location.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variable: Option<String>,
+    pub value: Option<i32>,
+}
+
+impl ExecutionEvent {
+    /// Creates the event for the instruction in line `line`, by comparing the memory before and after the instruction was executed.
+    ///
+    /// `jump` is the line of the instruction that is executed next, if the instruction jumped. The names of `variables` are added to the memory writes.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        step: usize,
+        line: usize,
+        instruction: String,
+        before: &RuntimeMemory,
+        after: &RuntimeMemory,
+        variables: &Variables,
+        jump: Option<usize>,
+        finished: bool,
+    ) -> Self {
+        let common = before
+            .stack
+            .iter()
+            .zip(&after.stack)
+            .take_while(|(a, b)| a == b)
+            .count();
+        Self {
+            step,
+            line,
+            instruction,
+            writes: memory_writes(before, after, variables),
+            popped: before.stack[common..].iter().rev().copied().collect(),
+            pushed: after.stack[common..].to_vec(),
+            jump,
+            finished,
+        }
+    }
+}
+
+/// Returns the memory values that are different in `after` compared to `before`, sorted by memory location.
+fn memory_writes(
+    before: &RuntimeMemory,
+    after: &RuntimeMemory,
+    variables: &Variables,
+) -> Vec<MemoryWrite> {
+    let mut writes = BTreeMap::new();
+    for (idx, acc) in &after.accumulators {
+        if before.accumulators.get(idx).map(|a| a.data) != Some(acc.data) {
+            writes.insert((0, format!("{idx:0>10}")), (format!("a{idx}"), acc.data));
+        }
+    }
+    if let Some(gamma) = after.gamma {
+        if before.gamma != Some(gamma) {
+            writes.insert((1, String::new()), ("y".to_string(), gamma));
+        }
+    }
+    for (label, cell) in &after.memory_cells {
+        if before.memory_cells.get(label).map(|c| c.data) != Some(cell.data) {
+            writes.insert((2, label.clone()), (format!("p({label})"), cell.data));
+        }
+    }
+    for (idx, value) in &after.index_memory_cells {
+        if before.index_memory_cells.get(idx) != Some(value) {
+            writes.insert((3, format!("{idx:0>10}")), (format!("p({idx})"), *value));
+        }
+    }
+    writes
+        .into_values()
+        .map(|(target, value)| MemoryWrite {
+            variable: variables.name(&target).map(ToString::to_string),
+            target,
+            value,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{instructions::Instruction, utils::test_utils};
+
+    use super::{ExecutionEvent, MemoryWrite};
+
+    #[test]
+    fn test_run_observed() {
+        let mut rt = test_utils::runtime_from_str(
+            "a := 3\npush\na := 4\npush\nstack+\npop\np(h1) := a\ngoto end\np(2) := 1",
+        )
+        .unwrap();
+        let mut events = Vec::new();
+        rt.run_observed(&mut |event: &ExecutionEvent| events.push(event.clone()))
+            .unwrap();
+        assert_eq!(events.len(), 8);
+        assert_eq!(
+            events[0].writes,
+            vec![MemoryWrite {
+                target: "a0".to_string(),
+                variable: None,
+                value: Some(3)
+            }]
+        );
+        assert_eq!(events[4].instruction, "stack+");
+        assert_eq!(events[4].popped, vec![4, 3]);
+        assert_eq!(events[4].pushed, vec![7]);
+        assert_eq!(events[5].popped, vec![7]);
+        assert_eq!(
+            events[6].writes,
+            vec![MemoryWrite {
+                target: "p(h1)".to_string(),
+                variable: None,
+                value: Some(7)
+            }]
+        );
+        assert_eq!(events[6].jump, None);
+        assert_eq!(events[7].line, 8);
+        assert_eq!(events[7].jump, Some(10));
+        assert!(events[7].finished);
+        assert_eq!(
+            serde_json::to_string(&events[1]).unwrap(),
+            r#"{"step":2,"line":2,"instruction":"push","writes":[],"popped":[],"pushed":[3],"jump":null,"finished":false}"#
+        );
+    }
+
+    #[test]
+    fn test_run_observed_variables() {
+        let mut rt = test_utils::runtime_from_str("#var sum = p(h1)\np(h1) := 3\na := 2").unwrap();
+        let mut events = Vec::new();
+        rt.run_observed(&mut |event: &ExecutionEvent| events.push(event.clone()))
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&events[0].writes).unwrap(),
+            r#"[{"target":"p(h1)","variable":"sum","value":3}]"#
+        );
+        assert_eq!(
+            serde_json::to_string(&events[1].writes).unwrap(),
+            r#"[{"target":"a0","value":2}]"#
+        );
+    }
+
+    #[test]
+    fn test_run_observed_jump() {
+        let mut rt =
+            test_utils::runtime_from_str("a := 0\nloop: a := a + 1\nif a < 2 then goto loop")
+                .unwrap();
+        let mut events = Vec::new();
+        rt.run_observed(&mut |event: &ExecutionEvent| events.push(event.clone()))
+            .unwrap();
+        assert_eq!(
+            events.iter().map(|e| e.jump).collect::<Vec<_>>(),
+            vec![None, None, Some(2), None, None]
+        );
+    }
+
+    #[test]
+    fn test_run_observed_jump_to_end() {
+        // the jump target of a jump that ends the program is the line after the last instruction
+        let mut rt = test_utils::runtime_from_str("a := 1\ngoto END\na := 2").unwrap();
+        let mut events = Vec::new();
+        rt.run_observed(&mut |event: &ExecutionEvent| events.push(event.clone()))
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].jump, Some(4));
+        assert!(events[1].finished);
+        assert!(serde_json::to_string(&events[1])
+            .unwrap()
+            .ends_with(r#""jump":4,"finished":true}"#));
+        let mut rt = test_utils::runtime_from_str("return\na := 2").unwrap();
+        let mut events = Vec::new();
+        rt.run_observed(&mut |event: &ExecutionEvent| events.push(event.clone()))
+            .unwrap();
+        assert_eq!(events[0].jump, Some(3));
+    }
+
+    #[test]
+    fn test_preview_foreign_instruction_rand() {
+        // the previewed value is the value that is assigned when the instruction is run
+        let mut rt = test_utils::runtime_from_str("a := 1").unwrap();
+        let instruction = Instruction::try_from("a := rand(1, 1000000)").unwrap();
+        let preview = rt.preview_foreign_instruction(&instruction).unwrap();
+        rt.run_foreign_instruction(instruction).unwrap();
+        assert_eq!(
+            preview.writes[0].value,
+            rt.runtime_memory().accumulators[&0].data
+        );
+    }
+
+    #[test]
+    fn test_preview_foreign_instruction() {
+        let mut rt = test_utils::runtime_from_str("loop: a := 1\npush\na := 2").unwrap();
+        rt.step().unwrap();
+        let preview = rt
+            .preview_foreign_instruction(&Instruction::try_from("p(h1) := a + 4").unwrap())
+            .unwrap();
+        assert_eq!(preview.line, 2);
+        assert_eq!(
+            preview.writes,
+            vec![MemoryWrite {
+                target: "p(h1)".to_string(),
+                variable: None,
+                value: Some(5)
+            }]
+        );
+        assert_eq!(preview.jump, None);
+        // the runtime is not changed
+        assert_eq!(rt.runtime_memory().memory_cells["h1"].data, None);
+        let preview = rt
+            .preview_foreign_instruction(&Instruction::try_from("goto loop").unwrap())
+            .unwrap();
+        assert!(preview.writes.is_empty());
+        assert_eq!(preview.jump, Some(1));
+        assert!(rt
+            .preview_foreign_instruction(&Instruction::try_from("pop").unwrap())
+            .is_err());
+    }
+}
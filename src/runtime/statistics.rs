@@ -0,0 +1,128 @@
+use std::{collections::BTreeSet, fmt::Display};
+
+use crate::instructions::Instruction;
+
+use super::{
+    builder::END_LABELS,
+    memory_usage::{used_memory_locations, MemoryLocation},
+    Runtime,
+};
+
+/// Metrics that give an overview of the structure of a program, e.g. to give feedback on a solution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramStatistics {
+    /// Number of lines that contain an instruction.
+    pub instructions: usize,
+    /// Number of labels that are defined in the program.
+    ///
+    /// The end labels and the labels of structured loops are not included.
+    pub labels: usize,
+    /// Largest number of lines that is skipped by a jump, either forwards or backwards.
+    pub max_jump_distance: usize,
+    /// Number of linearly independent paths through the program, this is the number of conditional jumps and
+    /// conditional instructions plus one.
+    pub cyclomatic_complexity: usize,
+    /// Memory locations that are referenced by the program.
+    ///
+    /// Index memory cells are only included if their index is written directly in the program, e.g. `p(3)`.
+    pub memory_locations: BTreeSet<MemoryLocation>,
+}
+
+impl ProgramStatistics {
+    /// Calculates the statistics of the program of the runtime.
+    pub fn new(runtime: &Runtime) -> Self {
+        let instructions = runtime.instructions();
+        let labels = &runtime.control_flow().instruction_labels;
+        let generated_labels = runtime
+            .structured_loops()
+            .iter()
+            .flat_map(|l| std::iter::once(&l.start_label).chain(l.exit_label.as_ref()))
+            .collect::<BTreeSet<_>>();
+        let max_jump_distance = instructions
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, instruction)| match instruction {
+                Instruction::Goto(label)
+                | Instruction::JumpIf(_, _, _, label)
+                | Instruction::Call(label) => labels.get(label).map(|target| idx.abs_diff(*target)),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0);
+        let decisions = instructions
+            .iter()
+            .filter(|i| matches!(i, Instruction::JumpIf(..) | Instruction::CondExec(..)))
+            .count();
+        Self {
+            instructions: instructions
+                .iter()
+                .filter(|i| !matches!(i, Instruction::Noop))
+                .count(),
+            labels: labels
+                .keys()
+                .filter(|l| !END_LABELS.contains(&l.as_str()) && !generated_labels.contains(l))
+                .count(),
+            max_jump_distance,
+            cyclomatic_complexity: decisions + 1,
+            memory_locations: used_memory_locations(instructions),
+        }
+    }
+}
+
+impl Display for ProgramStatistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let memory_locations = if self.memory_locations.is_empty() {
+            "-".to_string()
+        } else {
+            self.memory_locations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        writeln!(f, "Statistics:")?;
+        writeln!(f, "  instructions:          {}", self.instructions)?;
+        writeln!(f, "  labels:                {}", self.labels)?;
+        writeln!(f, "  max jump distance:     {}", self.max_jump_distance)?;
+        writeln!(f, "  cyclomatic complexity: {}", self.cyclomatic_complexity)?;
+        write!(f, "  memory referenced:     {memory_locations}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::test_utils;
+
+    use super::ProgramStatistics;
+
+    fn statistics(input: &str) -> ProgramStatistics {
+        ProgramStatistics::new(&test_utils::runtime_from_str(input).unwrap())
+    }
+
+    #[test]
+    fn test_statistics() {
+        let stats = statistics(
+            "a := 5\nloop: if a <= 0 then goto END\na := a - 1\np(h1) := p(h1) + a\ngoto loop",
+        );
+        assert_eq!(stats.instructions, 5);
+        assert_eq!(stats.labels, 1);
+        // END is located after the last instruction
+        assert_eq!(stats.max_jump_distance, 4);
+        assert_eq!(stats.cyclomatic_complexity, 2);
+        assert_eq!(
+            stats
+                .memory_locations
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec!["a0", "p(h1)"]
+        );
+    }
+
+    #[test]
+    fn test_statistics_empty_lines_and_loops() {
+        let stats = statistics("a := 3\n\nwhile a > 0 do\na := a - 1\nend");
+        assert_eq!(stats.labels, 0);
+        assert_eq!(stats.cyclomatic_complexity, 2);
+    }
+}
@@ -0,0 +1,191 @@
+use std::{collections::BTreeMap, fmt::Display, time::Duration};
+
+/// Time that was spent running each kind of instruction.
+///
+/// Is only recorded when the runtime is built with `--measure`, to validate changes that should make the runtime
+/// faster.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InstructionTimings {
+    /// Maps the kind of instruction, e.g. `assign`, to the timing of its runs.
+    kinds: BTreeMap<&'static str, KindTiming>,
+}
+
+/// Accumulated time of all runs of one kind of instruction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KindTiming {
+    pub runs: usize,
+    pub total: Duration,
+}
+
+impl KindTiming {
+    /// Returns the average time of a single run.
+    pub fn average(&self) -> Duration {
+        if self.runs == 0 {
+            return Duration::ZERO;
+        }
+        let nanos = self.total.as_nanos() / self.runs as u128;
+        Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+    }
+}
+
+impl InstructionTimings {
+    /// Adds a run of an instruction of kind `kind` that took `time`.
+    pub fn record(&mut self, kind: &'static str, time: Duration) {
+        let timing = self.kinds.entry(kind).or_default();
+        timing.runs += 1;
+        timing.total += time;
+    }
+
+    /// Returns the timing of each kind of instruction that was run, the kind that took the most time is first.
+    pub fn kinds(&self) -> Vec<(&'static str, KindTiming)> {
+        let mut kinds = self
+            .kinds
+            .iter()
+            .map(|(kind, timing)| (*kind, *timing))
+            .collect::<Vec<_>>();
+        kinds.sort_by_key(|(_, timing)| std::cmp::Reverse(timing.total));
+        kinds
+    }
+
+    /// Returns the accumulated timing of all instructions.
+    pub fn total(&self) -> KindTiming {
+        self.kinds
+            .values()
+            .fold(KindTiming::default(), |total, timing| KindTiming {
+                runs: total.runs + timing.runs,
+                total: total.total + timing.total,
+            })
+    }
+}
+
+impl Display for InstructionTimings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timing:")?;
+        if self.kinds.is_empty() {
+            return write!(f, "\n  no instructions were run");
+        }
+        let width = self.kinds.keys().map(|k| k.len()).max().unwrap_or(0);
+        let line = |f: &mut std::fmt::Formatter<'_>, name: &str, timing: KindTiming| {
+            write!(
+                f,
+                "\n  {:<width$}  {:>9} runs  {:>12?} total  {:>10?} average",
+                format!("{name}:"),
+                timing.runs,
+                timing.total,
+                timing.average(),
+                width = width + 1
+            )
+        };
+        for (kind, timing) in self.kinds() {
+            line(f, kind, timing)?;
+        }
+        line(f, "total", self.total())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use crate::{
+        cli::CheckLoadArgs,
+        clock::{ManualClock, SharedClock},
+        runtime::builder::RuntimeBuilder,
+        utils::test_utils,
+    };
+
+    use super::{InstructionTimings, KindTiming};
+
+    #[test]
+    fn test_timings() {
+        let mut timings = InstructionTimings::default();
+        timings.record("assign", Duration::from_micros(3));
+        timings.record("goto", Duration::from_micros(10));
+        timings.record("assign", Duration::from_micros(5));
+        assert_eq!(
+            timings.kinds(),
+            vec![
+                (
+                    "goto",
+                    KindTiming {
+                        runs: 1,
+                        total: Duration::from_micros(10)
+                    }
+                ),
+                (
+                    "assign",
+                    KindTiming {
+                        runs: 2,
+                        total: Duration::from_micros(8)
+                    }
+                ),
+            ]
+        );
+        assert_eq!(timings.kinds()[1].1.average(), Duration::from_micros(4));
+        assert_eq!(timings.total().runs, 3);
+        assert_eq!(
+            timings.to_string(),
+            "Timing:\n  goto:            1 runs          10µs total        10µs average\n  assign:          2 runs           8µs total         4µs average\n  total:           3 runs          18µs total         6µs average"
+        );
+        assert_eq!(
+            InstructionTimings::default().to_string(),
+            "Timing:\n  no instructions were run"
+        );
+    }
+
+    #[test]
+    fn test_runtime_timings() {
+        let instructions =
+            test_utils::string_literal_to_vec("a := 0\nloop: a := a + 1\nif a < 5 then goto loop");
+        let mut rt = RuntimeBuilder::new(&instructions, "test")
+            .unwrap()
+            .build()
+            .unwrap();
+        rt.run().unwrap();
+        assert_eq!(rt.timings(), None);
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.apply_check_load_args(&CheckLoadArgs {
+            measure: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let mut rt = rb.build().unwrap();
+        rt.run().unwrap();
+        let runs = rt
+            .timings()
+            .unwrap()
+            .kinds()
+            .into_iter()
+            .map(|(kind, timing)| (kind, timing.runs))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            runs,
+            BTreeMap::from([("assign", 1), ("calc", 5), ("jump_if", 5)])
+        );
+    }
+
+    #[test]
+    fn test_runtime_timings_with_manual_clock() {
+        let instructions = test_utils::string_literal_to_vec(
+            "a := 0
+loop: a := a + 1
+if a < 5 then goto loop",
+        );
+        let mut rb = RuntimeBuilder::new(&instructions, "test").unwrap();
+        rb.apply_check_load_args(&CheckLoadArgs {
+            measure: true,
+            ..Default::default()
+        })
+        .unwrap();
+        // every instruction takes exactly one tick
+        rb.set_clock(SharedClock::new(ManualClock::with_tick(
+            Duration::from_micros(2),
+        )));
+        let mut rt = rb.build().unwrap();
+        rt.run().unwrap();
+        let total = rt.timings().unwrap().total();
+        assert_eq!(total.runs, 11);
+        assert_eq!(total.total, Duration::from_micros(22));
+        assert_eq!(total.average(), Duration::from_micros(2));
+    }
+}
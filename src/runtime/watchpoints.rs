@@ -0,0 +1,214 @@
+use std::fmt::Display;
+
+use crate::{
+    base::Comparison,
+    instructions::{Instruction, TargetType, Value},
+};
+
+use super::{observer::ExecutionEvent, RuntimeMemory};
+
+/// Condition on the memory at which the execution of a program is stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Watchpoint {
+    /// Stops when a value is written to the memory location, e.g. `p(h1)`.
+    ///
+    /// Contains the memory location in alpha notation.
+    Write(String),
+    /// Stops when the comparison becomes true, e.g. `a0 > 100`.
+    ///
+    /// Contains the condition as it was written.
+    Value(Value, Comparison, Value, String),
+}
+
+impl Watchpoint {
+    /// Parses a watchpoint that stops when a value is written to `location`, e.g. `p(h1)`.
+    pub fn on_write(location: &str) -> Result<Self, String> {
+        let location = location.trim().to_string();
+        match TargetType::try_from((&location, (0, location.len()))) {
+            Ok(target) => Ok(Self::Write(target.to_string())),
+            Err(_) => Err(format!("'{location}' is not a memory location")),
+        }
+    }
+
+    /// Parses a watchpoint that stops when `condition` becomes true, e.g. `a0 > 100`.
+    ///
+    /// The condition is written like the condition of `if`.
+    pub fn on_value(condition: &str) -> Result<Self, String> {
+        // the condition is parsed as part of an if instruction, so that all notations of the condition are supported
+        match Instruction::try_from(format!("if {condition} then goto watchpoint").as_str()) {
+            Ok(Instruction::JumpIf(value_a, cmp, value_b, _)) => Ok(Self::Value(
+                value_a,
+                cmp,
+                value_b,
+                condition.trim().to_string(),
+            )),
+            _ => Err(format!(
+                "'{}' is not a condition, conditions are written like the condition of if, e.g. \"a0 > 100\"",
+                condition.trim()
+            )),
+        }
+    }
+
+    /// Checks if the condition of a value watchpoint is true, uninitialized values never fulfill the condition.
+    ///
+    /// Is always false for write watchpoints.
+    fn condition_met(&self, memory: &RuntimeMemory) -> bool {
+        match self {
+            Self::Write(_) => false,
+            Self::Value(value_a, cmp, value_b, _) => {
+                match (value_a.value(memory), value_b.value(memory)) {
+                    (Ok(a), Ok(b)) => cmp.cmp(a, b),
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// Watchpoints that are checked after every instruction.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Watchpoints {
+    watchpoints: Vec<Watchpoint>,
+    /// For each watchpoint, if its condition was met after the last instruction.
+    ///
+    /// Value watchpoints only stop the execution when the condition becomes true, not while it stays true.
+    conditions_met: Vec<bool>,
+}
+
+impl Watchpoints {
+    /// Creates the watchpoints, `memory` is the memory before the program is run.
+    pub fn new(watchpoints: Vec<Watchpoint>, memory: &RuntimeMemory) -> Self {
+        let mut watchpoints = Self {
+            watchpoints,
+            conditions_met: Vec::new(),
+        };
+        watchpoints.reset(memory);
+        watchpoints
+    }
+
+    /// Resets the watchpoints when the program is run again, `memory` is the memory before the program is run.
+    pub fn reset(&mut self, memory: &RuntimeMemory) {
+        self.conditions_met = self
+            .watchpoints
+            .iter()
+            .map(|w| w.condition_met(memory))
+            .collect();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.watchpoints.is_empty()
+    }
+
+    /// Checks the watchpoints after the instruction of `event` was executed, `memory` is the memory after the
+    /// instruction was executed.
+    ///
+    /// Returns the watchpoints that were triggered, if any.
+    pub fn check(
+        &mut self,
+        event: &ExecutionEvent,
+        memory: &RuntimeMemory,
+    ) -> Option<WatchpointHit> {
+        let mut triggered = Vec::new();
+        for (watchpoint, was_met) in self.watchpoints.iter().zip(self.conditions_met.iter_mut()) {
+            match watchpoint {
+                Watchpoint::Write(location) => {
+                    if let Some(write) = event.writes.iter().find(|w| &w.target == location) {
+                        let value = write
+                            .value
+                            .map_or_else(|| "uninitialized".to_string(), |v| v.to_string());
+                        triggered.push(format!("{location} was set to {value}"));
+                    }
+                }
+                Watchpoint::Value(.., condition) => {
+                    let met = watchpoint.condition_met(memory);
+                    if met && !*was_met {
+                        triggered.push(format!("{condition} is true"));
+                    }
+                    *was_met = met;
+                }
+            }
+        }
+        if triggered.is_empty() {
+            return None;
+        }
+        Some(WatchpointHit {
+            line: event.line,
+            reasons: triggered,
+        })
+    }
+}
+
+/// Watchpoints that were triggered by an instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchpointHit {
+    /// Line of the instruction that triggered the watchpoints.
+    pub line: usize,
+    /// Describes why each watchpoint was triggered, e.g. `p(h1) was set to 5`.
+    pub reasons: Vec<String>,
+}
+
+impl Display for WatchpointHit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Stopped after line {}: {}",
+            self.line,
+            self.reasons.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{runtime::observer::ExecutionEvent, utils::test_utils};
+
+    use super::{Watchpoint, Watchpoints};
+
+    #[test]
+    fn test_parse_watchpoints() {
+        assert_eq!(
+            Watchpoint::on_write("ρ(h1)"),
+            Ok(Watchpoint::Write("p(h1)".to_string()))
+        );
+        assert!(Watchpoint::on_write("5").is_err());
+        assert!(matches!(
+            Watchpoint::on_value("a0 > 100"),
+            Ok(Watchpoint::Value(.., ref c)) if c == "a0 > 100"
+        ));
+        assert!(Watchpoint::on_value("a0").is_err());
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let mut rt =
+            test_utils::runtime_from_str("a := 5\np(h1) := a\na := a * 30\na := a + 1").unwrap();
+        let mut watchpoints = Watchpoints::new(
+            vec![
+                Watchpoint::on_write("p(h1)").unwrap(),
+                Watchpoint::on_value("a0 > 100").unwrap(),
+            ],
+            rt.runtime_memory(),
+        );
+        let mut triggered = Vec::new();
+        for _ in 0..4 {
+            let mut last_event = None;
+            rt.step_observed(&mut |event: &ExecutionEvent| last_event = Some(event.clone()))
+                .unwrap();
+            triggered.push(
+                watchpoints
+                    .check(&last_event.unwrap(), rt.runtime_memory())
+                    .map(|hit| hit.to_string()),
+            );
+        }
+        assert_eq!(
+            triggered,
+            vec![
+                None,
+                Some("Stopped after line 2: p(h1) was set to 5".to_string()),
+                Some("Stopped after line 3: a0 > 100 is true".to_string()),
+                // the condition stays true
+                None,
+            ]
+        );
+    }
+}
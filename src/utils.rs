@@ -1,7 +1,9 @@
 use std::{
     collections::HashSet,
     fs::{remove_file, File},
-    io::{BufRead, BufReader, LineWriter, Write},
+    io::{self, BufRead, BufReader, LineWriter, Write},
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
 };
 
 use miette::{miette, IntoDiagnostic, NamedSource, Result, SourceOffset, SourceSpan};
@@ -11,10 +13,34 @@ use crate::instructions::{
     Identifier, Instruction,
 };
 
+/// Name that is displayed as filename, when the program is read from stdin.
+pub const STDIN_FILENAME: &str = "<stdin>";
+
+/// Set when files may not be downloaded, see [`disable_network_access`].
+static NETWORK_ACCESS_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Time after which downloading a file is cancelled.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Prevents that files are downloaded, when an url is read with [`read_file`].
+pub fn disable_network_access() {
+    NETWORK_ACCESS_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Returns true if `path` is a http or https url.
+pub fn is_url(path: &str) -> bool {
+    path.starts_with("http://") || path.starts_with("https://")
+}
+
 /// Reads a file into a string vector.
 ///
 /// Each  line is a new entry.
+///
+/// If `path` is a http or https url, the file is downloaded, unless network access is disabled.
 pub fn read_file(path: &str) -> Result<Vec<String>> {
+    if is_url(path) {
+        return download_file(path);
+    }
     let mut content = Vec::new();
     let file = match File::open(path) {
         Ok(f) => f,
@@ -31,6 +57,35 @@ pub fn read_file(path: &str) -> Result<Vec<String>> {
     Ok(content)
 }
 
+/// Reads stdin until it is closed into a string vector.
+///
+/// Each line is a new entry.
+pub fn read_stdin() -> Result<Vec<String>> {
+    io::stdin()
+        .lock()
+        .lines()
+        .collect::<Result<Vec<_>, _>>()
+        .into_diagnostic()
+}
+
+/// Downloads the file located at the url into a string vector.
+///
+/// Each line is a new entry.
+fn download_file(url: &str) -> Result<Vec<String>> {
+    if NETWORK_ACCESS_DISABLED.load(Ordering::Relaxed) {
+        return Err(miette!(
+            "the file can not be downloaded, because network access is disabled with --offline"
+        ));
+    }
+    let content = ureq::get(url)
+        .timeout(DOWNLOAD_TIMEOUT)
+        .call()
+        .map_err(|e| miette!("download failed: {e}"))?
+        .into_string()
+        .map_err(|e| miette!("download failed: {e}"))?;
+    Ok(content.lines().map(str::to_string).collect())
+}
+
 pub fn write_file(contet: &Vec<String>, path: &str) -> Result<()> {
     remove_file(path).into_diagnostic()?;
     let file = File::create(path).into_diagnostic()?;
@@ -54,19 +109,80 @@ pub fn write_line_to_file(line: &str, path: &str) -> Result<()> {
     write_file(&content, path)
 }
 
+/// Returns the length in bytes of the character literal (e.g. `'a'` or `' '`) at the start of `s`.
+fn char_literal_len(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices();
+    let (_, '\'') = chars.next()? else {
+        return None;
+    };
+    chars.next()?;
+    match chars.next()? {
+        (index, '\'') => Some(index + 1),
+        _ => None,
+    }
+}
+
+/// Returns the index at which the comment in `line` starts, comments start with `//` or `#`.
+///
+/// If the line contains both, the comment starting with `//` is used. Delimiters inside character literals,
+/// e.g. `'#'`, do not start a comment.
+pub fn comment_start(line: &str) -> Option<usize> {
+    let mut hash = None;
+    let mut index = 0;
+    let mut token_start = true;
+    while let Some(c) = line[index..].chars().next() {
+        if token_start {
+            if let Some(len) = char_literal_len(&line[index..]) {
+                index += len;
+                token_start = false;
+                continue;
+            }
+        }
+        if line[index..].starts_with("//") {
+            return Some(index);
+        }
+        if c == '#' && hash.is_none() {
+            hash = Some(index);
+        }
+        token_start = c.is_whitespace();
+        index += c.len_utf8();
+    }
+    hash
+}
+
+/// Splits `s` at whitespaces, character literals like `' '` are kept as one token.
+pub fn split_tokens(s: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    let mut index = 0;
+    while let Some(c) = s[index..].chars().next() {
+        if c.is_whitespace() {
+            if let Some(start) = start.take() {
+                tokens.push(&s[start..index]);
+            }
+        } else if start.is_none() {
+            start = Some(index);
+            if let Some(len) = char_literal_len(&s[index..]) {
+                index += len;
+                continue;
+            }
+        }
+        index += c.len_utf8();
+    }
+    if let Some(start) = start {
+        tokens.push(&s[start..]);
+    }
+    tokens
+}
+
 // TODO change to take String (with ownership)
 /// Removes everything behind # or // from the string
 pub fn remove_comment(instruction: &str) -> String {
     instruction
         .lines()
-        .map(|line| {
-            if let Some(index) = line.find("//") {
-                line[..index].trim()
-            } else if let Some(index) = line.find('#') {
-                line[..index].trim()
-            } else {
-                line.trim()
-            }
+        .map(|line| match comment_start(line) {
+            Some(index) => line[..index].trim(),
+            None => line.trim(),
         })
         .collect::<Vec<_>>()
         .join("\n")
@@ -77,14 +193,9 @@ pub fn remove_comment(instruction: &str) -> String {
 pub fn get_comment(instruction: &str) -> Option<String> {
     let comment = instruction
         .lines()
-        .map(|line| {
-            if let Some(index) = line.find("//") {
-                line[index..].trim()
-            } else if let Some(index) = line.find('#') {
-                line[index..].trim()
-            } else {
-                ""
-            }
+        .map(|line| match comment_start(line) {
+            Some(index) => line[index..].trim(),
+            None => "",
         })
         .collect::<Vec<_>>()
         .join("\n");
@@ -168,22 +279,38 @@ pub fn prepare_whitelist_file(content: Vec<String>) -> Vec<String> {
         }
         let chunks = line.split(' ');
         for chunk in chunks {
-            match chunk {
-                "A" => new_chunks.push("a0"),
-                "M" => new_chunks.push("p(h1)"),
-                "M(A)" => new_chunks.push("p(a0)"),
-                "M(C)" => new_chunks.push("p(1)"),
-                "M(Y)" => new_chunks.push("p(y)"),
-                "M(M(C))" => new_chunks.push("p(p(1))"),
-                "M(M)" => new_chunks.push("p(p(h1))"),
-                "C" => new_chunks.push("0"),
-                "Y" => new_chunks.push("y"),
-                "OP" => new_chunks.push("+"),
-                "stackOP" => new_chunks.push("stack+"),
-                "CMP" => new_chunks.push("=="),
-                "goto" => new_chunks.push("goto loop"),
-                _ => new_chunks.push(chunk),
-            }
+            // placeholders can be wrapped in functions, e.g. rand(C, C) or abs(M)
+            let (prefix, chunk) = match ["rand(", "min(", "max(", "abs("]
+                .into_iter()
+                .find_map(|prefix| chunk.strip_prefix(prefix).map(|rest| (prefix, rest)))
+            {
+                Some((prefix, rest)) => (prefix, rest),
+                None => ("", chunk),
+            };
+            let (chunk, suffix) = if let Some(rest) = chunk.strip_suffix(',') {
+                (rest, ",")
+            } else if chunk.matches(')').count() > chunk.matches('(').count() {
+                (&chunk[..chunk.len() - 1], ")")
+            } else {
+                (chunk, "")
+            };
+            let replacement = match chunk {
+                "A" => "a0",
+                "M" => "p(h1)",
+                "M(A)" => "p(a0)",
+                "M(C)" => "p(1)",
+                "M(Y)" => "p(y)",
+                "M(M(C))" => "p(p(1))",
+                "M(M)" => "p(p(h1))",
+                "C" => "0",
+                "Y" => "y",
+                "OP" => "+",
+                "stackOP" => "stack+",
+                "CMP" => "==",
+                "goto" => "goto loop",
+                _ => chunk,
+            };
+            new_chunks.push(format!("{prefix}{replacement}{suffix}"));
         }
         prepared.push(new_chunks.join(" "));
     }
@@ -193,7 +320,7 @@ pub fn prepare_whitelist_file(content: Vec<String>) -> Vec<String> {
 #[cfg(test)]
 pub mod test_utils {
     use crate::{
-        cli::{GlobalArgs, InstructionLimitingArgs},
+        cli::{GlobalArgs, MemoryArgs},
         runtime::{builder::RuntimeBuilder, Runtime},
     };
 
@@ -220,16 +347,18 @@ pub mod test_utils {
     pub fn runtime_from_str_with_disable_memory_detection(input: &str) -> miette::Result<Runtime> {
         let mut rb = RuntimeBuilder::new(&string_literal_to_vec(input), "test").unwrap();
 
-        let mut ila = InstructionLimitingArgs::default();
-        ila.disable_memory_detection = true;
-        rb.apply_instruction_limiting_args(&ila).unwrap();
+        let memory_args = MemoryArgs {
+            disable_memory_detection: true,
+            ..Default::default()
+        };
+        rb.apply_memory_args(&memory_args).unwrap();
         rb.build()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::utils::{get_comment, prepare_whitelist_file, remove_comment};
+    use crate::utils::{get_comment, is_url, prepare_whitelist_file, remove_comment, split_tokens};
 
     #[test]
     fn test_remove_comments() {
@@ -245,6 +374,22 @@ mod tests {
         assert_eq!(remove_comment("a //:= 5"), String::from("a"));
         assert_eq!(remove_comment("#a := 5"), String::from(""));
         assert_eq!(remove_comment("//a := 5"), String::from(""));
+        assert_eq!(
+            remove_comment("a := '#' # comment"),
+            String::from("a := '#'")
+        );
+        assert_eq!(
+            remove_comment("a := '/' // comment"),
+            String::from("a := '/'")
+        );
+        assert_eq!(remove_comment("a := ' '"), String::from("a := ' '"));
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://example.com/program.alpha"));
+        assert!(is_url("http://localhost:8080/program.alpha"));
+        assert!(!is_url("programs/http.alpha"));
     }
 
     #[test]
@@ -262,6 +407,23 @@ mod tests {
         assert_eq!(get_comment("#a := 5"), Some(String::from("#a := 5")));
         assert_eq!(get_comment("//a := 5"), Some(String::from("//a := 5")));
         assert_eq!(get_comment("a := 5"), None);
+        assert_eq!(get_comment("a := '#'"), None);
+        assert_eq!(
+            get_comment("a := '#' // comment"),
+            Some(String::from("// comment"))
+        );
+    }
+
+    #[test]
+    fn test_split_tokens() {
+        assert_eq!(split_tokens("  a1 := 5 "), vec!["a1", ":=", "5"]);
+        assert_eq!(split_tokens("a1 := ' '"), vec!["a1", ":=", "' '"]);
+        assert_eq!(
+            split_tokens("a1 := ' ' + 'x'"),
+            vec!["a1", ":=", "' '", "+", "'x'"]
+        );
+        // a quote that does not start a character literal is part of the token
+        assert_eq!(split_tokens("a1 := ' x"), vec!["a1", ":=", "'", "x"]);
     }
 
     #[test]
@@ -284,4 +446,23 @@ mod tests {
         ];
         assert_eq!(*contents, after);
     }
+
+    #[test]
+    fn test_prepare_whitelist_file_functions() {
+        let contents = prepare_whitelist_file(vec![
+            "A := rand(C, C)".to_string(),
+            "M := rand(M(C), M(M(C)))".to_string(),
+            "A := min(A, M)".to_string(),
+            "Y := abs(M(C))".to_string(),
+        ]);
+        assert_eq!(
+            contents,
+            vec![
+                "a0 := rand(0, 0)".to_string(),
+                "p(h1) := rand(p(1), p(p(1)))".to_string(),
+                "a0 := min(a0, p(h1))".to_string(),
+                "y := abs(p(1))".to_string(),
+            ]
+        );
+    }
 }
@@ -70,10 +70,10 @@ fn test_allowed_instructions_only_instructions_fail() {
         r#"Building instructions
 Building runtime
 Check unsuccessful, program did not compile.
-Error: build_program_error
+Error: E100
 
   × when building program
-  ╰─▶ build_program::instruction_not_allowed_error
+  ╰─▶ E103
       
         × instruction 'p(h1) := 20' in line '2' is not allowed
         help: Make sure that you include this type ('M := C') of instruction
@@ -83,7 +83,7 @@ Error: build_program_error
       
               A := C
       
-
+For more information about this error, run `alpha_tui explain E103`.
 "#,
     );
 }
@@ -115,10 +115,10 @@ fn test_allowed_instructions_only_comparisons_fail() {
         r#"Building instructions
 Building runtime
 Check unsuccessful, program did not compile.
-Error: build_program_error
+Error: E100
 
   × when building program
-  ╰─▶ build_program::comparison_not_allowed_error
+  ╰─▶ E104
       
         × comparison '==' in line '1' is not allowed
         help: Make sure that you include this comparison ('==') in the allowed
@@ -126,7 +126,7 @@ Error: build_program_error
               To mark this comparison as allowed you can use: '--allowed-
               comparisons "eq"'
       
-
+For more information about this error, run `alpha_tui explain E104`.
 "#,
     );
 }
@@ -145,10 +145,10 @@ fn test_allowed_instructions_no_comparisons() {
         r#"Building instructions
 Building runtime
 Check unsuccessful, program did not compile.
-Error: build_program_error
+Error: E100
 
   × when building program
-  ╰─▶ build_program::comparison_not_allowed_error
+  ╰─▶ E104
       
         × comparison '==' in line '1' is not allowed
         help: Make sure that you include this comparison ('==') in the allowed
@@ -156,7 +156,7 @@ Error: build_program_error
               To mark this comparison as allowed you can use: '--allowed-
               comparisons "eq"'
       
-
+For more information about this error, run `alpha_tui explain E104`.
 "#,
     );
 }
@@ -188,10 +188,10 @@ fn test_allowed_instructions_only_operations_fail() {
         r#"Building instructions
 Building runtime
 Check unsuccessful, program did not compile.
-Error: build_program_error
+Error: E100
 
   × when building program
-  ╰─▶ build_program::operation_not_allowed_error
+  ╰─▶ E105
       
         × operation '-' in line '2' is not allowed
         help: Make sure that you include this operation ('-') in the allowed
@@ -200,7 +200,7 @@ Error: build_program_error
       operations
               "sub"'
       
-
+For more information about this error, run `alpha_tui explain E105`.
 "#,
     );
 }
@@ -219,10 +219,10 @@ fn test_allowed_instructions_no_operations() {
         r#"Building instructions
 Building runtime
 Check unsuccessful, program did not compile.
-Error: build_program_error
+Error: E100
 
   × when building program
-  ╰─▶ build_program::operation_not_allowed_error
+  ╰─▶ E105
       
         × operation '+' in line '1' is not allowed
         help: Make sure that you include this operation ('+') in the allowed
@@ -231,7 +231,58 @@ Error: build_program_error
       operations
               "add"'
       
+For more information about this error, run `alpha_tui explain E105`.
+"#,
+    );
+}
+
+#[test]
+fn test_allowed_instructions_no_structured_loops() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_allowed_instructions_no_structured_loops/program.alpha")
+        .arg("compile")
+        .arg("--allowed-instructions-file")
+        .arg("tests/input/test_allowed_instructions_no_structured_loops/instructions.json")
+        .assert();
+    assert.code(4);
+}
 
+#[test]
+fn test_allowed_instructions_violations() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_allowed_instructions_violations/program.alpha")
+        .arg("compile")
+        .arg("--allowed-instructions-file")
+        .arg("tests/input/test_allowed_instructions_violations/instructions.json")
+        .assert();
+    assert.failure().stdout(
+        r#"Building instructions
+Building runtime
+Check unsuccessful, program did not compile.
+Error: E100
+
+  × when building program
+  ╰─▶ E106
+      
+        × 3 violations of the allowed instructions, comparisons and operations
+        help: line 2 'a0 := a0 * 2':
+                - operation '*' is not allowed
+              line 3 'p(h1) := a0':
+                - instruction type 'M := A' is not allowed
+              line 4 'if a0 == 4 then goto end':
+                - comparison '==' is not allowed
+      
+              Include these instruction types, comparisons and operations
+      in the
+              allowed instructions file or use different instructions.
+              Comparisons and operations can also be allowed with '--allowed-
+              comparisons' and '--allowed-operations'.
+      
+For more information about this error, run `alpha_tui explain E106`.
 "#,
     );
 }
@@ -1,5 +1,28 @@
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+};
+
 use assert_cmd::Command;
 
+/// Serves `content` for a single http request, returns the url at which it is served.
+fn serve_once(content: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/program.alpha", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0; 1024];
+        let _ = stream.read(&mut request);
+        let _ = write!(
+            stream,
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{content}",
+            content.len()
+        );
+    });
+    url
+}
+
 #[test]
 fn test_cmd_check_compile_with_allowed_instructions() {
     let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
@@ -25,3 +48,635 @@ fn test_cmd_check_compile_with_allowed_instructions_2() {
         .assert();
     assert.success();
 }
+
+#[test]
+fn test_cmd_import_ram() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("import")
+        .arg("tests/input/test_cmd_import/program.ram")
+        .arg("--format")
+        .arg("ram")
+        .assert();
+    assert.success().stdout(
+        "// computes 2^3 and stores the result in address 2\na := 3\np(1) := a\na := 1\nloop: p(2) := a\na := p(1)\nif a == 0 then goto end\na := a - 1\np(1) := a\na := p(2)\na := a * 2\ngoto loop\nend: goto END\n",
+    );
+}
+
+#[test]
+fn test_cmd_missing_program_file() {
+    // every command uses the same exit code when the program file can not be read
+    let file = "tests/input/does_not_exist.alpha";
+    for args in [
+        &["check", file, "compile"][..],
+        &["test", file],
+        &["load", file],
+    ] {
+        let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+        let assert = cmd.args(args).assert();
+        let stderr = String::from_utf8(assert.code(10).get_output().stderr.clone()).unwrap();
+        assert!(stderr.contains("Unable to read file"));
+    }
+}
+
+#[test]
+fn test_cmd_check_run_emit_events() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_run_emit_events/program.alpha")
+        .arg("run")
+        .arg("--emit-events")
+        .assert();
+    assert.success().stdout(
+        r#"{"step":1,"line":1,"instruction":"a0 := 2","writes":[{"target":"a0","value":2}],"popped":[],"pushed":[],"jump":null,"finished":false}
+{"step":2,"line":2,"instruction":"push","writes":[],"popped":[],"pushed":[2],"jump":null,"finished":false}
+{"step":3,"line":3,"instruction":"if a0 > 0 then goto end","writes":[],"popped":[],"pushed":[],"jump":5,"finished":true}
+"#,
+    );
+}
+
+#[test]
+fn test_cmd_check_run_keep_going() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_run_keep_going/program.alpha")
+        .arg("run")
+        .arg("--keep-going")
+        .assert();
+    let stdout = String::from_utf8(assert.code(5).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Check unsuccessful, 2 runtime errors while running program."));
+    assert!(stdout.contains("runtime error in line 2"));
+    assert!(stdout.contains("runtime error in line 3"));
+}
+
+#[test]
+fn test_cmd_check_run_watchpoints() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_run_watchpoints/program.alpha")
+        .arg("run")
+        .arg("--break-on-value")
+        .arg("a0 > 100")
+        .assert();
+    let stdout = String::from_utf8(assert.code(0).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Stopped after line 3: a0 > 100 is true"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_run_watchpoints/program.alpha")
+        .arg("run")
+        .arg("--break-on-write")
+        .arg("p(h1)")
+        .assert();
+    let stdout = String::from_utf8(assert.code(0).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Stopped after line 2: p(h1) was set to 5"));
+}
+
+#[test]
+fn test_cmd_check_end_behavior() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_end_behavior/program.alpha")
+        .arg("run")
+        .assert();
+    let stdout = String::from_utf8(assert.code(0).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Return with an empty call stack jumps to the END label"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_end_behavior/program.alpha")
+        .arg("run")
+        .arg("--end-behavior")
+        .arg("error")
+        .assert();
+    let stdout = String::from_utf8(assert.code(5).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Return with an empty call stack causes a runtime error"));
+    assert!(stdout.contains("E319"));
+}
+
+#[test]
+fn test_cmd_check_quiet_verbose() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_run_watchpoints/program.alpha")
+        .arg("run")
+        .arg("--quiet")
+        .assert();
+    let stdout = String::from_utf8(assert.code(0).get_output().stdout.clone()).unwrap();
+    assert_eq!(stdout, "Check successful\n");
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_run_watchpoints/program.alpha")
+        .arg("run")
+        .arg("--verbose")
+        .assert();
+    let stdout = String::from_utf8(assert.code(0).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Building runtime"));
+    assert!(stdout.contains("Instruction limit: 1000000"));
+    assert!(stdout.contains("Executed "));
+}
+
+#[test]
+fn test_cmd_check_preset_strict() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_preset_strict/program.alpha")
+        .arg("compile")
+        .arg("--preset")
+        .arg("strict")
+        .assert()
+        .failure();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_preset_strict/program.alpha")
+        .arg("run")
+        .arg("--preset")
+        .arg("strict")
+        .arg("-a")
+        .arg("1")
+        .arg("-m")
+        .arg("h1")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_preset_strict/extension.alpha")
+        .arg("compile")
+        .arg("--preset")
+        .arg("strict")
+        .arg("-a")
+        .arg("1")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cmd_check_preset_config_file() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_preset_strict/program.alpha")
+        .arg("run")
+        .arg("--config-file")
+        .arg("tests/input/test_cmd_check_preset_config_file/config.json")
+        .arg("--preset")
+        .arg("exam1")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_preset_strict/program.alpha")
+        .arg("run")
+        .arg("--config-file")
+        .arg("tests/input/test_cmd_check_preset_config_file/config.json")
+        .arg("--preset")
+        .arg("exam2")
+        .assert();
+    let stderr = String::from_utf8(assert.failure().get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("exam1, strict"));
+}
+
+#[test]
+fn test_cmd_check_memory() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("tests/input/test_cmd_check_memory/program.alpha")
+        .arg("--memory-config-file")
+        .arg("tests/input/test_cmd_check_memory/memory_config.json")
+        .arg("memory")
+        .assert();
+    let stdout = String::from_utf8(assert.success().get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains(
+        "Memory usage:
+  declared and used:     a0, p(h1), p(1)
+  declared but not used: a1, p(h2)
+  used but not declared: p(2) (created by autodetection)
+"
+    ));
+}
+
+#[test]
+fn test_cmd_test() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("test")
+        .arg("tests/input/test_cmd_test/program.alpha")
+        .arg("--cases")
+        .arg("tests/input/test_cmd_test/case_3.json,tests/input/test_cmd_test/case_5.json")
+        .arg("--traces-dir")
+        .arg("tests/input/test_cmd_test/traces")
+        .assert();
+    assert.success();
+}
+
+#[test]
+fn test_cmd_test_record() {
+    let traces_dir = std::env::temp_dir().join("alpha_tui_test_cmd_test_record");
+    let test = |record: bool| {
+        let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+        cmd.arg("test")
+            .arg("tests/input/test_cmd_test/program.alpha")
+            .arg("--cases")
+            .arg("tests/input/test_cmd_test/case_3.json")
+            .arg("--traces-dir")
+            .arg(&traces_dir);
+        if record {
+            cmd.arg("--record");
+        }
+        cmd.assert().success();
+    };
+    test(true);
+    // the golden trace starts with the metadata of the run, it is ignored when the traces are compared
+    let trace = std::fs::read_to_string(traces_dir.join("case_3.trace")).unwrap();
+    let header = trace.lines().next().unwrap();
+    assert!(header.starts_with(r#"{"run_metadata":{"#));
+    assert!(header.contains(r#""seed":0"#));
+    assert!(header.contains(r#""--record""#));
+    test(false);
+    std::fs::remove_dir_all(traces_dir).unwrap();
+}
+
+#[test]
+fn test_cmd_test_trace_differs() {
+    // final memory is the same, but the value is calculated differently
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("test")
+        .arg("tests/input/test_cmd_test/program_changed.alpha")
+        .arg("--cases")
+        .arg("tests/input/test_cmd_test/case_3.json,tests/input/test_cmd_test/case_5.json")
+        .arg("--traces-dir")
+        .arg("tests/input/test_cmd_test/traces")
+        .assert();
+    assert.code(7);
+}
+
+#[test]
+fn test_cmd_check_help_lists_exit_codes() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("check").arg("--help").assert();
+    let stdout = String::from_utf8(assert.code(0).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Exit codes:"));
+    assert!(stdout.contains(" 3 - The program could not be parsed"));
+    assert!(stdout.contains(" 6 - The instruction limit was reached"));
+}
+
+#[test]
+fn test_cmd_explain() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("explain").arg("e311").assert();
+    let stdout = String::from_utf8(assert.success().get_output().stdout.clone()).unwrap();
+    assert!(stdout.starts_with("E311: Pop from empty stack\n\n"));
+    assert!(stdout.contains("Example:"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("explain").assert();
+    let stdout = String::from_utf8(assert.success().get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("E001  Unknown operation\n"));
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("explain").arg("E999").assert().code(1);
+}
+
+#[test]
+fn test_cmd_check_error_code_hint() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("-")
+        .arg("run")
+        .write_stdin("a := 1\npop\n")
+        .assert();
+    let stdout = String::from_utf8(assert.code(5).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("E311"));
+    assert!(
+        stdout.ends_with("For more information about this error, run `alpha_tui explain E311`.\n")
+    );
+}
+
+#[test]
+fn test_cmd_check_parse_error_suggestion() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("-")
+        .arg("compile")
+        .write_stdin("loop: a := 1\nif a < 3 goto loop\n")
+        .assert();
+    let stdout = String::from_utf8(assert.code(3).get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("help: You might be missing 'then', try 'if a < 3 then goto loop'"));
+}
+
+#[test]
+fn test_cmd_grade() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("grade")
+        .arg("tests/input/test_cmd_grade/program.alpha")
+        .arg("--rubric")
+        .arg("tests/input/test_cmd_grade/rubric.json")
+        .assert();
+    let stdout = String::from_utf8(assert.success().get_output().stdout.clone()).unwrap();
+    // the report starts with the information that is needed to reproduce the run
+    assert!(stdout.starts_with(&format!(
+        "alpha_tui {}
+Arguments:          grade tests/input/test_cmd_grade/program.alpha --rubric tests/input/test_cmd_grade/rubric.json
+Memory config:      ",
+        env!("CARGO_PKG_VERSION")
+    )));
+    assert!(stdout.contains("\nSeed:               0\n\n"));
+    assert!(stdout.ends_with(
+        "[0/2] forbidden instructions: 1 used
+      line 2: a0 := a0 * 2
+[0/1] instructions executed: 4 (max 3)
+[1/1] memory cells used: 2 (max 2)
+[0/3] final values: 1 of 2 correct
+      p(h2): expected 5, got 6
+Score: 1/7
+"
+    ));
+}
+
+#[test]
+fn test_cmd_bundle() {
+    let output = std::env::temp_dir().join("alpha_tui_test_cmd_bundle.tar");
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("bundle")
+        .arg("examples/programs/faculty.alpha")
+        .arg("--output")
+        .arg(&output)
+        .assert();
+    assert.success();
+    assert!(output.exists());
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("bundle").arg("verify").arg(&output).assert();
+    std::fs::remove_file(output).unwrap();
+    assert.success();
+}
+
+#[test]
+fn test_cmd_bundle_stdin() {
+    let output = std::env::temp_dir().join("alpha_tui_test_cmd_bundle_stdin.tar");
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("bundle")
+        .arg("-")
+        .arg("--output")
+        .arg(&output)
+        .write_stdin("a := 5\np(h1) := a\n")
+        .assert();
+    assert.success();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("bundle").arg("verify").arg(&output).assert();
+    std::fs::remove_file(output).unwrap();
+    assert.success();
+}
+
+#[test]
+fn test_cmd_refactor_rename() {
+    let file = std::env::temp_dir().join("alpha_tui_test_cmd_refactor_rename.alpha");
+    std::fs::copy("tests/input/test_cmd_refactor_rename/program.alpha", &file).unwrap();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("refactor")
+        .arg("rename")
+        .arg(&file)
+        .arg("h1")
+        .arg("sum")
+        .assert();
+    let renamed = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(file).unwrap();
+    assert
+        .success()
+        .stdout("Renamed memory cell 'h1' to 'sum' in 4 lines\n");
+    assert_eq!(
+        renamed,
+        std::fs::read_to_string("tests/input/test_cmd_refactor_rename/expected.alpha").unwrap()
+    );
+}
+
+#[test]
+fn test_cmd_refactor_extract() {
+    let file = std::env::temp_dir().join("alpha_tui_test_cmd_refactor_extract.alpha");
+    std::fs::copy("tests/input/test_cmd_refactor_extract/program.alpha", &file).unwrap();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("refactor")
+        .arg("extract")
+        .arg(&file)
+        .arg("2")
+        .arg("3")
+        .arg("increment")
+        .assert();
+    let extracted = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(file).unwrap();
+    assert
+        .success()
+        .stdout("Extracted lines 2-3 into subroutine 'increment'\n");
+    assert_eq!(
+        extracted,
+        std::fs::read_to_string("tests/input/test_cmd_refactor_extract/expected.alpha").unwrap()
+    );
+}
+
+#[test]
+fn test_cmd_refactor_notation() {
+    let file = std::env::temp_dir().join("alpha_tui_test_cmd_refactor_notation.alpha");
+    std::fs::copy(
+        "tests/input/test_cmd_refactor_notation/program.alpha",
+        &file,
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("refactor")
+        .arg("notation")
+        .arg(&file)
+        .arg("--to")
+        .arg("unicode")
+        .assert();
+    let normalized = std::fs::read_to_string(&file).unwrap();
+    std::fs::remove_file(file).unwrap();
+    assert.success().stdout("Changed the notation in 5 lines\n");
+    assert_eq!(
+        normalized,
+        std::fs::read_to_string("tests/input/test_cmd_refactor_notation/expected.alpha").unwrap()
+    );
+}
+
+#[test]
+fn test_cmd_refactor_extract_jump_into_selection() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("refactor")
+        .arg("extract")
+        .arg("tests/input/test_cmd_refactor_extract/program.alpha")
+        .arg("1")
+        .arg("2")
+        .arg("increment")
+        .assert();
+    assert.code(1);
+}
+
+#[test]
+fn test_cmd_export_animate() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("export")
+        .arg("tests/input/test_cmd_export_animate/program.alpha")
+        .arg("--animate")
+        .arg("frames")
+        .arg("--width")
+        .arg("120")
+        .arg("--height")
+        .arg("20")
+        .assert()
+        .success();
+    let output = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    // one frame before the program is started and one frame for each instruction
+    assert_eq!(output.matches("--- frame").count(), 3);
+    assert!(output.contains("h1: 15"));
+    assert!(output.contains("Execution finished!"));
+}
+
+#[test]
+fn test_cmd_check_remote_file() {
+    let url = serve_once("a := 5\np(h1) := a\n");
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd.arg("check").arg(&url).arg("run").assert();
+    assert.success();
+}
+
+#[test]
+fn test_cmd_check_remote_file_offline() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("--offline")
+        .arg("check")
+        .arg("http://127.0.0.1:1/program.alpha")
+        .arg("run")
+        .assert()
+        .failure();
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.contains("network access is disabled"));
+}
+
+#[test]
+fn test_cmd_update_check_offline() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("--offline")
+        .arg("update")
+        .arg("--check")
+        .assert()
+        .success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    assert!(stdout.contains("Unable to check for updates"));
+    assert!(stdout.contains(&format!("Installed version: {}", env!("CARGO_PKG_VERSION"))));
+}
+
+#[test]
+fn test_cmd_check_stdin() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("-")
+        .arg("run")
+        .write_stdin("a := 5\np(h1) := a\n")
+        .assert();
+    assert.success();
+}
+
+#[test]
+fn test_cmd_check_directives() {
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    // the instruction limit of the directive is reached
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_directives/program.alpha")
+        .arg("run")
+        .assert()
+        .failure();
+    // options on the command line take precedence over directives
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("--instruction-limit")
+        .arg("10")
+        .arg("check")
+        .arg("tests/input/test_cmd_check_directives/program.alpha")
+        .arg("run")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cmd_check_lint_levels() {
+    // the warning is suppressed by the comment
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_lint_levels/program.alpha")
+        .arg("compile")
+        .arg("--deny")
+        .arg("unbalanced_subroutine")
+        .assert()
+        .success();
+    // denied lints can't be suppressed
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_lint_levels/program.alpha")
+        .arg("compile")
+        .arg("--deny")
+        .arg("pop_from_empty_stack")
+        .assert()
+        .failure();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_lint_levels/unsuppressed.alpha")
+        .arg("compile")
+        .arg("--allow")
+        .arg("pop_from_empty_stack")
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    cmd.arg("check")
+        .arg("tests/input/test_cmd_check_lint_levels/unsuppressed.alpha")
+        .arg("compile")
+        .arg("--deny")
+        .arg("unknown_lint")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cmd_check_emit_ast() {
+    let output = std::env::temp_dir().join("alpha_tui_test_cmd_check_emit_ast.json");
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg("examples/programs/faculty.alpha")
+        .arg("compile")
+        .arg("--emit")
+        .arg(&output)
+        .assert();
+    assert.success();
+    let ast: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+    // the program can be run from the ast
+    let mut cmd = Command::cargo_bin("alpha_tui").unwrap();
+    let assert = cmd
+        .arg("check")
+        .arg(&output)
+        .arg("--ast")
+        .arg("run")
+        .assert();
+    std::fs::remove_file(output).unwrap();
+    assert.success();
+    assert_eq!(ast["start_line"], 16);
+    assert_eq!(ast["labels"]["rec"], 5);
+    assert_eq!(ast["instructions"][7]["source"], "call fac");
+    assert_eq!(ast["instructions"][7]["instruction"]["kind"], "call");
+}
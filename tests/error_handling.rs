@@ -10,10 +10,10 @@ fn test_bai_error() {
         .arg("tests/input/test_bai_error/allowed_instructions_a.json")
         .assert();
     assert.stderr(
-        r#"Error: build_program_error
+        r#"Error: E100
 
   × when building program
-  ╰─▶ build_program::instruction_not_allowed_error
+  ╰─▶ E103
       
         × instruction 'pop' in line '2' is not allowed
         help: Make sure that you include this type ('pop') of instruction
@@ -38,10 +38,10 @@ fn test_bpe_operation_not_allowed() {
         .arg("add")
         .assert();
     assert.stderr(
-        r#"Error: build_program_error
+        r#"Error: E100
 
   × when building program
-  ╰─▶ build_program::operation_not_allowed_error
+  ╰─▶ E105
       
         × operation '-' in line '1' is not allowed
         help: Make sure that you include this operation ('-') in the allowed